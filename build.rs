@@ -0,0 +1,27 @@
+//! Build script embedding the git commit of the build
+//! in the `GIT_HASH` environment variable,
+//! or "unknown" when git is not available
+//! (e.g. when building from a source tarball).
+
+use std::{path::Path, process::Command};
+
+fn main() {
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_HASH={hash}");
+
+    // rebuild when the checked out commit changes
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+    } else {
+        println!("cargo:rerun-if-changed=build.rs");
+    }
+}
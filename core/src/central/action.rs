@@ -0,0 +1,285 @@
+//! Module for containing all player actions and their consequences.
+
+use std::{borrow::Cow, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{t, Cost, Money, Ops, ServiceKind, Time};
+
+use super::{staff::StaffTier, state::MAX_PINNED_CARDS, tariff::Tariff};
+
+/// An action that a player can take that affects the game state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlayerAction {
+    /// Perform a cloud service operation
+    /// by request of the player.
+    OpClick {
+        /// the kind of service that was clicked
+        kind: ServiceKind,
+        /// the number of operations to perform
+        amount: u32,
+    },
+
+    /// Sub-action that deducts money from the player's balance.
+    Payment { amount: Money },
+
+    /// Pay the electricity bill.
+    PayElectricityBill,
+
+    /// Pay some money in advance for future electricity bills.
+    PrepayElectricity { amount: Money },
+
+    /// Turn paying electricity bills automatically on or off
+    /// (once unlocked).
+    SetAutoPay { on: bool },
+
+    /// Hire a staff member of the given tier
+    /// to perform ops of the given service
+    /// (once unlocked), for a fee.
+    Hire {
+        tier: StaffTier,
+        service: ServiceKind,
+    },
+
+    /// Fire the staff member at the given position,
+    /// paying a severance.
+    Fire { index: usize },
+
+    /// Switch to the energy provider with the given tariff
+    /// (once unlocked), for a fee.
+    SwitchTariff { tariff: Tariff },
+
+    /// Bring back a customer whose trial ran out moments ago
+    /// for another trial of the given duration, for a fee.
+    ExtendTrial { spec_id: u32, duration: Time },
+
+    /// Accept the bailout on offer,
+    /// taking a rescue loan to get funds back to positive.
+    AcceptBailout,
+
+    /// Refuse the bailout on offer and start over.
+    DeclareBankruptcy,
+
+    /// Repay the remaining balance of the loan at once.
+    RepayLoan,
+
+    /// Cancel the infrastructure insurance policy,
+    /// which can only be taken again after a cooldown.
+    CancelInsurance,
+
+    /// Hide the latest period report.
+    DismissReport,
+
+    /// Close the message announcing the win.
+    DismissWin,
+
+    /// Answer the pending narrative event.
+    ResolveEvent {
+        /// the event's identifier
+        id: Cow<'static, str>,
+        /// the index of the choice made
+        choice: usize,
+    },
+
+    /// Give the player's company a new name.
+    RenameCompany { name: String },
+
+    /// Pause or resume customer traffic to a cloud service,
+    /// e.g. while upgrading hardware.
+    SetMaintenance { kind: ServiceKind, on: bool },
+
+    /// Use only a fraction of the cache (between 0 and 1),
+    /// reserving less memory for it at the cost of fewer cache hits.
+    SetCacheFraction { fraction: f32 },
+
+    /// Change the price of a cloud service.
+    ChangePrice { kind: ServiceKind, new_price: Money },
+
+    /// Trade ops of a service for ops of a higher tier service
+    /// at the op exchange.
+    ConvertOps {
+        from: ServiceKind,
+        to: ServiceKind,
+        /// the ops of the `from` service offered
+        amount: Ops,
+    },
+
+    /// Upgrade a node's CPU
+    UpgradeCpu { node: u32 },
+
+    /// Upgrade a node's RAM
+    UpgradeRam { node: u32 },
+
+    /// Restart a node,
+    /// freeing its memory at the cost of a short downtime
+    RestartNode { node: u32 },
+
+    /// Take back the latest CPU or RAM upgrade for a full refund,
+    /// shortly after it was made.
+    UndoLastPurchase,
+
+    /// Acquire a new cloud node
+    AddNode,
+
+    /// Acquire a new fully upgraded cloud node
+    AddUpgradedNode,
+
+    /// Acquire a new fully equiped cloud node rack
+    AddRack,
+
+    /// Use a card by applying its effect.
+    ///
+    /// Knowing the effects of the card requires
+    /// looking it up on the list of all cards.
+    UseCard {
+        /// the card's identifier
+        id: Cow<'static, str>,
+    },
+
+    /// Pin a card on offer to the top of the projects panel,
+    /// to keep an eye on it while saving for it.
+    PinCard {
+        /// the card's identifier
+        id: Cow<'static, str>,
+    },
+
+    /// Take a card off the top of the projects panel.
+    UnpinCard {
+        /// the card's identifier
+        id: Cow<'static, str>,
+    },
+
+    /// Directly manipulate the game state
+    /// (only available in sandbox mode).
+    #[cfg(feature = "debug-tools")]
+    Debug(DebugAction),
+}
+
+impl PlayerAction {
+    /// Whether the action spends money on something new,
+    /// which is not allowed while in debt
+    /// (see [`WorldState::is_in_debt`](crate::WorldState::is_in_debt)).
+    ///
+    /// Paying bills and debts is not a purchase.
+    /// Neither is using a card as such,
+    /// as only the cards which cost money are blocked
+    /// (see [`Cost::money`](crate::Cost::money)).
+    pub fn is_purchase(&self) -> bool {
+        matches!(
+            self,
+            PlayerAction::Hire { .. }
+                | PlayerAction::SwitchTariff { .. }
+                | PlayerAction::ExtendTrial { .. }
+                | PlayerAction::UpgradeCpu { .. }
+                | PlayerAction::UpgradeRam { .. }
+                | PlayerAction::AddNode
+                | PlayerAction::AddUpgradedNode
+                | PlayerAction::AddRack
+        )
+    }
+}
+
+/// The reason why a player action could not be applied.
+///
+/// A failed action leaves the game state untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionError {
+    /// The player does not have enough funds for a purchase.
+    InsufficientFunds { needed: Money, have: Money },
+    /// The player does not have enough ops of a service.
+    InsufficientOps {
+        kind: ServiceKind,
+        needed: Ops,
+        have: Ops,
+    },
+    /// The hardware is already at its highest level.
+    MaxLevelReached,
+    /// There is no cloud node with the given ID.
+    NoSuchNode(u32),
+    /// There is no project card with the given ID.
+    NoSuchCard(Cow<'static, str>),
+    /// The player cannot afford the cost of a project card.
+    CannotAfford(Cost),
+    /// The cloud service has not been unlocked yet.
+    NotUnlocked(ServiceKind),
+    /// The action is not on offer right now
+    /// (e.g. a bailout when there is none pending).
+    NotAvailable,
+    /// The cloud node is already restarting.
+    NodeRestarting(u32),
+    /// The cloud node stands for a whole rack or data center,
+    /// which cannot be upgraded as a single node.
+    NotUpgradable(u32),
+    /// The cloud node is already making use of its latest upgrade,
+    /// which can no longer be taken back.
+    UpgradeInUse(u32),
+    /// The daily challenge does not allow prices below the given one.
+    BelowPriceFloor(Money),
+    /// As many cards as possible are already pinned.
+    TooManyPinned,
+    /// Nothing can be bought while the funds are negative.
+    InDebt,
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::InsufficientFunds { needed, have } => f.write_str(&t!(
+                "error.insufficient_funds",
+                needed = needed,
+                have = have
+            )),
+            ActionError::InsufficientOps { kind, needed, have } => f.write_str(&t!(
+                "error.insufficient_ops",
+                service = kind,
+                needed = needed,
+                have = have
+            )),
+            ActionError::MaxLevelReached => f.write_str(t!("error.max_level")),
+            ActionError::NoSuchNode(node) => f.write_str(&t!("error.no_such_node", node = node)),
+            ActionError::NoSuchCard(id) => f.write_str(&t!("error.no_such_card", id = id)),
+            ActionError::CannotAfford(cost) => f.write_str(&t!("error.cannot_afford", cost = cost)),
+            ActionError::NotUnlocked(kind) => {
+                f.write_str(&t!("error.not_unlocked", service = kind))
+            }
+            ActionError::NotAvailable => f.write_str(t!("error.not_available")),
+            ActionError::NodeRestarting(node) => {
+                f.write_str(&t!("error.node_restarting", node = node))
+            }
+            ActionError::NotUpgradable(node) => {
+                f.write_str(&t!("error.not_upgradable", node = node))
+            }
+            ActionError::UpgradeInUse(node) => {
+                f.write_str(&t!("error.upgrade_in_use", node = node))
+            }
+            ActionError::BelowPriceFloor(floor) => {
+                f.write_str(&t!("error.below_price_floor", floor = floor))
+            }
+            ActionError::TooManyPinned => {
+                f.write_str(&t!("error.too_many_pinned", max = MAX_PINNED_CARDS))
+            }
+            ActionError::InDebt => f.write_str(t!("error.in_debt")),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+/// A sandbox mode action for experimenting with the game.
+#[cfg(feature = "debug-tools")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DebugAction {
+    /// Add (or remove) funds without counting them as earnings.
+    AddFunds(Money),
+    /// Add ops to a service, both to the total and available counts.
+    AddOps { kind: ServiceKind, amount: Ops },
+    /// Set the base service demand.
+    SetDemand(f64),
+    /// Advance the game clock by the given number of seconds,
+    /// processing everything that happens in between.
+    AdvanceTime { seconds: u32 },
+    /// Enter powersave mode with a due bill, or leave it by clearing bills.
+    TogglePowersave,
+    /// Start a DoS attack on a service.
+    SpawnDos(ServiceKind),
+}
@@ -0,0 +1,24 @@
+//! Module for the sounds that the game asks to play.
+//!
+//! Gameplay code only says what happened through an [`AudioEvent`],
+//! and the audio module decides what that sounds like,
+//! so that the engine does not depend on any web APIs.
+
+use serde::{Deserialize, Serialize};
+
+/// Something in the game that should be heard by the player.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioEvent {
+    /// the player clicked on an op button
+    OpClick,
+    /// the player clicked on any other button
+    UiClick,
+    /// something went wrong and needs the player's attention
+    Warning,
+    /// an electricity bill was issued
+    Bill,
+    /// a new project card became available
+    CardAvailable,
+    /// the player reached the end of the game
+    Win,
+}
@@ -0,0 +1,162 @@
+//! Module for the "Welcome back" bonus,
+//! which softens the return of players who were away for a while.
+//!
+//! When a game is loaded long after it was last saved,
+//! a card appears granting ops and funds
+//! in proportion to the (wall-clock) time away, up to a cap.
+//! The bonus expires if left unused for too long,
+//! and it is not offered again until a cooldown has passed
+//! since the last offer, so that reloading the game cannot farm it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, Ops, Time, WorldState};
+
+/// the minimum time away for a bonus to be offered (30 minutes)
+pub const MIN_AWAY_MILLIS: u64 = 30 * 60_000;
+
+/// the maximum time away which is rewarded (8 hours)
+pub const MAX_AWAY_MILLIS: u64 = 8 * 3_600_000;
+
+/// the minimum time between two bonus offers (12 hours)
+pub const AWAY_BONUS_COOLDOWN_MILLIS: u64 = 12 * 3_600_000;
+
+/// the game time after which an unused bonus expires (5 minutes)
+pub const AWAY_BONUS_EXPIRY: Time = 3_000_000;
+
+/// the funds granted per hour away
+pub const AWAY_FUNDS_PER_HOUR: Money = Money::dollars(25);
+
+/// the base ops granted per hour away, for each op per click
+pub const AWAY_OPS_PER_HOUR: u32 = 1_000;
+
+/// A bonus offered to a returning player.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AwayBonus {
+    /// how long the player was away in milliseconds,
+    /// capped to [`MAX_AWAY_MILLIS`]
+    pub away_millis: u64,
+    /// the game time at which the bonus was offered
+    pub offered_at: Time,
+}
+
+impl AwayBonus {
+    /// The funds granted by the bonus.
+    pub fn funds(&self) -> Money {
+        Money::millicents(
+            (AWAY_FUNDS_PER_HOUR.to_millicents() as u64 * self.away_millis / 3_600_000) as i64,
+        )
+    }
+
+    /// The base ops granted by the bonus.
+    pub fn ops(&self, ops_per_click: u32) -> Ops {
+        let ops = AWAY_OPS_PER_HOUR as u64 * ops_per_click as u64 * self.away_millis / 3_600_000;
+        Ops(ops.min(i64::MAX as u64) as i64)
+    }
+
+    /// Whether the bonus is no longer available at the given game time.
+    pub fn is_expired(&self, time: Time) -> bool {
+        time >= self.offered_at + AWAY_BONUS_EXPIRY
+    }
+}
+
+/// Offer a bonus to the player if they return to the game
+/// long enough after it was last saved,
+/// given the current wall-clock time in milliseconds.
+///
+/// Returns whether a bonus was offered.
+pub fn check_return(state: &mut WorldState, now_millis: u64) -> bool {
+    // saves made before the timestamp was recorded are not rewarded
+    if state.saved_at == 0 {
+        return false;
+    }
+    let away_millis = now_millis.saturating_sub(state.saved_at);
+    if away_millis < MIN_AWAY_MILLIS {
+        return false;
+    }
+    if state.last_away_bonus_at != 0
+        && now_millis.saturating_sub(state.last_away_bonus_at) < AWAY_BONUS_COOLDOWN_MILLIS
+    {
+        return false;
+    }
+    state.away_bonus = Some(AwayBonus {
+        away_millis: away_millis.min(MAX_AWAY_MILLIS),
+        offered_at: state.time,
+    });
+    state.last_away_bonus_at = now_millis;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_return, AwayBonus, AWAY_BONUS_COOLDOWN_MILLIS, AWAY_BONUS_EXPIRY, MAX_AWAY_MILLIS,
+        MIN_AWAY_MILLIS,
+    };
+    use crate::{Money, Ops, WorldState};
+
+    const HOUR: u64 = 3_600_000;
+
+    #[test]
+    fn test_bonus_scales_up_to_cap() {
+        let bonus = AwayBonus {
+            away_millis: 2 * HOUR,
+            offered_at: 0,
+        };
+        assert_eq!(bonus.funds(), Money::dollars(50));
+        assert_eq!(bonus.ops(1), Ops(2_000));
+        assert_eq!(bonus.ops(4), Ops(8_000));
+
+        let mut state = WorldState {
+            saved_at: 1_000,
+            ..Default::default()
+        };
+        assert!(check_return(&mut state, 1_000 + 100 * HOUR));
+        let bonus = state.away_bonus.unwrap();
+        assert_eq!(bonus.away_millis, MAX_AWAY_MILLIS);
+        assert_eq!(bonus.funds(), Money::dollars(200));
+    }
+
+    #[test]
+    fn test_no_bonus_for_short_absences() {
+        let mut state = WorldState {
+            saved_at: 1_000,
+            ..Default::default()
+        };
+        assert!(!check_return(&mut state, 1_000 + MIN_AWAY_MILLIS - 1));
+        assert_eq!(state.away_bonus, None);
+        assert_eq!(state.last_away_bonus_at, 0);
+
+        // nor for saves without a timestamp
+        let mut state = WorldState::default();
+        assert!(!check_return(&mut state, 100 * HOUR));
+        assert_eq!(state.away_bonus, None);
+    }
+
+    #[test]
+    fn test_bonus_cooldown() {
+        let mut state = WorldState {
+            saved_at: 1_000,
+            ..Default::default()
+        };
+        let now = 1_000 + HOUR;
+        assert!(check_return(&mut state, now));
+
+        // the bonus expires unused
+        assert!(!state.away_bonus.unwrap().is_expired(AWAY_BONUS_EXPIRY - 1));
+        assert!(state.away_bonus.unwrap().is_expired(AWAY_BONUS_EXPIRY));
+        state.away_bonus = None;
+
+        // leaving and coming back again is not rewarded during the cooldown
+        state.saved_at = now;
+        assert!(!check_return(&mut state, now + HOUR));
+        assert!(!check_return(
+            &mut state,
+            now + AWAY_BONUS_COOLDOWN_MILLIS - 1
+        ));
+        assert_eq!(state.away_bonus, None);
+
+        assert!(check_return(&mut state, now + AWAY_BONUS_COOLDOWN_MILLIS));
+        assert_eq!(state.away_bonus.unwrap().away_millis, MAX_AWAY_MILLIS);
+    }
+}
@@ -0,0 +1,129 @@
+//! Module for the network egress of the cloud,
+//! a consumable shared by all nodes next to their own memory.
+//!
+//! Every request served takes some bandwidth according to its service tier,
+//! out of an allowance which renews in every metering window
+//! and grows with the bandwidth level of the player.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Time;
+
+use super::engine::MAJOR_UPDATE_PERIOD;
+
+/// The bandwidth available in each metering window
+/// at each bandwidth level, in bandwidth units
+/// (a base op takes one unit).
+///
+/// The first level is enough for a few fully upgraded nodes,
+/// so that bandwidth only becomes a concern at rack scale.
+pub static BANDWIDTH_LEVELS: [u64; 4] = [10_000, 100_000, 1_000_000, 10_000_000];
+
+/// The length of a bandwidth metering window
+pub const BANDWIDTH_WINDOW: Time = MAJOR_UPDATE_PERIOD;
+
+/// The bandwidth available in each metering window at the given level.
+pub fn bandwidth_capacity(level: u8) -> u64 {
+    BANDWIDTH_LEVELS[(level as usize).min(BANDWIDTH_LEVELS.len() - 1)]
+}
+
+/// The bandwidth taken in the current metering window
+/// and in the one before it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthMeter {
+    /// the index of the current window
+    window: u64,
+    /// the bandwidth taken in the current window
+    used: u64,
+    /// the bandwidth taken in the window before the current one
+    last_used: u64,
+}
+
+impl BandwidthMeter {
+    /// Take the given amount of bandwidth at the given time,
+    /// unless it would go over the capacity of its window.
+    ///
+    /// Returns whether the bandwidth was taken.
+    pub fn take(&mut self, time: Time, amount: u64, capacity: u64) -> bool {
+        self.roll(time);
+        if self.used + amount > capacity {
+            return false;
+        }
+        self.used += amount;
+        true
+    }
+
+    /// The share of the capacity taken
+    /// in the last complete window before the given time,
+    /// between 0 and 1.
+    pub fn load(&self, time: Time, capacity: u64) -> f32 {
+        let window = time / BANDWIDTH_WINDOW;
+        let used = if window == self.window {
+            self.last_used
+        } else if window == self.window + 1 {
+            self.used
+        } else {
+            0
+        };
+        (used as f64 / capacity.max(1) as f64).min(1.) as f32
+    }
+
+    /// Move on to the window of the given time.
+    ///
+    /// Requests come in time order,
+    /// so an earlier time is taken as part of the current window.
+    fn roll(&mut self, time: Time) {
+        let window = time / BANDWIDTH_WINDOW;
+        if window <= self.window {
+            return;
+        }
+        self.last_used = if window == self.window + 1 {
+            self.used
+        } else {
+            0
+        };
+        self.window = window;
+        self.used = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bandwidth_capacity, BandwidthMeter, BANDWIDTH_LEVELS, BANDWIDTH_WINDOW};
+
+    #[test]
+    fn test_bandwidth_capacity() {
+        assert_eq!(bandwidth_capacity(0), BANDWIDTH_LEVELS[0]);
+        assert!(bandwidth_capacity(1) > bandwidth_capacity(0));
+        // levels beyond the last one are capped
+        assert_eq!(bandwidth_capacity(200), BANDWIDTH_LEVELS[3]);
+    }
+
+    #[test]
+    fn test_bandwidth_meter_windows() {
+        let mut meter = BandwidthMeter::default();
+        assert!(meter.take(0, 60, 100));
+        assert!(meter.take(10, 40, 100));
+        // the window is full
+        assert!(!meter.take(20, 1, 100));
+        assert_eq!(meter.load(20, 100), 0.);
+
+        // the next window starts afresh
+        let next = BANDWIDTH_WINDOW + 5;
+        assert_eq!(meter.load(next, 100), 1.);
+        assert!(meter.take(next, 30, 100));
+        assert_eq!(meter.load(next, 100), 1.);
+        assert!(meter.take(next, 70, 100));
+        assert!(!meter.take(next, 1, 100));
+
+        // a request running late counts in the current window
+        assert!(!meter.take(next - 10, 1, 100));
+
+        // windows without any requests used nothing
+        assert_eq!(meter.load(BANDWIDTH_WINDOW * 2, 100), 1.);
+        assert_eq!(meter.load(BANDWIDTH_WINDOW * 3, 100), 0.);
+        assert!(meter.take(BANDWIDTH_WINDOW * 3, 50, 100));
+        assert_eq!(meter.load(BANDWIDTH_WINDOW * 3 + 1, 100), 0.);
+        assert_eq!(meter.load(BANDWIDTH_WINDOW * 4, 100), 0.5);
+    }
+}
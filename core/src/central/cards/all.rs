@@ -0,0 +1,998 @@
+//! All card entries encoded here
+//!
+
+use crate::{
+    central::{
+        engine::{DEMAND_DOS_THRESHOLD, RACK_CAPACITY},
+        state::RoutingLevel,
+    },
+    CloudClientSpec, Cost, Money, Ops, ServiceKind,
+};
+
+use super::{CardCondition, CardEffect, CardSpec};
+
+// declare constants for some card IDs to help prevent mistakes
+const ID_BASE_OPS_PUBLISHED: &str = "a0p";
+const ID_SUPER_OPS_UNLOCKED: &str = "a1";
+const ID_EPIC_OPS_UNLOCKED: &str = "a2";
+const ID_AWESOME_OPS_UNLOCKED: &str = "a3";
+const ID_LEGENDARY_OPS_UNLOCKED: &str = "a4";
+const ID_MORE_CACHING: &str = "c1";
+
+/// the card of the win chain which announces the win
+pub const ID_WIN: &str = "win6";
+/// the card of the win chain right after the win announcement
+pub const ID_WIN_EPILOGUE: &str = "win7";
+/// the last card of the win chain, which ends the game
+pub const ID_GAME_OVER: &str = "win9";
+
+/// All project cards in the game.
+///
+/// They _must_ be inserted in id ascending order.
+pub static ALL_CARDS: &[CardSpec] = &[
+    // --- service unlocking and publishing ---
+    CardSpec {
+        id: ID_BASE_OPS_PUBLISHED,
+        title_key: "card.a0p.title",
+        description_key: "card.a0p.description",
+        cost: Cost::base_ops(8),
+        condition: CardCondition::appear_immediately(),
+        effect: CardEffect::PublishService(ServiceKind::Base),
+    },
+    CardSpec {
+        id: ID_SUPER_OPS_UNLOCKED,
+        title_key: "card.a1.title",
+        description_key: "card.a1.description",
+        cost: Cost::base_ops(4_000).and(Cost::dollars(200)),
+        condition: CardCondition::TotalBaseOps(Ops(1_500)),
+        effect: CardEffect::UnlockService(ServiceKind::Super),
+    },
+    CardSpec {
+        id: "a1p",
+        title_key: "card.a1p.title",
+        description_key: "card.a1p.description",
+        cost: Cost::super_ops(16),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_SUPER_OPS_UNLOCKED,
+            duration: 6_000,
+        },
+        effect: CardEffect::PublishService(ServiceKind::Super),
+    },
+    CardSpec {
+        id: ID_EPIC_OPS_UNLOCKED,
+        title_key: "card.a2.title",
+        description_key: "card.a2.description",
+        cost: Cost::super_ops(20_000)
+            .and(Cost::base_ops(150_000))
+            .and(Cost::dollars(5_420)),
+        condition: CardCondition::TotalSuperOps(Ops(6_000)),
+        effect: CardEffect::UnlockService(ServiceKind::Epic),
+    },
+    CardSpec {
+        id: "a2p",
+        title_key: "card.a2p.title",
+        description_key: "card.a2p.description",
+        cost: Cost::epic_ops(32),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_EPIC_OPS_UNLOCKED,
+            duration: 50_000,
+        },
+        effect: CardEffect::PublishService(ServiceKind::Epic),
+    },
+    CardSpec {
+        id: ID_AWESOME_OPS_UNLOCKED,
+        title_key: "card.a3.title",
+        description_key: "card.a3.description",
+        cost: Cost::epic_ops(700_000)
+            .and(Cost::super_ops(1_000_000))
+            .and(Cost::base_ops(2_000_000))
+            .and(Cost::dollars(166_000)),
+        condition: CardCondition::TotalEpicOps(Ops(468_900)),
+        effect: CardEffect::UnlockService(ServiceKind::Awesome),
+    },
+    CardSpec {
+        id: "a3p",
+        title_key: "card.a3p.title",
+        description_key: "card.a3p.description",
+        cost: Cost::awesome_ops(64),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_AWESOME_OPS_UNLOCKED,
+            duration: 500_000,
+        },
+        effect: CardEffect::PublishService(ServiceKind::Awesome),
+    },
+    CardSpec {
+        id: ID_LEGENDARY_OPS_UNLOCKED,
+        title_key: "card.a4.title",
+        description_key: "card.a4.description",
+        cost: Cost::awesome_ops(50_000_000)
+            .and(Cost::epic_ops(100_000_000))
+            .and(Cost::dollars(80_000_000)),
+        condition: CardCondition::TotalAwesomeOps(Ops(100_000_000)),
+        effect: CardEffect::UnlockService(ServiceKind::Legendary),
+    },
+    CardSpec {
+        id: "a4p",
+        title_key: "card.a4p.title",
+        description_key: "card.a4p.description",
+        cost: Cost::legendary_ops(128),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_LEGENDARY_OPS_UNLOCKED,
+            duration: 1_000_000,
+        },
+        effect: CardEffect::PublishService(ServiceKind::Legendary),
+    },
+    // --- money bonuses and entitlements ---
+    CardSpec {
+        id: "b0",
+        title_key: "card.b0.title",
+        description_key: "card.b0.description",
+        cost: Cost::base_ops(50),
+        condition: CardCondition::AvailableBaseOps(Ops(100)),
+        effect: CardEffect::AddFunds(Money::dollars(60)),
+    },
+    CardSpec {
+        id: "b00",
+        title_key: "card.b00.title",
+        description_key: "card.b00.description",
+        cost: Cost::base_ops(500),
+        condition: CardCondition::AvailableBaseOps(Ops(1_000)),
+        effect: CardEffect::AddFunds(Money::dollars(500)),
+    },
+    CardSpec {
+        id: "b000",
+        title_key: "card.b000.title",
+        description_key: "card.b000.description",
+        cost: Cost::super_ops(1_024),
+        condition: CardCondition::AvailableSuperOps(Ops(2_048)),
+        effect: CardEffect::AddFunds(Money::dollars(10_000)),
+    },
+    CardSpec {
+        id: "b1",
+        title_key: "card.b1.title",
+        description_key: "card.b1.description",
+        cost: Cost::base_ops(720),
+        condition: CardCondition::TotalBaseOps(Ops(500)),
+        effect: CardEffect::UpgradeEntitlements(ServiceKind::Base, Money::millicents(5)),
+    },
+    CardSpec {
+        id: "b1s",
+        title_key: "card.b1s.title",
+        description_key: "card.b1s.description",
+        cost: Cost::base_ops(1_500),
+        condition: CardCondition::after_card_millis("b1", 120_000),
+        effect: CardEffect::SignSla {
+            service: ServiceKind::Base,
+            bonus: Money::millicents(8),
+            penalty: Money::dollars(40),
+        },
+    },
+    CardSpec {
+        id: "b2",
+        title_key: "card.b2.title",
+        description_key: "card.b2.description",
+        cost: Cost::super_ops(2_990),
+        condition: CardCondition::TotalSuperOps(Ops(1_500)),
+        effect: CardEffect::UpgradeEntitlements(ServiceKind::Super, Money::millicents(50)),
+    },
+    CardSpec {
+        id: "b2s",
+        title_key: "card.b2s.title",
+        description_key: "card.b2s.description",
+        cost: Cost::super_ops(6_000),
+        condition: CardCondition::after_card_millis("b2", 120_000),
+        effect: CardEffect::SignSla {
+            service: ServiceKind::Super,
+            bonus: Money::millicents(80),
+            penalty: Money::dollars(400),
+        },
+    },
+    CardSpec {
+        id: "b3",
+        title_key: "card.b3.title",
+        description_key: "card.b3.description",
+        cost: Cost::epic_ops(12_800).and(Cost::super_ops(12_800)),
+        condition: CardCondition::TotalEpicOps(Ops(2_000)),
+        effect: CardEffect::UpgradeEntitlements(ServiceKind::Epic, Money::dec_cents(5)),
+    },
+    CardSpec {
+        id: "b4",
+        title_key: "card.b4.title",
+        description_key: "card.b4.description",
+        cost: Cost::awesome_ops(36_000).and(Cost::epic_ops(128_000)),
+        condition: CardCondition::TotalAwesomeOps(Ops(9_777)),
+        effect: CardEffect::UpgradeEntitlements(ServiceKind::Awesome, Money::cents(5)),
+    },
+    CardSpec {
+        id: "bx",
+        title_key: "card.bx.title",
+        description_key: "card.bx.description",
+        cost: Cost::dollars(20_000).and(Cost::super_ops(50_000)),
+        condition: CardCondition::TotalEpicOps(Ops(20_000)),
+        effect: CardEffect::UnlockOpExchange,
+    },
+    // --- caching cards ---
+    CardSpec {
+        id: "c0",
+        title_key: "card.c0.title",
+        description_key: "card.c0.description",
+        cost: Cost::money(Money::dollars(100)).and(Cost::base_ops(260)),
+        condition: CardCondition::TotalMemoryUpgrades(1),
+        effect: CardEffect::MoreCaching,
+    },
+    CardSpec {
+        id: ID_MORE_CACHING,
+        title_key: "card.c1.title",
+        description_key: "card.c1.description",
+        cost: Cost::money(Money::dollars(400)).and(Cost::super_ops(250)),
+        condition: CardCondition::TotalMemoryUpgrades(4),
+        effect: CardEffect::MoreCaching,
+    },
+    CardSpec {
+        id: "c2",
+        title_key: "card.c2.title",
+        description_key: "card.c2.description",
+        cost: Cost::money(Money::dollars(2_000))
+            .and(Cost::epic_ops(50_000))
+            .and(Cost::super_ops(100_000)),
+        condition: CardCondition::TotalMemoryUpgrades(40),
+        effect: CardEffect::MoreCaching,
+    },
+    CardSpec {
+        id: "c3",
+        title_key: "card.c3.title",
+        description_key: "card.c3.description",
+        cost: Cost::money(Money::dollars(400_000))
+            .and(Cost::awesome_ops(60_000))
+            .and(Cost::epic_ops(100_000)),
+        condition: CardCondition::TotalAwesomeOps(Ops(500)),
+        effect: CardEffect::MoreCaching,
+    },
+    // --- advertisement ---
+    CardSpec {
+        id: "d0",
+        title_key: "card.d0.title",
+        description_key: "card.d0.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_BASE_OPS_PUBLISHED,
+            duration: 20_000,
+        },
+        effect: CardEffect::AddClientsWithPublicity(
+            CloudClientSpec::trial_secs(ServiceKind::Base, 5),
+            2.,
+        ),
+    },
+    CardSpec {
+        id: "d1",
+        title_key: "card.d1.title",
+        description_key: "card.d1.description",
+        cost: Cost::dollars(5).and(Cost::base_ops(850)),
+        condition: CardCondition::TotalBaseOps(Ops(650)),
+        effect: CardEffect::AddPublicityRate(24., 0.25),
+    },
+    CardSpec {
+        id: "d2",
+        title_key: "card.d2.title",
+        description_key: "card.d2.description",
+        cost: Cost::dollars(70).and(Cost::base_ops(900)),
+        condition: CardCondition::Earned(Money::dollars(50)),
+        effect: CardEffect::AddPublicityRate(48.0, 0.5),
+    },
+    CardSpec {
+        id: "d3",
+        title_key: "card.d3.title",
+        description_key: "card.d3.description",
+        cost: Cost::dollars(290).and(Cost::super_ops(300)),
+        condition: CardCondition::Earned(Money::dollars(200)),
+        effect: CardEffect::AddPublicityRate(88.0, 1.),
+    },
+    CardSpec {
+        id: "d3.5",
+        title_key: "card.d3.5.title",
+        description_key: "card.d3.5.description",
+        condition: CardCondition::Earned(Money::dollars(1_200)),
+        cost: Cost::dollars(750).and(Cost::super_ops(1_000)),
+        effect: CardEffect::AddPublicityRate(250., 2.0),
+    },
+    CardSpec {
+        id: "d4",
+        title_key: "card.d4.title",
+        description_key: "card.d4.description",
+        cost: Cost::dollars(7_500).and(Cost::super_ops(3_000)),
+        condition: CardCondition::Earned(Money::dollars(6_200)),
+        effect: CardEffect::AddPublicityRate(600.0, 8.0),
+    },
+    CardSpec {
+        id: "d4.5",
+        title_key: "card.d4.5.title",
+        description_key: "card.d4.5.description",
+        cost: Cost::dollars(2_000),
+        condition: CardCondition::RequestsDropped(500),
+        effect: CardEffect::AddPublicityRate(64., 0.5),
+    },
+    CardSpec {
+        id: "d5",
+        title_key: "card.d5.title",
+        description_key: "card.d5.description",
+        condition: CardCondition::Earned(Money::dollars(50_000)),
+        cost: Cost::dollars(74_000).and(Cost::epic_ops(6_000)),
+        effect: CardEffect::AddPublicityRate(1_999., 20.),
+    },
+    CardSpec {
+        id: "d5.5",
+        title_key: "card.d5.5.title",
+        description_key: "card.d5.5.description",
+        condition: CardCondition::Earned(Money::dollars(270_000)),
+        cost: Cost::dollars(300_000).and(Cost::epic_ops(48_000)),
+        effect: CardEffect::AddPublicityRate(9_000., 48.),
+    },
+    CardSpec {
+        id: "d6",
+        title_key: "card.d6.title",
+        description_key: "card.d6.description",
+        condition: CardCondition::Earned(Money::dollars(8_000_000)),
+        cost: Cost::dollars(16_940_000).and(Cost::epic_ops(250_000)),
+        effect: CardEffect::AddPublicityRate(60_000.0, 75.),
+    },
+    CardSpec {
+        id: "d7",
+        title_key: "card.d7.title",
+        description_key: "card.d7.description",
+        condition: CardCondition::TotalAwesomeOps(Ops(600_000)),
+        cost: Cost::dollars(50_000_000).and(Cost::awesome_ops(700_000)),
+        effect: CardEffect::AddPublicityRate(250_000.0, 150.),
+    },
+    // --- energy cards ---
+    CardSpec {
+        id: "e0",
+        title_key: "card.e0.title",
+        description_key: "card.e0.description",
+        cost: Cost::base_ops(170),
+        condition: CardCondition::FirstBillArrived,
+        effect: CardEffect::UnlockTariffs(1),
+    },
+    CardSpec {
+        id: "e1",
+        title_key: "card.e1.title",
+        description_key: "card.e1.description",
+        cost: Cost::dollars(180).and(Cost::base_ops(400)),
+        condition: CardCondition::TotalBaseOps(Ops(100_000)),
+        effect: CardEffect::SetElectricityCostLevel(2),
+    },
+    CardSpec {
+        id: "e2",
+        title_key: "card.e2.title",
+        description_key: "card.e2.description",
+        cost: Cost::dollars(520).and(Cost::super_ops(80_000)),
+        condition: CardCondition::TotalCloudNodes(2),
+        effect: CardEffect::SetElectricityCostLevel(3),
+    },
+    CardSpec {
+        id: "e3",
+        title_key: "card.e3.title",
+        description_key: "card.e3.description",
+        cost: Cost::dollars(8_800).and(Cost::super_ops(1_000_000)),
+        condition: CardCondition::TotalCloudNodes(6),
+        effect: CardEffect::SetElectricityCostLevel(4),
+    },
+    CardSpec {
+        id: "e4",
+        title_key: "card.e4.title",
+        description_key: "card.e4.description",
+        cost: Cost::dollars(280_000).and(Cost::epic_ops(222_000)),
+        condition: CardCondition::TotalCloudNodes(17),
+        effect: CardEffect::SetElectricityCostLevel(5),
+    },
+    CardSpec {
+        id: "e5",
+        title_key: "card.e5.title",
+        description_key: "card.e5.description",
+        cost: Cost::dollars(8_000_000).and(Cost::awesome_ops(1_000_000)),
+        condition: CardCondition::TotalAwesomeOps(Ops(700_000)),
+        effect: CardEffect::SetElectricityCostLevel(6),
+    },
+    CardSpec {
+        id: "ea",
+        title_key: "card.ea.title",
+        description_key: "card.ea.description",
+        cost: Cost::dollars(25),
+        condition: CardCondition::Earned(Money::dollars(2_000)),
+        effect: CardEffect::UnlockAutoPay,
+    },
+    // --- bad request protection cards ---
+    CardSpec {
+        id: "f0",
+        title_key: "card.f0.title",
+        description_key: "card.f0.description",
+        cost: Cost::base_ops(200).and(Cost::super_ops(200)),
+        condition: CardCondition::Demand(DEMAND_DOS_THRESHOLD + 0.25),
+        effect: CardEffect::UpgradeSpamProtection(0.5),
+    },
+    CardSpec {
+        id: "f1",
+        title_key: "card.f1.title",
+        description_key: "card.f1.description",
+        cost: Cost::super_ops(4_000).and(Cost::epic_ops(2_000)),
+        condition: CardCondition::RequestsFailed(25_000),
+        effect: CardEffect::UpgradeSpamProtection(0.875),
+    },
+    CardSpec {
+        id: "f2",
+        title_key: "card.f2.title",
+        description_key: "card.f2.description",
+        cost: Cost::epic_ops(40_000).and(Cost::awesome_ops(20_000)),
+        condition: CardCondition::TotalAwesomeOps(Ops(5_000)),
+        effect: CardEffect::UpgradeSpamProtection(1.),
+    },
+    // --- network cards ---
+    CardSpec {
+        id: "g1",
+        title_key: "card.g1.title",
+        description_key: "card.g1.description",
+        cost: Cost::dollars(1_000).and(Cost::epic_ops(4_000)),
+        condition: CardCondition::after_card_millis("n3", 60_000),
+        effect: CardEffect::UpgradeBandwidth(1),
+    },
+    CardSpec {
+        id: "g2",
+        title_key: "card.g2.title",
+        description_key: "card.g2.description",
+        cost: Cost::dollars(25_000).and(Cost::awesome_ops(2_000)),
+        condition: CardCondition::TotalCloudNodes(12),
+        effect: CardEffect::UpgradeBandwidth(2),
+    },
+    CardSpec {
+        id: "g3",
+        title_key: "card.g3.title",
+        description_key: "card.g3.description",
+        cost: Cost::dollars(300_000).and(Cost::awesome_ops(20_000)),
+        condition: CardCondition::after_card_millis("n5", 600_000),
+        effect: CardEffect::UpgradeBandwidth(3),
+    },
+    // --- hardware cards ---
+    CardSpec {
+        id: "h0",
+        title_key: "card.h0.title",
+        description_key: "card.h0.description",
+        cost: Cost::dollars(250).and(Cost::base_ops(2_000)),
+        condition: CardCondition::TotalCloudNodes(2),
+        effect: CardEffect::UpgradeAllNodesRam(1),
+    },
+    CardSpec {
+        id: "h1",
+        title_key: "card.h1.title",
+        description_key: "card.h1.description",
+        cost: Cost::dollars(600).and(Cost::super_ops(800)),
+        condition: CardCondition::TotalCloudNodes(4),
+        effect: CardEffect::OverclockNodes {
+            speed_bonus: 1,
+            energy_penalty: 0.2,
+        },
+    },
+    CardSpec {
+        id: "h2",
+        title_key: "card.h2.title",
+        description_key: "card.h2.description",
+        cost: Cost::dollars(1_000).and(Cost::super_ops(1_500)),
+        condition: CardCondition::Insurable(5_000),
+        effect: CardEffect::TakeInsurance {
+            premium: Money::dollars(25),
+        },
+    },
+    // --- informative cards ---
+    CardSpec {
+        id: "i0",
+        title_key: "card.i0.title",
+        description_key: "card.i0.description",
+        cost: Cost::base_ops(500),
+        condition: CardCondition::TotalBaseOps(Ops(200)),
+        effect: CardEffect::UnlockDemandEstimate,
+    },
+    CardSpec {
+        id: "i1",
+        title_key: "card.i1.title",
+        description_key: "card.i1.description",
+        condition: CardCondition::TotalSuperOps(Ops(350)),
+        cost: Cost::dollars(100).and(Cost::super_ops(500)),
+        effect: CardEffect::UnlockEnergyEstimate,
+    },
+    CardSpec {
+        id: "i2",
+        title_key: "card.i2.title",
+        description_key: "card.i2.description",
+        condition: CardCondition::TotalEpicOps(Ops(500)),
+        cost: Cost::dollars(500).and(Cost::epic_ops(750)),
+        effect: CardEffect::UnlockRequestRateEstimate,
+    },
+    // --- hardware scaling cards ---
+    CardSpec {
+        id: "lose",
+        title_key: "card.lose.title",
+        description_key: "card.lose.description",
+        cost: Cost {
+            legendary_ops: Ops(0),
+            awesome_ops: Ops(0x7FFF_FFFF_FFFF_FFFF),
+            epic_ops: Ops(0),
+            super_ops: Ops(0),
+            base_ops: Ops(0),
+            money: Money::zero(),
+        },
+        condition: CardCondition::TimeAfterCard {
+            card: ID_GAME_OVER,
+            duration: 750_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "n1",
+        title_key: "card.n1.title",
+        description_key: "card.n1.description",
+        condition: CardCondition::FullyUpgradedNode,
+        cost: Cost::dollars(150).and(Cost::base_ops(1_000)),
+        effect: CardEffect::UnlockMultiNodes,
+    },
+    CardSpec {
+        id: "n2",
+        title_key: "card.n2.title",
+        description_key: "card.n2.description",
+        condition: CardCondition::TotalCloudNodes(3),
+        cost: Cost::dollars(100).and(Cost::super_ops(1_000)),
+        effect: CardEffect::UpgradeRoutingLevel(RoutingLevel::Distributed),
+    },
+    CardSpec {
+        id: "n3",
+        title_key: "card.n3.title",
+        description_key: "card.n3.description",
+        condition: CardCondition::FullyUpgradedRack,
+        cost: Cost::dollars(340).and(Cost::epic_ops(6_000)),
+        effect: CardEffect::UnlockMultiRacks,
+    },
+    CardSpec {
+        id: "n5",
+        title_key: "card.n5.title",
+        description_key: "card.n5.description",
+        condition: CardCondition::FullyUpgradedDatacenter,
+        cost: Cost::dollars(75_000).and(Cost::super_ops(55_000)),
+        effect: CardEffect::UnlockMultiDatacenters,
+    },
+    CardSpec {
+        id: "n6",
+        title_key: "card.n6.title",
+        description_key: "card.n6.description",
+        condition: CardCondition::TotalCloudNodes(36),
+        cost: Cost::dollars(222_000).and(Cost::awesome_ops(8_000)),
+        effect: CardEffect::UpgradeRoutingLevel(RoutingLevel::NoRoutingCost),
+    },
+    // --- partnership cards ---
+    CardSpec {
+        id: "o0",
+        title_key: "card.o0.title",
+        description_key: "card.o0.description",
+        cost: Cost::dollars(250).and(Cost::super_ops(800)),
+        condition: CardCondition::TotalSuperOps(Ops(4_000)),
+        effect: CardEffect::AddResearchers(ServiceKind::Super),
+    },
+    // --- penalty cards ---
+    CardSpec {
+        id: "p0",
+        title_key: "card.p0.title",
+        description_key: "card.p0.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TotalCloudNodes(RACK_CAPACITY),
+        effect: CardEffect::Penalty(Money::dollars(500)),
+    },
+    CardSpec {
+        id: "p1",
+        title_key: "card.p1.title",
+        description_key: "card.p1.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::after_card_millis("n5", 1_800_000),
+        effect: CardEffect::Penalty(Money::dollars(50_000)),
+    },
+    // --- software upgrade cards ---
+    CardSpec {
+        id: "s1",
+        title_key: "card.s1.title",
+        description_key: "card.s1.description",
+        cost: Cost::money(Money::dollars(5)).and(Cost::base_ops(64)),
+        condition: CardCondition::Funds(Money::dollars(20)),
+        effect: CardEffect::UpgradeServices,
+    },
+    CardSpec {
+        id: "s2",
+        title_key: "card.s2.title",
+        description_key: "card.s2.description",
+        cost: Cost::money(Money::dollars(66)).and(Cost::base_ops(750)),
+        condition: CardCondition::TotalBaseOps(Ops(2_000)),
+        effect: CardEffect::UpgradeServices,
+    },
+    CardSpec {
+        id: "s3",
+        title_key: "card.s3.title",
+        description_key: "card.s3.description",
+        cost: Cost::money(Money::dollars(460)).and(Cost::super_ops(500)),
+        condition: CardCondition::TotalSuperOps(Ops(2_000)),
+        effect: CardEffect::UpgradeServices,
+    },
+    CardSpec {
+        id: "s4",
+        title_key: "card.s4.title",
+        description_key: "card.s4.description",
+        cost: Cost::money(Money::dollars(3_600)).and(Cost::epic_ops(48_000)),
+        condition: CardCondition::TotalEpicOps(Ops(40_000)),
+        effect: CardEffect::UpgradeServices,
+    },
+    // --- staff cards ---
+    CardSpec {
+        id: "st0",
+        title_key: "card.st0.title",
+        description_key: "card.st0.description",
+        cost: Cost::dollars(120).and(Cost::base_ops(1_500)),
+        condition: CardCondition::Earned(Money::dollars(600)),
+        effect: CardEffect::UnlockStaff,
+    },
+    // test cards
+    CardSpec {
+        id: "test-0",
+        title_key: "card.test-0.title",
+        description_key: "card.test-0.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::appear_immediately(),
+        effect: CardEffect::AddFunds(Money::dollars(200)),
+    },
+    CardSpec {
+        id: "test-1",
+        title_key: "card.test-1.title",
+        description_key: "card.test-1.description",
+        cost: Cost::base_ops(500),
+        condition: CardCondition::appear_immediately(),
+        effect: CardEffect::UpgradeServices,
+    },
+    CardSpec {
+        id: "test-2",
+        title_key: "card.test-2.title",
+        description_key: "card.test-2.description",
+        cost: Cost::super_ops(100),
+        condition: CardCondition::appear_immediately(),
+        effect: CardEffect::AddPublicityRate(20., 0.),
+    },
+    CardSpec {
+        id: "test-3",
+        title_key: "card.test-3.title",
+        description_key: "card.test-3.description",
+        cost: Cost::super_ops(500_000),
+        condition: CardCondition::appear_immediately(),
+        effect: CardEffect::UpgradeServices,
+    },
+    CardSpec {
+        id: "test-4",
+        title_key: "card.test-4.title",
+        description_key: "card.test-4.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::Test { test: false },
+        effect: CardEffect::Nothing,
+    },
+    // returning player cards
+    CardSpec {
+        id: "wb",
+        title_key: "card.wb.title",
+        description_key: "card.wb.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::AwayBonusPending,
+        effect: CardEffect::WelcomeBack,
+    },
+    // winning cards
+    CardSpec {
+        id: "win0",
+        title_key: "card.win0.title",
+        description_key: "card.win0.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TotalAwesomeOps(Ops(1_000_000_000)),
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win1",
+        title_key: "card.win1.title",
+        description_key: "card.win1.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win0",
+            duration: 200_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win2",
+        title_key: "card.win2.title",
+        description_key: "card.win2.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win1",
+            duration: 160_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win3",
+        title_key: "card.win3.title",
+        description_key: "card.win3.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win2",
+            duration: 100_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win4",
+        title_key: "card.win4.title",
+        description_key: "card.win4.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win3",
+            duration: 20_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win5",
+        title_key: "card.win5.title",
+        description_key: "card.win5.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win4",
+            duration: 50_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: ID_WIN,
+        title_key: "card.win6.title",
+        description_key: "card.win6.description",
+        cost: Cost::awesome_ops(1),
+        condition: CardCondition::TimeAfterCard {
+            card: "win5",
+            duration: 20_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: ID_WIN_EPILOGUE,
+        title_key: "card.win7.title",
+        description_key: "card.win7.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_WIN,
+            duration: 5_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: "win8",
+        title_key: "card.win8.title",
+        description_key: "card.win8.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: ID_WIN_EPILOGUE,
+            duration: 5_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+    CardSpec {
+        id: ID_GAME_OVER,
+        title_key: "card.win9.title",
+        description_key: "card.win9.description",
+        cost: Cost::nothing(),
+        condition: CardCondition::TimeAfterCard {
+            card: "win8",
+            duration: 500_000,
+        },
+        effect: CardEffect::Nothing,
+    },
+];
+
+/// Find a card by its identifier.
+///
+/// Falls back to a linear search
+/// if the cards are not in id ascending order,
+/// so that an ordering mistake does not break card purchases.
+pub fn card_by_id(id: &str) -> Option<&'static CardSpec> {
+    match ALL_CARDS.binary_search_by(|c| c.id.cmp(id)) {
+        Ok(idx) => Some(&ALL_CARDS[idx]),
+        Err(_) => {
+            let card = ALL_CARDS.iter().find(|c| c.id == id);
+            if card.is_some() {
+                log::warn!("Card list is out of order, found card by linear search: {id}");
+            }
+            card
+        }
+    }
+}
+
+/// Find the first pair of consecutive cards
+/// which are not in strictly ascending id order,
+/// or `None` if all ids are sorted and unique.
+pub fn find_misordered_cards(cards: &[CardSpec]) -> Option<(&'static str, &'static str)> {
+    cards
+        .windows(2)
+        .find(|pair| pair[0].id >= pair[1].id)
+        .map(|pair| (pair[0].id, pair[1].id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{card_by_id, find_misordered_cards, ALL_CARDS};
+    use crate::{
+        central::cards::{CardCondition, CardEffect, CardSpec},
+        Cost, Ops, ServiceKind,
+    };
+
+    /// The highest service tier which the player must have unlocked
+    /// for the condition of the card to hold,
+    /// if the condition tells.
+    fn condition_tier(card: &CardSpec) -> Option<ServiceKind> {
+        match card.condition {
+            CardCondition::TotalBaseOps(_) | CardCondition::AvailableBaseOps(_) => {
+                Some(ServiceKind::Base)
+            }
+            CardCondition::TotalSuperOps(_) | CardCondition::AvailableSuperOps(_) => {
+                Some(ServiceKind::Super)
+            }
+            CardCondition::TotalEpicOps(_) | CardCondition::AvailableEpicOps(_) => {
+                Some(ServiceKind::Epic)
+            }
+            CardCondition::TotalAwesomeOps(_) | CardCondition::AvailableAwesomeOps(_) => {
+                Some(ServiceKind::Awesome)
+            }
+            CardCondition::TimeAfterCard { card: other, .. } => {
+                match card_by_id(other).map(|c| &c.effect) {
+                    Some(CardEffect::UnlockService(kind) | CardEffect::PublishService(kind)) => {
+                        Some(*kind)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_cards_in_id_order() {
+        assert_eq!(find_misordered_cards(ALL_CARDS), None);
+    }
+
+    #[test]
+    fn test_find_misordered_cards() {
+        let card = |id| CardSpec {
+            id,
+            title_key: "",
+            description_key: "",
+            cost: Cost::nothing(),
+            condition: CardCondition::appear_immediately(),
+            effect: CardEffect::Nothing,
+        };
+        let cards = [
+            card("d3"),
+            card("d3.5"),
+            card("d4"),
+            card("d4.5"),
+            card("d5"),
+        ];
+        assert_eq!(find_misordered_cards(&cards), None);
+
+        let cards = [card("d3"), card("d4"), card("d3.5")];
+        assert_eq!(find_misordered_cards(&cards), Some(("d4", "d3.5")));
+
+        // duplicates are not allowed either
+        let cards = [card("d3"), card("d3")];
+        assert_eq!(find_misordered_cards(&cards), Some(("d3", "d3")));
+    }
+
+    #[test]
+    fn test_card_by_id_finds_all_cards() {
+        for card in ALL_CARDS {
+            assert_eq!(card_by_id(card.id).map(|c| c.id), Some(card.id));
+        }
+        assert!(card_by_id("nope").is_none());
+    }
+
+    #[test]
+    fn test_card_references_exist() {
+        for card in ALL_CARDS {
+            if let CardCondition::TimeAfterCard { card: other, .. } = card.condition {
+                assert!(
+                    card_by_id(other).is_some(),
+                    "card {} refers to unknown card {}",
+                    card.id,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_card_costs_non_negative() {
+        for card in ALL_CARDS {
+            assert!(
+                card.cost.is_non_negative(),
+                "card {} has a negative cost",
+                card.id
+            );
+            if card.is_penalty() {
+                assert!(
+                    card.cost.is_nothing(),
+                    "penalty card {} has a cost",
+                    card.id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_card_costs_within_reach() {
+        for card in ALL_CARDS {
+            let Some(tier) = condition_tier(card) else {
+                continue;
+            };
+            for (kind, ops) in card.cost.ops() {
+                assert!(
+                    ops == Ops(0) || kind.to_code() <= tier.to_code(),
+                    "card {} appears at the {tier} tier but costs {kind} ops",
+                    card.id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_card_titles_unique() {
+        let mut titles = HashMap::new();
+        for card in ALL_CARDS.iter().filter(|card| !card.id.starts_with("test")) {
+            if let Some(other) = titles.insert(card.title(), card.id) {
+                panic!(
+                    "cards {other} and {} share the title {:?}",
+                    card.id,
+                    card.title()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_card_chains_acyclic() {
+        for card in ALL_CARDS {
+            let mut seen = vec![card.id];
+            let mut current = card;
+            while let CardCondition::TimeAfterCard { card: other, .. } = current.condition {
+                assert!(
+                    !seen.contains(&other),
+                    "card {} is part of a cycle: {seen:?}",
+                    card.id
+                );
+                seen.push(other);
+                current = card_by_id(other).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_win_chain_connected() {
+        // win0 is reached by playing, then each step follows the previous one
+        assert!(matches!(
+            card_by_id("win0").unwrap().condition,
+            CardCondition::TotalAwesomeOps(_)
+        ));
+        for i in 1..=9 {
+            let id = format!("win{i}");
+            let card = card_by_id(&id).unwrap();
+            let CardCondition::TimeAfterCard { card: previous, .. } = card.condition else {
+                panic!("card {id} does not follow another card");
+            };
+            assert_eq!(previous, format!("win{}", i - 1), "card {id}");
+        }
+        assert!(card_by_id("win10").is_none());
+    }
+}
@@ -0,0 +1,570 @@
+use std::borrow::Cow;
+
+use crate::{
+    display::{GameDuration, Separating},
+    i18n, t, CloudClientSpec, Cost, Money, Ops, ServiceKind, Time, WorldState,
+    TIME_UNITS_PER_MILLISECOND,
+};
+
+use super::{
+    engine::{CloudNode, RACK_CAPACITY},
+    state::RoutingLevel,
+};
+
+pub mod all;
+
+/// The specification for a card,
+/// including in what circumstances it should become available.
+#[derive(Debug)]
+pub struct CardSpec {
+    /// the unique identifier as a small static string
+    pub id: &'static str,
+    /// the translation key of the card's title
+    /// (see [`i18n`](crate::i18n))
+    pub title_key: &'static str,
+    /// the translation key of a short description of the card
+    pub description_key: &'static str,
+    /// the cost of the card,
+    /// including which operations are needed
+    pub cost: Cost,
+    /// the primary condition for the card to appear
+    /// in the project's panel
+    /// (the secondary condition is that
+    /// the operation service kinds in `cost` are unlocked)
+    pub condition: CardCondition,
+    /// the effect of the card once used
+    pub effect: CardEffect,
+}
+
+impl CardSpec {
+    /// The card's title in the locale in use.
+    pub fn title(&self) -> &'static str {
+        i18n::translate(self.title_key)
+    }
+
+    /// A short description of the card in the locale in use.
+    pub fn description(&self) -> &'static str {
+        i18n::translate(self.description_key)
+    }
+
+    /// Whether the card's effect permanently changes the player's setup
+    /// in a way which cannot be undone,
+    /// so that the player is always asked to confirm it first.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self.effect, CardEffect::UnlockMultiDatacenters)
+    }
+
+    /// A short description of the card in the locale in use,
+    /// followed by the trial period of the clients it brings, if any.
+    pub fn full_description(&self) -> Cow<'static, str> {
+        match &self.effect {
+            CardEffect::AddClients(spec) | CardEffect::AddClientsWithPublicity(spec, _)
+                if spec.trial_duration > 0 =>
+            {
+                let trial = t!(
+                    "card.trial",
+                    duration = GameDuration(spec.trial_duration as Time)
+                );
+                format!("{} ({trial})", self.description()).into()
+            }
+            _ => self.description().into(),
+        }
+    }
+
+    /// Returns true if the card should be visible
+    /// according to the given world state.
+    pub fn should_appear(&self, state: &WorldState) -> bool {
+        // should not be a used card
+        (!state.is_card_used(self.id) || self.is_repeatable())
+        // condition of appearance is fulfilled
+            && self.condition.should_appear(state)
+        // check if the player has unlocked the service kinds
+            && self.has_services_unlocked(state)
+        // and should not be a test card
+            && !self.id.starts_with("test")
+    }
+
+    /// Returns the progress towards the card's condition
+    /// if the card is yet to appear
+    /// and its condition is a quantifiable milestone
+    /// (see [`CardCondition::progress`]).
+    pub fn upcoming_progress(&self, state: &WorldState) -> Option<(f32, String)> {
+        if (state.is_card_used(self.id) && !self.is_repeatable())
+            || self.id.starts_with("test")
+            || self.condition.should_appear(state)
+            || !self.has_services_unlocked(state)
+        {
+            return None;
+        }
+        self.condition.progress(state)
+    }
+
+    /// Whether research progress discounts the operation costs of this card
+    /// (see [`WorldState::research_discount`]).
+    pub fn is_research_discounted(&self) -> bool {
+        matches!(
+            self.effect,
+            CardEffect::UpgradeServices | CardEffect::MoreCaching
+        )
+    }
+
+    /// Whether this card can be used again
+    /// whenever its condition holds.
+    pub fn is_repeatable(&self) -> bool {
+        matches!(
+            self.effect,
+            CardEffect::WelcomeBack | CardEffect::TakeInsurance { .. }
+        )
+    }
+
+    /// Whether this card charges a penalty,
+    /// and is therefore used as soon as it appears.
+    pub fn is_penalty(&self) -> bool {
+        matches!(self.effect, CardEffect::Penalty(_))
+    }
+
+    fn has_services_unlocked(&self, state: &WorldState) -> bool {
+        // super service must be unlocked if it costs super ops
+        (self.cost.super_ops == Ops(0) || state.super_service.state.is_unlocked())
+        // epic service must be unlocked if it costs epic ops
+            && (self.cost.epic_ops == Ops(0) || state.epic_service.state.is_unlocked())
+        // awesome service must be unlocked if it costs awesome ops
+            && (self.cost.awesome_ops == Ops(0) || state.awesome_service.state.is_unlocked())
+        // legendary service must be unlocked if it costs legendary ops
+            && (self.cost.legendary_ops == Ops(0) || state.legendary_service.state.is_unlocked())
+    }
+}
+
+/// The condition at which a card should become available.
+#[derive(Debug)]
+pub enum CardCondition {
+    /// the card should appear iif `test` is true
+    Test { test: bool },
+    /// the player has accumulated funds
+    Funds(Money),
+    /// the player has spent funds
+    Spent(Money),
+    /// the player has earned this much money
+    Earned(Money),
+    /// the player has accrued a net total of base ops
+    TotalBaseOps(Ops),
+    /// the player has accumulated an amount of extra base ops
+    AvailableBaseOps(Ops),
+    /// the player has accrued a net total of super ops
+    TotalSuperOps(Ops),
+    /// the player has accumulated an amount of extra super ops
+    AvailableSuperOps(Ops),
+    /// the player has accrued a net total of epic ops
+    TotalEpicOps(Ops),
+    /// the player has accumulated an amount of extra epic ops
+    AvailableEpicOps(Ops),
+    /// the player has accrued a net total of awesome ops
+    TotalAwesomeOps(Ops),
+    /// the player has accumulated an amount of extra awesome ops
+    AvailableAwesomeOps(Ops),
+    /// at least N requests have been dropped
+    RequestsDropped(u32),
+    /// at least N requests have failed
+    RequestsFailed(u32),
+    /// the player has reached a certain baseline demand level
+    Demand(f64),
+    /// the player received their first electricity bill
+    FirstBillArrived,
+    /// appear N ticks after another card has been used
+    TimeAfterCard {
+        /// the card index
+        card: &'static str,
+        /// the time after a card was used
+        /// at which this card should appear
+        duration: u32,
+    },
+    /// the player has at least N nodes
+    TotalCloudNodes(u32),
+    /// the player has at least N memory upgrades
+    TotalMemoryUpgrades(u32),
+    /// the first node has been upgraded to maximum CPU
+    FullyUpgradedNode,
+    /// the player is being offered a bonus for returning to the game
+    /// (see [`away`](crate::central::away))
+    AwayBonusPending,
+    /// the first rack has been fully upgraded
+    FullyUpgradedRack,
+    /// the first data center has been fully upgraded
+    FullyUpgradedDatacenter,
+    /// at least N requests have been dropped,
+    /// and the player is neither insured
+    /// nor cancelled a policy too recently
+    /// (see [`insurance`](crate::central::insurance))
+    Insurable(u32),
+}
+
+impl CardCondition {
+    pub const fn appear_immediately() -> Self {
+        Self::Test { test: true }
+    }
+
+    /// appear approximately N milliseconds
+    /// after the player uses a card
+    pub const fn after_card_millis(card_id: &'static str, millis: u32) -> Self {
+        Self::TimeAfterCard {
+            card: card_id,
+            duration: millis / TIME_UNITS_PER_MILLISECOND,
+        }
+    }
+
+    /// Returns true if the card condition is true for the given world state.
+    ///
+    /// Does not check whether the card has been used
+    /// nor whether the service kinds in the cost are unlocked.
+    pub fn should_appear(&self, state: &WorldState) -> bool {
+        // then check the primary condition
+        match self {
+            Self::Test { test } => *test,
+            Self::Funds(money) => state.funds >= *money,
+            Self::Spent(money) => state.spent < *money,
+            Self::Earned(money) => state.earned >= *money,
+            Self::TotalBaseOps(ops) => state.base_service.total >= *ops,
+            Self::AvailableBaseOps(ops) => state.base_service.available >= *ops,
+            Self::TotalSuperOps(ops) => state.super_service.total >= *ops,
+            Self::AvailableSuperOps(ops) => state.super_service.available >= *ops,
+            Self::TotalEpicOps(ops) => state.epic_service.total >= *ops,
+            Self::AvailableEpicOps(ops) => state.epic_service.available >= *ops,
+            Self::TotalAwesomeOps(ops) => state.awesome_service.total >= *ops,
+            Self::AvailableAwesomeOps(ops) => state.awesome_service.available >= *ops,
+            Self::Demand(demand) => state.demand >= *demand,
+            Self::RequestsDropped(count) => state.requests_dropped >= *count as u64,
+            Self::RequestsFailed(count) => state.requests_failed >= *count as u64,
+            Self::FirstBillArrived => state.electricity.last_bill_time > 0,
+            Self::TimeAfterCard { card, duration } => {
+                match state
+                    .cards_used
+                    .binary_search_by(|used_card| used_card.id.as_ref().cmp(*card))
+                {
+                    Err(_) => false,
+                    Ok(index) => {
+                        let used_card = &state.cards_used[index];
+                        used_card.time + *duration as u64 <= state.time
+                    }
+                }
+            }
+            Self::TotalCloudNodes(count) => state.nodes.len() >= *count as usize,
+            Self::TotalMemoryUpgrades(count) => {
+                state
+                    .nodes
+                    .iter()
+                    .map(|node| node.ram_level as u32)
+                    .sum::<u32>()
+                    >= *count
+            }
+            Self::FullyUpgradedNode => state
+                .nodes
+                .iter()
+                .any(|node| node.is_rack() || node.is_fully_upgraded()),
+            Self::FullyUpgradedRack => {
+                state.nodes.iter().any(CloudNode::is_rack)
+                    || state
+                        .nodes
+                        .iter()
+                        .filter(|node| node.is_fully_upgraded())
+                        .count()
+                        >= RACK_CAPACITY as usize
+            }
+            Self::FullyUpgradedDatacenter => {
+                state
+                    .nodes
+                    .iter()
+                    .map(|node| node.scale.machines())
+                    .sum::<u32>()
+                    >= RACK_CAPACITY * 10
+            }
+            Self::AwayBonusPending => state.away_bonus.is_some(),
+            Self::Insurable(count) => {
+                state.requests_dropped >= *count as u64
+                    && state.insurance.is_none()
+                    && state.time >= state.insurance_ready_at()
+            }
+        }
+    }
+
+    /// Returns how close the world state is to fulfilling the condition,
+    /// as a completion fraction between 0 and 1
+    /// and a human-readable description of the requirement.
+    ///
+    /// Only quantifiable milestones report progress,
+    /// so this is `None` for all other conditions.
+    pub fn progress(&self, state: &WorldState) -> Option<(f32, String)> {
+        fn fraction(current: f64, target: f64) -> f32 {
+            if target <= 0. {
+                1.
+            } else {
+                (current / target).clamp(0., 1.) as f32
+            }
+        }
+
+        let (current, target, requirement) = match self {
+            Self::TotalBaseOps(ops) => (
+                state.base_service.total.0 as f64,
+                ops.0 as f64,
+                t!("card.needs.base_ops", ops = ops),
+            ),
+            Self::TotalSuperOps(ops) => (
+                state.super_service.total.0 as f64,
+                ops.0 as f64,
+                t!("card.needs.super_ops", ops = ops),
+            ),
+            Self::TotalEpicOps(ops) => (
+                state.epic_service.total.0 as f64,
+                ops.0 as f64,
+                t!("card.needs.epic_ops", ops = ops),
+            ),
+            Self::TotalAwesomeOps(ops) => (
+                state.awesome_service.total.0 as f64,
+                ops.0 as f64,
+                t!("card.needs.awesome_ops", ops = ops),
+            ),
+            Self::Earned(money) => (
+                state.earned.to_millicents() as f64,
+                money.to_millicents() as f64,
+                t!("card.needs.earned", money = money),
+            ),
+            Self::Funds(money) => (
+                state.funds.to_millicents() as f64,
+                money.to_millicents() as f64,
+                t!("card.needs.funds", money = money),
+            ),
+            Self::RequestsDropped(count) => (
+                state.requests_dropped as f64,
+                *count as f64,
+                t!("card.needs.dropped", count = Separating(*count as i64)),
+            ),
+            Self::TotalCloudNodes(count) => (
+                state.nodes.len() as f64,
+                *count as f64,
+                t!("card.needs.nodes", count = count),
+            ),
+            _ => return None,
+        };
+
+        Some((fraction(current, target), requirement))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CardEffect {
+    /// Might be useful
+    Nothing,
+    /// Make the service public,
+    /// so that it can be used by customers
+    PublishService(ServiceKind),
+    /// Make the service visible to the player,
+    /// staying private to customers.
+    ///
+    /// The base service does not need unlocking.
+    UnlockService(ServiceKind),
+    /// Add or remove funds
+    AddFunds(Money),
+    /// Charge the player a fine or a repair bill,
+    /// added to the electricity bill like a broken SLA
+    /// so that it is paid under the same policy.
+    ///
+    /// Cards with this effect are not shown to the player,
+    /// they are used automatically as soon as they appear.
+    Penalty(Money),
+    /// Grant the pending bonus for returning to the game,
+    /// scaled by the time away
+    WelcomeBack,
+    /// Change how much extra money you earn per op
+    /// (regardless of who issued it).
+    UpgradeEntitlements(ServiceKind, Money),
+    /// Sign a service-level agreement on a service,
+    /// adding to how much extra money you earn per op
+    /// in exchange for a penalty in every bill period
+    /// in which too many customer requests are dropped
+    /// (see [`ServiceLevelAgreement`](crate::central::state::ServiceLevelAgreement)).
+    SignSla {
+        service: ServiceKind,
+        bonus: Money,
+        penalty: Money,
+    },
+    /// Take an infrastructure insurance policy,
+    /// adding the premium to every electricity bill
+    /// in exchange for payouts in bill periods
+    /// in which too many customer requests are dropped
+    /// (see [`InsurancePolicy`](crate::central::insurance::InsurancePolicy)).
+    TakeInsurance { premium: Money },
+    /// Add cloud clients with the given specification
+    AddClients(CloudClientSpec),
+    /// Add research partners using the given service
+    AddResearchers(ServiceKind),
+    /// Add cloud clients with the given specification,
+    /// plus increase general service demand by the given percentage
+    AddClientsWithPublicity(CloudClientSpec, f64),
+    /// Increase general service visibility (0),
+    /// and visibility increase rate (1),
+    /// by the given amounts
+    AddPublicityRate(f64, f64),
+    /// Increase the number of operations per player click
+    UpgradeOpsPerClick(u32),
+    /// Set the electricity bill level (higher levels mean cheaper electricity)
+    SetElectricityCostLevel(u8),
+    /// Unlock hiring staff
+    UnlockStaff,
+    /// Set the electricity bill level as [`SetElectricityCostLevel`](Self::SetElectricityCostLevel),
+    /// and unlock choosing among energy providers
+    UnlockTariffs(u8),
+    /// Upgrade software services to the next level
+    UpgradeServices,
+    /// Upgrade the software caching level
+    MoreCaching,
+    /// Unlock node purchasing
+    UnlockMultiNodes,
+    /// Unlock rack purchasing
+    UnlockMultiRacks,
+    /// Unlock purchasing of new data center warehouses
+    UnlockMultiDatacenters,
+    /// Unlock demand estimate in business panel
+    UnlockDemandEstimate,
+    /// Unlock energy consumption estimate in business panel
+    UnlockEnergyEstimate,
+    /// Unlock request drop&failure rate estimate in business panel
+    UnlockRequestRateEstimate,
+    /// Unlock the op exchange in business panel
+    UnlockOpExchange,
+    /// Unlock paying electricity bills automatically,
+    /// turning it on
+    UnlockAutoPay,
+    /// Add protection from bad requests (detection rate)
+    UpgradeSpamProtection(f32),
+    /// Upgrade the routing implementation
+    UpgradeRoutingLevel(RoutingLevel),
+    /// Upgrade the network uplink to the given bandwidth level
+    /// (see [`bandwidth_capacity`](crate::central::bandwidth::bandwidth_capacity))
+    UpgradeBandwidth(u8),
+    /// Upgrade the RAM of every single node by the given number of levels,
+    /// up to the maximum level, for free
+    UpgradeAllNodesRam(u8),
+    /// Overclock every node currently in place,
+    /// adding to their CPU speed
+    /// and to the energy consumed per request by the given fraction
+    OverclockNodes {
+        speed_bonus: u32,
+        energy_penalty: f32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        all::{card_by_id, ALL_CARDS},
+        CardCondition, CardEffect,
+    };
+    use crate::{
+        central::{engine::CloudNode, testing::WorldStateBuilder},
+        display::GameDuration,
+        Money, Ops, ServiceKind, Time, WorldState,
+    };
+
+    #[test]
+    fn test_progress_quantifiable() {
+        let state = WorldStateBuilder::new()
+            .total_ops(ServiceKind::Base, Ops(750))
+            .total_ops(ServiceKind::Super, Ops(50))
+            .total_ops(ServiceKind::Awesome, Ops(10))
+            .earned(Money::dollars(25))
+            .funds(Money::dollars(1))
+            .requests_dropped(30)
+            .build();
+
+        let cases = [
+            (CardCondition::TotalBaseOps(Ops(1_500)), 0.5),
+            (CardCondition::TotalSuperOps(Ops(200)), 0.25),
+            (CardCondition::TotalEpicOps(Ops(100)), 0.),
+            (CardCondition::TotalAwesomeOps(Ops(40)), 0.25),
+            (CardCondition::Earned(Money::dollars(100)), 0.25),
+            (CardCondition::Funds(Money::dollars(4)), 0.25),
+            (CardCondition::RequestsDropped(120), 0.25),
+            (CardCondition::TotalCloudNodes(4), 0.25),
+        ];
+
+        for (condition, expected) in cases {
+            let (fraction, requirement) = condition.progress(&state).unwrap();
+            assert!(
+                (fraction - expected).abs() < 1e-6,
+                "{condition:?}: expected {expected}, got {fraction}"
+            );
+            assert!(!requirement.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_progress_saturates() {
+        let mut state = WorldStateBuilder::new()
+            .total_ops(ServiceKind::Base, Ops(3_000))
+            .earned(Money::dollars(500))
+            .requests_dropped(1_000)
+            .with_node(CloudNode::new(1))
+            .with_node(CloudNode::new(2))
+            .build();
+
+        for condition in [
+            CardCondition::TotalBaseOps(Ops(1_500)),
+            CardCondition::Earned(Money::dollars(100)),
+            CardCondition::RequestsDropped(10),
+            CardCondition::TotalCloudNodes(2),
+        ] {
+            let (fraction, _) = condition.progress(&state).unwrap();
+            assert_eq!(fraction, 1., "{condition:?}");
+        }
+
+        // negative funds do not go below zero
+        state.funds = Money::dollars(-5);
+        let (fraction, _) = CardCondition::Funds(Money::dollars(10))
+            .progress(&state)
+            .unwrap();
+        assert_eq!(fraction, 0.);
+    }
+
+    #[test]
+    fn test_trial_in_description() {
+        let card = card_by_id("d0").unwrap();
+        assert_eq!(
+            card.full_description(),
+            format!("{} (5s trial)", card.description())
+        );
+        let card = card_by_id("b1").unwrap();
+        assert_eq!(card.full_description(), card.description());
+
+        // the trial shown always comes from the clients brought by the card
+        for card in ALL_CARDS {
+            let (CardEffect::AddClients(spec) | CardEffect::AddClientsWithPublicity(spec, _)) =
+                &card.effect
+            else {
+                continue;
+            };
+            if spec.trial_duration == 0 {
+                assert_eq!(card.full_description(), card.description(), "{}", card.id);
+            } else {
+                let trial = GameDuration(spec.trial_duration as Time).to_string();
+                assert!(
+                    card.full_description()
+                        .ends_with(&format!("({trial} trial)")),
+                    "{}: {}",
+                    card.id,
+                    card.full_description()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_progress_surprises_hidden() {
+        let state = WorldState::default();
+        assert!(CardCondition::appear_immediately()
+            .progress(&state)
+            .is_none());
+        assert!(CardCondition::after_card_millis("a0", 1_000)
+            .progress(&state)
+            .is_none());
+        assert!(CardCondition::FirstBillArrived.progress(&state).is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Module for statistics on the player's clicks,
+//! and for spotting clicking too regular to come from a person.
+//!
+//! Clicks are counted per second over the last [`CLICK_RATE_WINDOW`],
+//! and the times of the latest [`CLICK_HISTORY`] clicks are kept
+//! to measure how steady the clicking is,
+//! so that memory stays bounded however long the player clicks.
+//!
+//! Clicking faster than [`REGULAR_MIN_RATE`] per second
+//! with intervals varying less than [`REGULAR_MAX_VARIATION`]
+//! for [`REGULAR_MIN_DURATION`] is likely automated.
+//! This is only noted in the save, it is never punished.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Time, TIME_UNITS_PER_MINUTE, TIME_UNITS_PER_SECOND};
+
+/// the number of latest clicks whose times are kept
+pub const CLICK_HISTORY: usize = 64;
+
+/// the sliding window over which the click rate is measured (1 minute)
+pub const CLICK_RATE_WINDOW: Time = TIME_UNITS_PER_MINUTE;
+
+/// the clicks per second above which clicking may be automated
+pub const REGULAR_MIN_RATE: f32 = 15.;
+
+/// the coefficient of variation of the intervals between clicks
+/// below which clicking may be automated
+pub const REGULAR_MAX_VARIATION: f32 = 0.1;
+
+/// the time of sustained regular clicking
+/// for it to be considered automated (5 minutes)
+pub const REGULAR_MIN_DURATION: Time = 3_000_000;
+
+/// The click statistics of the player across all services.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClickStats {
+    /// the times of the latest clicks,
+    /// up to [`CLICK_HISTORY`] of them
+    recent: VecDeque<Time>,
+    /// the number of clicks in each second with any clicks
+    /// within the rate window, oldest first
+    seconds: VecDeque<(Time, u32)>,
+    /// since when clicking has been fast and regular, if it is
+    regular_since: Option<Time>,
+}
+
+impl ClickStats {
+    /// Register a player click at the given time.
+    ///
+    /// Returns whether clicking has been fast and regular
+    /// for long enough to be considered automated.
+    pub fn record_click(&mut self, time: Time) -> bool {
+        if self.recent.len() == CLICK_HISTORY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(time);
+
+        let second = time / TIME_UNITS_PER_SECOND;
+        match self.seconds.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => self.seconds.push_back((second, 1)),
+        }
+        while self
+            .seconds
+            .front()
+            .is_some_and(|&(s, _)| (s + 1) * TIME_UNITS_PER_SECOND + CLICK_RATE_WINDOW <= time)
+        {
+            self.seconds.pop_front();
+        }
+
+        // judge only once there is enough history
+        if self.recent.len() == CLICK_HISTORY {
+            let regular = self
+                .click_rate()
+                .is_some_and(|rate| rate > REGULAR_MIN_RATE)
+                && self
+                    .interval_variation()
+                    .is_some_and(|cv| cv < REGULAR_MAX_VARIATION);
+            if !regular {
+                self.regular_since = None;
+            } else if self.regular_since.is_none() {
+                self.regular_since = Some(self.recent[0]);
+            }
+        }
+        self.regular_since
+            .is_some_and(|since| time >= since + REGULAR_MIN_DURATION)
+    }
+
+    /// The number of clicks within the rate window up to the given time.
+    pub fn clicks_per_minute(&self, time: Time) -> u32 {
+        self.seconds
+            .iter()
+            .filter(|&&(s, _)| (s + 1) * TIME_UNITS_PER_SECOND + CLICK_RATE_WINDOW > time)
+            .map(|&(_, count)| count)
+            .sum()
+    }
+
+    /// The clicks per second over the latest clicks,
+    /// if there are at least two of them some time apart.
+    pub fn click_rate(&self) -> Option<f32> {
+        let (first, last) = (*self.recent.front()?, *self.recent.back()?);
+        if last == first {
+            return None;
+        }
+        Some((self.recent.len() - 1) as f32 * TIME_UNITS_PER_SECOND as f32 / (last - first) as f32)
+    }
+
+    /// The coefficient of variation of the intervals between the latest clicks
+    /// (their standard deviation over their mean),
+    /// if there are at least two of them some time apart.
+    pub fn interval_variation(&self) -> Option<f32> {
+        if self.recent.len() < 2 {
+            return None;
+        }
+        let intervals = self.recent.iter().zip(self.recent.iter().skip(1));
+        let n = (self.recent.len() - 1) as f32;
+        let mean = intervals.clone().map(|(a, b)| (b - a) as f32).sum::<f32>() / n;
+        if mean == 0. {
+            return None;
+        }
+        let variance = intervals
+            .map(|(a, b)| ((b - a) as f32 - mean).powi(2))
+            .sum::<f32>()
+            / n;
+        Some(variance.sqrt() / mean)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClickStats, CLICK_HISTORY, REGULAR_MIN_DURATION};
+    use crate::Time;
+
+    /// click at the given intervals, cycling through them,
+    /// until the given time,
+    /// returning whether clicking was ever considered automated
+    fn click_until(stats: &mut ClickStats, intervals: &[Time], to: Time) -> bool {
+        let mut time = 0;
+        let mut flagged = false;
+        for interval in intervals.iter().cycle() {
+            time += interval;
+            if time > to {
+                break;
+            }
+            flagged |= stats.record_click(time);
+        }
+        flagged
+    }
+
+    #[test]
+    fn test_click_stats() {
+        let mut stats = ClickStats::default();
+        assert_eq!(stats.clicks_per_minute(0), 0);
+        assert_eq!(stats.click_rate(), None);
+
+        // 5 clicks per second for 2 minutes
+        click_until(&mut stats, &[2_000], 1_199_999);
+        assert_eq!(stats.clicks_per_minute(1_200_000), 300);
+        assert_eq!(stats.click_rate(), Some(5.));
+        assert_eq!(stats.interval_variation(), Some(0.));
+        // the rate window slides
+        assert_eq!(stats.clicks_per_minute(1_500_000), 150);
+        assert_eq!(stats.clicks_per_minute(1_800_000), 0);
+
+        // only the latest clicks are kept
+        assert_eq!(stats.recent.len(), CLICK_HISTORY);
+        assert!(stats.seconds.len() <= 61);
+
+        // alternating intervals of 100 and 300 units vary by half their mean
+        let mut stats = ClickStats::default();
+        click_until(&mut stats, &[100, 300], 100_000);
+        let cv = stats.interval_variation().unwrap();
+        assert!((cv - 0.5).abs() < 0.01, "{cv}");
+        let rate = stats.click_rate().unwrap();
+        assert!((rate - 50.).abs() < 0.5, "{rate}");
+    }
+
+    #[test]
+    fn test_autoclicker_heuristic() {
+        // 20 clicks per second like clockwork
+        let mut stats = ClickStats::default();
+        assert!(!click_until(
+            &mut stats,
+            &[500],
+            REGULAR_MIN_DURATION - 10_000
+        ));
+        let mut stats = ClickStats::default();
+        assert!(click_until(
+            &mut stats,
+            &[500],
+            REGULAR_MIN_DURATION + 10_000
+        ));
+
+        // just as fast, but with a person's irregular rhythm
+        let mut stats = ClickStats::default();
+        assert!(!click_until(
+            &mut stats,
+            &[350, 620, 410, 530, 700, 390, 480, 560],
+            REGULAR_MIN_DURATION * 2
+        ));
+
+        // regular, but slow
+        let mut stats = ClickStats::default();
+        assert!(!click_until(&mut stats, &[1_000], REGULAR_MIN_DURATION * 2));
+
+        // regular and fast, but with breaks every minute
+        let mut stats = ClickStats::default();
+        let mut intervals = vec![500; 1_199];
+        intervals.push(100_000);
+        assert!(!click_until(
+            &mut stats,
+            &intervals,
+            REGULAR_MIN_DURATION * 2
+        ));
+    }
+}
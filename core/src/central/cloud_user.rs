@@ -0,0 +1,302 @@
+//! module for Cloud users (clients and researchers)
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Time, TIME_UNITS_PER_SECOND};
+
+use super::stuff::ServiceKind;
+
+/// The kind of cloud user,
+/// which determines how its requests are handled.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserKind {
+    /// a regular client, which pays for requests after the trial period
+    #[default]
+    Customer,
+    /// a research partner, which pays nothing
+    /// but contributes to research progress
+    Researcher,
+    /// an evil user which only produces bad requests
+    Attacker,
+}
+
+/// The behavioral specification for a cloud user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "CloudUserSpecRepr")]
+pub struct CloudUserSpec {
+    /// a unique identifier
+    pub id: u32,
+    /// the service this client will be using
+    pub service: ServiceKind,
+    /// the time up to which the user does not have to pay per request
+    /// (set 0 to always pay)
+    pub trial_time: Time,
+    /// the kind of user
+    pub kind: UserKind,
+}
+
+impl CloudUserSpec {
+    pub fn is_paying(&self, time: Time) -> bool {
+        self.kind == UserKind::Customer && time >= self.trial_time
+    }
+
+    /// Whether the user is evil and only produces bad requests.
+    pub fn is_bad(&self) -> bool {
+        self.kind == UserKind::Attacker
+    }
+}
+
+/// The serialized form of [`CloudUserSpec`],
+/// which also accepts older saves with a `bad` flag instead of a kind.
+#[derive(Deserialize)]
+struct CloudUserSpecRepr {
+    id: u32,
+    service: ServiceKind,
+    trial_time: Time,
+    #[serde(default)]
+    kind: Option<UserKind>,
+    #[serde(default)]
+    bad: bool,
+}
+
+impl From<CloudUserSpecRepr> for CloudUserSpec {
+    fn from(repr: CloudUserSpecRepr) -> Self {
+        let kind = match (repr.kind, repr.bad) {
+            (Some(kind), _) => kind,
+            (None, true) => UserKind::Attacker,
+            (None, false) => UserKind::Customer,
+        };
+        CloudUserSpec {
+            id: repr.id,
+            service: repr.service,
+            trial_time: repr.trial_time,
+            kind,
+        }
+    }
+}
+
+/// The specifications of the cloud users in play,
+/// always sorted by ID so that users can be looked up quickly.
+///
+/// It is serialized as a plain list of specifications,
+/// which is sorted on load to heal saves where it was not.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(from = "Vec<CloudUserSpec>")]
+pub struct UserSpecRegistry(Vec<CloudUserSpec>);
+
+impl UserSpecRegistry {
+    /// An identifier higher than all existing ones.
+    ///
+    /// New users take their ID from
+    /// [`WorldState::add_user_spec`](crate::WorldState::add_user_spec),
+    /// which never reuses the ID of a user who left.
+    pub fn next_id(&self) -> u32 {
+        self.0.last().map_or(0, |spec| spec.id) + 1
+    }
+
+    /// Add a user specification in its place by ID,
+    /// replacing the one with the same ID if any.
+    pub fn insert(&mut self, spec: CloudUserSpec) {
+        match self.0.binary_search_by_key(&spec.id, |other| other.id) {
+            Ok(index) => self.0[index] = spec,
+            Err(index) => self.0.insert(index, spec),
+        }
+    }
+
+    /// The user specification with the given ID, if any.
+    pub fn get(&self, id: u32) -> Option<&CloudUserSpec> {
+        self.0
+            .binary_search_by_key(&id, |spec| spec.id)
+            .ok()
+            .map(|index| &self.0[index])
+    }
+
+    /// Remove the user specification with the given ID,
+    /// returning it if there was one.
+    pub fn remove(&mut self, id: u32) -> Option<CloudUserSpec> {
+        self.0
+            .binary_search_by_key(&id, |spec| spec.id)
+            .ok()
+            .map(|index| self.0.remove(index))
+    }
+
+    /// Keep only the user specifications for which the predicate holds.
+    pub fn retain(&mut self, f: impl FnMut(&CloudUserSpec) -> bool) {
+        self.0.retain(f);
+    }
+
+    /// Remove all user specifications.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl Deref for UserSpecRegistry {
+    type Target = [CloudUserSpec];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<CloudUserSpec>> for UserSpecRegistry {
+    fn from(mut specs: Vec<CloudUserSpec>) -> Self {
+        specs.sort_by_key(|spec| spec.id);
+        specs.dedup_by_key(|spec| spec.id);
+        UserSpecRegistry(specs)
+    }
+}
+
+impl Serialize for UserSpecRegistry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'a> IntoIterator for &'a UserSpecRegistry {
+    type Item = &'a CloudUserSpec;
+    type IntoIter = std::slice::Iter<'a, CloudUserSpec>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// The non-live behavioral specification for a cloud client.
+///
+/// It is different from CloudUserSpec because it is never evil
+/// and trial time is specified as a duration instead of a timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudClientSpec {
+    /// the service this client will be using
+    pub service: ServiceKind,
+    /// the time duration in which the user does not have to pay per request
+    /// (set 0 to always pay)
+    pub trial_duration: u32,
+}
+
+impl CloudClientSpec {
+    /// a client of the given service
+    /// with a trial period of N seconds of game time
+    pub const fn trial_secs(service: ServiceKind, secs: u32) -> Self {
+        CloudClientSpec {
+            service,
+            trial_duration: secs * TIME_UNITS_PER_SECOND as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{Rng, SeedableRng};
+
+    use super::{CloudUserSpec, UserKind, UserSpecRegistry};
+    use crate::ServiceKind;
+
+    #[test]
+    fn test_bad_flag_migrates_to_kind() {
+        let spec: CloudUserSpec =
+            serde_json::from_str(r#"{"id":3,"service":"Super","trial_time":0,"bad":true}"#)
+                .unwrap();
+        assert_eq!(spec.kind, UserKind::Attacker);
+        assert!(spec.is_bad());
+
+        let spec: CloudUserSpec =
+            serde_json::from_str(r#"{"id":4,"service":"Base","trial_time":100,"bad":false}"#)
+                .unwrap();
+        assert_eq!(spec.kind, UserKind::Customer);
+
+        // new saves round trip
+        let spec = CloudUserSpec {
+            id: 5,
+            service: ServiceKind::Epic,
+            trial_time: 0,
+            kind: UserKind::Researcher,
+        };
+        let json = serde_json::to_string(&spec).unwrap();
+        assert!(!json.contains("bad"));
+        assert_eq!(serde_json::from_str::<CloudUserSpec>(&json).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_researchers_never_pay() {
+        let spec = CloudUserSpec {
+            id: 1,
+            service: ServiceKind::Base,
+            trial_time: 0,
+            kind: UserKind::Researcher,
+        };
+        assert!(!spec.is_paying(0));
+        assert!(!spec.is_paying(u64::MAX));
+    }
+
+    #[test]
+    fn test_registry_heals_unsorted_saves() {
+        let json = concat!(
+            r#"[{"id":3,"service":"Super","trial_time":0,"kind":"Customer"},"#,
+            r#"{"id":1,"service":"Base","trial_time":0,"kind":"Customer"},"#,
+            r#"{"id":2,"service":"Base","trial_time":0,"bad":true},"#,
+            r#"{"id":1,"service":"Base","trial_time":0,"kind":"Customer"}]"#,
+        );
+        let registry: UserSpecRegistry = serde_json::from_str(json).unwrap();
+        let ids: Vec<_> = registry.iter().map(|spec| spec.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert!(registry.get(2).unwrap().is_bad());
+        assert_eq!(registry.get(3).unwrap().service, ServiceKind::Super);
+        assert_eq!(registry.next_id(), 4);
+
+        // saved as the plain list
+        let json = serde_json::to_string(&registry).unwrap();
+        assert!(json.starts_with(r#"[{"id":1,"#), "{json}");
+        assert_eq!(
+            serde_json::from_str::<UserSpecRegistry>(&json).unwrap(),
+            registry
+        );
+    }
+
+    #[test]
+    fn test_registry_random_changes() {
+        for seed in 0..20 {
+            let mut rng = rand_pcg::Pcg32::seed_from_u64(seed);
+            let mut registry = UserSpecRegistry::default();
+            let mut expected = BTreeMap::new();
+            for _ in 0..200 {
+                match rng.gen_range(0..4) {
+                    0 | 1 => {
+                        let spec = CloudUserSpec {
+                            id: registry.next_id(),
+                            service: ServiceKind::Base,
+                            trial_time: 0,
+                            kind: UserKind::Customer,
+                        };
+                        registry.insert(spec.clone());
+                        assert!(expected.insert(spec.id, spec).is_none());
+                    }
+                    2 => {
+                        let id = rng.gen_range(1..registry.next_id() + 5);
+                        assert_eq!(registry.remove(id), expected.remove(&id));
+                    }
+                    _ => {
+                        // such as a customer coming back
+                        let spec = CloudUserSpec {
+                            id: rng.gen_range(1..registry.next_id() + 5),
+                            service: ServiceKind::Epic,
+                            trial_time: 100,
+                            kind: UserKind::Customer,
+                        };
+                        registry.insert(spec.clone());
+                        expected.insert(spec.id, spec);
+                    }
+                }
+                assert!(registry.windows(2).all(|pair| pair[0].id < pair[1].id));
+                for id in 0..registry.next_id() + 5 {
+                    assert_eq!(registry.get(id), expected.get(&id), "seed {seed}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Module for the op button combo meter,
+//! which rewards the player for clicking steadily.
+//!
+//! Each service keeps its own combo.
+//! While the player clicks at least [`COMBO_MIN_CLICKS`] times
+//! within [`COMBO_WINDOW`], the combo level builds up,
+//! and it drains once clicking slows down.
+//! The level translates into a multiplier on the ops per click
+//! with diminishing returns, up to [`MAX_COMBO_MULTIPLIER`],
+//! so that clicking faster than needed gains nothing.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Time;
+
+/// the sliding window in which clicks are counted (2 seconds)
+pub const COMBO_WINDOW: Time = 20_000;
+
+/// the number of clicks within the window
+/// needed to build up the combo
+pub const COMBO_MIN_CLICKS: usize = 8;
+
+/// the time of sustained clicking
+/// for the combo to go from empty to full (10 seconds)
+pub const COMBO_BUILDUP: Time = 100_000;
+
+/// the time for a full combo to drain
+/// once clicking slows down (3 seconds)
+pub const COMBO_DECAY: Time = 30_000;
+
+/// the highest multiplier on the ops per click
+pub const MAX_COMBO_MULTIPLIER: f32 = 3.;
+
+/// The combo state of a service.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Combo {
+    /// the times of the latest clicks,
+    /// up to [`COMBO_MIN_CLICKS`] of them
+    clicks: VecDeque<Time>,
+    /// how full the combo is, between 0 and 1
+    level: f32,
+    /// the time of the last update
+    updated_at: Time,
+}
+
+impl Combo {
+    /// Register a player click at the given time.
+    pub fn record_click(&mut self, time: Time) {
+        if self.clicks.len() == COMBO_MIN_CLICKS {
+            self.clicks.pop_front();
+        }
+        self.clicks.push_back(time);
+    }
+
+    /// Build up or drain the combo up to the given time.
+    pub fn update(&mut self, time: Time) {
+        let elapsed = time.saturating_sub(self.updated_at) as f32;
+        self.updated_at = time;
+        let sustained =
+            self.clicks.len() == COMBO_MIN_CLICKS && self.clicks[0] + COMBO_WINDOW > time;
+        if sustained {
+            self.level += elapsed / COMBO_BUILDUP as f32;
+        } else {
+            self.level -= elapsed / COMBO_DECAY as f32;
+        }
+        self.level = self.level.clamp(0., 1.);
+    }
+
+    /// How full the combo is, between 0 and 1.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// The multiplier on the ops per click,
+    /// between 1 and [`MAX_COMBO_MULTIPLIER`].
+    pub fn multiplier(&self) -> f32 {
+        (1. + (MAX_COMBO_MULTIPLIER - 1.) * self.level.sqrt()).min(MAX_COMBO_MULTIPLIER)
+    }
+
+    /// The ops generated by a click of the given amount.
+    pub fn apply(&self, amount: u32) -> u32 {
+        (amount as f32 * self.multiplier()).round() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Combo, COMBO_BUILDUP, COMBO_DECAY, COMBO_MIN_CLICKS, COMBO_WINDOW, MAX_COMBO_MULTIPLIER,
+    };
+    use crate::Time;
+
+    /// click 5 times per second until the given time,
+    /// returning the time of the last click
+    fn click_until(combo: &mut Combo, from: Time, to: Time) -> Time {
+        let mut time = from;
+        while time < to {
+            time += 2_000;
+            combo.record_click(time);
+            combo.update(time);
+        }
+        time
+    }
+
+    #[test]
+    fn test_combo_builds_up() {
+        let mut combo = Combo::default();
+        assert_eq!(combo.multiplier(), 1.);
+        assert_eq!(combo.apply(4), 4);
+
+        // a slow click does not build anything
+        combo.record_click(0);
+        combo.update(10_000);
+        assert_eq!(combo.level(), 0.);
+
+        click_until(&mut combo, 10_000, 10_000 + COMBO_BUILDUP / 2);
+        let half = combo.multiplier();
+        assert!(half > 1. && half < MAX_COMBO_MULTIPLIER, "{half}");
+        // diminishing returns: half the buildup gives more than half the bonus
+        assert!(half > 2., "{half}");
+
+        click_until(
+            &mut combo,
+            10_000 + COMBO_BUILDUP / 2,
+            30_000 + COMBO_BUILDUP,
+        );
+        assert_eq!(combo.level(), 1.);
+        assert_eq!(combo.apply(4), 12);
+    }
+
+    #[test]
+    fn test_combo_capped() {
+        let mut combo = Combo::default();
+        // an autoclicker clicking 100 times per second for a minute
+        let mut time = 0;
+        while time < 600_000 {
+            time += 100;
+            combo.record_click(time);
+            combo.update(time);
+            assert!(combo.multiplier() <= MAX_COMBO_MULTIPLIER);
+        }
+        assert_eq!(combo.multiplier(), MAX_COMBO_MULTIPLIER);
+        assert_eq!(combo.apply(1), 3);
+    }
+
+    #[test]
+    fn test_combo_decays() {
+        let mut combo = Combo::default();
+        let last_click = click_until(&mut combo, 0, COMBO_BUILDUP * 2);
+        assert_eq!(combo.level(), 1.);
+
+        // the combo holds while enough of the latest clicks are in the window
+        combo.update(last_click + 5_000);
+        assert_eq!(combo.level(), 1.);
+
+        // then drains over the decay time,
+        // starting when the oldest of the latest clicks leaves the window
+        let stopped = last_click - (COMBO_MIN_CLICKS as Time - 1) * 2_000 + COMBO_WINDOW;
+        combo.update(stopped - 1);
+        assert_eq!(combo.level(), 1.);
+        combo.update(stopped + 1_000);
+        assert!(combo.level() < 1.);
+        combo.update(stopped + COMBO_DECAY / 2);
+        assert!(combo.level() > 0.);
+        combo.update(stopped + COMBO_DECAY);
+        assert_eq!(combo.level(), 0.);
+        assert_eq!(combo.multiplier(), 1.);
+    }
+}
@@ -0,0 +1,187 @@
+//! Module for the tunable game balance parameters.
+//!
+//! The engine reads these through a [`GameConfig`]
+//! instead of the constants in the engine module,
+//! so that difficulty levels can change them without recompiling.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Memory, Money};
+
+use super::{
+    engine::{
+        BARE_NODE_COST, CPU_LEVELS, DAILY_DEMAND_AMPLITUDE, DAY_LENGTH, DEMAND_DOS_THRESHOLD,
+        ELECTRICITY_BILL_PERIOD, ELECTRICITY_COST_LEVELS, GAME_SAVE_PERIOD, INCREASE_DEMAND_PERIOD,
+        RAM_LEVELS, REQUEST_TIMEOUT, TIMEOUT_CLEANUP_PERIOD, UPGRADED_NODE_COST,
+        UPGRADED_RACK_COST, WAITING_QUEUE_MIN, WAITING_QUEUE_PER_NODE,
+    },
+    state::RoutingLevel,
+};
+
+/// The difficulty level of a game,
+/// chosen when starting a new game.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// cheaper hardware and faster demand growth
+    Easy,
+    /// the game as originally balanced
+    #[default]
+    Normal,
+    /// pricier hardware and slower demand growth
+    Hard,
+}
+
+impl Difficulty {
+    /// All difficulty levels, from easiest to hardest.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// The multiplier applied to hardware and electricity costs.
+    pub fn cost_multiplier(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// The multiplier applied to the periodic increase in demand.
+    pub fn demand_growth_multiplier(self) -> f64 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.75,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Easy => write!(f, "Easy"),
+            Self::Normal => write!(f, "Normal"),
+            Self::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+/// The tunable subset of the game's balance constants.
+///
+/// Not serialized:
+/// it is rebuilt from the difficulty recorded in the save.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    /// the difficulty this configuration was built for
+    pub difficulty: Difficulty,
+    /// all levels of CPU upgrades (see [`CPU_LEVELS`])
+    pub cpu_levels: [(u32, u32, Money); 11],
+    /// all levels of RAM upgrades (see [`RAM_LEVELS`])
+    pub ram_levels: [(Memory, Money); 11],
+    /// the cost of a bare node
+    pub bare_node_cost: Money,
+    /// the cost of a fully upgraded node
+    pub upgraded_node_cost: Money,
+    /// the cost of a fully upgraded rack
+    pub upgraded_rack_cost: Money,
+    /// the electricity cost per Wattever at each cost level
+    pub electricity_cost_levels: [Money; 7],
+    /// the threshold of base demand at which DoS attacks will emerge
+    pub demand_dos_threshold: f64,
+    /// multiplier applied to the periodic increase in demand
+    pub demand_growth: f64,
+    /// time period after which base demand increases a small bit
+    pub increase_demand_period: u64,
+    /// the length of a day in the daily demand cycle
+    pub day_length: u64,
+    /// how far demand swings around its base value over a day
+    pub daily_amplitude: f32,
+    /// time period after which the user is given electricity bills to pay
+    pub electricity_bill_period: u64,
+    /// time period after which the game should be saved
+    pub game_save_period: u64,
+    /// time period after which very old request events are cleaned up
+    pub timeout_cleanup_period: u64,
+    /// the time threshold for a request to be considered timed out
+    pub request_timeout: u64,
+    /// the least number of requests which can wait to be routed
+    pub waiting_queue_min: usize,
+    /// the number of requests which can wait to be routed per node,
+    /// by routing level (see [`WAITING_QUEUE_PER_NODE`])
+    pub waiting_queue_per_node: [usize; RoutingLevel::ALL.len()],
+}
+
+impl GameConfig {
+    /// Create the game configuration for the given difficulty.
+    pub fn new(difficulty: Difficulty) -> Self {
+        let m = difficulty.cost_multiplier();
+        Self {
+            difficulty,
+            cpu_levels: CPU_LEVELS.map(|(cores, speed, cost)| (cores, speed, cost * m)),
+            ram_levels: RAM_LEVELS.map(|(ram, cost)| (ram, cost * m)),
+            bare_node_cost: BARE_NODE_COST * m,
+            upgraded_node_cost: UPGRADED_NODE_COST * m,
+            upgraded_rack_cost: UPGRADED_RACK_COST * m,
+            electricity_cost_levels: ELECTRICITY_COST_LEVELS.map(|cost| cost * m),
+            demand_dos_threshold: DEMAND_DOS_THRESHOLD,
+            demand_growth: difficulty.demand_growth_multiplier(),
+            increase_demand_period: INCREASE_DEMAND_PERIOD,
+            day_length: DAY_LENGTH,
+            daily_amplitude: DAILY_DEMAND_AMPLITUDE,
+            electricity_bill_period: ELECTRICITY_BILL_PERIOD,
+            game_save_period: GAME_SAVE_PERIOD,
+            timeout_cleanup_period: TIMEOUT_CLEANUP_PERIOD,
+            request_timeout: REQUEST_TIMEOUT,
+            waiting_queue_min: WAITING_QUEUE_MIN,
+            waiting_queue_per_node: WAITING_QUEUE_PER_NODE,
+        }
+    }
+
+    /// The number of requests which can wait to be routed
+    /// with the given number of nodes and routing level,
+    /// beyond which new requests are dropped.
+    pub fn waiting_queue_capacity(&self, node_count: usize, routing_level: RoutingLevel) -> usize {
+        let per_node = self.waiting_queue_per_node[routing_level as usize];
+        (node_count * per_node).max(self.waiting_queue_min)
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::new(Difficulty::Normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Difficulty, GameConfig};
+    use crate::central::{
+        engine::{BARE_NODE_COST, CPU_LEVELS, ELECTRICITY_COST_LEVELS, WAITING_QUEUE_MIN},
+        state::RoutingLevel,
+    };
+
+    #[test]
+    fn test_normal_config_matches_constants() {
+        let config = GameConfig::default();
+        assert_eq!(config.difficulty, Difficulty::Normal);
+        assert_eq!(config.cpu_levels, CPU_LEVELS);
+        assert_eq!(config.bare_node_cost, BARE_NODE_COST);
+        assert_eq!(config.electricity_cost_levels, ELECTRICITY_COST_LEVELS);
+        assert_eq!(config.demand_growth, 1.);
+    }
+
+    #[test]
+    fn test_waiting_queue_capacity() {
+        let config = GameConfig::default();
+        // a few nodes get the least capacity
+        assert_eq!(
+            config.waiting_queue_capacity(2, RoutingLevel::MainNode),
+            WAITING_QUEUE_MIN
+        );
+        // more nodes buffer more, more so with distributed routing
+        let main = config.waiting_queue_capacity(40, RoutingLevel::MainNode);
+        let distributed = config.waiting_queue_capacity(40, RoutingLevel::Distributed);
+        assert!(main > WAITING_QUEUE_MIN);
+        assert!(distributed > main);
+    }
+}
@@ -0,0 +1,42 @@
+//! Warnings of the game engine.
+//!
+//! The engine logs through the [`log`] facade,
+//! which the front-end forwards to the browser console,
+//! so that it can also be run natively (e.g. in unit tests).
+
+/// the number of latest engine warnings kept for the developer overlay
+#[cfg(feature = "debug-tools")]
+pub const MAX_WARNINGS: usize = 20;
+
+/// Warnings of the game engine,
+/// logged as warnings
+/// and, with the debug tools enabled,
+/// kept for the developer overlay.
+#[derive(Debug, Default)]
+pub(crate) struct Warnings {
+    /// the latest warnings and when they happened, oldest first
+    #[cfg(feature = "debug-tools")]
+    latest: std::collections::VecDeque<(crate::Time, String)>,
+}
+
+impl Warnings {
+    /// Log a warning which happened at the given time.
+    pub fn push(&mut self, time: crate::Time, message: String) {
+        log::warn!("{message}");
+        #[cfg(feature = "debug-tools")]
+        {
+            if self.latest.len() == MAX_WARNINGS {
+                self.latest.pop_front();
+            }
+            self.latest.push_back((time, message));
+        }
+        #[cfg(not(feature = "debug-tools"))]
+        let _ = time;
+    }
+
+    /// The latest warnings, oldest first.
+    #[cfg(feature = "debug-tools")]
+    pub fn latest(&self) -> impl Iterator<Item = &(crate::Time, String)> {
+        self.latest.iter()
+    }
+}
@@ -0,0 +1,242 @@
+//! Module for the daily challenge,
+//! a short game which is the same for every player on the same day.
+//!
+//! The seed of the random number generator
+//! and a set of modifiers to the usual game
+//! are derived from the current date in UTC (see [`DailySpec::for_date`]),
+//! so that two players on the same date taking the same actions
+//! get identical outcomes.
+//! The challenge ends after [`DAILY_CHALLENGE_DURATION`] of game time,
+//! when the game freezes and the score is final.
+//!
+//! The derivation must never change,
+//! or scores shared on the same day would no longer be comparable.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, ServiceKind, Time, TIME_UNITS_PER_MINUTE};
+
+/// the game time which a daily challenge lasts (30 minutes)
+pub const DAILY_CHALLENGE_DURATION: Time = 30 * TIME_UNITS_PER_MINUTE;
+
+/// the milliseconds in a day
+const MILLIS_PER_DAY: u64 = 86_400_000;
+
+/// mixed into the day number,
+/// so that daily seeds do not follow the seeds used elsewhere
+const DAILY_SEED_SALT: u64 = 0x10C1_0DC4_A3B1_0000;
+
+/// A calendar date in UTC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DailyDate {
+    pub year: i32,
+    /// the month, from 1 to 12
+    pub month: u8,
+    /// the day of the month, from 1 to 31
+    pub day: u8,
+}
+
+impl DailyDate {
+    /// The UTC date at the given time,
+    /// in milliseconds since the Unix epoch.
+    pub fn from_unix_millis(millis: u64) -> Self {
+        // days to civil date, after Howard Hinnant's algorithm
+        let days = (millis / MILLIS_PER_DAY) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (year_of_era + era * 400) as i32 + i32::from(month <= 2);
+        Self { year, month, day }
+    }
+
+    /// The number of days since the Unix epoch.
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+impl fmt::Display for DailyDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The changes to the usual game in a daily challenge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DailyModifiers {
+    /// the funds at the start of the game
+    pub starting_funds: Money,
+    /// the game time at which attackers arrive,
+    /// whatever the demand
+    pub dos_wave_at: Time,
+    /// the lowest price allowed for each service,
+    /// as a percentage of its default price
+    pub price_floor_percent: u8,
+}
+
+impl DailyModifiers {
+    /// The lowest price allowed for the given service.
+    pub fn price_floor(&self, kind: ServiceKind) -> Money {
+        let default_price = kind.tier().default_price.to_millicents();
+        Money::millicents(default_price * i64::from(self.price_floor_percent) / 100)
+    }
+}
+
+/// How the daily challenge of a date is made.
+pub struct DailySpec;
+
+impl DailySpec {
+    /// The seed of the random number generator
+    /// and the modifiers of the daily challenge on the given date.
+    pub fn for_date(date: DailyDate) -> (u64, DailyModifiers) {
+        let seed = splitmix64(date.days_since_epoch() as u64 ^ DAILY_SEED_SALT);
+        let mut bits = seed;
+        let mut pick = |choices: u64| {
+            bits = splitmix64(bits);
+            bits % choices
+        };
+        let modifiers = DailyModifiers {
+            // $10 to $50
+            starting_funds: Money::dollars(10 + 5 * pick(9) as i64),
+            // 3 to 8 minutes in
+            dos_wave_at: TIME_UNITS_PER_MINUTE * (3 + pick(6)),
+            // 50% to 90% of the default price
+            price_floor_percent: 50 + 10 * pick(5) as u8,
+        };
+        (seed, modifiers)
+    }
+}
+
+/// The daily challenge being played.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DailyChallenge {
+    /// the date of the challenge
+    pub date: DailyDate,
+    /// the seed of the random number generator
+    pub seed: u64,
+    /// the changes to the usual game
+    pub modifiers: DailyModifiers,
+}
+
+impl DailyChallenge {
+    /// The daily challenge of the given date.
+    pub fn for_date(date: DailyDate) -> Self {
+        let (seed, modifiers) = DailySpec::for_date(date);
+        Self {
+            date,
+            seed,
+            modifiers,
+        }
+    }
+
+    /// The game time at which the challenge ends.
+    pub fn ends_at(&self) -> Time {
+        DAILY_CHALLENGE_DURATION
+    }
+}
+
+/// The SplitMix64 mixing function,
+/// which turns consecutive inputs into unrelated outputs.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DailyDate, DailyModifiers, DailySpec};
+    use crate::{Money, ServiceKind, TIME_UNITS_PER_MINUTE};
+
+    fn date(year: i32, month: u8, day: u8) -> DailyDate {
+        DailyDate { year, month, day }
+    }
+
+    #[test]
+    fn test_date_from_unix_millis() {
+        assert_eq!(DailyDate::from_unix_millis(0), date(1970, 1, 1));
+        // 2024-02-29T23:59:59.999Z
+        assert_eq!(
+            DailyDate::from_unix_millis(1_709_251_199_999),
+            date(2024, 2, 29)
+        );
+        // 2026-10-16T12:00:00Z
+        assert_eq!(
+            DailyDate::from_unix_millis(1_792_152_000_000),
+            date(2026, 10, 16)
+        );
+        for millis in [0, 951_782_400_000, 1_709_251_200_000, 4_102_444_800_000] {
+            let date = DailyDate::from_unix_millis(millis);
+            assert_eq!(date.days_since_epoch() as u64, millis / 86_400_000);
+        }
+        assert_eq!(date(2026, 1, 5).to_string(), "2026-01-05");
+    }
+
+    /// Pinned so that the daily challenges of past days stay the same:
+    /// the derivation must not change.
+    #[test]
+    fn test_daily_spec_pinned_dates() {
+        let cases = [
+            (date(2024, 2, 29), 5_381_276_266_354_537_223, 40, 6, 80),
+            (date(2026, 1, 1), 18_319_023_521_298_349_122, 30, 3, 90),
+            (date(2026, 10, 16), 4_409_150_689_241_129_449, 30, 6, 50),
+        ];
+        for (date, seed, dollars, minutes, percent) in cases {
+            let (actual_seed, modifiers) = DailySpec::for_date(date);
+            assert_eq!(
+                (actual_seed, modifiers),
+                (
+                    seed,
+                    DailyModifiers {
+                        starting_funds: Money::dollars(dollars),
+                        dos_wave_at: minutes * TIME_UNITS_PER_MINUTE,
+                        price_floor_percent: percent,
+                    }
+                ),
+                "{date}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_daily_spec_varies_by_date() {
+        let (seed, _) = DailySpec::for_date(date(2026, 10, 16));
+        assert_eq!(DailySpec::for_date(date(2026, 10, 16)).0, seed);
+        assert_ne!(DailySpec::for_date(date(2026, 10, 17)).0, seed);
+        assert_ne!(DailySpec::for_date(date(2025, 10, 16)).0, seed);
+    }
+
+    #[test]
+    fn test_price_floor() {
+        let modifiers = DailyModifiers {
+            starting_funds: Money::dollars(10),
+            dos_wave_at: 0,
+            price_floor_percent: 60,
+        };
+        // the base service defaults to 50 millicents
+        assert_eq!(
+            modifiers.price_floor(ServiceKind::Base),
+            Money::millicents(30)
+        );
+        assert_eq!(
+            modifiers.price_floor(ServiceKind::Awesome),
+            Money::cents(60)
+        );
+    }
+}
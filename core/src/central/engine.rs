@@ -0,0 +1,9426 @@
+//! Module for the active entity in the game,
+//! which takes the current state of the program
+//! and processes it over time.
+
+use std::{collections::VecDeque, sync::Once};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display::Separating, expected_requests_per_second, t, ActionError, CloudUserSpec, Memory,
+    Money, Ops, PlayerAction, SampleGenerator, ServiceKind, UserKind, WorldState,
+    TIME_UNITS_PER_MINUTE, TIME_UNITS_PER_SECOND,
+};
+
+use super::{
+    audio_event::AudioEvent,
+    bandwidth::{bandwidth_capacity, BandwidthMeter},
+    cards::{
+        all::{card_by_id, find_misordered_cards, ALL_CARDS, ID_GAME_OVER},
+        CardEffect, CardSpec,
+    },
+    clicks::ClickStats,
+    combo::Combo,
+    config::GameConfig,
+    console,
+    eta::{eta_for, ResourceRates},
+    exchange::Exchange,
+    histogram::ProcessingTimes,
+    insurance::InsurancePolicy,
+    metrics::{DropCause, MetricsSnapshot, PlayerOps},
+    narrative::{eligible_events, NARRATIVE_EVENT_CHANCE, NARRATIVE_ROLL_PERIOD, SAFE_CHOICE},
+    notification::NotificationLog,
+    queue::{RequestEvent, RequestEventQueue, RequestEventStage, Time},
+    report::{PeriodReport, PeriodSnapshot, MAX_PERIOD_REPORTS},
+    score::ScoreBreakdown,
+    staff::{self, StaffMember, MAX_STAFF, STAFF_WORK_PERIOD},
+    state::{
+        sanitize_company_name, Loan, RoutingLevel, ServiceInfo, ServiceLevelAgreement,
+        TrafficClass, UsedCard, DOS_IMMUNITY_PERIOD, HARDWARE_PANEL_LOAD_BARS,
+        HARDWARE_PANEL_UPGRADES, MAX_PINNED_CARDS,
+    },
+    streak::{rebate, StreakReward, STREAK_DEMAND_BOOST_DURATION},
+    tariff::{Tariff, TARIFF_SWITCH_FEE},
+};
+
+/// all levels of CPU upgrades
+/// (count, base speed, and cost),
+/// where the first value is the initial level
+pub static CPU_LEVELS: [(u32, u32, Money); 11] = [
+    (1, 2, Money::dollars(0)),
+    (2, 2, Money::dollars(60)),
+    (3, 2, Money::dollars(160)),
+    (4, 3, Money::dollars(360)),
+    (6, 3, Money::dollars(850)),
+    (8, 4, Money::dollars(1_980)),
+    (16, 4, Money::dollars(3_000)),
+    (24, 5, Money::dollars(5_400)),
+    (32, 6, Money::dollars(8_200)),
+    (48, 7, Money::dollars(14_000)),
+    (64, 8, Money::dollars(25_000)),
+];
+
+/// all levels of RAM that can be purchased and their base cost,
+/// where the first value is the initial level
+pub static RAM_LEVELS: [(Memory, Money); 11] = [
+    (Memory::mb(256), Money::dollars(0)),
+    (Memory::mb(512), Money::dollars(40)),
+    (Memory::gb(1), Money::dollars(60)),
+    (Memory::gb(2), Money::dollars(100)),
+    (Memory::gb(4), Money::dollars(180)),
+    (Memory::gb(8), Money::dollars(320)),
+    (Memory::gb(16), Money::dollars(660)),
+    (Memory::gb(24), Money::dollars(900)),
+    (Memory::gb(32), Money::dollars(1_200)),
+    (Memory::gb(48), Money::dollars(2_000)),
+    (Memory::gb(64), Money::dollars(3_600)),
+];
+
+/// The number of nodes that fit in a rack
+pub const RACK_CAPACITY: u32 = 4;
+
+/// The number of nodes that fit in a data center
+pub const DATACENTER_CAPACITY: u32 = 32;
+
+/// The cost of a bare node
+pub const BARE_NODE_COST: Money = Money::dollars(2_000);
+
+/// The cost of a fully upgraded node
+pub const UPGRADED_NODE_COST: Money = BARE_NODE_COST
+    .plus(Money::dollars(59_000))
+    .plus(Money::dollars(9_000));
+
+/// The cost of a fully upgraded rack
+pub const UPGRADED_RACK_COST: Money = UPGRADED_NODE_COST
+    .plus(UPGRADED_NODE_COST)
+    .plus(UPGRADED_NODE_COST)
+    .plus(UPGRADED_NODE_COST);
+
+/// The total cost of the CPU upgrades of a node
+/// from the initial level up to the given level,
+/// according to the given table of CPU levels.
+pub fn cumulative_cpu_cost(cpu_levels: &[(u32, u32, Money)], level: u8) -> Money {
+    cpu_levels
+        .iter()
+        .take(level as usize + 1)
+        .map(|&(_, _, cost)| cost)
+        .sum()
+}
+
+/// The total cost of the RAM upgrades of a node
+/// from the initial level up to the given level,
+/// according to the given table of RAM levels.
+pub fn cumulative_ram_cost(ram_levels: &[(Memory, Money)], level: u8) -> Money {
+    ram_levels
+        .iter()
+        .take(level as usize + 1)
+        .map(|&(_, cost)| cost)
+        .sum()
+}
+
+/// All levels of caching,
+/// namely the memory reserve multiplier (0)
+/// and the cache hit rate (1)
+pub static CACHE_LEVELS: [(f32, f32); 5] =
+    [(1., 0.), (4., 0.25), (16., 0.5), (18., 0.75), (20., 0.875)];
+
+/// The memory reserve multiplier of the cache
+/// at the given level when using only a fraction of it,
+/// so that a fraction of 0 reserves no memory for caching at all.
+pub fn cache_memory_factor(cache_level: u8, cache_fraction: f32) -> f32 {
+    1. + (CACHE_LEVELS[cache_level as usize].0 - 1.) * cache_fraction
+}
+
+/// The cache hit rate at the given level
+/// when using only a fraction of the cache.
+pub fn cache_hit_rate(cache_level: u8, cache_fraction: f32) -> f32 {
+    CACHE_LEVELS[cache_level as usize].1 * cache_fraction
+}
+
+/// How many times faster a request is processed on a cache hit
+pub const CACHE_HIT_SPEEDUP: u32 = 20;
+
+/// The expected speed-up of processing requests
+/// at the given cache level when using only a fraction of the cache,
+/// given its hit rate and [`CACHE_HIT_SPEEDUP`].
+pub fn expected_cache_speedup(cache_level: u8, cache_fraction: f32) -> f64 {
+    let hit_rate = cache_hit_rate(cache_level, cache_fraction) as f64;
+    1. / (1. - hit_rate + hit_rate / CACHE_HIT_SPEEDUP as f64)
+}
+
+/// Modifiers for the time to process a request and memory required,
+/// a number between 0 and 1,
+/// where 1 means full cost.
+pub static SOFTWARE_LEVELS: [(f64, f64); 5] = [
+    (1., 1.),
+    (0.9375, 0.96875),
+    (0.625, 0.75),
+    (0.3125, 0.625),
+    (0.0390625, 0.125),
+];
+
+/// The electricity cost in Wattever
+pub static ELECTRICITY_COST_LEVELS: [Money; 7] = [
+    // base cost
+    Money::cents(32),
+    // renegotiate
+    Money::cents(29),
+    // repair A/C
+    Money::cents(25),
+    // buy solar panels
+    Money::cents(18),
+    // commit to clean power plan
+    Money::cents(5),
+    // dedicated power plant
+    Money::dec_cents(5),
+    // free energy
+    Money::zero(),
+];
+
+/// the threshold of base demand at which DoS attacks will emerge
+pub static DEMAND_DOS_THRESHOLD: f64 = 2500.0;
+
+/// the services attacked once demand goes over
+/// [`DEMAND_DOS_THRESHOLD`]
+pub static DOS_TARGETS: [ServiceKind; 4] = [
+    ServiceKind::Base,
+    ServiceKind::Super,
+    ServiceKind::Epic,
+    ServiceKind::Awesome,
+];
+
+/// time period after which base demand increases a small bit
+pub static INCREASE_DEMAND_PERIOD: u64 = 150_000;
+
+/// time period after which the user is given electricity bills to pay
+pub const ELECTRICITY_BILL_PERIOD: u64 = 2_500_000;
+
+/// the length of a day in the daily demand cycle
+pub static DAY_LENGTH: u64 = 6_000_000;
+
+/// how far demand swings above and below its base value
+/// over the course of a day
+pub static DAILY_DEMAND_AMPLITUDE: f32 = 0.4;
+
+/// time period after which a major update is performed
+/// (also subtle but can do more expensive things)
+pub static MAJOR_UPDATE_PERIOD: u64 = 2_500;
+
+/// the longest time by which the first arrival of a user
+/// is staggered when bootstrapping (20 seconds)
+pub static BOOTSTRAP_MAX_INTERVAL: u64 = 200_000;
+
+/// the demand (in requests per second) from a single user
+/// above which arrivals are scheduled in advance when bootstrapping
+pub static BOOTSTRAP_PREWARM_DEMAND: f64 = 100.;
+
+/// the number of arrivals scheduled in advance when bootstrapping
+/// users with very high demand
+pub static BOOTSTRAP_PREWARM_ARRIVALS: u32 = 4;
+
+/// time period after which the game is automatically saved to local storage
+pub static GAME_SAVE_PERIOD: u64 = 360_000;
+
+/// time period after which the game will clean up very old request events
+pub static TIMEOUT_CLEANUP_PERIOD: u64 = 40_000;
+
+/// the time threshold for a request to be considered timed out
+pub static REQUEST_TIMEOUT: u64 = 300_000;
+
+/// the time that a node takes to restart,
+/// during which it cannot process any requests
+pub static NODE_RESTART_COOLDOWN: u64 = 50_000;
+
+/// the funds below which the player is considered insolvent;
+/// staying insolvent for a full bill period triggers a bailout offer
+pub static BANKRUPTCY_THRESHOLD: Money = Money::dollars(-50);
+
+/// the funds that the player is left with after accepting a bailout
+pub static BAILOUT_FUNDS: Money = Money::dollars(20);
+
+/// the interest added to a loan's balance on every bill period
+pub static LOAN_INTEREST_RATE: f64 = 0.05;
+
+/// the share of all earnings automatically used to repay a loan
+pub static LOAN_REPAYMENT_SHARE: f64 = 0.25;
+
+/// the most requests combined into one request set
+/// when demand is very high
+pub static MAX_REQUEST_BUNDLE: u32 = 250;
+
+/// the largest share of the memory of the biggest node
+/// which a request set may take,
+/// so that request sets still fit as demand grows
+pub static BUNDLE_MEMORY_SHARE: f32 = 0.25;
+
+/// the most request sets per second arriving from one user,
+/// above which request sets grow past their memory cap
+/// to keep the simulation going at a bearable pace
+pub static MAX_REQUEST_SET_RATE: f64 = 40_000.;
+
+/// the most request sets which a batch of player requests is split into,
+/// so that batching still happens when memory is scarce
+pub static MAX_PLAYER_REQUEST_SETS: u32 = 256;
+
+/// the estimated wait in the node queues
+/// above which customers start leaving a service,
+/// a quarter of [`REQUEST_TIMEOUT`]
+pub static QUEUE_PRESSURE_WAIT: u64 = REQUEST_TIMEOUT / 4;
+
+/// the share of a service's demand lost on every major update
+/// while under queue pressure
+pub static QUEUE_PRESSURE_DECAY: f64 = 0.002;
+
+/// the share of a service's demand won back on every major update
+/// once the queues clear
+pub static QUEUE_PRESSURE_RECOVERY: f64 = 0.005;
+
+/// the lowest demand multiplier which queue pressure can bring a service to
+pub static MIN_QUEUE_DEMAND_FACTOR: f64 = 0.5;
+
+/// the smoothed drop rate above which demand stops growing,
+/// as word gets around that the service cannot keep up
+pub static GROWTH_STALL_DROP_RATE: f32 = 0.5;
+
+/// the smoothed drop rate below which demand grows again
+pub static GROWTH_RESUME_DROP_RATE: f32 = 0.25;
+
+/// the least number of requests which can wait to be routed
+pub static WAITING_QUEUE_MIN: usize = 2_000;
+
+/// the number of requests which can wait to be routed per node,
+/// by routing level,
+/// as distributed routing buffers more
+pub static WAITING_QUEUE_PER_NODE: [usize; RoutingLevel::ALL.len()] = [200, 500, 500];
+
+/// the share of the waiting queue capacity
+/// below which the queue is no longer saturated
+pub static WAITING_QUEUE_CLEAR_SHARE: f32 = 0.5;
+
+/// the weight of the latest drop rate in the smoothed drop rate
+const DROP_RATE_SMOOTHING: f32 = 0.1;
+
+/// the share of a service's demand lost for every full minute of maintenance,
+/// as customers grow impatient
+pub static MAINTENANCE_DEMAND_DECAY: f64 = 0.005;
+
+/// the chance of each customer leaving a service
+/// per unit of price raise share
+/// (see [`CHURN_RAISE_THRESHOLD`](super::state::CHURN_RAISE_THRESHOLD))
+pub static CHURN_CHANCE_PER_RAISE: f64 = 0.5;
+
+/// the maximum chance of each customer leaving a service over a price raise
+pub static MAX_CHURN_CHANCE: f64 = 0.75;
+
+/// the time after customers left a service
+/// at which new customers come by if none are left
+pub const CHURN_COOLDOWN: Time = 2 * TIME_UNITS_PER_MINUTE;
+
+/// the chance of a bad request being extra heavy
+/// when there is no spam protection at all
+pub static HEAVY_BAD_REQUEST_CHANCE: f32 = 0.2;
+
+/// the time after a hardware upgrade
+/// during which it can be taken back for a full refund (10 seconds)
+pub const PURCHASE_UNDO_WINDOW: Time = 100_000;
+
+/// the time after a trial runs out
+/// during which it can still be extended (30 seconds)
+pub const TRIAL_EXTENSION_WINDOW: Time = 300_000;
+
+/// the time by which a trial is extended in one go (60 seconds)
+pub const TRIAL_EXTENSION: Time = TIME_UNITS_PER_MINUTE;
+
+/// the fee for extending a trial of the base service by [`TRIAL_EXTENSION`],
+/// which is 4 times higher for each service tier above it
+pub static TRIAL_EXTENSION_FEE: Money = Money::dollars(5);
+
+/// the most expired trials kept around for extending them
+const MAX_EXPIRED_TRIALS: usize = 4;
+
+/// A customer whose trial ran out recently
+/// (see [`PlayerAction::ExtendTrial`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiredTrial {
+    /// the customer's user specification as it was
+    pub spec: CloudUserSpec,
+    /// the time at which the trial ran out
+    pub expired_at: Time,
+    /// the ops fulfilled for the customer during the trial
+    pub ops: u64,
+}
+
+impl ExpiredTrial {
+    /// Whether the trial can still be extended at the given time.
+    pub fn is_extendable(&self, time: Time) -> bool {
+        time < self.expired_at + TRIAL_EXTENSION_WINDOW
+    }
+}
+
+/// The fee for extending a trial of the given service by some time.
+pub fn trial_extension_fee(service: ServiceKind, duration: Time) -> Money {
+    let tier_factor = 4_i64.pow(service.to_code() as u32);
+    let fee = TRIAL_EXTENSION_FEE.to_millicents() * tier_factor;
+    Money::millicents((fee as f64 * duration as f64 / TRIAL_EXTENSION as f64).round() as i64)
+}
+
+/// A node resource which can be upgraded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HardwareResource {
+    Cpu,
+    Ram,
+}
+
+/// A hardware upgrade which might still be taken back
+/// (see [`PlayerAction::UndoLastPurchase`]).
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardwarePurchase {
+    /// the ID of the upgraded node
+    pub node: u32,
+    /// the resource upgraded
+    pub resource: HardwareResource,
+    /// the level of the resource before the upgrade
+    pub previous_level: u8,
+    /// the money paid for the upgrade
+    pub cost: Money,
+    /// the time of the upgrade
+    pub time: Time,
+    /// whether the node finished processing any request since,
+    /// in which case the upgrade can no longer be taken back
+    pub node_used: bool,
+}
+
+impl HardwarePurchase {
+    /// Whether the upgrade can still be taken back at the given time.
+    pub fn is_undoable(&self, time: Time) -> bool {
+        !self.node_used && time < self.time + PURCHASE_UNDO_WINDOW
+    }
+}
+
+/// What the view needs from an engine running elsewhere,
+/// such as in a web worker (see [`simulation`](super::simulation)).
+///
+/// The engine which takes the mirror
+/// answers the view's questions as the mirrored one would,
+/// without processing any events itself.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineMirror {
+    drop_rate: f32,
+    failure_rate: f32,
+    cache_hit_rate: f32,
+    cache_speedup: f32,
+    routing_latency: f32,
+    drop_causes: [u64; DropCause::ALL.len()],
+    total_drop_causes: [u64; DropCause::ALL.len()],
+    bandwidth: BandwidthMeter,
+    log: NotificationLog,
+    processing_times: ProcessingTimes,
+    visible_cards: Vec<usize>,
+    upcoming_cards: Vec<usize>,
+    queue_pressure: [f32; ServiceKind::ALL.len()],
+    combos: [Combo; ServiceKind::ALL.len()],
+    clicks: ClickStats,
+    growth_stalled: bool,
+    queue_saturated: bool,
+    last_purchase: Option<HardwarePurchase>,
+    expired_trials: Vec<ExpiredTrial>,
+    requests_in_flight: u64,
+    waiting_len: usize,
+    accounting_anomalies: u64,
+    // taken from the mirrored engine,
+    // to be taken in turn by the owner of the engine taking the mirror
+    player_ops: [PlayerOps; ServiceKind::ALL.len()],
+    audio_events: Vec<AudioEvent>,
+    new_expired_trials: Vec<ExpiredTrial>,
+}
+
+impl EngineMirror {
+    /// Whether the mirror carries anything to be taken
+    /// by the owner of the engine taking it.
+    pub fn has_news(&self) -> bool {
+        self.player_ops.iter().any(|ops| !ops.is_empty())
+            || !self.audio_events.is_empty()
+            || !self.new_expired_trials.is_empty()
+    }
+}
+
+/// The main game engine, which processes the game state
+/// and produces new events.
+#[derive(Debug)]
+pub struct GameEngine {
+    /// the tunable game balance parameters
+    config: GameConfig,
+    /// the event queue
+    queue: RequestEventQueue,
+    /// the number generator
+    gen: SampleGenerator,
+    /// a waiting queue where requests are placed
+    /// when no node is available to process them
+    waiting_queue: VecDeque<WaitingRouteRequest>,
+    /// messages announced to the player
+    log: NotificationLog,
+    /// warnings about inconsistencies found while processing events
+    warnings: console::Warnings,
+    /// the number of times more memory was released from a node
+    /// than it had in use (see [`MemoryAccountingError`])
+    accounting_anomalies: u64,
+    /// whether the system is in powersave mode,
+    /// determined at the start of each update
+    powersave: bool,
+    /// player clicks received since the last update,
+    /// as the total amount of ops per service kind
+    pending_clicks: Vec<(ServiceKind, u32)>,
+
+    /// The number of requests recently fulfilled
+    recent_requests_fulfilled: u64,
+
+    /// The number of requests recently dropped
+    /// due to lack of resources
+    recent_requests_dropped: u64,
+
+    /// The number of requests recently dropped by cause
+    recent_drop_causes: [u64; DropCause::ALL.len()],
+
+    /// The number of bad requests recently fulfilled
+    recent_requests_failed: u64,
+
+    /// The number of requests recently looked up in the cache
+    recent_cache_lookups: u64,
+
+    /// The number of recent cache lookups which hit
+    recent_cache_hits: u64,
+
+    /// The processing time of the recent cache lookups
+    /// as it would have been without the cache
+    recent_uncached_time: u64,
+
+    /// The actual processing time of the recent cache lookups
+    recent_cached_time: u64,
+
+    /// The number of requests recently sent to a processing node
+    recent_routed: u64,
+
+    /// The time recently added to request processing
+    /// for the distance between routing and processing nodes
+    recent_routing_latency: u64,
+
+    /// The outcome of the player's ops per service kind
+    /// since they were last taken
+    player_ops: [PlayerOps; ServiceKind::ALL.len()],
+
+    /// The drop rate calculated since the last major update
+    pub drop_rate: f32,
+
+    /// The failure rate since the last major update
+    pub failure_rate: f32,
+
+    /// The share of requests served from the cache
+    /// in the period before the last major update
+    pub cache_hit_rate: f32,
+
+    /// How many times faster requests were processed thanks to the cache
+    /// in the period before the last major update
+    pub cache_speedup: f32,
+
+    /// The average time added to each request
+    /// for the distance between routing and processing nodes,
+    /// in the period before the last major update
+    pub routing_latency: f32,
+
+    /// The number of requests dropped by cause
+    /// in the period before the last major update
+    pub drop_causes: [u64; DropCause::ALL.len()],
+
+    /// The number of requests dropped by cause
+    /// since the engine started,
+    /// up to the last major update
+    total_drop_causes: [u64; DropCause::ALL.len()],
+
+    /// The network bandwidth taken by the requests served lately
+    bandwidth: BandwidthMeter,
+
+    /// Whether the game is due to be saved,
+    /// so that the owner of the world state can save it
+    save_requested: bool,
+
+    /// The sounds to play since they were last taken
+    audio_events: Vec<AudioEvent>,
+
+    /// The time since which each node has had nothing to do
+    /// (or 0 if it is not idle)
+    idle_since: Vec<Time>,
+
+    /// The positions in [`ALL_CARDS`] of the project cards
+    /// visible to the player, as of the last check
+    visible_cards: Vec<usize>,
+
+    /// The positions in [`ALL_CARDS`] of the project cards
+    /// yet to appear whose progress can be shown, as of the last check
+    upcoming_cards: Vec<usize>,
+
+    /// Whether the visible cards have been checked at least once
+    cards_checked: bool,
+
+    /// The positions in [`ALL_CARDS`] of the penalty cards
+    /// which appeared as of the last check, to be charged
+    penalty_cards: Vec<usize>,
+
+    /// The histograms of request processing times,
+    /// starting over on every software or caching upgrade
+    processing_times: ProcessingTimes,
+
+    /// The drop rate smoothed over recent major updates,
+    /// regardless of whether the player can see it
+    capacity_pressure: f32,
+
+    /// The estimated wait in the node queues of each service kind
+    /// as a share of [`QUEUE_PRESSURE_WAIT`],
+    /// as of the last major update
+    queue_pressure: [f32; ServiceKind::ALL.len()],
+
+    /// The demand multiplier of each service kind
+    /// from customers leaving slow services,
+    /// between [`MIN_QUEUE_DEMAND_FACTOR`] and 1
+    queue_demand_factor: [f64; ServiceKind::ALL.len()],
+
+    /// The most requests of each service kind
+    /// combined into one request set,
+    /// so that it fits in the memory of the nodes
+    /// (refreshed on every major update)
+    bundle_caps: [u32; ServiceKind::ALL.len()],
+
+    /// The op button combo of each service kind
+    combos: [Combo; ServiceKind::ALL.len()],
+
+    /// The statistics of the player's clicks across all services
+    clicks: ClickStats,
+
+    /// Whether demand growth is paused
+    /// because too many requests are being dropped
+    growth_stalled: bool,
+
+    /// Whether the waiting queue is full
+    /// and new requests to route are being dropped
+    queue_saturated: bool,
+
+    /// The latest hardware upgrade, if any
+    last_purchase: Option<HardwarePurchase>,
+
+    /// The customers whose trial ran out lately,
+    /// oldest first, up to [`MAX_EXPIRED_TRIALS`] of them
+    expired_trials: Vec<ExpiredTrial>,
+
+    /// The trials which ran out since the owner of the engine last checked
+    new_expired_trials: Vec<ExpiredTrial>,
+
+    /// The number of requests in flight in the mirrored engine,
+    /// if this engine follows one (see [`EngineMirror`])
+    mirrored_in_flight: Option<u64>,
+
+    /// The number of requests waiting to be routed in the mirrored engine,
+    /// if this engine follows one (see [`EngineMirror`])
+    mirrored_waiting_len: Option<usize>,
+}
+
+impl GameEngine {
+    pub fn new() -> Self {
+        Self::with_config(GameConfig::default())
+    }
+
+    /// Create a game engine with the given balance parameters.
+    pub fn with_config(config: GameConfig) -> Self {
+        Self::with_config_and_generator(config, SampleGenerator::new())
+    }
+
+    /// Create a game engine with the given balance parameters,
+    /// drawing random numbers from a generator with the given seed.
+    pub fn with_config_seeded(config: GameConfig, seed: u64) -> Self {
+        Self::with_config_and_generator(config, SampleGenerator::from_seed(seed))
+    }
+
+    fn with_config_and_generator(config: GameConfig, gen: SampleGenerator) -> Self {
+        // card lookups rely on the cards being sorted by id
+        static CHECK_CARD_ORDER: Once = Once::new();
+        CHECK_CARD_ORDER.call_once(|| {
+            let misordered = find_misordered_cards(ALL_CARDS);
+            debug_assert!(
+                misordered.is_none(),
+                "ALL_CARDS must be in id ascending order, found {:?}",
+                misordered
+            );
+        });
+
+        GameEngine {
+            config,
+            queue: RequestEventQueue::new(),
+            gen,
+            waiting_queue: VecDeque::new(),
+            log: NotificationLog::new(),
+            warnings: Default::default(),
+            accounting_anomalies: 0,
+            powersave: false,
+            pending_clicks: Vec::new(),
+            recent_requests_fulfilled: 0,
+            recent_requests_dropped: 0,
+            recent_drop_causes: Default::default(),
+            recent_requests_failed: 0,
+            recent_cache_lookups: 0,
+            recent_cache_hits: 0,
+            recent_uncached_time: 0,
+            recent_cached_time: 0,
+            recent_routed: 0,
+            recent_routing_latency: 0,
+            player_ops: Default::default(),
+            drop_rate: 0.,
+            failure_rate: 0.,
+            cache_hit_rate: 0.,
+            cache_speedup: 1.,
+            routing_latency: 0.,
+            drop_causes: Default::default(),
+            total_drop_causes: Default::default(),
+            bandwidth: BandwidthMeter::default(),
+            save_requested: false,
+            audio_events: Vec::new(),
+            idle_since: Vec::new(),
+            visible_cards: Vec::new(),
+            upcoming_cards: Vec::new(),
+            cards_checked: false,
+            penalty_cards: Vec::new(),
+            processing_times: ProcessingTimes::default(),
+            capacity_pressure: 0.,
+            queue_pressure: [0.; ServiceKind::ALL.len()],
+            queue_demand_factor: [1.; ServiceKind::ALL.len()],
+            bundle_caps: [MAX_REQUEST_BUNDLE; ServiceKind::ALL.len()],
+            growth_stalled: false,
+            queue_saturated: false,
+            combos: Default::default(),
+            clicks: ClickStats::default(),
+            last_purchase: None,
+            expired_trials: Vec::new(),
+            new_expired_trials: Vec::new(),
+            mirrored_in_flight: None,
+            mirrored_waiting_len: None,
+        }
+    }
+
+    /// Take what the view needs from this engine,
+    /// so that another engine can follow it (see [`apply_mirror`](Self::apply_mirror)).
+    ///
+    /// Like the `take_*` methods,
+    /// this takes the player ops, sounds and expired trials.
+    /// The save request is left to the owner of this engine.
+    pub fn mirror(&mut self, state: &WorldState) -> EngineMirror {
+        EngineMirror {
+            drop_rate: self.drop_rate,
+            failure_rate: self.failure_rate,
+            cache_hit_rate: self.cache_hit_rate,
+            cache_speedup: self.cache_speedup,
+            routing_latency: self.routing_latency,
+            drop_causes: self.drop_causes,
+            total_drop_causes: self.total_drop_causes,
+            bandwidth: self.bandwidth,
+            log: self.log.clone(),
+            processing_times: self.processing_times.clone(),
+            visible_cards: self.visible_cards.clone(),
+            upcoming_cards: self.upcoming_cards.clone(),
+            queue_pressure: self.queue_pressure,
+            combos: self.combos.clone(),
+            clicks: self.clicks.clone(),
+            growth_stalled: self.growth_stalled,
+            queue_saturated: self.queue_saturated,
+            last_purchase: self.last_purchase,
+            expired_trials: self.expired_trials.clone(),
+            requests_in_flight: self.requests_in_flight(state),
+            waiting_len: self.waiting_len(),
+            accounting_anomalies: self.accounting_anomalies,
+            player_ops: self.take_player_ops(),
+            audio_events: self.take_audio_events(),
+            new_expired_trials: self.take_expired_trials(),
+        }
+    }
+
+    /// Follow the engine which produced the given mirror,
+    /// replacing everything that the view reads from this engine.
+    ///
+    /// The player ops, sounds and expired trials in the mirror
+    /// are added to those waiting to be taken from this engine.
+    pub fn apply_mirror(&mut self, mirror: EngineMirror) {
+        self.drop_rate = mirror.drop_rate;
+        self.failure_rate = mirror.failure_rate;
+        self.cache_hit_rate = mirror.cache_hit_rate;
+        self.cache_speedup = mirror.cache_speedup;
+        self.routing_latency = mirror.routing_latency;
+        self.drop_causes = mirror.drop_causes;
+        self.total_drop_causes = mirror.total_drop_causes;
+        self.bandwidth = mirror.bandwidth;
+        self.log = mirror.log;
+        self.processing_times = mirror.processing_times;
+        self.visible_cards = mirror.visible_cards;
+        self.upcoming_cards = mirror.upcoming_cards;
+        self.cards_checked = true;
+        self.queue_pressure = mirror.queue_pressure;
+        self.combos = mirror.combos;
+        self.clicks = mirror.clicks;
+        self.growth_stalled = mirror.growth_stalled;
+        self.queue_saturated = mirror.queue_saturated;
+        self.last_purchase = mirror.last_purchase;
+        self.expired_trials = mirror.expired_trials;
+        self.mirrored_in_flight = Some(mirror.requests_in_flight);
+        self.mirrored_waiting_len = Some(mirror.waiting_len);
+        self.accounting_anomalies = mirror.accounting_anomalies;
+        for (ops, new) in self.player_ops.iter_mut().zip(mirror.player_ops) {
+            ops.fulfilled += new.fulfilled;
+            ops.dropped += new.dropped;
+        }
+        self.audio_events.extend(mirror.audio_events);
+        self.new_expired_trials.extend(mirror.new_expired_trials);
+    }
+
+    /// The latest hardware upgrade,
+    /// if it can still be taken back at the given time.
+    pub fn undoable_purchase(&self, time: Time) -> Option<&HardwarePurchase> {
+        self.last_purchase
+            .as_ref()
+            .filter(|purchase| purchase.is_undoable(time))
+    }
+
+    /// Take the outcome of the ops requested by the player
+    /// since the last call, indexed by service kind code,
+    /// so that the interface can tell whether clicks are being dropped.
+    pub fn take_player_ops(&mut self) -> [PlayerOps; ServiceKind::ALL.len()] {
+        std::mem::take(&mut self.player_ops)
+    }
+
+    /// The op button combo of the given service kind.
+    pub fn combo(&self, kind: ServiceKind) -> &Combo {
+        &self.combos[kind.to_code() as usize]
+    }
+
+    /// Take the sounds that the game asked to play since the last call,
+    /// so that the owner of the engine can play them.
+    pub fn take_audio_events(&mut self) -> Vec<AudioEvent> {
+        std::mem::take(&mut self.audio_events)
+    }
+
+    /// Take the trials which ran out since the last call,
+    /// so that the player can be offered to extend them.
+    pub fn take_expired_trials(&mut self) -> Vec<ExpiredTrial> {
+        std::mem::take(&mut self.new_expired_trials)
+    }
+
+    /// The balance parameters used by this engine.
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// The phase of the day at the given time,
+    /// from 0 (dawn) to 1 (the next dawn).
+    ///
+    /// The first half of the phase is daytime.
+    pub fn day_phase(&self, time: Time) -> f32 {
+        let day_length = self.config.day_length.max(1);
+        (time % day_length) as f32 / day_length as f32
+    }
+
+    /// The number of requests already in the nodes,
+    /// either waiting or being processed.
+    pub fn requests_in_flight(&self, state: &WorldState) -> u64 {
+        if let Some(in_flight) = self.mirrored_in_flight {
+            return in_flight;
+        }
+        let in_queue: u64 = self
+            .queue
+            .iter()
+            .filter(|event| !matches!(event.kind, RequestEventStage::RequestArrived { .. }))
+            .map(|event| event.amount as u64)
+            .sum();
+        let waiting: u64 = state
+            .nodes
+            .iter()
+            .flat_map(|node| &node.requests)
+            .map(|request| request.amount as u64)
+            .sum();
+        in_queue + waiting
+    }
+
+    /// The tariffs which the player can switch to,
+    /// with the projected bill of a full period under each.
+    ///
+    /// Peak/off-peak rates are only on offer
+    /// if demand follows a daily cycle.
+    pub fn tariff_offers(&self, state: &WorldState) -> Vec<(Tariff, Money)> {
+        Tariff::ALL
+            .into_iter()
+            .filter(|&tariff| tariff != Tariff::PeakOffPeak || self.config.daily_amplitude > 0.)
+            .map(|tariff| {
+                let bill = state
+                    .electricity
+                    .projected_bill(tariff, &self.config.electricity_cost_levels);
+                (tariff, bill)
+            })
+            .collect()
+    }
+
+    /// The rates at which money has been earned and ops performed,
+    /// over the last bill period and the current one so far.
+    pub fn resource_rates(&self, state: &WorldState) -> ResourceRates {
+        let period = self.config.electricity_bill_period;
+        let elapsed = state.time % period;
+        let start = state.period_snapshot.clone().unwrap_or_default();
+        let mut earned = state.earned - start.earned;
+        let mut ops = ServiceKind::ALL
+            .map(|kind| (state.service_by_kind(kind).total - start.ops[kind.to_code() as usize]).0);
+        let mut duration = elapsed;
+        if let Some(report) = state
+            .reports
+            .last()
+            .filter(|report| report.time + elapsed == state.time)
+        {
+            earned += report.revenue;
+            for (ops, report_ops) in ops.iter_mut().zip(report.ops) {
+                *ops += report_ops.0;
+            }
+            duration += period;
+        }
+        if duration == 0 {
+            return ResourceRates::default();
+        }
+        let seconds = duration as f64 / TIME_UNITS_PER_SECOND as f64;
+        ResourceRates {
+            money: earned.to_millicents().max(0) as f64 / seconds,
+            ops: ops.map(|ops| ops.max(0) as f64 / seconds),
+        }
+    }
+
+    /// The estimated game time until the player can afford the given card,
+    /// at the rates at which money and ops have been coming in
+    /// (see [`eta_for`]).
+    pub fn card_eta(&self, state: &WorldState, card: &CardSpec) -> Option<Time> {
+        eta_for(
+            &state.card_cost(card),
+            &state.resources(),
+            &self.resource_rates(state),
+        )
+    }
+
+    /// The share of the network bandwidth
+    /// taken in the last complete metering window,
+    /// between 0 and 1.
+    pub fn bandwidth_load(&self, state: &WorldState) -> f32 {
+        self.bandwidth
+            .load(state.time, bandwidth_capacity(state.bandwidth_level))
+    }
+
+    /// The multiplier applied to demand at the given time,
+    /// following a smooth daily cycle around the base demand.
+    pub fn daily_cycle(&self, time: Time) -> f32 {
+        let angle = self.day_phase(time) * std::f32::consts::TAU;
+        1. + self.config.daily_amplitude * angle.sin()
+    }
+
+    /// Gather the current metrics of the game.
+    pub fn metrics(&self, state: &WorldState) -> MetricsSnapshot {
+        MetricsSnapshot {
+            time: state.time,
+            funds: state.funds,
+            earned: state.earned,
+            spent: state.spent,
+            demand: state.demand,
+            day_phase: self.day_phase(state.time),
+            requests_dropped: state.requests_dropped,
+            requests_failed: state.requests_failed,
+            attacks_mitigated: state.attacks_mitigated,
+            drop_rate: self.drop_rate,
+            failure_rate: self.failure_rate,
+            cache_hit_rate: self.cache_hit_rate,
+            cache_speedup: self.cache_speedup,
+            routing_latency: self.routing_latency,
+            drop_causes: self.drop_causes,
+            bandwidth_load: self.bandwidth_load(state),
+            visible_cards: self.visible_cards().map(|card| card.id).collect(),
+            processing_times: self.processing_times.clone(),
+            traffic: state.traffic,
+            clicks_per_minute: self.clicks.clicks_per_minute(state.time),
+            total_player_clicks: state.services().map(|(_, s)| s.total_player_clicks).sum(),
+            ops_exchanged: ServiceKind::ALL.map(|kind| state.service_by_kind(kind).exchanged),
+            hardware_value: state.hardware_value(),
+            net_worth: state.net_worth(),
+            queue_pressure: self.queue_pressure,
+            score: ScoreBreakdown::of(state),
+            resource_rates: self.resource_rates(state),
+        }
+    }
+
+    /// The messages announced to the player so far.
+    pub fn notifications(&self) -> &NotificationLog {
+        &self.log
+    }
+
+    /// Take a snapshot of the engine internals for the developer overlay.
+    #[cfg(feature = "debug-tools")]
+    pub fn debug_snapshot(&self, state: &WorldState) -> super::inspect::DebugSnapshot {
+        use super::inspect::{DebugSnapshot, NodeSnapshot, SNAPSHOT_EVENTS};
+
+        let daily_cycle = self.daily_cycle(state.time) as f64;
+        DebugSnapshot {
+            time: state.time,
+            queue_len: self.queue.len(),
+            next_events: self.queue.iter().take(SNAPSHOT_EVENTS).copied().collect(),
+            waiting_len: self.waiting_queue.len(),
+            waiting_capacity: self
+                .config
+                .waiting_queue_capacity(state.nodes.len(), state.routing_level),
+            nodes: state
+                .nodes
+                .iter()
+                .map(|node| NodeSnapshot {
+                    id: node.id,
+                    processing: node.processing,
+                    num_cores: node.num_cores,
+                    ram_reserved: node.ram_reserved,
+                    ram_capacity: node.ram_capacity,
+                    queued: node.requests.len(),
+                })
+                .collect(),
+            demand: ServiceKind::ALL.map(|kind| {
+                let service = state.service_by_kind(kind);
+                service.state.is_published().then(|| {
+                    service.calculate_demand(state.demand * daily_cycle * state.demand_boost())
+                        * self.queue_demand_factor[kind as usize]
+                        * service.maintenance_demand_factor
+                })
+            }),
+            warnings: self.warnings.latest().cloned().collect(),
+            accounting_anomalies: self.accounting_anomalies,
+        }
+    }
+
+    /// Write the game metrics in the Prometheus text exposition format,
+    /// for external dashboards and bug reports.
+    #[cfg(feature = "debug-tools")]
+    pub fn metrics_text(&self, state: &WorldState) -> String {
+        use super::exposition::{Exposition, MetricKind};
+
+        let label = |value: &dyn std::fmt::Debug| format!("{value:?}").to_lowercase();
+
+        let mut doc = Exposition::new();
+        doc.family(
+            MetricKind::Counter,
+            "ops_total",
+            "Operations performed by each service",
+        );
+        for (kind, service) in state.services() {
+            doc.sample(&[("service", &label(&kind))], service.total.0 as f64);
+        }
+        doc.family(
+            MetricKind::Counter,
+            "requests_dropped_total",
+            "Requests dropped since the engine started, by cause",
+        );
+        for (cause, dropped) in DropCause::ALL.iter().zip(self.total_drop_causes) {
+            doc.sample(&[("cause", &label(cause))], dropped as f64);
+        }
+        doc.family(
+            MetricKind::Counter,
+            "earned_millicents_total",
+            "Money earned, in millicents",
+        )
+        .sample(&[], state.earned.to_millicents() as f64)
+        .family(
+            MetricKind::Counter,
+            "spent_millicents_total",
+            "Money spent, in millicents",
+        )
+        .sample(&[], state.spent.to_millicents() as f64)
+        .family(MetricKind::Gauge, "demand", "Base service demand")
+        .sample(&[], state.demand)
+        .family(
+            MetricKind::Gauge,
+            "funds_millicents",
+            "Available funds, in millicents",
+        )
+        .sample(&[], state.funds.to_millicents() as f64)
+        .family(MetricKind::Gauge, "nodes", "Cloud nodes")
+        .sample(&[], state.nodes.len() as f64)
+        .family(
+            MetricKind::Gauge,
+            "queue_length",
+            "Requests in the event queue, waiting to be routed and queued in nodes",
+        )
+        .sample(&[("queue", "events")], self.queue.len() as f64)
+        .sample(&[("queue", "waiting")], self.waiting_len() as f64)
+        .sample(
+            &[("queue", "nodes")],
+            state
+                .nodes
+                .iter()
+                .map(|node| node.requests.len())
+                .sum::<usize>() as f64,
+        );
+        doc.finish()
+    }
+
+    /// The histograms of request processing times,
+    /// for the current and previous window.
+    pub fn processing_times(&self) -> &ProcessingTimes {
+        &self.processing_times
+    }
+
+    /// Whether demand growth is paused
+    /// because too many requests are being dropped.
+    pub fn growth_stalled(&self) -> bool {
+        self.growth_stalled
+    }
+
+    /// Whether requests are being dropped
+    /// because the waiting queue of the load balancer is full.
+    pub fn queue_saturated(&self) -> bool {
+        self.queue_saturated
+    }
+
+    /// The number of requests waiting for a node to route them.
+    pub fn waiting_len(&self) -> usize {
+        self.mirrored_waiting_len
+            .unwrap_or(self.waiting_queue.len())
+    }
+
+    /// The share of the waiting queue capacity in use,
+    /// between 0 and 1.
+    pub fn waiting_queue_occupancy(&self, state: &WorldState) -> f32 {
+        let capacity = self
+            .config
+            .waiting_queue_capacity(state.nodes.len(), state.routing_level);
+        (self.waiting_len() as f32 / capacity.max(1) as f32).min(1.)
+    }
+
+    /// Whether requests of the given service wait in the node queues
+    /// for so long that its customers are leaving.
+    pub fn is_queue_pressured(&self, kind: ServiceKind) -> bool {
+        self.queue_pressure[kind as usize] > 1.
+    }
+
+    /// The number of requests per second
+    /// which each customer of the given service is expected to make
+    /// if it had the given price,
+    /// at the current point of the daily demand cycle.
+    ///
+    /// Multiplied by the number of customers of the service
+    /// (see [`WorldState::customers_per_service`]),
+    /// this previews the effect of a price change on demand.
+    pub fn projected_requests_per_customer(
+        &self,
+        state: &WorldState,
+        kind: ServiceKind,
+        price: Money,
+    ) -> f64 {
+        let service = ServiceInfo {
+            price,
+            ..*state.service_by_kind(kind)
+        };
+        let daily_cycle = self.daily_cycle(state.time) as f64;
+        let demand = service.calculate_demand(state.demand * daily_cycle * state.demand_boost())
+            * self.queue_demand_factor[kind as usize]
+            * service.maintenance_demand_factor;
+        let (demand, amount) = Self::group_demand(demand, self.bundle_caps[kind as usize]);
+        expected_requests_per_second(demand) * amount as f64
+    }
+
+    /// The number of times more memory was released from a node
+    /// than it had in use, which should always be zero.
+    pub fn accounting_anomalies(&self) -> u64 {
+        self.accounting_anomalies
+    }
+
+    /// Check whether the game is due to be saved,
+    /// resetting the request.
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
+    /// The project cards visible to the player,
+    /// as of the last major update or player action.
+    pub fn visible_cards(&self) -> impl Iterator<Item = &'static CardSpec> + '_ {
+        self.visible_cards.iter().map(|&index| &ALL_CARDS[index])
+    }
+
+    /// The project cards yet to appear
+    /// whose progress towards appearing can be shown,
+    /// as of the last major update or player action.
+    pub fn upcoming_cards(&self) -> impl Iterator<Item = &'static CardSpec> + '_ {
+        self.upcoming_cards.iter().map(|&index| &ALL_CARDS[index])
+    }
+
+    /// Check which project cards are visible to the player,
+    /// chiming if a new card became available since the last check.
+    ///
+    /// Penalty cards are never visible,
+    /// they are charged on the next major update instead.
+    ///
+    /// This is done on every major update and player action,
+    /// so that the view does not need to go through all cards.
+    pub fn update_visible_cards(&mut self, state: &WorldState) {
+        let mut visible = Vec::with_capacity(self.visible_cards.len() + 1);
+        let mut upcoming = Vec::with_capacity(self.upcoming_cards.len());
+        self.penalty_cards.clear();
+        for (index, card) in ALL_CARDS.iter().enumerate() {
+            if card.should_appear(state) {
+                if card.is_penalty() {
+                    self.penalty_cards.push(index);
+                } else {
+                    visible.push(index);
+                }
+            } else if card.upcoming_progress(state).is_some() {
+                upcoming.push(index);
+            }
+        }
+        let any_new = visible
+            .iter()
+            .any(|index| self.visible_cards.binary_search(index).is_err());
+        if self.cards_checked && any_new {
+            self.audio_events.push(AudioEvent::CardAvailable);
+        }
+        self.visible_cards = visible;
+        self.upcoming_cards = upcoming;
+        self.cards_checked = true;
+    }
+
+    pub fn apply_action(
+        &mut self,
+        state: &mut WorldState,
+        action: PlayerAction,
+    ) -> Result<(), ActionError> {
+        if state.is_daily_over() {
+            // the game is frozen
+            return Err(ActionError::NotAvailable);
+        }
+        if state.is_in_debt() && action.is_purchase() {
+            return Err(ActionError::InDebt);
+        }
+        // clicks only affect the cards once processed
+        let check_cards = !matches!(action, PlayerAction::OpClick { .. });
+        self.apply_action_impl(state, action)?;
+        if check_cards {
+            self.update_visible_cards(state);
+            self.drop_stale_pins(state);
+        }
+        Ok(())
+    }
+
+    /// Unpin the cards which are no longer on offer,
+    /// because they were used or went away.
+    fn drop_stale_pins(&self, state: &mut WorldState) {
+        let visible = &self.visible_cards;
+        state.cards_pinned.retain(|pinned| {
+            visible
+                .iter()
+                .any(|&index| ALL_CARDS[index].id == pinned.as_ref())
+        });
+    }
+
+    fn apply_action_impl(
+        &mut self,
+        state: &mut WorldState,
+        action: PlayerAction,
+    ) -> Result<(), ActionError> {
+        match action {
+            PlayerAction::OpClick { kind, amount } => {
+                // steady clicking multiplies the ops
+                let combo = &mut self.combos[kind.to_code() as usize];
+                combo.record_click(state.time);
+                let amount = combo.apply(amount);
+                state.service_by_kind_mut(kind).total_player_clicks += 1;
+                if self.clicks.record_click(state.time) {
+                    state.autoclicker_suspected = true;
+                }
+                // accumulate the operation,
+                // to be scheduled in the next update
+                match self.pending_clicks.iter_mut().find(|(k, _)| *k == kind) {
+                    Some((_, pending)) => *pending += amount,
+                    None => self.pending_clicks.push((kind, amount)),
+                }
+            }
+            PlayerAction::Payment { amount } => {
+                state.funds -= amount;
+                state.spent += amount;
+            }
+            PlayerAction::PayElectricityBill => {
+                // pay what the funds allow, never going into the red
+                let amount = state.bill_payment();
+                let bill = state.electricity.bill_amount();
+                if amount == Money::zero() && bill > Money::zero() {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: bill,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(state, PlayerAction::Payment { amount })?;
+                state.electricity.pay(amount);
+            }
+            PlayerAction::PrepayElectricity { amount } => {
+                if amount <= Money::zero() {
+                    return Err(ActionError::NotAvailable);
+                }
+                if state.funds < amount {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: amount,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(state, PlayerAction::Payment { amount })?;
+                state.electricity.credit += amount;
+                // settle what is already due
+                state.electricity.draw_credit();
+            }
+            PlayerAction::SetCacheFraction { fraction } => {
+                if state.cache_level == 0 || !(0. ..=1.).contains(&fraction) {
+                    return Err(ActionError::NotAvailable);
+                }
+                state.cache_fraction = fraction;
+                // a smaller cache needs less memory in reserve
+                let maximum_reserve = Self::calculate_memory_reserve_required(
+                    state.service_tier(),
+                    state.cache_level,
+                    fraction,
+                    state.software_level,
+                );
+                for node in state.nodes.iter_mut() {
+                    Self::audit_release(
+                        &mut self.warnings,
+                        &mut self.accounting_anomalies,
+                        state.time,
+                        node.release_excess_reserve(maximum_reserve),
+                        "resizing the cache",
+                    );
+                }
+                self.processing_times.rotate();
+            }
+            PlayerAction::ExtendTrial { spec_id, duration } => {
+                let Some(index) = self
+                    .expired_trials
+                    .iter()
+                    .position(|trial| trial.spec.id == spec_id && trial.is_extendable(state.time))
+                else {
+                    return Err(ActionError::NotAvailable);
+                };
+                if duration == 0 {
+                    return Err(ActionError::NotAvailable);
+                }
+                let service = self.expired_trials[index].spec.service;
+                let fee = trial_extension_fee(service, duration);
+                if state.funds < fee {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: fee,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(state, PlayerAction::Payment { amount: fee })?;
+                // a customer can only be brought back once
+                self.expired_trials.remove(index);
+                let user_spec =
+                    state.add_user_spec(service, UserKind::Customer, state.time + duration);
+                self.bootstrap_events_for(state, &user_spec);
+            }
+            PlayerAction::SetAutoPay { on } => {
+                if !state.can_auto_pay {
+                    return Err(ActionError::NotAvailable);
+                }
+                state.auto_pay = on;
+            }
+            PlayerAction::Hire { tier, service } => {
+                if !state.can_hire_staff
+                    || !state.service_by_kind(service).state.is_unlocked()
+                    || state.staff.len() >= MAX_STAFF
+                {
+                    return Err(ActionError::NotAvailable);
+                }
+                let cost = tier.hiring_cost();
+                if state.funds < cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: cost,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(state, PlayerAction::Payment { amount: cost })?;
+                state.staff.push(StaffMember {
+                    tier,
+                    service,
+                    hired_at: state.time,
+                });
+                self.log.push(
+                    state.time,
+                    t!("log.staff_hired", tier = tier, service = service),
+                );
+            }
+            PlayerAction::Fire { index } => {
+                let Some(member) = state.staff.get(index).copied() else {
+                    return Err(ActionError::NotAvailable);
+                };
+                let severance = member.tier.severance();
+                if state.funds < severance {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: severance,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(state, PlayerAction::Payment { amount: severance })?;
+                state.staff.remove(index);
+            }
+            PlayerAction::SwitchTariff { tariff } => {
+                if !state.can_choose_tariff
+                    || tariff == state.electricity.tariff
+                    || (tariff == Tariff::PeakOffPeak && self.config.daily_amplitude <= 0.)
+                    || state.time < state.electricity.tariff_switch_ready_at()
+                {
+                    return Err(ActionError::NotAvailable);
+                }
+                if state.funds < TARIFF_SWITCH_FEE {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: TARIFF_SWITCH_FEE,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(
+                    state,
+                    PlayerAction::Payment {
+                        amount: TARIFF_SWITCH_FEE,
+                    },
+                )?;
+                state.electricity.tariff = tariff;
+                state.electricity.tariff_switched_at = Some(state.time);
+                self.log.push(
+                    state.time,
+                    t!("log.tariff_switched", provider = tariff.provider()),
+                );
+            }
+            PlayerAction::AcceptBailout => {
+                if !state.bailout_pending {
+                    return Err(ActionError::NotAvailable);
+                }
+                // lend whatever is needed to get back on track
+                let principal = BAILOUT_FUNDS - state.funds;
+                state.funds = BAILOUT_FUNDS;
+                match &mut state.loan {
+                    Some(loan) => loan.balance += principal,
+                    None => state.loan = Some(Loan { balance: principal }),
+                }
+                state.bailout_pending = false;
+                state.insolvent_since = 0;
+            }
+            PlayerAction::DeclareBankruptcy => {
+                if !state.bailout_pending {
+                    return Err(ActionError::NotAvailable);
+                }
+                // start over, keeping what was achieved
+                // (and the setup of the daily challenge, if any)
+                let mut fresh = match state.daily {
+                    Some(daily) => WorldState::new_daily(daily, state.company_name.clone()),
+                    None => WorldState::default(),
+                };
+                fresh.time = state.time;
+                fresh.difficulty = state.difficulty;
+                fresh.achievements = state.achievements.after_bankruptcy(state);
+                *state = fresh;
+                self.queue = RequestEventQueue::new();
+                self.waiting_queue.clear();
+                self.pending_clicks.clear();
+                self.combos = Default::default();
+                self.clicks = ClickStats::default();
+                self.bootstrap_events(state);
+            }
+            #[cfg(feature = "debug-tools")]
+            PlayerAction::Debug(action) => self.apply_debug_action(state, action),
+            PlayerAction::CancelInsurance => {
+                if state.insurance.take().is_none() {
+                    return Err(ActionError::NotAvailable);
+                }
+                state.insurance_cancelled_at = Some(state.time);
+            }
+            PlayerAction::DismissReport => {
+                state.report_dismissed = true;
+            }
+            PlayerAction::DismissWin => {
+                state.win_dismissed = true;
+            }
+            PlayerAction::ResolveEvent { id, choice } => {
+                self.resolve_event(state, &id, choice)?;
+            }
+            PlayerAction::RenameCompany { name } => {
+                state.company_name = sanitize_company_name(&name);
+            }
+            PlayerAction::SetMaintenance { kind, on } => {
+                let time = state.time;
+                let service = state.service_by_kind_mut(kind);
+                if !service.state.is_unlocked() {
+                    return Err(ActionError::NotUnlocked(kind));
+                }
+                if service.in_maintenance() == on {
+                    // nothing to do
+                    return Ok(());
+                }
+                if on {
+                    service.maintenance_since = Some(time);
+                    // stop customer requests which have yet to arrive
+                    self.queue.clear_arrivals(|event| {
+                        event.service == kind && event.user_spec_id.is_some() && !event.bad
+                    });
+                } else {
+                    service.maintenance_since = None;
+                    // customers come back
+                    let specs: Vec<_> = state
+                        .user_specs
+                        .iter()
+                        .filter(|spec| spec.service == kind && !spec.is_bad())
+                        .cloned()
+                        .collect();
+                    for spec in &specs {
+                        self.bootstrap_events_for(state, spec);
+                    }
+                }
+            }
+            PlayerAction::ConvertOps { from, to, amount } => {
+                if !state.can_exchange_ops {
+                    return Err(ActionError::NotAvailable);
+                }
+                for kind in [from, to] {
+                    if !state.service_by_kind(kind).state.is_unlocked() {
+                        return Err(ActionError::NotUnlocked(kind));
+                    }
+                }
+                let exchange =
+                    Exchange::preview(from, to, amount).ok_or(ActionError::NotAvailable)?;
+                let have = state.service_by_kind(from).available;
+                if exchange.obtained == Ops(0) || exchange.spent > have {
+                    return Err(ActionError::InsufficientOps {
+                        kind: from,
+                        needed: exchange.spent.max(exchange.rate),
+                        have,
+                    });
+                }
+                state.service_by_kind_mut(from).available -= exchange.spent;
+                let service = state.service_by_kind_mut(to);
+                service.available += exchange.obtained;
+                service.exchanged += exchange.obtained;
+            }
+            PlayerAction::RepayLoan => {
+                let Some(loan) = &state.loan else {
+                    return Err(ActionError::NotAvailable);
+                };
+                if state.funds < loan.balance {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: loan.balance,
+                        have: state.funds,
+                    });
+                }
+                self.apply_action(
+                    state,
+                    PlayerAction::Payment {
+                        amount: loan.balance,
+                    },
+                )?;
+                state.loan = None;
+            }
+            PlayerAction::ChangePrice { kind, new_price } => {
+                // change the price and recalculate demand
+                let time = state.time;
+                let service = state.service_by_kind_mut(kind);
+                if !service.state.is_unlocked() {
+                    return Err(ActionError::NotUnlocked(kind));
+                }
+                if let Some(daily) = &state.daily {
+                    let floor = daily.modifiers.price_floor(kind);
+                    if new_price < floor {
+                        return Err(ActionError::BelowPriceFloor(floor));
+                    }
+                }
+                let service = state.service_by_kind_mut(kind);
+                if let Some(raise) = service.change_price(new_price, time) {
+                    self.churn_customers(state, kind, raise);
+                }
+            }
+            PlayerAction::RestartNode { node: node_id } => {
+                let time = state.time;
+                let Some(node) = state.node_mut(node_id) else {
+                    return Err(ActionError::NoSuchNode(node_id));
+                };
+                if node.is_restarting(time) {
+                    return Err(ActionError::NodeRestarting(node_id));
+                }
+                let (requests, released) = node.restart(time + NODE_RESTART_COOLDOWN);
+                Self::audit_release(
+                    &mut self.warnings,
+                    &mut self.accounting_anomalies,
+                    time,
+                    released,
+                    "restarting the node",
+                );
+                // requeue the waiting requests instead of dropping them
+                for request in requests {
+                    let bad = request
+                        .user_spec_id
+                        .and_then(|id| state.user_spec(id))
+                        .is_some_and(|spec| spec.is_bad());
+                    self.waiting_queue.push_back(WaitingRouteRequest {
+                        amount: request.amount,
+                        user_spec_id: request.user_spec_id,
+                        service: request.service,
+                        bad,
+                        staff: request.staff,
+                    });
+                }
+            }
+            PlayerAction::UpgradeCpu { node: node_id } => {
+                let funds = state.funds;
+                let node = state
+                    .node_mut(node_id)
+                    .ok_or(ActionError::NoSuchNode(node_id))?;
+                if node.scale != NodeScale::Single {
+                    return Err(ActionError::NotUpgradable(node_id));
+                }
+                let next_level = node.cpu_level + 1;
+                if next_level as usize >= self.config.cpu_levels.len() {
+                    return Err(ActionError::MaxLevelReached);
+                }
+                let (num_cores, cpu_speed, cost) = self.config.cpu_levels[next_level as usize];
+                if funds < cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: cost,
+                        have: funds,
+                    });
+                }
+                node.cpu_level = next_level;
+                node.num_cores = num_cores;
+                node.cpu_speed = cpu_speed + node.overclock.speed_bonus;
+                state.funds -= cost;
+                state.spent += cost;
+                self.last_purchase = Some(HardwarePurchase {
+                    node: node_id,
+                    resource: HardwareResource::Cpu,
+                    previous_level: next_level - 1,
+                    cost,
+                    time: state.time,
+                    node_used: false,
+                });
+            }
+            PlayerAction::UpgradeRam { node: node_id } => {
+                let funds = state.funds;
+                let node = state
+                    .node_mut(node_id)
+                    .ok_or(ActionError::NoSuchNode(node_id))?;
+                if node.scale != NodeScale::Single {
+                    return Err(ActionError::NotUpgradable(node_id));
+                }
+                let next_level = node.ram_level + 1;
+                if next_level as usize >= self.config.ram_levels.len() {
+                    return Err(ActionError::MaxLevelReached);
+                }
+                let (ram_capacity, cost) = self.config.ram_levels[next_level as usize];
+                if funds < cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: cost,
+                        have: funds,
+                    });
+                }
+                node.ram_level = next_level;
+                node.ram_capacity = ram_capacity;
+                state.funds -= cost;
+                state.spent += cost;
+                self.last_purchase = Some(HardwarePurchase {
+                    node: node_id,
+                    resource: HardwareResource::Ram,
+                    previous_level: next_level - 1,
+                    cost,
+                    time: state.time,
+                    node_used: false,
+                });
+            }
+            PlayerAction::UndoLastPurchase => {
+                let purchase = *self
+                    .undoable_purchase(state.time)
+                    .ok_or(ActionError::NotAvailable)?;
+                let node = state
+                    .node_mut(purchase.node)
+                    .ok_or(ActionError::NoSuchNode(purchase.node))?;
+                let level = purchase.previous_level as usize;
+                match purchase.resource {
+                    HardwareResource::Cpu => {
+                        let (num_cores, cpu_speed, _) = self.config.cpu_levels[level];
+                        // the extra cores are already busy
+                        if node.processing > num_cores {
+                            return Err(ActionError::UpgradeInUse(purchase.node));
+                        }
+                        node.cpu_level = purchase.previous_level;
+                        node.num_cores = num_cores;
+                        node.cpu_speed = cpu_speed + node.overclock.speed_bonus;
+                    }
+                    HardwareResource::Ram => {
+                        let (ram_capacity, _) = self.config.ram_levels[level];
+                        // the extra memory is already in use
+                        if node.ram_usage > ram_capacity {
+                            return Err(ActionError::UpgradeInUse(purchase.node));
+                        }
+                        node.ram_level = purchase.previous_level;
+                        node.ram_capacity = ram_capacity;
+                    }
+                }
+                state.funds += purchase.cost;
+                state.spent -= purchase.cost;
+                self.last_purchase = None;
+            }
+            PlayerAction::AddNode => {
+                // check cost
+                if state.funds < self.config.bare_node_cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: self.config.bare_node_cost,
+                        have: state.funds,
+                    });
+                }
+                // note: whether there is space for the new node
+                // is determined elsewhere
+
+                state.funds -= self.config.bare_node_cost;
+
+                let id = state.nodes.len() as u32;
+                state.nodes.push(CloudNode::new(id).set_up_at(state.time));
+            }
+            PlayerAction::AddUpgradedNode => {
+                // check cost
+                if state.funds < self.config.upgraded_node_cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: self.config.upgraded_node_cost,
+                        have: state.funds,
+                    });
+                }
+                // note: whether there is space for the new node
+                // is determined elsewhere
+
+                state.funds -= self.config.upgraded_node_cost;
+
+                let id = state.nodes.len() as u32;
+                state
+                    .nodes
+                    .push(CloudNode::new_fully_upgraded(id).set_up_at(state.time));
+            }
+            PlayerAction::AddRack => {
+                // check cost
+                if state.funds < self.config.upgraded_rack_cost {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: self.config.upgraded_rack_cost,
+                        have: state.funds,
+                    });
+                }
+                // note: whether there is space for the new node
+                // is determined elsewhere
+
+                state.funds -= self.config.upgraded_rack_cost;
+
+                let id = state.nodes.len() as u32;
+                state
+                    .nodes
+                    .push(CloudNode::new_fully_upgraded_rack(id).set_up_at(state.time));
+            }
+            PlayerAction::UseCard { id } => {
+                // 1. find the card
+                match card_by_id(id.as_ref()) {
+                    Some(card) => {
+                        if !card.is_repeatable() && state.is_card_used(card.id) {
+                            return Err(ActionError::NotAvailable);
+                        }
+                        // 2. deduct its cost
+                        let cost = state.card_cost(card);
+                        if state.is_in_debt() && cost.money > Money::zero() {
+                            return Err(ActionError::InDebt);
+                        }
+                        if !state.can_afford(&cost) {
+                            return Err(ActionError::CannotAfford(cost));
+                        }
+                        state.apply_cost(&cost);
+                        // the discount spends the research done so far
+                        if card.is_research_discounted() {
+                            state.research_progress = 0;
+                        }
+                        // 3. apply the card's effects
+                        self.apply_card(state, card);
+                        if card.id == ID_GAME_OVER {
+                            self.audio_events.push(AudioEvent::Win);
+                        }
+                        // 4. add the card to the used cards list
+                        // (but only if the card was actually applied)
+                        if card.is_repeatable() {
+                            // only the last use is kept
+                            state.cards_used.retain(|used| used.id != id);
+                        }
+                        let time = state.time;
+                        state.cards_used.push(UsedCard {
+                            id: id.clone(),
+                            time,
+                        });
+                        // keep cards_used sorted by ID
+                        state
+                            .cards_used
+                            .sort_unstable_by(|c1, c2| c1.id.cmp(&c2.id));
+                        // the player is no longer saving for it
+                        state.cards_pinned.retain(|pinned| *pinned != id);
+                    }
+                    None => return Err(ActionError::NoSuchCard(id)),
+                }
+            }
+            PlayerAction::PinCard { id } => {
+                let Some(card) = card_by_id(id.as_ref()) else {
+                    return Err(ActionError::NoSuchCard(id));
+                };
+                if !self.visible_cards().any(|visible| visible.id == card.id) {
+                    return Err(ActionError::NotAvailable);
+                }
+                if state.is_card_pinned(card.id) {
+                    // nothing to do
+                    return Ok(());
+                }
+                // cards which went away without being used
+                // do not keep holding a pin
+                self.drop_stale_pins(state);
+                if state.cards_pinned.len() >= MAX_PINNED_CARDS {
+                    return Err(ActionError::TooManyPinned);
+                }
+                state.cards_pinned.push(id);
+            }
+            PlayerAction::UnpinCard { id } => {
+                state.cards_pinned.retain(|pinned| *pinned != id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the consequences of a choice in the pending narrative event.
+    fn resolve_event(
+        &mut self,
+        state: &mut WorldState,
+        id: &str,
+        choice: usize,
+    ) -> Result<(), ActionError> {
+        let event = state
+            .narrative
+            .pending()
+            .filter(|pending| pending.id == id)
+            .and_then(|pending| pending.event())
+            .ok_or(ActionError::NotAvailable)?;
+        let choice = event.choices.get(choice).ok_or(ActionError::NotAvailable)?;
+        state.narrative.resolve(id);
+        for effect in choice.effects {
+            self.apply_card_effect(state, effect);
+        }
+        Ok(())
+    }
+
+    fn apply_card(&mut self, state: &mut WorldState, card: &CardSpec) {
+        self.apply_card_effect(state, &card.effect)
+    }
+
+    fn apply_card_effect(&mut self, state: &mut WorldState, effect: &CardEffect) {
+        // keep what the upgrade changes to tell the player about it
+        let before = matches!(
+            effect,
+            CardEffect::UpgradeServices | CardEffect::MoreCaching
+        )
+        .then(|| state.clone());
+
+        match effect {
+            CardEffect::Nothing => { /* no op */ }
+            CardEffect::UnlockDemandEstimate => {
+                state.can_see_demand = true;
+            }
+            CardEffect::UnlockEnergyEstimate => {
+                state.can_see_energy_consumption = true;
+            }
+            CardEffect::UnlockRequestRateEstimate => {
+                state.can_see_request_rates = true;
+            }
+            CardEffect::UnlockOpExchange => {
+                state.can_exchange_ops = true;
+            }
+            CardEffect::UnlockAutoPay => {
+                state.can_auto_pay = true;
+                state.auto_pay = true;
+            }
+            CardEffect::UnlockService(kind) => {
+                let time = state.time;
+                let service = state.service_by_kind_mut(*kind);
+                if let Err(e) = service.state.unlock() {
+                    self.warnings
+                        .push(time, format!("Could not unlock {kind}: {e}"));
+                }
+            }
+            CardEffect::PublishService(kind) => {
+                let time = state.time;
+                let service = state.service_by_kind_mut(*kind);
+                if let Err(e) = service.state.publish() {
+                    self.warnings
+                        .push(time, format!("Could not publish {kind}: {e}"));
+                }
+                if !service.state.is_published() {
+                    // never bring customers to a locked service
+                    return;
+                }
+
+                // if service is base, add base publicity
+                // (it means that the game has just started)
+                if *kind == ServiceKind::Base {
+                    state.demand = 1.;
+                }
+
+                // add user specification for this service
+                if !state
+                    .user_specs
+                    .iter()
+                    .any(|spec| spec.service == *kind && !spec.is_bad())
+                {
+                    let user_spec = state.add_user_spec(*kind, UserKind::Customer, 0);
+                    self.bootstrap_events_for(state, &user_spec);
+                }
+                // add DoS specification for this service
+                // if there is high demand
+                if state.demand > self.config.demand_dos_threshold {
+                    self.spawn_attackers(state, &[*kind]);
+                }
+            }
+            CardEffect::UpgradeEntitlements(service, money) => {
+                let service = state.service_by_kind_mut(*service);
+                service.entitlement = service.entitlement.max(*money);
+            }
+            CardEffect::SignSla {
+                service,
+                bonus,
+                penalty,
+            } => {
+                if !state.slas.iter().any(|sla| sla.service == *service) {
+                    state.service_by_kind_mut(*service).entitlement += *bonus;
+                    state
+                        .slas
+                        .push(ServiceLevelAgreement::new(*service, *penalty));
+                }
+            }
+            CardEffect::TakeInsurance { premium } => {
+                if state.insurance.is_none() {
+                    state.insurance = Some(InsurancePolicy::new(*premium, state.time));
+                }
+            }
+            CardEffect::SetElectricityCostLevel(level) => {
+                state.electricity.cost_level = state.electricity.cost_level.max(*level);
+                if *level == 4 {
+                    // bonus ads at level 4 (clean energy plan)
+                    state.demand += 5_000.;
+                    state.demand_rate += 32.;
+                } else if *level == 6 {
+                    // bonus ads at level 6 (free energy)
+                    state.demand += 50_000.;
+                    state.demand_rate += 64.;
+                }
+            }
+            CardEffect::UnlockStaff => {
+                state.can_hire_staff = true;
+            }
+            CardEffect::UnlockTariffs(level) => {
+                state.electricity.cost_level = state.electricity.cost_level.max(*level);
+                state.can_choose_tariff = true;
+            }
+            CardEffect::UpgradeOpsPerClick(amount) => {
+                state.ops_per_click = state.ops_per_click.max(*amount);
+            }
+            CardEffect::AddFunds(money) => {
+                state.funds += *money;
+            }
+            CardEffect::WelcomeBack => {
+                if let Some(bonus) = state.away_bonus.take() {
+                    let ops = bonus.ops(state.ops_per_click);
+                    state.base_service.available += ops;
+                    state.base_service.total += ops;
+                    state.funds += bonus.funds();
+                }
+            }
+            CardEffect::Penalty(money) => {
+                state.electricity.charge_penalty(*money, state.time);
+                self.audio_events.push(AudioEvent::Bill);
+            }
+            CardEffect::AddClients(spec) => {
+                let user_spec = state.add_user_spec(
+                    spec.service,
+                    UserKind::Customer,
+                    state.time + spec.trial_duration as u64,
+                );
+                self.bootstrap_events_for(state, &user_spec);
+            }
+            CardEffect::AddResearchers(service) => {
+                let user_spec = state.add_user_spec(*service, UserKind::Researcher, 0);
+                self.bootstrap_events_for(state, &user_spec);
+            }
+            CardEffect::AddClientsWithPublicity(spec, demand_delta) => {
+                state.demand += demand_delta;
+
+                let trial_time = if spec.trial_duration > 0 {
+                    state.time + spec.trial_duration as u64
+                } else {
+                    0
+                };
+                let user_spec = state.add_user_spec(spec.service, UserKind::Customer, trial_time);
+                self.bootstrap_events_for(state, &user_spec);
+            }
+            CardEffect::AddPublicityRate(demand_delta, demand_rate_delta) => {
+                let was_high_demand = state.demand > self.config.demand_dos_threshold;
+                state.demand += demand_delta;
+                state.demand_rate += demand_rate_delta;
+                // if demand increased a lot,
+                // insert DoS users if not added already
+                if !was_high_demand && state.demand > self.config.demand_dos_threshold {
+                    self.spawn_attackers(state, &DOS_TARGETS);
+                }
+            }
+            CardEffect::UpgradeServices => {
+                state.software_level += 1;
+                // refresh memory reserves
+                // might be reserving too much
+                let maximum_reserve = state.expected_ram_reserved();
+                for node in state.nodes.iter_mut() {
+                    Self::audit_release(
+                        &mut self.warnings,
+                        &mut self.accounting_anomalies,
+                        state.time,
+                        node.release_excess_reserve(maximum_reserve),
+                        "upgrading the services",
+                    );
+                }
+                self.processing_times.rotate();
+            }
+            CardEffect::MoreCaching => {
+                state.cache_level += 1;
+                self.processing_times.rotate();
+            }
+            CardEffect::UnlockMultiNodes => {
+                state.can_buy_nodes = true;
+            }
+            CardEffect::UnlockMultiRacks => {
+                state.can_buy_racks = true;
+            }
+            CardEffect::UnlockMultiDatacenters => {
+                state.can_buy_datacenters = true;
+
+                // take the requests out of the nodes
+                // and send them back to be routed again
+                let time = state.time + 1;
+                let mut in_flight: Vec<_> = self
+                    .queue
+                    .take_in_nodes()
+                    .into_iter()
+                    .map(|event| event.into_rerouted(time))
+                    .collect();
+                for node in &state.nodes {
+                    for request in &node.requests {
+                        in_flight.push(request.to_rerouted(state, time));
+                    }
+                }
+
+                // transform all nodes into datacenter nodes
+                let conversion = RackConversion::of(&state.nodes, &self.config);
+                state.nodes = conversion.convert(&state.nodes, state.time);
+                if conversion.credit > Money::zero() {
+                    state.funds += conversion.credit;
+                    self.log.push(
+                        state.time,
+                        t!("log.rack_credit", amount = conversion.credit),
+                    );
+                }
+
+                for event in in_flight {
+                    self.queue.push(event);
+                }
+            }
+            CardEffect::UpgradeSpamProtection(rate) => {
+                state.spam_protection = state.spam_protection.max(*rate);
+                // give the new protection a head start
+                state.dos_immune_until = state.time + DOS_IMMUNITY_PERIOD;
+                // remove bad actors if they are kept out for good
+                state.purge_blocked_attackers();
+            }
+            CardEffect::UpgradeRoutingLevel(level) => {
+                state.routing_level = state.routing_level.max(*level);
+            }
+            CardEffect::UpgradeBandwidth(level) => {
+                state.bandwidth_level = state.bandwidth_level.max(*level);
+            }
+            CardEffect::UpgradeAllNodesRam(levels) => {
+                let max_level = self.config.ram_levels.len() as u8 - 1;
+                for node in state.nodes.iter_mut() {
+                    // racks and fully upgraded nodes are left as they are
+                    if node.scale != NodeScale::Single || node.ram_level >= max_level {
+                        continue;
+                    }
+                    node.ram_level = node.ram_level.saturating_add(*levels).min(max_level);
+                    node.ram_capacity = self.config.ram_levels[node.ram_level as usize].0;
+                }
+            }
+            CardEffect::OverclockNodes {
+                speed_bonus,
+                energy_penalty,
+            } => {
+                for node in state.nodes.iter_mut() {
+                    node.overclock(*speed_bonus, *energy_penalty);
+                }
+            }
+        }
+
+        if let Some(before) = before {
+            let changes = describe_upgrade(effect, &before, state);
+            if !changes.is_empty() {
+                self.log.push(state.time, changes.join("; "));
+            }
+        }
+    }
+
+    #[cfg(feature = "debug-tools")]
+    fn apply_debug_action(&mut self, state: &mut WorldState, action: crate::DebugAction) {
+        use crate::DebugAction;
+
+        match action {
+            DebugAction::AddFunds(money) => {
+                state.funds += money;
+            }
+            DebugAction::AddOps { kind, amount } => {
+                let service = state.service_by_kind_mut(kind);
+                service.total += amount;
+                service.available += amount;
+            }
+            DebugAction::SetDemand(demand) => {
+                state.demand = demand.max(0.);
+            }
+            DebugAction::AdvanceTime { seconds } => {
+                let target =
+                    state.time + seconds as u64 * 1_000 * crate::TIME_UNITS_PER_MILLISECOND as u64;
+                // advance one major update at a time,
+                // so that no periodic event is skipped
+                while state.time < target {
+                    let time = (state.time + 2_500).min(target);
+                    self.update(state, time);
+                }
+            }
+            DebugAction::TogglePowersave => {
+                if state.is_powersaving() {
+                    state.electricity.pay_bills();
+                } else {
+                    // pretend that a big bill has been due for a while
+                    state.electricity.total_due += state.powersave_threshold() + Money::dollars(1);
+                    state.electricity.last_bill_time = state
+                        .time
+                        .saturating_sub(self.config.electricity_bill_period)
+                        .max(1);
+                }
+            }
+            DebugAction::SpawnDos(kind) => {
+                if state
+                    .user_specs
+                    .iter()
+                    .any(|spec| spec.service == kind && spec.is_bad())
+                {
+                    return;
+                }
+                let user_spec = state.add_user_spec(kind, UserKind::Attacker, 0);
+                self.bootstrap_events_for(state, &user_spec);
+            }
+        }
+    }
+
+    /// Make customers of a service leave after a steep price raise,
+    /// each with a chance which grows with the raise.
+    fn churn_customers(&mut self, state: &mut WorldState, kind: ServiceKind, raise: f64) {
+        let chance = (raise * CHURN_CHANCE_PER_RAISE).min(MAX_CHURN_CHANCE) as f32;
+        let churned: Vec<u32> = state
+            .user_specs
+            .iter()
+            .filter(|spec| spec.service == kind && spec.kind == UserKind::Customer)
+            .map(|spec| spec.id)
+            .filter(|_| self.gen.gen_bool(chance))
+            .collect();
+        if churned.is_empty() {
+            return;
+        }
+
+        state.user_specs.retain(|spec| !churned.contains(&spec.id));
+        self.queue
+            .clear_arrivals(|event| event.user_spec_id.is_some_and(|id| churned.contains(&id)));
+        state.service_by_kind_mut(kind).churned_at = Some(state.time);
+        self.log.push(
+            state.time,
+            t!("log.churn", count = churned.len(), service = kind),
+        );
+    }
+
+    /// Bring a new customer to each public service
+    /// which lost all of its users to churn a while ago.
+    fn return_churned_customers(&mut self, state: &mut WorldState) {
+        for kind in ServiceKind::ALL {
+            let service = state.service_by_kind(kind);
+            let Some(churned_at) = service.churned_at else {
+                continue;
+            };
+            if state.time < churned_at + CHURN_COOLDOWN {
+                continue;
+            }
+            let public = service.state.is_published();
+            state.service_by_kind_mut(kind).churned_at = None;
+            if !public
+                || state
+                    .user_specs
+                    .iter()
+                    .any(|spec| spec.service == kind && !spec.is_bad())
+            {
+                continue;
+            }
+
+            let user_spec = state.add_user_spec(kind, UserKind::Customer, 0);
+            self.bootstrap_events_for(state, &user_spec);
+        }
+    }
+
+    /// Initiate request arrival events based on the current world state
+    /// (see [`bootstrap_events_for`](Self::bootstrap_events_for)).
+    pub fn bootstrap_events(&mut self, state: &WorldState) {
+        self.update_bundle_caps(state);
+        for user_spec in state.user_specs.iter() {
+            self.bootstrap_events_for(state, user_spec);
+        }
+        self.update_visible_cards(state);
+    }
+
+    /// Initiate request arrival events for the given cloud user specification
+    ///
+    /// The first arrival is staggered
+    /// by a random fraction of the user's expected time between arrivals,
+    /// so that loading a game with many users
+    /// does not bring all of their requests at once.
+    /// Users with very high demand also get a few arrivals in advance,
+    /// one such time apart.
+    pub fn bootstrap_events_for(&mut self, state: &WorldState, user_spec: &CloudUserSpec) {
+        let time = state.time;
+        // calculate demand based on base demand and cloud service price
+        let service = state.service_by_kind(user_spec.service);
+        if service.in_maintenance() && !user_spec.is_bad() {
+            // arrivals start once maintenance is over
+            return;
+        }
+
+        let daily_cycle = self.daily_cycle(time) as f64;
+        let Some(demand) =
+            Self::user_demand(state, user_spec, daily_cycle, &self.queue_demand_factor)
+        else {
+            return;
+        };
+        let (demand, amount) =
+            Self::group_demand(demand, self.bundle_caps[user_spec.service as usize]);
+        let interval = ((TIME_UNITS_PER_SECOND as f64 / demand) as Time)
+            .clamp(1, BOOTSTRAP_MAX_INTERVAL) as u32;
+        let mut timestamp = time + self.gen.gen_range(0, interval) as Time;
+        let prewarmed = if demand >= BOOTSTRAP_PREWARM_DEMAND {
+            BOOTSTRAP_PREWARM_ARRIVALS
+        } else {
+            0
+        };
+        let event = |timestamp| {
+            RequestEvent::new_arrived(
+                timestamp,
+                Some(user_spec.id),
+                amount,
+                user_spec.service,
+                user_spec.is_bad(),
+            )
+        };
+        // only the last arrival goes on to schedule the next ones
+        for _ in 0..prewarmed {
+            self.queue.push(event(timestamp).unchained());
+            timestamp += interval as Time;
+        }
+        self.queue.push(event(timestamp));
+    }
+
+    /// Add an attacker to each of the given services which has none yet,
+    /// unless the spam protection keeps them out
+    /// (see [`WorldState::can_spawn_attackers`]).
+    fn spawn_attackers(&mut self, state: &mut WorldState, services: &[ServiceKind]) {
+        if !state.can_spawn_attackers() {
+            return;
+        }
+        for &service in services {
+            if state
+                .user_specs
+                .iter()
+                .any(|spec| spec.service == service && spec.is_bad())
+            {
+                continue;
+            }
+            let user_spec = state.add_user_spec(service, UserKind::Attacker, 0);
+            self.bootstrap_events_for(state, &user_spec);
+        }
+    }
+
+    /// The demand of requests from the given user,
+    /// or `None` if they bring no requests at all.
+    ///
+    /// Attackers only bring the share of their demand
+    /// which gets past the spam protection,
+    /// and customers of a service under queue pressure
+    /// or which was under maintenance
+    /// only the share of those who stayed.
+    fn user_demand(
+        state: &WorldState,
+        user_spec: &CloudUserSpec,
+        daily_cycle: f64,
+        queue_demand_factor: &[f64; ServiceKind::ALL.len()],
+    ) -> Option<f64> {
+        let service = state.service_by_kind(user_spec.service);
+        let demand = service.calculate_demand(state.demand * daily_cycle * state.demand_boost())
+            * queue_demand_factor[user_spec.service as usize]
+            * service.maintenance_demand_factor;
+        if user_spec.is_bad() {
+            let strength = state.attack_strength();
+            (strength > 0.).then_some(demand * strength)
+        } else {
+            Some(demand)
+        }
+    }
+
+    /// Split the demand of a user into the frequency of request sets
+    /// and the number of requests in each set,
+    /// with at most `cap` requests per set
+    /// unless that would make sets arrive too often.
+    fn group_demand(demand: f64, cap: u32) -> (f64, u32) {
+        // if demand is very high, combine requests into one set
+        // with a shorter frequency,
+        // to reduce real CPU workload
+        if demand > 30_000. {
+            let k = (demand / 20_000.)
+                .floor()
+                .clamp(1., MAX_REQUEST_BUNDLE.min(cap).max(1) as f64)
+                .max((demand / MAX_REQUEST_SET_RATE).ceil())
+                .min(MAX_REQUEST_BUNDLE as f64);
+            (demand / k, k as u32)
+        } else {
+            (demand, 1)
+        }
+    }
+
+    /// Cap the request sets of each service
+    /// to a share of the memory left in the biggest node
+    /// after the memory reserve of that service,
+    /// so that high demand brings more frequent smaller sets
+    /// instead of sets which no node can take.
+    fn update_bundle_caps(&mut self, state: &WorldState) {
+        let ram_capacity = state
+            .nodes
+            .iter()
+            .map(|node| node.ram_capacity)
+            .max()
+            .unwrap_or(Memory::zero());
+        self.bundle_caps = ServiceKind::ALL.map(|kind| {
+            let reserve = Self::calculate_memory_reserve_required(
+                kind,
+                state.cache_level,
+                state.cache_fraction,
+                state.software_level,
+            );
+            let free = (ram_capacity - reserve).max(Memory::zero());
+            let fitting = (free * BUNDLE_MEMORY_SHARE).ratio(kind.mem_required()) as u32;
+            fitting.clamp(1, MAX_REQUEST_BUNDLE)
+        });
+    }
+
+    /// Process the game state and produce new events.
+    pub fn update(&mut self, state: &mut WorldState, time: Time) {
+        self.update_with(state, time, |_, _| {});
+    }
+
+    /// Play a script of timed player actions,
+    /// then advance the game to `until`.
+    ///
+    /// Each action is applied at exactly its time,
+    /// once everything leading up to that time was processed
+    /// but before the periodic side effects of reaching it
+    /// (see [`MAJOR_UPDATE_PERIOD`]),
+    /// so the outcome does not depend on how the time is split into ticks.
+    /// Actions may fail as they would for the player,
+    /// and the outcome of each one is returned in order.
+    ///
+    /// The script must be sorted by time,
+    /// starting no earlier than the game time
+    /// and ending no later than `until`,
+    /// or the game is left untouched.
+    pub fn run_script(
+        &mut self,
+        state: &mut WorldState,
+        script: &[(Time, PlayerAction)],
+        until: Time,
+    ) -> Result<Vec<Result<(), ActionError>>, ScriptError> {
+        let mut previous = state.time;
+        for (index, &(time, _)) in script.iter().enumerate() {
+            if time < previous {
+                return Err(ScriptError::Unsorted {
+                    index,
+                    time,
+                    previous,
+                });
+            }
+            previous = time;
+        }
+        if until < previous {
+            return Err(ScriptError::EndsEarly {
+                until,
+                last: previous,
+            });
+        }
+
+        let mut outcomes = Vec::with_capacity(script.len());
+        for actions in script.chunk_by(|a, b| a.0 == b.0) {
+            let time = actions[0].0;
+            self.update_with(state, time, |engine, state| {
+                for (_, action) in actions {
+                    outcomes.push(engine.apply_action(state, action.clone()));
+                }
+            });
+        }
+        self.update(state, until);
+        Ok(outcomes)
+    }
+
+    /// Process the game state up to the given time,
+    /// calling `at_time` with the game time set to it
+    /// once everything leading up to that time was processed,
+    /// but before the periodic side effects of reaching it
+    /// (such as electricity bills).
+    fn update_with(
+        &mut self,
+        state: &mut WorldState,
+        mut time: Time,
+        at_time: impl FnOnce(&mut Self, &mut WorldState),
+    ) {
+        if let Some(daily) = &state.daily {
+            // the daily challenge freezes once its time is up
+            if state.time >= daily.ends_at() {
+                at_time(self, state);
+                return;
+            }
+            time = time.min(daily.ends_at());
+        }
+        self.flush_clicks(state);
+        for combo in &mut self.combos {
+            combo.update(time);
+        }
+        let powersave = state.is_powersaving();
+        if powersave != self.powersave {
+            // requests in flight were admitted under the previous capacity
+            for node in &mut state.nodes {
+                node.powersave_grace = if powersave { node.processing } else { 0 };
+            }
+            // a missed payment ends the streak
+            if powersave {
+                state.electricity.on_time_streak = 0;
+            }
+        }
+        self.powersave = powersave;
+
+        // process events until the given time
+        while let Some(next_event_time) = self.queue.next_event_time() {
+            // add a safety net from events
+            // which are in the past but were not consumed
+            if next_event_time < state.time {
+                continue;
+            }
+            if next_event_time > time {
+                break;
+            }
+            let event = self.queue.pop().unwrap();
+            self.process_event(state, time, event);
+        }
+
+        let duration = time - state.time;
+
+        // check whether to do a major update
+        let major = duration > 0 && periods_crossed(state.time, time, MAJOR_UPDATE_PERIOD) > 0;
+
+        // update time
+        let previous_time = std::mem::replace(&mut state.time, time);
+
+        // the staff work at a steady pace
+        self.schedule_staff_work(state, previous_time);
+
+        // the daily challenge brings attackers early,
+        // whatever the demand
+        let dos_wave_at = state.daily.map(|daily| daily.modifiers.dos_wave_at);
+        if let Some(at) = dos_wave_at.filter(|&at| previous_time < at && at <= time) {
+            let targets: Vec<_> = DOS_TARGETS
+                .into_iter()
+                .filter(|&kind| state.service_by_kind(kind).state.is_unlocked())
+                .collect();
+            if state.can_spawn_attackers() {
+                self.log.push(at, t!("log.daily_dos_wave"));
+            }
+            self.spawn_attackers(state, &targets);
+        }
+        if state.is_daily_over() {
+            self.log.push(time, t!("log.daily_over"));
+            self.save_requested = true;
+        }
+
+        at_time(self, state);
+
+        if major {
+            // do a major update,
+            // whose periods count from the previous time
+            state.time = previous_time;
+            self.update_major(state, time);
+            state.time = time;
+
+            self.return_churned_customers(state);
+            // chime when new project cards become available
+            self.update_visible_cards(state);
+            if !self.penalty_cards.is_empty() {
+                self.charge_penalties(state);
+                self.update_visible_cards(state);
+            }
+            self.drop_stale_pins(state);
+        }
+    }
+
+    /// Use all penalty cards which appeared,
+    /// regardless of whether the player can afford them.
+    fn charge_penalties(&mut self, state: &mut WorldState) {
+        for index in std::mem::take(&mut self.penalty_cards) {
+            let card = &ALL_CARDS[index];
+            self.apply_card(state, card);
+            self.log.push(
+                state.time,
+                format!("{}: {}", card.title(), card.description()),
+            );
+            state.cards_used.push(UsedCard {
+                id: card.id.into(),
+                time: state.time,
+            });
+        }
+        state
+            .cards_used
+            .sort_unstable_by(|c1, c2| c1.id.cmp(&c2.id));
+    }
+
+    /// Schedule the player clicks accumulated since the last update,
+    /// coalesced into a few request sets per service.
+    fn flush_clicks(&mut self, state: &WorldState) {
+        for (kind, amount) in std::mem::take(&mut self.pending_clicks) {
+            self.push_player_requests(state, kind, amount, false);
+        }
+    }
+
+    /// Schedule the ops performed by the staff
+    /// for every second which went by since the given time,
+    /// coalesced into a few request sets per service.
+    fn schedule_staff_work(&mut self, state: &WorldState, previous_time: Time) {
+        let periods = periods_crossed(previous_time, state.time, STAFF_WORK_PERIOD);
+        if periods == 0 {
+            return;
+        }
+        let ops = staff::ops_per_second(&state.staff);
+        for (kind, ops) in ServiceKind::ALL.into_iter().zip(ops) {
+            let amount = (ops as u64 * periods).min(u32::MAX as u64) as u32;
+            if amount > 0 {
+                self.push_player_requests(state, kind, amount, true);
+            }
+        }
+    }
+
+    /// Schedule requests which belong to no cloud user,
+    /// made by the player or their staff.
+    fn push_player_requests(
+        &mut self,
+        state: &WorldState,
+        kind: ServiceKind,
+        mut amount: u32,
+        staff: bool,
+    ) {
+        let time = state.time + 1;
+        // split into request sets which fit comfortably
+        // in the memory of the smallest node,
+        // but no fewer than there are cores to process them
+        // and not so many that batching stops
+        let mem_chunk = state
+            .nodes
+            .iter()
+            .map(|node| {
+                let free = node.ram_capacity - node.ram_reserved;
+                (free.ratio(kind.mem_required()) / 4.) as u32
+            })
+            .min()
+            .unwrap_or_default();
+        let total_cores: u32 = state.nodes.iter().map(|node| node.num_cores).sum();
+        let chunk = mem_chunk
+            .min(amount.div_ceil(total_cores.max(1)))
+            .max(amount.div_ceil(MAX_PLAYER_REQUEST_SETS))
+            .max(1);
+
+        while amount > 0 {
+            let n = amount.min(chunk);
+            let event = RequestEvent::new_arrived(time, None, n, kind, false);
+            self.queue
+                .push(if staff { event.by_staff() } else { event });
+            amount -= n;
+        }
+    }
+
+    /// Record a failure to release memory from a node,
+    /// with what the engine was doing at the time.
+    fn audit_release<T>(
+        warnings: &mut console::Warnings,
+        anomalies: &mut u64,
+        time: Time,
+        released: Result<T, MemoryAccountingError>,
+        context: &str,
+    ) {
+        if let Err(e) = released {
+            *anomalies += 1;
+            warnings.push(time, format!("{e} ({context})"));
+        }
+    }
+
+    /// Count a set of requests as dropped,
+    /// attributing them to their traffic class.
+    ///
+    /// Bad traffic is left out of the recent drop count,
+    /// since dropping it does not harm the business.
+    fn count_dropped(
+        recent_dropped: &mut u64,
+        recent_drop_causes: &mut [u64; DropCause::ALL.len()],
+        player_ops: &mut [PlayerOps; ServiceKind::ALL.len()],
+        state: &mut WorldState,
+        event: &RequestEvent,
+        time: Time,
+        cause: DropCause,
+    ) {
+        let amount = event.amount;
+        let class = state.traffic_class(event, time);
+        state.requests_dropped += amount as u64;
+        state.traffic[class as usize].dropped += amount as u64;
+        if matches!(class, TrafficClass::Paying | TrafficClass::Trial) {
+            state.count_sla_requests(event.service, 0, amount as u64);
+            if let Some(policy) = &mut state.insurance {
+                policy.count_dropped(event.service, amount as u64);
+            }
+        }
+        if class != TrafficClass::Bad {
+            *recent_dropped += amount as u64;
+            recent_drop_causes[cause as usize] += amount as u64;
+        }
+        if event.user_spec_id.is_none() && !event.staff {
+            player_ops[event.service.to_code() as usize].dropped += amount;
+        }
+    }
+
+    /// Resolve the pending narrative event if left unanswered for too long,
+    /// or otherwise roll for a new one.
+    fn update_narrative(&mut self, state: &mut WorldState, time: Time) {
+        if let Some(pending) = state.narrative.pending() {
+            if pending.is_timed_out(time) {
+                let id = pending.id.clone();
+                if let Some(event) = pending.event() {
+                    let choice = event.choices[SAFE_CHOICE].label();
+                    self.log
+                        .push(time, t!("log.event_auto_resolved", choice = choice));
+                }
+                if self.resolve_event(state, &id, SAFE_CHOICE).is_err() {
+                    // an event from another version of the game
+                    state.narrative.resolve(&id);
+                }
+            }
+            return;
+        }
+
+        for _ in 0..periods_crossed(state.time, time, NARRATIVE_ROLL_PERIOD) {
+            if !state.narrative.can_fire(time) {
+                break;
+            }
+            if !self.gen.gen_bool(NARRATIVE_EVENT_CHANCE) {
+                continue;
+            }
+            let eligible = eligible_events(state);
+            if eligible.is_empty() {
+                break;
+            }
+            let event = eligible[self.gen.gen_range(0, eligible.len() as u32) as usize];
+            state.narrative.fire(event.id, time);
+            self.audio_events.push(AudioEvent::Warning);
+            break;
+        }
+    }
+
+    /// Perform the periodic work due between the state's time and the given time.
+    ///
+    /// When an update spans several periods at once,
+    /// work which accumulates (demand growth, loan interest,
+    /// electricity bills and period reports)
+    /// is done once for each period crossed,
+    /// so that the outcome does not depend on how often the game is updated.
+    /// Energy consumed is not tracked over time,
+    /// so all of it goes into the bill of the first period crossed.
+    /// Work which only catches up with the present
+    /// (timeout cleanup, saving the game) is done at most once.
+    fn update_major(&mut self, state: &mut WorldState, time: Time) {
+        self.update_narrative(state, time);
+
+        let config = &self.config;
+
+        // check whether to increase demand from time passing by,
+        // unless the hardware cannot keep up with it
+        let was_high_demand = state.demand > config.demand_dos_threshold;
+        if !self.growth_stalled {
+            let periods = periods_crossed(state.time, time, config.increase_demand_period);
+            for _ in 0..periods {
+                // increase demand a tiny bit
+                state.demand += state.demand_rate * config.demand_growth;
+            }
+        }
+        // demand growing on its own draws attackers too
+        if !was_high_demand && state.demand > config.demand_dos_threshold {
+            self.spawn_attackers(state, &DOS_TARGETS);
+        }
+        let config = &self.config;
+        state.peak_demand = state.peak_demand.max(state.demand);
+
+        // services under maintenance lose a bit of demand every full minute
+        for kind in ServiceKind::ALL {
+            let Some(since) = state.service_by_kind(kind).maintenance_since else {
+                continue;
+            };
+            let minutes = (time.saturating_sub(since) / TIME_UNITS_PER_MINUTE)
+                .saturating_sub(state.time.saturating_sub(since) / TIME_UNITS_PER_MINUTE);
+            if minutes > 0 {
+                state.service_by_kind_mut(kind).maintenance_demand_factor *=
+                    (1. - MAINTENANCE_DEMAND_DECAY).powi(minutes as i32);
+            }
+        }
+
+        // an unused bonus for returning to the game expires
+        if state.away_bonus.is_some_and(|bonus| bonus.is_expired(time)) {
+            state.away_bonus = None;
+        }
+
+        // calculate energy consumption
+        state.electricity.calculate_consumption_rate();
+
+        // check whether to issue electricity bills
+        let bill_periods = periods_crossed(state.time, time, config.electricity_bill_period);
+        for _ in 0..bill_periods {
+            // charge interest on the loan
+            if let Some(loan) = &mut state.loan {
+                loan.accrue_interest(LOAN_INTEREST_RATE);
+            }
+
+            // check whether we have enough costs to worth issuing a bill
+            let total_cost = state
+                .electricity
+                .check_bill(&config.electricity_cost_levels);
+            if total_cost > Money::cents(50) {
+                // bills paid before this one extend the streak
+                Self::update_bill_streak(&mut self.log, state, time);
+                // issue an electricity bill
+                state.electricity.emit_bill_for(total_cost, time);
+                self.audio_events.push(AudioEvent::Bill);
+            }
+
+            // pay the staff, who quit if they cannot be paid
+            for member in state.pay_staff() {
+                self.log.push(
+                    time,
+                    t!(
+                        "log.staff_quit",
+                        tier = member.tier,
+                        service = member.service
+                    ),
+                );
+            }
+
+            // charge for the service-level agreements broken
+            for sla in &mut state.slas {
+                let drop_rate = sla.drop_rate();
+                if let Some(penalty) = sla.close_period() {
+                    state.electricity.charge_penalty(penalty, time);
+                    self.log.push(
+                        time,
+                        t!(
+                            "log.sla_penalty",
+                            service = sla.service,
+                            rate = format!("{:.1}%", drop_rate * 100.),
+                            penalty = penalty
+                        ),
+                    );
+                }
+            }
+
+            // charge the insurance premium,
+            // and compensate the revenue lost to dropped requests
+            let prices = ServiceKind::ALL.map(|kind| state.service_by_kind(kind).price);
+            if let Some(policy) = &mut state.insurance {
+                state.electricity.charge_penalty(policy.premium, time);
+                let dropped = policy.total_dropped();
+                if let Some(payout) = policy.close_period(&prices) {
+                    state.funds += payout;
+                    self.log.push(
+                        time,
+                        t!(
+                            "log.insurance_payout",
+                            payout = payout,
+                            count = Separating(dropped as i64)
+                        ),
+                    );
+                }
+            }
+
+            // summarize the period that just ended
+            let snapshot = PeriodSnapshot::capture(state);
+            if let Some(start) = &state.period_snapshot {
+                if state.reports.len() >= MAX_PERIOD_REPORTS {
+                    state.reports.remove(0);
+                }
+                state
+                    .reports
+                    .push(PeriodReport::between(start, &snapshot, time));
+                state.report_dismissed = false;
+            }
+            state.period_snapshot = Some(snapshot);
+        }
+        if bill_periods > 0 {
+            self.settle_bills(state, time);
+        }
+        let config = &self.config;
+
+        // check whether the player has gone bankrupt
+        if state.funds < BANKRUPTCY_THRESHOLD {
+            if state.insolvent_since == 0 {
+                state.insolvent_since = time;
+            } else if !state.bailout_pending
+                && time - state.insolvent_since >= config.electricity_bill_period
+            {
+                // offer a bailout (only once until the player decides)
+                state.bailout_pending = true;
+                self.audio_events.push(AudioEvent::Warning);
+            }
+        } else {
+            state.insolvent_since = 0;
+        }
+
+        // reveal more of the hardware panel as milestones are reached
+        let milestone = state.hardware_panel_milestone();
+        if milestone > state.hardware_panel_level {
+            state.hardware_panel_level = milestone;
+            let message = match milestone {
+                HARDWARE_PANEL_LOAD_BARS => t!("log.hardware_load_bars"),
+                HARDWARE_PANEL_UPGRADES => t!("log.hardware_upgrades"),
+                _ => t!("log.hardware_purchases"),
+            };
+            self.log.push(time, message);
+        }
+
+        // bring restarted nodes back up,
+        // and release the memory reserve of nodes left idle for a while
+        let routing_needed =
+            state.nodes.len() > 1 && state.routing_level != RoutingLevel::NoRoutingCost;
+        self.idle_since.resize(state.nodes.len(), 0);
+        for (node, idle_since) in state.nodes.iter_mut().zip(&mut self.idle_since) {
+            if node.restarting_until > 0 && !node.is_restarting(time) {
+                node.restarting_until = 0;
+                // take on the requests left waiting while it was down
+                let node_num = node.id;
+                for _ in 0..node.free_cores(self.powersave) {
+                    let Some(request) = self.waiting_queue.pop_front() else {
+                        break;
+                    };
+                    let duration = if routing_needed {
+                        node.processing += 1;
+                        node.time_per_request_routing() * request.amount
+                    } else {
+                        0
+                    };
+                    self.queue.push(RequestEvent {
+                        timestamp: time + duration as Time,
+                        user_spec_id: request.user_spec_id,
+                        amount: request.amount,
+                        service: request.service,
+                        bad: request.bad,
+                        staff: request.staff,
+                        kind: RequestEventStage::RequestRouted { node_num },
+                    });
+                }
+            }
+
+            if !node.is_idle() {
+                *idle_since = 0;
+            } else if *idle_since == 0 {
+                *idle_since = time;
+            } else if time - *idle_since >= config.timeout_cleanup_period {
+                Self::audit_release(
+                    &mut self.warnings,
+                    &mut self.accounting_anomalies,
+                    time,
+                    node.release_excess_reserve(Memory::zero()),
+                    "releasing the reserve of an idle node",
+                );
+            }
+        }
+
+        // check whether to cleanup timed out requests
+        if periods_crossed(state.time, time, config.timeout_cleanup_period) > 0 {
+            // clean up waiting requests for each node
+            let mut timedout = vec![];
+            for node in &mut state.nodes {
+                let (requests, released) =
+                    node.clear_timedout_requests(time, config.request_timeout);
+                Self::audit_release(
+                    &mut self.warnings,
+                    &mut self.accounting_anomalies,
+                    time,
+                    released,
+                    "clearing timed out requests",
+                );
+                timedout.extend(requests);
+            }
+            for request in timedout {
+                let event = RequestEvent::new_arrived(
+                    request.timestamp,
+                    request.user_spec_id,
+                    request.amount,
+                    request.service,
+                    false,
+                );
+                Self::count_dropped(
+                    &mut self.recent_requests_dropped,
+                    &mut self.recent_drop_causes,
+                    &mut self.player_ops,
+                    state,
+                    &event,
+                    time,
+                    DropCause::Cpu,
+                );
+            }
+
+            // archive the revenue of customers who left
+            state.customer_revenue.prune(&state.user_specs);
+        }
+
+        // check whether to save the game
+        if periods_crossed(state.time, time, config.game_save_period) > 0 {
+            // let the owner of the state save the game
+            self.save_requested = true;
+        }
+
+        // if player has unlocked it,
+        // calculate request statistics
+        if state.can_see_request_rates {
+            let total_requests = self.recent_requests_fulfilled
+                + self.recent_requests_dropped
+                + self.recent_requests_failed;
+            if total_requests > 0 {
+                self.drop_rate = self.recent_requests_dropped as f32 / total_requests as f32;
+                self.failure_rate = self.recent_requests_failed as f32 / total_requests as f32;
+            } else {
+                log::debug!("Skipping req rate calculation because total requests is zero");
+            }
+        }
+        self.update_capacity_pressure(time);
+        self.update_queue_saturation(state, time);
+        self.update_queue_pressure(state);
+        self.update_bundle_caps(state);
+        self.update_cache_performance();
+        self.update_routing_latency();
+        for (total, recent) in self
+            .total_drop_causes
+            .iter_mut()
+            .zip(self.recent_drop_causes)
+        {
+            *total += recent;
+        }
+        self.drop_causes = std::mem::take(&mut self.recent_drop_causes);
+        // reset counters
+        self.recent_requests_fulfilled = 0;
+        self.recent_requests_dropped = 0;
+        self.recent_requests_failed = 0;
+    }
+
+    /// Extend the on-time streak if the bills were paid
+    /// before the next one is issued, or reset it otherwise,
+    /// granting the reward for the milestone reached if any.
+    fn update_bill_streak(log: &mut NotificationLog, state: &mut WorldState, time: Time) {
+        let electricity = &mut state.electricity;
+        if electricity.last_bill == Money::zero() {
+            // nothing was billed before
+            return;
+        }
+        if electricity.last_bill_time != 0 {
+            electricity.on_time_streak = 0;
+            return;
+        }
+        electricity.on_time_streak += 1;
+        let streak = electricity.on_time_streak;
+        let Some(reward) = StreakReward::reached(streak, electricity.streak_rewards) else {
+            return;
+        };
+        electricity.streak_rewards += 1;
+        match reward {
+            StreakReward::Rebate => state.funds += rebate(electricity.last_bill),
+            StreakReward::DemandBoost => {
+                state.demand_boost_until = time + STREAK_DEMAND_BOOST_DURATION;
+            }
+            StreakReward::LoyaltyDiscount => electricity.loyalty_discount = true,
+        }
+        log.push(
+            time,
+            t!("log.streak_reward", streak = streak, reward = reward),
+        );
+    }
+
+    /// Settle the bills just emitted,
+    /// first from the prepaid credit
+    /// and then from the funds if auto-pay is on.
+    ///
+    /// If the funds fall short,
+    /// the bills are left for the player to pay as usual.
+    fn settle_bills(&mut self, state: &mut WorldState, time: Time) {
+        state.electricity.draw_credit();
+        let bill = state.electricity.bill_amount();
+        if !state.auto_pay || bill == Money::zero() {
+            return;
+        }
+        if state.funds >= bill {
+            // paid in full, as if the player clicked the button
+            let _ = self.apply_action(state, PlayerAction::PayElectricityBill);
+        } else {
+            self.log.push(time, t!("log.auto_pay_failed", bill = bill));
+        }
+    }
+
+    /// Update the smoothed drop rate from the recent counters,
+    /// stalling or resuming demand growth accordingly.
+    fn update_capacity_pressure(&mut self, time: Time) {
+        let total_requests = self.recent_requests_fulfilled + self.recent_requests_dropped;
+        if total_requests == 0 {
+            return;
+        }
+        let drop_rate = self.recent_requests_dropped as f32 / total_requests as f32;
+        self.capacity_pressure += (drop_rate - self.capacity_pressure) * DROP_RATE_SMOOTHING;
+
+        if !self.growth_stalled && self.capacity_pressure > GROWTH_STALL_DROP_RATE {
+            self.growth_stalled = true;
+            self.log.push(time, t!("log.growth_stalled"));
+        } else if self.growth_stalled && self.capacity_pressure < GROWTH_RESUME_DROP_RATE {
+            self.growth_stalled = false;
+            self.log.push(time, t!("log.growth_resumed"));
+        }
+    }
+
+    /// Announce when the waiting queue is no longer saturated,
+    /// once it drains well below its capacity,
+    /// so that a queue on the edge does not flood the log.
+    fn update_queue_saturation(&mut self, state: &WorldState, time: Time) {
+        if self.queue_saturated && self.waiting_queue_occupancy(state) < WAITING_QUEUE_CLEAR_SHARE {
+            self.queue_saturated = false;
+            self.log.push(time, t!("log.queue_cleared"));
+        }
+    }
+
+    /// Estimate the wait in the node queues for each service,
+    /// letting its demand decay while the wait is too long
+    /// and recover once the queues clear.
+    ///
+    /// Unlike the capacity pressure,
+    /// this kicks in before any request is dropped,
+    /// and only for the services which are backlogged.
+    fn update_queue_pressure(&mut self, state: &WorldState) {
+        let waits = queue_waits(state);
+        for kind in ServiceKind::ALL {
+            let pressure = waits[kind as usize] / QUEUE_PRESSURE_WAIT as f64;
+            self.queue_pressure[kind as usize] = pressure as f32;
+            let factor = &mut self.queue_demand_factor[kind as usize];
+            *factor = if pressure > 1. {
+                (*factor * (1. - QUEUE_PRESSURE_DECAY)).max(MIN_QUEUE_DEMAND_FACTOR)
+            } else {
+                (*factor + QUEUE_PRESSURE_RECOVERY).min(1.)
+            };
+        }
+    }
+
+    /// Measure the cache performance from the recent lookups,
+    /// keeping the last measurement if there were none.
+    fn update_cache_performance(&mut self) {
+        if self.recent_cache_lookups > 0 {
+            self.cache_hit_rate = self.recent_cache_hits as f32 / self.recent_cache_lookups as f32;
+            self.cache_speedup =
+                self.recent_uncached_time as f32 / self.recent_cached_time.max(1) as f32;
+        }
+        self.recent_cache_lookups = 0;
+        self.recent_cache_hits = 0;
+        self.recent_uncached_time = 0;
+        self.recent_cached_time = 0;
+    }
+
+    /// Measure the latency added by routing from the recent requests,
+    /// keeping the last measurement if there were none.
+    fn update_routing_latency(&mut self) {
+        if self.recent_routed > 0 {
+            self.routing_latency = self.recent_routing_latency as f32 / self.recent_routed as f32;
+        }
+        self.recent_routed = 0;
+        self.recent_routing_latency = 0;
+    }
+
+    /// process a single request event
+    fn process_event(&mut self, state: &mut WorldState, time: Time, event: RequestEvent) {
+        let daily_cycle = self.daily_cycle(event.timestamp);
+        // energy consumed during the day is billed at peak rates
+        let peak = self.day_phase(event.timestamp) < 0.5;
+        // closure to add a new event to the main queue
+        let mut push_event = |event: RequestEvent| {
+            self.queue.push(event);
+        };
+
+        match event.kind {
+            RequestEventStage::RequestArrived { chained } => {
+                if event.user_spec_id.is_some()
+                    && !event.bad
+                    && state.service_by_kind(event.service).in_maintenance()
+                {
+                    // customer traffic is paused,
+                    // arrivals are bootstrapped again once maintenance is over
+                    return;
+                }
+                let powersave = self.powersave;
+                // route the request if necessary
+                let node_count = state.nodes.len() as u32;
+                if node_count == 1 {
+                    // immediately route to the only node
+                    push_event(event.into_routed(0, 0));
+                } else if state.routing_level == RoutingLevel::NoRoutingCost {
+                    // immediately route to a random node
+                    let node_num = self.gen.gen_range(0, node_count);
+                    push_event(event.into_routed(0, node_num));
+                } else {
+                    // route the request:
+
+                    // check if any node is not busy
+                    if state.nodes.iter().all(|node| node.is_busy(powersave, time)) {
+                        // enqueue it unless the waiting queue is too large already
+                        let capacity = self
+                            .config
+                            .waiting_queue_capacity(state.nodes.len(), state.routing_level);
+                        if self.waiting_queue.len() >= capacity {
+                            if !self.queue_saturated {
+                                self.queue_saturated = true;
+                                self.log.push(time, t!("log.queue_saturated"));
+                            }
+                            // drop the request
+                            Self::count_dropped(
+                                &mut self.recent_requests_dropped,
+                                &mut self.recent_drop_causes,
+                                &mut self.player_ops,
+                                state,
+                                &event,
+                                time,
+                                DropCause::Queue,
+                            );
+                        } else {
+                            // enqueue it
+                            self.waiting_queue.push_back(WaitingRouteRequest {
+                                amount: event.amount,
+                                user_spec_id: event.user_spec_id,
+                                service: event.service,
+                                bad: event.bad,
+                                staff: event.staff,
+                            });
+                        }
+                    } else {
+                        // pick a routing node
+                        let node_num = if state.routing_level == RoutingLevel::Distributed {
+                            loop {
+                                let n = self.gen.gen_range(0, node_count);
+                                let picked_node = state.node(n).unwrap();
+                                // if node is not busy, use it
+                                if !picked_node.is_busy(powersave, time) {
+                                    break n;
+                                }
+                                // otherwise put it on the waiting queue
+                            }
+                        } else {
+                            // always use the first one
+                            // until the player gets the upgrade
+                            0
+                        };
+                        // add processing to the routing node
+                        let node = state.node_mut(node_num).unwrap();
+                        // drop request if node is busy
+                        if node.is_busy(powersave, time) {
+                            Self::count_dropped(
+                                &mut self.recent_requests_dropped,
+                                &mut self.recent_drop_causes,
+                                &mut self.player_ops,
+                                state,
+                                &event,
+                                time,
+                                DropCause::Cpu,
+                            );
+                        } else {
+                            node.processing += 1;
+                            let duration = node.time_per_request_routing() * event.amount;
+
+                            // 2. push event to request routed
+                            push_event(event.into_routed(duration, node_num));
+                        }
+                    }
+                }
+
+                let mut should_drop_spec = false;
+                if let Some(user_spec_id) = event.user_spec_id.filter(|_| chained) {
+                    // also generate a new request for the upcoming request
+                    // from the same client spec
+                    if let Some(spec) = &state.user_spec(user_spec_id) {
+                        // check trial period
+                        let demand = Self::user_demand(
+                            state,
+                            spec,
+                            daily_cycle as f64,
+                            &self.queue_demand_factor,
+                        );
+                        if let Some(demand) =
+                            demand.filter(|_| spec.trial_time > time || spec.trial_time == 0)
+                        {
+                            // determine demand for the service by this spec
+                            let (demand, amount) =
+                                Self::group_demand(demand, self.bundle_caps[spec.service as usize]);
+                            let duration = self.gen.next_request(demand);
+                            let timestamp = event.timestamp + duration * event.amount as u64;
+                            push_event(RequestEvent::new_arrived(
+                                timestamp,
+                                event.user_spec_id,
+                                amount,
+                                spec.service,
+                                spec.is_bad(),
+                            ));
+                        } else if spec.trial_time > 0 || demand.is_none() {
+                            // trial period over,
+                            // or an attacker kept out by the spam protection
+                            should_drop_spec = true;
+                        }
+
+                        if should_drop_spec {
+                            if spec.kind == UserKind::Customer && spec.trial_time > 0 {
+                                // let the player know, and offer to extend the trial
+                                let trial = ExpiredTrial {
+                                    spec: (*spec).clone(),
+                                    expired_at: time,
+                                    ops: state.customer_revenue.ops_of(user_spec_id),
+                                };
+                                self.log.push(
+                                    time,
+                                    t!(
+                                        "log.trial_expired",
+                                        service = spec.service,
+                                        ops = Ops(trial.ops as i64),
+                                    ),
+                                );
+                                if self.expired_trials.len() >= MAX_EXPIRED_TRIALS {
+                                    self.expired_trials.remove(0);
+                                }
+                                self.expired_trials.push(trial.clone());
+                                self.new_expired_trials.push(trial);
+                            }
+                            // clean up unused user spec
+                            // (this is safe because the user spec ID is unique)
+                            state.user_specs.remove(user_spec_id);
+                        }
+                    } else {
+                        self.warnings.push(
+                            event.timestamp,
+                            format!("Invalid user specification ID {user_spec_id}"),
+                        );
+                    }
+                }
+            }
+            RequestEventStage::RequestRouted { node_num } => {
+                let software_level = state.software_level;
+                let cache_level = state.cache_level;
+                let cache_fraction = state.cache_fraction;
+                let powersave = self.powersave;
+                let routing_needed =
+                    state.nodes.len() > 1 && state.routing_level != RoutingLevel::NoRoutingCost;
+                let Some(routing_node) = state.node_mut(node_num) else {
+                    return;
+                };
+
+                // 1. if required, decrement processing on the routing node
+                if routing_needed {
+                    if !routing_node.release_core() {
+                        self.warnings.push(
+                            event.timestamp,
+                            format!(
+                                "Processing count of routing node {} is zero, there is probably a bug",
+                                routing_node.id
+                            ),
+                        );
+                    }
+                    // add small electricity cost
+                    if !powersave {
+                        routing_node.lifetime.energy += 10;
+                        state.electricity.add_consumption_during(10, peak);
+                    }
+                }
+
+                // spam detection
+                if event.bad && state.spam_protection > 0. {
+                    let detected = self.gen.gen_bool(state.spam_protection);
+                    if detected {
+                        // dropped intentionally
+                        return;
+                    }
+                };
+
+                // 2. pick a request processing node
+                let routing_node_num = node_num;
+                let node_num = self.gen.gen_range(0, state.nodes.len() as u32);
+
+                // a restarting node cannot take it,
+                // so leave it waiting to be routed again
+                if state
+                    .node(node_num)
+                    .is_some_and(|node| node.is_restarting(time))
+                {
+                    self.waiting_queue.push_back(WaitingRouteRequest {
+                        amount: event.amount,
+                        user_spec_id: event.user_spec_id,
+                        service: event.service,
+                        bad: event.bad,
+                        staff: event.staff,
+                    });
+                    return;
+                }
+
+                // bad requests which got past detection
+                // may still be caught while processing
+                let outcome = if event.bad {
+                    BadRequestOutcome::roll(&mut self.gen, state.spam_protection)
+                } else {
+                    BadRequestOutcome::Unchecked
+                };
+
+                // 3. check memory reserve requirement
+                let mem_reserve_required = Self::calculate_memory_reserve_required(
+                    event.service,
+                    state.cache_level,
+                    state.cache_fraction,
+                    state.software_level,
+                );
+                let node = state.node_mut(node_num).unwrap();
+
+                let reserved = node.reserve_for(mem_reserve_required);
+                Self::audit_release(
+                    &mut self.warnings,
+                    &mut self.accounting_anomalies,
+                    event.timestamp,
+                    reserved,
+                    "growing the memory reserve",
+                );
+                if reserved != Ok(true) {
+                    // can't reserve, drop the request
+                    Self::count_dropped(
+                        &mut self.recent_requests_dropped,
+                        &mut self.recent_drop_causes,
+                        &mut self.player_ops,
+                        state,
+                        &event,
+                        time,
+                        DropCause::Ram,
+                    );
+                    return;
+                }
+
+                // 4. check memory requirement for request
+                let mem_required =
+                    outcome.scale_memory(event.service.mem_required() * event.amount as i32);
+                if mem_required > node.ram_capacity - node.ram_usage {
+                    // 4.1. if not enough memory, drop the request.
+                    Self::count_dropped(
+                        &mut self.recent_requests_dropped,
+                        &mut self.recent_drop_causes,
+                        &mut self.player_ops,
+                        state,
+                        &event,
+                        time,
+                        DropCause::Ram,
+                    );
+                    return;
+                }
+
+                // 5. check that there is bandwidth left to serve it,
+                // only taking it for requests which are served
+                let bandwidth = event.service.bandwidth_required() as u64 * event.amount as u64;
+                if !self.bandwidth.take(
+                    event.timestamp,
+                    bandwidth,
+                    bandwidth_capacity(state.bandwidth_level),
+                ) {
+                    Self::count_dropped(
+                        &mut self.recent_requests_dropped,
+                        &mut self.recent_drop_causes,
+                        &mut self.player_ops,
+                        state,
+                        &event,
+                        time,
+                        DropCause::Bandwidth,
+                    );
+                    return;
+                }
+
+                // 6. add memory usage to the processing node
+                let node = state.node_mut(node_num).unwrap();
+                node.ram_usage += mem_required;
+
+                // the request takes longer the farther it was routed
+                let latency = Hop::between(state, routing_node_num, node_num)
+                    .latency(state.routing_level)
+                    * event.amount;
+                self.recent_routed += event.amount as u64;
+                self.recent_routing_latency += latency as u64;
+                let node = state.node_mut(node_num).unwrap();
+
+                // 7. if node has a CPU available,
+                if node.free_cores(powersave) >= 1 {
+                    // calculate time to process the request
+                    let mut duration =
+                        node.time_per_request(event.service, software_level) * event.amount;
+
+                    // if in powersave mode, make it slower
+                    if powersave {
+                        duration *= 4;
+                    }
+                    duration = outcome.scale_duration(duration);
+
+                    // test whether this request will hit the cache
+                    let cache_rate = cache_hit_rate(cache_level, cache_fraction);
+                    let cache_hit = self.gen.gen_bool(cache_rate);
+                    let uncached_duration = duration;
+                    if cache_hit {
+                        // make it much faster
+                        duration = (duration / CACHE_HIT_SPEEDUP).max(1);
+                    }
+                    if cache_level > 0 {
+                        self.recent_cache_lookups += 1;
+                        self.recent_cache_hits += cache_hit as u64;
+                        self.recent_uncached_time += uncached_duration as u64;
+                        self.recent_cached_time += duration as u64;
+                    }
+                    duration += latency;
+                    self.processing_times.record(
+                        event.service,
+                        duration / event.amount.max(1),
+                        event.amount,
+                        cache_hit,
+                    );
+
+                    //  & increment CPU usage
+                    node.processing += 1;
+                    if outcome == BadRequestOutcome::Mitigated {
+                        state.attacks_mitigated += event.amount as u64;
+                    }
+                    //  & push request processed event to the queue
+                    push_event(event.into_processed(node_num, duration, mem_required));
+                } else {
+                    // add to waiting queue
+                    node.requests.push_back(WaitingRequest {
+                        timestamp: event.timestamp,
+                        amount: event.amount,
+                        user_spec_id: event.user_spec_id,
+                        service: event.service,
+                        staff: event.staff,
+                        mem_required,
+                        outcome,
+                        latency,
+                    });
+                }
+            }
+            RequestEventStage::RequestProcessed {
+                node_num,
+                ram_required,
+            } => {
+                let powersave = self.powersave;
+                let routing_level = state.routing_level;
+                let routing_needed =
+                    state.nodes.len() > 1 && routing_level != RoutingLevel::NoRoutingCost;
+                let software_level = state.software_level;
+                let Some(energy_factor) = state.node(node_num).map(|node| node.energy_factor())
+                else {
+                    return;
+                };
+                if let Some(purchase) = &mut self.last_purchase {
+                    purchase.node_used |= purchase.node == node_num;
+                }
+
+                // 1. add electricity consumption
+                // (more if the node is overclocked)
+                let consumption = if powersave {
+                    0
+                } else {
+                    (1_000. * energy_factor).round() as i64
+                };
+                if consumption > 0 {
+                    state.electricity.add_consumption_during(consumption, peak);
+                }
+
+                // 2. increment op counts (available & total)
+                let service = state.service_by_kind_mut(event.service);
+                if !event.bad {
+                    service.total += Ops(event.amount as i64);
+                    service.available += Ops(event.amount as i64);
+                }
+                let service_price = service.price;
+                let service_entitlement = service.entitlement;
+
+                // 3. calculate revenue if applicable
+                let revenue = if !event.bad {
+                    if let Some(id) = event.user_spec_id {
+                        if let Some(spec) = state.user_spec(id).cloned() {
+                            if spec.kind == UserKind::Researcher {
+                                // researchers pay nothing,
+                                // but advance research instead
+                                state.research_progress += event.amount as u64;
+                                Money::zero()
+                            } else {
+                                let revenue = if spec.is_paying(time) {
+                                    service_price * event.amount as i32 + service_entitlement
+                                } else {
+                                    // within trial period
+                                    service_entitlement
+                                };
+                                state
+                                    .customer_revenue
+                                    .record(&spec, revenue, event.amount as u64);
+                                revenue
+                            }
+                        } else {
+                            // specification was deleted,
+                            // treat it as clicked by player
+                            service_entitlement
+                        }
+                    } else {
+                        // player request
+                        service_entitlement
+                    }
+                } else {
+                    // bad request
+                    Money::zero()
+                };
+
+                let node = state.node_mut(node_num).unwrap();
+                node.lifetime.requests_processed += event.amount as u64;
+                if !event.bad {
+                    node.lifetime.ops_served += event.amount as u64;
+                }
+                node.lifetime.energy += consumption;
+                // 4. decrement memory usage
+                Self::audit_release(
+                    &mut self.warnings,
+                    &mut self.accounting_anomalies,
+                    event.timestamp,
+                    node.try_release(ram_required),
+                    "finishing a request",
+                );
+
+                let node_num = node.id;
+
+                // 5. if there are routing requests waiting
+                if !self.waiting_queue.is_empty()
+                    && (node_num == 0 || routing_level == RoutingLevel::Distributed)
+                {
+                    let request = self.waiting_queue.pop_front().unwrap();
+                    // pop one and route the request now using this node
+                    let duration = if routing_needed {
+                        node.time_per_request_routing() * request.amount
+                    } else {
+                        // no routing step will release this core
+                        // (requests only wait here after a node restart)
+                        node.release_core();
+                        0
+                    };
+
+                    // push event to request routed
+                    push_event(RequestEvent {
+                        timestamp: event.timestamp + duration as Time,
+                        user_spec_id: request.user_spec_id,
+                        amount: request.amount,
+                        service: request.service,
+                        bad: request.bad,
+                        staff: request.staff,
+                        kind: RequestEventStage::RequestRouted { node_num },
+                    });
+                } else {
+                    // decrement processing on the processing node
+                    if !node.release_core() {
+                        self.warnings.push(
+                            event.timestamp,
+                            format!(
+                                "Processing count of node {} is zero, there is probably a bug",
+                                node.id
+                            ),
+                        );
+                    }
+
+                    let cores_available = node.free_cores(powersave);
+                    for _ in 0..cores_available {
+                        // create a narrower borrow of the node
+                        // so I can query the state for user_specs
+                        let node = state.node_mut(node_num).unwrap();
+                        if let Some(request) = node.requests.pop_front() {
+                            // pop one and schedule a new request processed event
+                            let duration = request.outcome.scale_duration(
+                                node.time_per_request(event.service, software_level)
+                                    * request.amount,
+                            ) + request.latency;
+
+                            // increment processing
+                            node.processing += 1;
+
+                            let service = request.service;
+                            if request.outcome == BadRequestOutcome::Mitigated {
+                                state.attacks_mitigated += request.amount as u64;
+                            }
+                            let bad = if let Some(id) = request.user_spec_id {
+                                state
+                                    .user_spec(id)
+                                    .map(|spec| spec.is_bad())
+                                    .unwrap_or(false)
+                            } else {
+                                false
+                            };
+                            push_event(RequestEvent {
+                                timestamp: event.timestamp + duration as u64,
+                                user_spec_id: request.user_spec_id,
+                                amount: request.amount,
+                                service,
+                                bad,
+                                staff: request.staff,
+                                kind: RequestEventStage::RequestProcessed {
+                                    node_num,
+                                    ram_required: request.mem_required,
+                                },
+                            })
+                        } else {
+                            break;
+                        }
+                    }
+                }
+
+                self.recent_requests_fulfilled += event.amount as u64;
+                if event.user_spec_id.is_none() && !event.staff {
+                    self.player_ops[event.service.to_code() as usize].fulfilled += event.amount;
+                }
+                let class = state.traffic_class(&event, time);
+                state.traffic[class as usize].fulfilled += event.amount as u64;
+                if matches!(class, TrafficClass::Paying | TrafficClass::Trial) {
+                    state.count_sla_requests(event.service, event.amount as u64, 0);
+                }
+                // apply revenue
+                if revenue > Money::zero() {
+                    // part of it goes towards repaying the loan
+                    let repayment = match &mut state.loan {
+                        Some(loan) => loan.repay_from(revenue, LOAN_REPAYMENT_SHARE),
+                        None => Money::zero(),
+                    };
+                    if state.loan.as_ref().is_some_and(Loan::is_repaid) {
+                        state.loan = None;
+                    }
+                    state.funds += revenue - repayment;
+                    state.earned += revenue;
+                }
+                // apply bad request count
+                if event.bad {
+                    state.requests_failed += event.amount as u64;
+                    self.recent_requests_failed += event.amount as u64;
+                }
+            }
+        }
+    }
+
+    fn calculate_memory_reserve_required(
+        service: ServiceKind,
+        cache_level: u8,
+        cache_fraction: f32,
+        software_level: u8,
+    ) -> Memory {
+        service.tier().memory_reserve
+            * cache_memory_factor(cache_level, cache_fraction)
+            * SOFTWARE_LEVELS[software_level as usize].1
+    }
+}
+
+/// The number of period boundaries crossed
+/// when time goes from `from` to `to`.
+fn periods_crossed(from: Time, to: Time, period: u64) -> u64 {
+    to / period - from / period
+}
+
+/// Estimate how long requests of each service kind wait in the node queues:
+/// the time to process the requests queued for it,
+/// spread over the cores usable right now
+/// (fewer in powersave mode, and none on restarting nodes).
+fn queue_waits(state: &WorldState) -> [f64; ServiceKind::ALL.len()] {
+    let powersave = state.is_powersaving();
+    let cores: u32 = state
+        .nodes
+        .iter()
+        .filter(|node| !node.is_restarting(state.time))
+        .map(|node| node.usable_cores(powersave))
+        .sum();
+    let mut work = [0.; ServiceKind::ALL.len()];
+    for node in &state.nodes {
+        for request in &node.requests {
+            work[request.service as usize] += request.amount as f64
+                * node.time_per_request(request.service, state.software_level) as f64;
+        }
+    }
+    work.map(|work| work / cores.max(1) as f64)
+}
+
+/// Describe what a software or caching upgrade changed,
+/// one line per measure, based on the world state before and after it.
+///
+/// Op times are measured on the first node
+/// with the expected speed-up of cache hits (see [`expected_cache_speedup`]),
+/// and memory on the highest service tier unlocked.
+/// Other card effects are not described.
+pub fn describe_upgrade(
+    effect: &CardEffect,
+    before: &WorldState,
+    after: &WorldState,
+) -> Vec<String> {
+    let top_service = after.service_tier();
+    let node = &before.nodes[0];
+    let op_time = |service, state: &WorldState| {
+        format!(
+            "{:.1}ms",
+            node.time_per_request(service, state.software_level) as f64
+                / expected_cache_speedup(state.cache_level, state.cache_fraction)
+                / crate::TIME_UNITS_PER_MILLISECOND as f64
+        )
+    };
+    match effect {
+        CardEffect::UpgradeServices => {
+            let mut out: Vec<String> = after
+                .services()
+                .filter(|(_, service)| service.state.is_unlocked())
+                .map(|(service, _)| {
+                    t!(
+                        "upgrade.op_time",
+                        service = service,
+                        before = op_time(service, before),
+                        after = op_time(service, after),
+                    )
+                })
+                .collect();
+            out.push(t!(
+                "upgrade.memory_reserve",
+                before = before.expected_ram_reserved(),
+                after = after.expected_ram_reserved(),
+            ));
+            out
+        }
+        CardEffect::MoreCaching => {
+            let hit_rate = |state: &WorldState| {
+                (cache_hit_rate(state.cache_level, state.cache_fraction) * 100.).round()
+            };
+            let op_memory = |state: &WorldState| {
+                GameEngine::calculate_memory_reserve_required(
+                    top_service,
+                    state.cache_level,
+                    state.cache_fraction,
+                    state.software_level,
+                )
+            };
+            vec![
+                t!(
+                    "upgrade.cache_hit_rate",
+                    before = hit_rate(before),
+                    after = hit_rate(after),
+                ),
+                t!(
+                    "upgrade.op_time",
+                    service = top_service,
+                    before = op_time(top_service, before),
+                    after = op_time(top_service, after),
+                ),
+                t!(
+                    "upgrade.op_memory",
+                    service = top_service,
+                    before = op_memory(before),
+                    after = op_memory(after),
+                ),
+            ]
+        }
+        _ => vec![],
+    }
+}
+
+impl Default for GameEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A request (or request set) waiting to be routed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaitingRouteRequest {
+    /// multiplier for the number of requests
+    /// bundled into one
+    amount: u32,
+
+    /// the cloud user specification ID
+    /// (or None if it was requested by the player)
+    user_spec_id: Option<u32>,
+
+    /// the request's cloud service kind
+    service: ServiceKind,
+
+    /// whether the request is bad
+    bad: bool,
+
+    /// whether the request was made by the player's staff
+    #[serde(default)]
+    staff: bool,
+}
+
+/// the time added to a request routed to a node in another rack (15ms)
+pub const RACK_HOP_LATENCY: u32 = 150;
+
+/// the time added to a request routed to a node in another data center (100ms)
+pub const DATACENTER_HOP_LATENCY: u32 = 1_000;
+
+/// How far apart the routing node and the processing node of a request are.
+///
+/// The topology follows the order of the nodes, as shown to the player:
+/// single machines are grouped into racks of [`RACK_CAPACITY`] nodes,
+/// all in the same office,
+/// while rack nodes are grouped into data centers of [`DATACENTER_CAPACITY`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hop {
+    /// both are in the same rack
+    SameRack,
+    /// in different racks of the same data center
+    Rack,
+    /// in different data centers
+    Datacenter,
+}
+
+impl Hop {
+    /// The hop between the nodes with the given IDs.
+    pub fn between(state: &WorldState, from: u32, to: u32) -> Self {
+        let position = |id| {
+            state
+                .nodes
+                .binary_search_by_key(&id, |node: &CloudNode| node.id)
+                .unwrap_or_default() as u32
+        };
+        let (from, to) = (position(from), position(to));
+        if state.nodes.first().is_some_and(CloudNode::is_rack) {
+            if from == to {
+                Hop::SameRack
+            } else if from / DATACENTER_CAPACITY == to / DATACENTER_CAPACITY {
+                Hop::Rack
+            } else {
+                Hop::Datacenter
+            }
+        } else if from / RACK_CAPACITY == to / RACK_CAPACITY {
+            Hop::SameRack
+        } else {
+            Hop::Rack
+        }
+    }
+
+    /// The time added to each request for this hop
+    /// at the given routing level.
+    ///
+    /// Distributed routing halves it,
+    /// and it is gone once routing costs nothing.
+    pub fn latency(self, routing_level: RoutingLevel) -> u32 {
+        let latency = match self {
+            Hop::SameRack => 0,
+            Hop::Rack => RACK_HOP_LATENCY,
+            Hop::Datacenter => DATACENTER_HOP_LATENCY,
+        };
+        match routing_level {
+            RoutingLevel::MainNode => latency,
+            RoutingLevel::Distributed => latency / 2,
+            RoutingLevel::NoRoutingCost => 0,
+        }
+    }
+}
+
+/// How the nodes are turned into racks once data centers are unlocked.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RackConversion {
+    /// the number of nodes before the conversion
+    pub nodes: u32,
+    /// the number of rack nodes after the conversion
+    pub racks: u32,
+    /// the hardware value of the nodes which do not make up a rack,
+    /// credited to the player's funds
+    pub credit: Money,
+}
+
+impl RackConversion {
+    /// Work out how the given nodes are turned into racks.
+    ///
+    /// Every [`RACK_CAPACITY`] nodes make up a rack.
+    /// The nodes left over make up one more rack
+    /// if they are at least half of one,
+    /// and are refunded at their hardware value
+    /// at the configured prices otherwise.
+    pub fn of(nodes: &[CloudNode], config: &GameConfig) -> Self {
+        let full_racks = nodes.len() / RACK_CAPACITY as usize;
+        let remainder = &nodes[full_racks * RACK_CAPACITY as usize..];
+        let (racks, credit) = if remainder.len() * 2 >= RACK_CAPACITY as usize {
+            (full_racks + 1, Money::zero())
+        } else {
+            let credit = remainder.iter().fold(Money::zero(), |total, node| {
+                total + node.hardware_value(config)
+            });
+            (full_racks, credit)
+        };
+        RackConversion {
+            nodes: nodes.len() as u32,
+            racks: racks as u32,
+            credit,
+        }
+    }
+
+    /// Turn the given nodes into racks at the given time,
+    /// as worked out in this conversion.
+    ///
+    /// Each rack carries over the lifetime of the nodes making it up,
+    /// and the last rack also that of the nodes left over.
+    pub fn convert(&self, nodes: &[CloudNode], time: Time) -> Vec<CloudNode> {
+        let mut racks: Vec<_> = (0..self.racks)
+            .map(|id| CloudNode::new_fully_upgraded_rack(id).set_up_at(time))
+            .collect();
+        for (index, node) in nodes.iter().enumerate() {
+            let rack = (index / RACK_CAPACITY as usize).min(racks.len().saturating_sub(1));
+            if let Some(rack) = racks.get_mut(rack) {
+                rack.lifetime.merge(&node.lifetime);
+            }
+        }
+        racks
+    }
+}
+
+/// A request (or request set) waiting to be processed in a node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaitingRequest {
+    /// The timestamp for when the request arrived
+    timestamp: Time,
+
+    /// multiplier for the number of requests
+    /// bundled into one
+    amount: u32,
+
+    /// the cloud user specification ID
+    /// (or None if it was requested by the player)
+    user_spec_id: Option<u32>,
+
+    /// the request's cloud service kind
+    service: ServiceKind,
+
+    /// whether the request was made by the player's staff
+    #[serde(default)]
+    staff: bool,
+
+    /// the amount of memory required to process the request set
+    mem_required: Memory,
+
+    /// how the request is processed if it is bad
+    #[serde(default)]
+    outcome: BadRequestOutcome,
+
+    /// the time added to the processing of the request set
+    /// for the distance it was routed over
+    #[serde(default)]
+    latency: u32,
+}
+
+impl WaitingRequest {
+    /// Make an arrival event for this request,
+    /// to be routed again at the given time.
+    fn to_rerouted(&self, state: &WorldState, time: Time) -> RequestEvent {
+        let bad = self
+            .user_spec_id
+            .and_then(|id| state.user_spec(id))
+            .is_some_and(|spec| spec.is_bad());
+        RequestEvent {
+            timestamp: time,
+            user_spec_id: self.user_spec_id,
+            amount: self.amount,
+            service: self.service,
+            bad,
+            staff: self.staff,
+            kind: RequestEventStage::RequestArrived { chained: false },
+        }
+    }
+}
+
+/// How a request is processed
+/// once past spam detection at routing.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BadRequestOutcome {
+    /// processed as any other request
+    /// (always the case for good requests)
+    #[default]
+    Unchecked,
+    /// a bad request caught late,
+    /// cut short at half the processing time
+    Mitigated,
+    /// a bad request which is extra heavy,
+    /// taking twice the processing time and memory
+    Heavy,
+}
+
+impl BadRequestOutcome {
+    /// Decide how a bad request which got past detection at routing
+    /// is processed, given the level of spam protection.
+    ///
+    /// Late detection follows the same chance as at routing,
+    /// while only unprotected services see heavy requests.
+    pub fn roll(gen: &mut SampleGenerator, spam_protection: f32) -> Self {
+        if spam_protection > 0. {
+            if gen.gen_bool(spam_protection) {
+                BadRequestOutcome::Mitigated
+            } else {
+                BadRequestOutcome::Unchecked
+            }
+        } else if gen.gen_bool(HEAVY_BAD_REQUEST_CHANCE) {
+            BadRequestOutcome::Heavy
+        } else {
+            BadRequestOutcome::Unchecked
+        }
+    }
+
+    /// The processing time of a request with this outcome.
+    pub fn scale_duration(self, duration: u32) -> u32 {
+        match self {
+            BadRequestOutcome::Unchecked => duration,
+            BadRequestOutcome::Mitigated => (duration / 2).max(1),
+            BadRequestOutcome::Heavy => duration * 2,
+        }
+    }
+
+    /// The memory taken by a request with this outcome.
+    pub fn scale_memory(self, memory: Memory) -> Memory {
+        match self {
+            BadRequestOutcome::Heavy => memory * 2,
+            _ => memory,
+        }
+    }
+}
+
+/// How many machines a cloud node stands for.
+///
+/// Once data centers are unlocked,
+/// whole racks of fully upgraded nodes are simulated as one node,
+/// which cannot be upgraded any further.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum NodeScale {
+    /// a single machine
+    #[default]
+    Single,
+    /// a rack of [`RACK_CAPACITY`] fully upgraded machines
+    Rack,
+    /// a data center of [`DATACENTER_CAPACITY`] racks
+    Datacenter,
+}
+
+impl NodeScale {
+    /// The number of single machines which a node of this scale stands for.
+    pub fn machines(self) -> u32 {
+        match self {
+            NodeScale::Single => 1,
+            NodeScale::Rack => RACK_CAPACITY,
+            NodeScale::Datacenter => RACK_CAPACITY * DATACENTER_CAPACITY,
+        }
+    }
+}
+
+/// More memory was released from a node than it had in use,
+/// which means that the books on its memory are wrong
+/// (e.g. the same request released twice).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryAccountingError {
+    /// the ID of the node
+    pub node: u32,
+    /// the memory in use in the node before the release
+    pub in_use: Memory,
+    /// the memory released
+    pub released: Memory,
+}
+
+impl std::fmt::Display for MemoryAccountingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Released {} from node {} with only {} in use",
+            self.released, self.node, self.in_use
+        )
+    }
+}
+
+impl std::error::Error for MemoryAccountingError {}
+
+/// A script of player actions which cannot be played
+/// (see [`GameEngine::run_script`]).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The action at this index is timed
+    /// before the one preceding it or the game time.
+    Unsorted {
+        index: usize,
+        time: Time,
+        previous: Time,
+    },
+    /// The script is to stop before its last action.
+    EndsEarly { until: Time, last: Time },
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::Unsorted {
+                index,
+                time,
+                previous,
+            } => write!(
+                f,
+                "Script action {index} at {time} comes after time {previous}"
+            ),
+            ScriptError::EndsEarly { until, last } => write!(
+                f,
+                "Script ends at {until}, before its last action at {last}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// A cloud processing node and its state
+///
+/// Saved in a compact form,
+/// since the node's capacity follows from its levels.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "CloudNodeRepr")]
+pub struct CloudNode {
+    /// a unique identifier for the node
+    pub id: u32,
+    /// the node's CPU level (see [`CPU_LEVELS`])
+    pub cpu_level: u8,
+    /// the node's RAM level (see [`RAM_LEVELS`])
+    pub ram_level: u8,
+    /// how many machines the node stands for
+    pub scale: NodeScale,
+    /// the node's overclock, if any
+    pub overclock: Overclock,
+
+    /// the number of requests that it can fulfill in parallel
+    pub num_cores: u32,
+    /// the amount of memory that it has
+    pub ram_capacity: Memory,
+
+    /// the base speed of the node,
+    /// which translates to the number of base requests fulfilled per core
+    /// (higher tiered services will have a lower speed)
+    pub cpu_speed: u32,
+
+    /// the number of requests currently being processed right now
+    ///
+    /// Transient.
+    pub processing: u32,
+
+    /// the total amount of RAM in use
+    ///
+    /// Transient.
+    pub ram_usage: Memory,
+
+    /// how much of `ram_usage` is reserved
+    ///
+    /// Transient.
+    pub ram_reserved: Memory,
+
+    /// queue of requests sitting in memory and waiting to be processed
+    ///
+    /// Transient.
+    pub requests: VecDeque<WaitingRequest>,
+
+    /// the time until which the node is restarting
+    /// (or 0 if it is not restarting)
+    ///
+    /// Transient.
+    pub restarting_until: Time,
+
+    /// the number of requests being processed
+    /// since before powersave mode started,
+    /// which do not count against its reduced capacity
+    ///
+    /// Transient.
+    pub powersave_grace: u32,
+
+    /// what the node has done since it was set up
+    pub lifetime: NodeLifetime,
+}
+
+/// What a cloud node has done since it was set up,
+/// for the player to get attached to it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeLifetime {
+    /// the game time at which the node was set up
+    #[serde(default)]
+    pub created_at: Time,
+    /// the number of requests processed by the node,
+    /// including bad requests
+    #[serde(default)]
+    pub requests_processed: u64,
+    /// the number of ops served to customers and the player
+    #[serde(default)]
+    pub ops_served: u64,
+    /// the electricity consumed by the node in microWattever,
+    /// for routing and processing requests
+    #[serde(default)]
+    pub energy: i64,
+}
+
+impl NodeLifetime {
+    pub fn is_empty(&self) -> bool {
+        *self == NodeLifetime::default()
+    }
+
+    /// Add up the lifetime of a node merged into this one,
+    /// which has been serving since the earliest of them.
+    pub fn merge(&mut self, other: &NodeLifetime) {
+        self.created_at = self.created_at.min(other.created_at);
+        self.requests_processed += other.requests_processed;
+        self.ops_served += other.ops_served;
+        self.energy += other.energy;
+    }
+}
+
+/// A modifier on a node's speed and energy consumption,
+/// given by project cards.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Overclock {
+    /// the speed added to the node's CPU speed
+    pub speed_bonus: u32,
+    /// the fraction of energy added to every request processed by the node
+    pub energy_penalty: f32,
+}
+
+impl Overclock {
+    /// Whether the node is not overclocked at all.
+    pub fn is_none(&self) -> bool {
+        self.speed_bonus == 0 && self.energy_penalty == 0.
+    }
+}
+
+/// The saved form of a [`CloudNode`].
+///
+/// The capacity fields are only present in older saves,
+/// and are otherwise derived from the levels.
+#[derive(Serialize, Deserialize)]
+struct CloudNodeRepr {
+    id: u32,
+    cpu_level: u8,
+    ram_level: u8,
+    #[serde(default, skip_serializing_if = "is_single")]
+    scale: NodeScale,
+    #[serde(default, skip_serializing_if = "Overclock::is_none")]
+    overclock: Overclock,
+    #[serde(default, skip_serializing_if = "NodeLifetime::is_empty")]
+    lifetime: NodeLifetime,
+    /// whether the node is a fully upgraded rack in a data center,
+    /// as written by older saves
+    #[serde(default, skip_serializing)]
+    rack: bool,
+    #[serde(default, skip_serializing)]
+    num_cores: Option<u32>,
+    #[serde(default, skip_serializing)]
+    ram_capacity: Option<Memory>,
+    #[serde(default, skip_serializing)]
+    cpu_speed: Option<u32>,
+}
+
+fn is_single(&scale: &NodeScale) -> bool {
+    scale == NodeScale::Single
+}
+
+impl TryFrom<CloudNodeRepr> for CloudNode {
+    type Error = String;
+
+    /// Fails if the levels of a single machine are out of range,
+    /// as in a corrupt save or one from a future version.
+    fn try_from(repr: CloudNodeRepr) -> Result<Self, Self::Error> {
+        // older saves tell racks apart by a flag,
+        // or only by their number of cores
+        let max_cores = CPU_LEVELS[CPU_LEVELS.len() - 1].0;
+        let scale = if repr.rack || repr.num_cores.is_some_and(|cores| cores > max_cores) {
+            NodeScale::Rack
+        } else {
+            repr.scale
+        };
+        let mut node = if scale != NodeScale::Single {
+            CloudNode::new_at_scale(repr.id, scale)
+        } else {
+            let (num_cores, cpu_speed, _) = *CPU_LEVELS
+                .get(repr.cpu_level as usize)
+                .ok_or_else(|| format!("node {} has no CPU level {}", repr.id, repr.cpu_level))?;
+            let (ram_capacity, _) = *RAM_LEVELS
+                .get(repr.ram_level as usize)
+                .ok_or_else(|| format!("node {} has no RAM level {}", repr.id, repr.ram_level))?;
+            CloudNode {
+                cpu_level: repr.cpu_level,
+                ram_level: repr.ram_level,
+                num_cores,
+                ram_capacity,
+                cpu_speed,
+                ..CloudNode::new(repr.id)
+            }
+        };
+        node.overclock(repr.overclock.speed_bonus, repr.overclock.energy_penalty);
+        node.lifetime = repr.lifetime;
+        // older saves have the capacity written down
+        if let Some(num_cores) = repr.num_cores {
+            node.num_cores = num_cores;
+        }
+        if let Some(ram_capacity) = repr.ram_capacity {
+            node.ram_capacity = ram_capacity;
+        }
+        if let Some(cpu_speed) = repr.cpu_speed {
+            node.cpu_speed = cpu_speed;
+        }
+        Ok(node)
+    }
+}
+
+impl Serialize for CloudNode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        CloudNodeRepr {
+            id: self.id,
+            cpu_level: self.cpu_level,
+            ram_level: self.ram_level,
+            scale: self.scale,
+            overclock: self.overclock,
+            lifetime: self.lifetime,
+            rack: false,
+            num_cores: None,
+            ram_capacity: None,
+            cpu_speed: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The time units needed by one core of the given speed
+/// to process a request of a single op of the given service.
+pub fn processing_time(cpu_speed: u32, service: ServiceKind, software_level: u8) -> u32 {
+    let factor = service.tier().time_factor;
+
+    let software = software_level as u32;
+    2_500 * factor / cpu_speed + (4_500 / (software * software + 1))
+}
+
+impl CloudNode {
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            cpu_level: 0,
+            ram_level: 0,
+            scale: NodeScale::Single,
+            num_cores: CPU_LEVELS[0].0,
+            ram_capacity: RAM_LEVELS[0].0,
+            cpu_speed: CPU_LEVELS[0].1,
+            processing: 0,
+            ram_usage: Memory::zero(),
+            ram_reserved: Memory::zero(),
+            requests: VecDeque::new(),
+            restarting_until: 0,
+            powersave_grace: 0,
+            overclock: Overclock::default(),
+            lifetime: NodeLifetime::default(),
+        }
+    }
+
+    pub fn new_fully_upgraded(id: u32) -> Self {
+        Self {
+            id,
+            cpu_level: CPU_LEVELS.len() as u8 - 1,
+            ram_level: RAM_LEVELS.len() as u8 - 1,
+            scale: NodeScale::Single,
+            num_cores: CPU_LEVELS[CPU_LEVELS.len() - 1].0,
+            ram_capacity: RAM_LEVELS[RAM_LEVELS.len() - 1].0,
+            cpu_speed: CPU_LEVELS[CPU_LEVELS.len() - 1].1,
+            processing: 0,
+            ram_usage: Memory::zero(),
+            ram_reserved: Memory::zero(),
+            requests: VecDeque::new(),
+            restarting_until: 0,
+            powersave_grace: 0,
+            overclock: Overclock::default(),
+            lifetime: NodeLifetime::default(),
+        }
+    }
+
+    /// Whether this node stands for a whole rack of fully upgraded nodes
+    /// (or more), as happens once data centers are unlocked.
+    pub fn is_rack(&self) -> bool {
+        self.scale >= NodeScale::Rack
+    }
+
+    /// The cumulative purchase cost of the node at the configured prices:
+    /// a bare node and every upgrade up to its levels,
+    /// or the cost of fully upgraded racks for larger scales.
+    pub fn hardware_value(&self, config: &GameConfig) -> Money {
+        match self.scale {
+            NodeScale::Single => {
+                config.bare_node_cost
+                    + cumulative_cpu_cost(&config.cpu_levels, self.cpu_level)
+                    + cumulative_ram_cost(&config.ram_levels, self.ram_level)
+            }
+            NodeScale::Rack | NodeScale::Datacenter => {
+                config.upgraded_rack_cost * (self.scale.machines() / RACK_CAPACITY) as i32
+            }
+        }
+    }
+
+    /// Whether this is a single machine with the highest CPU and RAM levels.
+    pub fn is_fully_upgraded(&self) -> bool {
+        self.scale == NodeScale::Single
+            && self.cpu_level == (CPU_LEVELS.len() - 1) as u8
+            && self.ram_level == (RAM_LEVELS.len() - 1) as u8
+    }
+
+    /// A node with the capabilities resembling a fully beefed rack
+    pub fn new_fully_upgraded_rack(id: u32) -> Self {
+        Self::new_at_scale(id, NodeScale::Rack)
+    }
+
+    /// A node standing for as many fully upgraded machines
+    /// as there are in the given scale,
+    /// with their cores and memory added up.
+    pub fn new_at_scale(id: u32, scale: NodeScale) -> Self {
+        let machines = scale.machines();
+        let node = Self::new_fully_upgraded(id);
+        Self {
+            scale,
+            num_cores: node.num_cores * machines,
+            ram_capacity: node.ram_capacity * machines as i32,
+            ..node
+        }
+    }
+
+    /// The node set up at the given game time.
+    pub fn set_up_at(mut self, time: Time) -> Self {
+        self.lifetime.created_at = time;
+        self
+    }
+
+    /// Overclock the node,
+    /// making it faster at the cost of more energy per request.
+    ///
+    /// Overclocks add up.
+    pub fn overclock(&mut self, speed_bonus: u32, energy_penalty: f32) {
+        self.overclock.speed_bonus += speed_bonus;
+        self.overclock.energy_penalty += energy_penalty;
+        self.cpu_speed += speed_bonus;
+    }
+
+    /// The factor applied to the energy consumed per processed request.
+    pub fn energy_factor(&self) -> f64 {
+        1. + self.overclock.energy_penalty as f64
+    }
+
+    /// Calculate the time units needed to process the request,
+    /// based on service kind and other global parameters
+    pub(crate) fn time_per_request(&self, service: ServiceKind, software_level: u8) -> u32 {
+        processing_time(self.cpu_speed, service, software_level)
+    }
+
+    pub(crate) fn time_per_request_routing(&self) -> u32 {
+        256 / self.cpu_speed
+    }
+
+    /// The cost of the next CPU upgrade,
+    /// or `None` if the node cannot be upgraded any further.
+    pub fn next_cpu_upgrade_cost(&self, config: &GameConfig) -> Option<Money> {
+        if self.scale != NodeScale::Single {
+            return None;
+        }
+        config
+            .cpu_levels
+            .get(self.cpu_level as usize + 1)
+            .map(|(_, _, cost)| *cost)
+    }
+
+    /// The cost of the next RAM upgrade,
+    /// or `None` if the node cannot be upgraded any further.
+    pub fn next_ram_upgrade_cost(&self, config: &GameConfig) -> Option<Money> {
+        if self.scale != NodeScale::Single {
+            return None;
+        }
+        config
+            .ram_levels
+            .get(self.ram_level as usize + 1)
+            .map(|(_, cost)| *cost)
+    }
+
+    /// Ensure that the node has enough memory reserved,
+    /// and update `ram_reserved` if possible.
+    ///
+    /// This will not release memory already reserved.
+    ///
+    /// Returns false if the node does not have enough memory,
+    /// in which case no changes are made.
+    pub fn reserve_for(&mut self, memory: Memory) -> Result<bool, MemoryAccountingError> {
+        let available = self.ram_capacity + self.ram_reserved - self.ram_usage;
+        if available < memory {
+            Ok(false)
+        } else {
+            if self.ram_reserved < memory {
+                self.release_reserved()?;
+                self.ram_reserved = memory;
+                self.ram_usage += self.ram_reserved;
+            }
+            Ok(true)
+        }
+    }
+
+    /// Release memory in use,
+    /// failing if there is less memory in use than that.
+    ///
+    /// The memory in use drops to zero on failure,
+    /// so that the node remains usable.
+    pub fn try_release(&mut self, amount: Memory) -> Result<(), MemoryAccountingError> {
+        match self.ram_usage.checked_sub(amount) {
+            Some(usage) => {
+                self.ram_usage = usage;
+                Ok(())
+            }
+            None => {
+                let in_use = std::mem::replace(&mut self.ram_usage, Memory::zero());
+                Err(MemoryAccountingError {
+                    node: self.id,
+                    in_use,
+                    released: amount,
+                })
+            }
+        }
+    }
+
+    /// Release reserved memory,
+    /// reclaiming it back as available.
+    pub(crate) fn release_reserved(&mut self) -> Result<(), MemoryAccountingError> {
+        let reserved = std::mem::replace(&mut self.ram_reserved, Memory::zero());
+        self.try_release(reserved)
+    }
+
+    /// Release some memory so that
+    /// the node has at most `maximum_reserve` reserved.
+    ///
+    /// Returns whether any memory was released.
+    pub(crate) fn release_excess_reserve(
+        &mut self,
+        maximum_reserve: Memory,
+    ) -> Result<bool, MemoryAccountingError> {
+        if self.ram_reserved > maximum_reserve {
+            // check difference
+            let mem_diff = self.ram_reserved - maximum_reserve;
+            self.ram_reserved = maximum_reserve;
+            self.try_release(mem_diff)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Whether the node is restarting at the given time.
+    pub fn is_restarting(&self, time: Time) -> bool {
+        time < self.restarting_until
+    }
+
+    /// Restart the node,
+    /// releasing its memory reserve and all memory of waiting requests.
+    ///
+    /// Requests already being processed are left to finish.
+    /// Returns the requests which were waiting,
+    /// so that they can be routed elsewhere,
+    /// and the first memory accounting error found on the way, if any.
+    pub(crate) fn restart(
+        &mut self,
+        until: Time,
+    ) -> (Vec<WaitingRequest>, Result<(), MemoryAccountingError>) {
+        let requests: Vec<_> = self.requests.drain(..).collect();
+        let mut released = Ok(());
+        for request in &requests {
+            released = released.and(self.try_release(request.mem_required));
+        }
+        released = released.and(self.release_reserved());
+        self.restarting_until = until;
+        (requests, released)
+    }
+
+    /// Whether the node has nothing to process or waiting to be processed.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.processing == 0 && self.requests.is_empty()
+    }
+
+    /// Check whether this node cannot process any more requests in parallel
+    /// at this time.
+    pub(crate) fn is_busy(&self, powersave: bool, time: Time) -> bool {
+        // the reduced capacity in powersave mode is not a hard limit,
+        // so only the actual number of cores is checked here
+        if self.processing > self.num_cores {
+            log::warn!("Cloud node {} is over its capacity!", self.id);
+        }
+
+        self.is_restarting(time) || self.free_cores(powersave) == 0
+    }
+
+    /// Release a core after a request was routed or processed.
+    ///
+    /// Returns false if the node was not processing anything.
+    pub(crate) fn release_core(&mut self) -> bool {
+        if self.processing == 0 {
+            return false;
+        }
+        self.processing -= 1;
+        // the oldest requests are assumed to finish first
+        self.powersave_grace = self.powersave_grace.saturating_sub(1);
+        true
+    }
+
+    /// The share of the node's memory reserved (mostly for caching)
+    /// and the share taken by requests, each between 0 and 1.
+    pub fn memory_breakdown(&self) -> (f32, f32) {
+        if self.ram_capacity == Memory::zero() {
+            return (0., 0.);
+        }
+        (
+            self.ram_reserved.ratio(self.ram_capacity),
+            (self.ram_usage - self.ram_reserved).ratio(self.ram_capacity),
+        )
+    }
+
+    /// The number of cores the node can use,
+    /// which is a quarter of them in powersave mode.
+    pub fn usable_cores(&self, powersave: bool) -> u32 {
+        if powersave {
+            self.num_cores / 4
+        } else {
+            self.num_cores
+        }
+    }
+
+    /// Check how many cores are available for processing requests.
+    pub(crate) fn free_cores(&self, powersave: bool) -> u32 {
+        let free = self.num_cores.saturating_sub(self.processing);
+        if powersave {
+            // requests admitted before powersave mode started
+            // do not count against its reduced capacity
+            let admitted = self.processing.saturating_sub(self.powersave_grace);
+            free.min(self.usable_cores(true).saturating_sub(admitted))
+        } else {
+            free
+        }
+    }
+
+    /// Remove the requests from the node's waiting queue
+    /// which have timed out,
+    /// returning them so that they can be counted as dropped
+    /// (each by its op amount and traffic class),
+    /// and the first memory accounting error found on the way, if any.
+    fn clear_timedout_requests(
+        &mut self,
+        time: u64,
+        timeout: u64,
+    ) -> (Vec<WaitingRequest>, Result<(), MemoryAccountingError>) {
+        let mut timedout = vec![];
+        self.requests.retain(|request| {
+            if request.timestamp + timeout < time {
+                timedout.push(request.clone());
+                false
+            } else {
+                true
+            }
+        });
+        // drop memory allocated for the requests
+        let mut released = Ok(());
+        for request in &timedout {
+            released = released.and(self.try_release(request.mem_required));
+        }
+        (timedout, released)
+    }
+}
+
+/// A rack containing multiple nodes
+#[derive(Debug)]
+pub struct CloudRack {
+    pub nodes: Vec<CloudNode>,
+    /// the maximum capacity of a single rack
+    pub capacity: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::central::away;
+
+    use super::{
+        cache_hit_rate, cache_memory_factor, cumulative_cpu_cost, cumulative_ram_cost,
+        describe_upgrade, expected_cache_speedup, queue_waits, trial_extension_fee,
+        BadRequestOutcome, CloudNode, ExpiredTrial, GameEngine, Hop, MemoryAccountingError,
+        NodeLifetime, NodeScale, RackConversion, ScriptError, WaitingRequest, BAILOUT_FUNDS,
+        BARE_NODE_COST, BUNDLE_MEMORY_SHARE, CACHE_LEVELS, CHURN_COOLDOWN, CPU_LEVELS,
+        DATACENTER_HOP_LATENCY, DEMAND_DOS_THRESHOLD, DOS_TARGETS, ELECTRICITY_BILL_PERIOD,
+        HEAVY_BAD_REQUEST_CHANCE, INCREASE_DEMAND_PERIOD, MAINTENANCE_DEMAND_DECAY,
+        MAJOR_UPDATE_PERIOD, MAX_PERIOD_REPORTS, MAX_PLAYER_REQUEST_SETS, MIN_QUEUE_DEMAND_FACTOR,
+        NODE_RESTART_COOLDOWN, PURCHASE_UNDO_WINDOW, RACK_HOP_LATENCY, RAM_LEVELS, REQUEST_TIMEOUT,
+        SOFTWARE_LEVELS, TIMEOUT_CLEANUP_PERIOD, TRIAL_EXTENSION, TRIAL_EXTENSION_FEE,
+        TRIAL_EXTENSION_WINDOW, UPGRADED_RACK_COST,
+    };
+    use crate::central::{
+        bandwidth::{bandwidth_capacity, BANDWIDTH_WINDOW},
+        cards::{
+            all::{card_by_id, ALL_CARDS, ID_WIN},
+            CardCondition, CardEffect,
+        },
+        daily::{DailyChallenge, DailyDate},
+        eta::ResourceRates,
+        histogram::HISTOGRAM_BUCKETS,
+        insurance::INSURANCE_COOLDOWN,
+        metrics::{DropCause, PlayerOps},
+        narrative::NARRATIVE_TIMEOUT,
+        notification::NOTIFICATION_LOG_CAPACITY,
+        queue::{RequestEvent, RequestEventStage},
+        report::PeriodReport,
+        staff::{StaffMember, StaffTier, MAX_STAFF, STAFF_WORK_PERIOD},
+        state::{
+            Loan, RoutingLevel, TrafficClass, UsedCard, DOS_IMMUNITY_PERIOD,
+            HARDWARE_PANEL_LOAD_BARS, HARDWARE_PANEL_NODES, HARDWARE_PANEL_PURCHASES,
+            HARDWARE_PANEL_UPGRADES, MICRO_WATTEVER_PER_WATTEVER, POWERSAVE_GRACE_PERIOD,
+            RESEARCH_FOR_MAX_DISCOUNT,
+        },
+        streak::{rebate, STREAK_DEMAND_BOOST_DURATION},
+        tariff::{Tariff, TARIFF_SWITCH_COOLDOWN, TARIFF_SWITCH_FEE},
+    };
+    use crate::{
+        central::{
+            engine::{DATACENTER_CAPACITY, RACK_CAPACITY},
+            testing::WorldStateBuilder,
+        },
+        t, ActionError, AudioEvent, CloudUserSpec, Cost, Difficulty, GameConfig, Memory, Money,
+        Ops, PlayerAction, SampleGenerator, ServiceKind, Time, UserKind, WorldState,
+        TIME_UNITS_PER_CYCLE, TIME_UNITS_PER_MINUTE, TIME_UNITS_PER_SECOND,
+    };
+
+    /// Bad requests are caught at routing or while processing
+    /// with the same chance, and hit harder when unprotected.
+    #[test]
+    fn test_bad_request_duration_distribution() {
+        const REQUESTS: u32 = 20_000;
+        const DURATION: u32 = 1_000;
+
+        // protection level, then expected shares of requests
+        // dropped at routing, mitigated and heavy
+        let tiers = [
+            (0., 0., 0., HEAVY_BAD_REQUEST_CHANCE),
+            (0.5, 0.5, 0.25, 0.),
+            (0.875, 0.875, 0.109375, 0.),
+            (1., 1., 0., 0.),
+        ];
+        for (protection, dropped, mitigated, heavy) in tiers {
+            let mut gen = SampleGenerator::from_seed(878);
+            let mut counts = [0; 3];
+            let mut total_duration = 0;
+            for _ in 0..REQUESTS {
+                if gen.gen_bool(protection) {
+                    // dropped at routing
+                    counts[0] += 1;
+                    continue;
+                }
+                let outcome = BadRequestOutcome::roll(&mut gen, protection);
+                total_duration += outcome.scale_duration(DURATION) as u64;
+                match outcome {
+                    BadRequestOutcome::Mitigated => counts[1] += 1,
+                    BadRequestOutcome::Heavy => counts[2] += 1,
+                    BadRequestOutcome::Unchecked => {}
+                }
+            }
+
+            let share = |count: u32| count as f32 / REQUESTS as f32;
+            for (what, count, expected) in [
+                ("dropped", counts[0], dropped),
+                ("mitigated", counts[1], mitigated),
+                ("heavy", counts[2], heavy),
+            ] {
+                assert!(
+                    (share(count) - expected).abs() < 0.01,
+                    "{what} at protection {protection}: {} != {expected}",
+                    share(count)
+                );
+            }
+
+            // the average processing time among all bad requests,
+            // counting those dropped at routing as taking no time
+            let expected_duration = DURATION as f32
+                * ((1. - dropped - mitigated - heavy) + mitigated / 2. + heavy * 2.);
+            let average = total_duration as f32 / REQUESTS as f32;
+            assert!(
+                (average - expected_duration).abs() < DURATION as f32 * 0.02,
+                "average duration at protection {protection}: {average} != {expected_duration}"
+            );
+        }
+
+        // heavy requests take twice the memory too
+        assert_eq!(
+            BadRequestOutcome::Heavy.scale_memory(Memory::mb(4)),
+            Memory::mb(8)
+        );
+        assert_eq!(
+            BadRequestOutcome::Mitigated.scale_memory(Memory::mb(4)),
+            Memory::mb(4)
+        );
+    }
+
+    #[test]
+    fn test_hard_node_costs_more() {
+        let mut spent = vec![];
+        for difficulty in [Difficulty::Normal, Difficulty::Hard] {
+            let mut engine = GameEngine::with_config(GameConfig::new(difficulty));
+            let mut state = WorldStateBuilder::new()
+                .funds(Money::dollars(10_000))
+                .build();
+            engine
+                .apply_action(&mut state, PlayerAction::AddNode)
+                .unwrap();
+            assert_eq!(state.nodes.len(), 2);
+            spent.push(Money::dollars(10_000) - state.funds);
+        }
+        assert!(spent[1] > spent[0], "{} <= {}", spent[1], spent[0]);
+    }
+
+    #[test]
+    fn test_hard_bills_more_electricity() {
+        let mut bills = vec![];
+        for difficulty in [Difficulty::Normal, Difficulty::Hard] {
+            let mut engine = GameEngine::with_config(GameConfig::new(difficulty));
+            let mut state = WorldState::default();
+            let mut time = 0;
+            while time <= ELECTRICITY_BILL_PERIOD {
+                // same steady load in both games
+                state.electricity.add_consumption(100_000);
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            bills.push(state.electricity.total_due);
+        }
+        assert!(bills[0] > Money::zero());
+        assert!(bills[1] > bills[0], "{} <= {}", bills[1], bills[0]);
+    }
+
+    #[test]
+    fn test_sla_penalty_on_bill() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .total_ops(ServiceKind::Base, Ops(1_000))
+            .build();
+        let card = card_by_id("b1s").unwrap();
+        engine.apply_card(&mut state, card);
+        assert_eq!(state.base_service.entitlement, Money::millicents(8));
+        assert_eq!(state.slas.len(), 1);
+        // an agreement is only signed once
+        engine.apply_card(&mut state, card);
+        assert_eq!(state.base_service.entitlement, Money::millicents(8));
+        assert_eq!(state.slas.len(), 1);
+
+        let mut time = 0;
+        let mut run_until = |state: &mut WorldState, end: u64| {
+            while time + (TIME_UNITS_PER_CYCLE as u64) < end {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(state, time);
+            }
+        };
+
+        // a period with few drops goes without a penalty
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD);
+        state.count_sla_requests(ServiceKind::Base, 99, 1);
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD + 10_000);
+        assert_eq!(state.slas[0].violations, 0);
+        assert_eq!((state.slas[0].fulfilled, state.slas[0].dropped), (0, 0));
+        let due = state.electricity.total_due;
+        assert!(due < Money::dollars(40), "{due}");
+
+        // a period with too many drops adds the penalty to the bill
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD * 2);
+        state.count_sla_requests(ServiceKind::Base, 80, 20);
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD * 2 + 10_000);
+        assert_eq!(state.slas[0].violations, 1);
+        assert!(state.electricity.total_due >= due + Money::dollars(40));
+        assert!(state.electricity.last_bill_time > 0);
+        let entry = engine.notifications().recent(1).next().unwrap();
+        assert_eq!(
+            entry.message,
+            "Base SLA broken with 20.0% of requests dropped: $40 penalty added to the bill"
+        );
+    }
+
+    #[test]
+    fn test_insurance_premium_and_payout() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .service_published(ServiceKind::Base, Money::cents(1))
+            .requests_dropped(5_000)
+            .build();
+        let card = card_by_id("h2").unwrap();
+        assert!(card.condition.should_appear(&state));
+        engine.apply_card(&mut state, card);
+        let premium = state.insurance.as_ref().unwrap().premium;
+        assert!(!card.condition.should_appear(&state));
+
+        let mut time = 0;
+        let mut run_until = |state: &mut WorldState, end: u64| {
+            while time + (TIME_UNITS_PER_CYCLE as u64) < end {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(state, time);
+            }
+        };
+
+        // the premium is added to the bill even without drops
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD + 10_000);
+        assert!(state.electricity.total_due >= premium);
+        assert_eq!(state.insurance.as_ref().unwrap().claims, 0);
+        state.electricity.pay_bills();
+
+        // a period with many customer requests dropped pays out
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD * 2);
+        state
+            .insurance
+            .as_mut()
+            .unwrap()
+            .count_dropped(ServiceKind::Base, 2_000);
+        let funds = state.funds;
+        run_until(&mut state, ELECTRICITY_BILL_PERIOD * 2 + 10_000);
+        let policy = state.insurance.as_ref().unwrap();
+        assert_eq!(policy.claims, 1);
+        assert_eq!(policy.payouts, Money::cents(1_600));
+        assert_eq!(state.funds, funds + Money::cents(1_600));
+        assert!(state.electricity.total_due >= premium);
+        let entry = engine.notifications().recent(1).next().unwrap();
+        assert_eq!(
+            entry.message,
+            "Insurance paid out $16 for 2\u{2006}000 requests dropped"
+        );
+    }
+
+    #[test]
+    fn test_insurance_cancellation_cooldown() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .time(1_000)
+            .funds(Money::dollars(5_000))
+            .available_ops(ServiceKind::Super, Ops(10_000))
+            .requests_dropped(5_000)
+            .build();
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::CancelInsurance),
+            Err(ActionError::NotAvailable)
+        );
+
+        let use_card = PlayerAction::UseCard { id: "h2".into() };
+        engine.apply_action(&mut state, use_card.clone()).unwrap();
+        assert!(state.insurance.is_some());
+        engine
+            .apply_action(&mut state, PlayerAction::CancelInsurance)
+            .unwrap();
+        assert!(state.insurance.is_none());
+        assert_eq!(state.insurance_cancelled_at, Some(1_000));
+
+        // not on offer again until the cooldown is over
+        let card = card_by_id("h2").unwrap();
+        state.time += INSURANCE_COOLDOWN - 1;
+        assert!(!card.should_appear(&state));
+        state.time += 1;
+        assert!(card.should_appear(&state));
+        engine.apply_action(&mut state, use_card).unwrap();
+        assert_eq!(state.insurance.as_ref().unwrap().since, state.time);
+        assert_eq!(
+            state
+                .cards_used
+                .iter()
+                .filter(|used| used.id == "h2")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_audio_event_on_bill() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        let mut time = 0;
+        let mut events = vec![];
+        while time <= ELECTRICITY_BILL_PERIOD {
+            state.electricity.add_consumption(100_000);
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+            let new_events = engine.take_audio_events();
+            if state.electricity.total_due == Money::zero() {
+                // no bill, no sound
+                assert!(!new_events.contains(&AudioEvent::Bill));
+            }
+            events.extend(new_events);
+        }
+        assert!(state.electricity.total_due > Money::zero());
+        assert_eq!(events.iter().filter(|e| **e == AudioEvent::Bill).count(), 1);
+        // taken events are not repeated
+        assert!(engine.take_audio_events().is_empty());
+    }
+
+    #[test]
+    fn test_pin_cards() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(100_000))
+            .funds(Money::dollars(1_000))
+            .build();
+        engine.update_visible_cards(&state);
+        let pin = |engine: &mut GameEngine, state: &mut WorldState, id: &'static str| {
+            engine.apply_action(state, PlayerAction::PinCard { id: id.into() })
+        };
+
+        // only cards on offer can be pinned
+        assert_eq!(
+            pin(&mut engine, &mut state, "nope"),
+            Err(ActionError::NoSuchCard("nope".into()))
+        );
+        assert_eq!(
+            pin(&mut engine, &mut state, "win9"),
+            Err(ActionError::NotAvailable)
+        );
+
+        for id in ["b0", "b00", "a0p"] {
+            pin(&mut engine, &mut state, id).unwrap();
+        }
+        // pinning again changes nothing
+        pin(&mut engine, &mut state, "b0").unwrap();
+        assert_eq!(state.cards_pinned, ["b0", "b00", "a0p"]);
+        assert_eq!(
+            pin(&mut engine, &mut state, "s1"),
+            Err(ActionError::TooManyPinned)
+        );
+
+        // unpinning makes room
+        engine
+            .apply_action(&mut state, PlayerAction::UnpinCard { id: "b00".into() })
+            .unwrap();
+        pin(&mut engine, &mut state, "s1").unwrap();
+        assert_eq!(state.cards_pinned, ["b0", "a0p", "s1"]);
+
+        // using a card unpins it
+        engine
+            .apply_action(&mut state, PlayerAction::UseCard { id: "a0p".into() })
+            .unwrap();
+        assert_eq!(state.cards_pinned, ["b0", "s1"]);
+
+        // so does the card going away
+        state.cards_pinned.push("win9".into());
+        engine.update(&mut state, MAJOR_UPDATE_PERIOD);
+        assert_eq!(state.cards_pinned, ["b0", "s1"]);
+    }
+
+    #[test]
+    fn test_welcome_back_card() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState {
+            saved_at: 1_000,
+            ..Default::default()
+        };
+        engine.update_visible_cards(&state);
+        assert!(!engine.visible_cards().any(|card| card.id == "wb"));
+
+        // returning after two hours
+        assert!(away::check_return(&mut state, 1_000 + 7_200_000));
+        engine.update_visible_cards(&state);
+        assert!(engine.visible_cards().any(|card| card.id == "wb"));
+        let funds = state.funds;
+        engine
+            .apply_action(&mut state, PlayerAction::UseCard { id: "wb".into() })
+            .unwrap();
+        assert_eq!(state.funds, funds + Money::dollars(50));
+        assert_eq!(state.base_service.available, Ops(2_000));
+        assert_eq!(state.away_bonus, None);
+        assert!(!engine.visible_cards().any(|card| card.id == "wb"));
+
+        // the card can come back on the next return, but expires if unused
+        state.saved_at = 1_000 + 7_200_000;
+        let now = state.saved_at + away::AWAY_BONUS_COOLDOWN_MILLIS;
+        assert!(away::check_return(&mut state, now));
+        engine.update_visible_cards(&state);
+        assert!(engine.visible_cards().any(|card| card.id == "wb"));
+        let mut time = state.time;
+        while time <= away::AWAY_BONUS_EXPIRY + 2_500 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.away_bonus, None);
+        assert!(!engine.visible_cards().any(|card| card.id == "wb"));
+    }
+
+    #[test]
+    fn test_bill_paid_as_shown() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(1)).build();
+        // 1.5 cents are shown as 2 cents
+        state.electricity.total_due = Money::millicents(1_500);
+        assert_eq!(state.electricity.bill_amount().to_string(), "$0.02");
+
+        engine
+            .apply_action(&mut state, PlayerAction::PayElectricityBill)
+            .unwrap();
+        assert_eq!(state.funds, Money::dollars(1) - Money::cents(2));
+        assert_eq!(state.spent, Money::cents(2));
+        assert_eq!(state.electricity.total_due, Money::zero());
+
+        // a rounded down bill is paid for less than its total
+        state.electricity.total_due = Money::millicents(1_499);
+        assert_eq!(state.electricity.bill_amount(), Money::cents(1));
+        engine
+            .apply_action(&mut state, PlayerAction::PayElectricityBill)
+            .unwrap();
+        assert_eq!(state.funds, Money::dollars(1) - Money::cents(3));
+    }
+
+    #[test]
+    fn test_periods_independent_of_update_rate() {
+        let new_state = || {
+            let mut state = WorldStateBuilder::new().demand(10.).build();
+            state.loan = Some(Loan {
+                balance: Money::dollars(1_000),
+            });
+            // enough energy consumed for a bill
+            state.electricity.consumed = 2_000_000_000;
+            state
+        };
+        let end = ELECTRICITY_BILL_PERIOD * 10 + 10_000;
+
+        // in small steps
+        let mut engine = GameEngine::new();
+        let mut stepped = new_state();
+        let mut time = 0;
+        while time < end {
+            time = (time + TIME_UNITS_PER_CYCLE as u64).min(end);
+            engine.update(&mut stepped, time);
+        }
+
+        // in one jump
+        let mut engine = GameEngine::new();
+        let mut jumped = new_state();
+        engine.update(&mut jumped, end);
+
+        assert_eq!(stepped.time, jumped.time);
+        // every demand increase happened
+        let increases = end / INCREASE_DEMAND_PERIOD;
+        let expected =
+            10. + increases as f64 * jumped.demand_rate * GameConfig::default().demand_growth;
+        assert!(
+            (stepped.demand - expected).abs() < 1e-6,
+            "{}",
+            stepped.demand
+        );
+        assert!((jumped.demand - expected).abs() < 1e-6, "{}", jumped.demand);
+        // every bill period was accounted for
+        assert_eq!(stepped.reports.len(), MAX_PERIOD_REPORTS);
+        assert_eq!(jumped.reports.len(), stepped.reports.len());
+        let balance = stepped.loan.as_ref().unwrap().balance;
+        // ten times 5% interest
+        assert!(balance > Money::dollars(1_628) && balance < Money::dollars(1_629));
+        assert_eq!(jumped.loan, stepped.loan);
+        assert!(stepped.electricity.total_billed > Money::zero());
+        assert_eq!(
+            jumped.electricity.total_billed,
+            stepped.electricity.total_billed
+        );
+    }
+
+    #[test]
+    fn test_auto_pay_bills() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000))
+            .build();
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::SetAutoPay { on: true }),
+            Err(ActionError::NotAvailable)
+        );
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockAutoPay);
+        assert!(state.auto_pay);
+
+        state.electricity.consumed = 2_000_000_000;
+        let bill = state
+            .electricity
+            .check_bill(&engine.config().electricity_cost_levels)
+            .round_to_cents_half_up();
+        assert!(bill > Money::zero());
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD);
+
+        // paid through the same path as the button
+        assert_eq!(state.electricity.total_due, Money::zero());
+        assert_eq!(state.electricity.last_bill_time, 0);
+        assert_eq!(state.electricity.last_bill, bill);
+        assert_eq!(state.funds, Money::dollars(1_000) - bill);
+        assert_eq!(state.spent, bill);
+
+        // not when turned off
+        engine
+            .apply_action(&mut state, PlayerAction::SetAutoPay { on: false })
+            .unwrap();
+        state.electricity.consumed = 2_000_000_000;
+        state.time = ELECTRICITY_BILL_PERIOD;
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD * 2);
+        assert_eq!(state.electricity.bill_amount(), bill);
+        assert_eq!(state.funds, Money::dollars(1_000) - bill);
+    }
+
+    #[test]
+    fn test_bill_streak() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(100_000))
+            .build();
+        // pay what is due if asked and go through one more bill period
+        let bill_period = |engine: &mut GameEngine, state: &mut WorldState, pay: bool| {
+            if pay {
+                engine
+                    .apply_action(state, PlayerAction::PayElectricityBill)
+                    .unwrap();
+            }
+            state.electricity.consumed = 2_000_000_000;
+            let end = state.time + ELECTRICITY_BILL_PERIOD;
+            engine.update_major(state, end);
+            state.time = end;
+        };
+
+        // nothing to pay on time before the first bill
+        bill_period(&mut engine, &mut state, true);
+        assert_eq!(state.electricity.on_time_streak, 0);
+        for streak in 1..=2 {
+            bill_period(&mut engine, &mut state, true);
+            assert_eq!(state.electricity.on_time_streak, streak);
+        }
+        assert_eq!(state.electricity.streak_rewards, 0);
+
+        // the third bill paid on time earns a rebate on it
+        let funds = state.funds - state.electricity.bill_amount();
+        let rebate = rebate(state.electricity.last_bill);
+        bill_period(&mut engine, &mut state, true);
+        assert_eq!(state.electricity.on_time_streak, 3);
+        assert_eq!(state.electricity.streak_rewards, 1);
+        assert_eq!(state.funds, funds + rebate);
+
+        // the streak survives saving and loading
+        let json = serde_json::to_string(&state).unwrap();
+        let mut state: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.electricity.on_time_streak, 3);
+        assert_eq!(state.electricity.streak_rewards, 1);
+        bill_period(&mut engine, &mut state, true);
+        assert_eq!(state.electricity.on_time_streak, 4);
+
+        // and ends with a bill left unpaid
+        bill_period(&mut engine, &mut state, false);
+        assert_eq!(state.electricity.on_time_streak, 0);
+
+        // rewards are granted once
+        for _ in 0..3 {
+            bill_period(&mut engine, &mut state, true);
+        }
+        assert_eq!(state.electricity.on_time_streak, 3);
+        assert_eq!(state.electricity.streak_rewards, 1);
+        bill_period(&mut engine, &mut state, true);
+        bill_period(&mut engine, &mut state, true);
+        assert_eq!(state.electricity.streak_rewards, 2);
+        assert!(state.demand_boost() > 1.);
+
+        // powersave ends the streak right away
+        state.electricity.total_due = state.powersave_threshold() + Money::dollars(1);
+        state.electricity.last_bill_time = state.time;
+        state.time += POWERSAVE_GRACE_PERIOD;
+        let time = state.time + 1;
+        engine.update(&mut state, time);
+        assert!(state.is_powersaving());
+        assert_eq!(state.electricity.on_time_streak, 0);
+        assert!(state.demand_boost() > 1.);
+        state.time += STREAK_DEMAND_BOOST_DURATION;
+        assert_eq!(state.demand_boost(), 1.);
+    }
+
+    #[test]
+    fn test_loyalty_discount_with_cost_cards() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        let levels = engine.config().electricity_cost_levels;
+        state.electricity.consumed = 10_000_000_000;
+        let bill = state.electricity.check_bill(&levels);
+
+        state.electricity.loyalty_discount = true;
+        let discounted = state.electricity.check_bill(&levels);
+        assert!(discounted < bill);
+        assert!(state.electricity.cost_per_wattever(&levels) > levels[1]);
+
+        // the next cost level still brings the bill down
+        engine.apply_card_effect(&mut state, &CardEffect::SetElectricityCostLevel(1));
+        let cost = state.electricity.cost_per_wattever(&levels);
+        assert!(cost < levels[1]);
+        assert!(cost > levels[2]);
+        assert!(state.electricity.check_bill(&levels) < discounted);
+
+        // and so does switching tariffs
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockTariffs(2));
+        assert_eq!(state.electricity.cost_level, 2);
+        assert!(state.electricity.cost_per_wattever(&levels) < levels[2]);
+    }
+
+    #[test]
+    fn test_auto_pay_insufficient_funds() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::cents(10)).build();
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockAutoPay);
+        state.electricity.consumed = 2_000_000_000;
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD);
+
+        // left for the player to pay
+        let bill = state.electricity.bill_amount();
+        assert!(bill > Money::cents(10));
+        assert_eq!(state.electricity.last_bill_time, ELECTRICITY_BILL_PERIOD);
+        assert_eq!(state.funds, Money::cents(10));
+        assert_eq!(state.spent, Money::zero());
+        let entry = engine.notifications().recent(1).next().unwrap();
+        assert_eq!(
+            entry.message,
+            format!("Auto-pay failed: not enough funds for the {bill} electricity bill")
+        );
+    }
+
+    #[test]
+    fn test_prepaid_electricity_credit() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(10_000))
+            .build();
+        assert_eq!(
+            engine.apply_action(
+                &mut state,
+                PlayerAction::PrepayElectricity {
+                    amount: Money::zero()
+                }
+            ),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(
+            engine.apply_action(
+                &mut state,
+                PlayerAction::PrepayElectricity {
+                    amount: Money::dollars(20_000)
+                }
+            ),
+            Err(ActionError::InsufficientFunds {
+                needed: Money::dollars(20_000),
+                have: Money::dollars(10_000)
+            })
+        );
+
+        state.electricity.consumed = 2_000_000_000;
+        let bill = state
+            .electricity
+            .check_bill(&engine.config().electricity_cost_levels)
+            .round_to_cents_half_up();
+        let prepaid = bill + Money::dollars(1);
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::PrepayElectricity { amount: prepaid },
+            )
+            .unwrap();
+        assert_eq!(state.funds, Money::dollars(10_000) - prepaid);
+        assert_eq!(state.spent, prepaid);
+
+        // bills draw from the credit first, even without auto-pay
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD);
+        assert_eq!(state.electricity.total_due, Money::zero());
+        assert_eq!(state.electricity.credit, Money::dollars(1));
+        assert_eq!(state.funds, Money::dollars(10_000) - prepaid);
+
+        // and then from the funds with auto-pay
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockAutoPay);
+        state.electricity.consumed = 2_000_000_000;
+        state.time = ELECTRICITY_BILL_PERIOD;
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD * 2);
+        assert_eq!(state.electricity.total_due, Money::zero());
+        assert_eq!(state.electricity.credit, Money::zero());
+        assert_eq!(
+            state.funds,
+            Money::dollars(10_000) - prepaid - bill + Money::dollars(1)
+        );
+        assert_eq!(state.spent, prepaid + bill - Money::dollars(1));
+    }
+
+    #[test]
+    fn test_resource_rates() {
+        let engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().build();
+        assert_eq!(engine.resource_rates(&state), ResourceRates::default());
+
+        // 50 seconds into the first period
+        state.time = 500_000;
+        state.earned = Money::dollars(10);
+        state.base_service.total = Ops(100);
+        let rates = engine.resource_rates(&state);
+        assert_eq!(rates.money, 20_000.);
+        assert_eq!(rates.ops, [2., 0., 0., 0., 0.]);
+
+        // 50 seconds into the second period,
+        // after earning $40 and 900 base ops in the first
+        state.reports.push(PeriodReport {
+            time: ELECTRICITY_BILL_PERIOD,
+            revenue: Money::dollars(40),
+            ops: [Ops(900), Ops(0), Ops(0), Ops(0), Ops(0)],
+            requests_dropped: 0,
+            energy_cost: Money::zero(),
+            demand_change: 0.,
+            cards_bought: 0,
+        });
+        state.time = ELECTRICITY_BILL_PERIOD + 500_000;
+        let rates = engine.resource_rates(&state);
+        // $50 and 1000 ops in 300 seconds
+        assert_eq!(rates.money, 5_000_000. / 300.);
+        assert_eq!(rates.ops, [1_000. / 300., 0., 0., 0., 0.]);
+    }
+
+    #[test]
+    fn test_switch_tariff() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(50)).build();
+        let switch = |tariff| PlayerAction::SwitchTariff { tariff };
+        assert_eq!(
+            engine.apply_action(&mut state, switch(Tariff::Tiered)),
+            Err(ActionError::NotAvailable)
+        );
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockTariffs(1));
+        assert!(state.can_choose_tariff);
+        assert_eq!(state.electricity.cost_level, 1);
+        assert_eq!(
+            engine.apply_action(&mut state, switch(Tariff::Flat)),
+            Err(ActionError::NotAvailable)
+        );
+
+        // half of the energy consumed during the day, half during the night
+        let day_length = engine.config().day_length;
+        for time in [day_length / 4, day_length * 3 / 4] {
+            state
+                .electricity
+                .add_consumption_during(100_000_000, engine.day_phase(time) < 0.5);
+        }
+        assert_eq!(state.electricity.peak_consumed, 100_000_000);
+        let offers = engine.tariff_offers(&state);
+        let price = engine.config().electricity_cost_levels[1];
+        assert_eq!(
+            offers,
+            vec![
+                (Tariff::Flat, price * 200),
+                // 50 Wev at 125% + 150 Wev at 50%
+                (
+                    Tariff::Tiered,
+                    Money::millicents(price.to_millicents() * (50 * 125 + 150 * 50) / 100)
+                ),
+                (Tariff::PeakOffPeak, price * 200),
+            ]
+        );
+
+        engine
+            .apply_action(&mut state, switch(Tariff::Tiered))
+            .unwrap();
+        assert_eq!(state.electricity.tariff, Tariff::Tiered);
+        assert_eq!(state.funds, Money::dollars(30));
+        assert_eq!(state.spent, TARIFF_SWITCH_FEE);
+        assert_eq!(
+            state
+                .electricity
+                .check_bill(&engine.config().electricity_cost_levels),
+            offers[1].1
+        );
+
+        // not again until the cooldown is over
+        assert_eq!(
+            engine.apply_action(&mut state, switch(Tariff::PeakOffPeak)),
+            Err(ActionError::NotAvailable)
+        );
+        state.time += TARIFF_SWITCH_COOLDOWN;
+        state.funds = Money::dollars(10);
+        assert_eq!(
+            engine.apply_action(&mut state, switch(Tariff::PeakOffPeak)),
+            Err(ActionError::InsufficientFunds {
+                needed: TARIFF_SWITCH_FEE,
+                have: Money::dollars(10)
+            })
+        );
+
+        // no peak rates without a daily cycle
+        let mut engine = GameEngine::with_config(GameConfig {
+            daily_amplitude: 0.,
+            ..Default::default()
+        });
+        state.funds = Money::dollars(50);
+        assert_eq!(
+            engine.apply_action(&mut state, switch(Tariff::PeakOffPeak)),
+            Err(ActionError::NotAvailable)
+        );
+        assert!(engine
+            .tariff_offers(&state)
+            .iter()
+            .all(|&(tariff, _)| tariff != Tariff::PeakOffPeak));
+    }
+
+    #[test]
+    fn test_hire_and_fire_staff() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(500)).build();
+        let hire = |tier, service| PlayerAction::Hire { tier, service };
+        assert_eq!(
+            engine.apply_action(&mut state, hire(StaffTier::Intern, ServiceKind::Base)),
+            Err(ActionError::NotAvailable)
+        );
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockStaff);
+        assert!(state.can_hire_staff);
+
+        // not for a service which is not available yet
+        assert_eq!(
+            engine.apply_action(&mut state, hire(StaffTier::Intern, ServiceKind::Epic)),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(
+            engine.apply_action(&mut state, hire(StaffTier::Senior, ServiceKind::Base)),
+            Err(ActionError::InsufficientFunds {
+                needed: Money::dollars(3_000),
+                have: Money::dollars(500),
+            })
+        );
+        engine
+            .apply_action(&mut state, hire(StaffTier::Junior, ServiceKind::Base))
+            .unwrap();
+        engine
+            .apply_action(&mut state, hire(StaffTier::Intern, ServiceKind::Base))
+            .unwrap();
+        assert_eq!(state.funds, Money::dollars(60));
+        assert_eq!(state.spent, Money::dollars(440));
+        assert_eq!(state.staff.len(), 2);
+
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::Fire { index: 2 }),
+            Err(ActionError::NotAvailable)
+        );
+        engine
+            .apply_action(&mut state, PlayerAction::Fire { index: 0 })
+            .unwrap();
+        assert_eq!(state.funds, Money::dollars(44));
+        assert_eq!(state.staff.len(), 1);
+        assert_eq!(state.staff[0].tier, StaffTier::Intern);
+
+        // no more than the maximum
+        state.funds = Money::dollars(10_000);
+        for _ in 1..MAX_STAFF {
+            engine
+                .apply_action(&mut state, hire(StaffTier::Intern, ServiceKind::Base))
+                .unwrap();
+        }
+        assert_eq!(
+            engine.apply_action(&mut state, hire(StaffTier::Intern, ServiceKind::Base)),
+            Err(ActionError::NotAvailable)
+        );
+    }
+
+    #[test]
+    fn test_staff_ops_rate() {
+        const SECONDS: u64 = 10;
+        for tier in StaffTier::ALL {
+            let mut engine = GameEngine::new();
+            let mut state = WorldStateBuilder::new()
+                .with_nodes_fully_upgraded(1)
+                .build();
+            state.can_hire_staff = true;
+            state.staff.push(StaffMember {
+                tier,
+                service: ServiceKind::Base,
+                hired_at: 0,
+            });
+
+            let mut time = state.time;
+            while time < SECONDS * STAFF_WORK_PERIOD {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            // let the last requests through
+            state.staff.clear();
+            for _ in 0..1_000 {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+
+            let counters = state.traffic[TrafficClass::Staff as usize];
+            assert_eq!(
+                counters.fulfilled + counters.dropped,
+                SECONDS * tier.ops_per_second() as u64,
+                "{tier:?}"
+            );
+            // not counted as the player's own ops
+            assert_eq!(state.traffic[TrafficClass::Player as usize].fulfilled, 0);
+        }
+    }
+
+    #[test]
+    fn test_staff_payroll() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(100)).build();
+        state.staff = vec![
+            StaffMember {
+                tier: StaffTier::Junior,
+                service: ServiceKind::Base,
+                hired_at: 0,
+            },
+            StaffMember {
+                tier: StaffTier::Intern,
+                service: ServiceKind::Base,
+                hired_at: 0,
+            },
+        ];
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD);
+        assert_eq!(state.funds, Money::dollars(80));
+        assert_eq!(state.spent, Money::dollars(20));
+        assert_eq!(state.total_payroll, Money::dollars(20));
+        assert_eq!(state.staff.len(), 2);
+    }
+
+    #[test]
+    fn test_unpaid_staff_quit() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(10)).build();
+        state.staff = vec![
+            StaffMember {
+                tier: StaffTier::Junior,
+                service: ServiceKind::Base,
+                hired_at: 0,
+            },
+            StaffMember {
+                tier: StaffTier::Intern,
+                service: ServiceKind::Base,
+                hired_at: 0,
+            },
+        ];
+        // the junior cannot be paid and quits, the intern is paid
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD);
+        assert_eq!(state.staff.len(), 1);
+        assert_eq!(state.staff[0].tier, StaffTier::Intern);
+        assert_eq!(state.funds, Money::dollars(6));
+        assert_eq!(state.total_payroll, Money::dollars(4));
+
+        // eventually nobody can be paid
+        state.time = ELECTRICITY_BILL_PERIOD;
+        state.funds = Money::dollars(3);
+        engine.update_major(&mut state, ELECTRICITY_BILL_PERIOD * 2);
+        assert!(state.staff.is_empty());
+        assert_eq!(state.funds, Money::dollars(3));
+    }
+
+    #[test]
+    fn test_auto_pay_prevents_powersave() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(100_000))
+            .build();
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockAutoPay);
+        let mut time = 0;
+        while time <= ELECTRICITY_BILL_PERIOD * 5 {
+            state.electricity.add_consumption(10_000_000);
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+            assert_eq!(state.time_until_powersave(), None);
+            assert!(!state.is_powersaving());
+        }
+        assert!(state.electricity.total_billed > state.powersave_threshold());
+        assert_eq!(
+            state.spent,
+            state.electricity.total_billed.round_to_cents_half_up()
+        );
+    }
+
+    #[test]
+    fn test_bill_paid_partially() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::cents(30)).build();
+        state.electricity.total_due = Money::cents(50);
+        state.electricity.last_bill_time = 1_000;
+        assert_eq!(state.bill_payment(), Money::cents(30));
+
+        // what the funds allow is paid, the rest is still due
+        engine
+            .apply_action(&mut state, PlayerAction::PayElectricityBill)
+            .unwrap();
+        assert_eq!(state.funds, Money::zero());
+        assert_eq!(state.spent, Money::cents(30));
+        assert_eq!(state.electricity.total_due, Money::cents(20));
+        assert_eq!(state.electricity.last_bill_time, 1_000);
+
+        // nothing is paid without funds
+        assert_eq!(state.bill_payment(), Money::zero());
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::PayElectricityBill),
+            Err(ActionError::InsufficientFunds {
+                needed: Money::cents(20),
+                have: Money::zero(),
+            })
+        );
+        state.funds = Money::cents(-500);
+        assert_eq!(state.bill_payment(), Money::zero());
+        assert!(engine
+            .apply_action(&mut state, PlayerAction::PayElectricityBill)
+            .is_err());
+        assert_eq!(state.funds, Money::cents(-500));
+        assert_eq!(state.electricity.total_due, Money::cents(20));
+
+        // the rest is paid once funds come in
+        state.funds = Money::dollars(1);
+        engine
+            .apply_action(&mut state, PlayerAction::PayElectricityBill)
+            .unwrap();
+        assert_eq!(state.funds, Money::cents(80));
+        assert_eq!(state.electricity.total_due, Money::zero());
+        assert_eq!(state.electricity.last_bill_time, 0);
+    }
+
+    #[test]
+    fn test_audio_event_on_card_available() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        let mut time = 0;
+        let mut step = |engine: &mut GameEngine, state: &mut WorldState| {
+            for _ in 0..10 {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(state, time);
+            }
+            engine.take_audio_events()
+        };
+
+        // the cards available from the start do not chime
+        assert!(!step(&mut engine, &mut state).contains(&AudioEvent::CardAvailable));
+
+        // "Super Ops" appears after enough base ops
+        state.base_service.total = Ops(1_500);
+        let events = step(&mut engine, &mut state);
+        assert_eq!(events, vec![AudioEvent::CardAvailable]);
+
+        // nothing new, no chime
+        assert!(step(&mut engine, &mut state).is_empty());
+    }
+
+    #[test]
+    fn test_visible_cards_match_full_sweep() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 7);
+        let mut state = WorldState::default();
+        engine.bootstrap_events(&state);
+        let mut time = 0;
+
+        fn assert_matches_sweep(engine: &GameEngine, state: &WorldState, step: &str) {
+            let visible: Vec<_> = engine.visible_cards().map(|card| card.id).collect();
+            let expected: Vec<_> = ALL_CARDS
+                .iter()
+                .filter(|card| card.should_appear(state))
+                .map(|card| card.id)
+                .collect();
+            assert_eq!(visible, expected, "visible cards after {step}");
+            let upcoming: Vec<_> = engine.upcoming_cards().map(|card| card.id).collect();
+            let expected: Vec<_> = ALL_CARDS
+                .iter()
+                .filter(|card| card.upcoming_progress(state).is_some())
+                .map(|card| card.id)
+                .collect();
+            assert_eq!(upcoming, expected, "upcoming cards after {step}");
+        }
+        assert_matches_sweep(&engine, &state, "bootstrap");
+
+        let actions = [
+            PlayerAction::UseCard { id: "a0p".into() },
+            PlayerAction::UpgradeRam { node: 0 },
+            PlayerAction::UseCard { id: "a1".into() },
+            PlayerAction::UpgradeCpu { node: 0 },
+            PlayerAction::AddNode,
+            PlayerAction::ChangePrice {
+                kind: ServiceKind::Base,
+                new_price: Money::millicents(40),
+            },
+        ];
+        for action in actions {
+            let step = format!("{action:?}");
+            // make sure that the action can be afforded
+            state.funds += Money::dollars(2_000);
+            state.base_service.total += Ops(2_000);
+            state.base_service.available += Ops(2_000);
+            engine.apply_action(&mut state, action).unwrap();
+            assert_matches_sweep(&engine, &state, &step);
+
+            for _ in 0..20 {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            assert_matches_sweep(&engine, &state, &format!("updates after {step}"));
+        }
+        assert!(state.is_card_used("a1"));
+        assert_eq!(state.nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_restart_node_requeues_waiting_requests() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        let node = &mut state.nodes[0];
+        assert_eq!(node.reserve_for(Memory::mb(64)), Ok(true));
+        let mem_required = ServiceKind::Base.mem_required() * 2;
+        for _ in 0..2 {
+            node.ram_usage += mem_required;
+            node.requests.push_back(WaitingRequest {
+                timestamp: 0,
+                amount: 2,
+                user_spec_id: None,
+                service: ServiceKind::Base,
+                mem_required,
+                outcome: BadRequestOutcome::Unchecked,
+                latency: 0,
+                staff: false,
+            });
+        }
+
+        engine
+            .apply_action(&mut state, PlayerAction::RestartNode { node: 0 })
+            .unwrap();
+        let node = &state.nodes[0];
+        assert!(node.requests.is_empty());
+        assert_eq!(node.ram_usage, Memory::zero());
+        assert_eq!(node.ram_reserved, Memory::zero());
+        assert_eq!(engine.waiting_queue.len(), 2);
+
+        // the requests are processed once the node is back up
+        let mut time = 0;
+        while time < NODE_RESTART_COOLDOWN + 50_000 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert!(engine.waiting_queue.is_empty());
+        assert_eq!(state.base_service.total, Ops(4));
+        assert_eq!(state.nodes[0].processing, 0);
+    }
+
+    #[test]
+    fn test_waiting_queue_saturation() {
+        let config = GameConfig {
+            waiting_queue_min: 10,
+            waiting_queue_per_node: [1; RoutingLevel::ALL.len()],
+            ..GameConfig::default()
+        };
+        let mut engine = GameEngine::with_config_seeded(config, 931);
+        let mut state = WorldStateBuilder::new()
+            .with_node(CloudNode::new(1))
+            .build();
+        // no node is free to route anything
+        for node in &mut state.nodes {
+            node.processing = node.num_cores;
+        }
+        let count_log = |engine: &GameEngine, message: &str| {
+            engine
+                .notifications()
+                .recent(NOTIFICATION_LOG_CAPACITY)
+                .filter(|entry| entry.message == message)
+                .count()
+        };
+
+        let mut time = 0;
+        let mut arrive = |engine: &mut GameEngine, state: &mut WorldState, count: u32| {
+            for _ in 0..count {
+                time += 1;
+                engine.queue.push(RequestEvent::new_arrived(
+                    time,
+                    None,
+                    1,
+                    ServiceKind::Base,
+                    false,
+                ));
+            }
+            time += MAJOR_UPDATE_PERIOD;
+            engine.update(state, time);
+        };
+
+        arrive(&mut engine, &mut state, 15);
+        assert_eq!(engine.waiting_queue.len(), 10);
+        assert_eq!(engine.drop_causes[DropCause::Queue as usize], 5);
+        assert!(engine.queue_saturated());
+        assert_eq!(engine.waiting_queue_occupancy(&state), 1.);
+        assert_eq!(count_log(&engine, t!("log.queue_saturated")), 1);
+
+        // one warning for the whole episode
+        arrive(&mut engine, &mut state, 15);
+        assert_eq!(count_log(&engine, t!("log.queue_saturated")), 1);
+        assert_eq!(count_log(&engine, t!("log.queue_cleared")), 0);
+
+        // still saturated while the queue is barely below capacity
+        engine.waiting_queue.truncate(8);
+        arrive(&mut engine, &mut state, 0);
+        assert!(engine.queue_saturated());
+
+        // clear once drained
+        engine.waiting_queue.truncate(2);
+        arrive(&mut engine, &mut state, 0);
+        assert!(!engine.queue_saturated());
+        assert_eq!(count_log(&engine, t!("log.queue_cleared")), 1);
+
+        // a new episode gets a new warning
+        arrive(&mut engine, &mut state, 10);
+        assert_eq!(count_log(&engine, t!("log.queue_saturated")), 2);
+        assert_eq!(engine.drop_causes[DropCause::Queue as usize], 2);
+    }
+
+    #[test]
+    fn test_double_release_is_detected() {
+        let mut node = CloudNode::new(0);
+        let mem_required = ServiceKind::Base.mem_required();
+        node.ram_usage += mem_required;
+        assert_eq!(node.try_release(mem_required), Ok(()));
+        assert_eq!(
+            node.try_release(mem_required),
+            Err(MemoryAccountingError {
+                node: 0,
+                in_use: Memory::zero(),
+                released: mem_required,
+            })
+        );
+        assert_eq!(node.ram_usage, Memory::zero());
+
+        // a waiting request whose memory was already released
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        state.nodes[0].requests.push_back(WaitingRequest {
+            timestamp: 0,
+            amount: 1,
+            user_spec_id: None,
+            service: ServiceKind::Base,
+            mem_required,
+            outcome: BadRequestOutcome::Unchecked,
+            latency: 0,
+            staff: false,
+        });
+        assert_eq!(engine.accounting_anomalies(), 0);
+        engine
+            .apply_action(&mut state, PlayerAction::RestartNode { node: 0 })
+            .unwrap();
+        assert_eq!(engine.accounting_anomalies(), 1);
+        assert_eq!(state.nodes[0].ram_usage, Memory::zero());
+        #[cfg(feature = "debug-tools")]
+        assert_eq!(engine.debug_snapshot(&state).accounting_anomalies, 1);
+    }
+
+    #[test]
+    fn test_restart_cooldown_blocks_processing() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        engine
+            .apply_action(&mut state, PlayerAction::RestartNode { node: 0 })
+            .unwrap();
+        let restarting_until = state.nodes[0].restarting_until;
+        assert_eq!(restarting_until, NODE_RESTART_COOLDOWN);
+
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::OpClick {
+                    kind: ServiceKind::Base,
+                    amount: 3,
+                },
+            )
+            .unwrap();
+        let mut time = 0;
+        while time + (TIME_UNITS_PER_CYCLE as u64) < NODE_RESTART_COOLDOWN {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert!(state.nodes[0].is_restarting(time));
+        assert_eq!(state.base_service.total, Ops(0));
+        assert_eq!(state.nodes[0].processing, 0);
+
+        // restarting again does not extend the downtime
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::RestartNode { node: 0 }),
+            Err(ActionError::NodeRestarting(0))
+        );
+        assert_eq!(state.nodes[0].restarting_until, restarting_until);
+
+        while time < NODE_RESTART_COOLDOWN + 50_000 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert!(!state.nodes[0].is_restarting(time));
+        assert_eq!(state.base_service.total, Ops(3));
+    }
+
+    #[test]
+    fn test_early_game_unaffected_by_legendary_tier() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 42);
+        let mut state = WorldState::default();
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 4,
+        };
+        engine.apply_action(&mut state, click.clone()).unwrap();
+        let mut time = 0;
+        for _ in 0..40 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        state.base_service.available = Ops(8);
+        engine
+            .apply_action(&mut state, PlayerAction::UseCard { id: "a0p".into() })
+            .unwrap();
+        assert!(state.is_card_used("a0p"));
+        for i in 0..2_000 {
+            if i % 40 == 0 {
+                engine.apply_action(&mut state, click.clone()).unwrap();
+            }
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+
+        // recorded before the legendary tier existed,
+        // then again once customers left services with long queues
+        // and the first arrivals of new users were staggered
+        assert_eq!(state.base_service.total, Ops(174));
+        assert_eq!(state.earned, Money::millicents(4_300));
+        assert_eq!(state.funds, Money::millicents(1_004_300));
+        assert_eq!(state.requests_dropped, 261);
+
+        assert!(!state.legendary_service.state.is_unlocked());
+        let card = card_by_id("a4").unwrap();
+        assert!(!card.should_appear(&state));
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("legendary"));
+    }
+
+    #[test]
+    fn test_clicks_counted() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 7);
+        let mut state = WorldState::default();
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 1,
+        };
+        let mut time = 0;
+        for _ in 0..60 {
+            engine.apply_action(&mut state, click.clone()).unwrap();
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.base_service.total_player_clicks, 60);
+        assert_eq!(state.super_service.total_player_clicks, 0);
+        let metrics = engine.metrics(&state);
+        assert_eq!(metrics.total_player_clicks, 60);
+        assert_eq!(metrics.clicks_per_minute, 60);
+        // a few seconds of clicking is nothing suspicious
+        assert!(!state.autoclicker_suspected);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.base_service.total_player_clicks, 60);
+    }
+
+    #[test]
+    fn test_rename_company() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::RenameCompany {
+                    name: "  Nimbus Inc. ".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(state.company_name, "Nimbus Inc.");
+        assert!(state.summary_text().contains("Company: Nimbus Inc.\n"));
+
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::RenameCompany {
+                    name: "   ".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(state.company_name, "My Cloud Co.");
+    }
+
+    #[test]
+    fn test_dismiss_win() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        assert!(!state.is_win_announced());
+        state.cards_used.push(UsedCard {
+            id: ID_WIN.into(),
+            time: 0,
+        });
+        assert!(state.is_win_announced());
+
+        engine
+            .apply_action(&mut state, PlayerAction::DismissWin)
+            .unwrap();
+        assert!(!state.is_win_announced());
+
+        // the dismissal survives a reload
+        let json = serde_json::to_string(&state).unwrap();
+        let state: WorldState = serde_json::from_str(&json).unwrap();
+        assert!(state.win_dismissed);
+        assert!(!state.is_win_announced());
+    }
+
+    #[test]
+    fn test_clicks_coalesced() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        for _ in 0..500 {
+            engine
+                .apply_action(
+                    &mut state,
+                    PlayerAction::OpClick {
+                        kind: ServiceKind::Base,
+                        amount: 1,
+                    },
+                )
+                .unwrap();
+        }
+        // nothing is scheduled until the next update
+        assert!(engine.queue.is_empty());
+
+        engine.flush_clicks(&state);
+        assert!(
+            engine.queue.len() <= 5,
+            "too many events: {}",
+            engine.queue.len()
+        );
+
+        // still batched when no node has memory to spare
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        state.nodes[0].ram_reserved = state.nodes[0].ram_capacity;
+        engine.push_player_requests(&state, ServiceKind::Base, 10_000, true);
+        let sets = engine.queue.len();
+        assert!(
+            sets <= MAX_PLAYER_REQUEST_SETS as usize,
+            "too many events: {sets}"
+        );
+
+        // all ops are fulfilled as before
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .build();
+        for _ in 0..500 {
+            engine
+                .apply_action(
+                    &mut state,
+                    PlayerAction::OpClick {
+                        kind: ServiceKind::Base,
+                        amount: 1,
+                    },
+                )
+                .unwrap();
+        }
+        let mut time = state.time;
+        for _ in 0..100 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.base_service.total, Ops(500));
+        assert_eq!(state.requests_dropped, 0);
+    }
+
+    #[test]
+    fn test_combo_multiplies_clicks() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .build();
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 1,
+        };
+        // click on every tick for 15 seconds
+        let mut time = state.time;
+        let mut expected = 0;
+        for _ in 0..300 {
+            expected += engine.combo(ServiceKind::Base).apply(1);
+            engine.apply_action(&mut state, click.clone()).unwrap();
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(engine.combo(ServiceKind::Base).multiplier(), 3.);
+        assert_eq!(engine.combo(ServiceKind::Super).multiplier(), 1.);
+        assert!(expected > 600, "{expected}");
+
+        // a single click now generates 3 ops
+        engine.apply_action(&mut state, click).unwrap();
+        for _ in 0..120 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.base_service.total, Ops(expected as i64 + 3));
+        // and the combo is gone after stopping
+        assert_eq!(engine.combo(ServiceKind::Base).multiplier(), 1.);
+    }
+
+    #[test]
+    fn test_powersave_transition_mid_load() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .time(ELECTRICITY_BILL_PERIOD)
+            .with_nodes_fully_upgraded(1)
+            .build();
+        let num_cores = state.nodes[0].num_cores;
+        let powersave_cores = state.nodes[0].usable_cores(true);
+        // requests arriving one after the other,
+        // all before the first one is processed
+        let start = state.time;
+        for i in 0..num_cores as u64 * 4 {
+            engine.queue.push(RequestEvent::new_arrived(
+                start + 1 + i * 20,
+                None,
+                1,
+                ServiceKind::Super,
+                false,
+            ));
+        }
+        let mut time = start;
+        while time < start + num_cores as u64 * 4 * 20 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.nodes[0].processing, num_cores);
+        assert!(!state.nodes[0].requests.is_empty());
+
+        // an unpaid bill sends the system into powersave mode
+        state.electricity.total_due = state.powersave_threshold() + Money::dollars(1);
+        state.electricity.last_bill_time = 1;
+        assert!(state.is_powersaving());
+
+        let mut waiting = state.nodes[0].requests.len();
+        let mut processed = state.super_service.total;
+        let mut stalled_ticks = 0;
+        let mut admitted_in_grace = false;
+        while waiting > 0 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+            let node = &state.nodes[0];
+            // never over capacity, nor admitting more than powersave allows
+            assert!(node.processing <= num_cores, "{}", node.processing);
+            assert!(node.processing - node.powersave_grace <= powersave_cores);
+            // the queue drains monotonically,
+            // without waiting for the requests in flight to finish
+            assert!(node.requests.len() <= waiting);
+            if node.requests.len() < waiting && node.processing > powersave_cores {
+                admitted_in_grace = true;
+            }
+            waiting = node.requests.len();
+            // and the node keeps working
+            if state.super_service.total == processed {
+                stalled_ticks += 1;
+            } else {
+                stalled_ticks = 0;
+            }
+            assert!(stalled_ticks < 100, "processing stalled");
+            processed = state.super_service.total;
+        }
+        assert!(admitted_in_grace);
+        assert!(state.total_processing().0 <= 1.);
+    }
+
+    #[test]
+    fn test_player_ops_outcome() {
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 3,
+        };
+
+        // a node with room to spare fulfills all clicks
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        push_requests_from_all(&mut engine);
+        engine.apply_action(&mut state, click.clone()).unwrap();
+        let mut time = 0;
+        for _ in 0..50 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        let player_ops = engine.take_player_ops();
+        // only ops requested by the player are counted (1 + 3 clicked),
+        // not those from customers or deleted user specs
+        assert_eq!(
+            player_ops[0],
+            PlayerOps {
+                fulfilled: 4,
+                dropped: 0
+            }
+        );
+        assert!(player_ops[1..].iter().all(PlayerOps::is_empty));
+        // taking them resets the counters
+        assert!(engine.take_player_ops().iter().all(PlayerOps::is_empty));
+
+        // a saturated node drops them
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        state.nodes[0].ram_usage = state.nodes[0].ram_capacity;
+        push_requests_from_all(&mut engine);
+        engine.apply_action(&mut state, click).unwrap();
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+        let player_ops = engine.take_player_ops();
+        assert_eq!(
+            player_ops[0],
+            PlayerOps {
+                fulfilled: 0,
+                dropped: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_bailout_offered_once() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(-80)).build();
+
+        let mut time = 0;
+        let mut step = |engine: &mut GameEngine, state: &mut WorldState| {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(state, time);
+        };
+
+        // not yet after a short while
+        for _ in 0..10 {
+            step(&mut engine, &mut state);
+        }
+        let insolvent_since = state.insolvent_since;
+        assert!(insolvent_since > 0);
+        assert!(!state.bailout_pending);
+
+        // offered after a full bill period
+        while !state.bailout_pending {
+            step(&mut engine, &mut state);
+        }
+        assert!(state.time - insolvent_since >= ELECTRICITY_BILL_PERIOD);
+
+        // does not fire again while the player decides
+        for _ in 0..10_000 {
+            step(&mut engine, &mut state);
+        }
+        assert!(state.bailout_pending);
+        assert_eq!(state.insolvent_since, insolvent_since);
+        assert!(state.loan.is_none());
+
+        engine
+            .apply_action(&mut state, PlayerAction::AcceptBailout)
+            .unwrap();
+        assert!(!state.bailout_pending);
+        assert_eq!(state.funds, BAILOUT_FUNDS);
+        assert_eq!(
+            state.loan.as_ref().map(|loan| loan.balance),
+            Some(BAILOUT_FUNDS - Money::dollars(-80))
+        );
+
+        // accepting again does nothing
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::AcceptBailout),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state.funds, BAILOUT_FUNDS);
+        assert_eq!(state.insolvent_since, 0);
+    }
+
+    #[test]
+    fn test_cumulative_upgrade_cost() {
+        // the initial levels are free
+        assert_eq!(cumulative_cpu_cost(&CPU_LEVELS, 0), Money::zero());
+        assert_eq!(cumulative_ram_cost(&RAM_LEVELS, 0), Money::zero());
+
+        // each level adds its own cost
+        for (level, &(_, _, cost)) in CPU_LEVELS.iter().enumerate().skip(1) {
+            assert_eq!(
+                cumulative_cpu_cost(&CPU_LEVELS, level as u8)
+                    - cumulative_cpu_cost(&CPU_LEVELS, level as u8 - 1),
+                cost
+            );
+        }
+        for (level, &(_, cost)) in RAM_LEVELS.iter().enumerate().skip(1) {
+            assert_eq!(
+                cumulative_ram_cost(&RAM_LEVELS, level as u8)
+                    - cumulative_ram_cost(&RAM_LEVELS, level as u8 - 1),
+                cost
+            );
+        }
+        assert_eq!(cumulative_cpu_cost(&CPU_LEVELS, 3), Money::dollars(580));
+        assert_eq!(cumulative_cpu_cost(&CPU_LEVELS, 10), Money::dollars(59_010));
+        assert_eq!(cumulative_ram_cost(&RAM_LEVELS, 10), Money::dollars(9_060));
+
+        // scaled by difficulty
+        let config = GameConfig::new(Difficulty::Hard);
+        assert_eq!(
+            cumulative_cpu_cost(&config.cpu_levels, 3),
+            config.cpu_levels[1].2 + config.cpu_levels[2].2 + config.cpu_levels[3].2
+        );
+    }
+
+    #[test]
+    fn test_node_hardware_value() {
+        let config = GameConfig::default();
+        let mut node = CloudNode::new(0);
+        assert_eq!(node.hardware_value(&config), BARE_NODE_COST);
+        node.cpu_level = 2;
+        node.ram_level = 1;
+        assert_eq!(
+            node.hardware_value(&config),
+            BARE_NODE_COST + Money::dollars(220) + Money::dollars(40)
+        );
+
+        // larger scales count full racks
+        let mut rack = CloudNode::new_fully_upgraded_rack(1);
+        assert_eq!(rack.hardware_value(&config), UPGRADED_RACK_COST);
+        rack.scale = NodeScale::Datacenter;
+        assert_eq!(
+            rack.hardware_value(&config),
+            UPGRADED_RACK_COST * DATACENTER_CAPACITY as i32
+        );
+
+        // prices follow the difficulty
+        let hard = GameConfig::new(Difficulty::Hard);
+        assert_eq!(
+            node.hardware_value(&hard),
+            hard.bare_node_cost
+                + hard.cpu_levels[1].2
+                + hard.cpu_levels[2].2
+                + hard.ram_levels[1].1
+        );
+        assert_eq!(
+            rack.hardware_value(&hard),
+            hard.upgraded_rack_cost * DATACENTER_CAPACITY as i32
+        );
+        assert!(node.hardware_value(&hard) > node.hardware_value(&config));
+    }
+
+    #[test]
+    fn test_request_sets_fit_node_memory() {
+        // with the cache taking half of a 256MB node,
+        // the biggest request sets would take all memory left
+        let run = |nodes: u32| {
+            let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 912);
+            let mut state = WorldStateBuilder::new()
+                .demand(50_000.)
+                .service_published(ServiceKind::Base, Money::millicents(14))
+                .with_customer(ServiceKind::Base)
+                .build();
+            state.cache_level = 1;
+            for id in 1..nodes {
+                state.nodes.push(CloudNode::new(id));
+            }
+            for node in &mut state.nodes {
+                let (num_cores, cpu_speed, _) = CPU_LEVELS[10];
+                node.cpu_level = 10;
+                node.cpu_speed = cpu_speed;
+                node.num_cores = num_cores;
+            }
+            engine.bootstrap_events(&state);
+
+            let cap = engine.bundle_caps[ServiceKind::Base as usize];
+            let free = state.nodes[0].ram_capacity
+                - GameEngine::calculate_memory_reserve_required(ServiceKind::Base, 1, 1., 0);
+            assert!(ServiceKind::Base.mem_required() * cap as i32 <= free * BUNDLE_MEMORY_SHARE);
+            let (_, amount) = GameEngine::group_demand(2_000_000., cap);
+            assert!(amount <= cap);
+
+            let mut time = 0;
+            while time < 60 * TIME_UNITS_PER_SECOND {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            state.base_service.total.0
+        };
+
+        let single = run(1);
+        let cluster = run(4);
+        assert!(single > 0);
+        assert!(cluster > 2 * single, "{cluster} vs {single}");
+    }
+
+    /// Run a game with one trial customer until the trial runs out.
+    fn expire_trial(engine: &mut GameEngine, state: &mut WorldState) -> ExpiredTrial {
+        engine.bootstrap_events(state);
+        let mut time = state.time;
+        while !state.user_specs.is_empty() {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(state, time);
+            assert!(time < 300_000, "the trial never ran out");
+        }
+        let mut expired = engine.take_expired_trials();
+        assert_eq!(expired.len(), 1);
+        assert!(engine.take_expired_trials().is_empty());
+        expired.remove(0)
+    }
+
+    #[test]
+    fn test_trial_expiry_offers_extension() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 915);
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(100))
+            .demand(20.)
+            .with_user(ServiceKind::Base, UserKind::Customer, 100_000)
+            .build();
+        let spec = state.user_specs[0].clone();
+        let trial = expire_trial(&mut engine, &mut state);
+        assert_eq!(trial.spec, spec);
+        assert!(trial.ops > 0);
+        assert!(trial.expired_at >= 100_000);
+        let notification = engine.notifications().recent(1).next().unwrap();
+        assert!(
+            notification.message.contains("trial"),
+            "{}",
+            notification.message
+        );
+
+        // the player takes up the offer
+        let fee = trial_extension_fee(ServiceKind::Base, TRIAL_EXTENSION);
+        assert_eq!(fee, TRIAL_EXTENSION_FEE);
+        let extend = PlayerAction::ExtendTrial {
+            spec_id: spec.id,
+            duration: TRIAL_EXTENSION,
+        };
+        engine.apply_action(&mut state, extend.clone()).unwrap();
+        assert_eq!(state.funds, Money::dollars(100) - fee);
+        assert_eq!(state.user_specs.len(), 1);
+        assert_eq!(state.user_specs[0].service, ServiceKind::Base);
+        assert_eq!(state.user_specs[0].trial_time, state.time + TRIAL_EXTENSION);
+
+        // clicking again neither duplicates the customer nor charges twice
+        assert_eq!(
+            engine.apply_action(&mut state, extend),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state.user_specs.len(), 1);
+        assert_eq!(state.funds, Money::dollars(100) - fee);
+    }
+
+    #[test]
+    fn test_trial_extension_window() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 915);
+        let mut state = WorldStateBuilder::new()
+            .with_user(ServiceKind::Super, UserKind::Customer, 50_000)
+            .build();
+        let trial = expire_trial(&mut engine, &mut state);
+        let extend = PlayerAction::ExtendTrial {
+            spec_id: trial.spec.id,
+            duration: TRIAL_EXTENSION / 2,
+        };
+
+        // not enough funds for the fee
+        let fee = trial_extension_fee(ServiceKind::Super, TRIAL_EXTENSION / 2);
+        assert_eq!(fee, TRIAL_EXTENSION_FEE * 2);
+        state.funds = fee - Money::cents(1);
+        assert_eq!(
+            engine.apply_action(&mut state, extend.clone()),
+            Err(ActionError::InsufficientFunds {
+                needed: fee,
+                have: state.funds,
+            })
+        );
+
+        // too late
+        state.funds = fee;
+        state.time = trial.expired_at + TRIAL_EXTENSION_WINDOW;
+        assert!(!trial.is_extendable(state.time));
+        assert_eq!(
+            engine.apply_action(&mut state, extend),
+            Err(ActionError::NotAvailable)
+        );
+        assert!(state.user_specs.is_empty());
+        assert_eq!(state.funds, fee);
+    }
+
+    /// Play a daily challenge with a canned player script
+    /// until past its end.
+    fn play_daily(challenge: DailyChallenge) -> (GameEngine, WorldState) {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), challenge.seed);
+        let mut state = WorldStateBuilder::new()
+            .funds(challenge.modifiers.starting_funds)
+            .demand(5.)
+            .service_published(ServiceKind::Base, Money::millicents(50))
+            .with_customer(ServiceKind::Base)
+            .build();
+        state.daily = Some(challenge);
+        engine.bootstrap_events(&state);
+        let mut time = 0;
+        while time < challenge.ends_at() + TIME_UNITS_PER_MINUTE {
+            time += 2_500;
+            if time % 100_000 == 0 {
+                let _ = engine.apply_action(
+                    &mut state,
+                    PlayerAction::OpClick {
+                        kind: ServiceKind::Base,
+                        amount: 1,
+                    },
+                );
+            }
+            if time == 5 * TIME_UNITS_PER_MINUTE {
+                engine
+                    .apply_action(
+                        &mut state,
+                        PlayerAction::ChangePrice {
+                            kind: ServiceKind::Base,
+                            new_price: Money::millicents(60),
+                        },
+                    )
+                    .unwrap();
+            }
+            engine.update(&mut state, time);
+        }
+        (engine, state)
+    }
+
+    /// Two players on the same date taking the same actions
+    /// end up with the same game.
+    #[test]
+    fn test_daily_challenge_is_deterministic() {
+        let date = DailyDate {
+            year: 2026,
+            month: 10,
+            day: 16,
+        };
+        let (_, first) = play_daily(DailyChallenge::for_date(date));
+        let (_, second) = play_daily(DailyChallenge::for_date(date));
+        assert!(first.base_service.total > Ops(0));
+        assert_eq!(first, second);
+        assert_eq!(first.score(), second.score());
+
+        let (_, other) = play_daily(DailyChallenge::for_date(DailyDate { day: 17, ..date }));
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_daily_challenge_freezes_at_the_end() {
+        let challenge = DailyChallenge::for_date(DailyDate {
+            year: 2026,
+            month: 10,
+            day: 16,
+        });
+        let (mut engine, mut state) = play_daily(challenge);
+        assert_eq!(state.time, challenge.ends_at());
+        assert!(state.is_daily_over());
+        assert_eq!(state.daily_time_left(), Some(0));
+        assert_eq!(
+            engine.notifications().recent(1).next().unwrap().message,
+            "The daily challenge is over"
+        );
+        // the attackers came early, whatever the demand
+        assert!(state.demand < DEMAND_DOS_THRESHOLD);
+        assert!(state.user_specs.iter().any(|spec| spec.is_bad()));
+        assert!(state.summary_text().contains("Daily challenge: 2026-10-16"));
+
+        // nothing moves any more
+        let frozen = state.clone();
+        engine.update(&mut state, challenge.ends_at() + 10 * TIME_UNITS_PER_MINUTE);
+        assert_eq!(state, frozen);
+        assert_eq!(
+            engine.apply_action(
+                &mut state,
+                PlayerAction::OpClick {
+                    kind: ServiceKind::Base,
+                    amount: 1,
+                },
+            ),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state, frozen);
+    }
+
+    #[test]
+    fn test_daily_challenge_price_floor() {
+        let challenge = DailyChallenge::for_date(DailyDate {
+            year: 2026,
+            month: 10,
+            day: 16,
+        });
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), challenge.seed);
+        let mut state = WorldState::new_daily(challenge, "Daily Co".to_string());
+        assert_eq!(state.funds, challenge.modifiers.starting_funds);
+        let floor = challenge.modifiers.price_floor(ServiceKind::Base);
+        assert_eq!(
+            engine.apply_action(
+                &mut state,
+                PlayerAction::ChangePrice {
+                    kind: ServiceKind::Base,
+                    new_price: floor - Money::millicents(1),
+                },
+            ),
+            Err(ActionError::BelowPriceFloor(floor))
+        );
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::ChangePrice {
+                    kind: ServiceKind::Base,
+                    new_price: floor,
+                },
+            )
+            .unwrap();
+        assert_eq!(state.base_service.price, floor);
+
+        // regular games have no floor
+        let mut state = WorldState::default();
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::ChangePrice {
+                    kind: ServiceKind::Base,
+                    new_price: Money::millicents(1),
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_undo_last_purchase() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000))
+            .build();
+        let before = state.clone();
+
+        engine
+            .apply_action(&mut state, PlayerAction::UpgradeCpu { node: 0 })
+            .unwrap();
+        engine
+            .apply_action(&mut state, PlayerAction::UpgradeRam { node: 0 })
+            .unwrap();
+        let upgraded = state.clone();
+        assert!(state.funds < before.funds);
+
+        // only the latest upgrade is taken back
+        engine
+            .apply_action(&mut state, PlayerAction::UndoLastPurchase)
+            .unwrap();
+        assert_eq!(state.nodes[0].cpu_level, upgraded.nodes[0].cpu_level);
+        assert_eq!(state.nodes[0].ram_level, before.nodes[0].ram_level);
+        assert_eq!(state.nodes[0].ram_capacity, before.nodes[0].ram_capacity);
+        let ram_cost = engine.config().ram_levels[1].1;
+        assert_eq!(state.funds, upgraded.funds + ram_cost);
+        assert_eq!(state.spent, upgraded.spent - ram_cost);
+
+        // and only once
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::UndoLastPurchase),
+            Err(ActionError::NotAvailable)
+        );
+    }
+
+    #[test]
+    fn test_undo_purchase_expires() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000))
+            .build();
+
+        engine
+            .apply_action(&mut state, PlayerAction::UpgradeCpu { node: 0 })
+            .unwrap();
+        state.time += PURCHASE_UNDO_WINDOW - 1;
+        assert!(engine.undoable_purchase(state.time).is_some());
+        state.time += 1;
+        assert!(engine.undoable_purchase(state.time).is_none());
+        let upgraded = state.clone();
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::UndoLastPurchase),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state, upgraded);
+    }
+
+    #[test]
+    fn test_undo_purchase_blocked_by_memory_in_use() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000))
+            .build();
+
+        engine
+            .apply_action(&mut state, PlayerAction::UpgradeRam { node: 0 })
+            .unwrap();
+        // requests already take up more than the memory before the upgrade
+        state.nodes[0].ram_usage = state.nodes[0].ram_capacity;
+        let upgraded = state.clone();
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::UndoLastPurchase),
+            Err(ActionError::UpgradeInUse(0))
+        );
+        assert_eq!(state, upgraded);
+
+        // the memory is freed in time
+        state.nodes[0].ram_usage = Memory::zero();
+        engine
+            .apply_action(&mut state, PlayerAction::UndoLastPurchase)
+            .unwrap();
+        assert_eq!(state.nodes[0].ram_level, 0);
+    }
+
+    #[test]
+    fn test_upgrade_all_nodes_ram() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .with_node(CloudNode::new(1))
+            .with_node(CloudNode::new_fully_upgraded(2))
+            .build();
+        state.nodes[1].ram_level = RAM_LEVELS.len() as u8 - 2;
+        let maxed = state.nodes[2].clone();
+
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeAllNodesRam(1));
+        assert_eq!(state.nodes[0].ram_level, 1);
+        assert_eq!(state.nodes[0].ram_capacity, RAM_LEVELS[1].0);
+        assert_eq!(state.nodes[1].ram_level, RAM_LEVELS.len() as u8 - 1);
+        // maxed out nodes are skipped
+        assert_eq!(state.nodes[2], maxed);
+
+        // never beyond the maximum level
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeAllNodesRam(3));
+        assert_eq!(state.nodes[0].ram_level, 4);
+        assert_eq!(state.nodes[1].ram_level, RAM_LEVELS.len() as u8 - 1);
+        assert_eq!(
+            state.nodes[1].ram_capacity,
+            RAM_LEVELS[RAM_LEVELS.len() - 1].0
+        );
+    }
+
+    #[test]
+    fn test_overclock_costs_energy() {
+        let effect = CardEffect::OverclockNodes {
+            speed_bonus: 1,
+            energy_penalty: 0.2,
+        };
+        let mut bills = vec![];
+        for overclock in [false, true] {
+            let mut engine = GameEngine::new();
+            let mut state = WorldStateBuilder::new()
+                .with_nodes_fully_upgraded(1)
+                .build();
+            if overclock {
+                engine.apply_card_effect(&mut state, &effect);
+                assert_eq!(
+                    state.nodes[0].cpu_speed,
+                    CPU_LEVELS[CPU_LEVELS.len() - 1].1 + 1
+                );
+            }
+            for i in 0..2_000 {
+                engine.queue.push(RequestEvent::new_arrived(
+                    1 + i * 100,
+                    None,
+                    1,
+                    ServiceKind::Base,
+                    false,
+                ));
+            }
+            let mut time = 0;
+            while time <= ELECTRICITY_BILL_PERIOD {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            assert_eq!(state.base_service.total, Ops(2_000));
+            bills.push(state.electricity.total_due);
+        }
+        assert!(bills[0] > Money::zero());
+        assert!(bills[1] > bills[0], "{} <= {}", bills[1], bills[0]);
+    }
+
+    fn count_attackers(state: &WorldState) -> usize {
+        state.user_specs.iter().filter(|spec| spec.is_bad()).count()
+    }
+
+    #[test]
+    fn test_dos_spawns_respect_spam_protection() {
+        for (protection, spawns) in [(0., true), (0.5, true), (1., false)] {
+            // publishing a service under high demand
+            let mut engine = GameEngine::new();
+            let mut state = WorldStateBuilder::new()
+                .demand(DEMAND_DOS_THRESHOLD + 1.)
+                .service_unlocked(ServiceKind::Super)
+                .build();
+            state.spam_protection = protection;
+            engine.apply_card_effect(&mut state, &CardEffect::PublishService(ServiceKind::Super));
+            assert_eq!(
+                count_attackers(&state),
+                spawns as usize,
+                "publishing at protection {protection}"
+            );
+
+            // publicity taking demand over the threshold
+            let mut engine = GameEngine::new();
+            let mut state = WorldStateBuilder::new()
+                .demand(DEMAND_DOS_THRESHOLD - 1.)
+                .build();
+            state.spam_protection = protection;
+            engine.apply_card_effect(&mut state, &CardEffect::AddPublicityRate(2., 0.));
+            assert_eq!(
+                count_attackers(&state),
+                spawns as usize * DOS_TARGETS.len(),
+                "publicity at protection {protection}"
+            );
+
+            // demand growing over the threshold on its own
+            let mut engine = GameEngine::new();
+            let mut state = WorldStateBuilder::new()
+                .demand(DEMAND_DOS_THRESHOLD - 0.01)
+                .build();
+            state.demand_rate = 100.;
+            state.spam_protection = protection;
+            engine.update_major(&mut state, INCREASE_DEMAND_PERIOD);
+            assert!(state.demand > DEMAND_DOS_THRESHOLD);
+            assert_eq!(
+                count_attackers(&state),
+                spawns as usize * DOS_TARGETS.len(),
+                "organic growth at protection {protection}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spam_protection_weakens_attackers() {
+        let attacker = CloudUserSpec {
+            id: 100,
+            service: ServiceKind::Base,
+            trial_time: 0,
+            kind: UserKind::Attacker,
+        };
+        let customer = CloudUserSpec {
+            kind: UserKind::Customer,
+            ..attacker.clone()
+        };
+        let engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().demand(4_000.).build();
+        let full =
+            GameEngine::user_demand(&state, &customer, 1., &engine.queue_demand_factor).unwrap();
+        assert_eq!(
+            GameEngine::user_demand(&state, &attacker, 1., &engine.queue_demand_factor),
+            Some(full)
+        );
+
+        state.spam_protection = 0.5;
+        assert_eq!(
+            GameEngine::user_demand(&state, &attacker, 1., &engine.queue_demand_factor),
+            Some(full / 2.)
+        );
+        assert_eq!(
+            GameEngine::user_demand(&state, &customer, 1., &engine.queue_demand_factor),
+            Some(full)
+        );
+
+        state.spam_protection = 1.;
+        assert_eq!(
+            GameEngine::user_demand(&state, &attacker, 1., &engine.queue_demand_factor),
+            None
+        );
+    }
+
+    #[test]
+    fn test_queue_waits_usable_cores() {
+        let mut state = WorldStateBuilder::new().build();
+        state.nodes = vec![
+            CloudNode::new_fully_upgraded(0),
+            CloudNode::new_fully_upgraded(1),
+        ];
+        for _ in 0..10 {
+            state.nodes[0].requests.push_back(WaitingRequest {
+                timestamp: 0,
+                amount: 4,
+                user_spec_id: None,
+                service: ServiceKind::Base,
+                mem_required: Memory::zero(),
+                outcome: BadRequestOutcome::Unchecked,
+                latency: 0,
+                staff: false,
+            });
+        }
+        let wait = queue_waits(&state)[ServiceKind::Base as usize];
+        assert!(wait > 0.);
+
+        // a restarting node lends no cores
+        state.time = POWERSAVE_GRACE_PERIOD + 1;
+        state.nodes[1].restarting_until = state.time + NODE_RESTART_COOLDOWN;
+        let restarting = queue_waits(&state)[ServiceKind::Base as usize];
+        assert!((restarting - wait * 2.).abs() < 1e-6);
+
+        // powersave mode leaves a quarter of the cores
+        state.nodes[1].restarting_until = 0;
+        state.electricity.total_due = state.powersave_threshold() + Money::dollars(1);
+        state.electricity.last_bill_time = 1;
+        assert!(state.is_powersaving());
+        let powersave = queue_waits(&state)[ServiceKind::Base as usize];
+        let ratio = state.nodes[0].num_cores as f64 / state.nodes[0].usable_cores(true) as f64;
+        assert!((powersave - wait * ratio).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_queue_pressure_decays_demand() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .demand(400.)
+            .service_published(ServiceKind::Super, Money::millicents(40))
+            .build();
+        // keep demand from growing on its own
+        state.demand_rate = 0.;
+        let customer = |service| CloudUserSpec {
+            id: 0,
+            service,
+            trial_time: 0,
+            kind: UserKind::Customer,
+        };
+        let demand = |engine: &GameEngine, state: &WorldState, service| {
+            GameEngine::user_demand(state, &customer(service), 1., &engine.queue_demand_factor)
+                .unwrap()
+        };
+        let base = demand(&engine, &state, ServiceKind::Base);
+        let super_ = demand(&engine, &state, ServiceKind::Super);
+
+        // a backlog of base requests, none dropped
+        for _ in 0..100 {
+            state.nodes[0].requests.push_back(WaitingRequest {
+                timestamp: 0,
+                amount: 4,
+                user_spec_id: None,
+                service: ServiceKind::Base,
+                mem_required: Memory::zero(),
+                outcome: BadRequestOutcome::Unchecked,
+                latency: 0,
+                staff: false,
+            });
+        }
+        let mut time = 0;
+        for _ in 0..100 {
+            time += MAJOR_UPDATE_PERIOD;
+            engine.update_major(&mut state, time);
+            state.time = time;
+        }
+        assert_eq!(state.requests_dropped, 0);
+        assert!(engine.is_queue_pressured(ServiceKind::Base));
+        assert!(!engine.is_queue_pressured(ServiceKind::Super));
+        let metrics = engine.metrics(&state);
+        assert!(metrics.queue_pressure[ServiceKind::Base as usize] > 1.);
+        assert_eq!(metrics.queue_pressure[ServiceKind::Super as usize], 0.);
+
+        // only the backlogged service loses customers
+        let decayed = demand(&engine, &state, ServiceKind::Base);
+        assert!(decayed < base * 0.9, "{decayed} vs {base}");
+        assert!(decayed >= base * MIN_QUEUE_DEMAND_FACTOR);
+        assert_eq!(demand(&engine, &state, ServiceKind::Super), super_);
+
+        // customers come back once the queue clears
+        state.nodes[0].requests.clear();
+        time += MAJOR_UPDATE_PERIOD;
+        engine.update_major(&mut state, time);
+        state.time = time;
+        assert!(!engine.is_queue_pressured(ServiceKind::Base));
+        assert!(demand(&engine, &state, ServiceKind::Base) > decayed);
+        for _ in 0..200 {
+            time += MAJOR_UPDATE_PERIOD;
+            engine.update_major(&mut state, time);
+            state.time = time;
+        }
+        assert_eq!(demand(&engine, &state, ServiceKind::Base), base);
+    }
+
+    #[test]
+    fn test_dos_immunity_after_protection_upgrade() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .demand(DEMAND_DOS_THRESHOLD - 1.)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+            .build();
+        state.time = 1_000;
+
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeSpamProtection(0.5));
+        // existing attackers stay, if weakened
+        assert_eq!(count_attackers(&state), 1);
+        state.user_specs.clear();
+        engine.apply_card_effect(&mut state, &CardEffect::AddPublicityRate(2., 0.));
+        assert_eq!(count_attackers(&state), 0);
+
+        // once the window is over, demand crossing the threshold brings them
+        state.demand = DEMAND_DOS_THRESHOLD - 1.;
+        state.time += DOS_IMMUNITY_PERIOD;
+        engine.apply_card_effect(&mut state, &CardEffect::AddPublicityRate(2., 0.));
+        assert_eq!(count_attackers(&state), DOS_TARGETS.len());
+
+        // full protection removes them for good
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeSpamProtection(1.));
+        assert_eq!(count_attackers(&state), 0);
+        state.time += DOS_IMMUNITY_PERIOD;
+        state.demand = DEMAND_DOS_THRESHOLD - 1.;
+        engine.apply_card_effect(&mut state, &CardEffect::AddPublicityRate(2., 0.));
+        assert_eq!(count_attackers(&state), 0);
+    }
+
+    #[test]
+    fn test_purge_blocked_attackers() {
+        // a save from before full protection kept attackers out
+        let mut state = WorldStateBuilder::new()
+            .with_user(ServiceKind::Base, UserKind::Customer, 0)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+            .build();
+        state.spam_protection = 0.875;
+        state.purge_blocked_attackers();
+        assert_eq!(count_attackers(&state), 1);
+
+        state.spam_protection = 1.;
+        state.purge_blocked_attackers();
+        assert_eq!(count_attackers(&state), 0);
+        assert_eq!(state.user_specs.len(), 1);
+
+        // an attacker left behind ends its chain of requests
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+            .build();
+        state.spam_protection = 1.;
+        let id = state.user_specs[0].id;
+        engine.queue.push(RequestEvent::new_arrived(
+            100,
+            Some(id),
+            1,
+            ServiceKind::Base,
+            true,
+        ));
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+        assert_eq!(count_attackers(&state), 0);
+    }
+
+    #[test]
+    fn test_hop_between_nodes() {
+        let state = WorldState {
+            nodes: (0..RACK_CAPACITY * 2).map(CloudNode::new).collect(),
+            ..Default::default()
+        };
+        // single machines are all in the same office
+        assert_eq!(Hop::between(&state, 0, RACK_CAPACITY - 1), Hop::SameRack);
+        assert_eq!(Hop::between(&state, 0, RACK_CAPACITY), Hop::Rack);
+
+        let state = WorldState {
+            nodes: (0..DATACENTER_CAPACITY * 2)
+                .map(CloudNode::new_fully_upgraded_rack)
+                .collect(),
+            ..Default::default()
+        };
+        // each rack node is a rack of its own
+        assert_eq!(Hop::between(&state, 3, 3), Hop::SameRack);
+        assert_eq!(Hop::between(&state, 0, DATACENTER_CAPACITY - 1), Hop::Rack);
+        assert_eq!(
+            Hop::between(&state, 0, DATACENTER_CAPACITY),
+            Hop::Datacenter
+        );
+
+        for (hop, latency) in [
+            (Hop::SameRack, [0, 0, 0]),
+            (Hop::Rack, [RACK_HOP_LATENCY, RACK_HOP_LATENCY / 2, 0]),
+            (
+                Hop::Datacenter,
+                [DATACENTER_HOP_LATENCY, DATACENTER_HOP_LATENCY / 2, 0],
+            ),
+        ] {
+            let levels = [
+                RoutingLevel::MainNode,
+                RoutingLevel::Distributed,
+                RoutingLevel::NoRoutingCost,
+            ];
+            assert_eq!(levels.map(|level| hop.latency(level)), latency, "{hop:?}");
+        }
+    }
+
+    /// Requests routed across data centers take longer to process,
+    /// unless routing costs nothing.
+    #[test]
+    fn test_routing_latency() {
+        let mut latencies = vec![];
+        for (racks, routing_level) in [
+            (false, RoutingLevel::MainNode),
+            (true, RoutingLevel::MainNode),
+            (true, RoutingLevel::Distributed),
+            (true, RoutingLevel::NoRoutingCost),
+        ] {
+            let nodes = if racks {
+                (0..DATACENTER_CAPACITY * 2)
+                    .map(CloudNode::new_fully_upgraded_rack)
+                    .collect()
+            } else {
+                (0..RACK_CAPACITY)
+                    .map(CloudNode::new_fully_upgraded)
+                    .collect()
+            };
+            let mut state = WorldState {
+                nodes,
+                routing_level,
+                ..Default::default()
+            };
+            let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 902);
+            for i in 0..2_000 {
+                engine.queue.push(RequestEvent::new_arrived(
+                    1 + i * 50,
+                    None,
+                    1,
+                    ServiceKind::Base,
+                    false,
+                ));
+            }
+            let mut time = 0;
+            while time < 2_000 * 50 + TIME_UNITS_PER_MINUTE {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+            assert_eq!(state.base_service.total, Ops(2_000));
+            latencies.push(engine.metrics(&state).routing_latency);
+        }
+
+        // nodes in the same rack add nothing
+        assert_eq!(latencies[0], 0.);
+        // about half of the requests cross data centers
+        let expected = (RACK_HOP_LATENCY + DATACENTER_HOP_LATENCY) as f32 / 2.;
+        assert!(
+            (latencies[1] - expected).abs() < expected * 0.2,
+            "{} vs {expected}",
+            latencies[1]
+        );
+        assert!(
+            (latencies[2] - expected / 2.).abs() < expected * 0.2,
+            "{} vs {}",
+            latencies[2],
+            expected / 2.
+        );
+        assert_eq!(latencies[3], 0.);
+    }
+
+    #[test]
+    fn test_narrative_event_times_out() {
+        fn run_until(engine: &mut GameEngine, state: &mut WorldState, end: u64) {
+            let mut time = state.time;
+            while time < end {
+                time += TIME_UNITS_PER_MINUTE / 10;
+                engine.update(state, time);
+            }
+        }
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 897);
+
+        // nothing happens while no event's condition holds
+        let mut state = WorldState::default();
+        run_until(&mut engine, &mut state, TIME_UNITS_PER_MINUTE * 60);
+        assert!(state.narrative.pending().is_none());
+
+        // only the blogger is interested so far
+        let mut state = WorldStateBuilder::new()
+            .time(state.time)
+            .earned(Money::dollars(25))
+            .build();
+        while state.narrative.pending().is_none() {
+            assert!(state.time < TIME_UNITS_PER_MINUTE * 180, "no event fired");
+            let end = state.time + TIME_UNITS_PER_MINUTE;
+            run_until(&mut engine, &mut state, end);
+        }
+        let pending = state.narrative.pending().unwrap().clone();
+        assert_eq!(pending.id, "blogger");
+
+        // the game goes on without the player's answer
+        run_until(
+            &mut engine,
+            &mut state,
+            pending.since + NARRATIVE_TIMEOUT - TIME_UNITS_PER_MINUTE,
+        );
+        assert!(state.narrative.pending().is_some());
+        assert!(state.time > pending.since);
+
+        // until it is settled with the safe choice
+        run_until(
+            &mut engine,
+            &mut state,
+            pending.since + NARRATIVE_TIMEOUT + TIME_UNITS_PER_MINUTE,
+        );
+        assert!(state.narrative.pending().is_none());
+        assert!(state.narrative.has_happened("blogger"));
+        let message = &engine.notifications().recent(1).next().unwrap().message;
+        assert!(message.ends_with("Politely decline"), "{message}");
+
+        // and it never happens again
+        let end = state.time + TIME_UNITS_PER_MINUTE * 60;
+        run_until(&mut engine, &mut state, end);
+        assert!(state.narrative.pending().is_none());
+    }
+
+    #[test]
+    fn test_resolve_event_once() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(500))
+            .demand(10.)
+            .build();
+        let resolve = |id: &'static str, choice| PlayerAction::ResolveEvent {
+            id: id.into(),
+            choice,
+        };
+
+        assert_eq!(
+            engine.apply_action(&mut state, resolve("hackathon", 1)),
+            Err(ActionError::NotAvailable)
+        );
+
+        state.narrative.fire("hackathon", 0);
+        // only the pending event and its choices can be picked
+        assert_eq!(
+            engine.apply_action(&mut state, resolve("cat", 1)),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(
+            engine.apply_action(&mut state, resolve("hackathon", 2)),
+            Err(ActionError::NotAvailable)
+        );
+        assert!(state.narrative.pending().is_some());
+
+        // the sponsorship is charged on the next bill
+        engine
+            .apply_action(&mut state, resolve("hackathon", 1))
+            .unwrap();
+        assert_eq!(state.electricity.total_due, Money::dollars(150));
+        assert_eq!(state.funds, Money::dollars(500));
+        assert_eq!(state.demand, 50.);
+
+        // answering again does nothing
+        assert_eq!(
+            engine.apply_action(&mut state, resolve("hackathon", 1)),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state.electricity.total_due, Money::dollars(150));
+        assert_eq!(state.demand, 50.);
+        assert!(state.narrative.has_happened("hackathon"));
+    }
+
+    #[test]
+    fn test_declare_bankruptcy() {
+        let mut engine = GameEngine::with_config(GameConfig::new(Difficulty::Hard));
+        let mut state = WorldState {
+            difficulty: Difficulty::Hard,
+            bailout_pending: true,
+            ..WorldStateBuilder::new()
+                .funds(Money::dollars(-500))
+                .earned(Money::dollars(120))
+                .demand(900.)
+                .total_ops(ServiceKind::Base, Ops(5_000))
+                .build()
+        };
+        state.peak_demand = 900.;
+
+        engine
+            .apply_action(&mut state, PlayerAction::DeclareBankruptcy)
+            .unwrap();
+        assert!(!state.bailout_pending);
+        assert_eq!(state.funds, WorldState::default().funds);
+        assert_eq!(state.base_service.total, Ops(0));
+        assert_eq!(state.difficulty, Difficulty::Hard);
+        assert_eq!(state.achievements.bankruptcies, 1);
+        assert_eq!(state.achievements.best_earned, Money::dollars(120));
+        assert_eq!(state.achievements.peak_demand, 900.);
+        assert!(state.achievements.best_score > 0);
+
+        // records are only ever beaten
+        state.peak_demand = 10.;
+        state.bailout_pending = true;
+        engine
+            .apply_action(&mut state, PlayerAction::DeclareBankruptcy)
+            .unwrap();
+        assert_eq!(state.achievements.bankruptcies, 2);
+        assert_eq!(state.achievements.peak_demand, 900.);
+        assert_eq!(state.achievements.best_earned, Money::dollars(120));
+    }
+
+    #[test]
+    fn test_declare_bankruptcy_in_daily_challenge() {
+        let challenge = DailyChallenge::for_date(DailyDate {
+            year: 2026,
+            month: 10,
+            day: 16,
+        });
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::new_daily(challenge, "Nimbus Inc.".to_string());
+        state.funds = Money::dollars(-500);
+        state.bailout_pending = true;
+
+        engine
+            .apply_action(&mut state, PlayerAction::DeclareBankruptcy)
+            .unwrap();
+        // the challenge starts over with its own funds
+        assert_eq!(state.daily, Some(challenge));
+        assert_eq!(state.funds, challenge.modifiers.starting_funds);
+        assert_eq!(state.company_name, "Nimbus Inc.");
+        assert_eq!(state.achievements.bankruptcies, 1);
+    }
+
+    /// Actions which cannot be applied say why
+    /// and leave the state untouched.
+    #[test]
+    fn test_action_errors() {
+        let mut engine = GameEngine::new();
+        let state = WorldStateBuilder::new()
+            .time(1_000)
+            .with_node(CloudNode {
+                restarting_until: 5_000,
+                ..CloudNode::new(1)
+            })
+            .with_node(CloudNode::new_fully_upgraded(2))
+            .build();
+        let b0 = card_by_id("b0").unwrap();
+
+        let cases = [
+            (
+                PlayerAction::UpgradeCpu { node: 0 },
+                ActionError::InsufficientFunds {
+                    needed: engine.config.cpu_levels[1].2,
+                    have: state.funds,
+                },
+            ),
+            (
+                PlayerAction::AddNode,
+                ActionError::InsufficientFunds {
+                    needed: engine.config.bare_node_cost,
+                    have: state.funds,
+                },
+            ),
+            (
+                PlayerAction::UpgradeRam { node: 2 },
+                ActionError::MaxLevelReached,
+            ),
+            (
+                PlayerAction::UpgradeCpu { node: 7 },
+                ActionError::NoSuchNode(7),
+            ),
+            (
+                PlayerAction::UseCard { id: "nope".into() },
+                ActionError::NoSuchCard("nope".into()),
+            ),
+            (
+                PlayerAction::UseCard { id: "b0".into() },
+                ActionError::CannotAfford(state.card_cost(b0)),
+            ),
+            (
+                PlayerAction::ChangePrice {
+                    kind: ServiceKind::Epic,
+                    new_price: Money::cents(1),
+                },
+                ActionError::NotUnlocked(ServiceKind::Epic),
+            ),
+            (PlayerAction::RepayLoan, ActionError::NotAvailable),
+            (
+                PlayerAction::RestartNode { node: 1 },
+                ActionError::NodeRestarting(1),
+            ),
+        ];
+
+        for (action, error) in cases {
+            let mut after = state.clone();
+            assert_eq!(
+                engine.apply_action(&mut after, action.clone()),
+                Err(error),
+                "{action:?}"
+            );
+            assert_eq!(after, state, "{action:?} changed the state");
+        }
+    }
+
+    /// A card is recorded as used exactly once,
+    /// and only when it was applied.
+    #[test]
+    fn test_card_used_once() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(40))
+            .build();
+        let use_b0 = PlayerAction::UseCard { id: "b0".into() };
+
+        assert!(engine.apply_action(&mut state, use_b0.clone()).is_err());
+        assert!(!state.is_card_used("b0"));
+
+        state.base_service.available = Ops(200);
+        engine.apply_action(&mut state, use_b0.clone()).unwrap();
+        assert_eq!(
+            engine.apply_action(&mut state, use_b0),
+            Err(ActionError::NotAvailable)
+        );
+        let uses = state.cards_used.iter().filter(|c| c.id == "b0").count();
+        assert_eq!(uses, 1);
+        assert_eq!(state.base_service.available, Ops(150));
+    }
+
+    #[test]
+    fn test_steep_price_raise_churns_customers() {
+        let mut engine = GameEngine::with_config_and_generator(
+            GameConfig::default(),
+            SampleGenerator::from_seed(882),
+        );
+        let mut builder = WorldStateBuilder::new()
+            .service_published(ServiceKind::Base, Money::cents(1))
+            .with_user(ServiceKind::Base, UserKind::Researcher, 0)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0);
+        for _ in 0..30 {
+            builder = builder.with_customer(ServiceKind::Base);
+        }
+        let mut state = builder.build();
+        let customers = |state: &WorldState| {
+            state
+                .user_specs
+                .iter()
+                .filter(|spec| spec.kind == UserKind::Customer)
+                .count()
+        };
+
+        // lowering the price is always safe
+        for cents in [1, 0, 0] {
+            engine
+                .apply_action(
+                    &mut state,
+                    PlayerAction::ChangePrice {
+                        kind: ServiceKind::Base,
+                        new_price: Money::cents(cents),
+                    },
+                )
+                .unwrap();
+        }
+        assert_eq!(customers(&state), 30);
+        assert!(engine.notifications().is_empty());
+
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::ChangePrice {
+                    kind: ServiceKind::Base,
+                    new_price: Money::cents(5),
+                },
+            )
+            .unwrap();
+        let left = 30 - customers(&state);
+        assert!((10..30).contains(&left), "{left} customers left");
+        // only customers care about the price
+        assert_eq!(state.user_specs.len(), customers(&state) + 2);
+        assert_eq!(state.base_service.churned_at, Some(state.time));
+        assert_eq!(engine.notifications().len(), 1);
+    }
+
+    #[test]
+    fn test_churned_service_gets_customer_back() {
+        let mut engine = GameEngine::with_config_and_generator(
+            GameConfig::default(),
+            SampleGenerator::from_seed(882),
+        );
+        let mut state = WorldStateBuilder::new()
+            .service_published(ServiceKind::Base, Money::cents(1))
+            .with_customer(ServiceKind::Base)
+            .build();
+        engine.bootstrap_events(&state);
+
+        // yo-yo pricing until the only customer leaves
+        for _ in 0..50 {
+            if state.user_specs.is_empty() {
+                break;
+            }
+            for cents in [1, 10] {
+                engine
+                    .apply_action(
+                        &mut state,
+                        PlayerAction::ChangePrice {
+                            kind: ServiceKind::Base,
+                            new_price: Money::cents(cents),
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+        assert!(state.user_specs.is_empty());
+        let churned_at = state.base_service.churned_at.unwrap();
+
+        let mut time = state.time;
+        while time < churned_at + CHURN_COOLDOWN - TIME_UNITS_PER_MINUTE {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert!(state.user_specs.is_empty());
+
+        while time < churned_at + CHURN_COOLDOWN + 5_000 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.user_specs.len(), 1);
+        assert_eq!(state.user_specs[0].kind, UserKind::Customer);
+        assert_eq!(state.user_specs[0].trial_time, 0);
+        assert_eq!(state.base_service.churned_at, None);
+    }
+
+    /// The upgrade descriptions follow the constants tables.
+    #[test]
+    fn test_describe_upgrade() {
+        let before = WorldStateBuilder::new()
+            .service_unlocked(ServiceKind::Super)
+            .build();
+
+        let after = WorldState {
+            software_level: 1,
+            ..before.clone()
+        };
+        // op times are 2500 * time factor / CPU speed + 4500 / (level² + 1),
+        // reserves 256MB times the software factor
+        assert_eq!(CPU_LEVELS[0].1, 2);
+        assert_eq!(SOFTWARE_LEVELS[1].1, 0.96875);
+        assert_eq!(
+            describe_upgrade(&CardEffect::UpgradeServices, &before, &after),
+            [
+                "Base op time 575.0ms \u{2192} 350.0ms",
+                "Super op time 950.0ms \u{2192} 725.0ms",
+                "memory reserve 256MB \u{2192} 248MB",
+            ]
+        );
+
+        let after = WorldState {
+            cache_level: 2,
+            ..before.clone()
+        };
+        assert_eq!(CACHE_LEVELS[2], (16., 0.5));
+        assert_eq!(
+            describe_upgrade(&CardEffect::MoreCaching, &before, &after),
+            [
+                "Cache hit rate 0% \u{2192} 50%",
+                "Super op time 950.0ms \u{2192} 498.8ms",
+                "Super op memory 256MB \u{2192} 4\u{2006}096MB",
+            ]
+        );
+
+        assert!(describe_upgrade(&CardEffect::UnlockMultiNodes, &before, &after).is_empty());
+    }
+
+    /// Using an upgrade card tells the player what it changed.
+    #[test]
+    fn test_upgrade_card_logs_changes() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(100_000))
+            .funds(Money::dollars(1_000))
+            .build();
+        let card = ALL_CARDS
+            .iter()
+            .find(|card| card.effect == CardEffect::MoreCaching)
+            .unwrap();
+        engine.apply_card(&mut state, card);
+        let entry = engine.notifications().recent(1).next().unwrap();
+        assert_eq!(
+            entry.message,
+            "Cache hit rate 0% \u{2192} 25%; Base op time 575.0ms \u{2192} 438.4ms; \
+             Base op memory 32MB \u{2192} 128MB"
+        );
+    }
+
+    #[test]
+    fn test_compact_node_round_trip() {
+        let mut upgraded = CloudNode::new(2);
+        upgraded.cpu_level = 3;
+        upgraded.ram_level = 5;
+        upgraded.num_cores = CPU_LEVELS[3].0;
+        upgraded.cpu_speed = CPU_LEVELS[3].1;
+        upgraded.ram_capacity = RAM_LEVELS[5].0;
+        let mut overclocked = CloudNode::new(4);
+        overclocked.overclock(1, 0.2);
+
+        for node in [
+            CloudNode::new(0),
+            CloudNode::new_fully_upgraded(1),
+            upgraded,
+            CloudNode::new_fully_upgraded_rack(3),
+            overclocked,
+            CloudNode::new_at_scale(5, NodeScale::Datacenter),
+        ] {
+            let json = serde_json::to_string(&node).unwrap();
+            assert!(!json.contains("num_cores"), "{json}");
+            let loaded: CloudNode = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded, node);
+        }
+    }
+
+    #[test]
+    fn test_old_node_form_loads() {
+        let json = r#"{"id":4,"cpu_level":2,"ram_level":1,"num_cores":3,"ram_capacity":1000,"cpu_speed":7}"#;
+        let node: CloudNode = serde_json::from_str(json).unwrap();
+        assert_eq!(node.id, 4);
+        assert_eq!((node.cpu_level, node.ram_level), (2, 1));
+        // the written down capacity is kept as is
+        assert_eq!(node.num_cores, 3);
+        assert_eq!(node.ram_capacity, Memory::bytes(1000));
+        assert_eq!(node.cpu_speed, 7);
+    }
+
+    #[test]
+    fn test_node_levels_out_of_range() {
+        for json in [
+            r#"{"id":1,"cpu_level":200,"ram_level":0}"#,
+            r#"{"id":1,"cpu_level":0,"ram_level":200}"#,
+        ] {
+            let error = serde_json::from_str::<CloudNode>(json).unwrap_err();
+            assert!(error.to_string().contains("level 200"), "{error}");
+        }
+        // a whole save with such a node fails to load instead of panicking
+        let mut state = serde_json::to_value(WorldState::default()).unwrap();
+        state["nodes"][0]["cpu_level"] = 99.into();
+        assert!(serde_json::from_value::<WorldState>(state).is_err());
+    }
+
+    #[test]
+    fn test_node_scale_saved() {
+        let rack = CloudNode::new_fully_upgraded_rack(3);
+        assert_eq!(rack.scale, NodeScale::Rack);
+        let json = serde_json::to_string(&rack).unwrap();
+        assert!(json.contains(r#""scale":"Rack""#), "{json}");
+        let json = serde_json::to_string(&CloudNode::new(0)).unwrap();
+        assert!(!json.contains("scale"), "{json}");
+
+        // older saves flag racks
+        let json = r#"{"id":1,"cpu_level":6,"ram_level":7,"rack":true}"#;
+        let node: CloudNode = serde_json::from_str(json).unwrap();
+        assert_eq!(node, CloudNode::new_fully_upgraded_rack(1));
+        // or only write down their capacity
+        let json = format!(
+            r#"{{"id":2,"cpu_level":6,"ram_level":7,"num_cores":{},"ram_capacity":1000,"cpu_speed":7}}"#,
+            CPU_LEVELS[CPU_LEVELS.len() - 1].0 * RACK_CAPACITY
+        );
+        let node: CloudNode = serde_json::from_str(&json).unwrap();
+        assert_eq!(node.scale, NodeScale::Rack);
+        assert!(node.is_rack());
+    }
+
+    #[test]
+    fn test_rack_nodes_not_upgradable() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000_000))
+            .with_node(CloudNode {
+                cpu_level: 0,
+                ram_level: 0,
+                ..CloudNode::new_fully_upgraded_rack(0)
+            })
+            .build();
+        let config = GameConfig::default();
+        assert_eq!(state.nodes[0].next_cpu_upgrade_cost(&config), None);
+        assert_eq!(state.nodes[0].next_ram_upgrade_cost(&config), None);
+        for action in [
+            PlayerAction::UpgradeCpu { node: 0 },
+            PlayerAction::UpgradeRam { node: 0 },
+        ] {
+            assert_eq!(
+                engine.apply_action(&mut state, action),
+                Err(ActionError::NotUpgradable(0))
+            );
+        }
+        assert_eq!(state.funds, Money::dollars(1_000_000));
+    }
+
+    #[test]
+    fn test_fully_upgraded_conditions_across_datacenter_conversion() {
+        let conditions = [
+            CardCondition::FullyUpgradedNode,
+            CardCondition::FullyUpgradedRack,
+            CardCondition::FullyUpgradedDatacenter,
+        ];
+        let satisfied = |state: &WorldState| {
+            conditions
+                .iter()
+                .map(|c| c.should_appear(state))
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = WorldState::default();
+        assert_eq!(satisfied(&state), [false, false, false]);
+        state.nodes[0] = CloudNode::new_fully_upgraded(0);
+        state.nodes.push(CloudNode::new(1));
+        assert_eq!(satisfied(&state), [true, false, false]);
+        // only fully upgraded nodes fill a rack
+        state.nodes.push(CloudNode::new_fully_upgraded(2));
+        state.nodes.push(CloudNode::new_fully_upgraded(3));
+        assert_eq!(satisfied(&state), [true, false, false]);
+        state.nodes[1] = CloudNode::new_fully_upgraded(1);
+        assert_eq!(satisfied(&state), [true, true, false]);
+
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(RACK_CAPACITY * 10)
+            .build();
+        assert_eq!(satisfied(&state), [true, true, true]);
+
+        // the conversion leaves no single nodes behind
+        let mut engine = GameEngine::new();
+        let card = ALL_CARDS
+            .iter()
+            .find(|card| card.effect == CardEffect::UnlockMultiDatacenters)
+            .unwrap();
+        engine.apply_card(&mut state, card);
+        assert_eq!(state.nodes.len(), 10);
+        assert!(state.nodes.iter().all(CloudNode::is_rack));
+        assert_eq!(satisfied(&state), [true, true, true]);
+
+        // even a lone rack no longer relies on the first nodes
+        state.nodes.truncate(1);
+        assert_eq!(satisfied(&state), [true, true, false]);
+    }
+
+    #[test]
+    fn test_rack_conversion_keeps_value() {
+        let nodes = |count: u32| {
+            (0..count)
+                .map(CloudNode::new_fully_upgraded)
+                .collect::<Vec<_>>()
+        };
+        let config = GameConfig::default();
+        let node_value = CloudNode::new_fully_upgraded(0).hardware_value(&config);
+
+        // whole racks
+        assert_eq!(
+            RackConversion::of(&nodes(8), &config),
+            RackConversion {
+                nodes: 8,
+                racks: 2,
+                credit: Money::zero(),
+            }
+        );
+        // less than half a rack left over is refunded
+        assert_eq!(
+            RackConversion::of(&nodes(9), &config),
+            RackConversion {
+                nodes: 9,
+                racks: 2,
+                credit: node_value,
+            }
+        );
+        let mut mixed = nodes(9);
+        mixed[8] = CloudNode::new(8);
+        assert_eq!(RackConversion::of(&mixed, &config).credit, BARE_NODE_COST);
+        // half a rack or more makes up one more rack
+        for count in [10, 11] {
+            assert_eq!(
+                RackConversion::of(&nodes(count), &config),
+                RackConversion {
+                    nodes: count,
+                    racks: 3,
+                    credit: Money::zero(),
+                }
+            );
+        }
+
+        // the refund follows the prices of the difficulty
+        let hard = GameConfig::new(Difficulty::Hard);
+        assert_eq!(
+            RackConversion::of(&mixed, &hard).credit,
+            hard.bare_node_cost
+        );
+        assert_eq!(
+            RackConversion::of(&nodes(9), &hard).credit,
+            CloudNode::new_fully_upgraded(0).hardware_value(&hard)
+        );
+    }
+
+    #[test]
+    fn test_datacenter_conversion_reroutes_requests() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(RACK_CAPACITY * 2 + 1)
+            .build();
+        for _ in 0..20 {
+            engine.queue.push(RequestEvent::new_arrived(
+                1,
+                None,
+                5,
+                ServiceKind::Base,
+                false,
+            ));
+        }
+        let mut time = state.time;
+        time += TIME_UNITS_PER_CYCLE as u64;
+        engine.update(&mut state, time);
+        assert!(engine.requests_in_flight(&state) > 0);
+
+        let funds = state.funds;
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockMultiDatacenters);
+        assert_eq!(state.nodes.len(), 2);
+        assert_eq!(
+            state.funds,
+            funds + CloudNode::new_fully_upgraded(0).hardware_value(&GameConfig::default())
+        );
+        assert_eq!(engine.requests_in_flight(&state), 0);
+
+        // no request is lost in the move
+        for _ in 0..1_000 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.traffic[TrafficClass::Player as usize].fulfilled, 100);
+        assert_eq!(state.requests_dropped, 0);
+    }
+
+    #[test]
+    fn test_node_lifetime_attribution() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 5);
+        let mut state = WorldStateBuilder::new()
+            .time(1_000)
+            .with_nodes_fully_upgraded(3)
+            .build();
+        for i in 0..60 {
+            engine.queue.push(RequestEvent::new_arrived(
+                1_000 + i,
+                None,
+                2,
+                ServiceKind::Base,
+                // bad requests are processed, but serve no ops
+                i % 3 == 0,
+            ));
+        }
+        let mut time = state.time;
+        for _ in 0..100 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(engine.requests_in_flight(&state), 0);
+
+        let lifetimes: Vec<_> = state.nodes.iter().map(|node| node.lifetime).collect();
+        // the work was shared among the nodes
+        assert!(
+            lifetimes
+                .iter()
+                .all(|lifetime| lifetime.requests_processed > 0),
+            "{lifetimes:?}"
+        );
+        let total = |f: fn(&NodeLifetime) -> u64| lifetimes.iter().map(f).sum::<u64>();
+        assert_eq!(total(|lifetime| lifetime.requests_processed), 120);
+        assert_eq!(total(|lifetime| lifetime.ops_served), 80);
+        assert_eq!(state.base_service.total, Ops(80));
+        // and so was the energy, routing included
+        let energy: i64 = lifetimes.iter().map(|lifetime| lifetime.energy).sum();
+        assert_eq!(energy, state.electricity.total_consumed);
+        assert!(energy > 60 * 1_000);
+
+        // nodes bought later have been serving since then
+        state.funds = Money::dollars(10_000);
+        engine
+            .apply_action(&mut state, PlayerAction::AddNode)
+            .unwrap();
+        assert_eq!(state.nodes[3].lifetime.created_at, state.time);
+        assert_eq!(state.nodes[3].lifetime.requests_processed, 0);
+    }
+
+    #[test]
+    fn test_rack_conversion_keeps_lifetimes() {
+        let nodes: Vec<_> = (0..RACK_CAPACITY * 2 + 1)
+            .map(|id| {
+                let mut node = CloudNode::new_fully_upgraded(id).set_up_at(100 + id as u64);
+                node.lifetime.requests_processed = 10 * (id as u64 + 1);
+                node.lifetime.ops_served = id as u64 + 1;
+                node.lifetime.energy = 1_000;
+                node
+            })
+            .collect();
+        let conversion = RackConversion::of(&nodes, &GameConfig::default());
+        let racks = conversion.convert(&nodes, 5_000);
+        assert_eq!(racks.len(), 2);
+        assert!(racks.iter().all(CloudNode::is_rack));
+        assert_eq!(
+            racks[0].lifetime,
+            NodeLifetime {
+                created_at: 100,
+                requests_processed: 10 + 20 + 30 + 40,
+                ops_served: 1 + 2 + 3 + 4,
+                energy: 4_000,
+            }
+        );
+        // the node left over is refunded, but its work is not forgotten
+        assert_eq!(
+            racks[1].lifetime,
+            NodeLifetime {
+                created_at: 104,
+                requests_processed: 50 + 60 + 70 + 80 + 90,
+                ops_served: 5 + 6 + 7 + 8 + 9,
+                energy: 5_000,
+            }
+        );
+
+        // and so does the card unlocking data centers
+        let mut engine = GameEngine::new();
+        let mut state = WorldState {
+            nodes: nodes.clone(),
+            ..WorldState::default()
+        };
+        engine.apply_card_effect(&mut state, &CardEffect::UnlockMultiDatacenters);
+        assert_eq!(state.nodes, conversion.convert(&nodes, state.time));
+    }
+
+    /// world state with one user spec for each kind of customer
+    fn state_with_customers() -> WorldState {
+        // user specs 1 to 3
+        WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .with_customer(ServiceKind::Base)
+            .with_user(ServiceKind::Base, UserKind::Customer, u64::MAX)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+            .build()
+    }
+
+    /// push one request from the player, each customer,
+    /// and a user spec which no longer exists (ID 9)
+    fn push_requests_from_all(engine: &mut GameEngine) {
+        for (id, bad) in [
+            (None, false),
+            (Some(1), false),
+            (Some(2), false),
+            (Some(3), true),
+            (Some(9), false),
+        ] {
+            engine
+                .queue
+                .push(RequestEvent::new_arrived(1, id, 1, ServiceKind::Base, bad));
+        }
+    }
+
+    #[test]
+    fn test_traffic_fulfilled_attribution() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        push_requests_from_all(&mut engine);
+
+        let mut time = 0;
+        for _ in 0..50 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+
+        let fulfilled = state.traffic.map(|counters| counters.fulfilled);
+        // the deleted spec falls back to the player
+        assert_eq!(fulfilled[TrafficClass::Player as usize], 2);
+        assert_eq!(fulfilled[TrafficClass::Paying as usize], 1);
+        assert_eq!(fulfilled[TrafficClass::Trial as usize], 1);
+        assert_eq!(fulfilled[TrafficClass::Bad as usize], 1);
+        assert_eq!(state.requests_dropped, 0);
+    }
+
+    #[test]
+    fn test_bootstrap_staggered() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+        let mut builder = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .service_published(ServiceKind::Base, Money::millicents(50));
+        for _ in 0..500 {
+            builder = builder.with_customer(ServiceKind::Base);
+        }
+        let mut state = builder.build();
+        // about one request per second from each customer
+        state.demand = 1.
+            / state
+                .base_service
+                .calculate_demand(engine.daily_cycle(0) as f64);
+        engine.bootstrap_events(&state);
+
+        // first arrivals spread evenly over the first second,
+        // 50 per tenth of a second on average
+        assert_eq!(engine.queue.len(), 500);
+        let mut buckets = [0; 10];
+        for event in engine.queue.iter() {
+            assert!(event.timestamp < TIME_UNITS_PER_SECOND);
+            buckets[(event.timestamp * 10 / TIME_UNITS_PER_SECOND) as usize] += 1;
+        }
+        assert!(
+            buckets.iter().all(|&count| (30..=70).contains(&count)),
+            "{buckets:?}"
+        );
+
+        // no arrivals for services in maintenance
+        state.base_service.maintenance_since = Some(0);
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+        engine.bootstrap_events(&state);
+        assert_eq!(engine.queue.len(), 0);
+    }
+
+    /// The projected requests per second of a service
+    /// match the requests which its customers actually make.
+    #[test]
+    fn test_projected_requests_per_customer() {
+        let config = GameConfig {
+            demand_growth: 0.,
+            daily_amplitude: 0.,
+            ..GameConfig::default()
+        };
+        let mut engine = GameEngine::with_config_seeded(config, 11);
+        let mut builder = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(4)
+            .service_published(ServiceKind::Base, Money::millicents(50));
+        for _ in 0..100 {
+            builder = builder.with_customer(ServiceKind::Base);
+        }
+        let mut state = builder.build();
+        // about half a request per second from each customer
+        state.demand = 0.5 / state.base_service.calculate_demand(1.);
+
+        let customers = state.customers_per_service()[ServiceKind::Base as usize];
+        assert_eq!(customers, 100);
+        assert_eq!(
+            state.customers_per_service()[ServiceKind::Super as usize],
+            0
+        );
+        let projected = |price| {
+            engine.projected_requests_per_customer(&state, ServiceKind::Base, price)
+                * customers as f64
+        };
+        let current = projected(state.base_service.price);
+        assert!((current / 50. - 1.).abs() < 0.01, "{current}");
+        // a lower price brings more requests, a higher one fewer
+        assert!(projected(Money::millicents(45)) > current);
+        assert!(projected(Money::millicents(55)) < current);
+
+        engine.bootstrap_events(&state);
+        let seconds = 120;
+        let mut time = 0;
+        while time < seconds * TIME_UNITS_PER_SECOND {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        let counters = state.traffic[TrafficClass::Paying as usize];
+        let made = (counters.fulfilled + counters.dropped) as f64 / seconds as f64;
+        assert!((made / current - 1.).abs() < 0.05, "{made} vs {current}");
+    }
+
+    #[test]
+    fn test_bootstrap_prewarmed() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(1)
+            .with_customer(ServiceKind::Base)
+            .build();
+        // a single customer with 200 requests per second
+        state.demand = 200.
+            / state
+                .base_service
+                .calculate_demand(engine.daily_cycle(0) as f64);
+        engine.bootstrap_events(&state);
+
+        let arrivals = |engine: &GameEngine| -> Vec<_> {
+            engine
+                .queue
+                .iter()
+                .filter_map(|event| match event.kind {
+                    RequestEventStage::RequestArrived { chained } => Some(chained),
+                    _ => None,
+                })
+                .collect()
+        };
+        // spaced 5ms apart, and only the last one schedules more
+        assert_eq!(arrivals(&engine), [false, false, false, false, true]);
+        let times: Vec<_> = engine.queue.iter().map(|event| event.timestamp).collect();
+        assert!(times.windows(2).all(|pair| pair[1] - pair[0] == 50));
+
+        // which does not multiply the customer's requests
+        let mut time = 0;
+        while time < TIME_UNITS_PER_SECOND {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(arrivals(&engine), [true]);
+
+        // customers joining later are prewarmed too,
+        // with request sets spaced by their own interval whatever their size
+        let rate = 60_000.;
+        state.demand *= rate / 200.;
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+        engine.update_bundle_caps(&state);
+        engine.bootstrap_events_for(&state, &state.user_specs[0]);
+        assert_eq!(arrivals(&engine), [false, false, false, false, true]);
+        let amount = engine.queue.iter().next().unwrap().amount;
+        assert!(amount > 1);
+        let interval = (TIME_UNITS_PER_SECOND as f64 * amount as f64 / rate).max(1.) as u64;
+        let times: Vec<_> = engine.queue.iter().map(|event| event.timestamp).collect();
+        assert!(
+            times.windows(2).all(|pair| pair[1] - pair[0] == interval),
+            "{times:?}"
+        );
+    }
+
+    #[test]
+    fn test_convert_ops() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(2_500))
+            .total_ops(ServiceKind::Base, Ops(2_500))
+            .service_unlocked(ServiceKind::Super)
+            .build();
+        let convert = |amount| PlayerAction::ConvertOps {
+            from: ServiceKind::Base,
+            to: ServiceKind::Super,
+            amount: Ops(amount),
+        };
+
+        // not before the exchange is unlocked
+        assert_eq!(
+            engine.apply_action(&mut state, convert(2_500)),
+            Err(ActionError::NotAvailable)
+        );
+
+        state.can_exchange_ops = true;
+        engine.apply_action(&mut state, convert(2_500)).unwrap();
+        assert_eq!(state.base_service.available, Ops(500));
+        assert_eq!(state.super_service.available, Ops(2));
+        assert_eq!(state.super_service.exchanged, Ops(2));
+        // ops obtained were never processed
+        assert_eq!(state.base_service.total, Ops(2_500));
+        assert_eq!(state.super_service.total, Ops(0));
+
+        assert_eq!(
+            engine.apply_action(&mut state, convert(2_000)),
+            Err(ActionError::InsufficientOps {
+                kind: ServiceKind::Base,
+                needed: Ops(2_000),
+                have: Ops(500),
+            })
+        );
+        assert_eq!(
+            engine.apply_action(&mut state, convert(500)),
+            Err(ActionError::InsufficientOps {
+                kind: ServiceKind::Base,
+                needed: Ops(1_000),
+                have: Ops(500),
+            })
+        );
+        assert_eq!(state.base_service.available, Ops(500));
+    }
+
+    #[test]
+    fn test_revenue_per_customer() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 7);
+        // user specs 1 and 2 pay, 3 is on trial for a minute
+        let mut state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(2)
+            .demand(50.)
+            .service_published(ServiceKind::Base, Money::millicents(50))
+            .with_customer(ServiceKind::Base)
+            .with_customer(ServiceKind::Base)
+            .with_user(ServiceKind::Base, UserKind::Customer, TIME_UNITS_PER_MINUTE)
+            .build();
+        state.base_service.entitlement = Money::millicents(1);
+        engine.bootstrap_events(&state);
+
+        let mut time = 0;
+        let mut run_until = |state: &mut WorldState, end: u64| {
+            while time < end {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(state, time);
+            }
+        };
+
+        // customers of the same service each get their own share
+        run_until(&mut state, TIME_UNITS_PER_MINUTE / 2);
+        let customers = state.top_customers();
+        assert_eq!(customers.len(), 3);
+        let total: Money = customers.iter().map(|customer| customer.revenue).sum();
+        assert_eq!(total, state.earned);
+        let ops: u64 = customers.iter().map(|customer| customer.ops).sum();
+        assert_eq!(ops, state.base_service.total.0 as u64);
+        assert!(customers[..2].iter().all(|customer| !customer.in_trial));
+        let trial = &customers[2];
+        assert!(trial.in_trial);
+        assert!(trial.ops > 0);
+        // a customer on trial only brings in the entitlement
+        assert!(trial.revenue < customers[1].revenue);
+
+        // once the trial is over, the customer goes to the archive
+        run_until(&mut state, TIME_UNITS_PER_MINUTE * 2);
+        let customers = state.top_customers();
+        assert_eq!(customers.len(), 2);
+        assert!(customers.iter().all(|customer| !customer.in_trial));
+        let archive = state.customer_revenue.archive();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive[0].spec_id, 3);
+        assert!(archive[0].revenue >= trial.revenue);
+    }
+
+    #[test]
+    fn test_processing_times_recorded() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState {
+            cache_level: 4,
+            ..state_with_customers()
+        };
+        push_requests_from_all(&mut engine);
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+
+        let histogram = engine.metrics(&state).processing_times.current_overall();
+        assert_eq!(histogram.total(), 5);
+        // cache hits are much faster than the rest
+        let hits: u64 = histogram.cache_hits.iter().sum();
+        assert!(hits > 0);
+        let slowest_hit = histogram.cache_hits.iter().rposition(|&n| n > 0).unwrap();
+        let fastest_miss = (0..HISTOGRAM_BUCKETS)
+            .find(|&bucket| histogram.counts[bucket] > histogram.cache_hits[bucket]);
+        if let Some(fastest_miss) = fastest_miss {
+            assert!(slowest_hit < fastest_miss);
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_rate_measured() {
+        for cache_level in 1..CACHE_LEVELS.len() as u8 {
+            let mut engine = GameEngine::with_config_and_generator(
+                GameConfig::default(),
+                SampleGenerator::from_seed(880),
+            );
+            let mut state = WorldState {
+                cache_level,
+                ..WorldStateBuilder::new()
+                    .with_nodes_fully_upgraded(1)
+                    .build()
+            };
+            // a steady stream of player requests until the first major update
+            for i in 0..2_000 {
+                engine.queue.push(RequestEvent::new_arrived(
+                    1 + i,
+                    None,
+                    1,
+                    ServiceKind::Base,
+                    false,
+                ));
+            }
+            let mut time = 0;
+            while time < 2_500 {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+
+            let metrics = engine.metrics(&state);
+            let expected = CACHE_LEVELS[cache_level as usize].1;
+            assert!(
+                (metrics.cache_hit_rate - expected).abs() < 0.05,
+                "cache level {cache_level}: {} != {expected}",
+                metrics.cache_hit_rate
+            );
+            // the measured speed-up is the one expected in upgrade descriptions
+            let expected_speedup = expected_cache_speedup(cache_level, 1.) as f32;
+            assert!(
+                (metrics.cache_speedup - expected_speedup).abs() < expected_speedup * 0.2,
+                "cache level {cache_level}: speedup {} != {expected_speedup}",
+                metrics.cache_speedup
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_fraction_scales_reserve_and_hit_rate() {
+        let reserve = |cache_level, cache_fraction| {
+            GameEngine::calculate_memory_reserve_required(
+                ServiceKind::Super,
+                cache_level,
+                cache_fraction,
+                0,
+            )
+        };
+        for cache_level in 1..CACHE_LEVELS.len() as u8 {
+            let (multiplier, hit_rate) = CACHE_LEVELS[cache_level as usize];
+            // the full cache is as before
+            assert_eq!(cache_memory_factor(cache_level, 1.), multiplier);
+            assert_eq!(cache_hit_rate(cache_level, 1.), hit_rate);
+            // no cache at all reserves as much as without caching
+            assert_eq!(reserve(cache_level, 0.), reserve(0, 1.));
+            assert_eq!(cache_hit_rate(cache_level, 0.), 0.);
+            // in between, the extra memory and the hit rate scale together
+            assert_eq!(
+                cache_memory_factor(cache_level, 0.5),
+                1. + (multiplier - 1.) / 2.
+            );
+            assert_eq!(cache_hit_rate(cache_level, 0.5), hit_rate / 2.);
+        }
+    }
+
+    #[test]
+    fn test_cache_fraction_releases_reserve() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().build();
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::SetCacheFraction { fraction: 0.5 }),
+            Err(ActionError::NotAvailable)
+        );
+
+        state.cache_level = 1;
+        let full = GameEngine::calculate_memory_reserve_required(ServiceKind::Base, 1, 1., 0);
+        assert_eq!(state.nodes[0].reserve_for(full), Ok(true));
+        let (reserved, used) = state.nodes[0].memory_breakdown();
+        assert_eq!(reserved, full.ratio(state.nodes[0].ram_capacity));
+        assert_eq!(used, 0.);
+
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::SetCacheFraction { fraction: 0.25 },
+            )
+            .unwrap();
+        assert_eq!(state.cache_fraction, 0.25);
+        let smaller = GameEngine::calculate_memory_reserve_required(ServiceKind::Base, 1, 0.25, 0);
+        assert!(smaller < full);
+        assert_eq!(state.nodes[0].ram_reserved, smaller);
+        assert_eq!(state.nodes[0].ram_usage, smaller);
+
+        // growing the cache again reserves more on the next request
+        engine
+            .apply_action(&mut state, PlayerAction::SetCacheFraction { fraction: 1. })
+            .unwrap();
+        assert_eq!(state.nodes[0].ram_reserved, smaller);
+        assert_eq!(
+            engine.apply_action(&mut state, PlayerAction::SetCacheFraction { fraction: 1.5 }),
+            Err(ActionError::NotAvailable)
+        );
+        assert_eq!(state.cache_fraction, 1.);
+    }
+    #[test]
+    fn test_penalty_added_to_bill() {
+        let mut engine = GameEngine::new();
+        let mut state = (1..RACK_CAPACITY)
+            .fold(
+                WorldStateBuilder::new().funds(Money::dollars(100)),
+                |builder, id| builder.with_node(CloudNode::new(id)),
+            )
+            .build();
+        let due = state.electricity.total_due;
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64 * 5);
+
+        // the noise complaint fine was charged right away,
+        // onto the bill rather than the funds
+        assert!(state.is_card_used("p0"));
+        assert_eq!(state.funds, Money::dollars(100));
+        assert!(state.electricity.total_due >= due + Money::dollars(500));
+        assert!(engine.visible_cards().all(|card| !card.is_penalty()));
+        assert!(engine
+            .notifications()
+            .recent(1)
+            .any(|notification| notification.message.starts_with("Noise complaint fine")));
+
+        // and only once
+        let due = state.electricity.total_due;
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64 * 10);
+        assert!(state.electricity.total_due < due + Money::dollars(500));
+
+        // paying it in full takes the funds negative, which blocks purchases
+        state.funds -= state.electricity.total_due;
+        assert!(state.is_in_debt());
+        assert!(!state.can_afford(&Cost::dollars(1)));
+    }
+
+    #[test]
+    fn test_purchases_blocked_in_debt() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new().funds(Money::dollars(-10)).build();
+        state.can_buy_nodes = true;
+        assert!(state.is_in_debt());
+        assert!(!state.can_afford(&Cost::dollars(1)));
+        assert!(state.can_afford(&Cost::nothing()));
+
+        for action in [
+            PlayerAction::AddNode,
+            PlayerAction::UpgradeRam { node: 0 },
+            PlayerAction::UseCard { id: "d1".into() },
+        ] {
+            assert_eq!(
+                engine.apply_action(&mut state, action.clone()),
+                Err(ActionError::InDebt),
+                "{action:?}"
+            );
+        }
+        assert_eq!(state.nodes.len(), 1);
+        assert_eq!(state.funds, Money::dollars(-10));
+
+        // cards paid in ops alone can still be used,
+        // such as selling ops for money
+        state.base_service.available = Ops(100);
+        engine
+            .apply_action(&mut state, PlayerAction::UseCard { id: "b0".into() })
+            .unwrap();
+        assert!(state.is_card_used("b0"));
+        assert_eq!(state.base_service.available, Ops(50));
+        assert_eq!(state.funds, Money::dollars(50));
+        state.funds = Money::dollars(-10);
+
+        // the company can still work its way out of debt
+        assert!(!PlayerAction::PayElectricityBill.is_purchase());
+        assert!(!PlayerAction::RepayLoan.is_purchase());
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::OpClick {
+                    kind: ServiceKind::Base,
+                    amount: 1,
+                },
+            )
+            .unwrap();
+
+        state.funds += Money::dollars(10) + engine.config().bare_node_cost;
+        assert!(!state.is_in_debt());
+        engine
+            .apply_action(&mut state, PlayerAction::AddNode)
+            .unwrap();
+        assert_eq!(state.nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_researchers_pay_nothing() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        state.base_service.entitlement = Money::cents(1);
+        state.user_specs.insert(CloudUserSpec {
+            id: 4,
+            service: ServiceKind::Base,
+            trial_time: 0,
+            kind: UserKind::Researcher,
+        });
+        engine.queue.push(RequestEvent::new_arrived(
+            1,
+            Some(4),
+            3,
+            ServiceKind::Base,
+            false,
+        ));
+        let funds = state.funds;
+
+        let mut time = 0;
+        for _ in 0..50 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+
+        assert_eq!(state.funds, funds);
+        assert_eq!(state.earned, Money::zero());
+        assert_eq!(state.research_progress, 3);
+        assert_eq!(state.traffic[TrafficClass::Research as usize].fulfilled, 3);
+    }
+
+    #[test]
+    fn test_research_discounts_software_cards() {
+        let mut engine = GameEngine::new();
+        // "Profile-guided optimization" costs 750 base ops
+        let mut state = WorldState {
+            research_progress: RESEARCH_FOR_MAX_DISCOUNT * 2,
+            ..WorldStateBuilder::new()
+                .funds(Money::dollars(100))
+                .available_ops(ServiceKind::Base, Ops(375))
+                .build()
+        };
+
+        engine
+            .apply_action(&mut state, PlayerAction::UseCard { id: "s2".into() })
+            .unwrap();
+
+        assert!(state.is_card_used("s2"));
+        assert_eq!(state.software_level, 1);
+        assert_eq!(state.base_service.available, Ops(0));
+        // the research was spent on the discount
+        assert_eq!(state.research_progress, 0);
+    }
+
+    #[test]
+    fn test_traffic_dropped_attribution() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        // no memory left to process anything
+        state.nodes[0].ram_usage = state.nodes[0].ram_capacity;
+        push_requests_from_all(&mut engine);
+
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+
+        let dropped = state.traffic.map(|counters| counters.dropped);
+        assert_eq!(dropped[TrafficClass::Player as usize], 2);
+        assert_eq!(dropped[TrafficClass::Paying as usize], 1);
+        assert_eq!(dropped[TrafficClass::Trial as usize], 1);
+        assert_eq!(dropped[TrafficClass::Bad as usize], 1);
+        assert_eq!(state.requests_dropped, 5);
+        // bad traffic does not count towards the drop rate
+        assert_eq!(engine.recent_requests_dropped, 4);
+    }
+
+    #[test]
+    fn test_bandwidth_drops() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        let capacity = bandwidth_capacity(0);
+        // two requests fill up the window, the third one is dropped
+        for _ in 0..3 {
+            engine.queue.push(RequestEvent::new_arrived(
+                1,
+                Some(1),
+                (capacity / 2) as u32,
+                ServiceKind::Base,
+                false,
+            ));
+        }
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+        assert_eq!(
+            engine.recent_drop_causes[DropCause::Bandwidth as usize],
+            capacity / 2
+        );
+        assert_eq!(engine.bandwidth_load(&state), 0.);
+
+        // the load is measured on the window just past
+        state.time = BANDWIDTH_WINDOW;
+        assert_eq!(engine.bandwidth_load(&state), 1.);
+
+        // a network card raises the capacity for the next window
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeBandwidth(1));
+        assert_eq!(state.bandwidth_level, 1);
+        assert_eq!(engine.bandwidth_load(&state), 0.1);
+        // and never lowers it back
+        engine.apply_card_effect(&mut state, &CardEffect::UpgradeBandwidth(0));
+        assert_eq!(state.bandwidth_level, 1);
+    }
+
+    #[test]
+    fn test_ram_drops_take_no_bandwidth() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        // no memory left for the request
+        state.nodes[0].ram_usage = state.nodes[0].ram_capacity;
+        engine.queue.push(RequestEvent::new_arrived(
+            1,
+            Some(1),
+            100,
+            ServiceKind::Base,
+            false,
+        ));
+        engine.update(&mut state, TIME_UNITS_PER_CYCLE as u64);
+        assert_eq!(engine.recent_drop_causes[DropCause::Ram as usize], 100);
+        assert_eq!(engine.recent_drop_causes[DropCause::Bandwidth as usize], 0);
+
+        // the request never used the network
+        state.time = BANDWIDTH_WINDOW;
+        assert_eq!(engine.bandwidth_load(&state), 0.);
+    }
+
+    #[test]
+    fn test_traffic_timedout_attribution() {
+        let mut engine = GameEngine::new();
+        let mut state = state_with_customers();
+        for user_spec_id in [Some(2), Some(3)] {
+            state.nodes[0].requests.push_back(WaitingRequest {
+                timestamp: 0,
+                amount: 3,
+                user_spec_id,
+                service: ServiceKind::Base,
+                mem_required: Memory::zero(),
+                outcome: BadRequestOutcome::Unchecked,
+                latency: 0,
+                staff: false,
+            });
+        }
+
+        engine.update(&mut state, REQUEST_TIMEOUT + TIMEOUT_CLEANUP_PERIOD);
+
+        assert_eq!(state.traffic[TrafficClass::Trial as usize].dropped, 3);
+        assert_eq!(state.traffic[TrafficClass::Bad as usize].dropped, 3);
+        assert_eq!(state.requests_dropped, 6);
+    }
+
+    /// the number of requests by customers which reached the nodes
+    fn customer_requests(state: &WorldState) -> u64 {
+        [TrafficClass::Paying, TrafficClass::Trial]
+            .into_iter()
+            .map(|class| {
+                let counters = state.traffic[class as usize];
+                counters.fulfilled + counters.dropped
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_maintenance_pauses_customer_traffic() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 42);
+        let mut state = WorldState {
+            demand: 2_000.,
+            ..state_with_customers()
+        };
+        engine.bootstrap_events(&state);
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::SetMaintenance {
+                    kind: ServiceKind::Base,
+                    on: true,
+                },
+            )
+            .unwrap();
+        assert!(state.base_service.in_maintenance());
+
+        // a minute goes by without any customer requests
+        let mut time = 0;
+        while time < TIME_UNITS_PER_MINUTE {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(customer_requests(&state), 0);
+        // attackers do not care
+        assert!(state.traffic[TrafficClass::Bad as usize].fulfilled > 0);
+        // the customers are still there
+        assert_eq!(state.user_specs.len(), 3);
+
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::SetMaintenance {
+                    kind: ServiceKind::Base,
+                    on: false,
+                },
+            )
+            .unwrap();
+        assert!(!state.base_service.in_maintenance());
+
+        // customers come back within a second
+        for _ in 0..20 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        assert!(customer_requests(&state) > 0);
+    }
+
+    #[test]
+    fn test_maintenance_demand_decay() {
+        let config = GameConfig {
+            demand_growth: 0.,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_config(config);
+        let mut state = WorldStateBuilder::new()
+            .demand(100.)
+            .service_unlocked(ServiceKind::Super)
+            .build();
+        let customer = CloudUserSpec {
+            id: 1,
+            service: ServiceKind::Super,
+            trial_time: 0,
+            kind: UserKind::Customer,
+        };
+        let super_demand = |engine: &GameEngine, state: &WorldState| {
+            GameEngine::user_demand(state, &customer, 1., &engine.queue_demand_factor)
+        };
+        let before = super_demand(&engine, &state);
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::SetMaintenance {
+                    kind: ServiceKind::Base,
+                    on: true,
+                },
+            )
+            .unwrap();
+
+        // no decay before the first full minute
+        let cycle = TIME_UNITS_PER_CYCLE as u64;
+        let mut time = 0;
+        while time + cycle < TIME_UNITS_PER_MINUTE {
+            time += cycle;
+            engine.update(&mut state, time);
+        }
+        assert_eq!(state.base_service.maintenance_demand_factor, 1.);
+
+        // one step of decay per full minute
+        while time < 3 * TIME_UNITS_PER_MINUTE {
+            time += cycle;
+            engine.update(&mut state, time);
+        }
+        let expected = (1. - MAINTENANCE_DEMAND_DECAY).powi(3);
+        let factor = state.base_service.maintenance_demand_factor;
+        assert!((factor - expected).abs() < 1e-9, "{factor}");
+
+        // only for the service under maintenance
+        assert_eq!(state.demand, 100.);
+        assert_eq!(state.super_service.maintenance_demand_factor, 1.);
+        assert_eq!(super_demand(&engine, &state), before);
+
+        // and none after maintenance is over
+        engine
+            .apply_action(
+                &mut state,
+                PlayerAction::SetMaintenance {
+                    kind: ServiceKind::Base,
+                    on: false,
+                },
+            )
+            .unwrap();
+        for _ in 0..2_400 {
+            time += cycle;
+            engine.update(&mut state, time);
+        }
+        let factor = state.base_service.maintenance_demand_factor;
+        assert!((factor - expected).abs() < 1e-9, "{factor}");
+    }
+
+    #[test]
+    fn test_demand_growth_stalls_when_under_provisioned() {
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 7);
+        let mut state = WorldState {
+            demand_rate: 1.,
+            ..(0..20)
+                .fold(WorldStateBuilder::new().demand(200.), |builder, _| {
+                    builder.with_customer(ServiceKind::Base)
+                })
+                .build()
+        };
+        engine.bootstrap_events(&state);
+
+        let cycle = TIME_UNITS_PER_CYCLE as u64;
+        let mut time = 0;
+        let mut run_for = |state: &mut WorldState, engine: &mut GameEngine, duration| {
+            let end = time + duration;
+            while time < end {
+                time += cycle;
+                engine.update(state, time);
+            }
+        };
+
+        // the bare node cannot keep up, so growth stalls early on
+        run_for(&mut state, &mut engine, 5 * TIME_UNITS_PER_MINUTE);
+        assert!(engine.growth_stalled());
+        assert!(state.demand < 205., "{}", state.demand);
+
+        // and demand stays put while the player is away
+        let plateau = state.demand;
+        run_for(&mut state, &mut engine, 5 * TIME_UNITS_PER_MINUTE);
+        assert!(engine.growth_stalled());
+        assert_eq!(state.demand, plateau);
+
+        // add plenty of capacity
+        let upgraded = CloudNode::new_fully_upgraded_rack(0);
+        let node = &mut state.nodes[0];
+        node.cpu_level = upgraded.cpu_level;
+        node.ram_level = upgraded.ram_level;
+        node.num_cores = upgraded.num_cores;
+        node.cpu_speed = upgraded.cpu_speed;
+        node.ram_capacity = upgraded.ram_capacity;
+
+        // growth resumes once drops subside
+        run_for(&mut state, &mut engine, 5 * TIME_UNITS_PER_MINUTE);
+        assert!(!engine.growth_stalled());
+        assert!(state.demand > plateau + 10., "{}", state.demand);
+    }
+
+    #[test]
+    fn test_hardware_panel_milestones() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        let mut time = 0;
+        let mut major_update = |engine: &mut GameEngine, state: &mut WorldState| {
+            time += 2_500;
+            engine.update(state, time);
+        };
+
+        major_update(&mut engine, &mut state);
+        assert_eq!(state.hardware_panel_level, HARDWARE_PANEL_NODES);
+        assert!(engine.notifications().is_empty());
+
+        state.earned = Money::dollars(10);
+        major_update(&mut engine, &mut state);
+        assert_eq!(state.hardware_panel_level, HARDWARE_PANEL_LOAD_BARS);
+        assert_eq!(engine.notifications().len(), 1);
+
+        state.requests_dropped = 1;
+        major_update(&mut engine, &mut state);
+        assert_eq!(state.hardware_panel_level, HARDWARE_PANEL_UPGRADES);
+
+        state.can_buy_nodes = true;
+        major_update(&mut engine, &mut state);
+        assert_eq!(state.hardware_panel_level, HARDWARE_PANEL_PURCHASES);
+        assert_eq!(engine.notifications().len(), 3);
+
+        // never goes back
+        state.can_buy_nodes = false;
+        state.requests_dropped = 0;
+        state.earned = Money::zero();
+        major_update(&mut engine, &mut state);
+        assert_eq!(state.hardware_panel_level, HARDWARE_PANEL_PURCHASES);
+        assert_eq!(engine.notifications().len(), 3);
+
+        // and is kept in the save
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.hardware_panel_level, HARDWARE_PANEL_PURCHASES);
+
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        assert!(!json.contains("hardware_panel_level"));
+    }
+
+    #[test]
+    fn test_period_reports() {
+        fn run_period(engine: &mut GameEngine, state: &mut WorldState) {
+            let end = (state.time / ELECTRICITY_BILL_PERIOD + 1) * ELECTRICITY_BILL_PERIOD;
+            while state.time < end {
+                let time = state.time + 2_500;
+                engine.update(state, time);
+            }
+        }
+
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+
+        state.earned += Money::dollars(5);
+        state.base_service.total = Ops(300);
+        run_period(&mut engine, &mut state);
+        assert_eq!(state.reports.len(), 1);
+
+        state.earned += Money::dollars(8);
+        state.base_service.total = Ops(350);
+        state.requests_dropped = 7;
+        run_period(&mut engine, &mut state);
+        assert_eq!(state.reports.len(), 2);
+
+        let first = &state.reports[0];
+        assert_eq!(first.revenue, Money::dollars(5));
+        assert_eq!(first.ops[0], Ops(300));
+        assert_eq!(first.requests_dropped, 0);
+        let second = &state.reports[1];
+        assert_eq!(second.revenue, Money::dollars(8));
+        assert_eq!(second.ops[0], Ops(50));
+        assert_eq!(second.requests_dropped, 7);
+        assert_eq!(second.time, 2 * ELECTRICITY_BILL_PERIOD);
+
+        // reports and the ongoing period survive a save/load cycle
+        let json = serde_json::to_string(&state).unwrap();
+        let mut state: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.reports[1].revenue, Money::dollars(8));
+
+        let mut engine = GameEngine::new();
+        state.earned += Money::dollars(1);
+        run_period(&mut engine, &mut state);
+        assert_eq!(state.reports.len(), 3);
+        assert_eq!(state.reports[2].revenue, Money::dollars(1));
+        assert_eq!(state.reports[2].requests_dropped, 0);
+
+        // the number of reports is bounded
+        for _ in 0..5 {
+            run_period(&mut engine, &mut state);
+        }
+        assert_eq!(state.reports.len(), MAX_PERIOD_REPORTS);
+    }
+
+    #[test]
+    fn test_concurrent_games_isolated() {
+        let mut engine_a = GameEngine::new();
+        let mut engine_b = GameEngine::new();
+        let mut state_a = WorldState::default();
+        let mut state_b = WorldState::default();
+
+        let mut time = 0;
+        for _ in 0..100 {
+            engine_a
+                .apply_action(
+                    &mut state_a,
+                    PlayerAction::OpClick {
+                        kind: ServiceKind::Base,
+                        amount: 1,
+                    },
+                )
+                .unwrap();
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine_a.update(&mut state_a, time);
+            engine_b.update(&mut state_b, time);
+        }
+
+        assert!(state_a.base_service.total > Ops(0));
+        assert_eq!(state_b.base_service.total, Ops(0));
+        assert_eq!(
+            state_b,
+            WorldState {
+                time,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "debug-tools")]
+    mod debug_tools {
+        use crate::{
+            central::{
+                engine::{GameEngine, ELECTRICITY_BILL_PERIOD, INCREASE_DEMAND_PERIOD},
+                inspect::SNAPSHOT_EVENTS,
+                testing::WorldStateBuilder,
+            },
+            DebugAction, GameConfig, Money, Ops, PlayerAction, ServiceKind, UserKind, WorldState,
+            TIME_UNITS_PER_CYCLE,
+        };
+
+        fn debug(engine: &mut GameEngine, state: &mut WorldState, action: DebugAction) {
+            engine
+                .apply_action(state, PlayerAction::Debug(action))
+                .unwrap();
+        }
+
+        #[test]
+        fn test_add_funds_and_ops() {
+            let mut engine = GameEngine::new();
+            let mut state = WorldState::default();
+            debug(
+                &mut engine,
+                &mut state,
+                DebugAction::AddFunds(Money::dollars(90)),
+            );
+            assert_eq!(state.funds, Money::dollars(100));
+            // not counted as earnings
+            assert_eq!(state.earned, Money::zero());
+
+            debug(
+                &mut engine,
+                &mut state,
+                DebugAction::AddOps {
+                    kind: ServiceKind::Epic,
+                    amount: Ops(1_000),
+                },
+            );
+            assert_eq!(state.epic_service.total, Ops(1_000));
+            assert_eq!(state.epic_service.available, Ops(1_000));
+        }
+
+        #[test]
+        fn test_debug_snapshot() {
+            let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+            let mut state = WorldStateBuilder::new()
+                .demand(500.)
+                .with_customer(ServiceKind::Base)
+                .with_customer(ServiceKind::Base)
+                .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+                .build();
+            engine.bootstrap_events(&state);
+            let mut time = 0;
+            for _ in 0..20 {
+                time += TIME_UNITS_PER_CYCLE as u64;
+                engine.update(&mut state, time);
+            }
+
+            let queue_len = engine.queue.len();
+            let snapshot = engine.debug_snapshot(&state);
+            assert_eq!(snapshot.queue_len, queue_len);
+            assert!(!snapshot.next_events.is_empty());
+            assert!(snapshot.next_events.len() <= SNAPSHOT_EVENTS);
+            assert_eq!(snapshot.nodes.len(), 1);
+            assert!(snapshot.demand[0].is_some_and(|demand| demand > 0.));
+            assert_eq!(snapshot.demand[1], None);
+
+            // taking a snapshot changes nothing
+            assert_eq!(engine.queue.len(), queue_len);
+            assert_eq!(engine.debug_snapshot(&state), snapshot);
+
+            // events are listed in the order they are processed
+            for event in &snapshot.next_events {
+                assert_eq!(engine.queue.pop().as_ref(), Some(event));
+            }
+        }
+
+        #[test]
+        fn test_metrics_text() {
+            let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 3);
+            let mut state = WorldStateBuilder::new()
+                .demand(5_000.)
+                .with_customer(ServiceKind::Base)
+                .with_customer(ServiceKind::Base)
+                .build();
+            engine.bootstrap_events(&state);
+
+            let run = |engine: &mut GameEngine, state: &mut WorldState| {
+                let mut time = state.time;
+                for _ in 0..200 {
+                    time += TIME_UNITS_PER_CYCLE as u64;
+                    engine.update(state, time);
+                }
+                engine.metrics_text(state)
+            };
+            // the value of each sample by its name and labels
+            let samples = |text: &str| -> Vec<(String, f64)> {
+                text.lines()
+                    .filter(|line| !line.starts_with('#'))
+                    .map(|line| {
+                        let (name, value) = line.rsplit_once(' ').unwrap();
+                        assert!(name.starts_with("cloud_champion_"), "{line}");
+                        (name.to_string(), value.parse().unwrap())
+                    })
+                    .collect()
+            };
+
+            let before = run(&mut engine, &mut state);
+            // every family is described and typed
+            for line in before.lines().filter(|line| line.starts_with("# TYPE")) {
+                let name = line.split(' ').nth(2).unwrap();
+                assert!(before.contains(&format!("# HELP {name} ")), "{name}");
+                assert!(
+                    line.ends_with(" gauge") || name.ends_with("_total"),
+                    "{line}"
+                );
+            }
+            assert!(before.contains("cloud_champion_ops_total{service=\"base\"} "));
+            assert!(before.contains("cloud_champion_requests_dropped_total{cause=\"cpu\"} "));
+            assert!(before.contains("cloud_champion_nodes 1\n"));
+
+            // counters never go down
+            let after = run(&mut engine, &mut state);
+            let (before, after) = (samples(&before), samples(&after));
+            assert_eq!(before.len(), after.len());
+            for ((name, old), (_, new)) in before.iter().zip(&after) {
+                if name.contains("_total") {
+                    assert!(new >= old, "{name}: {old} -> {new}");
+                }
+            }
+            let base_ops = |samples: &[(String, f64)]| {
+                samples
+                    .iter()
+                    .find(|(name, _)| name == "cloud_champion_ops_total{service=\"base\"}")
+                    .unwrap()
+                    .1
+            };
+            assert!(base_ops(&after) > base_ops(&before));
+        }
+
+        #[test]
+        fn test_set_demand() {
+            let mut engine = GameEngine::new();
+            let mut state = WorldState::default();
+            debug(&mut engine, &mut state, DebugAction::SetDemand(42.));
+            assert_eq!(state.demand, 42.);
+            debug(&mut engine, &mut state, DebugAction::SetDemand(-5.));
+            assert_eq!(state.demand, 0.);
+        }
+
+        #[test]
+        fn test_advance_time_fires_periodic_events() {
+            let mut engine = GameEngine::new();
+            let mut state = WorldState {
+                demand_rate: 1.,
+                ..Default::default()
+            };
+            state.electricity.add_consumption(100_000_000);
+
+            // a bit longer than a bill period
+            let seconds = (ELECTRICITY_BILL_PERIOD / 10_000 + 1) as u32;
+            debug(
+                &mut engine,
+                &mut state,
+                DebugAction::AdvanceTime { seconds },
+            );
+
+            assert_eq!(state.time, seconds as u64 * 10_000);
+            // the bill was issued
+            assert!(state.electricity.total_due > Money::zero());
+            // and demand grew on every period, not just once
+            let periods = (state.time / INCREASE_DEMAND_PERIOD) as f64;
+            assert!((state.demand - periods).abs() < 1e-3, "{}", state.demand);
+        }
+
+        #[test]
+        fn test_toggle_powersave() {
+            let mut engine = GameEngine::new();
+            let mut state = WorldState {
+                time: ELECTRICITY_BILL_PERIOD * 2,
+                ..Default::default()
+            };
+            assert!(!state.is_powersaving());
+            debug(&mut engine, &mut state, DebugAction::TogglePowersave);
+            assert!(state.is_powersaving());
+            debug(&mut engine, &mut state, DebugAction::TogglePowersave);
+            assert!(!state.is_powersaving());
+            assert_eq!(state.electricity.total_due, Money::zero());
+        }
+
+        #[test]
+        fn test_spawn_dos_once() {
+            let mut engine = GameEngine::new();
+            let mut state = WorldState::default();
+            debug(
+                &mut engine,
+                &mut state,
+                DebugAction::SpawnDos(ServiceKind::Base),
+            );
+            debug(
+                &mut engine,
+                &mut state,
+                DebugAction::SpawnDos(ServiceKind::Base),
+            );
+            let dos_specs: Vec<_> = state
+                .user_specs
+                .iter()
+                .filter(|spec| spec.is_bad())
+                .collect();
+            assert_eq!(dos_specs.len(), 1);
+            assert!(!engine.queue.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_gen_rate() {
+        let mut gen = super::SampleGenerator::new();
+        let k = 2_000;
+        let mut sum = 0.;
+        for _ in 0..k {
+            if gen.gen_bool(0.75) {
+                sum += 1.;
+            }
+        }
+        let avg = sum / k as f64;
+        // should be around 0.75
+        // (the margin is generous to prevent flaky tests)
+        assert!(avg > 0.72 && avg < 0.78, "hit rate was {}", avg);
+
+        let mut sum = 0.;
+        for _ in 0..k {
+            if gen.gen_bool(0.4) {
+                sum += 1.;
+            }
+        }
+        let avg = sum / k as f64;
+        // should be around 0.4
+        assert!(avg > 0.37 && avg < 0.43, "hit rate was {}", avg);
+    }
+
+    #[test]
+    fn test_daily_cycle() {
+        let engine = GameEngine::new();
+        let day = super::DAY_LENGTH;
+
+        // repeats every day
+        for time in [0, day / 7, day / 3, day / 2 + 12_345] {
+            let a = engine.daily_cycle(time);
+            let b = engine.daily_cycle(time + day);
+            let c = engine.daily_cycle(time + 5 * day);
+            assert!((a - b).abs() < 1e-4 && (a - c).abs() < 1e-4);
+        }
+
+        // stays within bounds, peaking at midday and bottoming at midnight
+        let samples = 1_000;
+        let mut sum = 0.;
+        for i in 0..samples {
+            let cycle = engine.daily_cycle(i * day / samples);
+            assert!((0.6 - 1e-4..=1.4 + 1e-4).contains(&cycle), "{cycle}");
+            sum += cycle;
+        }
+        assert!((engine.daily_cycle(day / 4) - 1.4).abs() < 1e-4);
+        assert!((engine.daily_cycle(3 * day / 4) - 0.6).abs() < 1e-4);
+
+        // averages out to the base demand
+        let avg = sum / samples as f32;
+        assert!((avg - 1.).abs() < 1e-3, "average was {avg}");
+    }
+
+    /// Play the cards in random order with plenty of resources,
+    /// looking for card combinations which break the engine.
+    #[test]
+    fn test_random_card_orders() {
+        use rand::{Rng, SeedableRng};
+
+        for seed in 0..12 {
+            let mut rng = rand_pcg::Pcg32::seed_from_u64(seed);
+            let mut engine = GameEngine::with_config_seeded(GameConfig::default(), seed);
+            let mut state = WorldStateBuilder::new().build();
+            engine.bootstrap_events(&state);
+
+            for _ in 0..80 {
+                // grant more than any card costs
+                state.funds = Money::dollars(100_000_000);
+                for kind in ServiceKind::ALL {
+                    let service = state.service_by_kind_mut(kind);
+                    service.available = Ops(service.available.0.max(200_000_000));
+                    service.total = Ops(service.total.0.max(service.available.0));
+                }
+
+                let playable: Vec<_> = ALL_CARDS
+                    .iter()
+                    .filter(|card| {
+                        card.should_appear(&state) && state.can_afford(&state.card_cost(card))
+                    })
+                    .collect();
+                if !playable.is_empty() {
+                    let card = playable[rng.gen_range(0..playable.len())];
+                    let _ = engine
+                        .apply_action(&mut state, PlayerAction::UseCard { id: card.id.into() });
+                }
+                let time = state.time + rng.gen_range(1..6) * TIME_UNITS_PER_SECOND;
+                engine.update(&mut state, time);
+
+                for (kind, service) in state.services() {
+                    assert!(
+                        service.available >= Ops(0),
+                        "seed {seed}: negative {kind} ops available"
+                    );
+                }
+                assert!(
+                    state.cards_used.windows(2).all(|w| w[0].id < w[1].id),
+                    "seed {seed}: cards used out of order or repeated: {:?}",
+                    state.cards_used
+                );
+            }
+        }
+    }
+
+    /// Timed actions for a scripted run,
+    /// none of which fall on a major update.
+    fn sample_script() -> Vec<(Time, PlayerAction)> {
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 4,
+        };
+        vec![
+            (1_250, click.clone()),
+            (1_250, click.clone()),
+            (31_700, PlayerAction::AddNode),
+            (31_700, click.clone()),
+            (62_120, PlayerAction::UpgradeRam { node: 0 }),
+            (75_010, PlayerAction::UpgradeCpu { node: 99 }),
+            (2_600_100, PlayerAction::PayElectricityBill),
+            (2_640_800, click),
+        ]
+    }
+
+    fn scripted_game() -> (GameEngine, WorldState) {
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(3_000))
+            .service_published(ServiceKind::Base, Money::millicents(5))
+            .with_customer(ServiceKind::Base)
+            .with_customer(ServiceKind::Base)
+            .build();
+        state.can_buy_nodes = true;
+        let mut engine = GameEngine::with_config_seeded(GameConfig::default(), 7);
+        engine.bootstrap_events(&state);
+        (engine, state)
+    }
+
+    /// Running a script is the same as
+    /// advancing the game to each action before applying it,
+    /// when no action falls on a major update.
+    #[test]
+    fn test_run_script_as_interleaved() {
+        let script = sample_script();
+        let until = 2_700_000;
+
+        let (mut engine, mut state) = scripted_game();
+        let outcomes = engine.run_script(&mut state, &script, until).unwrap();
+        assert_eq!(outcomes.len(), script.len());
+        assert_eq!(outcomes[5], Err(ActionError::NoSuchNode(99)));
+        assert_eq!(state.time, until);
+        assert_eq!(state.nodes.len(), 2);
+
+        let (mut manual_engine, mut manual_state) = scripted_game();
+        let mut manual_outcomes = vec![];
+        for (time, action) in script.iter().cloned() {
+            // actions at the same time follow each other
+            if manual_state.time < time {
+                manual_engine.update(&mut manual_state, time);
+            }
+            manual_outcomes.push(manual_engine.apply_action(&mut manual_state, action));
+        }
+        manual_engine.update(&mut manual_state, until);
+
+        assert_eq!(outcomes, manual_outcomes);
+        assert_eq!(
+            serde_json::to_value(&state).unwrap(),
+            serde_json::to_value(&manual_state).unwrap()
+        );
+    }
+
+    /// An action timed on a major update
+    /// is applied before the side effects of that update.
+    #[test]
+    fn test_run_script_action_before_major_update() {
+        let mut engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .time(ELECTRICITY_BILL_PERIOD - 10_000)
+            .funds(Money::dollars(500))
+            .build();
+        state.electricity.emit_bill_for(Money::dollars(5), 1);
+        state.electricity.consumed = 10 * MICRO_WATTEVER_PER_WATTEVER;
+        let mut manual_engine = GameEngine::new();
+        let mut manual_state = state.clone();
+        assert_eq!(ELECTRICITY_BILL_PERIOD % MAJOR_UPDATE_PERIOD, 0);
+
+        // the bill due is paid right before the next one is issued
+        let script = [(ELECTRICITY_BILL_PERIOD, PlayerAction::PayElectricityBill)];
+        let outcomes = engine
+            .run_script(&mut state, &script, ELECTRICITY_BILL_PERIOD)
+            .unwrap();
+        assert_eq!(outcomes, vec![Ok(())]);
+        let bill = state.electricity.last_bill;
+        assert!(bill > Money::dollars(1), "{bill}");
+        assert_eq!(state.electricity.total_due, bill);
+        assert_eq!(state.funds, Money::dollars(495));
+
+        // whereas acting after reaching that time pays both
+        manual_engine.update(&mut manual_state, ELECTRICITY_BILL_PERIOD);
+        manual_engine
+            .apply_action(&mut manual_state, PlayerAction::PayElectricityBill)
+            .unwrap();
+        assert_eq!(manual_state.electricity.total_due, Money::zero());
+        assert!(manual_state.funds < state.funds);
+    }
+
+    #[test]
+    fn test_run_script_unsorted() {
+        let click = PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount: 1,
+        };
+        let (mut engine, mut state) = scripted_game();
+        engine.update(&mut state, 5_000);
+        let before = serde_json::to_value(&state).unwrap();
+
+        let script = [(6_000, click.clone()), (5_500, click.clone())];
+        assert_eq!(
+            engine.run_script(&mut state, &script, 10_000),
+            Err(ScriptError::Unsorted {
+                index: 1,
+                time: 5_500,
+                previous: 6_000
+            })
+        );
+        // nothing in the past either
+        let script = [(4_000, click.clone())];
+        assert_eq!(
+            engine.run_script(&mut state, &script, 10_000),
+            Err(ScriptError::Unsorted {
+                index: 0,
+                time: 4_000,
+                previous: 5_000
+            })
+        );
+        let script = [(6_000, click)];
+        assert_eq!(
+            engine.run_script(&mut state, &script, 5_500),
+            Err(ScriptError::EndsEarly {
+                until: 5_500,
+                last: 6_000
+            })
+        );
+        assert_eq!(serde_json::to_value(&state).unwrap(), before);
+    }
+}
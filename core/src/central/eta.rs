@@ -0,0 +1,134 @@
+//! Module for estimating how long until the player can afford something,
+//! from the rates at which funds and ops have been coming in.
+
+use crate::{Cost, Ops, ServiceKind, Time, TIME_UNITS_PER_SECOND};
+
+/// the rate below which a resource is taken as not coming in at all,
+/// in units per second
+const MIN_RATE: f64 = 1e-6;
+
+/// The rates at which the player's resources have recently been coming in.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ResourceRates {
+    /// the money earned, in millicents per second
+    pub money: f64,
+    /// the ops performed for each service,
+    /// indexed by [`ServiceKind::to_code`],
+    /// in ops per second
+    pub ops: [f64; ServiceKind::ALL.len()],
+}
+
+/// Estimate the game time until the given cost can be afforded,
+/// assuming that resources keep coming in at the given rates
+/// and that nothing else is spent.
+///
+/// The estimate is the longest wait among the parts of the cost
+/// which cannot be afforded yet,
+/// or zero if all of it can already be afforded.
+/// Returns `None` if some part which cannot be afforded
+/// is not coming in at all.
+pub fn eta_for(cost: &Cost, available: &Cost, rates: &ResourceRates) -> Option<Time> {
+    let money = (
+        cost.money.to_millicents(),
+        available.money.to_millicents(),
+        rates.money,
+    );
+    let ops = cost.ops().into_iter().zip(available.ops()).map(
+        |((kind, Ops(needed)), (_, Ops(available)))| {
+            (needed, available, rates.ops[kind.to_code() as usize])
+        },
+    );
+
+    let mut longest: f64 = 0.;
+    for (needed, available, rate) in std::iter::once(money).chain(ops) {
+        let missing = needed - available;
+        if missing <= 0 {
+            continue;
+        }
+        if rate < MIN_RATE {
+            return None;
+        }
+        longest = longest.max(missing as f64 / rate);
+    }
+    Some((longest * TIME_UNITS_PER_SECOND as f64).ceil() as Time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eta_for, ResourceRates};
+    use crate::{Cost, Money, Ops, TIME_UNITS_PER_SECOND};
+
+    /// $1 per second and 10 base ops per second,
+    /// nothing else coming in
+    fn rates() -> ResourceRates {
+        ResourceRates {
+            money: 100_000.,
+            ops: [10., 0., 0., 0., 0.],
+        }
+    }
+
+    #[test]
+    fn test_eta_already_affordable() {
+        let cost = Cost::dollars(5).and(Cost::super_ops(10));
+        let available = Cost::dollars(5).and(Cost::super_ops(20));
+        assert_eq!(eta_for(&cost, &available, &rates()), Some(0));
+        // even if nothing is coming in
+        assert_eq!(
+            eta_for(&cost, &available, &ResourceRates::default()),
+            Some(0)
+        );
+        assert_eq!(
+            eta_for(&Cost::nothing(), &Cost::nothing(), &rates()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_eta_zero_rate() {
+        // super ops are not coming in
+        let cost = Cost::super_ops(10);
+        assert_eq!(eta_for(&cost, &Cost::nothing(), &rates()), None);
+        // nor anything at all
+        assert_eq!(
+            eta_for(
+                &Cost::dollars(1),
+                &Cost::nothing(),
+                &ResourceRates::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_eta_mixed_components() {
+        // 130 s for the money, 15 s for the base ops
+        let cost = Cost::dollars(150).and(Cost::base_ops(200));
+        let available = Cost {
+            money: Money::dollars(20),
+            base_ops: Ops(50),
+            ..Cost::nothing()
+        };
+        assert_eq!(
+            eta_for(&cost, &available, &rates()),
+            Some(130 * TIME_UNITS_PER_SECOND)
+        );
+
+        // the base ops take the longest
+        let cost = Cost::dollars(21).and(Cost::base_ops(1_350));
+        assert_eq!(
+            eta_for(&cost, &available, &rates()),
+            Some(130 * TIME_UNITS_PER_SECOND)
+        );
+
+        // partial seconds are rounded up
+        let cost = Cost::base_ops(55);
+        assert_eq!(
+            eta_for(&cost, &available, &rates()),
+            Some(TIME_UNITS_PER_SECOND / 2)
+        );
+
+        // one part not coming in makes it never
+        let cost = Cost::dollars(150).and(Cost::epic_ops(1));
+        assert_eq!(eta_for(&cost, &available, &rates()), None);
+    }
+}
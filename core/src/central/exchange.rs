@@ -0,0 +1,106 @@
+//! Module for the op exchange,
+//! where ops of a lower service tier are traded for ops of a higher one.
+//!
+//! The rates are steep on purpose:
+//! the exchange is a sink for the lower tier ops
+//! piling up in the late game,
+//! not a shortcut to the higher tiers.
+
+use crate::{Ops, ServiceKind};
+
+/// the ops of each service tier given for one op of the next tier,
+/// indexed by the lower tier
+pub static OP_EXCHANGE_RATES: [i64; ServiceKind::ALL.len() - 1] = [1_000, 1_000, 1_000, 1_000];
+
+/// The ops of the `from` service given for one op of the `to` service,
+/// or `None` if the exchange is not possible
+/// (only ops of a higher tier can be obtained).
+pub fn exchange_rate(from: ServiceKind, to: ServiceKind) -> Option<i64> {
+    if to as usize <= from as usize {
+        return None;
+    }
+    OP_EXCHANGE_RATES[from as usize..to as usize]
+        .iter()
+        .try_fold(1_i64, |rate, &step| rate.checked_mul(step))
+}
+
+/// The outcome of exchanging some ops.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    /// the ops given for each op obtained
+    pub rate: Ops,
+    /// the ops given, a multiple of the rate
+    pub spent: Ops,
+    /// the ops obtained
+    pub obtained: Ops,
+}
+
+impl Exchange {
+    /// Calculate the outcome of offering the given amount of `from` ops
+    /// in exchange for `to` ops.
+    ///
+    /// Only whole ops are obtained,
+    /// so the part of the amount short of another op is not spent.
+    /// Returns `None` if the exchange is not possible.
+    pub fn preview(from: ServiceKind, to: ServiceKind, amount: Ops) -> Option<Self> {
+        let rate = exchange_rate(from, to)?;
+        let obtained = amount.0.max(0) / rate;
+        Some(Self {
+            rate: Ops(rate),
+            spent: Ops(obtained * rate),
+            obtained: Ops(obtained),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exchange_rate, Exchange};
+    use crate::{Ops, ServiceKind};
+
+    #[test]
+    fn test_exchange_rates() {
+        assert_eq!(
+            exchange_rate(ServiceKind::Base, ServiceKind::Super),
+            Some(1_000)
+        );
+        assert_eq!(
+            exchange_rate(ServiceKind::Super, ServiceKind::Epic),
+            Some(1_000)
+        );
+        // skipping tiers compounds the rates
+        assert_eq!(
+            exchange_rate(ServiceKind::Base, ServiceKind::Epic),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            exchange_rate(ServiceKind::Base, ServiceKind::Legendary),
+            Some(1_000_000_000_000)
+        );
+        // only upwards
+        assert_eq!(exchange_rate(ServiceKind::Epic, ServiceKind::Super), None);
+        assert_eq!(exchange_rate(ServiceKind::Epic, ServiceKind::Epic), None);
+    }
+
+    #[test]
+    fn test_exchange_preview() {
+        let exchange =
+            Exchange::preview(ServiceKind::Base, ServiceKind::Super, Ops(2_500)).unwrap();
+        assert_eq!(exchange.rate, Ops(1_000));
+        assert_eq!(exchange.obtained, Ops(2));
+        // the remainder is kept
+        assert_eq!(exchange.spent, Ops(2_000));
+
+        let exchange = Exchange::preview(ServiceKind::Base, ServiceKind::Super, Ops(999)).unwrap();
+        assert_eq!(exchange.obtained, Ops(0));
+        assert_eq!(exchange.spent, Ops(0));
+
+        let exchange = Exchange::preview(ServiceKind::Base, ServiceKind::Super, Ops(-5)).unwrap();
+        assert_eq!(exchange.spent, Ops(0));
+
+        assert_eq!(
+            Exchange::preview(ServiceKind::Super, ServiceKind::Base, Ops(1_000)),
+            None
+        );
+    }
+}
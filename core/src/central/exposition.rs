@@ -0,0 +1,186 @@
+//! Module for exporting the game metrics
+//! in the Prometheus text exposition format,
+//! so that long sessions can be scraped into external dashboards
+//! or attached to bug reports.
+//!
+//! The document is built one metric family at a time
+//! with an [`Exposition`],
+//! and filled in by
+//! [`GameEngine::metrics_text`](super::engine::GameEngine::metrics_text).
+
+use std::fmt::Write;
+
+/// the prefix of the names of all exported metrics
+pub const METRIC_PREFIX: &str = "cloud_champion_";
+
+/// The type of a metric family.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MetricKind {
+    /// a value which only goes up,
+    /// until the engine is restarted
+    Counter,
+    /// a value which can go up and down
+    Gauge,
+}
+
+/// A document in the Prometheus text exposition format.
+#[derive(Debug, Default, Clone)]
+pub struct Exposition {
+    text: String,
+    /// the full name of the metric family being written
+    family: String,
+}
+
+impl Exposition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new metric family,
+    /// named without the [`METRIC_PREFIX`].
+    ///
+    /// Following the naming conventions,
+    /// the names of counters end in `_total` and those of gauges do not.
+    pub fn family(&mut self, kind: MetricKind, name: &str, help: &str) -> &mut Self {
+        debug_assert!(is_valid_name(name), "invalid metric name {name:?}");
+        debug_assert_eq!(
+            kind == MetricKind::Counter,
+            name.ends_with("_total"),
+            "badly named {kind:?} {name:?}"
+        );
+        self.family = format!("{METRIC_PREFIX}{name}");
+        let kind = match kind {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        };
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.text, "# HELP {} {help}", self.family);
+        let _ = writeln!(self.text, "# TYPE {} {kind}", self.family);
+        self
+    }
+
+    /// Add a sample with the given labels to the current metric family.
+    pub fn sample(&mut self, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        debug_assert!(!self.family.is_empty(), "sample outside of a family");
+        self.text.push_str(&self.family);
+        if !labels.is_empty() {
+            self.text.push('{');
+            for (i, (name, value)) in labels.iter().enumerate() {
+                debug_assert!(is_valid_name(name), "invalid label name {name:?}");
+                if i > 0 {
+                    self.text.push(',');
+                }
+                let _ = write!(self.text, "{name}=\"{}\"", escape_label(value));
+            }
+            self.text.push('}');
+        }
+        let _ = writeln!(self.text, " {}", format_value(value));
+        self
+    }
+
+    /// The document written so far.
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+/// Whether the given name is valid for a metric or label
+/// (no colons, which are reserved for recording rules).
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Escape a label value,
+/// which goes between double quotes.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_value, is_valid_name, Exposition, MetricKind};
+
+    #[test]
+    fn test_exposition_format() {
+        let mut doc = Exposition::new();
+        doc.family(MetricKind::Counter, "ops_total", "Operations performed")
+            .sample(&[("service", "base")], 12.)
+            .sample(&[("service", "super"), ("tier", "2")], 0.)
+            .family(MetricKind::Gauge, "demand", "Base demand")
+            .sample(&[], 1.5);
+        assert_eq!(
+            doc.finish(),
+            "# HELP cloud_champion_ops_total Operations performed\n\
+             # TYPE cloud_champion_ops_total counter\n\
+             cloud_champion_ops_total{service=\"base\"} 12\n\
+             cloud_champion_ops_total{service=\"super\",tier=\"2\"} 0\n\
+             # HELP cloud_champion_demand Base demand\n\
+             # TYPE cloud_champion_demand gauge\n\
+             cloud_champion_demand 1.5\n"
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        let mut doc = Exposition::new();
+        doc.family(MetricKind::Gauge, "odd", "Back\\slash\nand \"quotes\"")
+            .sample(&[("name", "say \"hi\"\\\n")], 1.);
+        assert_eq!(
+            doc.finish(),
+            "# HELP cloud_champion_odd Back\\\\slash\\nand \"quotes\"\n\
+             # TYPE cloud_champion_odd gauge\n\
+             cloud_champion_odd{name=\"say \\\"hi\\\"\\\\\\n\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert_eq!(format_value(f64::NAN), "NaN");
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(format_value(-0.25), "-0.25");
+        // no exponent notation for big counters
+        assert_eq!(format_value(1e15), "1000000000000000");
+    }
+
+    #[test]
+    fn test_names() {
+        assert!(is_valid_name("requests_dropped_total"));
+        assert!(is_valid_name("_x1"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("1x"));
+        assert!(!is_valid_name("queue-length"));
+        assert!(!is_valid_name("a:b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "badly named Gauge")]
+    fn test_gauge_named_like_counter() {
+        Exposition::new().family(MetricKind::Gauge, "ops_total", "");
+    }
+
+    #[test]
+    #[should_panic(expected = "badly named Counter")]
+    fn test_counter_without_total() {
+        Exposition::new().family(MetricKind::Counter, "ops", "");
+    }
+}
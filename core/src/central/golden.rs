@@ -0,0 +1,510 @@
+//! Golden checkpoints of the game economy,
+//! to catch balance regressions across refactors.
+//!
+//! Each scenario plays a seeded game with a canned player script,
+//! and takes a reduced snapshot of the world state
+//! at a few checkpoints of game time.
+//! The snapshots are compared with the goldens checked in next to this module,
+//! within a tolerance per field,
+//! and must hold a few invariants of their own
+//! (see [`check_invariants`]).
+//! Money is rounded to the dollar and demand to a tenth,
+//! and the funds left are compared relative to the money earned,
+//! since every purchase of the canned player moves them,
+//! so that small tweaks elsewhere do not call for new goldens.
+//! The scenarios are also played through a [`Simulation`],
+//! as when the game runs in a web worker,
+//! and must meet the same goldens.
+//!
+//! After an intended balance change,
+//! regenerate the goldens with:
+//!
+//! ```sh
+//! UPDATE_GOLDENS=1 cargo test golden_
+//! ```
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    central::{
+        engine::GameEngine,
+        metrics::DropCause,
+        simulation::{Simulation, SimulationInput, SimulationOutput},
+        testing::WorldStateBuilder,
+    },
+    GameConfig, Money, PlayerAction, ServiceKind, Time, WorldState, TIME_UNITS_PER_CYCLE,
+    TIME_UNITS_PER_MILLISECOND, TIME_UNITS_PER_MINUTE, TIME_UNITS_PER_SECOND,
+};
+
+/// the checkpoints at which snapshots are taken, in minutes of game time
+const CHECKPOINT_MINUTES: [u64; 3] = [5, 15, 60];
+
+/// the environment variable which makes the tests write the goldens
+const UPDATE_VAR: &str = "UPDATE_GOLDENS";
+
+/// The reduced world state compared at each checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    /// the game time of the checkpoint in minutes
+    minutes: u64,
+    /// the available funds in whole dollars
+    funds: f64,
+    /// the total money earned in whole dollars
+    earned: f64,
+    /// the total ops of each service
+    ops: [i64; ServiceKind::ALL.len()],
+    /// the base service demand, to a tenth
+    demand: f64,
+    /// the number of cloud nodes
+    nodes: usize,
+    /// the total number of requests dropped
+    dropped: u64,
+}
+
+impl Checkpoint {
+    fn of(state: &WorldState) -> Self {
+        let dollars = |money: Money| (money.to_millicents() as f64 / 100_000.).round();
+        Self {
+            minutes: state.time / TIME_UNITS_PER_MINUTE,
+            funds: dollars(state.funds),
+            earned: dollars(state.earned),
+            ops: ServiceKind::ALL.map(|kind| state.service_by_kind(kind).total.0),
+            demand: (state.demand * 10.).round() / 10.,
+            nodes: state.nodes.len(),
+            dropped: state.requests_dropped,
+        }
+    }
+
+    /// Describe every field out of tolerance,
+    /// or return an empty string if there is none.
+    fn diff(&self, actual: &Checkpoint) -> String {
+        let mut out = String::new();
+        let mut check = |field: &str, expected: f64, actual: f64, allowed: f64| {
+            if (actual - expected).abs() > allowed {
+                let _ = writeln!(
+                    out,
+                    "  at {} min, {field}: expected {expected:.2} \u{b1} {allowed:.2}, got {actual:.2}",
+                    self.minutes
+                );
+            }
+        };
+        // the funds swing with every purchase,
+        // so they may stray as far as a share of the money earned
+        check(
+            "funds",
+            self.funds,
+            actual.funds,
+            Tolerance::MONEY.allowed(self.earned),
+        );
+        check(
+            "earned",
+            self.earned,
+            actual.earned,
+            Tolerance::MONEY.allowed(self.earned),
+        );
+        for ((kind, expected), actual) in ServiceKind::ALL.iter().zip(self.ops).zip(actual.ops) {
+            let expected = expected as f64;
+            check(
+                &format!("{kind} ops"),
+                expected,
+                actual as f64,
+                Tolerance::COUNT.allowed(expected),
+            );
+        }
+        check(
+            "demand",
+            self.demand,
+            actual.demand,
+            Tolerance::COUNT.allowed(self.demand),
+        );
+        check(
+            "nodes",
+            self.nodes as f64,
+            actual.nodes as f64,
+            Tolerance::EXACT.allowed(self.nodes as f64),
+        );
+        let dropped = self.dropped as f64;
+        check(
+            "dropped",
+            dropped,
+            actual.dropped as f64,
+            Tolerance::COUNT.allowed(dropped),
+        );
+        out
+    }
+}
+
+/// How far a value may stray from its golden.
+#[derive(Debug, Copy, Clone)]
+struct Tolerance {
+    /// the allowed difference as a fraction of the golden value
+    relative: f64,
+    /// the allowed difference on top of the relative one
+    absolute: f64,
+}
+
+impl Tolerance {
+    const MONEY: Tolerance = Tolerance {
+        relative: 0.05,
+        absolute: 1.,
+    };
+    const COUNT: Tolerance = Tolerance {
+        relative: 0.05,
+        absolute: 10.,
+    };
+    const EXACT: Tolerance = Tolerance {
+        relative: 0.,
+        absolute: 0.,
+    };
+
+    fn allowed(self, expected: f64) -> f64 {
+        expected.abs() * self.relative + self.absolute
+    }
+}
+
+/// Where the game is played:
+/// right on the engine, as the view does in a single thread,
+/// or through a [`Simulation`], as the view does with a web worker.
+trait Table {
+    /// The engine as seen by the player.
+    fn engine(&self) -> &GameEngine;
+
+    /// The world state as seen by the player.
+    fn state(&self) -> &WorldState;
+
+    /// Take an action, ignoring whether it failed.
+    fn act(&mut self, action: PlayerAction);
+
+    /// Advance the game to the given time.
+    fn advance(&mut self, time: Time);
+
+    /// Take a snapshot of the game as it really is.
+    fn checkpoint(&self) -> Checkpoint;
+}
+
+/// The game played right on the engine.
+struct Local {
+    engine: GameEngine,
+    state: WorldState,
+}
+
+impl Table for Local {
+    fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    fn state(&self) -> &WorldState {
+        &self.state
+    }
+
+    fn act(&mut self, action: PlayerAction) {
+        // failed actions are part of playing
+        let _ = self.engine.apply_action(&mut self.state, action);
+    }
+
+    fn advance(&mut self, time: Time) {
+        self.engine.update(&mut self.state, time);
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::of(&self.state)
+    }
+}
+
+/// The game simulated away from the view,
+/// with every message going through serialization.
+struct Remote {
+    simulation: Simulation,
+    /// the view's engine, following the simulated one
+    engine: GameEngine,
+    /// the view's world state
+    state: WorldState,
+    /// the simulated game time
+    time: Time,
+}
+
+impl Remote {
+    fn send(&mut self, input: SimulationInput) {
+        let input = serde_json::from_str(&serde_json::to_string(&input).unwrap()).unwrap();
+        for output in self.simulation.handle(input, 0) {
+            let output = serde_json::from_str(&serde_json::to_string(&output).unwrap()).unwrap();
+            if let SimulationOutput::Frame(frame) = output {
+                frame.apply(&mut self.engine, &mut self.state);
+            }
+        }
+    }
+}
+
+impl Table for Remote {
+    fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    fn state(&self) -> &WorldState {
+        &self.state
+    }
+
+    fn act(&mut self, action: PlayerAction) {
+        self.send(SimulationInput::Action(action));
+    }
+
+    fn advance(&mut self, time: Time) {
+        let elapsed = (time - self.time) / TIME_UNITS_PER_MILLISECOND as Time;
+        self.time = time;
+        self.send(SimulationInput::Tick {
+            elapsed: elapsed as u32,
+        });
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::of(self.simulation.state().unwrap())
+    }
+}
+
+/// The canned player: every second,
+/// they click for base ops,
+/// pay the electricity bill,
+/// use the first project card they can afford,
+/// and buy the cheapest hardware they can afford twice over.
+fn play_second(table: &mut impl Table) {
+    let amount = table.state().ops_per_click;
+    for _ in 0..3 {
+        table.act(PlayerAction::OpClick {
+            kind: ServiceKind::Base,
+            amount,
+        });
+    }
+    let state = table.state();
+    if state.electricity.total_due > Money::zero() && state.funds > state.electricity.total_due {
+        table.act(PlayerAction::PayElectricityBill);
+    }
+
+    let state = table.state();
+    let card = table
+        .engine()
+        .visible_cards()
+        .find(|card| state.can_afford(&state.card_cost(card)));
+    if let Some(card) = card {
+        table.act(PlayerAction::UseCard { id: card.id.into() });
+    }
+
+    let (engine, state) = (table.engine(), table.state());
+    let config = engine.config();
+    let mut purchases: Vec<(Money, PlayerAction)> = state
+        .nodes
+        .iter()
+        .flat_map(|node| {
+            let cpu = node
+                .next_cpu_upgrade_cost(config)
+                .map(|cost| (cost, PlayerAction::UpgradeCpu { node: node.id }));
+            let ram = node
+                .next_ram_upgrade_cost(config)
+                .map(|cost| (cost, PlayerAction::UpgradeRam { node: node.id }));
+            cpu.into_iter().chain(ram)
+        })
+        .collect();
+    if state.can_buy_nodes && !state.can_buy_racks {
+        purchases.push((config.bare_node_cost, PlayerAction::AddNode));
+    }
+    purchases.sort_by_key(|(cost, _)| *cost);
+    if let Some((cost, action)) = purchases.into_iter().next() {
+        if state.funds >= cost * 2 {
+            table.act(action);
+        }
+    }
+}
+
+/// Play the scenario from the given state
+/// and take a snapshot at every checkpoint.
+fn run(state: WorldState, seed: u64) -> Vec<Checkpoint> {
+    let mut engine = GameEngine::with_config_seeded(GameConfig::default(), seed);
+    engine.bootstrap_events(&state);
+    play(Local { engine, state })
+}
+
+/// Play the scenario from the given state
+/// with the game simulated away from the view,
+/// and take a snapshot at every checkpoint.
+fn run_remote(state: WorldState, seed: u64) -> Vec<Checkpoint> {
+    let mut remote = Remote {
+        simulation: Simulation::new(),
+        engine: GameEngine::new(),
+        state: WorldState::default(),
+        time: state.time,
+    };
+    remote.send(SimulationInput::Start {
+        state: Box::new(state),
+        seed: Some(seed),
+    });
+    play(remote)
+}
+
+fn play(mut table: impl Table) -> Vec<Checkpoint> {
+    let start = table.checkpoint();
+    let mut checkpoints = vec![];
+    let mut time = table.state().time;
+    for minutes in CHECKPOINT_MINUTES {
+        let end = minutes * TIME_UNITS_PER_MINUTE;
+        while time < end {
+            time += TIME_UNITS_PER_CYCLE as Time;
+            table.advance(time);
+            if time.is_multiple_of(TIME_UNITS_PER_SECOND) {
+                // bandwidth only becomes a concern at rack scale
+                let metrics = table.engine().metrics(table.state());
+                assert_eq!(metrics.dropped_by(DropCause::Bandwidth), 0);
+                play_second(&mut table);
+            }
+        }
+        checkpoints.push(table.checkpoint());
+    }
+    check_invariants(&start, &checkpoints);
+    checkpoints
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "src", "central", "golden"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{name}.json"))
+}
+
+/// Check what must hold in any scenario, whatever the balance:
+/// the totals never go down from one checkpoint to the next.
+fn check_invariants(start: &Checkpoint, checkpoints: &[Checkpoint]) {
+    let mut previous = start;
+    for checkpoint in checkpoints {
+        let minutes = checkpoint.minutes;
+        assert!(
+            checkpoint.earned >= previous.earned,
+            "money earned went down at {minutes} min"
+        );
+        assert!(
+            checkpoint.dropped >= previous.dropped,
+            "requests dropped went down at {minutes} min"
+        );
+        for (kind, (&ops, before)) in ServiceKind::ALL
+            .iter()
+            .zip(checkpoint.ops.iter().zip(previous.ops))
+        {
+            assert!(ops >= before, "{kind} ops went down at {minutes} min");
+        }
+        previous = checkpoint;
+    }
+}
+
+/// Compare the checkpoints of a scenario with its golden,
+/// or write the golden if asked to.
+fn check_golden(name: &str, checkpoints: &[Checkpoint]) {
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        let json = serde_json::to_string_pretty(checkpoints).unwrap();
+        std::fs::write(golden_path(name), json + "\n").unwrap();
+        return;
+    }
+    compare_golden(name, checkpoints);
+}
+
+/// Compare the checkpoints of a scenario with its golden.
+fn compare_golden(name: &str, checkpoints: &[Checkpoint]) {
+    let path = golden_path(name);
+    let json = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "could not read golden {} ({e}), regenerate it with {UPDATE_VAR}=1",
+            path.display()
+        )
+    });
+    let goldens: Vec<Checkpoint> = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        goldens.len(),
+        checkpoints.len(),
+        "checkpoints changed, regenerate the golden with {UPDATE_VAR}=1"
+    );
+    let diff: String = goldens
+        .iter()
+        .zip(checkpoints)
+        .map(|(golden, actual)| golden.diff(actual))
+        .collect();
+    assert!(
+        diff.is_empty(),
+        "scenario {name} is out of balance:\n{diff}\
+         if this is intended, regenerate the golden with {UPDATE_VAR}=1"
+    );
+}
+
+/// A new game, played from the very start.
+#[test]
+fn test_golden_new_game() {
+    let checkpoints = run(WorldState::default(), 905);
+    check_golden("new_game", &checkpoints);
+}
+
+/// A new game, simulated as in a web worker.
+#[test]
+fn test_golden_new_game_remote() {
+    let checkpoints = run_remote(WorldState::default(), 905);
+    compare_golden("new_game", &checkpoints);
+}
+
+/// A small business which just started buying more nodes.
+fn first_nodes() -> WorldState {
+    let mut state = WorldStateBuilder::new()
+        .funds(Money::dollars(3_000))
+        .earned(Money::dollars(8_000))
+        .demand(40.)
+        .service_unlocked(ServiceKind::Super)
+        .with_customer(ServiceKind::Base)
+        .with_customer(ServiceKind::Base)
+        .with_customer(ServiceKind::Super)
+        .with_nodes_fully_upgraded(1)
+        .cards_used(&["a0p", "a1", "a1p", "n1"])
+        .build();
+    state.can_buy_nodes = true;
+    state
+}
+
+#[test]
+fn test_golden_first_nodes() {
+    let checkpoints = run(first_nodes(), 905);
+    check_golden("first_nodes", &checkpoints);
+}
+
+#[test]
+fn test_golden_first_nodes_remote() {
+    let checkpoints = run_remote(first_nodes(), 905);
+    compare_golden("first_nodes", &checkpoints);
+}
+
+#[test]
+fn test_checkpoint_diff() {
+    let golden = Checkpoint {
+        minutes: 5,
+        funds: 100.,
+        earned: 1_000.,
+        ops: [2_000, 0, 0, 0, 0],
+        demand: 10.,
+        nodes: 2,
+        dropped: 0,
+    };
+    assert_eq!(golden.diff(&golden), "");
+
+    // small drifts are tolerated
+    let drifted = Checkpoint {
+        funds: 104.,
+        ops: [2_090, 0, 0, 0, 0],
+        dropped: 10,
+        ..golden.clone()
+    };
+    assert_eq!(golden.diff(&drifted), "");
+
+    let off = Checkpoint {
+        funds: 20.,
+        nodes: 3,
+        ..golden.clone()
+    };
+    assert_eq!(
+        golden.diff(&off),
+        "  at 5 min, funds: expected 100.00 \u{b1} 51.00, got 20.00\n  \
+         at 5 min, nodes: expected 2.00 \u{b1} 0.00, got 3.00\n"
+    );
+}
@@ -0,0 +1,193 @@
+//! Module for the histogram of request processing times,
+//! which shows the player how much faster requests get
+//! after upgrading the software or caching.
+//!
+//! Processing times are bucketed logarithmically,
+//! each bucket holding twice as long durations as the previous one.
+//! The histograms are kept by the engine for the current window,
+//! which starts over whenever the services or caching are upgraded,
+//! so that the window before the upgrade can be shown for comparison.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ServiceKind, Time};
+
+/// the number of buckets in a histogram
+pub const HISTOGRAM_BUCKETS: usize = 8;
+
+/// the upper bound of the fastest bucket, in time units
+const FASTEST_BUCKET_BOUND: u32 = 128;
+
+/// The bucket of the given per-request processing duration.
+pub fn bucket_of(duration: u32) -> usize {
+    let mut bucket = 0;
+    let mut bound = FASTEST_BUCKET_BOUND;
+    while duration >= bound && bucket < HISTOGRAM_BUCKETS - 1 {
+        bucket += 1;
+        bound *= 2;
+    }
+    bucket
+}
+
+/// The exclusive upper bound of the durations in the given bucket,
+/// or `None` for the last bucket.
+pub fn bucket_bound(bucket: usize) -> Option<Time> {
+    (bucket < HISTOGRAM_BUCKETS - 1).then(|| (FASTEST_BUCKET_BOUND as Time) << bucket)
+}
+
+/// A histogram of request processing times.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// the number of requests processed in each bucket
+    pub counts: [u64; HISTOGRAM_BUCKETS],
+    /// the number of those requests which hit the cache
+    pub cache_hits: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl Histogram {
+    /// Record `amount` requests which took `duration` each.
+    pub fn record(&mut self, duration: u32, amount: u32, cache_hit: bool) {
+        let bucket = bucket_of(duration);
+        self.counts[bucket] += amount as u64;
+        if cache_hit {
+            self.cache_hits[bucket] += amount as u64;
+        }
+    }
+
+    /// The total number of requests recorded.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    /// The share of requests in each bucket, between 0 and 1.
+    pub fn shares(&self) -> [f32; HISTOGRAM_BUCKETS] {
+        let total = self.total().max(1) as f32;
+        self.counts.map(|count| count as f32 / total)
+    }
+
+    /// The share of requests which hit the cache in each bucket,
+    /// relative to all requests.
+    pub fn cache_hit_shares(&self) -> [f32; HISTOGRAM_BUCKETS] {
+        let total = self.total().max(1) as f32;
+        self.cache_hits.map(|count| count as f32 / total)
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for bucket in 0..HISTOGRAM_BUCKETS {
+            self.counts[bucket] += other.counts[bucket];
+            self.cache_hits[bucket] += other.cache_hits[bucket];
+        }
+    }
+}
+
+/// The histograms of processing times of each service,
+/// for the current window and the one before.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingTimes {
+    current: [Histogram; ServiceKind::ALL.len()],
+    previous: Option<[Histogram; ServiceKind::ALL.len()]>,
+}
+
+impl ProcessingTimes {
+    /// Record `amount` requests to a service which took `duration` each.
+    pub fn record(&mut self, service: ServiceKind, duration: u32, amount: u32, cache_hit: bool) {
+        self.current[service.to_code() as usize].record(duration, amount, cache_hit);
+    }
+
+    /// Start a new window, keeping the current one for comparison.
+    ///
+    /// If nothing was recorded in the current window,
+    /// the previous window is kept instead.
+    pub fn rotate(&mut self) {
+        if self.current.iter().all(Histogram::is_empty) {
+            return;
+        }
+        self.previous = Some(std::mem::take(&mut self.current));
+    }
+
+    /// The histogram of a service in the current window.
+    pub fn current(&self, service: ServiceKind) -> &Histogram {
+        &self.current[service.to_code() as usize]
+    }
+
+    /// The histogram of a service in the previous window, if any.
+    pub fn previous(&self, service: ServiceKind) -> Option<&Histogram> {
+        self.previous
+            .as_ref()
+            .map(|previous| &previous[service.to_code() as usize])
+    }
+
+    /// The histogram of all services in the current window.
+    pub fn current_overall(&self) -> Histogram {
+        overall(&self.current)
+    }
+
+    /// The histogram of all services in the previous window, if any.
+    pub fn previous_overall(&self) -> Option<Histogram> {
+        self.previous.as_ref().map(overall)
+    }
+}
+
+fn overall(histograms: &[Histogram; ServiceKind::ALL.len()]) -> Histogram {
+    let mut total = Histogram::default();
+    for histogram in histograms {
+        total.merge(histogram);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bucket_bound, bucket_of, ProcessingTimes, HISTOGRAM_BUCKETS};
+    use crate::ServiceKind;
+
+    #[test]
+    fn test_bucket_of() {
+        assert_eq!(bucket_of(0), 0);
+        assert_eq!(bucket_of(127), 0);
+        assert_eq!(bucket_of(128), 1);
+        assert_eq!(bucket_of(255), 1);
+        assert_eq!(bucket_of(256), 2);
+        assert_eq!(bucket_of(5_750), 6);
+        assert_eq!(bucket_of(8_192), 7);
+        assert_eq!(bucket_of(u32::MAX), HISTOGRAM_BUCKETS - 1);
+
+        // every bucket ends where the next one starts
+        for bucket in 0..HISTOGRAM_BUCKETS - 1 {
+            let bound = bucket_bound(bucket).unwrap() as u32;
+            assert_eq!(bucket_of(bound - 1), bucket);
+            assert_eq!(bucket_of(bound), bucket + 1);
+        }
+        assert_eq!(bucket_bound(HISTOGRAM_BUCKETS - 1), None);
+    }
+
+    #[test]
+    fn test_rotate_windows() {
+        let mut times = ProcessingTimes::default();
+        times.record(ServiceKind::Base, 5_750, 3, false);
+        times.record(ServiceKind::Super, 287, 1, true);
+        assert_eq!(times.current(ServiceKind::Base).counts[6], 3);
+        assert_eq!(times.current(ServiceKind::Super).cache_hits[2], 1);
+        assert_eq!(times.current_overall().total(), 4);
+        assert_eq!(times.previous(ServiceKind::Base), None);
+
+        times.rotate();
+        assert!(times.current_overall().is_empty());
+        assert_eq!(times.previous(ServiceKind::Base).unwrap().counts[6], 3);
+        assert_eq!(times.previous_overall().unwrap().total(), 4);
+
+        // an empty window does not replace the previous one
+        times.rotate();
+        assert_eq!(times.previous_overall().unwrap().total(), 4);
+
+        times.record(ServiceKind::Base, 3_500, 1, false);
+        times.rotate();
+        let previous = times.previous_overall().unwrap();
+        assert_eq!(previous.total(), 1);
+        assert_eq!(previous.shares()[5], 1.);
+    }
+}
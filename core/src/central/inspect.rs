@@ -0,0 +1,54 @@
+//! Module for inspecting the internals of the game engine,
+//! as shown in the developer overlay.
+//!
+//! A [`DebugSnapshot`] is built on demand
+//! with [`GameEngine::debug_snapshot`](super::engine::GameEngine::debug_snapshot),
+//! and only copies the head of the event queue,
+//! so that it stays cheap to build on every tick.
+
+use crate::{Memory, ServiceKind, Time};
+
+use super::queue::RequestEvent;
+
+/// the number of upcoming events included in a snapshot
+pub const SNAPSHOT_EVENTS: usize = 10;
+
+/// A snapshot of the internals of the game engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugSnapshot {
+    /// the time at which the snapshot was taken
+    pub time: Time,
+    /// the number of events in the event queue
+    pub queue_len: usize,
+    /// the next events in the queue, in the order they will be processed
+    pub next_events: Vec<RequestEvent>,
+    /// the number of requests waiting for a node to route them
+    pub waiting_len: usize,
+    /// the number of requests which can wait to be routed
+    pub waiting_capacity: usize,
+    /// the load of each node
+    pub nodes: Vec<NodeSnapshot>,
+    /// the request rate of each customer per service after the price adjustment,
+    /// indexed by [`ServiceKind::to_code`]
+    /// (or `None` if the service is not public)
+    pub demand: [Option<f64>; ServiceKind::ALL.len()],
+    /// the latest engine warnings and when they happened, oldest first
+    pub warnings: Vec<(Time, String)>,
+    /// the number of times more memory was released from a node
+    /// than it had in use
+    pub accounting_anomalies: u64,
+}
+
+/// The load of a cloud node in a [`DebugSnapshot`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSnapshot {
+    pub id: u32,
+    /// the number of requests being processed
+    pub processing: u32,
+    pub num_cores: u32,
+    /// the memory reserved by requests in the node
+    pub ram_reserved: Memory,
+    pub ram_capacity: Memory,
+    /// the number of requests waiting in the node's queue
+    pub queued: usize,
+}
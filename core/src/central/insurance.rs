@@ -0,0 +1,128 @@
+//! Module for the infrastructure insurance.
+//!
+//! While insured, the player pays a premium with every electricity bill,
+//! and every bill period in which more than [`INSURANCE_CLAIM_THRESHOLD`]
+//! customer requests were dropped
+//! pays out part of the revenue estimated to be lost with them.
+//! A cancelled policy can only be taken again
+//! after [`INSURANCE_COOLDOWN`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, ServiceKind, Time};
+
+use super::engine::ELECTRICITY_BILL_PERIOD;
+
+/// the customer requests which must be dropped in a bill period
+/// for the policy to pay out
+pub const INSURANCE_CLAIM_THRESHOLD: u64 = 1_000;
+
+/// the part of the estimated lost revenue paid out, as a percentage
+pub const INSURANCE_COVERAGE_PERCENT: i64 = 80;
+
+/// how long after cancelling a policy until insurance can be taken again
+/// (two bill periods)
+pub const INSURANCE_COOLDOWN: Time = 2 * ELECTRICITY_BILL_PERIOD;
+
+/// An infrastructure insurance policy taken by the player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsurancePolicy {
+    /// the amount added to every electricity bill
+    pub premium: Money,
+    /// the time at which the policy was taken
+    pub since: Time,
+    /// the customer requests dropped in the current bill period,
+    /// for each service tier
+    #[serde(default)]
+    pub dropped: [u64; ServiceKind::ALL.len()],
+    /// the total amount paid out so far
+    #[serde(default)]
+    pub payouts: Money,
+    /// the number of bill periods which paid out
+    #[serde(default)]
+    pub claims: u32,
+}
+
+impl InsurancePolicy {
+    pub fn new(premium: Money, since: Time) -> Self {
+        Self {
+            premium,
+            since,
+            dropped: [0; ServiceKind::ALL.len()],
+            payouts: Money::zero(),
+            claims: 0,
+        }
+    }
+
+    /// Count customer requests of the given service
+    /// dropped in the current bill period.
+    pub fn count_dropped(&mut self, service: ServiceKind, amount: u64) {
+        self.dropped[service.to_code() as usize] += amount;
+    }
+
+    /// The customer requests dropped in the current bill period.
+    pub fn total_dropped(&self) -> u64 {
+        self.dropped.iter().sum()
+    }
+
+    /// Close the current bill period,
+    /// returning the payout if enough requests were dropped,
+    /// given the price of each service tier.
+    pub fn close_period(&mut self, prices: &[Money; ServiceKind::ALL.len()]) -> Option<Money> {
+        let dropped = std::mem::take(&mut self.dropped);
+        if dropped.iter().sum::<u64>() <= INSURANCE_CLAIM_THRESHOLD {
+            return None;
+        }
+        let lost: i64 = dropped
+            .iter()
+            .zip(prices)
+            .map(|(&dropped, price)| price.to_millicents() * dropped as i64)
+            .sum();
+        let payout = Money::millicents(lost * INSURANCE_COVERAGE_PERCENT / 100);
+        if payout <= Money::zero() {
+            return None;
+        }
+        self.payouts += payout;
+        self.claims += 1;
+        Some(payout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InsurancePolicy, INSURANCE_CLAIM_THRESHOLD};
+    use crate::{Money, ServiceKind};
+
+    #[test]
+    fn test_payout_on_drops() {
+        let prices = [
+            Money::millicents(10),
+            Money::millicents(100),
+            Money::zero(),
+            Money::zero(),
+            Money::zero(),
+        ];
+        let mut policy = InsurancePolicy::new(Money::dollars(25), 0);
+
+        // too few requests dropped
+        policy.count_dropped(ServiceKind::Base, INSURANCE_CLAIM_THRESHOLD);
+        assert_eq!(policy.close_period(&prices), None);
+        assert_eq!(policy.total_dropped(), 0);
+
+        // 80% of the revenue lost with the requests dropped
+        policy.count_dropped(ServiceKind::Base, 1_000);
+        policy.count_dropped(ServiceKind::Super, 500);
+        assert_eq!(
+            policy.close_period(&prices),
+            Some(Money::millicents(48_000))
+        );
+        assert_eq!(policy.total_dropped(), 0);
+        assert_eq!(policy.payouts, Money::millicents(48_000));
+        assert_eq!(policy.claims, 1);
+
+        // nothing to compensate for free services
+        policy.count_dropped(ServiceKind::Epic, 5_000);
+        assert_eq!(policy.close_period(&prices), None);
+        assert_eq!(policy.claims, 1);
+    }
+}
@@ -0,0 +1,248 @@
+//! Module for the interactions in which the player is asked to decide
+//! while the game goes on.
+//!
+//! A confirmation shows the numbers as they were when it opened
+//! (see [`CardQuote`]),
+//! including what a destructive card would change for good,
+//! so that they do not shift under the player's eyes,
+//! and the quote is checked against the live state once confirmed.
+//! While a decision such as a narrative event or a bailout is pending,
+//! actions spending the funds which it may change
+//! wait until it is made (see [`conflicts_with_decision`]).
+
+use crate::{Cost, Money, Ops, PlayerAction, ServiceKind, WorldState};
+
+use super::{
+    cards::{all::card_by_id, CardEffect, CardSpec},
+    engine::{GameEngine, RackConversion},
+};
+
+/// The cost of using a project card
+/// and what the player had available,
+/// as shown when asked to confirm it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardQuote {
+    /// the card's identifier
+    pub id: &'static str,
+    /// the cost of using the card
+    pub cost: Cost,
+    /// the available ops of each service
+    pub available: [Ops; ServiceKind::ALL.len()],
+    /// the available funds
+    pub funds: Money,
+    /// how the nodes would be turned into racks
+    /// (or `None` if the card leaves the nodes as they are)
+    pub conversion: Option<RackConversion>,
+    /// the number of requests in the nodes
+    /// which would be routed again
+    pub requests_rerouted: u64,
+}
+
+impl CardQuote {
+    /// Quote the given card in the given world state.
+    pub fn new(engine: &GameEngine, state: &WorldState, card: &CardSpec) -> Self {
+        let conversion = (card.effect == CardEffect::UnlockMultiDatacenters)
+            .then(|| RackConversion::of(&state.nodes, engine.config()));
+        let requests_rerouted = if conversion.is_some() {
+            engine.requests_in_flight(state)
+        } else {
+            0
+        };
+        Self {
+            id: card.id,
+            cost: state.card_cost(card),
+            available: ServiceKind::ALL.map(|kind| state.service_by_kind(kind).available),
+            funds: state.funds,
+            conversion,
+            requests_rerouted,
+        }
+    }
+
+    /// The ops of each service spent by the card,
+    /// with the ops which were available and those which would remain.
+    pub fn ops_left(&self) -> impl Iterator<Item = (ServiceKind, Ops, Ops)> + '_ {
+        self.cost
+            .ops()
+            .into_iter()
+            .filter(|&(_, ops)| ops > Ops(0))
+            .map(|(kind, ops)| {
+                let available = self.available[kind as usize];
+                (kind, available, available - ops)
+            })
+    }
+
+    /// Check the quote against the live world state,
+    /// once the player confirmed it.
+    ///
+    /// A new cost or node conversion makes a new quote,
+    /// but the requests in flight come and go too quickly to count.
+    pub fn revalidate(&self, engine: &GameEngine, state: &WorldState) -> Revalidation {
+        let Some(card) = card_by_id(self.id) else {
+            return Revalidation::Unaffordable;
+        };
+        let cost = state.card_cost(card);
+        let quote = Self::new(engine, state, card);
+        if !state.can_afford(&cost) {
+            Revalidation::Unaffordable
+        } else if cost != self.cost || quote.conversion != self.conversion {
+            Revalidation::Changed(quote)
+        } else {
+            Revalidation::Confirmed
+        }
+    }
+}
+
+/// The outcome of checking a confirmed quote against the live state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revalidation {
+    /// the card can be used as quoted
+    Confirmed,
+    /// the card can still be afforded,
+    /// but its cost changed, so the player should see the new quote
+    Changed(CardQuote),
+    /// the card can no longer be afforded
+    Unaffordable,
+}
+
+/// Whether the given action should wait
+/// until a pending decision is made,
+/// because it spends funds which the decision may change.
+///
+/// Actions which spend nothing, such as changing prices, never wait.
+pub fn conflicts_with_decision(action: &PlayerAction, state: &WorldState) -> bool {
+    match action {
+        PlayerAction::UseCard { id } => {
+            card_by_id(id).is_some_and(|card| state.card_cost(card).money > Money::zero())
+        }
+        PlayerAction::UpgradeCpu { .. }
+        | PlayerAction::UpgradeRam { .. }
+        | PlayerAction::AddNode
+        | PlayerAction::AddUpgradedNode
+        | PlayerAction::AddRack
+        | PlayerAction::PayElectricityBill
+        | PlayerAction::PrepayElectricity { .. }
+        | PlayerAction::SwitchTariff { .. }
+        | PlayerAction::Hire { .. }
+        | PlayerAction::Fire { .. }
+        | PlayerAction::ExtendTrial { .. }
+        | PlayerAction::RepayLoan => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{conflicts_with_decision, CardQuote, Revalidation};
+    use crate::{
+        central::{
+            cards::all::card_by_id,
+            engine::{CloudNode, GameEngine},
+            testing::WorldStateBuilder,
+        },
+        Money, Ops, PlayerAction, ServiceKind,
+    };
+
+    #[test]
+    fn test_revalidate_card_quote() {
+        // a caching card, discounted by research
+        let card = card_by_id("c0").unwrap();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(150))
+            .available_ops(ServiceKind::Base, Ops(1_000))
+            .build();
+        let engine = GameEngine::new();
+        let quote = CardQuote::new(&engine, &state, card);
+        assert_eq!(
+            quote.ops_left().collect::<Vec<_>>(),
+            [(ServiceKind::Base, Ops(1_000), Ops(740))]
+        );
+
+        // the numbers shown may move as long as the card stays affordable
+        state.service_by_kind_mut(ServiceKind::Base).available = Ops(900);
+        assert_eq!(quote.revalidate(&engine, &state), Revalidation::Confirmed);
+
+        // funds dropped below the cost before the player confirmed
+        state.funds = Money::dollars(99);
+        assert_eq!(
+            quote.revalidate(&engine, &state),
+            Revalidation::Unaffordable
+        );
+        state.funds = Money::dollars(150);
+
+        // so did the ops
+        state.service_by_kind_mut(ServiceKind::Base).available = Ops(100);
+        assert_eq!(
+            quote.revalidate(&engine, &state),
+            Revalidation::Unaffordable
+        );
+        state.service_by_kind_mut(ServiceKind::Base).available = Ops(1_000);
+
+        // research made the card cheaper in the meantime
+        state.research_progress = 50_000;
+        match quote.revalidate(&engine, &state) {
+            Revalidation::Changed(new_quote) => {
+                assert!(new_quote.cost.base_ops < quote.cost.base_ops);
+                assert_eq!(new_quote.funds, Money::dollars(150));
+            }
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn test_quote_rack_conversion() {
+        let engine = GameEngine::new();
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(100_000))
+            .available_ops(ServiceKind::Super, Ops(100_000))
+            .with_nodes_fully_upgraded(8)
+            .build();
+
+        // only the data center card turns nodes into racks
+        let quote = CardQuote::new(&engine, &state, card_by_id("c0").unwrap());
+        assert_eq!(quote.conversion, None);
+        let card = card_by_id("n5").unwrap();
+        assert!(card.is_destructive());
+        let quote = CardQuote::new(&engine, &state, card);
+        let conversion = quote.conversion.unwrap();
+        assert_eq!((conversion.nodes, conversion.racks), (8, 2));
+        assert_eq!(quote.revalidate(&engine, &state), Revalidation::Confirmed);
+
+        // buying a node in the meantime changes what the player gets
+        state.nodes.push(CloudNode::new(8));
+        match quote.revalidate(&engine, &state) {
+            Revalidation::Changed(new_quote) => {
+                let conversion = new_quote.conversion.unwrap();
+                assert_eq!((conversion.nodes, conversion.racks), (9, 2));
+                assert!(conversion.credit > Money::zero());
+            }
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn test_conflicts_with_decision() {
+        let state = WorldStateBuilder::new().build();
+        // cards spending funds wait, cards spending only ops do not
+        assert!(conflicts_with_decision(
+            &PlayerAction::UseCard { id: "c0".into() },
+            &state
+        ));
+        assert!(!conflicts_with_decision(
+            &PlayerAction::UseCard { id: "b0".into() },
+            &state
+        ));
+        assert!(conflicts_with_decision(&PlayerAction::AddNode, &state));
+        assert!(!conflicts_with_decision(
+            &PlayerAction::ChangePrice {
+                kind: ServiceKind::Base,
+                new_price: Money::cents(1)
+            },
+            &state
+        ));
+        // answering the decision itself never waits
+        assert!(!conflicts_with_decision(
+            &PlayerAction::AcceptBailout,
+            &state
+        ));
+    }
+}
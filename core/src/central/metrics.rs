@@ -0,0 +1,127 @@
+//! Module for point-in-time metrics of the game,
+//! gathered from the world state and the engine.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, Ops, ServiceKind, Time};
+
+use super::{
+    eta::ResourceRates,
+    histogram::ProcessingTimes,
+    score::ScoreBreakdown,
+    state::{TrafficClass, TrafficCounters},
+};
+
+/// A snapshot of the game's business and request metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsSnapshot {
+    /// the time at which the snapshot was taken
+    pub time: Time,
+    /// the player's available funds
+    pub funds: Money,
+    /// the total money earned
+    pub earned: Money,
+    /// the total money spent
+    pub spent: Money,
+    /// the base service demand
+    pub demand: f64,
+    /// the phase of the daily demand cycle,
+    /// from 0 (dawn) to 1
+    pub day_phase: f32,
+    /// the total number of requests dropped
+    pub requests_dropped: u64,
+    /// the total number of bad requests processed
+    pub requests_failed: u64,
+    /// the total number of bad requests caught while processing
+    pub attacks_mitigated: u64,
+    /// the recent drop rate, excluding bad traffic
+    pub drop_rate: f32,
+    /// the recent failure rate
+    pub failure_rate: f32,
+    /// the recent share of requests served from the cache
+    pub cache_hit_rate: f32,
+    /// how many times faster requests were recently processed
+    /// thanks to the cache
+    pub cache_speedup: f32,
+    /// the recent average time added to each request
+    /// for the distance it was routed over
+    pub routing_latency: f32,
+    /// the number of requests recently dropped by cause,
+    /// excluding bad traffic
+    pub drop_causes: [u64; DropCause::ALL.len()],
+    /// the share of the network bandwidth recently used
+    pub bandwidth_load: f32,
+    /// the ids of the project cards visible to the player
+    pub visible_cards: Vec<&'static str>,
+    /// the histograms of request processing times
+    pub processing_times: ProcessingTimes,
+    /// the requests fulfilled and dropped
+    /// for each traffic class
+    pub traffic: [TrafficCounters; TrafficClass::ALL.len()],
+    /// the number of op button clicks in the last minute
+    pub clicks_per_minute: u32,
+    /// the total number of op button clicks
+    pub total_player_clicks: u64,
+    /// the ops of each service obtained at the op exchange
+    pub ops_exchanged: [Ops; ServiceKind::ALL.len()],
+    /// the total purchase value of the cloud nodes
+    pub hardware_value: Money,
+    /// the funds and hardware value minus debts
+    pub net_worth: Money,
+    /// the estimated wait in the node queues of each service
+    /// as a share of the wait at which customers start leaving
+    pub queue_pressure: [f32; ServiceKind::ALL.len()],
+    /// the components of the score of the game
+    pub score: ScoreBreakdown,
+    /// the rates at which money and ops have recently been coming in
+    pub resource_rates: ResourceRates,
+}
+
+impl MetricsSnapshot {
+    /// The request counts of the given class of traffic.
+    pub fn traffic(&self, class: TrafficClass) -> TrafficCounters {
+        self.traffic[class as usize]
+    }
+
+    /// The number of requests recently dropped for the given cause.
+    pub fn dropped_by(&self, cause: DropCause) -> u64 {
+        self.drop_causes[cause as usize]
+    }
+}
+
+/// The reason why a request was dropped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DropCause {
+    /// no processing capacity to take the request in time
+    Cpu = 0,
+    /// not enough memory for the request
+    Ram = 1,
+    /// the routing queue was full
+    Queue = 2,
+    /// no network bandwidth left to serve the request
+    Bandwidth = 3,
+}
+
+impl DropCause {
+    pub const ALL: [DropCause; 4] = [
+        DropCause::Cpu,
+        DropCause::Ram,
+        DropCause::Queue,
+        DropCause::Bandwidth,
+    ];
+}
+
+/// The outcome of the ops requested by the player for one service.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerOps {
+    /// the number of player ops fulfilled
+    pub fulfilled: u32,
+    /// the number of player ops dropped for lack of capacity
+    pub dropped: u32,
+}
+
+impl PlayerOps {
+    pub fn is_empty(&self) -> bool {
+        self.fulfilled == 0 && self.dropped == 0
+    }
+}
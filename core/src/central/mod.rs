@@ -0,0 +1,43 @@
+pub mod action;
+pub mod audio_event;
+pub mod away;
+pub mod bandwidth;
+pub mod cards;
+pub mod clicks;
+pub mod cloud_user;
+pub mod combo;
+pub mod config;
+mod console;
+pub mod daily;
+pub mod digest;
+pub mod engine;
+pub mod eta;
+pub mod exchange;
+#[cfg(feature = "debug-tools")]
+pub mod exposition;
+#[cfg(test)]
+mod golden;
+pub mod histogram;
+#[cfg(feature = "debug-tools")]
+pub mod inspect;
+pub mod insurance;
+pub mod interaction;
+pub mod metrics;
+pub mod narrative;
+pub mod notification;
+pub mod persistence;
+pub mod preview;
+pub mod queue;
+pub mod recommend;
+pub mod report;
+pub mod revenue;
+pub mod save;
+pub mod score;
+pub mod simulation;
+pub mod staff;
+pub mod state;
+pub mod streak;
+pub mod stuff;
+pub mod tariff;
+pub mod testing;
+pub mod tiers;
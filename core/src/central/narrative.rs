@@ -0,0 +1,374 @@
+//! Module for the narrative events,
+//! small stories in which the player makes a choice with consequences.
+//!
+//! Every now and then (see [`NARRATIVE_ROLL_PERIOD`]),
+//! the engine rolls for one of the events in [`ALL_EVENTS`]
+//! whose condition holds and which has not happened yet.
+//! Only one event can be pending at a time,
+//! and none fires within [`NARRATIVE_MIN_INTERVAL`] of the last one.
+//! The game goes on while an event awaits an answer,
+//! but an event left unanswered for [`NARRATIVE_TIMEOUT`]
+//! resolves to its first choice, which is always the safe one.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n, Money, Ops, ServiceKind, Time, WorldState, TIME_UNITS_PER_MINUTE};
+
+use super::cards::{CardCondition, CardEffect};
+
+/// the period at which to roll for a new event (1 minute)
+pub const NARRATIVE_ROLL_PERIOD: Time = TIME_UNITS_PER_MINUTE;
+
+/// the chance of an event firing on each roll
+pub const NARRATIVE_EVENT_CHANCE: f32 = 0.2;
+
+/// the minimum time between two events (10 minutes)
+pub const NARRATIVE_MIN_INTERVAL: Time = 10 * TIME_UNITS_PER_MINUTE;
+
+/// the time after which an unanswered event
+/// resolves to the safe choice (2 minutes)
+pub const NARRATIVE_TIMEOUT: Time = 2 * TIME_UNITS_PER_MINUTE;
+
+/// the index of the choice made when the player does not answer
+pub const SAFE_CHOICE: usize = 0;
+
+/// A narrative event and the choices it offers.
+#[derive(Debug)]
+pub struct NarrativeEvent {
+    /// the unique identifier as a small static string
+    pub id: &'static str,
+    /// the translation key of the event's story
+    pub text_key: &'static str,
+    /// the condition for the event to happen
+    pub condition: CardCondition,
+    /// the choices offered to the player,
+    /// the first of which is the safe one
+    pub choices: [NarrativeChoice; 2],
+}
+
+impl NarrativeEvent {
+    /// The event's story in the locale in use.
+    pub fn text(&self) -> &'static str {
+        i18n::translate(self.text_key)
+    }
+}
+
+/// A choice in a narrative event.
+#[derive(Debug)]
+pub struct NarrativeChoice {
+    /// the translation key of the choice's label
+    pub label_key: &'static str,
+    /// the consequences of the choice, applied in order
+    pub effects: &'static [CardEffect],
+}
+
+impl NarrativeChoice {
+    /// The choice's label in the locale in use.
+    pub fn label(&self) -> &'static str {
+        i18n::translate(self.label_key)
+    }
+}
+
+/// All narrative events in the game.
+///
+/// They _must_ be inserted in id ascending order.
+pub static ALL_EVENTS: &[NarrativeEvent] = &[
+    NarrativeEvent {
+        id: "blogger",
+        text_key: "event.blogger.text",
+        condition: CardCondition::Earned(Money::dollars(20)),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.blogger.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.blogger.bold",
+                effects: &[CardEffect::AddPublicityRate(16., 0.)],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "cat",
+        text_key: "event.cat.text",
+        condition: CardCondition::TotalCloudNodes(2),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.cat.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.cat.bold",
+                effects: &[
+                    CardEffect::Penalty(Money::dollars(5)),
+                    CardEffect::AddPublicityRate(8., 0.),
+                ],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "conference",
+        text_key: "event.conference.text",
+        condition: CardCondition::Earned(Money::dollars(5_000)),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.conference.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.conference.bold",
+                effects: &[
+                    CardEffect::Penalty(Money::dollars(800)),
+                    CardEffect::AddPublicityRate(300., 2.),
+                ],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "hackathon",
+        text_key: "event.hackathon.text",
+        condition: CardCondition::Earned(Money::dollars(500)),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.hackathon.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.hackathon.bold",
+                effects: &[
+                    CardEffect::Penalty(Money::dollars(150)),
+                    CardEffect::AddPublicityRate(40., 0.5),
+                ],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "intern",
+        text_key: "event.intern.text",
+        condition: CardCondition::TotalBaseOps(Ops(20_000)),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.intern.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.intern.bold",
+                effects: &[
+                    CardEffect::Penalty(Money::dollars(100)),
+                    CardEffect::AddResearchers(ServiceKind::Base),
+                ],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "investor",
+        text_key: "event.investor.text",
+        condition: CardCondition::Funds(Money::dollars(2_000)),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.investor.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.investor.bold",
+                effects: &[
+                    CardEffect::AddFunds(Money::dollars(1_000)),
+                    CardEffect::SignSla {
+                        service: ServiceKind::Base,
+                        bonus: Money::millicents(0),
+                        penalty: Money::dollars(100),
+                    },
+                ],
+            },
+        ],
+    },
+    NarrativeEvent {
+        id: "outage_rumor",
+        text_key: "event.outage_rumor.text",
+        condition: CardCondition::RequestsDropped(2_000),
+        choices: [
+            NarrativeChoice {
+                label_key: "event.outage_rumor.safe",
+                effects: &[CardEffect::Nothing],
+            },
+            NarrativeChoice {
+                label_key: "event.outage_rumor.bold",
+                effects: &[
+                    CardEffect::Penalty(Money::dollars(50)),
+                    CardEffect::AddPublicityRate(40., 0.25),
+                ],
+            },
+        ],
+    },
+];
+
+/// Find a narrative event by its identifier.
+pub fn event_by_id(id: &str) -> Option<&'static NarrativeEvent> {
+    ALL_EVENTS
+        .binary_search_by(|event| event.id.cmp(id))
+        .ok()
+        .map(|index| &ALL_EVENTS[index])
+}
+
+/// The events which could happen next in the given world state:
+/// those whose condition holds and which have not happened yet.
+pub fn eligible_events(state: &WorldState) -> Vec<&'static NarrativeEvent> {
+    ALL_EVENTS
+        .iter()
+        .filter(|event| {
+            !state.narrative.has_happened(event.id) && event.condition.should_appear(state)
+        })
+        .collect()
+}
+
+/// An event awaiting the player's answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingEvent {
+    /// the event's identifier
+    pub id: Cow<'static, str>,
+    /// the time at which the event fired
+    pub since: Time,
+}
+
+impl PendingEvent {
+    /// The specification of the pending event.
+    pub fn event(&self) -> Option<&'static NarrativeEvent> {
+        event_by_id(&self.id)
+    }
+
+    /// Whether the player took too long to answer at the given time.
+    pub fn is_timed_out(&self, time: Time) -> bool {
+        time >= self.since + NARRATIVE_TIMEOUT
+    }
+}
+
+/// The narrative events which happened so far.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NarrativeLog {
+    /// the event awaiting the player's answer, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending: Option<PendingEvent>,
+    /// the identifiers of the resolved events, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resolved: Vec<Cow<'static, str>>,
+    /// the time at which the last event fired
+    /// (or 0 if none did)
+    #[serde(default)]
+    last_fired: Time,
+}
+
+impl NarrativeLog {
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_none() && self.resolved.is_empty()
+    }
+
+    /// The event awaiting the player's answer, if any.
+    pub fn pending(&self) -> Option<&PendingEvent> {
+        self.pending.as_ref()
+    }
+
+    /// Whether the event with the given identifier
+    /// is pending or already resolved.
+    pub fn has_happened(&self, id: &str) -> bool {
+        self.pending
+            .as_ref()
+            .is_some_and(|pending| pending.id == id)
+            || self
+                .resolved
+                .binary_search_by(|resolved| resolved.as_ref().cmp(id))
+                .is_ok()
+    }
+
+    /// Whether a new event may fire at the given time.
+    pub fn can_fire(&self, time: Time) -> bool {
+        self.pending.is_none() && time >= self.last_fired + NARRATIVE_MIN_INTERVAL
+    }
+
+    /// Make the given event pending.
+    pub fn fire(&mut self, id: &'static str, time: Time) {
+        self.pending = Some(PendingEvent {
+            id: id.into(),
+            since: time,
+        });
+        self.last_fired = time;
+    }
+
+    /// Mark the pending event as resolved
+    /// if it has the given identifier.
+    ///
+    /// Returns whether it did.
+    pub fn resolve(&mut self, id: &str) -> bool {
+        match self.pending.take() {
+            Some(pending) if pending.id == id => {
+                if let Err(index) = self.resolved.binary_search(&pending.id) {
+                    self.resolved.insert(index, pending.id);
+                }
+                true
+            }
+            pending => {
+                self.pending = pending;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eligible_events, event_by_id, NarrativeLog, ALL_EVENTS, NARRATIVE_MIN_INTERVAL};
+    use crate::{central::testing::WorldStateBuilder, Money};
+
+    #[test]
+    fn test_events_in_id_order() {
+        for pair in ALL_EVENTS.windows(2) {
+            assert!(
+                pair[0].id < pair[1].id,
+                "{} should come after {}",
+                pair[0].id,
+                pair[1].id
+            );
+        }
+        for event in ALL_EVENTS {
+            assert_eq!(event_by_id(event.id).map(|e| e.id), Some(event.id));
+        }
+    }
+
+    #[test]
+    fn test_event_conditions() {
+        let state = WorldStateBuilder::new().build();
+        assert!(eligible_events(&state).is_empty());
+
+        let mut state = WorldStateBuilder::new().earned(Money::dollars(600)).build();
+        let eligible: Vec<_> = eligible_events(&state).iter().map(|e| e.id).collect();
+        assert_eq!(eligible, ["blogger", "hackathon"]);
+
+        // events which happened are no longer eligible
+        state.narrative.fire("blogger", 0);
+        let eligible: Vec<_> = eligible_events(&state).iter().map(|e| e.id).collect();
+        assert_eq!(eligible, ["hackathon"]);
+        assert!(state.narrative.resolve("blogger"));
+        let eligible: Vec<_> = eligible_events(&state).iter().map(|e| e.id).collect();
+        assert_eq!(eligible, ["hackathon"]);
+    }
+
+    #[test]
+    fn test_one_event_at_a_time() {
+        let mut log = NarrativeLog::default();
+        assert!(!log.can_fire(0));
+        assert!(log.can_fire(NARRATIVE_MIN_INTERVAL));
+
+        log.fire("cat", NARRATIVE_MIN_INTERVAL);
+        assert!(!log.can_fire(NARRATIVE_MIN_INTERVAL * 3));
+        // only the pending event can be resolved
+        assert!(!log.resolve("blogger"));
+        assert!(log.resolve("cat"));
+        assert!(!log.resolve("cat"));
+        assert!(log.pending().is_none());
+        assert!(log.has_happened("cat"));
+
+        assert!(!log.can_fire(NARRATIVE_MIN_INTERVAL * 2 - 1));
+        assert!(log.can_fire(NARRATIVE_MIN_INTERVAL * 2));
+    }
+}
@@ -0,0 +1,57 @@
+//! Module for the notification log,
+//! where the engine announces noteworthy happenings to the player.
+
+use std::{borrow::Cow, collections::VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Time;
+
+/// The maximum number of notifications kept in the log
+pub const NOTIFICATION_LOG_CAPACITY: usize = 50;
+
+/// A message for the player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    /// the time at which the notification was issued
+    pub time: Time,
+    /// the message to show
+    pub message: Cow<'static, str>,
+}
+
+/// A bounded log of notifications, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationLog {
+    entries: VecDeque<Notification>,
+}
+
+impl NotificationLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a notification,
+    /// discarding the oldest one if the log is full.
+    pub fn push(&mut self, time: Time, message: impl Into<Cow<'static, str>>) {
+        if self.entries.len() >= NOTIFICATION_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Notification {
+            time,
+            message: message.into(),
+        });
+    }
+
+    /// Iterate over the most recent notifications, newest first.
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &Notification> {
+        self.entries.iter().rev().take(count)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,824 @@
+//! Module for keeping track of whether the game can be saved,
+//! so that autosaves stop hammering a storage that keeps failing
+//! (e.g. site data cleared or storage disabled mid-session).
+//!
+//! After [`MAX_SAVE_FAILURES`] failures in a row,
+//! storage is deemed unavailable and autosaves are suppressed,
+//! except for one probe every [`STORAGE_PROBE_PERIOD`]
+//! to recover automatically once storage works again.
+//!
+//! The save is also backed up every [`BACKUP_PERIOD`] autosaves
+//! and before destructive operations (see [`back_up`]),
+//! keeping the last [`MAX_BACKUPS`] copies
+//! as long as they fit in [`BACKUP_STORAGE_BUDGET`].
+//!
+//! Games are saved and loaded through [`Persistence`],
+//! on top of whatever storage the front-end provides
+//! (see [`SaveStorage`]).
+//! A save which cannot be read as a valid game (see [`read_save`])
+//! is moved aside to its slot's corrupt key
+//! instead of being loaded or lost.
+
+use std::fmt;
+
+use super::{
+    save::{decode_save, write_save},
+    state::{DAILY_STORAGE_KEY_NAME, LOCAL_STORAGE_KEY_NAME},
+};
+use crate::{t, Difficulty, Money, Time, WorldState};
+
+/// the number of failed saves in a row
+/// after which storage is deemed unavailable
+pub const MAX_SAVE_FAILURES: u32 = 3;
+
+/// the time between attempts to save
+/// while storage is unavailable (5 minutes)
+pub const STORAGE_PROBE_PERIOD: Time = 3_000_000;
+
+/// the number of successful autosaves between backups
+pub const BACKUP_PERIOD: u32 = 10;
+
+/// the number of backups kept, newest first
+pub const MAX_BACKUPS: usize = 2;
+
+/// the total size of the save and its backups
+/// above which no more backups are made
+/// (browsers allow about 5 million characters per site)
+pub const BACKUP_STORAGE_BUDGET: usize = 3_000_000;
+
+/// The health of the game's storage.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum StorageHealth {
+    /// the last save went through (or none was attempted yet)
+    #[default]
+    Healthy,
+    /// the last saves failed this many times in a row
+    Failing(u32),
+    /// saving failed too many times,
+    /// only probing occasionally
+    Unavailable,
+}
+
+/// Tracks the outcome of saving the game
+/// and decides whether to attempt it at all.
+#[derive(Debug, Default, Clone)]
+pub struct StorageMonitor {
+    health: StorageHealth,
+    /// the time of the next attempt while unavailable
+    next_probe: Time,
+    /// the number of successful autosaves so far
+    saves: u32,
+}
+
+impl StorageMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn health(&self) -> StorageHealth {
+        self.health
+    }
+
+    /// The message to show the player about saving, if any.
+    pub fn banner(&self) -> Option<&'static str> {
+        match self.health {
+            StorageHealth::Healthy => None,
+            StorageHealth::Failing(_) => Some(t!("storage.failing")),
+            StorageHealth::Unavailable => Some(t!("storage.unavailable")),
+        }
+    }
+
+    /// Whether a save should be attempted at the given time.
+    pub fn should_attempt(&self, time: Time) -> bool {
+        self.health != StorageHealth::Unavailable || time >= self.next_probe
+    }
+
+    /// Save the game with the given function,
+    /// unless storage is unavailable and it is not yet time to probe it.
+    ///
+    /// Returns `None` if the attempt was suppressed.
+    pub fn autosave<E>(
+        &mut self,
+        time: Time,
+        save: impl FnOnce() -> Result<(), E>,
+    ) -> Option<Result<(), E>> {
+        if !self.should_attempt(time) {
+            return None;
+        }
+        let result = save();
+        match result {
+            Ok(()) => {
+                self.health = StorageHealth::Healthy;
+                self.saves += 1;
+            }
+            Err(_) => self.record_failure(time),
+        }
+        Some(result)
+    }
+
+    /// Whether the save should be backed up before the next autosave,
+    /// once every [`BACKUP_PERIOD`] successful autosaves
+    /// while storage is healthy.
+    pub fn backup_due(&self) -> bool {
+        self.health == StorageHealth::Healthy && self.saves % BACKUP_PERIOD == BACKUP_PERIOD - 1
+    }
+
+    fn record_failure(&mut self, time: Time) {
+        self.health = match self.health {
+            StorageHealth::Healthy => StorageHealth::Failing(1),
+            StorageHealth::Failing(failures) if failures + 1 < MAX_SAVE_FAILURES => {
+                StorageHealth::Failing(failures + 1)
+            }
+            StorageHealth::Failing(_) | StorageHealth::Unavailable => StorageHealth::Unavailable,
+        };
+        if self.health == StorageHealth::Unavailable {
+            self.next_probe = time + STORAGE_PROBE_PERIOD;
+        }
+    }
+}
+
+/// A key-value storage of text, such as the browser's local storage.
+pub trait SaveStorage {
+    type Error;
+
+    fn get(&self, key: &str) -> Result<Option<String>, Self::Error>;
+
+    fn set(&self, key: &str, value: &str) -> Result<(), Self::Error>;
+
+    fn remove(&self, key: &str) -> Result<(), Self::Error>;
+}
+
+/// Where a game is saved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SaveSlot {
+    /// the main game
+    Main,
+    /// the daily challenge,
+    /// kept apart so that it never overwrites the main game
+    Daily,
+}
+
+impl SaveSlot {
+    /// The slot which the given game is saved to.
+    pub fn of(state: &WorldState) -> Self {
+        if state.daily.is_some() {
+            SaveSlot::Daily
+        } else {
+            SaveSlot::Main
+        }
+    }
+
+    /// The storage key of the slot.
+    pub fn key(self) -> &'static str {
+        match self {
+            SaveSlot::Main => LOCAL_STORAGE_KEY_NAME,
+            SaveSlot::Daily => DAILY_STORAGE_KEY_NAME,
+        }
+    }
+
+    /// The storage key where a corrupted save in the slot is moved to,
+    /// so that it can still be looked into.
+    pub fn corrupt_key(self) -> String {
+        format!("{}_corrupt", self.key())
+    }
+}
+
+/// Why a saved game could not be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError<E> {
+    /// the storage could not be accessed
+    Storage(E),
+    /// the save is not a valid game,
+    /// for the given reason
+    Corrupt(String),
+}
+
+impl<E: fmt::Debug> fmt::Display for LoadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Storage(e) => write!(f, "Could not access storage: {e:?}"),
+            LoadError::Corrupt(reason) => write!(f, "Corrupted save: {reason}"),
+        }
+    }
+}
+
+/// Read the game state in the given save data,
+/// as written by [`write_save`].
+///
+/// Besides being well formed,
+/// the game must hold the basic invariants of the game
+/// (see [`check_save`]).
+pub fn read_save(data: &str) -> Result<WorldState, String> {
+    let json = decode_save(data)?;
+    let mut state: WorldState = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    check_save(&state)?;
+    state.purge_blocked_attackers();
+    Ok(state)
+}
+
+/// Check that a loaded game holds the basic invariants of the game,
+/// which any game saved by it does.
+pub fn check_save(state: &WorldState) -> Result<(), String> {
+    if state.nodes.is_empty() {
+        return Err("There are no cloud nodes".to_string());
+    }
+    if let Some((index, node)) = state
+        .nodes
+        .iter()
+        .enumerate()
+        .find(|(index, node)| node.id as usize != *index)
+    {
+        return Err(format!("Cloud node #{index} has ID {}", node.id));
+    }
+
+    let mut lower_unlocked = true;
+    for (kind, service) in state.services() {
+        if service.state.is_unlocked() && !lower_unlocked {
+            return Err(format!("{kind} is unlocked before a lower tier"));
+        }
+        lower_unlocked = service.state.is_unlocked();
+    }
+    if let Some(spec) = state
+        .user_specs
+        .iter()
+        .find(|spec| !state.service_by_kind(spec.service).state.is_unlocked())
+    {
+        return Err(format!(
+            "User {} is of locked service {}",
+            spec.id, spec.service
+        ));
+    }
+
+    if let Some(used) = state.cards_used.iter().find(|used| used.time > state.time) {
+        return Err(format!(
+            "Card {} was used at {}, after the game time {}",
+            used.id, used.time, state.time
+        ));
+    }
+    Ok(())
+}
+
+/// Saving and loading the game.
+///
+/// Every [`SaveStorage`] whose errors can be made from a message
+/// saves games this way.
+pub trait Persistence {
+    type Error;
+
+    /// Load the game in the given slot.
+    ///
+    /// Returns `Ok(None)` if there is no game save.
+    /// A save which cannot be read (see [`read_save`])
+    /// is moved to the slot's [corrupt key](SaveSlot::corrupt_key)
+    /// and reported as [`LoadError::Corrupt`].
+    fn load_game(&self, slot: SaveSlot) -> Result<Option<WorldState>, LoadError<Self::Error>>;
+
+    /// Checks whether there is a game saved in the given slot.
+    fn has_saved_game(&self, slot: SaveSlot) -> Result<bool, Self::Error>;
+
+    /// Write a serialized world state (see [`WorldState::to_save_json`])
+    /// to the given slot.
+    ///
+    /// The save is compressed if it is too big
+    /// or if the plain save does not fit in the storage quota.
+    fn store_save_json(&self, json: &str, slot: SaveSlot) -> Result<(), Self::Error>;
+
+    /// Save the world state to its slot,
+    /// recording the wall-clock time of the save
+    /// in milliseconds since the Unix epoch.
+    fn save_game(&self, state: &mut WorldState, now: u64) -> Result<(), Self::Error>;
+}
+
+impl<S> Persistence for S
+where
+    S: SaveStorage,
+    S::Error: From<String>,
+{
+    type Error = S::Error;
+
+    fn load_game(&self, slot: SaveSlot) -> Result<Option<WorldState>, LoadError<S::Error>> {
+        let Some(data) = self.get(slot.key()).map_err(LoadError::Storage)? else {
+            return Ok(None);
+        };
+        match read_save(&data) {
+            Ok(state) => {
+                log::info!("Saved game loaded successfully");
+                Ok(Some(state))
+            }
+            Err(reason) => {
+                log::error!("Saved game is corrupted: {reason}");
+                // keep the save where it is if it cannot be moved
+                let moved = self
+                    .set(&slot.corrupt_key(), &data)
+                    .and_then(|_| self.remove(slot.key()));
+                if moved.is_err() {
+                    log::warn!("Could not move the corrupted save aside");
+                }
+                Err(LoadError::Corrupt(reason))
+            }
+        }
+    }
+
+    fn has_saved_game(&self, slot: SaveSlot) -> Result<bool, S::Error> {
+        Ok(self.get(slot.key())?.is_some())
+    }
+
+    fn store_save_json(&self, json: &str, slot: SaveSlot) -> Result<(), S::Error> {
+        write_save(json, |data| self.set(slot.key(), data))?;
+        log::info!("Game saved");
+        Ok(())
+    }
+
+    fn save_game(&self, state: &mut WorldState, now: u64) -> Result<(), S::Error> {
+        state.saved_at = now;
+        let json = state
+            .to_save_json()
+            .map_err(|e| S::Error::from(e.to_string()))?;
+        self.store_save_json(&json, SaveSlot::of(state))
+    }
+}
+
+/// The storage key of the backup in the given slot (0 being the newest).
+fn backup_key(slot: usize) -> String {
+    format!("{LOCAL_STORAGE_KEY_NAME}_backup{slot}")
+}
+
+/// A copy of the saved game from an earlier time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    /// the slot of the backup, 0 being the newest
+    pub slot: usize,
+    /// when the backup was made, in milliseconds since the Unix epoch
+    pub backed_up_at: u64,
+    /// the save data, as written by [`write_save`](super::save::write_save)
+    pub data: String,
+}
+
+impl Backup {
+    fn parse(slot: usize, value: &str) -> Option<Self> {
+        let (backed_up_at, data) = value.split_once(';')?;
+        Some(Backup {
+            slot,
+            backed_up_at: backed_up_at.parse().ok()?,
+            data: data.to_string(),
+        })
+    }
+
+    /// Load the game state in the backup,
+    /// or `None` if it cannot be read.
+    pub fn load(&self) -> Option<WorldState> {
+        read_save(&self.data).ok()
+    }
+}
+
+/// A short description of a saved game,
+/// for the player to tell saves apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSummary {
+    pub company_name: String,
+    pub difficulty: Difficulty,
+    /// the time played, in seconds
+    pub playtime: u64,
+    pub earned: Money,
+    pub net_worth: Money,
+}
+
+impl SaveSummary {
+    pub fn of(state: &WorldState) -> Self {
+        SaveSummary {
+            company_name: state.company_name.clone(),
+            difficulty: state.difficulty,
+            playtime: state.time / 10_000,
+            earned: state.earned,
+            net_worth: state.net_worth(),
+        }
+    }
+}
+
+/// Copy the current save to the newest backup slot,
+/// shifting older backups along and dropping the oldest one.
+///
+/// Called every few autosaves (see [`StorageMonitor::backup_due`])
+/// and before anything which replaces or wipes the save.
+/// Returns whether a backup was made:
+/// there is nothing to back up without a save,
+/// and no backup is made if it would take the save and its backups
+/// over [`BACKUP_STORAGE_BUDGET`].
+pub fn back_up<S: SaveStorage>(storage: &S, now: u64) -> Result<bool, S::Error> {
+    let Some(data) = storage.get(LOCAL_STORAGE_KEY_NAME)? else {
+        return Ok(false);
+    };
+    let value = format!("{now};{data}");
+
+    // the oldest backup is about to be dropped
+    let mut total = data.len() + value.len();
+    for slot in 0..MAX_BACKUPS - 1 {
+        total += storage
+            .get(&backup_key(slot))?
+            .map_or(0, |value| value.len());
+    }
+    if total > BACKUP_STORAGE_BUDGET {
+        return Ok(false);
+    }
+
+    for slot in (1..MAX_BACKUPS).rev() {
+        match storage.get(&backup_key(slot - 1))? {
+            Some(older) => storage.set(&backup_key(slot), &older)?,
+            None => storage.remove(&backup_key(slot))?,
+        }
+    }
+    storage.set(&backup_key(0), &value)?;
+    Ok(true)
+}
+
+/// The backups of the save, newest first.
+pub fn backups<S: SaveStorage>(storage: &S) -> Result<Vec<Backup>, S::Error> {
+    let mut out = Vec::with_capacity(MAX_BACKUPS);
+    for slot in 0..MAX_BACKUPS {
+        if let Some(backup) = storage
+            .get(&backup_key(slot))?
+            .and_then(|value| Backup::parse(slot, &value))
+        {
+            out.push(backup);
+        }
+    }
+    Ok(out)
+}
+
+/// Replace the save with the backup in the given slot,
+/// backing up the save being replaced first
+/// unless it cannot be read anyway.
+///
+/// Returns whether there was such a backup.
+pub fn restore_backup<S: SaveStorage>(
+    storage: &S,
+    slot: usize,
+    now: u64,
+) -> Result<bool, S::Error> {
+    let Some(backup) = storage
+        .get(&backup_key(slot))?
+        .and_then(|value| Backup::parse(slot, &value))
+    else {
+        return Ok(false);
+    };
+    if storage
+        .get(LOCAL_STORAGE_KEY_NAME)?
+        .is_some_and(|data| read_save(&data).is_ok())
+    {
+        back_up(storage, now)?;
+    }
+    storage.set(LOCAL_STORAGE_KEY_NAME, &backup.data)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::BTreeMap};
+
+    use super::{
+        back_up, backup_key, backups, check_save, restore_backup, LoadError, Persistence, SaveSlot,
+        SaveStorage, SaveSummary, StorageHealth, StorageMonitor, BACKUP_PERIOD,
+        BACKUP_STORAGE_BUDGET, MAX_SAVE_FAILURES, STORAGE_PROBE_PERIOD,
+    };
+    use crate::{
+        central::{
+            engine::CloudNode,
+            save::write_save,
+            state::{ServiceState, LOCAL_STORAGE_KEY_NAME},
+            testing::WorldStateBuilder,
+        },
+        Money, ServiceKind, WorldState,
+    };
+
+    /// local storage held in memory
+    #[derive(Default)]
+    struct MemoryStorage {
+        items: RefCell<BTreeMap<String, String>>,
+    }
+
+    impl SaveStorage for MemoryStorage {
+        type Error = String;
+
+        fn get(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.items.borrow().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), String> {
+            self.items
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> Result<(), String> {
+            self.items.borrow_mut().remove(key);
+            Ok(())
+        }
+    }
+
+    impl MemoryStorage {
+        fn save(&self, state: &WorldState) {
+            let json = serde_json::to_string(state).unwrap();
+            write_save(&json, |data| self.set(LOCAL_STORAGE_KEY_NAME, data)).unwrap();
+        }
+
+        fn saved_earnings(&self) -> Money {
+            let data = self.get(LOCAL_STORAGE_KEY_NAME).unwrap().unwrap();
+            serde_json::from_str::<WorldState>(&data).unwrap().earned
+        }
+    }
+
+    fn state_with_earnings(dollars: i64) -> WorldState {
+        WorldStateBuilder::new()
+            .earned(Money::dollars(dollars))
+            .build()
+    }
+
+    #[test]
+    fn test_save_and_load_game() {
+        let storage = MemoryStorage::default();
+        assert_eq!(storage.has_saved_game(SaveSlot::Main), Ok(false));
+        assert_eq!(storage.load_game(SaveSlot::Main), Ok(None));
+
+        let mut state = state_with_earnings(12);
+        storage.save_game(&mut state, 1_234).unwrap();
+        assert_eq!(state.saved_at, 1_234);
+        assert_eq!(storage.has_saved_game(SaveSlot::Main), Ok(true));
+        assert_eq!(storage.has_saved_game(SaveSlot::Daily), Ok(false));
+        let loaded = storage.load_game(SaveSlot::Main).unwrap().unwrap();
+        assert_eq!(loaded.earned, Money::dollars(12));
+        assert_eq!(loaded.saved_at, 1_234);
+    }
+
+    /// Load the given save data from the main slot,
+    /// expecting it to be corrupted and moved aside.
+    fn assert_corrupt(data: &str) {
+        let storage = MemoryStorage::default();
+        storage.set(LOCAL_STORAGE_KEY_NAME, data).unwrap();
+        assert!(matches!(
+            storage.load_game(SaveSlot::Main),
+            Err(LoadError::Corrupt(_))
+        ));
+        // the payload is kept for debugging, but no longer in the way
+        assert_eq!(
+            storage
+                .get(&SaveSlot::Main.corrupt_key())
+                .unwrap()
+                .as_deref(),
+            Some(data)
+        );
+        assert_eq!(storage.has_saved_game(SaveSlot::Main), Ok(false));
+        assert_eq!(storage.load_game(SaveSlot::Main), Ok(None));
+    }
+
+    #[test]
+    fn test_malformed_save_is_corrupt() {
+        assert_corrupt("{oops");
+        assert_corrupt("");
+        assert_corrupt("z:not base64!");
+        // a partial write
+        let json = serde_json::to_string(&state_with_earnings(3)).unwrap();
+        assert_corrupt(&json[..json.len() / 2]);
+    }
+
+    #[test]
+    fn test_invalid_save_is_corrupt() {
+        let mut state = state_with_earnings(3);
+        state.nodes.clear();
+        assert!(check_save(&state).is_err());
+        assert_corrupt(&serde_json::to_string(&state).unwrap());
+
+        // nodes out of order
+        let mut state = state_with_earnings(3);
+        state.nodes.push(CloudNode::new(4));
+        assert!(check_save(&state).is_err());
+        assert_corrupt(&serde_json::to_string(&state).unwrap());
+
+        // a higher tier unlocked before a lower one
+        let mut state = state_with_earnings(3);
+        state.epic_service.state = ServiceState::Published;
+        assert!(check_save(&state).is_err());
+        assert_corrupt(&serde_json::to_string(&state).unwrap());
+
+        // a customer of a locked service
+        let state = WorldStateBuilder::new()
+            .service_published(ServiceKind::Super, Money::cents(1))
+            .with_customer(ServiceKind::Super)
+            .build();
+        assert_eq!(check_save(&state), Ok(()));
+        let mut state = state;
+        state.super_service.state = ServiceState::Locked;
+        assert!(check_save(&state).is_err());
+        assert_corrupt(&serde_json::to_string(&state).unwrap());
+
+        // a card used in the future
+        let mut state = WorldStateBuilder::new()
+            .time(100)
+            .cards_used(&["a1p"])
+            .build();
+        assert_eq!(check_save(&state), Ok(()));
+        state.cards_used[0].time = 101;
+        assert!(check_save(&state).is_err());
+        assert_corrupt(&serde_json::to_string(&state).unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_backup_is_unreadable() {
+        let storage = MemoryStorage::default();
+        storage.set(LOCAL_STORAGE_KEY_NAME, "{oops").unwrap();
+        back_up(&storage, 100).unwrap();
+        let backups = backups(&storage).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].load(), None);
+    }
+
+    #[test]
+    fn test_backups_rotate() {
+        let storage = MemoryStorage::default();
+        // nothing to back up yet
+        assert_eq!(back_up(&storage, 1), Ok(false));
+        assert_eq!(backups(&storage), Ok(vec![]));
+
+        for (i, dollars) in [1, 2, 3].into_iter().enumerate() {
+            storage.save(&state_with_earnings(dollars));
+            assert_eq!(back_up(&storage, 100 * (i as u64 + 1)), Ok(true));
+        }
+
+        // only the last two are kept, newest first
+        let backups = backups(&storage).unwrap();
+        let earned: Vec<_> = backups
+            .iter()
+            .map(|backup| backup.load().unwrap().earned)
+            .collect();
+        assert_eq!(earned, [Money::dollars(3), Money::dollars(2)]);
+        let times: Vec<_> = backups.iter().map(|backup| backup.backed_up_at).collect();
+        assert_eq!(times, [300, 200]);
+        assert_eq!(storage.items.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_restore_backup() {
+        let storage = MemoryStorage::default();
+        let state = state_with_earnings(5);
+        storage.save(&state);
+        back_up(&storage, 100).unwrap();
+        storage.save(&state_with_earnings(8));
+        back_up(&storage, 200).unwrap();
+
+        // the save goes bad
+        storage.set(LOCAL_STORAGE_KEY_NAME, "{oops").unwrap();
+
+        assert_eq!(restore_backup(&storage, 1, 300), Ok(true));
+        assert_eq!(storage.saved_earnings(), Money::dollars(5));
+        let backup = &backups(&storage).unwrap()[1];
+        assert_eq!(
+            SaveSummary::of(&backup.load().unwrap()),
+            SaveSummary::of(&state)
+        );
+        // the unreadable save does not push out the backups
+        assert_eq!(backups(&storage).unwrap().len(), 2);
+        assert_eq!(backups(&storage).unwrap()[0].backed_up_at, 200);
+        assert_eq!(restore_backup(&storage, 2, 300), Ok(false));
+    }
+
+    /// Restoring a backup keeps the save it replaces as a backup.
+    #[test]
+    fn test_restore_backup_keeps_current_save() {
+        let storage = MemoryStorage::default();
+        storage.save(&state_with_earnings(5));
+        back_up(&storage, 100).unwrap();
+        storage.save(&state_with_earnings(30));
+
+        assert_eq!(restore_backup(&storage, 0, 200), Ok(true));
+        assert_eq!(storage.saved_earnings(), Money::dollars(5));
+        let backups = backups(&storage).unwrap();
+        assert_eq!(backups[0].backed_up_at, 200);
+        assert_eq!(backups[0].load().unwrap().earned, Money::dollars(30));
+
+        // so the restore can be undone
+        assert_eq!(restore_backup(&storage, 0, 300), Ok(true));
+        assert_eq!(storage.saved_earnings(), Money::dollars(30));
+    }
+
+    /// A destructive operation backs up the save it is about to replace.
+    #[test]
+    fn test_back_up_before_destructive_operation() {
+        let storage = MemoryStorage::default();
+        storage.save(&state_with_earnings(40));
+
+        // e.g. starting a new game over the saved one
+        back_up(&storage, 1_000).unwrap();
+        storage.save(&WorldState::default());
+        assert_eq!(storage.saved_earnings(), Money::zero());
+
+        restore_backup(&storage, 0, 2_000).unwrap();
+        assert_eq!(storage.saved_earnings(), Money::dollars(40));
+    }
+
+    #[test]
+    fn test_backups_skipped_under_quota_pressure() {
+        let storage = MemoryStorage::default();
+        storage.save(&state_with_earnings(1));
+        back_up(&storage, 100).unwrap();
+
+        let huge = "x".repeat(BACKUP_STORAGE_BUDGET / 2);
+        storage.set(LOCAL_STORAGE_KEY_NAME, &huge).unwrap();
+        assert_eq!(back_up(&storage, 200), Ok(false));
+        // the older backup is left alone
+        assert_eq!(backups(&storage).unwrap()[0].backed_up_at, 100);
+        assert!(storage.get(&backup_key(1)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_backup_due_periodically() {
+        let mut monitor = StorageMonitor::new();
+        let mut due = vec![];
+        for i in 0..BACKUP_PERIOD * 2 {
+            due.push(monitor.backup_due());
+            monitor.autosave(i as u64, || Ok::<(), ()>(()));
+        }
+        assert_eq!(due.iter().filter(|&&due| due).count(), 2);
+        // failing storage is not backed up
+        for _ in 0..BACKUP_PERIOD - 1 {
+            monitor.autosave(0, || Ok::<(), ()>(()));
+        }
+        assert!(monitor.backup_due());
+        assert_eq!(monitor.autosave(0, || Err(())), Some(Err(())));
+        assert!(!monitor.backup_due());
+        // and failed saves do not count
+        monitor.autosave(0, || Ok::<(), ()>(()));
+        assert!(!monitor.backup_due());
+    }
+
+    /// storage which can be switched on and off
+    #[derive(Default)]
+    struct MockStorage {
+        working: bool,
+        attempts: u32,
+    }
+
+    impl MockStorage {
+        fn save(&mut self) -> Result<(), ()> {
+            self.attempts += 1;
+            if self.working {
+                Ok(())
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_storage_fails_and_recovers() {
+        let mut monitor = StorageMonitor::new();
+        let mut storage = MockStorage {
+            working: true,
+            ..Default::default()
+        };
+        // autosaves every 36 seconds
+        let period = 360_000;
+        let mut time = 0;
+
+        assert_eq!(monitor.autosave(time, || storage.save()), Some(Ok(())));
+        assert_eq!(monitor.banner(), None);
+
+        // storage goes away
+        storage.working = false;
+        for failures in 1..MAX_SAVE_FAILURES {
+            time += period;
+            assert_eq!(monitor.autosave(time, || storage.save()), Some(Err(())));
+            assert_eq!(monitor.health(), StorageHealth::Failing(failures));
+            assert_eq!(monitor.banner(), Some("\u{26a0} Could not save the game"));
+        }
+        time += period;
+        assert_eq!(monitor.autosave(time, || storage.save()), Some(Err(())));
+        assert_eq!(monitor.health(), StorageHealth::Unavailable);
+        let banner = monitor.banner().unwrap();
+        assert!(banner.contains("will not be saved"));
+
+        // autosaves are suppressed until the next probe
+        let attempts = storage.attempts;
+        let unavailable_at = time;
+        while time + period < unavailable_at + STORAGE_PROBE_PERIOD {
+            time += period;
+            assert_eq!(monitor.autosave(time, || storage.save()), None);
+        }
+        assert_eq!(storage.attempts, attempts);
+        assert_eq!(monitor.banner(), Some(banner));
+
+        // a failed probe keeps it unavailable
+        time += period;
+        assert_eq!(monitor.autosave(time, || storage.save()), Some(Err(())));
+        assert_eq!(storage.attempts, attempts + 1);
+        assert_eq!(monitor.health(), StorageHealth::Unavailable);
+        assert_eq!(monitor.autosave(time + period, || storage.save()), None);
+
+        // storage comes back, and is picked up by the next probe
+        storage.working = true;
+        time += STORAGE_PROBE_PERIOD;
+        assert_eq!(monitor.autosave(time, || storage.save()), Some(Ok(())));
+        assert_eq!(monitor.health(), StorageHealth::Healthy);
+        assert_eq!(monitor.banner(), None);
+        assert!(monitor.should_attempt(time + period));
+    }
+}
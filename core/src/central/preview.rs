@@ -0,0 +1,249 @@
+//! Module for previewing hardware purchases,
+//! so that the player knows what a new node brings before buying it.
+//!
+//! The request rate of a new node is estimated
+//! from the time its cores take per request,
+//! as if it were kept busy with requests of a single op
+//! of the service doing the most work lately.
+
+use crate::{
+    Cost, GameConfig, Memory, Money, ServiceKind, Time, WorldState, TIME_UNITS_PER_SECOND,
+};
+
+use super::{
+    engine::{processing_time, RACK_CAPACITY},
+    eta::{eta_for, ResourceRates},
+};
+
+/// The hardware of a node about to be purchased.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NodeSpec {
+    pub num_cores: u32,
+    /// the speed of each core
+    pub cpu_speed: u32,
+    pub ram_capacity: Memory,
+}
+
+impl NodeSpec {
+    /// A node at the given CPU and RAM levels
+    /// of the given game configuration.
+    pub fn at_levels(config: &GameConfig, cpu_level: u8, ram_level: u8) -> Self {
+        let (num_cores, cpu_speed, _) = config.cpu_levels[cpu_level as usize];
+        NodeSpec {
+            num_cores,
+            cpu_speed,
+            ram_capacity: config.ram_levels[ram_level as usize].0,
+        }
+    }
+
+    /// A bare node, as bought before racks are unlocked.
+    pub fn bare(config: &GameConfig) -> Self {
+        Self::at_levels(config, 0, 0)
+    }
+
+    /// A node with the highest CPU and RAM levels,
+    /// as bought once racks are unlocked.
+    pub fn fully_upgraded(config: &GameConfig) -> Self {
+        Self::at_levels(
+            config,
+            config.cpu_levels.len() as u8 - 1,
+            config.ram_levels.len() as u8 - 1,
+        )
+    }
+
+    /// A whole rack of fully upgraded nodes,
+    /// as bought once data centers are unlocked.
+    pub fn upgraded_rack(config: &GameConfig) -> Self {
+        let node = Self::fully_upgraded(config);
+        NodeSpec {
+            num_cores: node.num_cores * RACK_CAPACITY,
+            ram_capacity: node.ram_capacity * RACK_CAPACITY as i32,
+            ..node
+        }
+    }
+}
+
+/// The number of requests of a single op of the given service
+/// which the given node can process per second
+/// with all of its cores busy.
+pub fn estimated_throughput(node: &NodeSpec, service: ServiceKind, software_level: u8) -> f32 {
+    let time = processing_time(node.cpu_speed, service, software_level);
+    node.num_cores as f32 * TIME_UNITS_PER_SECOND as f32 / time as f32
+}
+
+/// The available service which performed the most ops per second lately,
+/// or the one with the most ops overall if none did,
+/// falling back to the base service.
+pub fn dominant_service(state: &WorldState, rates: &ResourceRates) -> ServiceKind {
+    let unlocked = || {
+        state
+            .services()
+            .filter(|(_, service)| service.state.is_unlocked())
+    };
+    unlocked()
+        .map(|(kind, _)| (kind, rates.ops[kind.to_code() as usize]))
+        .filter(|(_, rate)| *rate > 0.)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(kind, _)| kind)
+        .or_else(|| {
+            unlocked()
+                .filter(|(_, service)| service.total.0 > 0)
+                .max_by_key(|(_, service)| service.total)
+                .map(|(kind, _)| kind)
+        })
+        .unwrap_or(ServiceKind::Base)
+}
+
+/// What a hardware purchase would bring to the cloud.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PurchasePreview {
+    /// the cores added
+    pub cores: u32,
+    /// the memory added
+    pub ram: Memory,
+    /// the service for which the request rate is estimated
+    pub service: ServiceKind,
+    /// the estimated additional requests per second
+    /// (see [`estimated_throughput`])
+    pub requests_per_second: f32,
+    /// the estimated game time until the purchase can be afforded,
+    /// if it cannot be afforded yet and money is coming in
+    pub eta: Option<Time>,
+}
+
+impl PurchasePreview {
+    /// Preview the purchase of a node of the given spec at the given cost,
+    /// at the rates at which resources have been coming in.
+    pub fn new(state: &WorldState, node: &NodeSpec, cost: Money, rates: &ResourceRates) -> Self {
+        let service = dominant_service(state, rates);
+        let eta = if cost > state.funds {
+            eta_for(&Cost::money(cost), &state.resources(), rates)
+        } else {
+            None
+        };
+        PurchasePreview {
+            cores: node.num_cores,
+            ram: node.ram_capacity,
+            service,
+            requests_per_second: estimated_throughput(node, service, state.software_level),
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dominant_service, estimated_throughput, NodeSpec, PurchasePreview};
+    use crate::{
+        central::{
+            engine::{CloudNode, RACK_CAPACITY},
+            eta::ResourceRates,
+            state::ServiceState,
+        },
+        Difficulty, GameConfig, Money, Ops, ServiceKind, WorldState, TIME_UNITS_PER_SECOND,
+    };
+
+    #[test]
+    fn test_throughput_over_cpu_levels() {
+        let config = GameConfig::default();
+        let mut last = 0.;
+        for level in 0..config.cpu_levels.len() as u8 {
+            let node = NodeSpec::at_levels(&config, level, 0);
+            let throughput = estimated_throughput(&node, ServiceKind::Base, 0);
+            // every upgrade processes more requests
+            assert!(throughput > last, "level {level}: {throughput} <= {last}");
+            last = throughput;
+
+            // matches the time taken by the engine
+            let mut cloud_node = CloudNode::new(0);
+            cloud_node.num_cores = node.num_cores;
+            cloud_node.cpu_speed = node.cpu_speed;
+            let time = cloud_node.time_per_request(ServiceKind::Base, 0);
+            let expected = node.num_cores as f32 * TIME_UNITS_PER_SECOND as f32 / time as f32;
+            assert_eq!(throughput, expected);
+        }
+
+        // one core of speed 2: 1250 + 4500 time units per request
+        let bare = estimated_throughput(&NodeSpec::bare(&config), ServiceKind::Base, 0);
+        assert!((bare - 10_000. / 5_750.).abs() < 1e-4);
+        // higher tiers and better software make a difference
+        let node = NodeSpec::fully_upgraded(&config);
+        assert!(
+            estimated_throughput(&node, ServiceKind::Epic, 0)
+                < estimated_throughput(&node, ServiceKind::Base, 0)
+        );
+        assert!(
+            estimated_throughput(&node, ServiceKind::Base, 2)
+                > estimated_throughput(&node, ServiceKind::Base, 0)
+        );
+        // a rack is worth its nodes
+        let rack = estimated_throughput(&NodeSpec::upgraded_rack(&config), ServiceKind::Base, 0);
+        let node = estimated_throughput(&node, ServiceKind::Base, 0);
+        assert!((rack - node * RACK_CAPACITY as f32).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_node_spec_per_difficulty() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let config = GameConfig::new(difficulty);
+            let node = NodeSpec::fully_upgraded(&config);
+            let (num_cores, cpu_speed, _) = config.cpu_levels[config.cpu_levels.len() - 1];
+            let (ram_capacity, _) = config.ram_levels[config.ram_levels.len() - 1];
+            assert_eq!(
+                node,
+                NodeSpec {
+                    num_cores,
+                    cpu_speed,
+                    ram_capacity,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_dominant_service() {
+        let mut state = WorldState::default();
+        let mut rates = ResourceRates::default();
+        assert_eq!(dominant_service(&state, &rates), ServiceKind::Base);
+
+        state.base_service.state = ServiceState::Published;
+        state.super_service.state = ServiceState::Private;
+        state.super_service.total = Ops(10);
+        assert_eq!(dominant_service(&state, &rates), ServiceKind::Super);
+
+        rates.ops[0] = 5.;
+        rates.ops[1] = 1.;
+        assert_eq!(dominant_service(&state, &rates), ServiceKind::Base);
+        // locked services are left out
+        rates.ops[2] = 100.;
+        assert_eq!(dominant_service(&state, &rates), ServiceKind::Base);
+    }
+
+    #[test]
+    fn test_purchase_preview_eta() {
+        let mut state = WorldState {
+            funds: Money::dollars(10),
+            ..Default::default()
+        };
+        let rates = ResourceRates {
+            money: Money::dollars(1).to_millicents() as f64,
+            ..Default::default()
+        };
+        let node = NodeSpec::bare(&GameConfig::default());
+        let preview = PurchasePreview::new(&state, &node, Money::dollars(20), &rates);
+        assert_eq!(preview.cores, 1);
+        assert_eq!(preview.service, ServiceKind::Base);
+        assert_eq!(preview.eta, Some(10 * TIME_UNITS_PER_SECOND));
+
+        // nothing to wait for when affordable
+        state.funds = Money::dollars(20);
+        let preview = PurchasePreview::new(&state, &node, Money::dollars(20), &rates);
+        assert_eq!(preview.eta, None);
+
+        // nor any estimate without income
+        state.funds = Money::dollars(10);
+        let preview =
+            PurchasePreview::new(&state, &node, Money::dollars(20), &ResourceRates::default());
+        assert_eq!(preview.eta, None);
+    }
+}
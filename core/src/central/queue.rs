@@ -20,6 +20,9 @@ pub struct RequestEvent {
     pub service: ServiceKind,
     /// whether it was a bad request that will not fulfill anything
     pub bad: bool,
+    /// whether the request was made by the player's staff
+    /// (never the case for requests from cloud users)
+    pub staff: bool,
     /// the request event stage
     pub kind: RequestEventStage,
 }
@@ -29,7 +32,11 @@ pub enum RequestEventStage {
     /// a request or request set has just arrived at the system.
     /// if more than one node is available,
     /// routing may still be necessary
-    RequestArrived,
+    RequestArrived {
+        /// whether the arrival schedules the next arrival
+        /// from the same user
+        chained: bool,
+    },
     /// the request (or request set) has been routed to a node
     /// and is now being processed
     RequestRouted { node_num: u32 },
@@ -52,7 +59,44 @@ impl RequestEvent {
             amount,
             service,
             bad,
-            kind: RequestEventStage::RequestArrived,
+            staff: false,
+            kind: RequestEventStage::RequestArrived { chained: true },
+        }
+    }
+
+    /// Make an arrival event which does not schedule
+    /// the next arrival from the same user.
+    pub fn unchained(self) -> Self {
+        debug_assert!(
+            matches!(self.kind, RequestEventStage::RequestArrived { .. }),
+            "only arrivals can be unchained"
+        );
+        Self {
+            kind: RequestEventStage::RequestArrived { chained: false },
+            ..self
+        }
+    }
+
+    /// Make an arrival event of requests made by the player's staff.
+    pub fn by_staff(self) -> Self {
+        debug_assert!(
+            self.user_spec_id.is_none(),
+            "staff requests belong to no cloud user"
+        );
+        Self {
+            staff: true,
+            ..self
+        }
+    }
+
+    /// Send a request event already in the system
+    /// back to arrive again at the given time,
+    /// without scheduling the next arrival from the same user.
+    pub fn into_rerouted(self, timestamp: Time) -> Self {
+        Self {
+            timestamp,
+            kind: RequestEventStage::RequestArrived { chained: false },
+            ..self
         }
     }
 
@@ -63,6 +107,7 @@ impl RequestEvent {
             amount: self.amount,
             service: self.service,
             bad: self.bad,
+            staff: self.staff,
             kind: RequestEventStage::RequestRouted { node_num },
         }
     }
@@ -81,6 +126,7 @@ impl RequestEvent {
             amount: self.amount,
             service: self.service,
             bad: self.bad,
+            staff: self.staff,
             kind: RequestEventStage::RequestProcessed {
                 node_num,
                 ram_required,
@@ -126,15 +172,46 @@ impl RequestEventQueue {
         self.queue.pop_front()
     }
 
+    /// The next events in the queue, in the order they will be popped.
+    pub fn iter(&self) -> impl Iterator<Item = &RequestEvent> {
+        self.queue.iter()
+    }
+
+    /// The number of events in the queue.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     pub fn last_time(&self) -> Time {
         self.last_time
     }
 
-    /// Clear all request events for things already in the system.
-    /// (all but the ones yet to arrive)
-    pub fn clear_in_nodes(&mut self) {
-        self.queue
-            .retain(|event| matches!(event.kind, RequestEventStage::RequestArrived { .. }));
+    /// Take out all request events for things already in the system
+    /// (all but the ones yet to arrive).
+    pub fn take_in_nodes(&mut self) -> Vec<RequestEvent> {
+        let (arrivals, in_nodes): (_, VecDeque<_>) = std::mem::take(&mut self.queue)
+            .into_iter()
+            .partition(|event| matches!(event.kind, RequestEventStage::RequestArrived { .. }));
+        self.queue = arrivals;
+        in_nodes.into()
+    }
+
+    /// Clear the request events yet to arrive
+    /// which satisfy the given predicate.
+    pub fn clear_arrivals(&mut self, mut predicate: impl FnMut(&RequestEvent) -> bool) {
+        self.queue.retain(|event| {
+            !matches!(event.kind, RequestEventStage::RequestArrived { .. }) || !predicate(event)
+        });
+    }
+}
+
+impl Default for RequestEventQueue {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -0,0 +1,412 @@
+//! Module for recommending hardware purchases to the player,
+//! based on why requests have been dropped lately.
+
+use crate::{t, GameConfig, Money, PlayerAction, WorldState};
+
+use super::{
+    bandwidth::bandwidth_capacity,
+    cards::{all::ALL_CARDS, CardEffect},
+    engine::CloudNode,
+    metrics::{DropCause, MetricsSnapshot},
+    preview::{dominant_service, estimated_throughput, NodeSpec},
+    state::{HARDWARE_PANEL_PURCHASES, HARDWARE_PANEL_UPGRADES},
+};
+
+/// A suggested purchase for the hardware panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    /// the action which makes the purchase
+    pub action: PlayerAction,
+    /// what to do, e.g. "Upgrade RAM on node 0"
+    pub label: String,
+    /// why it is recommended and what it is expected to bring
+    pub benefit: String,
+    /// how much it costs
+    pub cost: Money,
+}
+
+/// Recommend a hardware purchase
+/// for the main cause of recently dropped requests:
+/// the cheapest RAM or CPU upgrade among the nodes,
+/// a new node, or a network card,
+/// along with what it is expected to bring.
+///
+/// Only purchases which are available to the player
+/// and which they can afford right now are recommended.
+/// Returns `None` until the player can see the request rates,
+/// if no requests were dropped,
+/// or if nothing suitable can be bought.
+pub fn recommend_purchase(
+    state: &WorldState,
+    metrics: &MetricsSnapshot,
+    config: &GameConfig,
+) -> Option<Recommendation> {
+    if !state.can_see_request_rates {
+        return None;
+    }
+    let total: u64 = metrics.drop_causes.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    // the first of the most frequent causes
+    let cause = DropCause::ALL
+        .into_iter()
+        .fold(DropCause::Cpu, |best, cause| {
+            if metrics.dropped_by(cause) > metrics.dropped_by(best) {
+                cause
+            } else {
+                best
+            }
+        });
+    let share = metrics.dropped_by(cause) * 100 / total;
+
+    match cause {
+        DropCause::Ram => {
+            ram_upgrade(state, config).map(|r| r.because(t!("recommend.drops_ram", share = share)))
+        }
+        DropCause::Cpu => cpu_upgrade(state, config)
+            .or_else(|| node_purchase(state, metrics, config))
+            .map(|r| r.because(t!("recommend.drops_cpu", share = share))),
+        DropCause::Queue => node_purchase(state, metrics, config)
+            .or_else(|| cpu_upgrade(state, config))
+            .map(|r| r.because(t!("recommend.drops_queue", share = share))),
+        DropCause::Bandwidth => bandwidth_upgrade(state)
+            .map(|r| r.because(t!("recommend.drops_bandwidth", share = share))),
+    }
+}
+
+impl Recommendation {
+    fn because(mut self, reason: String) -> Self {
+        self.benefit = t!("recommend.reason", reason = reason, benefit = self.benefit);
+        self
+    }
+}
+
+/// whether nodes can be upgraded one by one
+fn can_upgrade(state: &WorldState) -> bool {
+    state.hardware_panel_level >= HARDWARE_PANEL_UPGRADES && !state.can_buy_racks
+}
+
+/// the cheapest affordable upgrade among the nodes
+fn cheapest_upgrade(
+    state: &WorldState,
+    next_cost: impl Fn(&CloudNode) -> Option<Money>,
+) -> Option<(&CloudNode, Money)> {
+    state
+        .nodes
+        .iter()
+        .filter_map(|node| next_cost(node).map(|cost| (node, cost)))
+        .filter(|(_, cost)| *cost <= state.funds)
+        .min_by_key(|(_, cost)| *cost)
+}
+
+fn ram_upgrade(state: &WorldState, config: &GameConfig) -> Option<Recommendation> {
+    if !can_upgrade(state) {
+        return None;
+    }
+    let (node, cost) = cheapest_upgrade(state, |node| node.next_ram_upgrade_cost(config))?;
+    let (ram_capacity, _) = config.ram_levels[node.ram_level as usize + 1];
+    Some(Recommendation {
+        action: PlayerAction::UpgradeRam { node: node.id },
+        label: t!("recommend.upgrade_ram", node = node.id),
+        benefit: t!(
+            "recommend.more_memory",
+            memory = ram_capacity - node.ram_capacity
+        ),
+        cost,
+    })
+}
+
+fn cpu_upgrade(state: &WorldState, config: &GameConfig) -> Option<Recommendation> {
+    if !can_upgrade(state) {
+        return None;
+    }
+    let (node, cost) = cheapest_upgrade(state, |node| node.next_cpu_upgrade_cost(config))?;
+    let (num_cores, cpu_speed, _) = config.cpu_levels[node.cpu_level as usize + 1];
+    let benefit = if num_cores > node.num_cores {
+        t!("recommend.more_cores", cores = num_cores - node.num_cores)
+    } else {
+        t!(
+            "recommend.faster_cores",
+            factor = cpu_speed as f32 / node.cpu_speed as f32
+        )
+    };
+    Some(Recommendation {
+        action: PlayerAction::UpgradeCpu { node: node.id },
+        label: t!("recommend.upgrade_cpu", node = node.id),
+        benefit,
+        cost,
+    })
+}
+
+fn node_purchase(
+    state: &WorldState,
+    metrics: &MetricsSnapshot,
+    config: &GameConfig,
+) -> Option<Recommendation> {
+    if !state.can_buy_nodes
+        || state.can_buy_datacenters
+        || state.hardware_panel_level < HARDWARE_PANEL_PURCHASES
+    {
+        return None;
+    }
+    let (action, cost, node) = if state.can_buy_racks {
+        (
+            PlayerAction::AddUpgradedNode,
+            config.upgraded_node_cost,
+            NodeSpec::fully_upgraded(config),
+        )
+    } else {
+        (
+            PlayerAction::AddNode,
+            config.bare_node_cost,
+            NodeSpec::bare(config),
+        )
+    };
+    if state.nodes.len() as u32 >= state.node_capacity() || cost > state.funds {
+        return None;
+    }
+    let service = dominant_service(state, &metrics.resource_rates);
+    let rate = estimated_throughput(&node, service, state.software_level);
+    Some(Recommendation {
+        action,
+        label: t!("recommend.buy_node").to_string(),
+        benefit: t!(
+            "recommend.one_more_node",
+            rate = format!("{rate:.1}"),
+            service = service
+        )
+        .to_string(),
+        cost,
+    })
+}
+
+/// the first available network card which raises the bandwidth level
+fn bandwidth_upgrade(state: &WorldState) -> Option<Recommendation> {
+    ALL_CARDS.iter().find_map(|card| match card.effect {
+        CardEffect::UpgradeBandwidth(level)
+            if level > state.bandwidth_level
+                && card.should_appear(state)
+                && state.can_afford(&card.cost) =>
+        {
+            Some(Recommendation {
+                action: PlayerAction::UseCard { id: card.id.into() },
+                label: t!("recommend.use_card", card = card.title()),
+                benefit: t!(
+                    "recommend.more_bandwidth",
+                    factor = bandwidth_capacity(level) / bandwidth_capacity(state.bandwidth_level)
+                ),
+                cost: card.cost.money,
+            })
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::recommend_purchase;
+    use crate::{
+        central::{
+            engine::{CloudNode, GameEngine, RACK_CAPACITY},
+            metrics::{DropCause, MetricsSnapshot},
+            state::{ServiceState, HARDWARE_PANEL_PURCHASES},
+            testing::WorldStateBuilder,
+        },
+        GameConfig, Money, Ops, PlayerAction, ServiceKind, WorldState,
+    };
+
+    fn metrics_with(state: &WorldState, drops: [(DropCause, u64); 3]) -> MetricsSnapshot {
+        let mut metrics = GameEngine::new().metrics(state);
+        for (cause, amount) in drops {
+            metrics.drop_causes[cause as usize] = amount;
+        }
+        metrics
+    }
+
+    /// a state with the whole hardware panel and plenty of funds
+    fn rich_state() -> WorldState {
+        WorldState {
+            funds: Money::dollars(1_000_000),
+            can_buy_nodes: true,
+            can_see_request_rates: true,
+            hardware_panel_level: HARDWARE_PANEL_PURCHASES,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_no_recommendation_without_drops() {
+        let state = rich_state();
+        let metrics = metrics_with(&state, [(DropCause::Cpu, 0); 3]);
+        assert_eq!(
+            recommend_purchase(&state, &metrics, &GameConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recommend_by_drop_cause() {
+        let config = GameConfig::default();
+        let mut state = rich_state();
+        // node 1 has less RAM and a slower CPU than node 0
+        state.nodes[0].ram_level = 3;
+        state.nodes[0].cpu_level = 3;
+        state.nodes.push(CloudNode::new(1));
+
+        let metrics = metrics_with(
+            &state,
+            [
+                (DropCause::Cpu, 10),
+                (DropCause::Ram, 30),
+                (DropCause::Queue, 0),
+            ],
+        );
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(recommendation.action, PlayerAction::UpgradeRam { node: 1 });
+        assert_eq!(recommendation.label, "Upgrade RAM on node 1");
+        assert!(recommendation
+            .benefit
+            .starts_with("75% of drops are memory-related"));
+
+        let metrics = metrics_with(
+            &state,
+            [
+                (DropCause::Cpu, 30),
+                (DropCause::Ram, 10),
+                (DropCause::Queue, 0),
+            ],
+        );
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(recommendation.action, PlayerAction::UpgradeCpu { node: 1 });
+        assert_eq!(recommendation.cost, config.cpu_levels[1].2);
+
+        let metrics = metrics_with(
+            &state,
+            [
+                (DropCause::Cpu, 10),
+                (DropCause::Ram, 10),
+                (DropCause::Queue, 30),
+            ],
+        );
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(recommendation.action, PlayerAction::AddNode);
+        assert_eq!(recommendation.cost, config.bare_node_cost);
+        assert!(recommendation
+            .benefit
+            .ends_with("one more node for about 1.7 more Base requests/s"));
+
+        // the estimate goes by the service doing the most work lately
+        state.base_service.state = ServiceState::Published;
+        state.super_service.state = ServiceState::Published;
+        state.base_service.total = Ops(1_000);
+        let mut metrics = metrics;
+        metrics.resource_rates.ops[ServiceKind::Super.to_code() as usize] = 5.;
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(recommendation.action, PlayerAction::AddNode);
+        assert!(recommendation.benefit.ends_with("more Super requests/s"));
+    }
+
+    #[test]
+    fn test_recommend_only_affordable_and_available() {
+        let config = GameConfig::default();
+        let cpu_drops = [
+            (DropCause::Cpu, 10),
+            (DropCause::Ram, 0),
+            (DropCause::Queue, 0),
+        ];
+        let ram_drops = [
+            (DropCause::Cpu, 0),
+            (DropCause::Ram, 10),
+            (DropCause::Queue, 0),
+        ];
+
+        // too poor for anything
+        let mut state = WorldState {
+            funds: Money::dollars(10),
+            ..rich_state()
+        };
+        let metrics = metrics_with(&state, cpu_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        // maxed out CPU falls back to buying a node
+        state.funds = Money::dollars(1_000_000);
+        state.nodes[0].cpu_level = config.cpu_levels.len() as u8 - 1;
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(recommendation.action, PlayerAction::AddNode);
+
+        // unless the rack is full
+        for id in 1..RACK_CAPACITY {
+            let mut node = CloudNode::new(id);
+            node.cpu_level = state.nodes[0].cpu_level;
+            state.nodes.push(node);
+        }
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        // maxed out RAM has nothing to recommend
+        for node in &mut state.nodes {
+            node.ram_level = config.ram_levels.len() as u8 - 1;
+        }
+        let metrics = metrics_with(&state, ram_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        // nor do upgrades which are not revealed yet
+        let state = WorldState {
+            hardware_panel_level: 0,
+            ..rich_state()
+        };
+        let metrics = metrics_with(&state, ram_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        // nor anything before the request rates are shown
+        let state = WorldState {
+            can_see_request_rates: false,
+            ..rich_state()
+        };
+        let metrics = metrics_with(&state, cpu_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+    }
+
+    #[test]
+    fn test_recommend_network_card() {
+        let config = GameConfig::default();
+        let bandwidth_drops = [
+            (DropCause::Bandwidth, 20),
+            (DropCause::Cpu, 10),
+            (DropCause::Queue, 0),
+        ];
+        // the fiber uplink is not on offer yet
+        let state = rich_state();
+        let metrics = metrics_with(&state, bandwidth_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        let mut state = WorldStateBuilder::new()
+            .funds(Money::dollars(1_000_000))
+            .cards_used(&["n3"])
+            .time(1_000_000)
+            .service_unlocked(ServiceKind::Epic)
+            .available_ops(ServiceKind::Epic, Ops(1_000))
+            .build();
+        state.can_see_request_rates = true;
+        // not enough epic ops for it
+        let metrics = metrics_with(&state, bandwidth_drops);
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+
+        state.epic_service.available = Ops(10_000);
+        let recommendation = recommend_purchase(&state, &metrics, &config).unwrap();
+        assert_eq!(
+            recommendation.action,
+            PlayerAction::UseCard { id: "g1".into() }
+        );
+        assert_eq!(recommendation.label, "Use Fiber uplink");
+        assert_eq!(
+            recommendation.benefit,
+            "66% of drops are for lack of bandwidth, 10x the bandwidth"
+        );
+        assert_eq!(recommendation.cost, Money::dollars(1_000));
+
+        // nothing more once it is used
+        state.bandwidth_level = 1;
+        assert_eq!(recommend_purchase(&state, &metrics, &config), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Module for the periodic summary reports,
+//! which compare how the business went in the last period.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, Ops, ServiceKind, Time, WorldState};
+
+/// The maximum number of period reports kept in the world state
+pub const MAX_PERIOD_REPORTS: usize = 4;
+
+/// The cumulative counters of the game at a period boundary,
+/// from which the next period's report is calculated.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodSnapshot {
+    /// total money earned
+    pub earned: Money,
+    /// total ops of each service tier
+    #[serde(deserialize_with = "deserialize_ops")]
+    pub ops: [Ops; ServiceKind::ALL.len()],
+    /// total requests dropped
+    pub requests_dropped: u64,
+    /// total money billed for electricity
+    pub energy_cost: Money,
+    /// the base service demand
+    pub demand: f64,
+    /// the number of cards used
+    pub cards_used: u32,
+}
+
+impl PeriodSnapshot {
+    /// Take a snapshot of the counters in the given world state.
+    pub fn capture(state: &WorldState) -> Self {
+        Self {
+            earned: state.earned,
+            ops: ServiceKind::ALL.map(|kind| state.service_by_kind(kind).total),
+            requests_dropped: state.requests_dropped,
+            energy_cost: state.electricity.total_billed,
+            demand: state.demand,
+            cards_used: state.cards_used.len() as u32,
+        }
+    }
+}
+
+/// Deserialize the op counts of each service tier,
+/// accepting saves from before more service tiers were added.
+fn deserialize_ops<'de, D>(deserializer: D) -> Result<[Ops; ServiceKind::ALL.len()], D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let counts: Vec<Ops> = Deserialize::deserialize(deserializer)?;
+    let mut ops = [Ops(0); ServiceKind::ALL.len()];
+    for (slot, count) in ops.iter_mut().zip(counts) {
+        *slot = count;
+    }
+    Ok(ops)
+}
+
+/// A summary of what happened during one period of the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodReport {
+    /// the time at which the period ended
+    pub time: Time,
+    /// money earned in the period
+    pub revenue: Money,
+    /// ops performed in the period for each service tier
+    #[serde(deserialize_with = "deserialize_ops")]
+    pub ops: [Ops; ServiceKind::ALL.len()],
+    /// requests dropped in the period
+    pub requests_dropped: u64,
+    /// money billed for electricity in the period
+    pub energy_cost: Money,
+    /// how much the base service demand changed in the period
+    pub demand_change: f64,
+    /// the number of cards used in the period
+    pub cards_bought: u32,
+}
+
+impl PeriodReport {
+    /// Calculate the report of the period
+    /// between two snapshots.
+    ///
+    /// Counters which went backwards (such as after a new game)
+    /// are reported as zero.
+    pub fn between(start: &PeriodSnapshot, end: &PeriodSnapshot, time: Time) -> Self {
+        let mut ops = [Ops(0); ServiceKind::ALL.len()];
+        for (i, ops) in ops.iter_mut().enumerate() {
+            *ops = Ops((end.ops[i].0 - start.ops[i].0).max(0));
+        }
+        Self {
+            time,
+            revenue: (end.earned - start.earned).max(Money::zero()),
+            ops,
+            requests_dropped: end.requests_dropped.saturating_sub(start.requests_dropped),
+            energy_cost: (end.energy_cost - start.energy_cost).max(Money::zero()),
+            demand_change: end.demand - start.demand,
+            cards_bought: end.cards_used.saturating_sub(start.cards_used),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PeriodReport, PeriodSnapshot};
+    use crate::{Money, Ops, ServiceKind};
+
+    #[test]
+    fn test_report_between() {
+        let start = PeriodSnapshot {
+            earned: Money::dollars(10),
+            ops: [Ops(100), Ops(0), Ops(0), Ops(0), Ops(0)],
+            requests_dropped: 5,
+            energy_cost: Money::cents(50),
+            demand: 2.,
+            cards_used: 3,
+        };
+        let end = PeriodSnapshot {
+            earned: Money::dollars(25),
+            ops: [Ops(350), Ops(40), Ops(0), Ops(0), Ops(0)],
+            requests_dropped: 5,
+            energy_cost: Money::cents(120),
+            demand: 1.5,
+            cards_used: 5,
+        };
+        let report = PeriodReport::between(&start, &end, 1_000);
+        assert_eq!(report.time, 1_000);
+        assert_eq!(report.revenue, Money::dollars(15));
+        assert_eq!(report.ops, [Ops(250), Ops(40), Ops(0), Ops(0), Ops(0)]);
+        assert_eq!(report.requests_dropped, 0);
+        assert_eq!(report.energy_cost, Money::cents(70));
+        assert_eq!(report.demand_change, -0.5);
+        assert_eq!(report.cards_bought, 2);
+
+        // counters going backwards do not produce negative reports
+        let report = PeriodReport::between(&end, &PeriodSnapshot::default(), 2_000);
+        assert_eq!(report.revenue, Money::zero());
+        assert_eq!(report.ops, [Ops(0); ServiceKind::ALL.len()]);
+        assert_eq!(report.cards_bought, 0);
+    }
+
+    #[test]
+    fn test_snapshot_from_before_legendary_ops() {
+        let json = r#"{"earned":1000,"ops":[1,2,3,4],"requests_dropped":0,"energy_cost":0,"demand":1.0,"cards_used":0}"#;
+        let snapshot: PeriodSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(snapshot.ops, [Ops(1), Ops(2), Ops(3), Ops(4), Ops(0)]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Module for attributing revenue to the customers who brought it,
+//! so that the player can see which customers actually make money.
+//!
+//! Revenue and ops fulfilled are kept per user specification
+//! for as long as the customer stays.
+//! Once a customer leaves (their trial ran out, or they churned),
+//! their entry moves to a small archive of the best customers ever,
+//! so that the save stays bounded however many customers come and go.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CloudUserSpec, Money, ServiceKind, Time};
+
+/// the number of top customers shown to the player
+pub const TOP_CUSTOMERS: usize = 5;
+
+/// the maximum number of departed customers kept in the archive
+pub const MAX_ARCHIVED_CUSTOMERS: usize = 10;
+
+/// The revenue brought by one customer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomerRevenue {
+    /// the ID of the customer's user specification
+    pub spec_id: u32,
+    /// the service used by the customer
+    pub service: ServiceKind,
+    /// the money earned from the customer's requests
+    pub revenue: Money,
+    /// the ops fulfilled for the customer
+    pub ops: u64,
+}
+
+/// The revenue of each customer, current and past.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomerLedger {
+    /// the revenue of customers still around, sorted by spec ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    active: Vec<CustomerRevenue>,
+    /// the revenue of the best departed customers,
+    /// up to [`MAX_ARCHIVED_CUSTOMERS`] of them, highest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    archive: Vec<CustomerRevenue>,
+}
+
+impl CustomerLedger {
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty() && self.archive.is_empty()
+    }
+
+    /// Attribute the revenue and ops of a fulfilled request to a customer.
+    pub fn record(&mut self, spec: &CloudUserSpec, revenue: Money, ops: u64) {
+        match self
+            .active
+            .binary_search_by_key(&spec.id, |entry| entry.spec_id)
+        {
+            Ok(index) => {
+                let entry = &mut self.active[index];
+                entry.revenue += revenue;
+                entry.ops += ops;
+            }
+            Err(index) => self.active.insert(
+                index,
+                CustomerRevenue {
+                    spec_id: spec.id,
+                    service: spec.service,
+                    revenue,
+                    ops,
+                },
+            ),
+        }
+    }
+
+    /// The ops fulfilled so far for a customer still around.
+    pub fn ops_of(&self, spec_id: u32) -> u64 {
+        self.active
+            .binary_search_by_key(&spec_id, |entry| entry.spec_id)
+            .map_or(0, |index| self.active[index].ops)
+    }
+
+    /// Move the entries of customers no longer in the given specs
+    /// (sorted by ID) to the archive,
+    /// keeping only the best of them.
+    pub fn prune(&mut self, specs: &[CloudUserSpec]) {
+        let (active, departed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|entry| {
+                specs
+                    .binary_search_by_key(&entry.spec_id, |spec| spec.id)
+                    .is_ok()
+            });
+        self.active = active;
+        if departed.is_empty() {
+            return;
+        }
+        self.archive.extend(departed);
+        self.archive
+            .sort_by(|a, b| b.revenue.cmp(&a.revenue).then(a.spec_id.cmp(&b.spec_id)));
+        self.archive.truncate(MAX_ARCHIVED_CUSTOMERS);
+    }
+
+    /// The customers still around which brought the most revenue,
+    /// highest first.
+    pub fn top_active(&self, count: usize) -> Vec<&CustomerRevenue> {
+        let mut top: Vec<_> = self.active.iter().collect();
+        top.sort_by(|a, b| b.revenue.cmp(&a.revenue).then(a.spec_id.cmp(&b.spec_id)));
+        top.truncate(count);
+        top
+    }
+
+    /// The departed customers which brought the most revenue,
+    /// highest first.
+    pub fn archive(&self) -> &[CustomerRevenue] {
+        &self.archive
+    }
+}
+
+/// A top customer as shown to the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopCustomer {
+    /// the service used by the customer
+    pub service: ServiceKind,
+    /// the money earned from the customer's requests
+    pub revenue: Money,
+    /// the ops fulfilled for the customer
+    pub ops: u64,
+    /// whether the customer is still within the trial period
+    pub in_trial: bool,
+}
+
+impl TopCustomer {
+    /// Describe a customer's revenue along with their current terms.
+    pub fn new(entry: &CustomerRevenue, spec: &CloudUserSpec, time: Time) -> Self {
+        Self {
+            service: entry.service,
+            revenue: entry.revenue,
+            ops: entry.ops,
+            in_trial: !spec.is_paying(time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomerLedger, MAX_ARCHIVED_CUSTOMERS};
+    use crate::{CloudUserSpec, Money, ServiceKind, UserKind};
+
+    fn spec(id: u32, service: ServiceKind) -> CloudUserSpec {
+        CloudUserSpec {
+            id,
+            service,
+            trial_time: 0,
+            kind: UserKind::Customer,
+        }
+    }
+
+    #[test]
+    fn test_archive_bounded() {
+        let mut ledger = CustomerLedger::default();
+        let specs: Vec<_> = (1..=30).map(|id| spec(id, ServiceKind::Base)).collect();
+        for spec in &specs {
+            ledger.record(spec, Money::cents(spec.id as i64), 1);
+        }
+        assert_eq!(ledger.top_active(3).len(), 3);
+        assert_eq!(ledger.top_active(3)[0].spec_id, 30);
+
+        // everyone leaves but the first customer
+        ledger.prune(&specs[..1]);
+        assert_eq!(ledger.top_active(5).len(), 1);
+        assert_eq!(ledger.archive().len(), MAX_ARCHIVED_CUSTOMERS);
+        let archived: Vec<_> = ledger.archive().iter().map(|e| e.spec_id).collect();
+        assert_eq!(archived, (21..=30).rev().collect::<Vec<_>>());
+
+        // a later departure only makes it in if it earned enough
+        ledger.record(&specs[0], Money::cents(100), 1);
+        ledger.prune(&[]);
+        assert!(ledger.top_active(5).is_empty());
+        assert_eq!(ledger.archive().len(), MAX_ARCHIVED_CUSTOMERS);
+        assert_eq!(ledger.archive()[0].spec_id, 1);
+        assert_eq!(ledger.archive()[0].revenue, Money::cents(101));
+    }
+}
@@ -0,0 +1,161 @@
+//! Module for encoding the saved game,
+//! compressing it when it grows too big for local storage.
+//!
+//! Compressed saves are deflated, encoded in base64,
+//! and prefixed with [`COMPRESSED_PREFIX`],
+//! whereas plain saves are the game state in JSON.
+
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec};
+
+/// the prefix marking a compressed save
+pub const COMPRESSED_PREFIX: &str = "z:";
+
+/// the size of a plain save above which it is compressed right away
+pub const COMPRESSION_THRESHOLD: usize = 256 * 1024;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode the serialized game state in its compressed form.
+pub fn compress_save(json: &str) -> String {
+    let deflated = compress_to_vec(json.as_bytes(), 6);
+    let mut out = String::with_capacity(COMPRESSED_PREFIX.len() + deflated.len() * 4 / 3 + 4);
+    out.push_str(COMPRESSED_PREFIX);
+    encode_base64(&deflated, &mut out);
+    out
+}
+
+/// Obtain the serialized game state from a save,
+/// which may or may not be compressed.
+pub fn decode_save(data: &str) -> Result<String, String> {
+    match data.strip_prefix(COMPRESSED_PREFIX) {
+        Some(encoded) => {
+            let deflated = decode_base64(encoded).ok_or("Invalid compressed save data")?;
+            let json = decompress_to_vec(&deflated)
+                .map_err(|e| format!("Could not decompress saved game: {:?}", e.status))?;
+            String::from_utf8(json).map_err(|e| e.to_string())
+        }
+        None => Ok(data.to_string()),
+    }
+}
+
+/// Write the serialized game state using the given writer,
+/// in plain form if small enough.
+///
+/// If writing fails (e.g. because the storage quota was exceeded),
+/// it is retried once in compressed form.
+pub fn write_save<E>(json: &str, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    if json.len() > COMPRESSION_THRESHOLD {
+        return write(&compress_save(json));
+    }
+    write(json).or_else(|_| write(&compress_save(json)))
+}
+
+fn encode_base64(data: &[u8], out: &mut String) {
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+            n |= value << (18 - 6 * i);
+        }
+        let bytes = n.to_be_bytes();
+        // a chunk of k characters holds k - 1 bytes
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compress_save, decode_base64, decode_save, encode_base64, write_save, COMPRESSED_PREFIX,
+    };
+    use crate::{central::engine::CloudNode, WorldState};
+
+    #[test]
+    fn test_base64_round_trip() {
+        for data in [
+            &b""[..],
+            b"a",
+            b"ab",
+            b"abc",
+            b"abcd",
+            &[0, 255, 128, 7, 64],
+        ] {
+            let mut encoded = String::new();
+            encode_base64(data, &mut encoded);
+            assert_eq!(encoded.len() % 4, 0);
+            assert_eq!(decode_base64(&encoded).as_deref(), Some(data));
+        }
+        let mut encoded = String::new();
+        encode_base64(b"Man", &mut encoded);
+        assert_eq!(encoded, "TWFu");
+    }
+
+    #[test]
+    fn test_plain_save_decodes_as_is() {
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        assert_eq!(decode_save(&json).unwrap(), json);
+    }
+
+    #[test]
+    fn test_oversized_state_saves_compressed() {
+        // a big late game with plenty of nodes
+        let state = WorldState {
+            nodes: (0..4_000).map(CloudNode::new_fully_upgraded).collect(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+
+        // pretend the storage quota is a small fraction of the plain save
+        let quota = json.len() / 4;
+        let mut saved = None;
+        write_save(&json, |data: &str| {
+            if data.len() > quota {
+                return Err("quota exceeded");
+            }
+            saved = Some(data.to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        let saved = saved.unwrap();
+        assert!(saved.starts_with(COMPRESSED_PREFIX));
+        let loaded: WorldState = serde_json::from_str(&decode_save(&saved).unwrap()).unwrap();
+        assert_eq!(loaded, state);
+    }
+
+    #[test]
+    fn test_write_save_reports_failure() {
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        let mut attempts = 0;
+        let result = write_save(&json, |_: &str| {
+            attempts += 1;
+            Err("quota exceeded")
+        });
+        assert_eq!(result, Err("quota exceeded"));
+        // retried once in compressed form
+        assert_eq!(attempts, 2);
+        assert!(compress_save(&json).len() < json.len());
+    }
+}
@@ -0,0 +1,198 @@
+//! Module for the score of a game,
+//! a single number for comparing runs with one another.
+//!
+//! The formula is versioned (see [`SCORE_VERSION`]),
+//! and it must never change within a version,
+//! so that scores shared by players stay comparable.
+//!
+//! # Version 2
+//!
+//! As version 1, plus:
+//!
+//! - the net worth (see [`WorldState::net_worth`])
+//!   gives `1000 × log10(1 + dollars)` points,
+//!   or none if it is negative.
+//!
+//! # Version 1
+//!
+//! - each service tier gives `1000 × log10(1 + total ops)` points,
+//!   weighted by 1, 2, 4, 8 and 16 from the base to the legendary tier;
+//! - the money earned gives `2000 × log10(1 + dollars earned)` points;
+//! - 10 points are taken for every full minute of game time,
+//!   so that faster runs score higher.
+//!
+//! Each contribution is rounded down to a whole number of points,
+//! and the score never goes below zero.
+//! The logarithms keep every contribution well within range
+//! however many ops or dollars there are.
+
+use crate::{Money, ServiceKind, WorldState, TIME_UNITS_PER_MINUTE};
+
+/// the version of the score formula in use
+pub const SCORE_VERSION: u32 = 2;
+
+/// the weight of the ops of each service tier
+pub const SCORE_OP_WEIGHTS: [u64; ServiceKind::ALL.len()] = [1, 2, 4, 8, 16];
+
+/// the points per order of magnitude of the ops of a tier
+const POINTS_PER_OP_MAGNITUDE: f64 = 1_000.;
+
+/// the points per order of magnitude of the dollars earned
+const POINTS_PER_EARNED_MAGNITUDE: f64 = 2_000.;
+
+/// the points per order of magnitude of the net worth in dollars
+const POINTS_PER_NET_WORTH_MAGNITUDE: f64 = 1_000.;
+
+/// the points taken for each full minute of game time
+const POINTS_PER_MINUTE: u64 = 10;
+
+/// The components of the score of a game.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ScoreBreakdown {
+    /// the points given by the ops of each service tier
+    pub ops: [u64; ServiceKind::ALL.len()],
+    /// the points given by the money earned
+    pub earned: u64,
+    /// the points given by the net worth
+    pub net_worth: u64,
+    /// the points taken for the time played
+    pub time_penalty: u64,
+}
+
+impl ScoreBreakdown {
+    /// Calculate the score of the given game.
+    pub fn of(state: &WorldState) -> Self {
+        let ops = ServiceKind::ALL.map(|kind| {
+            let total = state.service_by_kind(kind).total.0.max(0);
+            log_points(total as f64, POINTS_PER_OP_MAGNITUDE)
+                .saturating_mul(SCORE_OP_WEIGHTS[kind as usize])
+        });
+        let dollars = |money: Money| money.to_millicents().max(0) as f64 / 100_000.;
+        Self {
+            ops,
+            earned: log_points(dollars(state.earned), POINTS_PER_EARNED_MAGNITUDE),
+            net_worth: log_points(dollars(state.net_worth()), POINTS_PER_NET_WORTH_MAGNITUDE),
+            time_penalty: (state.time / TIME_UNITS_PER_MINUTE).saturating_mul(POINTS_PER_MINUTE),
+        }
+    }
+
+    /// The score itself.
+    pub fn total(&self) -> u64 {
+        self.ops
+            .iter()
+            .fold(
+                self.earned.saturating_add(self.net_worth),
+                |total, &points| total.saturating_add(points),
+            )
+            .saturating_sub(self.time_penalty)
+    }
+}
+
+/// `scale × log10(1 + value)`, rounded down.
+///
+/// A tiny margin is added before rounding
+/// so that exact powers of ten are never rounded down by a whole point.
+fn log_points(value: f64, scale: f64) -> u64 {
+    ((1. + value).log10() * scale + 1e-6).floor() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoreBreakdown;
+    use crate::{Money, Ops, ServiceKind, WorldState, TIME_UNITS_PER_MINUTE};
+
+    fn state(ops: [i64; 5], earned: Money, minutes: u64) -> WorldState {
+        let mut state = WorldState {
+            earned,
+            time: minutes * TIME_UNITS_PER_MINUTE,
+            ..Default::default()
+        };
+        for (kind, ops) in ServiceKind::ALL.into_iter().zip(ops) {
+            state.service_by_kind_mut(kind).total = Ops(ops);
+        }
+        state
+    }
+
+    /// The formula must not change within a version:
+    /// update [`SCORE_VERSION`](super::SCORE_VERSION) instead.
+    #[test]
+    fn test_score_v2() {
+        // a new game has $10 and a bare node worth $2000
+        let cases = [
+            // a new game
+            (state([0; 5], Money::zero(), 0), [0; 5], 0, 3_303, 0, 3_303),
+            // exact powers of ten
+            (
+                WorldState {
+                    funds: Money::dollars(7_999),
+                    ..state([999, 99, 9, 0, 0], Money::dollars(999), 30)
+                },
+                [3_000, 4_000, 4_000, 0, 0],
+                6_000,
+                4_000,
+                300,
+                20_700,
+            ),
+            // in between
+            (
+                state([25_000, 3_000_000, 0, 0, 0], Money::cents(12_345), 62),
+                [4_397, 12_954, 0, 0, 0],
+                4_189,
+                3_303,
+                620,
+                24_223,
+            ),
+            // slow runs do not go negative
+            (
+                state([9, 0, 0, 0, 0], Money::zero(), 501),
+                [1_000, 0, 0, 0, 0],
+                0,
+                3_303,
+                5_010,
+                0,
+            ),
+            // negative values count as nothing
+            (
+                WorldState {
+                    funds: Money::dollars(-3_000),
+                    ..state([-5, 0, 0, 0, 0], Money::dollars(-20), 0)
+                },
+                [0; 5],
+                0,
+                0,
+                0,
+                0,
+            ),
+        ];
+        for (state, ops, earned, net_worth, time_penalty, total) in cases {
+            let score = ScoreBreakdown::of(&state);
+            assert_eq!(
+                score,
+                ScoreBreakdown {
+                    ops,
+                    earned,
+                    net_worth,
+                    time_penalty
+                }
+            );
+            assert_eq!(score.total(), total);
+            assert_eq!(state.score(), total);
+        }
+    }
+
+    #[test]
+    fn test_score_extremes() {
+        let state = state([i64::MAX; 5], Money::millicents(i64::MAX), 0);
+        let score = ScoreBreakdown::of(&state);
+        assert_eq!(score.ops, [18_964, 37_928, 75_856, 151_712, 303_424]);
+        assert_eq!(score.earned, 27_929);
+        assert_eq!(score.net_worth, 3_303);
+        assert_eq!(score.total(), 619_116);
+
+        let state = WorldState {
+            time: u64::MAX,
+            ..state
+        };
+        assert_eq!(state.score(), 0);
+    }
+}
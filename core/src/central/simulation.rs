@@ -0,0 +1,498 @@
+//! Module for running the simulation away from the view,
+//! such as in a web worker (see the `worker` feature),
+//! and for the messages exchanged with it.
+//!
+//! The view sends the ticks of its game watch and the player's actions,
+//! and the simulation answers with frames carrying
+//! what the view reads from the engine (see [`EngineMirror`]),
+//! the load of the nodes,
+//! and the parts of the world state which keep changing (see [`ViewState`])
+//! whenever what the player sees changed
+//! (see [`ui_digest`] and [`card_digest`]).
+//! The whole world state only goes along
+//! when the game starts or its layout changes (see [`layout_digest`]),
+//! such as when a card is used or a customer arrives.
+//! For saving, the simulation serializes it when the engine asks for it,
+//! leaving the view to write it to local storage.
+//!
+//! The view applies each frame to an engine of its own
+//! (see [`SimulationFrame::apply`]),
+//! which then answers the same questions as the simulated engine
+//! without ever processing events.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameConfig, Memory, Money, PlayerAction, ServiceKind, Time, WorldState,
+    TIME_UNITS_PER_MILLISECOND,
+};
+
+use super::{
+    digest::{card_digest, layout_digest, ui_digest},
+    engine::{CloudNode, EngineMirror, GameEngine, NodeLifetime},
+    insurance::InsurancePolicy,
+    report::PeriodSnapshot,
+    revenue::CustomerLedger,
+    state::{Electricity, Loan, ServiceInfo, ServiceLevelAgreement, TrafficClass, TrafficCounters},
+};
+
+/// A message to the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SimulationInput {
+    /// Start simulating the given world state,
+    /// drawing random numbers from the given seed if any
+    /// (e.g. for the daily challenge).
+    Start {
+        state: Box<WorldState>,
+        seed: Option<u64>,
+    },
+    /// Advance the game by the given wall-clock milliseconds.
+    Tick { elapsed: u32 },
+    /// Apply a player action.
+    Action(PlayerAction),
+    /// Serialize the world state for saving right away.
+    Save,
+}
+
+/// A message from the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SimulationOutput {
+    /// What the view needs to render the game.
+    Frame(Box<SimulationFrame>),
+    /// The outcome of a player action,
+    /// with the reason why it could not be applied if it failed.
+    ActionDone {
+        action: PlayerAction,
+        result: Result<(), String>,
+    },
+    /// The world state serialized for saving
+    /// (see [`WorldState::to_save_json`]).
+    Save {
+        /// the serialized world state
+        json: String,
+        /// whether it goes to the slot of the daily challenge
+        daily: bool,
+        /// the game time of the save
+        time: Time,
+    },
+}
+
+/// What the view needs from the simulation to render the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationFrame {
+    /// the game time
+    pub time: Time,
+    /// the parts of the world state which keep changing,
+    /// only if what the player sees changed
+    pub view: Option<Box<ViewState>>,
+    /// the whole world state,
+    /// only if the game just started or its layout changed
+    pub state: Option<Box<WorldState>>,
+    /// the transient load of each node,
+    /// which is left out of the serialized world state
+    pub nodes: Vec<NodeLoad>,
+    /// what the view reads from the engine
+    pub mirror: EngineMirror,
+}
+
+impl SimulationFrame {
+    /// Bring the view's engine and world state up to date.
+    pub fn apply(self, engine: &mut GameEngine, state: &mut WorldState) {
+        if let Some(new_state) = self.state {
+            *state = *new_state;
+        }
+        if let Some(view) = self.view {
+            view.apply_to(state);
+        }
+        state.time = self.time;
+        for (node, load) in state.nodes.iter_mut().zip(self.nodes) {
+            load.apply_to(node);
+        }
+        engine.apply_mirror(self.mirror);
+    }
+}
+
+/// The parts of the world state which keep changing while the game runs,
+/// such as the funds, the ops of each service and the electricity bills.
+///
+/// Everything else changes only now and then (see [`layout_digest`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    pub funds: Money,
+    pub spent: Money,
+    pub earned: Money,
+    pub demand: f64,
+    pub demand_rate: f64,
+    pub peak_demand: f64,
+    /// the information of each service, by [`ServiceKind::ALL`]
+    pub services: [ServiceInfo; ServiceKind::ALL.len()],
+    pub requests_dropped: u64,
+    pub requests_failed: u64,
+    pub attacks_mitigated: u64,
+    pub traffic: [TrafficCounters; TrafficClass::ALL.len()],
+    pub research_progress: u64,
+    pub electricity: Electricity,
+    pub total_payroll: Money,
+    pub dos_immune_until: Time,
+    pub demand_boost_until: Time,
+    pub loan: Option<Loan>,
+    pub insolvent_since: Time,
+    pub period_snapshot: Option<PeriodSnapshot>,
+    pub slas: Vec<ServiceLevelAgreement>,
+    pub insurance: Option<InsurancePolicy>,
+    pub customer_revenue: CustomerLedger,
+}
+
+impl ViewState {
+    pub fn of(state: &WorldState) -> Self {
+        ViewState {
+            funds: state.funds,
+            spent: state.spent,
+            earned: state.earned,
+            demand: state.demand,
+            demand_rate: state.demand_rate,
+            peak_demand: state.peak_demand,
+            services: ServiceKind::ALL.map(|kind| *state.service_by_kind(kind)),
+            requests_dropped: state.requests_dropped,
+            requests_failed: state.requests_failed,
+            attacks_mitigated: state.attacks_mitigated,
+            traffic: state.traffic,
+            research_progress: state.research_progress,
+            electricity: state.electricity.clone(),
+            total_payroll: state.total_payroll,
+            dos_immune_until: state.dos_immune_until,
+            demand_boost_until: state.demand_boost_until,
+            loan: state.loan.clone(),
+            insolvent_since: state.insolvent_since,
+            period_snapshot: state.period_snapshot.clone(),
+            slas: state.slas.clone(),
+            insurance: state.insurance.clone(),
+            customer_revenue: state.customer_revenue.clone(),
+        }
+    }
+
+    pub fn apply_to(self, state: &mut WorldState) {
+        state.funds = self.funds;
+        state.spent = self.spent;
+        state.earned = self.earned;
+        state.demand = self.demand;
+        state.demand_rate = self.demand_rate;
+        state.peak_demand = self.peak_demand;
+        for (kind, service) in ServiceKind::ALL.into_iter().zip(self.services) {
+            *state.service_by_kind_mut(kind) = service;
+        }
+        state.requests_dropped = self.requests_dropped;
+        state.requests_failed = self.requests_failed;
+        state.attacks_mitigated = self.attacks_mitigated;
+        state.traffic = self.traffic;
+        state.research_progress = self.research_progress;
+        state.electricity = self.electricity;
+        state.total_payroll = self.total_payroll;
+        state.dos_immune_until = self.dos_immune_until;
+        state.demand_boost_until = self.demand_boost_until;
+        state.loan = self.loan;
+        state.insolvent_since = self.insolvent_since;
+        state.period_snapshot = self.period_snapshot;
+        state.slas = self.slas;
+        state.insurance = self.insurance;
+        state.customer_revenue = self.customer_revenue;
+    }
+}
+
+/// The transient load of a node,
+/// and what it has done so far.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeLoad {
+    pub processing: u32,
+    pub ram_usage: Memory,
+    pub ram_reserved: Memory,
+    pub restarting_until: Time,
+    pub lifetime: NodeLifetime,
+}
+
+impl NodeLoad {
+    pub fn of(node: &CloudNode) -> Self {
+        NodeLoad {
+            processing: node.processing,
+            ram_usage: node.ram_usage,
+            ram_reserved: node.ram_reserved,
+            restarting_until: node.restarting_until,
+            lifetime: node.lifetime,
+        }
+    }
+
+    pub fn apply_to(self, node: &mut CloudNode) {
+        node.processing = self.processing;
+        node.ram_usage = self.ram_usage;
+        node.ram_reserved = self.ram_reserved;
+        node.restarting_until = self.restarting_until;
+        node.lifetime = self.lifetime;
+    }
+}
+
+/// The simulation of a game,
+/// driven by [`SimulationInput`] messages.
+#[derive(Debug, Default)]
+pub struct Simulation {
+    /// the engine and the world state,
+    /// once started
+    game: Option<(GameEngine, WorldState)>,
+    /// the digest of what the player sees as of the last frame
+    digest: u64,
+    /// the digest of the layout of the world state as of the last frame
+    /// (see [`layout_digest`])
+    layout: u64,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The world state being simulated, once started.
+    pub fn state(&self) -> Option<&WorldState> {
+        self.game.as_ref().map(|(_, state)| state)
+    }
+
+    /// Handle a message,
+    /// given the wall-clock time in milliseconds since the Unix epoch
+    /// to record on saves.
+    ///
+    /// Messages other than [`SimulationInput::Start`]
+    /// are ignored until the simulation is started.
+    pub fn handle(&mut self, input: SimulationInput, now: u64) -> Vec<SimulationOutput> {
+        let mut out = Vec::new();
+        if let SimulationInput::Start { state, seed } = input {
+            let config = GameConfig::new(state.difficulty);
+            let mut engine = match seed {
+                Some(seed) => GameEngine::with_config_seeded(config, seed),
+                None => GameEngine::with_config(config),
+            };
+            engine.bootstrap_events(&state);
+            self.game = Some((engine, *state));
+            self.push_frame(&mut out, true);
+            return out;
+        }
+
+        let Some((engine, state)) = &mut self.game else {
+            return out;
+        };
+        match input {
+            SimulationInput::Tick { elapsed } => {
+                let time = state.time + (elapsed * TIME_UNITS_PER_MILLISECOND) as Time;
+                engine.update(state, time);
+                let save = engine.take_save_request();
+                self.push_frame(&mut out, false);
+                if save {
+                    self.push_save(&mut out, now);
+                }
+            }
+            SimulationInput::Action(action) => {
+                let result = engine
+                    .apply_action(state, action.clone())
+                    .map_err(|e| e.to_string());
+                out.push(SimulationOutput::ActionDone { action, result });
+                self.push_frame(&mut out, false);
+            }
+            SimulationInput::Save => self.push_save(&mut out, now),
+            SimulationInput::Start { .. } => {}
+        }
+        out
+    }
+
+    /// Add a frame to the output if the view has something to catch up on.
+    ///
+    /// The frame carries the whole world state if its layout changed
+    /// or `always` is true,
+    /// and otherwise only the view state if what the player sees changed.
+    fn push_frame(&mut self, out: &mut Vec<SimulationOutput>, always: bool) {
+        let Some((engine, state)) = &mut self.game else {
+            return;
+        };
+        let mut hasher = DefaultHasher::new();
+        (ui_digest(state, engine), card_digest(state, engine)).hash(&mut hasher);
+        let digest = hasher.finish();
+        let layout = layout_digest(state);
+        let mirror = engine.mirror(state);
+        let relayout = always || layout != self.layout;
+        let changed = relayout || digest != self.digest;
+        if !(changed || mirror.has_news()) {
+            return;
+        }
+        self.digest = digest;
+        self.layout = layout;
+        out.push(SimulationOutput::Frame(Box::new(SimulationFrame {
+            time: state.time,
+            view: (changed && !relayout).then(|| Box::new(ViewState::of(state))),
+            state: relayout.then(|| Box::new(state.clone())),
+            nodes: state.nodes.iter().map(NodeLoad::of).collect(),
+            mirror,
+        })));
+    }
+
+    fn push_save(&mut self, out: &mut Vec<SimulationOutput>, now: u64) {
+        let Some((_, state)) = &mut self.game else {
+            return;
+        };
+        state.saved_at = now;
+        match state.to_save_json() {
+            Ok(json) => out.push(SimulationOutput::Save {
+                json,
+                daily: state.daily.is_some(),
+                time: state.time,
+            }),
+            Err(e) => log::error!("Failed to serialize game state: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Simulation, SimulationInput, SimulationOutput};
+    use crate::{
+        central::{
+            digest::{card_digest, ui_digest},
+            engine::GameEngine,
+            testing::WorldStateBuilder,
+        },
+        Money, PlayerAction, ServiceKind, WorldState,
+    };
+
+    /// Send a message to the simulation as a worker would receive it,
+    /// and apply the frames to the view's engine and world state.
+    fn send(
+        simulation: &mut Simulation,
+        input: SimulationInput,
+        engine: &mut GameEngine,
+        state: &mut WorldState,
+    ) -> Vec<SimulationOutput> {
+        let input = serde_json::from_str(&serde_json::to_string(&input).unwrap()).unwrap();
+        let outputs = simulation.handle(input, 0);
+        let outputs: Vec<SimulationOutput> =
+            serde_json::from_str(&serde_json::to_string(&outputs).unwrap()).unwrap();
+        for output in &outputs {
+            if let SimulationOutput::Frame(frame) = output {
+                (**frame).clone().apply(engine, state);
+            }
+        }
+        outputs
+    }
+
+    #[test]
+    fn test_simulation_frames_follow_the_engine() {
+        let initial = WorldStateBuilder::new()
+            .funds(Money::dollars(100))
+            .with_customer(ServiceKind::Base)
+            .build();
+        let mut simulation = Simulation::new();
+        let mut engine = GameEngine::new();
+        let mut state = WorldState::default();
+        assert!(send(
+            &mut simulation,
+            SimulationInput::Tick { elapsed: 50 },
+            &mut engine,
+            &mut state
+        )
+        .is_empty());
+
+        let start = SimulationInput::Start {
+            state: Box::new(initial.clone()),
+            seed: Some(923),
+        };
+        send(&mut simulation, start, &mut engine, &mut state);
+        assert_eq!(state.funds, Money::dollars(100));
+
+        // the view sees what the simulated engine would show,
+        // from the view state alone while the layout stays the same
+        let mut views = 0;
+        for _ in 0..200 {
+            let outputs = send(
+                &mut simulation,
+                SimulationInput::Tick { elapsed: 50 },
+                &mut engine,
+                &mut state,
+            );
+            for output in &outputs {
+                if let SimulationOutput::Frame(frame) = output {
+                    assert!(frame.state.is_none(), "unexpected world state in {frame:?}");
+                    views += usize::from(frame.view.is_some());
+                }
+            }
+            let (simulated_engine, simulated) = simulation.game.as_ref().unwrap();
+            assert_eq!(
+                ui_digest(&state, &engine),
+                ui_digest(simulated, simulated_engine)
+            );
+            assert_eq!(
+                card_digest(&state, &engine),
+                card_digest(simulated, simulated_engine)
+            );
+        }
+        assert!(views > 0);
+        let (simulated_engine, simulated) = simulation.game.as_ref().unwrap();
+        assert_eq!(simulated.time, 100_000);
+        assert!(state.time <= simulated.time);
+        assert!(engine.drop_rate == simulated_engine.drop_rate);
+
+        // actions are applied by the simulation, and failures reported back
+        let outputs = send(
+            &mut simulation,
+            SimulationInput::Action(PlayerAction::AddRack),
+            &mut engine,
+            &mut state,
+        );
+        assert!(matches!(
+            &outputs[0],
+            SimulationOutput::ActionDone { result: Err(_), .. }
+        ));
+        let outputs = send(
+            &mut simulation,
+            SimulationInput::Action(PlayerAction::OpClick {
+                kind: ServiceKind::Base,
+                amount: 1,
+            }),
+            &mut engine,
+            &mut state,
+        );
+        assert!(matches!(
+            &outputs[0],
+            SimulationOutput::ActionDone { result: Ok(()), .. }
+        ));
+
+        // the whole world state crosses over when the layout changes
+        let outputs = send(
+            &mut simulation,
+            SimulationInput::Action(PlayerAction::RenameCompany {
+                name: "Nimbus Inc.".to_string(),
+            }),
+            &mut engine,
+            &mut state,
+        );
+        let [SimulationOutput::ActionDone { .. }, SimulationOutput::Frame(frame)] = &outputs[..]
+        else {
+            panic!("expected an action and a frame, got {outputs:?}");
+        };
+        assert!(frame.state.is_some());
+        assert_eq!(state.company_name, "Nimbus Inc.");
+
+        // and is serialized when saving
+        let outputs = send(
+            &mut simulation,
+            SimulationInput::Save,
+            &mut engine,
+            &mut state,
+        );
+        let [SimulationOutput::Save { json, daily, time }] = &outputs[..] else {
+            panic!("expected a save, got {outputs:?}");
+        };
+        assert!(!daily);
+        assert_eq!(*time, 100_000);
+        let saved: WorldState = serde_json::from_str(json).unwrap();
+        assert_eq!(saved.funds, simulation.state().unwrap().funds);
+    }
+}
@@ -0,0 +1,160 @@
+//! Module for the player's staff,
+//! who perform ops of a service at a steady pace
+//! in exchange for a salary paid every bill period.
+//!
+//! Staff requests are made like the player's own
+//! (they belong to no cloud user),
+//! but are counted as their own class of traffic.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{t, Money, ServiceKind, Time};
+
+/// the time between each round of ops performed by the staff (1 second)
+pub const STAFF_WORK_PERIOD: Time = 10_000;
+
+/// the maximum number of staff members which can be hired
+pub const MAX_STAFF: usize = 12;
+
+/// The seniority of a staff member,
+/// which determines how much they cost and how much they do.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StaffTier {
+    Intern,
+    Junior,
+    Senior,
+    Principal,
+}
+
+impl StaffTier {
+    /// All staff tiers, from the least to the most senior.
+    pub const ALL: [StaffTier; 4] = [
+        StaffTier::Intern,
+        StaffTier::Junior,
+        StaffTier::Senior,
+        StaffTier::Principal,
+    ];
+
+    /// The name of the position.
+    pub fn name(self) -> &'static str {
+        match self {
+            StaffTier::Intern => t!("staff.intern"),
+            StaffTier::Junior => t!("staff.junior"),
+            StaffTier::Senior => t!("staff.senior"),
+            StaffTier::Principal => t!("staff.principal"),
+        }
+    }
+
+    /// The one-off cost of hiring a member of this tier.
+    pub const fn hiring_cost(self) -> Money {
+        match self {
+            StaffTier::Intern => Money::dollars(40),
+            StaffTier::Junior => Money::dollars(400),
+            StaffTier::Senior => Money::dollars(3_000),
+            StaffTier::Principal => Money::dollars(25_000),
+        }
+    }
+
+    /// The salary paid to a member of this tier every bill period.
+    pub const fn salary(self) -> Money {
+        match self {
+            StaffTier::Intern => Money::dollars(4),
+            StaffTier::Junior => Money::dollars(16),
+            StaffTier::Senior => Money::dollars(80),
+            StaffTier::Principal => Money::dollars(400),
+        }
+    }
+
+    /// The severance paid when firing a member of this tier,
+    /// which is one salary.
+    pub const fn severance(self) -> Money {
+        self.salary()
+    }
+
+    /// The number of ops which a member of this tier performs every second.
+    pub const fn ops_per_second(self) -> u32 {
+        match self {
+            StaffTier::Intern => 2,
+            StaffTier::Junior => 10,
+            StaffTier::Senior => 60,
+            StaffTier::Principal => 400,
+        }
+    }
+}
+
+impl fmt::Display for StaffTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A member of the player's staff.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StaffMember {
+    /// the seniority of the member
+    pub tier: StaffTier,
+    /// the service which the member performs ops of
+    pub service: ServiceKind,
+    /// the time at which the member was hired
+    pub hired_at: Time,
+}
+
+/// The total salary of the given staff for one bill period.
+pub fn payroll(staff: &[StaffMember]) -> Money {
+    staff
+        .iter()
+        .fold(Money::zero(), |total, member| total + member.tier.salary())
+}
+
+/// The number of ops of each service
+/// which the given staff perform every second,
+/// indexed by [`ServiceKind::to_code`].
+pub fn ops_per_second(staff: &[StaffMember]) -> [u32; ServiceKind::ALL.len()] {
+    let mut ops = [0; ServiceKind::ALL.len()];
+    for member in staff {
+        ops[member.service.to_code() as usize] += member.tier.ops_per_second();
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ops_per_second, payroll, StaffMember, StaffTier};
+    use crate::{Money, ServiceKind};
+
+    fn member(tier: StaffTier, service: ServiceKind) -> StaffMember {
+        StaffMember {
+            tier,
+            service,
+            hired_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_staff_totals() {
+        let staff = [
+            member(StaffTier::Intern, ServiceKind::Base),
+            member(StaffTier::Junior, ServiceKind::Base),
+            member(StaffTier::Senior, ServiceKind::Epic),
+        ];
+        assert_eq!(payroll(&staff), Money::dollars(4 + 16 + 80));
+        assert_eq!(payroll(&[]), Money::zero());
+        assert_eq!(ops_per_second(&staff), [12, 0, 60, 0, 0]);
+    }
+
+    #[test]
+    fn test_tiers_pay_off_in_order() {
+        // more senior staff cost more but do more per dollar
+        for pair in StaffTier::ALL.windows(2) {
+            let [junior, senior] = [pair[0], pair[1]];
+            assert!(senior.hiring_cost() > junior.hiring_cost());
+            assert!(senior.salary() > junior.salary());
+            let ops_per_dollar = |tier: StaffTier| {
+                tier.ops_per_second() as f64 / tier.salary().to_millicents() as f64
+            };
+            assert!(ops_per_dollar(senior) > ops_per_dollar(junior));
+        }
+    }
+}
@@ -0,0 +1,2625 @@
+//! Module for the full game state
+//!
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    display::to_seconds, CloudUserSpec, Cost, Memory, Money, Ops, ServiceKind, UserKind,
+    GAME_VERSION, TIME_UNITS_PER_MINUTE,
+};
+
+use super::{
+    away::AwayBonus,
+    cards::{
+        all::{ID_WIN, ID_WIN_EPILOGUE},
+        CardSpec,
+    },
+    cloud_user::UserSpecRegistry,
+    config::{Difficulty, GameConfig},
+    daily::DailyChallenge,
+    engine::{CloudNode, ELECTRICITY_BILL_PERIOD, RACK_CAPACITY, SOFTWARE_LEVELS},
+    insurance::{InsurancePolicy, INSURANCE_COOLDOWN},
+    narrative::NarrativeLog,
+    queue::{RequestEvent, Time},
+    report::{PeriodReport, PeriodSnapshot},
+    revenue::{CustomerLedger, TopCustomer, TOP_CUSTOMERS},
+    score::{ScoreBreakdown, SCORE_VERSION},
+    staff::StaffMember,
+    streak::{loyalty_discounted, STREAK_DEMAND_BOOST_PERCENT},
+    tariff::{ConsumptionBuckets, Tariff, TARIFF_SWITCH_COOLDOWN},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldState {
+    /// the current timestamp
+    pub time: Time,
+
+    /// the difficulty chosen when the game started
+    #[serde(default, skip_serializing_if = "is_default_difficulty")]
+    pub difficulty: Difficulty,
+
+    /// the name of the player's company
+    #[serde(default = "default_company_name")]
+    pub company_name: String,
+
+    /// the player's current available funds
+    pub funds: Money,
+
+    /// the player's total money spent
+    pub spent: Money,
+
+    /// the total money earned by cloud service provisioning
+    pub earned: Money,
+
+    /// a measurement of demand for the services
+    /// (a higher number means more request inflow
+    /// post service & price adjustments),
+    /// shown to the player as visibility
+    /// (see [`Topic::Visibility`](crate::i18n::Topic::Visibility))
+    pub demand: f64,
+
+    /// a measurement of demand for the services
+    /// (a higher number means more client inflow)
+    #[serde(default = "demand_rate_default")]
+    pub demand_rate: f64,
+
+    /// the highest demand reached so far
+    #[serde(default, skip_serializing_if = "is_zero_f64")]
+    pub peak_demand: f64,
+
+    /// the number of upgrades done to the cloud service software
+    pub software_level: u8,
+
+    /// the number of upgrades done to caching
+    /// (higher means more caching)
+    pub cache_level: u8,
+
+    /// the share of the cache in use at the current caching level,
+    /// which scales both its memory reserve and its hit rate
+    #[serde(
+        default = "cache_fraction_default",
+        skip_serializing_if = "is_full_cache"
+    )]
+    pub cache_fraction: f32,
+
+    /// the routing level used for requests
+    #[serde(default, skip_serializing_if = "is_default_routing_level")]
+    pub routing_level: RoutingLevel,
+
+    /// the number of upgrades done to the network uplink
+    /// (see [`bandwidth_capacity`](super::bandwidth::bandwidth_capacity))
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub bandwidth_level: u8,
+
+    /// number of operation requests performed per player click
+    pub ops_per_click: u32,
+
+    /// the op counts of the base service
+    pub base_service: ServiceInfo,
+
+    /// the op counts of the super service
+    pub super_service: ServiceInfo,
+
+    /// the op counts of the epic service
+    pub epic_service: ServiceInfo,
+
+    /// the total number of requests dropped
+    /// due to lack of system capacity
+    pub requests_dropped: u64,
+
+    /// the total number of requests discarded
+    /// for being bad
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub requests_failed: u64,
+
+    /// the total number of bad requests
+    /// caught late and cut short while processing
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub attacks_mitigated: u64,
+
+    /// the op counts of the awesome service
+    pub awesome_service: ServiceInfo,
+
+    /// the op counts of the legendary service
+    #[serde(
+        default = "default_legendary_service",
+        skip_serializing_if = "is_default_legendary_service"
+    )]
+    pub legendary_service: ServiceInfo,
+
+    /// the requests fulfilled and dropped
+    /// for each [`TrafficClass`]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_traffic",
+        skip_serializing_if = "is_default_traffic"
+    )]
+    pub traffic: [TrafficCounters; TrafficClass::ALL.len()],
+
+    /// the number of research ops fulfilled
+    /// since the last research-discounted card was used
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub research_progress: u64,
+
+    /// all active client specifications, sorted by ID
+    pub user_specs: UserSpecRegistry,
+
+    /// the ID for the next user specification,
+    /// so that the IDs of removed users are never reused
+    /// (see [`WorldState::add_user_spec`])
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub next_user_spec_id: u32,
+
+    /// electricity cost, consumption, and due payments
+    pub electricity: Electricity,
+
+    /// all server nodes
+    pub nodes: Vec<CloudNode>,
+
+    /// whether the player can see the service demand estimate
+    /// in the business panel
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_see_demand: bool,
+
+    /// whether the player can see the energy consumption estimate
+    /// in the hardware panel
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_see_energy_consumption: bool,
+
+    /// whether the player can see the request fulfillment & drop rates
+    /// in the hardware panel
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_see_request_rates: bool,
+
+    /// how much of the hardware panel is revealed to the player,
+    /// from [`HARDWARE_PANEL_NODES`] to [`HARDWARE_PANEL_PURCHASES`]
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub hardware_panel_level: u8,
+
+    /// whether the player has unlocked
+    /// trading ops at the op exchange
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_exchange_ops: bool,
+
+    /// whether the player has unlocked
+    /// paying electricity bills automatically
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_auto_pay: bool,
+
+    /// whether the player has unlocked
+    /// choosing among energy providers
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_choose_tariff: bool,
+
+    /// whether the player has unlocked
+    /// hiring staff
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_hire_staff: bool,
+
+    /// the staff hired by the player,
+    /// from the longest serving
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub staff: Vec<StaffMember>,
+
+    /// the total money ever paid in staff salaries
+    #[serde(default, skip_serializing_if = "is_zero_money")]
+    pub total_payroll: Money,
+
+    /// whether electricity bills are paid automatically
+    /// as soon as they are emitted, funds permitting
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub auto_pay: bool,
+
+    /// whether the player has unlocked
+    /// buying more cloud nodes
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_buy_nodes: bool,
+
+    /// whether the player has unlocked
+    /// buying more cloud racks
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_buy_racks: bool,
+
+    /// whether the player has unlocked
+    /// buying more datacenters
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub can_buy_datacenters: bool,
+
+    /// the rate at which to detect bad requests before routing them
+    #[serde(default, skip_serializing_if = "is_zero_f32")]
+    pub spam_protection: f32,
+
+    /// the time until which no new attackers appear,
+    /// after upgrading the spam protection
+    /// (or 0 if there was no upgrade)
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub dos_immune_until: Time,
+
+    /// the time until which demand is boosted,
+    /// as a reward for paying the bills on time
+    /// (see [`StreakReward::DemandBoost`](super::streak::StreakReward::DemandBoost))
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub demand_boost_until: Time,
+
+    /// the indices of the cards
+    /// (per [`ALL_CARDS`](crate::central::cards::ALL_CARDS))
+    /// already used,
+    /// in used time order
+    pub cards_used: Vec<UsedCard>,
+
+    /// the identifiers of the cards which the player is saving for,
+    /// in pinning order (at most [`MAX_PINNED_CARDS`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cards_pinned: Vec<Cow<'static, str>>,
+
+    /// the rescue loan taken by the player, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loan: Option<Loan>,
+
+    /// the time at which funds fell below the bankruptcy threshold
+    /// (or 0 if the player is solvent)
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub insolvent_since: Time,
+
+    /// the counters at the start of the current report period
+    /// (or `None` if the first period has not started yet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period_snapshot: Option<PeriodSnapshot>,
+
+    /// the latest period reports, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reports: Vec<PeriodReport>,
+
+    /// whether the player dismissed the latest period report
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub report_dismissed: bool,
+
+    /// whether the player dismissed the message announcing the win
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub win_dismissed: bool,
+
+    /// whether the player is being offered a bailout
+    /// and has yet to decide what to do
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub bailout_pending: bool,
+
+    /// the narrative events which happened so far,
+    /// including the one awaiting the player's answer
+    #[serde(default, skip_serializing_if = "NarrativeLog::is_empty")]
+    pub narrative: NarrativeLog,
+
+    /// the wall-clock time at which the game was last saved,
+    /// in milliseconds since the Unix epoch
+    /// (or 0 if unknown)
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub saved_at: u64,
+
+    /// the version of the game which last saved this state
+    /// (empty if saved by a version before it was recorded)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub game_version: String,
+
+    /// the bonus offered to the player for returning to the game,
+    /// if not yet used nor expired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub away_bonus: Option<AwayBonus>,
+
+    /// the wall-clock time at which the last away bonus was offered,
+    /// in milliseconds since the Unix epoch
+    /// (or 0 if never)
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub last_away_bonus_at: u64,
+
+    /// the service-level agreements signed by the player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slas: Vec<ServiceLevelAgreement>,
+
+    /// the infrastructure insurance policy taken by the player, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insurance: Option<InsurancePolicy>,
+
+    /// the time at which the last insurance policy was cancelled
+    /// (or `None` if never cancelled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insurance_cancelled_at: Option<Time>,
+
+    /// the revenue brought by each customer
+    #[serde(default, skip_serializing_if = "CustomerLedger::is_empty")]
+    pub customer_revenue: CustomerLedger,
+
+    /// whether the player's clicking was once steady enough
+    /// to likely be automated (only ever noted, never punished)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub autoclicker_suspected: bool,
+
+    /// the daily challenge being played,
+    /// or `None` in a regular game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<DailyChallenge>,
+
+    /// what the player achieved in the runs which ended in bankruptcy,
+    /// kept when starting over
+    #[serde(default, skip_serializing_if = "Achievements::is_empty")]
+    pub achievements: Achievements,
+}
+
+fn demand_rate_default() -> f64 {
+    0.25
+}
+
+fn cache_fraction_default() -> f32 {
+    1.
+}
+
+fn is_full_cache(&x: &f32) -> bool {
+    x == 1.
+}
+
+fn default_company_name() -> String {
+    DEFAULT_COMPANY_NAME.to_string()
+}
+
+fn is_default_difficulty(&difficulty: &Difficulty) -> bool {
+    difficulty == Difficulty::default()
+}
+
+fn is_flat_tariff(&tariff: &Tariff) -> bool {
+    tariff == Tariff::Flat
+}
+
+fn is_empty_buckets(buckets: &ConsumptionBuckets) -> bool {
+    buckets.is_empty()
+}
+
+fn is_default_routing_level(&routing_level: &RoutingLevel) -> bool {
+    routing_level == RoutingLevel::default()
+}
+
+/// Deserialize the traffic counters,
+/// accepting saves from before more traffic classes were added.
+fn deserialize_traffic<'de, D>(
+    deserializer: D,
+) -> Result<[TrafficCounters; TrafficClass::ALL.len()], D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let counters: Vec<TrafficCounters> = Deserialize::deserialize(deserializer)?;
+    let mut traffic = [TrafficCounters::default(); TrafficClass::ALL.len()];
+    for (slot, counters) in traffic.iter_mut().zip(counters) {
+        *slot = counters;
+    }
+    Ok(traffic)
+}
+
+fn is_default_traffic(traffic: &[TrafficCounters; TrafficClass::ALL.len()]) -> bool {
+    traffic
+        .iter()
+        .all(|counters| *counters == TrafficCounters::default())
+}
+
+fn default_legendary_service() -> ServiceInfo {
+    ServiceInfo::new_locked(ServiceKind::Legendary.tier().default_price)
+}
+
+fn is_default_legendary_service(service: &ServiceInfo) -> bool {
+    *service == default_legendary_service()
+}
+
+fn is_false(&b: &bool) -> bool {
+    !b
+}
+
+fn is_zero_u8(&x: &u8) -> bool {
+    x == 0
+}
+
+fn is_zero_u32(&x: &u32) -> bool {
+    x == 0
+}
+
+fn is_zero_u64(&x: &u64) -> bool {
+    x == 0
+}
+
+fn is_zero_ops(&ops: &Ops) -> bool {
+    ops == Ops(0)
+}
+
+fn one_f64() -> f64 {
+    1.
+}
+
+fn is_one_f64(&x: &f64) -> bool {
+    x == 1.
+}
+
+fn is_zero_money(&money: &Money) -> bool {
+    money == Money::zero()
+}
+
+fn is_zero_i64(&x: &i64) -> bool {
+    x == 0
+}
+
+fn is_zero_f32(&x: &f32) -> bool {
+    x == 0.
+}
+
+fn is_zero_f64(&x: &f64) -> bool {
+    x == 0.
+}
+
+/// Hardware panel level: only the nodes are shown
+pub const HARDWARE_PANEL_NODES: u8 = 0;
+/// Hardware panel level: CPU and memory load bars are shown
+pub const HARDWARE_PANEL_LOAD_BARS: u8 = 1;
+/// Hardware panel level: node upgrades are shown
+pub const HARDWARE_PANEL_UPGRADES: u8 = 2;
+/// Hardware panel level: equipment purchases are shown
+pub const HARDWARE_PANEL_PURCHASES: u8 = 3;
+
+/// The time after a bill is issued
+/// at which the system enters powersave mode if it remains unpaid
+pub const POWERSAVE_GRACE_PERIOD: Time = ELECTRICITY_BILL_PERIOD - 100_000;
+
+/// The research ops needed for the maximum research discount
+pub const RESEARCH_FOR_MAX_DISCOUNT: u64 = 50_000;
+
+/// The maximum fraction of card operation costs
+/// which can be discounted by research progress
+pub const MAX_RESEARCH_DISCOUNT: f64 = 0.5;
+
+/// The time window in which price raises add up,
+/// as customers still remember the price before them
+pub const PRICE_RAISE_WINDOW: Time = TIME_UNITS_PER_MINUTE;
+
+/// The share by which the price of a service can be raised
+/// within [`PRICE_RAISE_WINDOW`] before customers start to leave
+pub const CHURN_RAISE_THRESHOLD: f64 = 0.25;
+
+/// The share of customer requests which a service under
+/// a service-level agreement may drop in a bill period
+/// without the player paying a penalty
+pub const SLA_MAX_DROP_RATE: f64 = 0.05;
+
+/// The time after upgrading the spam protection
+/// in which no new attackers appear (10 minutes)
+pub const DOS_IMMUNITY_PERIOD: Time = 10 * TIME_UNITS_PER_MINUTE;
+
+/// The most project cards which can be pinned at once
+pub const MAX_PINNED_CARDS: usize = 3;
+
+pub(crate) const LOCAL_STORAGE_KEY_NAME: &str = "10xCloudChampion_save";
+
+/// the key of the daily challenge save,
+/// kept apart so that it never overwrites the main game
+pub(crate) const DAILY_STORAGE_KEY_NAME: &str = "10xCloudChampion_daily";
+
+impl WorldState {
+    /// Create a new game for the given daily challenge.
+    pub fn new_daily(challenge: DailyChallenge, company_name: String) -> Self {
+        WorldState {
+            company_name,
+            funds: challenge.modifiers.starting_funds,
+            daily: Some(challenge),
+            ..Default::default()
+        }
+    }
+
+    /// The game time left in the daily challenge,
+    /// or `None` in a regular game.
+    pub fn daily_time_left(&self) -> Option<Time> {
+        self.daily
+            .as_ref()
+            .map(|daily| daily.ends_at().saturating_sub(self.time))
+    }
+
+    /// Whether this is a daily challenge which has come to an end.
+    pub fn is_daily_over(&self) -> bool {
+        self.daily
+            .as_ref()
+            .is_some_and(|daily| self.time >= daily.ends_at())
+    }
+
+    /// convenience method to retrieve a cloud node by id
+    pub fn node(&self, id: u32) -> Option<&CloudNode> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+
+    /// convenience method to retrieve a cloud node by id
+    pub fn node_mut(&mut self, id: u32) -> Option<&mut CloudNode> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|index| &mut self.nodes[index])
+    }
+
+    pub fn service_by_kind(&self, kind: ServiceKind) -> &ServiceInfo {
+        match kind {
+            ServiceKind::Base => &self.base_service,
+            ServiceKind::Super => &self.super_service,
+            ServiceKind::Epic => &self.epic_service,
+            ServiceKind::Awesome => &self.awesome_service,
+            ServiceKind::Legendary => &self.legendary_service,
+        }
+    }
+
+    pub fn service_by_kind_mut(&mut self, kind: crate::ServiceKind) -> &mut ServiceInfo {
+        match kind {
+            ServiceKind::Base => &mut self.base_service,
+            ServiceKind::Super => &mut self.super_service,
+            ServiceKind::Epic => &mut self.epic_service,
+            ServiceKind::Awesome => &mut self.awesome_service,
+            ServiceKind::Legendary => &mut self.legendary_service,
+        }
+    }
+
+    /// All cloud services along with their kind,
+    /// from the lowest tier to the highest.
+    pub fn services(&self) -> impl Iterator<Item = (ServiceKind, &ServiceInfo)> {
+        ServiceKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.service_by_kind(kind)))
+    }
+
+    /// Whether the funds went negative,
+    /// which only bills and penalties can bring about.
+    ///
+    /// No purchases can be made while in debt.
+    pub fn is_in_debt(&self) -> bool {
+        self.funds < Money::zero()
+    }
+
+    pub fn can_afford(&self, cost: &Cost) -> bool {
+        debug_assert!(cost.is_non_negative(), "negative cost: {cost:?}");
+        let cost = cost.non_negative();
+        // costs in ops alone can be paid while in debt
+        (cost.money == Money::zero() || (!self.is_in_debt() && self.funds >= cost.money))
+            && self.base_service.available >= cost.base_ops
+            && self.super_service.available >= cost.super_ops
+            && self.epic_service.available >= cost.epic_ops
+            && self.awesome_service.available >= cost.awesome_ops
+            && self.legendary_service.available >= cost.legendary_ops
+    }
+
+    /// Pay the salaries of the staff for one bill period,
+    /// from the longest serving member.
+    ///
+    /// Members whose salary cannot be paid in full quit,
+    /// and are returned.
+    pub fn pay_staff(&mut self) -> Vec<StaffMember> {
+        let mut quit = Vec::new();
+        let funds = &mut self.funds;
+        let spent = &mut self.spent;
+        let total_payroll = &mut self.total_payroll;
+        self.staff.retain(|member| {
+            let salary = member.tier.salary();
+            if *funds < salary {
+                quit.push(*member);
+                return false;
+            }
+            *funds -= salary;
+            *spent += salary;
+            *total_payroll += salary;
+            true
+        });
+        quit
+    }
+
+    /// The funds and the ops available to spend,
+    /// as the most expensive cost which can be afforded.
+    pub fn resources(&self) -> Cost {
+        Cost {
+            money: self.funds,
+            base_ops: self.base_service.available,
+            super_ops: self.super_service.available,
+            epic_ops: self.epic_service.available,
+            awesome_ops: self.awesome_service.available,
+            legendary_ops: self.legendary_service.available,
+        }
+    }
+
+    /// Determine where a request set came from.
+    ///
+    /// Requests from a deleted user specification
+    /// are attributed to the player,
+    /// like it is done for revenue.
+    pub fn traffic_class(&self, event: &RequestEvent, time: Time) -> TrafficClass {
+        let spec = event.user_spec_id.and_then(|id| self.user_spec(id));
+        if event.bad || spec.is_some_and(|spec| spec.is_bad()) {
+            return TrafficClass::Bad;
+        }
+        match spec {
+            None if event.staff => TrafficClass::Staff,
+            None => TrafficClass::Player,
+            Some(spec) if spec.kind == UserKind::Researcher => TrafficClass::Research,
+            Some(spec) if spec.is_paying(time) => TrafficClass::Paying,
+            Some(_) => TrafficClass::Trial,
+        }
+    }
+
+    /// Count customer requests towards the service-level agreement
+    /// of their service, if there is one.
+    pub fn count_sla_requests(&mut self, service: ServiceKind, fulfilled: u64, dropped: u64) {
+        if let Some(sla) = self.slas.iter_mut().find(|sla| sla.service == service) {
+            sla.fulfilled += fulfilled;
+            sla.dropped += dropped;
+        }
+    }
+
+    /// The time from which insurance can be taken again,
+    /// after the cooldown of the last cancellation.
+    pub fn insurance_ready_at(&self) -> Time {
+        self.insurance_cancelled_at
+            .map_or(0, |time| time + INSURANCE_COOLDOWN)
+    }
+
+    /// The fraction by which research progress
+    /// discounts the operation costs of software upgrade and caching cards,
+    /// up to [`MAX_RESEARCH_DISCOUNT`].
+    pub fn research_discount(&self) -> f64 {
+        (self.research_progress as f64 / RESEARCH_FOR_MAX_DISCOUNT as f64).min(1.)
+            * MAX_RESEARCH_DISCOUNT
+    }
+
+    /// The cost of using the given card right now,
+    /// after any research discount.
+    pub fn card_cost(&self, card: &CardSpec) -> Cost {
+        if card.is_research_discounted() {
+            card.cost.discount_ops(self.research_discount())
+        } else {
+            card.cost.clone()
+        }
+    }
+
+    pub fn is_card_used(&self, card_id: &str) -> bool {
+        self.cards_used.iter().any(|c| c.id == card_id)
+    }
+
+    /// Whether the message announcing the win should be shown,
+    /// which lasts until the player dismisses it
+    /// or the win chain moves on.
+    pub fn is_win_announced(&self) -> bool {
+        self.is_card_used(ID_WIN) && !self.is_card_used(ID_WIN_EPILOGUE) && !self.win_dismissed
+    }
+
+    /// The cards used so far, in the order they were used.
+    ///
+    /// `cards_used` itself is kept sorted by card ID.
+    pub fn cards_used_chronological(&self) -> Vec<&UsedCard> {
+        let mut cards: Vec<_> = self.cards_used.iter().collect();
+        cards.sort_by_key(|card| card.time);
+        cards
+    }
+
+    pub fn is_card_pinned(&self, card_id: &str) -> bool {
+        self.cards_pinned.iter().any(|id| id == card_id)
+    }
+
+    /// Get total processing power and memory usage,
+    /// between 0 and 1
+    pub fn total_processing(&self) -> (f32, f32) {
+        let mut cpu = 0;
+        let mut mem = Memory::zero();
+        let mut cpu_capacity = 0;
+        let mut mem_capacity = Memory::zero();
+        for node in &self.nodes {
+            cpu += node.processing;
+            mem += node.ram_usage;
+            cpu_capacity += node.num_cores;
+            mem_capacity += node.ram_capacity;
+        }
+        (
+            (cpu as f32 / cpu_capacity as f32).min(1.),
+            mem.ratio(mem_capacity),
+        )
+    }
+
+    /// Get the memory reserved (mostly for caching)
+    /// and the memory taken by requests over all nodes,
+    /// each between 0 and 1.
+    pub fn memory_breakdown(&self) -> (f32, f32) {
+        let mut reserved = Memory::zero();
+        let mut used = Memory::zero();
+        let mut capacity = Memory::zero();
+        for node in &self.nodes {
+            reserved += node.ram_reserved;
+            used += node.ram_usage;
+            capacity += node.ram_capacity;
+        }
+        if capacity == Memory::zero() {
+            return (0., 0.);
+        }
+        (reserved.ratio(capacity), (used - reserved).ratio(capacity))
+    }
+
+    /// Get the CPU and memory usage of each rack, between 0 and 1.
+    ///
+    /// Nodes are grouped into racks of [`RACK_CAPACITY`],
+    /// except once data centers can be bought,
+    /// at which point each node is a rack of its own.
+    /// CPU usage is relative to the cores usable right now,
+    /// which are fewer in powersave mode
+    /// (a rack with no usable cores is fully loaded).
+    pub fn rack_utilizations(&self) -> Vec<(f32, f32)> {
+        let powersave = self.is_powersaving();
+        let rack_size = if self.can_buy_datacenters {
+            1
+        } else {
+            RACK_CAPACITY as usize
+        };
+        self.nodes
+            .chunks(rack_size)
+            .map(|nodes| {
+                let mut cpu = 0;
+                let mut mem = Memory::zero();
+                let mut cpu_capacity = 0;
+                let mut mem_capacity = Memory::zero();
+                for node in nodes {
+                    cpu += node.processing;
+                    mem += node.ram_usage;
+                    cpu_capacity += node.usable_cores(powersave);
+                    mem_capacity += node.ram_capacity;
+                }
+                let cpu_load = if cpu_capacity == 0 {
+                    1.
+                } else {
+                    (cpu as f32 / cpu_capacity as f32).min(1.)
+                };
+                let mem_load = if mem_capacity == Memory::zero() {
+                    0.
+                } else {
+                    mem.ratio(mem_capacity)
+                };
+                (cpu_load, mem_load)
+            })
+            .collect()
+    }
+
+    /// Serialize the world state for saving,
+    /// recording the version of the game writing it.
+    pub fn to_save_json(&mut self) -> serde_json::Result<String> {
+        self.game_version = GAME_VERSION.to_string();
+        serde_json::to_string(self)
+    }
+
+    pub(crate) fn user_spec(&self, id: u32) -> Option<&CloudUserSpec> {
+        self.user_specs.get(id)
+    }
+
+    /// Add a new user with a fresh ID,
+    /// returning its specification.
+    ///
+    /// IDs keep growing even as users leave,
+    /// and saves without the counter pick up after the highest ID in play.
+    pub fn add_user_spec(
+        &mut self,
+        service: ServiceKind,
+        kind: UserKind,
+        trial_time: Time,
+    ) -> CloudUserSpec {
+        let id = self.next_user_spec_id.max(self.user_specs.next_id());
+        let spec = CloudUserSpec {
+            id,
+            service,
+            trial_time,
+            kind,
+        };
+        self.user_specs.insert(spec.clone());
+        self.next_user_spec_id = id + 1;
+        spec
+    }
+
+    /// The number of users of each service which make requests,
+    /// that is all but the attackers,
+    /// indexed by service kind.
+    pub fn customers_per_service(&self) -> [usize; ServiceKind::ALL.len()] {
+        let mut customers = [0; ServiceKind::ALL.len()];
+        for spec in self.user_specs.iter().filter(|spec| !spec.is_bad()) {
+            customers[spec.service as usize] += 1;
+        }
+        customers
+    }
+
+    /// The customers still around which brought the most revenue,
+    /// highest first.
+    pub fn top_customers(&self) -> Vec<TopCustomer> {
+        self.customer_revenue
+            .top_active(TOP_CUSTOMERS)
+            .into_iter()
+            .filter_map(|entry| {
+                let spec = self.user_spec(entry.spec_id)?;
+                Some(TopCustomer::new(entry, spec, self.time))
+            })
+            .collect()
+    }
+
+    pub(crate) fn apply_cost(&mut self, cost: &Cost) {
+        debug_assert!(cost.is_non_negative(), "negative cost: {cost:?}");
+        // a negative cost must never credit the player
+        let cost = cost.non_negative();
+        self.funds -= cost.money;
+        self.spent += cost.money;
+        self.base_service.available -= cost.base_ops;
+        self.super_service.available -= cost.super_ops;
+        self.epic_service.available -= cost.epic_ops;
+        self.awesome_service.available -= cost.awesome_ops;
+        self.legendary_service.available -= cost.legendary_ops;
+    }
+
+    /// The maximum amount of memory that a cloud node is expected to reserve
+    /// in order to provide all unlocked services.
+    pub(crate) fn expected_ram_reserved(&self) -> Memory {
+        // check highest service tier
+        let base_reserve = self.service_tier().tier().memory_reserve;
+
+        // apply factor based on software level
+        let factor = SOFTWARE_LEVELS[self.software_level as usize].1;
+        base_reserve * factor
+    }
+
+    /// The highest tier of the services unlocked.
+    ///
+    /// The base service counts as unlocked even if a corrupted save says otherwise.
+    pub(crate) fn service_tier(&self) -> ServiceKind {
+        self.services()
+            .filter(|(_, service)| service.state.is_unlocked())
+            .map(|(kind, _)| kind)
+            .last()
+            .unwrap_or(ServiceKind::Base)
+    }
+
+    /// Determine how much of the hardware panel
+    /// should be revealed by the milestones reached so far.
+    pub fn hardware_panel_milestone(&self) -> u8 {
+        if self.can_buy_nodes {
+            HARDWARE_PANEL_PURCHASES
+        } else if self.requests_dropped > 0
+            || self
+                .nodes
+                .iter()
+                .any(|node| node.cpu_level > 0 || node.ram_level > 0)
+        {
+            HARDWARE_PANEL_UPGRADES
+        } else if self.earned >= Money::dollars(10) {
+            HARDWARE_PANEL_LOAD_BARS
+        } else {
+            HARDWARE_PANEL_NODES
+        }
+    }
+
+    /// The most nodes which the player's premises can hold
+    /// before moving on to data centers:
+    /// an open rack fits a few nodes, the first office fits 10 racks.
+    pub fn node_capacity(&self) -> u32 {
+        if self.can_buy_racks {
+            10 * RACK_CAPACITY
+        } else {
+            RACK_CAPACITY
+        }
+    }
+
+    /// The average electricity bill of the latest report periods.
+    pub fn average_bill(&self) -> Money {
+        if self.reports.is_empty() {
+            return Money::zero();
+        }
+        let total: Money = self.reports.iter().map(|report| report.energy_cost).sum();
+        Money::millicents(total.to_millicents() / self.reports.len() as i64)
+    }
+
+    /// Whether new attackers may appear:
+    /// never with full spam protection,
+    /// nor for a while after upgrading the protection
+    /// (see [`DOS_IMMUNITY_PERIOD`]).
+    pub fn can_spawn_attackers(&self) -> bool {
+        self.spam_protection < 1. && self.time >= self.dos_immune_until
+    }
+
+    /// The multiplier applied to the demand of all users,
+    /// above 1 while demand is boosted.
+    pub fn demand_boost(&self) -> f64 {
+        if self.time < self.demand_boost_until {
+            STREAK_DEMAND_BOOST_PERCENT as f64 / 100.
+        } else {
+            1.
+        }
+    }
+
+    /// The share of an attacker's usual demand which they still bring,
+    /// as the spam protection turns most of them away.
+    pub fn attack_strength(&self) -> f64 {
+        (1. - self.spam_protection as f64).clamp(0., 1.)
+    }
+
+    /// Remove the attackers which the spam protection keeps out,
+    /// such as those recorded in a save from before it did.
+    pub fn purge_blocked_attackers(&mut self) {
+        if self.spam_protection >= 1. {
+            self.user_specs.retain(|spec| !spec.is_bad());
+        }
+    }
+
+    /// The amount of money due above which unpaid bills lead to powersave mode.
+    ///
+    /// It grows with the company's usual electricity bills,
+    /// so that bigger companies are not throttled for small debts.
+    pub fn powersave_threshold(&self) -> Money {
+        Money::dollars(10).max(self.average_bill() * 2)
+    }
+
+    /// The amount paid for electricity when the player clicks "Pay":
+    /// the bill as shown,
+    /// or as much of it as the available funds allow.
+    pub fn bill_payment(&self) -> Money {
+        self.electricity
+            .bill_amount()
+            .min(self.funds)
+            .max(Money::zero())
+    }
+
+    /// The time left until powersave mode kicks in
+    /// if the electricity bill is not paid,
+    /// or `None` if there is no bill big enough for it.
+    ///
+    /// Returns `Some(0)` when already in powersave mode.
+    pub fn time_until_powersave(&self) -> Option<Time> {
+        if self.electricity.last_bill_time == 0
+            || self.electricity.total_due <= self.powersave_threshold()
+        {
+            return None;
+        }
+        let powersave_time = self.electricity.last_bill_time + POWERSAVE_GRACE_PERIOD;
+        Some(powersave_time.saturating_sub(self.time))
+    }
+
+    pub fn is_powersaving(&self) -> bool {
+        self.time_until_powersave() == Some(0)
+    }
+
+    /// The total purchase value of the cloud nodes,
+    /// at the prices of the game's difficulty
+    /// (see [`CloudNode::hardware_value`]).
+    pub fn hardware_value(&self) -> Money {
+        let config = GameConfig::new(self.difficulty);
+        self.nodes
+            .iter()
+            .map(|node| node.hardware_value(&config))
+            .sum()
+    }
+
+    /// The funds, electricity credit and hardware value of the company,
+    /// minus the electricity bill due and the loan balance.
+    ///
+    /// May be negative for a company deep in debt.
+    pub fn net_worth(&self) -> Money {
+        let loan = self
+            .loan
+            .as_ref()
+            .map_or(Money::zero(), |loan| loan.balance);
+        self.funds + self.electricity.credit + self.hardware_value()
+            - self.electricity.total_due
+            - loan
+    }
+
+    /// The score of the game so far
+    /// (see [`score`](crate::central::score) for the formula).
+    pub fn score(&self) -> u64 {
+        ScoreBreakdown::of(self).total()
+    }
+
+    /// Produce a plain text scoreboard of the game so far,
+    /// for the player to share.
+    pub fn summary_text(&self) -> String {
+        use std::fmt::Write as _;
+
+        let seconds = to_seconds(self.time);
+        let mut text = String::from("10× Cloud Champion\n");
+        let _ = writeln!(text, "Company: {}", self.company_name);
+        let _ = writeln!(
+            text,
+            "Playtime: {}h {:02}m {:02}s ({})",
+            seconds / 3_600,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.difficulty
+        );
+        if let Some(daily) = &self.daily {
+            let _ = writeln!(
+                text,
+                "Daily challenge: {} (seed {:016x})",
+                daily.date, daily.seed
+            );
+        }
+        let _ = writeln!(text, "Score: {} (v{SCORE_VERSION})", self.score());
+        for (kind, service) in self.services() {
+            if service.state.is_unlocked() {
+                let _ = writeln!(text, "{} ops: {}", kind, service.total.compact());
+            }
+        }
+        let _ = writeln!(text, "Peak visibility: {:.2}%", self.peak_demand / 100.);
+        let _ = writeln!(text, "Total earned: {}", self.earned);
+        let _ = writeln!(text, "Net worth: {}", self.net_worth());
+        let nodes = if self.can_buy_datacenters {
+            self.nodes.len() as u32 * RACK_CAPACITY
+        } else {
+            self.nodes.len() as u32
+        };
+        let _ = write!(text, "Cloud nodes: {}", nodes);
+        if self.autoclicker_suspected {
+            let _ = write!(text, "\nNote: steady clicking, likely automated");
+        }
+        text
+    }
+}
+
+impl Default for WorldState {
+    fn default() -> Self {
+        Self {
+            time: 0,
+            difficulty: Difficulty::default(),
+            company_name: default_company_name(),
+            funds: Money::dollars(10),
+            spent: Default::default(),
+            earned: Default::default(),
+            demand: 0.0,
+            demand_rate: 0.25,
+            peak_demand: 0.0,
+            software_level: 0,
+            cache_level: 0,
+            cache_fraction: 1.,
+            ops_per_click: 1,
+            spam_protection: 0.0,
+            dos_immune_until: 0,
+            demand_boost_until: 0,
+            base_service: ServiceInfo::new_private(ServiceKind::Base.tier().default_price),
+            super_service: ServiceInfo::new_locked(ServiceKind::Super.tier().default_price),
+            epic_service: ServiceInfo::new_locked(ServiceKind::Epic.tier().default_price),
+            awesome_service: ServiceInfo::new_locked(ServiceKind::Awesome.tier().default_price),
+            legendary_service: default_legendary_service(),
+            electricity: Default::default(),
+            requests_dropped: 0,
+            requests_failed: 0,
+            attacks_mitigated: 0,
+            traffic: Default::default(),
+            research_progress: 0,
+            nodes: vec![CloudNode::new(0)],
+            can_see_demand: false,
+            can_see_energy_consumption: false,
+            can_see_request_rates: false,
+            hardware_panel_level: HARDWARE_PANEL_NODES,
+            can_buy_nodes: false,
+            can_buy_racks: false,
+            can_buy_datacenters: false,
+            can_exchange_ops: false,
+            can_auto_pay: false,
+            can_choose_tariff: false,
+            can_hire_staff: false,
+            staff: Vec::new(),
+            total_payroll: Money::zero(),
+            auto_pay: false,
+            routing_level: RoutingLevel::default(),
+            bandwidth_level: 0,
+            user_specs: Default::default(),
+            next_user_spec_id: 0,
+            cards_used: Default::default(),
+            cards_pinned: Vec::new(),
+            loan: None,
+            insolvent_since: 0,
+            period_snapshot: Some(PeriodSnapshot::default()),
+            reports: Vec::new(),
+            report_dismissed: false,
+            win_dismissed: false,
+            bailout_pending: false,
+            narrative: NarrativeLog::default(),
+            saved_at: 0,
+            game_version: String::new(),
+            away_bonus: None,
+            last_away_bonus_at: 0,
+            slas: Vec::new(),
+            insurance: None,
+            insurance_cancelled_at: None,
+            customer_revenue: CustomerLedger::default(),
+            autoclicker_suspected: false,
+            daily: None,
+            achievements: Achievements::default(),
+        }
+    }
+}
+
+/// The different forms of request routing implemented.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum RoutingLevel {
+    /// The first node of the first rack handles all the routing.
+    #[default]
+    MainNode = 0,
+    /// Routing costs are distributed among all nodes.
+    Distributed = 1,
+    /// All routing costs removed.
+    NoRoutingCost = 2,
+}
+
+impl RoutingLevel {
+    /// All routing levels, from lowest to highest.
+    pub const ALL: [RoutingLevel; 3] = [
+        RoutingLevel::MainNode,
+        RoutingLevel::Distributed,
+        RoutingLevel::NoRoutingCost,
+    ];
+
+    /// Get the highest routing level of the two.
+    pub fn max(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::NoRoutingCost, _) | (_, Self::NoRoutingCost) => Self::NoRoutingCost,
+            (Self::Distributed, _) | (_, Self::Distributed) => Self::Distributed,
+            _ => Self::MainNode,
+        }
+    }
+}
+
+/// The origin of a request, for the purpose of request statistics.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrafficClass {
+    /// requested by the player
+    Player = 0,
+    /// requested by a paying customer
+    Paying = 1,
+    /// requested by a customer within the trial period
+    Trial = 2,
+    /// bad requests (such as from DoS attacks)
+    Bad = 3,
+    /// requested by a research partner
+    Research = 4,
+    /// requested by the player's staff
+    Staff = 5,
+}
+
+impl TrafficClass {
+    /// All traffic classes, in the order they are stored in the world state.
+    pub const ALL: [TrafficClass; 6] = [
+        TrafficClass::Player,
+        TrafficClass::Paying,
+        TrafficClass::Trial,
+        TrafficClass::Bad,
+        TrafficClass::Research,
+        TrafficClass::Staff,
+    ];
+}
+
+impl std::fmt::Display for TrafficClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Player => write!(f, "Player"),
+            Self::Paying => write!(f, "Paying customers"),
+            Self::Trial => write!(f, "Trial customers"),
+            Self::Bad => write!(f, "Bad traffic"),
+            Self::Research => write!(f, "Researchers"),
+            Self::Staff => write!(f, "Staff"),
+        }
+    }
+}
+
+/// Request counts for one class of traffic.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TrafficCounters {
+    /// the number of requests fully processed
+    #[serde(default)]
+    pub fulfilled: u64,
+    /// the number of requests dropped
+    #[serde(default)]
+    pub dropped: u64,
+}
+
+/// The record that a project card has been used, and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsedCard {
+    pub id: Cow<'static, str>,
+    pub time: Time,
+}
+
+/// Live information about a cloud service in the game,
+/// namely the current price per op,
+/// how many ops are available to spend,
+/// how many ops were performed in total.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    /// the price per op
+    pub price: Money,
+    /// entitlement: money earned per op
+    /// regardless of who issued it
+    /// (except for DoS attacks)
+    #[serde(default)]
+    pub entitlement: Money,
+    /// the number of operations that the player has available
+    /// from the service
+    pub available: Ops,
+    /// the total number of operations performed by the service
+    pub total: Ops,
+    /// whether the service is unlocked and open to customers
+    #[serde(flatten)]
+    pub state: ServiceState,
+    /// when the service went into maintenance,
+    /// if customer traffic is currently paused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_since: Option<Time>,
+    /// the demand multiplier from customers lost
+    /// while the service was under maintenance,
+    /// between 0 and 1
+    #[serde(default = "one_f64", skip_serializing_if = "is_one_f64")]
+    pub maintenance_demand_factor: f64,
+    /// the price which customers are used to,
+    /// against which price raises are measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_anchor: Option<PriceAnchor>,
+    /// when customers last left the service over a price raise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub churned_at: Option<Time>,
+    /// the total number of times the player clicked
+    /// the service's op button
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub total_player_clicks: u64,
+
+    /// the ops of this service obtained at the op exchange
+    /// (available to spend, but not counted in the total)
+    #[serde(default, skip_serializing_if = "is_zero_ops")]
+    pub exchanged: Ops,
+}
+
+/// A service-level agreement on a cloud service,
+/// under which the player pays a penalty
+/// for every bill period in which the service dropped
+/// more than [`SLA_MAX_DROP_RATE`] of its customers' requests.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceLevelAgreement {
+    pub service: ServiceKind,
+    /// the fee for each bill period in which the agreement is broken
+    pub penalty: Money,
+    /// the customer requests fulfilled in the current bill period
+    #[serde(default)]
+    pub fulfilled: u64,
+    /// the customer requests dropped in the current bill period
+    #[serde(default)]
+    pub dropped: u64,
+    /// the number of bill periods in which the agreement was broken
+    #[serde(default)]
+    pub violations: u32,
+}
+
+impl ServiceLevelAgreement {
+    pub fn new(service: ServiceKind, penalty: Money) -> Self {
+        Self {
+            service,
+            penalty,
+            fulfilled: 0,
+            dropped: 0,
+            violations: 0,
+        }
+    }
+
+    /// The share of customer requests dropped in the current bill period.
+    pub fn drop_rate(&self) -> f64 {
+        let total = self.fulfilled + self.dropped;
+        if total == 0 {
+            0.
+        } else {
+            self.dropped as f64 / total as f64
+        }
+    }
+
+    /// Whether the agreement is broken as of now in the current bill period.
+    pub fn is_violated(&self) -> bool {
+        self.drop_rate() > SLA_MAX_DROP_RATE
+    }
+
+    /// Close the current bill period,
+    /// returning the penalty to pay if the agreement was broken.
+    pub fn close_period(&mut self) -> Option<Money> {
+        let violated = self.is_violated();
+        self.fulfilled = 0;
+        self.dropped = 0;
+        if violated {
+            self.violations += 1;
+            Some(self.penalty)
+        } else {
+            None
+        }
+    }
+}
+
+/// A price of a cloud service and since when it was set.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceAnchor {
+    pub price: Money,
+    pub since: Time,
+}
+
+/// The availability of a cloud service,
+/// which only ever moves forward:
+/// from locked to private to published.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "ServiceStateRepr", into = "ServiceStateRepr")]
+pub enum ServiceState {
+    /// not available to the player yet
+    #[default]
+    Locked,
+    /// available to the player, but not to customers
+    Private,
+    /// available for public use
+    Published,
+}
+
+impl ServiceState {
+    /// Whether the service is available to the player.
+    pub fn is_unlocked(self) -> bool {
+        self != ServiceState::Locked
+    }
+
+    /// Whether the service is available to customers.
+    pub fn is_published(self) -> bool {
+        self == ServiceState::Published
+    }
+
+    /// Make a locked service available to the player.
+    ///
+    /// Any other service is left as is.
+    pub fn unlock(&mut self) -> Result<(), ServiceTransitionError> {
+        self.transition(ServiceState::Locked, ServiceState::Private)
+    }
+
+    /// Make a private service available to customers.
+    ///
+    /// Any other service is left as is,
+    /// so that a locked service is never published.
+    pub fn publish(&mut self) -> Result<(), ServiceTransitionError> {
+        self.transition(ServiceState::Private, ServiceState::Published)
+    }
+
+    fn transition(
+        &mut self,
+        from: ServiceState,
+        to: ServiceState,
+    ) -> Result<(), ServiceTransitionError> {
+        if *self != from {
+            return Err(ServiceTransitionError { from: *self, to });
+        }
+        *self = to;
+        Ok(())
+    }
+}
+
+/// A service state change which is not allowed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ServiceTransitionError {
+    /// the state of the service
+    pub from: ServiceState,
+    /// the state which the service could not move to
+    pub to: ServiceState,
+}
+
+impl std::fmt::Display for ServiceTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot go from {:?} to {:?}", self.from, self.to)
+    }
+}
+
+/// The saved form of a [`ServiceState`].
+///
+/// Older saves wrote down whether the service was unlocked and private instead,
+/// where an unlocked service is the default.
+#[derive(Serialize, Deserialize)]
+struct ServiceStateRepr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<Cow<'static, str>>,
+    #[serde(default, skip_serializing)]
+    unlocked: Option<bool>,
+    #[serde(default, skip_serializing)]
+    private: Option<bool>,
+}
+
+impl From<ServiceStateRepr> for ServiceState {
+    fn from(repr: ServiceStateRepr) -> Self {
+        match repr.state.as_deref() {
+            Some("Locked") => return ServiceState::Locked,
+            Some("Private") => return ServiceState::Private,
+            Some("Published") => return ServiceState::Published,
+            // unknown to this version of the game
+            Some(_) => return ServiceState::Private,
+            None => {}
+        }
+        match (repr.unlocked.unwrap_or(true), repr.private.unwrap_or(false)) {
+            (true, true) => ServiceState::Private,
+            (true, false) => ServiceState::Published,
+            // a locked service is never public,
+            // whatever the save says
+            (false, _) => ServiceState::Locked,
+        }
+    }
+}
+
+impl From<ServiceState> for ServiceStateRepr {
+    fn from(state: ServiceState) -> Self {
+        let state = match state {
+            ServiceState::Locked => "Locked",
+            ServiceState::Private => "Private",
+            ServiceState::Published => "Published",
+        };
+        ServiceStateRepr {
+            state: Some(state.into()),
+            unlocked: None,
+            private: None,
+        }
+    }
+}
+
+impl Default for ServiceInfo {
+    fn default() -> Self {
+        Self::new_locked(Money::zero())
+    }
+}
+
+impl ServiceInfo {
+    pub const fn new_private(price: Money) -> Self {
+        Self {
+            price,
+            entitlement: Money::zero(),
+            available: Ops(0),
+            total: Ops(0),
+            state: ServiceState::Private,
+            maintenance_since: None,
+            maintenance_demand_factor: 1.,
+            price_anchor: None,
+            churned_at: None,
+            total_player_clicks: 0,
+            exchanged: Ops(0),
+        }
+    }
+
+    pub const fn new_locked(price: Money) -> Self {
+        Self {
+            price,
+            entitlement: Money::zero(),
+            available: Ops(0),
+            total: Ops(0),
+            state: ServiceState::Locked,
+            maintenance_since: None,
+            maintenance_demand_factor: 1.,
+            price_anchor: None,
+            churned_at: None,
+            total_player_clicks: 0,
+            exchanged: Ops(0),
+        }
+    }
+
+    /// whether customer traffic to the service is paused for maintenance
+    pub fn in_maintenance(&self) -> bool {
+        self.maintenance_since.is_some()
+    }
+
+    /// Change the price of the service at the given time.
+    ///
+    /// Raises within [`PRICE_RAISE_WINDOW`] add up,
+    /// so that a steep raise cannot be split into small ones.
+    /// Returns the share by which the price was raised
+    /// if it went over [`CHURN_RAISE_THRESHOLD`],
+    /// in which case the raised price becomes the one customers are used to.
+    /// Lowering the price never returns a raise.
+    pub fn change_price(&mut self, new_price: Money, time: Time) -> Option<f64> {
+        let old_price = std::mem::replace(&mut self.price, new_price);
+        let anchor = match self.price_anchor {
+            Some(anchor) if time < anchor.since + PRICE_RAISE_WINDOW => anchor,
+            _ => PriceAnchor {
+                price: old_price,
+                since: time,
+            },
+        };
+
+        if new_price <= old_price {
+            // customers get used to a lower price right away
+            self.price_anchor = Some(if new_price < anchor.price {
+                PriceAnchor {
+                    price: new_price,
+                    since: time,
+                }
+            } else {
+                anchor
+            });
+            return None;
+        }
+
+        let raise = if anchor.price > Money::zero() {
+            (new_price - anchor.price).to_millicents() as f64 / anchor.price.to_millicents() as f64
+        } else {
+            f64::INFINITY
+        };
+        if raise > CHURN_RAISE_THRESHOLD {
+            self.price_anchor = Some(PriceAnchor {
+                price: new_price,
+                since: time,
+            });
+            Some(raise)
+        } else {
+            self.price_anchor = Some(anchor);
+            None
+        }
+    }
+
+    /// calculate service demand based on base demand and price
+    pub fn calculate_demand(&self, base_demand: f64) -> f64 {
+        let millicents = (self.price.to_millicents() as f64).max(0.25);
+        base_demand * 12288. / millicents.powf(2.125)
+    }
+}
+
+/// The name of the player's company unless they choose another
+pub const DEFAULT_COMPANY_NAME: &str = "My Cloud Co.";
+
+/// The maximum number of characters in a company name
+pub const MAX_COMPANY_NAME_LEN: usize = 24;
+
+/// Turn the name typed in by the player into a valid company name,
+/// trimmed and at most [`MAX_COMPANY_NAME_LEN`] characters long,
+/// or the default name if nothing is left.
+///
+/// An overlong name is never cut within a sequence shown as one symbol,
+/// such as an emoji joined with ZWJ, a flag, or a letter and its accents,
+/// so it may end up a few characters shorter.
+pub fn sanitize_company_name(name: &str) -> String {
+    let name = name.trim();
+    let mut end = name
+        .char_indices()
+        .nth(MAX_COMPANY_NAME_LEN)
+        .map_or(name.len(), |(i, _)| i);
+    while end > 0 && end < name.len() && !is_symbol_boundary(name, end) {
+        end = name[..end].char_indices().next_back().map_or(0, |(i, _)| i);
+    }
+    let name = name[..end].trim_end();
+    if name.is_empty() {
+        default_company_name()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether the text can be cut at the given byte offset
+/// without splitting what is shown as a single symbol.
+fn is_symbol_boundary(text: &str, at: usize) -> bool {
+    let (before, after) = text.split_at(at);
+    let (Some(last), Some(next)) = (before.chars().next_back(), after.chars().next()) else {
+        return true;
+    };
+    let is_regional_indicator = |c: char| ('\u{1f1e6}'..='\u{1f1ff}').contains(&c);
+    if is_regional_indicator(last) && is_regional_indicator(next) {
+        // flags are pairs of regional indicators
+        let run = before
+            .chars()
+            .rev()
+            .take_while(|&c| is_regional_indicator(c))
+            .count();
+        return run % 2 == 0;
+    }
+    last != '\u{200d}' && !extends_symbol(next)
+}
+
+/// Whether the character attaches to the one before it:
+/// a combining mark, a joiner, a variation selector,
+/// a skin tone modifier or an emoji tag.
+fn extends_symbol(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036f}'
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{200c}'..='\u{200d}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe00}'..='\u{fe0f}'
+        | '\u{fe20}'..='\u{fe2f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{e0020}'..='\u{e007f}'
+        | '\u{e0100}'..='\u{e01ef}')
+}
+
+/// The number of microWattever in a Wattever,
+/// the unit in which electricity consumption is counted
+pub const MICRO_WATTEVER_PER_WATTEVER: i64 = 1_000_000;
+
+/// Deserialize an amount of electricity consumed,
+/// accepting saves from before it was counted in whole microWattever
+/// (which had it in fractional milliWattever).
+fn deserialize_consumption<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Consumption {
+        MicroWattever(i64),
+        MilliWattever(f64),
+    }
+
+    Ok(match Consumption::deserialize(deserializer)? {
+        Consumption::MicroWattever(micro) => micro,
+        Consumption::MilliWattever(milli) => (milli * 1e3).round() as i64,
+    })
+}
+
+/// World state portion for electricity cost, consumption, and due payments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Electricity {
+    /// the current electricity cost level.
+    ///
+    /// Use [`ELECTRICITY_COST_LEVELS`](super::engine::ELECTRICITY_COST_LEVELS) to translate this to money per Wattever
+    pub cost_level: u8,
+
+    /// the amount of electricity consumed since the last bill in microWattever
+    #[serde(deserialize_with = "deserialize_consumption")]
+    pub consumed: i64,
+
+    /// the part of the electricity consumed since the last bill
+    /// during peak hours, in microWattever
+    #[serde(default, skip_serializing_if = "is_zero_i64")]
+    pub peak_consumed: i64,
+
+    /// the electricity consumed in the last bill period
+    #[serde(default, skip_serializing_if = "is_empty_buckets")]
+    pub last_period: ConsumptionBuckets,
+
+    /// the pricing model of the current energy provider
+    #[serde(default, skip_serializing_if = "is_flat_tariff")]
+    pub tariff: Tariff,
+
+    /// the time of the last switch of energy provider
+    /// (or `None` if never switched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tariff_switched_at: Option<Time>,
+
+    /// the total amount of electricity consumed in microWattever
+    #[serde(deserialize_with = "deserialize_consumption")]
+    pub total_consumed: i64,
+
+    /// the total amount of electricity payment due
+    pub total_due: Money,
+
+    /// the total amount of money ever billed for electricity
+    #[serde(default)]
+    pub total_billed: Money,
+
+    /// the timestamp of the last unpaid bill
+    /// (or 0 if there are no bills to pay)
+    pub last_bill_time: Time,
+
+    /// the amount of the last bill emitted
+    #[serde(default, skip_serializing_if = "is_zero_money")]
+    pub last_bill: Money,
+
+    /// the money paid in advance,
+    /// which bills draw from before touching the funds
+    #[serde(default, skip_serializing_if = "is_zero_money")]
+    pub credit: Money,
+
+    /// the number of bill periods in a row
+    /// in which the bills were paid before the next one was issued
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub on_time_streak: u32,
+
+    /// the number of rewards granted for paying on time so far
+    /// (see [`StreakReward`](super::streak::StreakReward))
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub streak_rewards: u8,
+
+    /// whether the electricity rate is reduced for loyalty
+    /// (see [`StreakReward::LoyaltyDiscount`](super::streak::StreakReward::LoyaltyDiscount))
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub loyalty_discount: bool,
+
+    /// The amount of energy recently consumed in microWattever
+    ///
+    /// Transient.
+    #[serde(skip, default)]
+    pub recent_energy_consumed: i64,
+
+    /// The energy consumption calculated last major update
+    /// in milliWattever
+    ///
+    /// Transient.
+    #[serde(skip, default)]
+    pub energy_consumption_rate: f64,
+}
+
+impl Electricity {
+    /// Add electricity consumed off-peak.
+    pub fn add_consumption(&mut self, micro_wattever: i64) {
+        self.add_consumption_during(micro_wattever, false);
+    }
+
+    /// Add electricity consumed,
+    /// during peak hours or otherwise.
+    pub fn add_consumption_during(&mut self, micro_wattever: i64, peak: bool) {
+        self.consumed += micro_wattever;
+        if peak {
+            self.peak_consumed += micro_wattever;
+        }
+        self.total_consumed += micro_wattever;
+        self.recent_energy_consumed += micro_wattever;
+    }
+
+    pub fn calculate_consumption_rate(&mut self) -> f64 {
+        let rate = self.recent_energy_consumed as f64 * 1e-3;
+        self.recent_energy_consumed = 0;
+        self.energy_consumption_rate = rate;
+        rate
+    }
+
+    /// Add a penalty to the amount due,
+    /// as if it were part of a bill emitted at the given time.
+    pub fn charge_penalty(&mut self, penalty: Money, time: Time) {
+        self.total_due += penalty;
+        if self.last_bill_time == 0 {
+            self.last_bill_time = time;
+        }
+    }
+
+    /// Calculate the cost of the bill if it were to be emitted now,
+    /// given the electricity cost per Wattever at each cost level
+    /// (see [`ELECTRICITY_COST_LEVELS`](super::engine::ELECTRICITY_COST_LEVELS))
+    /// according to the current tariff
+    pub fn check_bill(&self, cost_levels: &[Money]) -> Money {
+        self.tariff
+            .bill(self.cost_per_wattever(cost_levels), self.usage())
+    }
+
+    /// The electricity cost per Wattever at the current cost level,
+    /// after the loyalty discount if it was earned.
+    pub fn cost_per_wattever(&self, cost_levels: &[Money]) -> Money {
+        if self.loyalty_discount {
+            loyalty_discounted(cost_levels, self.cost_level)
+        } else {
+            cost_levels[self.cost_level as usize]
+        }
+    }
+
+    /// The time from which the energy provider can be switched again.
+    pub fn tariff_switch_ready_at(&self) -> Time {
+        self.tariff_switched_at
+            .map_or(0, |time| time + TARIFF_SWITCH_COOLDOWN)
+    }
+
+    /// The electricity consumed since the last bill.
+    pub fn usage(&self) -> ConsumptionBuckets {
+        ConsumptionBuckets {
+            peak: self.peak_consumed,
+            off_peak: self.consumed - self.peak_consumed,
+        }
+    }
+
+    /// The bill of a full period under the given tariff,
+    /// based on the consumption of the last bill period
+    /// (or the consumption so far if no bill was emitted yet).
+    pub fn projected_bill(&self, tariff: Tariff, cost_levels: &[Money]) -> Money {
+        let usage = if self.last_period.is_empty() {
+            self.usage()
+        } else {
+            self.last_period
+        };
+        tariff.bill(self.cost_per_wattever(cost_levels), usage)
+    }
+
+    /// emit a bill for the consumed electricity,
+    /// and reset the consumed amount to zero
+    pub fn emit_bill_for(&mut self, total_cost: Money, time: Time) {
+        self.total_due += total_cost;
+        self.total_billed += total_cost;
+        self.last_bill = total_cost;
+        self.last_period = self.usage();
+        self.consumed = 0;
+        self.peak_consumed = 0;
+        if self.last_bill_time == 0 {
+            self.last_bill_time = time;
+        }
+    }
+
+    /// The amount charged for paying the bills,
+    /// which is the total due rounded to the cent,
+    /// exactly as shown to the player.
+    pub fn bill_amount(&self) -> Money {
+        self.total_due.round_to_cents_half_up()
+    }
+
+    /// Reduce total due to zero
+    /// and mark bill as no longer emitted.
+    pub fn pay_bills(&mut self) {
+        self.total_due = Money::zero();
+        self.last_bill_time = 0;
+    }
+
+    /// Pay the given amount towards the bills,
+    /// clearing them once the amount shown is paid in full.
+    pub fn pay(&mut self, amount: Money) {
+        if amount >= self.bill_amount() {
+            self.pay_bills();
+        } else {
+            self.total_due = (self.total_due - amount).max(Money::zero());
+        }
+    }
+
+    /// Pay what the credit allows towards the bills,
+    /// returning the amount drawn from it.
+    pub fn draw_credit(&mut self) -> Money {
+        let amount = self.credit.min(self.bill_amount()).max(Money::zero());
+        self.credit -= amount;
+        self.pay(amount);
+        amount
+    }
+}
+
+impl Default for Electricity {
+    fn default() -> Self {
+        Self {
+            cost_level: 0,
+            consumed: 0,
+            peak_consumed: 0,
+            last_period: ConsumptionBuckets::default(),
+            tariff: Tariff::Flat,
+            tariff_switched_at: None,
+            total_consumed: 0,
+            total_due: Money::zero(),
+            total_billed: Money::zero(),
+            last_bill_time: 0,
+            last_bill: Money::zero(),
+            credit: Money::zero(),
+            on_time_streak: 0,
+            streak_rewards: 0,
+            loyalty_discount: false,
+            recent_energy_consumed: 0,
+            energy_consumption_rate: 0.,
+        }
+    }
+}
+
+/// The records of the player's past runs,
+/// which survive declaring bankruptcy.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Achievements {
+    /// the number of times the player declared bankruptcy
+    #[serde(default)]
+    pub bankruptcies: u32,
+    /// the highest demand reached in any past run
+    #[serde(default)]
+    pub peak_demand: f64,
+    /// the most money earned in a single past run
+    #[serde(default)]
+    pub best_earned: Money,
+    /// the highest score of a single past run
+    /// (see [`ScoreBreakdown`])
+    #[serde(default)]
+    pub best_score: u64,
+}
+
+impl Achievements {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The achievements once the given run ends in bankruptcy.
+    pub fn after_bankruptcy(&self, state: &WorldState) -> Self {
+        Self {
+            bankruptcies: self.bankruptcies + 1,
+            peak_demand: self.peak_demand.max(state.peak_demand),
+            best_earned: self.best_earned.max(state.earned),
+            best_score: self.best_score.max(ScoreBreakdown::of(state).total()),
+        }
+    }
+}
+
+/// World state portion for a rescue loan taken during a bailout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Loan {
+    /// the amount of money still owed
+    pub balance: Money,
+}
+
+impl Loan {
+    /// Add interest to the balance,
+    /// at the given rate for one period.
+    pub fn accrue_interest(&mut self, rate: f64) {
+        self.balance += self.balance * rate;
+    }
+
+    /// Take the given share of some earnings to repay the loan,
+    /// returning the amount repaid.
+    pub fn repay_from(&mut self, earnings: Money, share: f64) -> Money {
+        let repayment = (earnings * share).min(self.balance);
+        self.balance -= repayment;
+        repayment
+    }
+
+    /// Whether the loan has been fully repaid.
+    pub fn is_repaid(&self) -> bool {
+        self.balance <= Money::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        sanitize_company_name, Electricity, Loan, ServiceInfo, ServiceLevelAgreement, ServiceState,
+        ServiceTransitionError, TrafficClass, TrafficCounters, DEFAULT_COMPANY_NAME,
+        MAX_COMPANY_NAME_LEN, MAX_RESEARCH_DISCOUNT, POWERSAVE_GRACE_PERIOD, PRICE_RAISE_WINDOW,
+        RESEARCH_FOR_MAX_DISCOUNT,
+    };
+    use crate::{
+        central::{
+            cards::all::card_by_id,
+            config::Difficulty,
+            engine::{CloudNode, ELECTRICITY_COST_LEVELS, INCREASE_DEMAND_PERIOD},
+            report::PeriodReport,
+            tariff::{ConsumptionBuckets, Tariff},
+            testing::WorldStateBuilder,
+        },
+        Money, Ops, ServiceKind, UserKind, WorldState,
+    };
+
+    #[test]
+    fn test_sla_periods() {
+        let mut state = WorldState::default();
+        state.slas.push(ServiceLevelAgreement::new(
+            ServiceKind::Base,
+            Money::dollars(40),
+        ));
+        // other services are not covered
+        state.count_sla_requests(ServiceKind::Super, 0, 100);
+        state.count_sla_requests(ServiceKind::Base, 95, 5);
+        let sla = &mut state.slas[0];
+        assert_eq!((sla.fulfilled, sla.dropped), (95, 5));
+        // exactly at the limit is fine
+        assert!(!sla.is_violated());
+        assert_eq!(sla.close_period(), None);
+        assert_eq!((sla.fulfilled, sla.dropped), (0, 0));
+        // an idle period is fine too
+        assert_eq!(sla.close_period(), None);
+
+        state.count_sla_requests(ServiceKind::Base, 90, 10);
+        let sla = &mut state.slas[0];
+        assert!(sla.is_violated());
+        assert_eq!(sla.close_period(), Some(Money::dollars(40)));
+        assert_eq!(sla.violations, 1);
+        assert!(!sla.is_violated());
+    }
+
+    #[test]
+    fn test_price_raises_aggregate_within_window() {
+        let mut service = ServiceInfo::new_private(Money::cents(10));
+
+        // small raises add up until they go over the threshold
+        assert_eq!(service.change_price(Money::cents(11), 0), None);
+        assert_eq!(service.change_price(Money::cents(12), 1_000), None);
+        let raise = service.change_price(Money::cents(13), 2_000).unwrap();
+        assert!((raise - 0.3).abs() < 1e-9, "raise was {raise}");
+
+        // customers are now used to the raised price
+        assert_eq!(service.change_price(Money::cents(15), 3_000), None);
+
+        // raises outside the window start over
+        let later = 3_000 + PRICE_RAISE_WINDOW;
+        assert_eq!(service.change_price(Money::cents(16), later), None);
+        assert_eq!(service.change_price(Money::cents(17), later + 1_000), None);
+
+        // yo-yo pricing measures raises from the lowest price
+        assert_eq!(service.change_price(Money::cents(8), later + 2_000), None);
+        assert!(service
+            .change_price(Money::cents(17), later + 3_000)
+            .is_some());
+    }
+
+    #[test]
+    fn test_lowering_price_never_churns() {
+        let mut service = ServiceInfo::new_private(Money::cents(10));
+        assert!(service.change_price(Money::cents(100), 0).is_some());
+        for (i, cents) in [90, 50, 50, 20, 5, 1, 0].into_iter().enumerate() {
+            assert_eq!(
+                service.change_price(Money::cents(cents), i as u64 * 1_000),
+                None
+            );
+        }
+        // raising from a free service is always steep
+        assert!(service.change_price(Money::cents(1), 9_000).is_some());
+    }
+
+    #[test]
+    fn test_powersave_threshold_scales() {
+        let mut state = WorldState::default();
+        // small company: flat minimum
+        assert_eq!(state.powersave_threshold(), Money::dollars(10));
+
+        let report = |energy_cost| PeriodReport {
+            time: 0,
+            revenue: Money::zero(),
+            ops: [Ops(0); ServiceKind::ALL.len()],
+            requests_dropped: 0,
+            energy_cost,
+            demand_change: 0.,
+            cards_bought: 0,
+        };
+
+        // still small bills
+        state.reports = vec![report(Money::dollars(2)), report(Money::dollars(4))];
+        assert_eq!(state.powersave_threshold(), Money::dollars(10));
+
+        // big company: twice the average bill
+        state.reports = vec![report(Money::dollars(100)), report(Money::dollars(300))];
+        assert_eq!(state.powersave_threshold(), Money::dollars(400));
+
+        state.time = 10 * POWERSAVE_GRACE_PERIOD;
+        state.electricity.last_bill_time = 1;
+        state.electricity.total_due = Money::dollars(50);
+        assert!(!state.is_powersaving());
+        state.electricity.total_due = Money::dollars(401);
+        assert!(state.is_powersaving());
+    }
+
+    #[test]
+    fn test_time_until_powersave() {
+        let mut state = WorldState::default();
+        assert_eq!(state.time_until_powersave(), None);
+
+        state.time = 1_000_000;
+        state
+            .electricity
+            .emit_bill_for(Money::dollars(20), 1_000_000);
+        assert_eq!(state.time_until_powersave(), Some(POWERSAVE_GRACE_PERIOD));
+
+        state.time += 400_000;
+        assert_eq!(
+            state.time_until_powersave(),
+            Some(POWERSAVE_GRACE_PERIOD - 400_000)
+        );
+        assert!(!state.is_powersaving());
+
+        state.time = 1_000_000 + POWERSAVE_GRACE_PERIOD + 5;
+        assert_eq!(state.time_until_powersave(), Some(0));
+        assert!(state.is_powersaving());
+
+        // paying the bill removes the countdown
+        state.electricity.pay_bills();
+        assert_eq!(state.time_until_powersave(), None);
+    }
+
+    #[test]
+    fn test_rack_utilizations() {
+        let mut state = WorldState {
+            can_buy_racks: true,
+            nodes: (0..6).map(CloudNode::new_fully_upgraded).collect(),
+            ..Default::default()
+        };
+        let cores = state.nodes[0].num_cores;
+        let ram = state.nodes[0].ram_capacity;
+        state.nodes[0].processing = cores;
+        state.nodes[1].processing = cores / 2;
+        state.nodes[4].processing = cores / 4;
+        state.nodes[5].ram_usage = ram;
+
+        // one full rack and one with only 2 nodes
+        assert_eq!(state.rack_utilizations(), vec![(0.375, 0.), (0.125, 0.5)]);
+
+        // powersave leaves a quarter of the cores usable
+        state
+            .electricity
+            .emit_bill_for(Money::dollars(20), 1_000_000);
+        state.time = 1_000_000 + POWERSAVE_GRACE_PERIOD + 5;
+        assert!(state.is_powersaving());
+        assert_eq!(state.rack_utilizations(), vec![(1., 0.), (0.5, 0.5)]);
+
+        // once in data centers, each node is a rack
+        state.can_buy_datacenters = true;
+        assert_eq!(state.rack_utilizations().len(), 6);
+
+        // a single core node has no usable cores in powersave,
+        // so it is fully loaded
+        state.nodes = vec![CloudNode::new(0)];
+        assert_eq!(state.rack_utilizations(), vec![(1., 0.)]);
+
+        // and no nodes means no racks
+        state.nodes.clear();
+        assert!(state.rack_utilizations().is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_company_name() {
+        assert_eq!(sanitize_company_name("Acme Cloud"), "Acme Cloud");
+        assert_eq!(sanitize_company_name("  Acme Cloud \n"), "Acme Cloud");
+        // nothing left falls back to the default
+        assert_eq!(sanitize_company_name(""), DEFAULT_COMPANY_NAME);
+        assert_eq!(sanitize_company_name(" \t "), DEFAULT_COMPANY_NAME);
+        // overlong names are cut, without trailing whitespace
+        let name = sanitize_company_name("Extremely Scalable Cloud Enterprises");
+        assert_eq!(name, "Extremely Scalable Cloud");
+        assert_eq!(name.chars().count(), MAX_COMPANY_NAME_LEN);
+        assert_eq!(
+            sanitize_company_name(&"a ".repeat(20)),
+            "a a a a a a a a a a a a"
+        );
+        // emoji count as single characters
+        assert_eq!(
+            sanitize_company_name("\u{2601}\u{fe0f} Co."),
+            "\u{2601}\u{fe0f} Co."
+        );
+        assert_eq!(
+            sanitize_company_name(&"\u{1f680}".repeat(30))
+                .chars()
+                .count(),
+            MAX_COMPANY_NAME_LEN
+        );
+
+        // symbols made of several characters are kept whole or left out
+        let prefix = "a".repeat(MAX_COMPANY_NAME_LEN - 4);
+        let family = "\u{1f469}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+        assert_eq!(sanitize_company_name(&format!("{prefix}{family}")), prefix);
+        let prefix = "a".repeat(MAX_COMPANY_NAME_LEN - 1);
+        assert_eq!(sanitize_company_name(&format!("{prefix}e\u{301}")), prefix);
+        assert_eq!(
+            sanitize_company_name(&format!("{prefix}\u{1f1ef}\u{1f1f5}")),
+            prefix
+        );
+        assert_eq!(
+            sanitize_company_name(&format!("{prefix}\u{1f44d}\u{1f3fd}")),
+            prefix
+        );
+        let flags = "\u{1f1ef}\u{1f1f5}".repeat(MAX_COMPANY_NAME_LEN);
+        assert_eq!(
+            sanitize_company_name(&flags).chars().count(),
+            MAX_COMPANY_NAME_LEN
+        );
+    }
+
+    #[test]
+    fn test_company_name_serialization() {
+        let state = WorldState {
+            company_name: "\u{1f680} Rocket Hosting".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, state);
+
+        // saves from before companies had names get the default one
+        let json = json.replacen("\"company_name\":\"\u{1f680} Rocket Hosting\",", "", 1);
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.company_name, DEFAULT_COMPANY_NAME);
+    }
+
+    #[test]
+    fn test_pinned_cards_serialization() {
+        let state = WorldState {
+            cards_pinned: vec!["b0".into(), "s1".into()],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, state);
+        assert!(loaded.is_card_pinned("s1"));
+        assert!(!loaded.is_card_pinned("b00"));
+
+        // nothing pinned takes no room in the save
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        assert!(!json.contains("cards_pinned"));
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert!(loaded.cards_pinned.is_empty());
+    }
+
+    #[test]
+    fn test_cards_used_chronological() {
+        let state = WorldStateBuilder::new()
+            .time(300)
+            .cards_used(&["b0"])
+            .time(100)
+            .cards_used(&["s1", "a0p"])
+            .time(200)
+            .cards_used(&["n1"])
+            .build();
+        // still sorted by ID
+        let ids: Vec<_> = state.cards_used.iter().map(|c| c.id.as_ref()).collect();
+        assert_eq!(ids, ["a0p", "b0", "n1", "s1"]);
+
+        // cards used at the same time stay in ID order
+        let ids: Vec<_> = state
+            .cards_used_chronological()
+            .into_iter()
+            .map(|c| (c.id.as_ref(), c.time))
+            .collect();
+        assert_eq!(ids, [("a0p", 100), ("s1", 100), ("n1", 200), ("b0", 300)]);
+    }
+
+    #[test]
+    fn test_unsorted_user_specs_healed_on_load() {
+        let state = WorldStateBuilder::new()
+            .with_customer(ServiceKind::Base)
+            .with_user(ServiceKind::Base, UserKind::Researcher, 0)
+            .with_customer(ServiceKind::Base)
+            .build();
+        let mut json = serde_json::to_value(&state).unwrap();
+        json["user_specs"].as_array_mut().unwrap().reverse();
+        assert_eq!(json["user_specs"][0]["id"], 3);
+
+        // every user can still be found, and new ones get fresh IDs
+        let loaded: WorldState = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded, state);
+        for spec in state.user_specs.iter() {
+            assert_eq!(loaded.user_spec(spec.id), Some(spec));
+        }
+        assert_eq!(loaded.user_specs.next_id(), 4);
+    }
+
+    #[test]
+    fn test_user_spec_ids_not_reused() {
+        let mut state = WorldStateBuilder::new()
+            .with_customer(ServiceKind::Base)
+            .with_customer(ServiceKind::Base)
+            .build();
+        assert_eq!(state.next_user_spec_id, 3);
+
+        // the user with the highest ID leaves
+        state.user_specs.remove(2);
+        let spec = state.add_user_spec(ServiceKind::Base, UserKind::Customer, 0);
+        assert_eq!(spec.id, 3);
+
+        // the counter is saved along with the game
+        state.user_specs.remove(3);
+        let json = serde_json::to_string(&state).unwrap();
+        let mut loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.next_user_spec_id, 4);
+        let spec = loaded.add_user_spec(ServiceKind::Base, UserKind::Customer, 0);
+        assert_eq!(spec.id, 4);
+
+        // older saves pick up after the highest ID in play
+        let mut json = serde_json::to_value(&state).unwrap();
+        json.as_object_mut().unwrap().remove("next_user_spec_id");
+        let mut loaded: WorldState = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.next_user_spec_id, 0);
+        let spec = loaded.add_user_spec(ServiceKind::Base, UserKind::Customer, 0);
+        assert_eq!(spec.id, 2);
+        assert_eq!(loaded.next_user_spec_id, 3);
+    }
+
+    #[test]
+    fn test_save_records_game_version() {
+        let mut state = WorldState::default();
+        assert!(state.game_version.is_empty());
+        let json = state.to_save_json().unwrap();
+        assert!(json.contains(&format!(
+            "\"game_version\":\"{}\"",
+            env!("CARGO_PKG_VERSION")
+        )));
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.game_version, env!("CARGO_PKG_VERSION"));
+
+        // saves from before versions were recorded have none
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        assert!(!json.contains("game_version"));
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert!(loaded.game_version.is_empty());
+    }
+
+    #[test]
+    fn test_summary_text() {
+        let mut state = WorldState {
+            time: 10_000 * 3_725,
+            peak_demand: 12.5,
+            earned: Money::cents(12_345),
+            ..Default::default()
+        };
+        state.base_service.total = Ops(25_000);
+        state.super_service.state = ServiceState::Private;
+        state.super_service.total = Ops(3_000_000);
+
+        assert_eq!(
+            state.summary_text(),
+            "10× Cloud Champion\n\
+             Company: My Cloud Co.\n\
+             Playtime: 1h 02m 05s (Normal)\n\
+             Score: 24223 (v2)\n\
+             Base ops: 25k\n\
+             Super ops: 3M\n\
+             Peak visibility: 0.12%\n\
+             Total earned: $123.45\n\
+             Net worth: $2\u{2006}010\n\
+             Cloud nodes: 1"
+        );
+
+        // datacenter nodes count as full racks
+        state.can_buy_datacenters = true;
+        state.nodes.push(crate::central::engine::CloudNode::new(1));
+        assert!(state.summary_text().ends_with("Cloud nodes: 8"));
+
+        // automated clicking is noted
+        state.autoclicker_suspected = true;
+        assert!(state
+            .summary_text()
+            .ends_with("Cloud nodes: 8\nNote: steady clicking, likely automated"));
+    }
+
+    #[test]
+    fn test_research_discount_capped() {
+        let mut state = WorldState::default();
+        let card = card_by_id("s2").unwrap();
+        assert_eq!(state.research_discount(), 0.);
+        assert_eq!(state.card_cost(card), card.cost);
+
+        state.research_progress = RESEARCH_FOR_MAX_DISCOUNT / 2;
+        assert_eq!(state.research_discount(), 0.25);
+
+        state.research_progress = RESEARCH_FOR_MAX_DISCOUNT * 10;
+        assert_eq!(state.research_discount(), MAX_RESEARCH_DISCOUNT);
+        let cost = state.card_cost(card);
+        assert_eq!(cost.money, card.cost.money);
+        assert_eq!(cost.base_ops, Ops(375));
+
+        // other cards are not discounted
+        let card = card_by_id("b0").unwrap();
+        assert_eq!(state.card_cost(card), card.cost);
+    }
+
+    #[test]
+    fn test_old_traffic_counters_load() {
+        // saves from before researchers only had four traffic classes
+        let json = serde_json::to_string(&WorldState::default())
+            .unwrap()
+            .replacen(
+                "\"user_specs\":",
+                "\"traffic\":[{\"fulfilled\":1},{},{},{\"dropped\":2}],\"user_specs\":",
+                1,
+            );
+        let state: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.traffic[TrafficClass::Player as usize].fulfilled, 1);
+        assert_eq!(state.traffic[TrafficClass::Bad as usize].dropped, 2);
+        assert_eq!(
+            state.traffic[TrafficClass::Research as usize],
+            TrafficCounters::default()
+        );
+    }
+
+    #[test]
+    fn test_service_state_from_old_flags() {
+        let cases = [
+            (r#"{"unlocked":true,"private":true}"#, ServiceState::Private),
+            (
+                r#"{"unlocked":true,"private":false}"#,
+                ServiceState::Published,
+            ),
+            (r#"{"unlocked":false,"private":true}"#, ServiceState::Locked),
+            // locked but public makes no sense,
+            // so the service stays locked
+            (
+                r#"{"unlocked":false,"private":false}"#,
+                ServiceState::Locked,
+            ),
+            // older saves did not have the flags at all
+            (r#"{}"#, ServiceState::Published),
+            (r#"{"private":true}"#, ServiceState::Private),
+        ];
+        for (json, expected) in cases {
+            let state: ServiceState = serde_json::from_str(json).unwrap();
+            assert_eq!(state, expected, "{json}");
+        }
+
+        for state in [
+            ServiceState::Locked,
+            ServiceState::Private,
+            ServiceState::Published,
+        ] {
+            let json = serde_json::to_string(&state).unwrap();
+            assert!(!json.contains("private"), "{json}");
+            assert_eq!(serde_json::from_str::<ServiceState>(&json).unwrap(), state);
+        }
+        // a state from the future is not trusted
+        let state: ServiceState = serde_json::from_str(r#"{"state":"Beta"}"#).unwrap();
+        assert_eq!(state, ServiceState::Private);
+
+        // the state sits in the service itself
+        let service = ServiceInfo::new_locked(Money::cents(5));
+        let json = serde_json::to_string(&service).unwrap();
+        assert!(json.contains(r#""state":"Locked""#), "{json}");
+        assert_eq!(serde_json::from_str::<ServiceInfo>(&json).unwrap(), service);
+    }
+
+    #[test]
+    fn test_service_state_transitions() {
+        let mut state = ServiceState::Locked;
+        // a locked service cannot be published
+        assert_eq!(
+            state.publish(),
+            Err(ServiceTransitionError {
+                from: ServiceState::Locked,
+                to: ServiceState::Published
+            })
+        );
+        assert_eq!(state, ServiceState::Locked);
+
+        assert_eq!(state.unlock(), Ok(()));
+        assert_eq!(state, ServiceState::Private);
+        assert!(state.is_unlocked() && !state.is_published());
+        assert!(state.unlock().is_err());
+
+        assert_eq!(state.publish(), Ok(()));
+        assert_eq!(state, ServiceState::Published);
+        // never back to private
+        assert!(state.unlock().is_err());
+        assert!(state.publish().is_err());
+        assert_eq!(state, ServiceState::Published);
+    }
+
+    #[test]
+    fn test_old_save_has_legendary_service_locked() {
+        // saves from before the legendary tier never mention it
+        let json = serde_json::to_string(&WorldState::default()).unwrap();
+        assert!(!json.contains("legendary"));
+        let state: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.legendary_service.state, ServiceState::Locked);
+        assert_eq!(
+            state.legendary_service.price,
+            ServiceKind::Legendary.tier().default_price
+        );
+        assert_eq!(state.service_tier(), ServiceKind::Base);
+    }
+
+    #[test]
+    fn test_loan_interest() {
+        let mut loan = Loan {
+            balance: Money::dollars(100),
+        };
+        loan.accrue_interest(0.05);
+        assert_eq!(loan.balance, Money::dollars(105));
+        loan.accrue_interest(0.05);
+        assert_eq!(loan.balance, Money::cents(11_025));
+    }
+
+    #[test]
+    fn test_net_worth() {
+        let mut state = WorldState {
+            funds: Money::dollars(500),
+            ..Default::default()
+        };
+        state.nodes[0].cpu_level = 1;
+        state.nodes.push(crate::central::engine::CloudNode::new(1));
+        assert_eq!(state.hardware_value(), Money::dollars(4_060));
+        assert_eq!(state.net_worth(), Money::dollars(4_560));
+
+        // hardware costs more on harder difficulties
+        let hard = WorldState {
+            difficulty: Difficulty::Hard,
+            ..state.clone()
+        };
+        assert!(hard.hardware_value() > state.hardware_value());
+
+        // debts are taken off
+        state.electricity.total_due = Money::dollars(60);
+        state.loan = Some(Loan {
+            balance: Money::dollars(5_000),
+        });
+        assert_eq!(state.net_worth(), Money::dollars(-500));
+
+        // prepaying electricity keeps the money in the company
+        let worth = state.net_worth();
+        state.funds -= Money::dollars(100);
+        state.electricity.credit += Money::dollars(100);
+        assert_eq!(state.net_worth(), worth);
+    }
+
+    #[test]
+    fn test_loan_repayment() {
+        let mut loan = Loan {
+            balance: Money::dollars(10),
+        };
+        let repaid = loan.repay_from(Money::dollars(8), 0.25);
+        assert_eq!(repaid, Money::dollars(2));
+        assert_eq!(loan.balance, Money::dollars(8));
+        assert!(!loan.is_repaid());
+
+        // never takes more than what is owed
+        let repaid = loan.repay_from(Money::dollars(100), 0.25);
+        assert_eq!(repaid, Money::dollars(8));
+        assert!(loan.is_repaid());
+    }
+
+    /// a year of game time in seconds
+    const YEAR_SECS: i64 = 365 * 24 * 60 * 60;
+
+    #[test]
+    fn test_electricity_accumulates_exactly() {
+        // a year of one routed and processed request per second
+        let mut electricity = Electricity::default();
+        for _ in 0..YEAR_SECS {
+            electricity.add_consumption(10);
+            electricity.add_consumption(1_000);
+        }
+        assert_eq!(electricity.consumed, YEAR_SECS * 1_010);
+        assert_eq!(electricity.total_consumed, YEAR_SECS * 1_010);
+        // 31,851.36 Wattever at 32 cents each
+        assert_eq!(
+            electricity.check_bill(&ELECTRICITY_COST_LEVELS),
+            Money::millicents(1_019_243_520)
+        );
+    }
+
+    #[test]
+    fn test_electricity_keeps_last_period() {
+        let mut electricity = Electricity::default();
+        electricity.add_consumption_during(30_000_000, true);
+        electricity.add_consumption(10_000_000);
+        let usage = ConsumptionBuckets {
+            peak: 30_000_000,
+            off_peak: 10_000_000,
+        };
+        assert_eq!(electricity.usage(), usage);
+        // 30 Wev at 48 cents + 10 Wev at 16 cents
+        assert_eq!(
+            electricity.projected_bill(Tariff::PeakOffPeak, &ELECTRICITY_COST_LEVELS),
+            Money::cents(1_440 + 160)
+        );
+
+        let bill = electricity.check_bill(&ELECTRICITY_COST_LEVELS);
+        assert_eq!(bill, Money::cents(1_280));
+        electricity.emit_bill_for(bill, 1);
+        assert_eq!(electricity.usage(), ConsumptionBuckets::default());
+        assert_eq!(electricity.last_period, usage);
+        assert_eq!(electricity.total_consumed, 40_000_000);
+
+        // projections stay on the last full period
+        electricity.add_consumption(1_000_000);
+        assert_eq!(
+            electricity.projected_bill(Tariff::Flat, &ELECTRICITY_COST_LEVELS),
+            Money::cents(1_280)
+        );
+
+        // saves without a tariff are on the flat rate
+        let json = serde_json::to_string(&Electricity::default()).unwrap();
+        assert!(!json.contains("tariff"));
+        let loaded: Electricity = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.tariff, Tariff::Flat);
+    }
+
+    #[test]
+    fn test_demand_accumulates_precisely() {
+        // a year of periodic demand increases,
+        // starting from demand levels reached late in the game
+        let steps = YEAR_SECS as u64 * 10_000 / INCREASE_DEMAND_PERIOD;
+        let mut state = WorldState {
+            demand: 30_000_000.,
+            demand_rate: 0.1,
+            ..Default::default()
+        };
+        for _ in 0..steps {
+            state.demand += state.demand_rate;
+        }
+        let expected = 30_000_000. + steps as f64 * 0.1;
+        assert!(
+            (state.demand - expected).abs() < expected * 1e-9,
+            "{} != {}",
+            state.demand,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_old_float_accumulators_load() {
+        // saves from before demand was in f64
+        // and electricity was counted in whole microWattever
+        let json = serde_json::to_string(&WorldState::default())
+            .unwrap()
+            .replacen("\"demand\":0.0", "\"demand\":1.1", 1)
+            .replacen("\"consumed\":0", "\"consumed\":12.5", 1)
+            .replacen("\"total_consumed\":0", "\"total_consumed\":100.0", 1);
+        let state: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.demand, 1.1);
+        assert_eq!(state.electricity.consumed, 12_500);
+        assert_eq!(state.electricity.total_consumed, 100_000);
+
+        // and new saves keep them as they are
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, state);
+    }
+}
@@ -0,0 +1,148 @@
+//! Module for the rewards for paying the electricity bills on time.
+//!
+//! Every bill period in which the bills were paid
+//! before the next one was issued extends the on-time streak,
+//! and a missed payment resets it.
+//! At each of the [`STREAK_MILESTONES`] the player is granted
+//! the next of the [`StreakReward`]s, once per game.
+
+use std::fmt;
+
+use crate::{t, Money, Time, TIME_UNITS_PER_MINUTE};
+
+/// the on-time streaks at which the rewards are granted, in bill periods
+pub const STREAK_MILESTONES: [u32; 3] = [3, 5, 10];
+
+/// the part of the last bill refunded by the rebate, as a percentage
+pub const STREAK_REBATE_PERCENT: i64 = 10;
+
+/// the demand during the demand boost, as a percentage of the usual demand
+pub const STREAK_DEMAND_BOOST_PERCENT: i64 = 115;
+
+/// how long the demand boost lasts
+pub const STREAK_DEMAND_BOOST_DURATION: Time = 10 * TIME_UNITS_PER_MINUTE;
+
+/// the reduction of the electricity rate granted to loyal customers,
+/// as a percentage
+/// (never bringing the rate down to that of the next cost level)
+pub const LOYALTY_DISCOUNT_PERCENT: i64 = 5;
+
+/// A reward for paying the electricity bills on time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreakReward {
+    /// part of the last bill is refunded
+    Rebate,
+    /// demand is boosted for a while,
+    /// as word gets around of a reliable company
+    DemandBoost,
+    /// the electricity rate is permanently reduced a bit
+    LoyaltyDiscount,
+}
+
+impl StreakReward {
+    /// All rewards, in the order they are granted.
+    pub const ALL: [StreakReward; 3] = [
+        StreakReward::Rebate,
+        StreakReward::DemandBoost,
+        StreakReward::LoyaltyDiscount,
+    ];
+
+    /// The reward granted when the streak reaches the given length,
+    /// given the number of rewards already granted.
+    pub fn reached(streak: u32, granted: u8) -> Option<Self> {
+        let index = granted as usize;
+        (STREAK_MILESTONES.get(index) == Some(&streak)).then(|| Self::ALL[index])
+    }
+
+    /// The next reward to be granted and the streak needed for it,
+    /// given the number of rewards already granted.
+    pub fn next(granted: u8) -> Option<(u32, Self)> {
+        let index = granted as usize;
+        STREAK_MILESTONES
+            .get(index)
+            .map(|&streak| (streak, Self::ALL[index]))
+    }
+}
+
+impl fmt::Display for StreakReward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rebate => f.write_str(&t!("streak.rebate", percent = STREAK_REBATE_PERCENT)),
+            Self::DemandBoost => f.write_str(&t!(
+                "streak.demand_boost",
+                percent = STREAK_DEMAND_BOOST_PERCENT - 100
+            )),
+            Self::LoyaltyDiscount => f.write_str(&t!(
+                "streak.loyalty_discount",
+                percent = LOYALTY_DISCOUNT_PERCENT
+            )),
+        }
+    }
+}
+
+/// The rebate for the given bill.
+pub fn rebate(bill: Money) -> Money {
+    Money::millicents(bill.to_millicents() * STREAK_REBATE_PERCENT / 100)
+}
+
+/// The electricity cost per Wattever at the given cost level
+/// after the loyalty discount,
+/// which stays above the cost at the next level
+/// so that the cards lowering the cost are still worth it.
+pub fn loyalty_discounted(cost_levels: &[Money], level: u8) -> Money {
+    let cost = cost_levels[level as usize];
+    let discounted =
+        Money::millicents(cost.to_millicents() * (100 - LOYALTY_DISCOUNT_PERCENT) / 100);
+    match cost_levels.get(level as usize + 1) {
+        Some(&next) => discounted.max(next + Money::millicents(1)).min(cost),
+        None => discounted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{loyalty_discounted, rebate, StreakReward, STREAK_MILESTONES};
+    use crate::{central::engine::ELECTRICITY_COST_LEVELS, Money};
+
+    #[test]
+    fn test_rewards_in_order() {
+        assert_eq!(StreakReward::reached(1, 0), None);
+        assert_eq!(StreakReward::reached(3, 0), Some(StreakReward::Rebate));
+        // granted once
+        assert_eq!(StreakReward::reached(3, 1), None);
+        assert_eq!(StreakReward::reached(5, 1), Some(StreakReward::DemandBoost));
+        assert_eq!(
+            StreakReward::reached(10, 2),
+            Some(StreakReward::LoyaltyDiscount)
+        );
+        assert_eq!(StreakReward::reached(10, 3), None);
+
+        assert_eq!(StreakReward::next(0), Some((3, StreakReward::Rebate)));
+        assert_eq!(StreakReward::next(3), None);
+        assert_eq!(STREAK_MILESTONES.len(), StreakReward::ALL.len());
+    }
+
+    #[test]
+    fn test_rebate() {
+        assert_eq!(rebate(Money::dollars(20)), Money::dollars(2));
+        assert_eq!(rebate(Money::zero()), Money::zero());
+    }
+
+    #[test]
+    fn test_loyalty_discount_capped() {
+        let levels = &ELECTRICITY_COST_LEVELS;
+        for level in 0..levels.len() as u8 - 1 {
+            let cost = levels[level as usize];
+            let discounted = loyalty_discounted(levels, level);
+            assert!(discounted <= cost, "level {level}");
+            // never as cheap as the next level
+            assert!(discounted > levels[level as usize + 1], "level {level}");
+        }
+        // 5% off the base cost
+        assert_eq!(loyalty_discounted(levels, 0), Money::dec_cents(304));
+        // the renegotiated cost is only 3 cents above the next level
+        assert!(loyalty_discounted(levels, 1) < levels[1]);
+        // nothing to take off free energy
+        assert_eq!(loyalty_discounted(levels, 6), Money::zero());
+    }
+}
@@ -4,9 +4,11 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-use crate::display::Separating;
+use crate::{display::Separating, WorldState};
 
-#[derive(Debug, Default, Clone, PartialEq)]
+use super::tiers::{ServiceTier, SERVICE_TIERS};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct Cost {
     pub money: Money,
     /// operations from the base service
@@ -17,6 +19,8 @@ pub struct Cost {
     pub epic_ops: Ops,
     /// operations from the awesome service
     pub awesome_ops: Ops,
+    /// operations from the legendary service
+    pub legendary_ops: Ops,
 }
 
 impl Cost {
@@ -29,6 +33,7 @@ impl Cost {
             super_ops: Ops(0),
             epic_ops: Ops(0),
             awesome_ops: Ops(0),
+            legendary_ops: Ops(0),
         }
     }
 
@@ -40,6 +45,7 @@ impl Cost {
             super_ops: Ops(0),
             epic_ops: Ops(0),
             awesome_ops: Ops(0),
+            legendary_ops: Ops(0),
         }
     }
 
@@ -61,6 +67,7 @@ impl Cost {
             super_ops: Ops(0),
             epic_ops: Ops(0),
             awesome_ops: Ops(0),
+            legendary_ops: Ops(0),
         }
     }
 
@@ -72,6 +79,7 @@ impl Cost {
             super_ops: Ops(super_ops as i64),
             epic_ops: Ops(0),
             awesome_ops: Ops(0),
+            legendary_ops: Ops(0),
         }
     }
 
@@ -83,6 +91,7 @@ impl Cost {
             super_ops: Ops(0),
             epic_ops: Ops(epic_ops as i64),
             awesome_ops: Ops(0),
+            legendary_ops: Ops(0),
         }
     }
 
@@ -94,6 +103,19 @@ impl Cost {
             super_ops: Ops(0),
             epic_ops: Ops(0),
             awesome_ops: Ops(awesome_ops as i64),
+            legendary_ops: Ops(0),
+        }
+    }
+
+    #[inline]
+    pub const fn legendary_ops(legendary_ops: i32) -> Self {
+        Self {
+            money: Money(0),
+            base_ops: Ops(0),
+            super_ops: Ops(0),
+            epic_ops: Ops(0),
+            awesome_ops: Ops(0),
+            legendary_ops: Ops(legendary_ops as i64),
         }
     }
 
@@ -106,15 +128,116 @@ impl Cost {
             super_ops: Ops(self.super_ops.0 + cost.super_ops.0),
             epic_ops: Ops(self.epic_ops.0 + cost.epic_ops.0),
             awesome_ops: Ops(self.awesome_ops.0 + cost.awesome_ops.0),
+            legendary_ops: Ops(self.legendary_ops.0 + cost.legendary_ops.0),
         }
     }
 
+    /// Reduce the operation costs by the given fraction,
+    /// rounding up, while keeping the money cost.
+    pub fn discount_ops(&self, fraction: f64) -> Self {
+        let discount = |ops: Ops| Ops((ops.0 as f64 * (1. - fraction)).ceil() as i64);
+        Self {
+            money: self.money,
+            base_ops: discount(self.base_ops),
+            super_ops: discount(self.super_ops),
+            epic_ops: discount(self.epic_ops),
+            awesome_ops: discount(self.awesome_ops),
+            legendary_ops: discount(self.legendary_ops),
+        }
+    }
+
+    /// Whether no part of the cost is negative.
+    ///
+    /// A negative cost would credit the player on purchase,
+    /// so charges and fines are expressed as card effects instead
+    /// (see [`CardEffect::Penalty`](super::cards::CardEffect::Penalty)).
+    pub fn is_non_negative(&self) -> bool {
+        self.money >= Money(0)
+            && self.base_ops >= Ops(0)
+            && self.super_ops >= Ops(0)
+            && self.epic_ops >= Ops(0)
+            && self.awesome_ops >= Ops(0)
+            && self.legendary_ops >= Ops(0)
+    }
+
+    /// The same cost with any negative parts taken as zero.
+    pub fn non_negative(&self) -> Self {
+        Self {
+            money: self.money.max(Money(0)),
+            base_ops: self.base_ops.max(Ops(0)),
+            super_ops: self.super_ops.max(Ops(0)),
+            epic_ops: self.epic_ops.max(Ops(0)),
+            awesome_ops: self.awesome_ops.max(Ops(0)),
+            legendary_ops: self.legendary_ops.max(Ops(0)),
+        }
+    }
+
+    /// The ops part of the cost for each service kind,
+    /// from the lowest tier to the highest.
+    pub fn ops(&self) -> [(ServiceKind, Ops); 5] {
+        [
+            (ServiceKind::Base, self.base_ops),
+            (ServiceKind::Super, self.super_ops),
+            (ServiceKind::Epic, self.epic_ops),
+            (ServiceKind::Awesome, self.awesome_ops),
+            (ServiceKind::Legendary, self.legendary_ops),
+        ]
+    }
+
+    /// The largest share of the available ops of a service
+    /// which paying this cost would spend,
+    /// or 0 if it costs no ops.
+    ///
+    /// The share is above 1 (and possibly infinite)
+    /// if there are not enough ops available.
+    pub fn fraction_of_available(&self, state: &WorldState) -> f32 {
+        self.parts_available(state)
+            // the money is not spent from a service
+            .skip(1)
+            .filter(|&(needed, _)| needed > 0)
+            .map(|(needed, available)| {
+                if available > 0 {
+                    needed as f32 / available as f32
+                } else {
+                    f32::INFINITY
+                }
+            })
+            .fold(0., f32::max)
+    }
+
+    /// The share of each part of this cost which the player has,
+    /// between 0 and 1,
+    /// for the money and then the ops of each service
+    /// from the lowest tier to the highest.
+    ///
+    /// Parts which cost nothing are left out.
+    pub fn affordable_shares(&self, state: &WorldState) -> Vec<f32> {
+        self.parts_available(state)
+            .filter(|&(needed, _)| needed > 0)
+            .map(|(needed, available)| (available as f32 / needed as f32).clamp(0., 1.))
+            .collect()
+    }
+
+    /// The amount needed and available of each part of this cost,
+    /// with negative parts taken as zero:
+    /// first the money in millicents,
+    /// then the ops of each service from the lowest tier to the highest.
+    fn parts_available(&self, state: &WorldState) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let cost = self.non_negative();
+        let money = (cost.money.to_millicents(), state.funds.to_millicents());
+        let ops = cost
+            .ops()
+            .map(|(kind, ops)| (ops.0, state.service_by_kind(kind).available.0));
+        std::iter::once(money).chain(ops)
+    }
+
     pub fn is_nothing(&self) -> bool {
         self.money == Money(0)
             && self.base_ops == Ops(0)
             && self.super_ops == Ops(0)
             && self.epic_ops == Ops(0)
             && self.awesome_ops == Ops(0)
+            && self.legendary_ops == Ops(0)
     }
 }
 
@@ -128,6 +251,7 @@ impl std::ops::Add for Cost {
             super_ops: self.super_ops + rhs.super_ops,
             epic_ops: self.epic_ops + rhs.epic_ops,
             awesome_ops: self.awesome_ops + rhs.awesome_ops,
+            legendary_ops: self.legendary_ops + rhs.legendary_ops,
         }
     }
 }
@@ -140,37 +264,46 @@ impl std::iter::Sum for Cost {
 
 impl fmt::Display for Cost {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // negative parts are not charged, so they are not shown either
+        let cost = self.non_negative();
         let mut some = false;
-        if self.awesome_ops != Ops(0) {
-            write!(f, "{} awesome ops", Compact(self.awesome_ops))?;
+        if cost.legendary_ops != Ops(0) {
+            write!(f, "{} legendary ops", Compact(cost.legendary_ops))?;
             some = true;
         }
-        if self.epic_ops != Ops(0) {
+        if cost.awesome_ops != Ops(0) {
             if some {
                 f.write_str(" + ")?;
             }
-            write!(f, "{} epic ops", Compact(self.epic_ops))?;
+            write!(f, "{} awesome ops", Compact(cost.awesome_ops))?;
             some = true;
         }
-        if self.super_ops != Ops(0) {
+        if cost.epic_ops != Ops(0) {
             if some {
                 f.write_str(" + ")?;
             }
-            write!(f, "{} super ops", Compact(self.super_ops))?;
+            write!(f, "{} epic ops", Compact(cost.epic_ops))?;
             some = true;
         }
-        if self.base_ops != Ops(0) {
+        if cost.super_ops != Ops(0) {
             if some {
                 f.write_str(" + ")?;
             }
-            write!(f, "{} base ops", Compact(self.base_ops))?;
+            write!(f, "{} super ops", Compact(cost.super_ops))?;
             some = true;
         }
-        if self.money.0 != 0 {
+        if cost.base_ops != Ops(0) {
             if some {
                 f.write_str(" + ")?;
             }
-            write!(f, "{}", self.money)?;
+            write!(f, "{} base ops", Compact(cost.base_ops))?;
+            some = true;
+        }
+        if cost.money.0 != 0 {
+            if some {
+                f.write_str(" + ")?;
+            }
+            write!(f, "{}", cost.money)?;
         }
         Ok(())
     }
@@ -215,6 +348,25 @@ impl Money {
         Self::cents(self.to_cents())
     }
 
+    /// round to the nearest cent, with half cents rounded up
+    #[inline]
+    pub const fn round_to_cents_half_up(self) -> Self {
+        Self::cents((self.0 + 500).div_euclid(1_000))
+    }
+
+    /// The amount to show to the player:
+    /// rounded to the nearest cent,
+    /// unless it is a positive amount under a cent,
+    /// which is shown in full so that it does not look like nothing.
+    #[inline]
+    pub const fn for_display(self) -> Self {
+        if self.0 > 0 && self.0 < 1_000 {
+            self
+        } else {
+            self.round_to_cents_half_up()
+        }
+    }
+
     /// discard the decimal part
     #[inline]
     pub const fn into_dollar_precision(self) -> Self {
@@ -475,6 +627,16 @@ impl Memory {
     pub fn ratio(self, other: Self) -> f32 {
         self.0 as f32 / other.0 as f32
     }
+
+    /// Subtract the given amount,
+    /// or return `None` if there is less memory than that.
+    #[inline]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0
+            .checked_sub(rhs.0)
+            .filter(|&bytes| bytes >= 0)
+            .map(Memory)
+    }
 }
 
 impl From<i32> for Memory {
@@ -515,6 +677,10 @@ impl std::ops::Sub for Memory {
     }
 }
 
+/// Saturates at the integer bounds,
+/// which is only fit for display-level math:
+/// memory accounting should use [`Memory::checked_sub`]
+/// so that releasing more than is in use does not go unnoticed.
 impl std::ops::SubAssign for Memory {
     fn sub_assign(&mut self, rhs: Self) {
         self.0 = self.0.saturating_sub(rhs.0);
@@ -602,37 +768,37 @@ pub enum ServiceKind {
     Super,
     Epic,
     Awesome,
+    Legendary,
 }
 
 impl fmt::Display for ServiceKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Base => write!(f, "Base"),
-            Self::Super => write!(f, "Super"),
-            Self::Epic => write!(f, "Epic"),
-            Self::Awesome => write!(f, "Awesome"),
-        }
+        f.write_str(self.tier().name)
     }
 }
 
 impl ServiceKind {
+    /// All service kinds, from the lowest tier to the highest.
+    pub const ALL: [ServiceKind; 5] = [
+        ServiceKind::Base,
+        ServiceKind::Super,
+        ServiceKind::Epic,
+        ServiceKind::Awesome,
+        ServiceKind::Legendary,
+    ];
+
     pub fn from_code(code: u8) -> Option<Self> {
-        match code {
-            0 => Some(Self::Base),
-            1 => Some(Self::Super),
-            2 => Some(Self::Epic),
-            3 => Some(Self::Awesome),
-            _ => None,
-        }
+        Self::ALL.get(code as usize).copied()
     }
 
     pub fn to_code(self) -> u8 {
-        match self {
-            Self::Base => 0,
-            Self::Super => 1,
-            Self::Epic => 2,
-            Self::Awesome => 3,
-        }
+        self as u8
+    }
+
+    /// The description of this service tier.
+    #[inline]
+    pub fn tier(self) -> &'static ServiceTier {
+        &SERVICE_TIERS[self.to_code() as usize]
     }
 
     /**
@@ -640,24 +806,25 @@ impl ServiceKind {
      */
     #[inline]
     pub(crate) fn mem_required(&self) -> Memory {
-        match self {
-            Self::Base => Memory::kb(512),
-            Self::Super => Memory::kb(768),
-            Self::Epic => Memory::mb(1),
-            Self::Awesome => Memory::mb(4),
-        }
+        self.tier().mem_required
+    }
+
+    /// The network bandwidth taken per individual operation of this service tier.
+    #[inline]
+    pub(crate) fn bandwidth_required(&self) -> u32 {
+        self.tier().bandwidth
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Money, Ops};
+    use crate::{central::testing::WorldStateBuilder, Cost, Memory, Money, Ops, ServiceKind};
 
     #[test]
     fn test_money() {
         let money = Money::cents(123_456_789);
         assert_eq!(money, Money::dollars(1_234_567) + Money::cents(89));
-        assert_eq!(money, Money::millicents(123456_789_000));
+        assert_eq!(money, Money::millicents(123_456_789_000));
         assert_eq!(money.into_cent_precision(), Money::cents(123_456_789));
         assert_eq!(money.to_dollars(), 1_234_567);
         assert_eq!(money.to_cents(), 123_456_789);
@@ -685,6 +852,45 @@ mod tests {
 
         money4 -= money2;
         assert_eq!(money4, money3);
+
+        // negative amounts keep their sign at any precision
+        let debt = Money::millicents(-123_456);
+        assert_eq!(debt.into_cent_precision(), Money::cents(-123));
+        assert_eq!(debt.into_cent_precision().to_string(), "-$1.23");
+        assert_eq!(debt.for_display().to_string(), "-$1.23");
+        assert_eq!(Money::dollars(-1_500).to_string(), "-$1.5k");
+        assert_eq!(Money::dollars(-2_000_000).to_string(), "-$2M");
+        assert_eq!(Money::cents(-5).into_cent_precision().to_string(), "-$0.05");
+    }
+
+    #[test]
+    fn test_money_rounding_for_display() {
+        let cases = [
+            (Money::millicents(0), Money::cents(0)),
+            (Money::millicents(1_499), Money::cents(1)),
+            (Money::millicents(1_500), Money::cents(2)),
+            (Money::millicents(2_500), Money::cents(3)),
+            (Money::millicents(-1_499), Money::cents(-1)),
+            (Money::millicents(-1_500), Money::cents(-1)),
+            (Money::millicents(-1_501), Money::cents(-2)),
+        ];
+        for (money, rounded) in cases {
+            assert_eq!(money.round_to_cents_half_up(), rounded, "{money:?}");
+            assert_eq!(money.for_display(), rounded, "{money:?}");
+        }
+
+        // positive amounts under a cent are shown in full
+        assert_eq!(
+            Money::millicents(950).round_to_cents_half_up(),
+            Money::cents(1)
+        );
+        assert_eq!(Money::millicents(950).for_display().to_string(), "$0.0095");
+        assert_eq!(Money::millicents(499).for_display().to_string(), "$0.00499");
+        assert_eq!(Money::millicents(1_000).for_display().to_string(), "$0.01");
+        assert_eq!(Money::millicents(1_950).for_display().to_string(), "$0.02");
+        // negative ones are not
+        assert_eq!(Money::millicents(-950).for_display().to_string(), "-$0.01");
+        assert_eq!(Money::millicents(-400).for_display().to_string(), "$0");
     }
 
     #[test]
@@ -704,4 +910,83 @@ mod tests {
         ops3 -= ops1;
         assert_eq!(ops3, ops2);
     }
+
+    #[test]
+    fn test_cost_fraction_of_available() {
+        let state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(1_000))
+            .available_ops(ServiceKind::Super, Ops(40))
+            .build();
+
+        assert_eq!(Cost::nothing().fraction_of_available(&state), 0.);
+        // money does not count
+        assert_eq!(Cost::dollars(5_000).fraction_of_available(&state), 0.);
+        assert_eq!(Cost::base_ops(250).fraction_of_available(&state), 0.25);
+
+        // the most spent service counts
+        let cost = Cost::base_ops(900).and(Cost::super_ops(10));
+        assert_eq!(cost.fraction_of_available(&state), 0.9);
+        let cost = Cost::base_ops(100).and(Cost::super_ops(30));
+        assert_eq!(cost.fraction_of_available(&state), 0.75);
+        let cost = Cost::base_ops(100)
+            .and(Cost::super_ops(30))
+            .and(Cost::dollars(20));
+        assert_eq!(cost.fraction_of_available(&state), 0.75);
+
+        // unaffordable costs go above 1
+        assert_eq!(Cost::base_ops(2_000).fraction_of_available(&state), 2.);
+        assert_eq!(
+            Cost::epic_ops(1).fraction_of_available(&state),
+            f32::INFINITY
+        );
+        // negative parts spend nothing
+        let cost = Cost::base_ops(500).and(Cost::super_ops(-40));
+        assert_eq!(cost.fraction_of_available(&state), 0.5);
+    }
+
+    #[test]
+    fn test_cost_affordable_shares() {
+        let state = WorldStateBuilder::new()
+            .funds(Money::dollars(10))
+            .available_ops(ServiceKind::Base, Ops(1_000))
+            .available_ops(ServiceKind::Super, Ops(40))
+            .build();
+
+        assert_eq!(Cost::nothing().affordable_shares(&state), Vec::<f32>::new());
+        // money comes first, then each service by tier
+        let cost = Cost::dollars(40)
+            .and(Cost::super_ops(80))
+            .and(Cost::base_ops(500));
+        assert_eq!(cost.affordable_shares(&state), vec![0.25, 1., 0.5]);
+        // parts which cannot be afforded at all
+        let cost = Cost::epic_ops(10).and(Cost::super_ops(-40));
+        assert_eq!(cost.affordable_shares(&state), vec![0.]);
+    }
+
+    #[test]
+    fn test_cost_with_negative_parts() {
+        let cost = Cost::base_ops(10).and(Cost::dollars(-5));
+        assert!(!cost.is_non_negative());
+        assert_eq!(cost.non_negative(), Cost::base_ops(10));
+        // the negative part is not shown as a credit
+        assert_eq!(cost.to_string(), "10 base ops");
+        assert_eq!(Cost::dollars(-5).to_string(), "");
+        assert_eq!(Cost::dollars(5).to_string(), "$5");
+
+        // money itself renders negatives
+        assert_eq!(Money::dollars(-400).to_string(), "-$400");
+        assert_eq!(
+            (Money::dollars(100) - Money::cents(50_050)).to_string(),
+            "-$400.50"
+        );
+    }
+
+    #[test]
+    fn test_memory_checked_sub() {
+        let memory = Memory::mb(64);
+        assert_eq!(memory.checked_sub(Memory::mb(16)), Some(Memory::mb(48)));
+        assert_eq!(memory.checked_sub(memory), Some(Memory::zero()));
+        assert_eq!(memory.checked_sub(Memory::mb(65)), None);
+        assert_eq!(Memory::bytes(i64::MIN).checked_sub(Memory::bytes(1)), None);
+    }
 }
@@ -0,0 +1,208 @@
+//! Module for the electricity tariffs,
+//! the pricing models of the energy providers which the player can choose from
+//! once the energy contract is renegotiated.
+//!
+//! Every tariff starts from the price per Wattever of the current cost level
+//! (see [`ELECTRICITY_COST_LEVELS`](super::engine::ELECTRICITY_COST_LEVELS))
+//! and reshapes it:
+//!
+//! - [`Tariff::Flat`] charges the same for all energy;
+//! - [`Tariff::Tiered`] charges more for the first [`TIERED_THRESHOLD`]
+//!   of each bill period and much less beyond it;
+//! - [`Tariff::PeakOffPeak`] charges more during the day,
+//!   when demand peaks, and less during the night.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{t, Money, Time, TIME_UNITS_PER_MINUTE};
+
+use super::state::MICRO_WATTEVER_PER_WATTEVER;
+
+/// the energy consumed in a bill period (in microWattever)
+/// above which the tiered tariff becomes cheaper
+pub const TIERED_THRESHOLD: i64 = 50 * MICRO_WATTEVER_PER_WATTEVER;
+
+/// the price of energy up to the threshold in the tiered tariff,
+/// as a percentage of the flat price
+pub const TIERED_BASE_PERCENT: i64 = 125;
+
+/// the price of energy beyond the threshold in the tiered tariff,
+/// as a percentage of the flat price
+pub const TIERED_DISCOUNT_PERCENT: i64 = 50;
+
+/// the price of energy consumed during the day in the peak/off-peak tariff,
+/// as a percentage of the flat price
+pub const PEAK_PERCENT: i64 = 150;
+
+/// the price of energy consumed during the night in the peak/off-peak tariff,
+/// as a percentage of the flat price
+pub const OFF_PEAK_PERCENT: i64 = 50;
+
+/// the fee for switching to another energy provider
+pub const TARIFF_SWITCH_FEE: Money = Money::dollars(20);
+
+/// the time after switching providers before switching again (10 minutes)
+pub const TARIFF_SWITCH_COOLDOWN: Time = 10 * TIME_UNITS_PER_MINUTE;
+
+/// The energy consumed in a bill period,
+/// split by the time of day, in microWattever.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConsumptionBuckets {
+    /// consumed during the day, when demand peaks
+    pub peak: i64,
+    /// consumed during the night
+    pub off_peak: i64,
+}
+
+impl ConsumptionBuckets {
+    /// All energy consumed.
+    pub fn total(&self) -> i64 {
+        self.peak + self.off_peak
+    }
+
+    /// Whether no energy was consumed.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The pricing model of an energy provider.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Tariff {
+    /// the same price for all energy
+    #[default]
+    Flat,
+    /// pricier up to a threshold per bill period, cheaper beyond it
+    Tiered,
+    /// pricier during the day, cheaper during the night
+    PeakOffPeak,
+}
+
+impl Tariff {
+    /// All tariffs on offer.
+    pub const ALL: [Tariff; 3] = [Tariff::Flat, Tariff::Tiered, Tariff::PeakOffPeak];
+
+    /// The name of the provider offering this tariff.
+    pub fn provider(self) -> &'static str {
+        match self {
+            Tariff::Flat => t!("tariff.flat.provider"),
+            Tariff::Tiered => t!("tariff.tiered.provider"),
+            Tariff::PeakOffPeak => t!("tariff.peak.provider"),
+        }
+    }
+
+    /// A short description of how the tariff charges.
+    pub fn description(self) -> &'static str {
+        match self {
+            Tariff::Flat => t!("tariff.flat.description"),
+            Tariff::Tiered => t!("tariff.tiered.description"),
+            Tariff::PeakOffPeak => t!("tariff.peak.description"),
+        }
+    }
+
+    /// The bill for the given consumption in one bill period,
+    /// given the flat price per Wattever.
+    ///
+    /// Each part of the bill is rounded down to the millicent.
+    pub fn bill(self, price: Money, usage: ConsumptionBuckets) -> Money {
+        let part = |micro_wattever: i64, percent: i64| {
+            let millicents =
+                price.to_millicents() as i128 * micro_wattever as i128 * percent as i128
+                    / (MICRO_WATTEVER_PER_WATTEVER as i128 * 100);
+            Money::millicents(millicents as i64)
+        };
+        match self {
+            Tariff::Flat => part(usage.total(), 100),
+            Tariff::Tiered => {
+                let total = usage.total();
+                let base = total.min(TIERED_THRESHOLD);
+                part(base, TIERED_BASE_PERCENT) + part(total - base, TIERED_DISCOUNT_PERCENT)
+            }
+            Tariff::PeakOffPeak => {
+                part(usage.peak, PEAK_PERCENT) + part(usage.off_peak, OFF_PEAK_PERCENT)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Tariff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.provider())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsumptionBuckets, Tariff};
+    use crate::Money;
+
+    /// `w` Wattever split into peak and off-peak
+    fn usage(peak: i64, off_peak: i64) -> ConsumptionBuckets {
+        ConsumptionBuckets {
+            peak: peak * 1_000_000,
+            off_peak: off_peak * 1_000_000,
+        }
+    }
+
+    #[test]
+    fn test_flat_bill() {
+        let price = Money::cents(32);
+        assert_eq!(Tariff::Flat.bill(price, usage(0, 0)), Money::zero());
+        // 10 Wev at 32 cents
+        assert_eq!(Tariff::Flat.bill(price, usage(4, 6)), Money::cents(320));
+        // 1.5 Wev at 29 cents = 43.5 cents
+        let half = ConsumptionBuckets {
+            peak: 1_500_000,
+            off_peak: 0,
+        };
+        assert_eq!(
+            Tariff::Flat.bill(Money::cents(29), half),
+            Money::millicents(43_500)
+        );
+    }
+
+    #[test]
+    fn test_tiered_bill() {
+        let price = Money::cents(32);
+        // below the threshold: 20 Wev at 40 cents
+        assert_eq!(Tariff::Tiered.bill(price, usage(10, 10)), Money::cents(800));
+        // exactly at the threshold: 50 Wev at 40 cents
+        assert_eq!(
+            Tariff::Tiered.bill(price, usage(25, 25)),
+            Money::cents(2_000)
+        );
+        // 50 Wev at 40 cents + 150 Wev at 16 cents
+        assert_eq!(
+            Tariff::Tiered.bill(price, usage(120, 80)),
+            Money::cents(2_000 + 2_400)
+        );
+        // the same as flat at 75 Wev
+        assert_eq!(
+            Tariff::Tiered.bill(price, usage(75, 0)),
+            Tariff::Flat.bill(price, usage(75, 0))
+        );
+    }
+
+    #[test]
+    fn test_peak_off_peak_bill() {
+        let price = Money::cents(32);
+        // 10 Wev at 48 cents + 30 Wev at 16 cents
+        assert_eq!(
+            Tariff::PeakOffPeak.bill(price, usage(10, 30)),
+            Money::cents(480 + 480)
+        );
+        // the same as flat when evenly split
+        assert_eq!(
+            Tariff::PeakOffPeak.bill(price, usage(20, 20)),
+            Tariff::Flat.bill(price, usage(20, 20))
+        );
+        // rounded down per part: 1 µWev at peak and off-peak
+        let tiny = ConsumptionBuckets {
+            peak: 1,
+            off_peak: 1,
+        };
+        assert_eq!(Tariff::PeakOffPeak.bill(price, tiny), Money::zero());
+    }
+}
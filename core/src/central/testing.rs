@@ -0,0 +1,320 @@
+//! Module for building world states for tests and the playground.
+//!
+//! [`WorldStateBuilder`] keeps the invariants which the game maintains
+//! on its own while playing,
+//! so that fixtures do not describe states which cannot happen:
+//!
+//! - nodes are numbered from 0, in order;
+//! - services are unlocked in tier order,
+//!   and a public service is always unlocked;
+//! - user specs are sorted by ID and only use unlocked services;
+//! - used cards exist, appear once, and are sorted by ID.
+//!
+//! The invariants are checked with debug assertions on [`build`](WorldStateBuilder::build).
+
+use crate::{
+    central::{cards::all::card_by_id, engine::CloudNode, state::UsedCard},
+    Money, Ops, ServiceKind, Time, UserKind, WorldState,
+};
+
+/// A builder of consistent world states,
+/// starting from the state of a new game.
+///
+/// Fields not covered by the builder
+/// can still be set with struct update syntax on the built state.
+#[derive(Debug, Default)]
+pub struct WorldStateBuilder {
+    state: WorldState,
+}
+
+impl WorldStateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the game time.
+    pub fn time(mut self, time: Time) -> Self {
+        self.state.time = time;
+        self
+    }
+
+    /// Set the available funds.
+    pub fn funds(mut self, funds: Money) -> Self {
+        self.state.funds = funds;
+        self
+    }
+
+    /// Set the total money earned.
+    pub fn earned(mut self, earned: Money) -> Self {
+        self.state.earned = earned;
+        self
+    }
+
+    /// Set the base service demand.
+    pub fn demand(mut self, demand: f64) -> Self {
+        self.state.demand = demand;
+        self
+    }
+
+    /// Set the total number of requests dropped.
+    pub fn requests_dropped(mut self, count: u64) -> Self {
+        self.state.requests_dropped = count;
+        self
+    }
+
+    /// Add a node, or replace the node with the same ID.
+    ///
+    /// A new node must take the next ID in line.
+    pub fn with_node(mut self, node: CloudNode) -> Self {
+        let nodes = &mut self.state.nodes;
+        if let Some(existing) = nodes.get_mut(node.id as usize) {
+            *existing = node;
+        } else {
+            debug_assert_eq!(
+                node.id as usize,
+                nodes.len(),
+                "node IDs must follow one another"
+            );
+            nodes.push(node);
+        }
+        self
+    }
+
+    /// Replace all nodes with the given number of fully upgraded nodes.
+    pub fn with_nodes_fully_upgraded(mut self, count: u32) -> Self {
+        debug_assert!(count > 0, "there must be at least one node");
+        self.state.nodes = (0..count).map(CloudNode::new_fully_upgraded).collect();
+        self
+    }
+
+    /// Unlock a service, along with all services of lower tiers.
+    pub fn service_unlocked(mut self, kind: ServiceKind) -> Self {
+        for lower in ServiceKind::ALL.into_iter().take(kind as usize + 1) {
+            let _ = self.state.service_by_kind_mut(lower).state.unlock();
+        }
+        self
+    }
+
+    /// Unlock a service and publish it at the given price.
+    pub fn service_published(self, kind: ServiceKind, price: Money) -> Self {
+        let mut builder = self.service_unlocked(kind);
+        let service = builder.state.service_by_kind_mut(kind);
+        let _ = service.state.publish();
+        service.price = price;
+        builder
+    }
+
+    /// Set the price of a service, without publishing it.
+    pub fn price(mut self, kind: ServiceKind, price: Money) -> Self {
+        self.state.service_by_kind_mut(kind).price = price;
+        self
+    }
+
+    /// Set the ops available to spend from a service,
+    /// unlocking it if they are any.
+    pub fn available_ops(self, kind: ServiceKind, ops: Ops) -> Self {
+        let mut builder = if ops > Ops(0) {
+            self.service_unlocked(kind)
+        } else {
+            self
+        };
+        builder.state.service_by_kind_mut(kind).available = ops;
+        builder
+    }
+
+    /// Set the total ops performed by a service,
+    /// unlocking it if they are any.
+    pub fn total_ops(self, kind: ServiceKind, ops: Ops) -> Self {
+        let mut builder = if ops > Ops(0) {
+            self.service_unlocked(kind)
+        } else {
+            self
+        };
+        builder.state.service_by_kind_mut(kind).total = ops;
+        builder
+    }
+
+    /// Add a paying customer of a service,
+    /// publishing the service if needed.
+    pub fn with_customer(self, kind: ServiceKind) -> Self {
+        self.with_user(kind, UserKind::Customer, 0)
+    }
+
+    /// Add a user of a service, with the given trial time,
+    /// publishing the service if needed.
+    pub fn with_user(self, kind: ServiceKind, user: UserKind, trial_time: Time) -> Self {
+        let mut builder = if is_public(&self.state, kind) {
+            self
+        } else {
+            let price = self.state.service_by_kind(kind).price;
+            self.service_published(kind, price)
+        };
+        builder.state.add_user_spec(kind, user, trial_time);
+        builder
+    }
+
+    /// Mark the given project cards as used at the current game time.
+    pub fn cards_used(mut self, ids: &[&'static str]) -> Self {
+        for &id in ids {
+            debug_assert!(card_by_id(id).is_some(), "no such card {id}");
+            if !self.state.is_card_used(id) {
+                self.state.cards_used.push(UsedCard {
+                    id: id.into(),
+                    time: self.state.time,
+                });
+            }
+        }
+        self.state
+            .cards_used
+            .sort_unstable_by(|c1, c2| c1.id.cmp(&c2.id));
+        self
+    }
+
+    /// Build the world state.
+    pub fn build(self) -> WorldState {
+        debug_check_invariants(&self.state);
+        self.state
+    }
+}
+
+fn is_public(state: &WorldState, kind: ServiceKind) -> bool {
+    let service = state.service_by_kind(kind);
+    service.state.is_published()
+}
+
+/// Check with debug assertions
+/// that the world state holds the invariants of the game.
+pub fn debug_check_invariants(state: &WorldState) {
+    for (index, node) in state.nodes.iter().enumerate() {
+        debug_assert_eq!(node.id as usize, index, "nodes out of order");
+    }
+
+    let mut lower_unlocked = true;
+    for (kind, service) in state.services() {
+        debug_assert!(
+            !service.state.is_unlocked() || lower_unlocked,
+            "{kind} unlocked before a lower tier"
+        );
+        lower_unlocked = service.state.is_unlocked();
+    }
+
+    for pair in state.user_specs.windows(2) {
+        debug_assert!(
+            pair[0].id < pair[1].id,
+            "user specs out of order: {} >= {}",
+            pair[0].id,
+            pair[1].id
+        );
+    }
+    for spec in &state.user_specs {
+        debug_assert!(
+            state.service_by_kind(spec.service).state.is_unlocked(),
+            "user of locked service {}",
+            spec.service
+        );
+    }
+
+    for pair in state.cards_used.windows(2) {
+        debug_assert!(
+            pair[0].id < pair[1].id,
+            "used cards out of order or repeated: {} >= {}",
+            pair[0].id,
+            pair[1].id
+        );
+    }
+    for used in &state.cards_used {
+        debug_assert!(card_by_id(&used.id).is_some(), "no such card {}", used.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorldStateBuilder;
+    use crate::{
+        central::{engine::CloudNode, state::ServiceState},
+        Money, Ops, ServiceKind, UserKind,
+    };
+
+    #[test]
+    fn test_publishing_unlocks_lower_tiers() {
+        let state = WorldStateBuilder::new()
+            .service_published(ServiceKind::Epic, Money::cents(2))
+            .build();
+        assert!(state.base_service.state.is_unlocked());
+        assert!(state.super_service.state.is_unlocked());
+        assert_eq!(state.epic_service.state, ServiceState::Published);
+        assert_eq!(state.epic_service.price, Money::cents(2));
+        // lower tiers are unlocked but stay private
+        assert_eq!(state.super_service.state, ServiceState::Private);
+        assert_eq!(state.awesome_service.state, ServiceState::Locked);
+
+        // so does having ops from a service
+        let state = WorldStateBuilder::new()
+            .total_ops(ServiceKind::Awesome, Ops(10))
+            .build();
+        assert!(
+            state.super_service.state.is_unlocked() && state.awesome_service.state.is_unlocked()
+        );
+        assert_eq!(state.legendary_service.state, ServiceState::Locked);
+    }
+
+    #[test]
+    fn test_customers_sorted_and_published() {
+        let state = WorldStateBuilder::new()
+            .with_customer(ServiceKind::Super)
+            .with_customer(ServiceKind::Base)
+            .with_user(ServiceKind::Base, UserKind::Attacker, 0)
+            .build();
+        let ids: Vec<_> = state.user_specs.iter().map(|spec| spec.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(state.base_service.state, ServiceState::Published);
+        assert_eq!(state.super_service.state, ServiceState::Published);
+        // publishing keeps the default price
+        assert_eq!(
+            state.super_service.price,
+            ServiceKind::Super.tier().default_price
+        );
+    }
+
+    #[test]
+    fn test_cards_used_sorted() {
+        let state = WorldStateBuilder::new()
+            .time(500)
+            .cards_used(&["b1", "b0", "b1"])
+            .cards_used(&["a1p"])
+            .build();
+        let ids: Vec<_> = state.cards_used.iter().map(|c| c.id.as_ref()).collect();
+        assert_eq!(ids, ["a1p", "b0", "b1"]);
+        assert!(state.cards_used.iter().all(|c| c.time == 500));
+    }
+
+    #[test]
+    fn test_nodes_in_order() {
+        let state = WorldStateBuilder::new()
+            .with_node(CloudNode::new(1))
+            .with_node(CloudNode::new_fully_upgraded(0))
+            .build();
+        assert_eq!(state.nodes.len(), 2);
+        assert_eq!(state.nodes[0], CloudNode::new_fully_upgraded(0));
+
+        let state = WorldStateBuilder::new()
+            .with_nodes_fully_upgraded(3)
+            .build();
+        assert_eq!(state.nodes.len(), 3);
+        assert!(state.nodes.iter().all(|node| node.num_cores > 1));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "node IDs must follow one another")]
+    fn test_node_gap_rejected() {
+        WorldStateBuilder::new().with_node(CloudNode::new(2));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "no such card")]
+    fn test_unknown_card_rejected() {
+        WorldStateBuilder::new().cards_used(&["nope"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Module for the data describing each cloud service tier.
+//!
+//! Anything that differs between service tiers
+//! should be looked up here through [`ServiceKind::tier`]
+//! rather than matched on the service kind.
+
+use crate::{Memory, Money, ServiceKind};
+
+/// The description of a cloud service tier.
+#[derive(Debug)]
+pub struct ServiceTier {
+    /// the name of the tier
+    pub name: &'static str,
+    /// the color of the tier's service card
+    pub color: &'static str,
+    /// the memory required per individual operation
+    pub mem_required: Memory,
+    /// the network bandwidth taken per individual operation
+    /// (see [`bandwidth`](crate::central::bandwidth))
+    pub bandwidth: u32,
+    /// the amount of memory that each cloud node must reserve
+    /// to provide the tier, before modifiers
+    pub memory_reserve: Memory,
+    /// how many times longer an operation takes to process
+    /// than a base operation, before modifiers
+    pub time_factor: u32,
+    /// the price per operation when the tier is unlocked
+    pub default_price: Money,
+    /// the card which unlocks the tier
+    /// (or `None` if it is unlocked from the start)
+    pub unlock_card: Option<&'static str>,
+    /// the card which publishes the tier
+    pub publish_card: &'static str,
+}
+
+/// All service tiers, indexed by [`ServiceKind::to_code`].
+pub static SERVICE_TIERS: [ServiceTier; ServiceKind::ALL.len()] = [
+    ServiceTier {
+        name: "Base",
+        color: "#ccc",
+        mem_required: Memory::kb(512),
+        bandwidth: 1,
+        memory_reserve: Memory::mb(32),
+        time_factor: 1,
+        default_price: Money::millicents(50),
+        unlock_card: None,
+        publish_card: "a0p",
+    },
+    ServiceTier {
+        name: "Super",
+        color: "#bbf",
+        mem_required: Memory::kb(768),
+        bandwidth: 2,
+        memory_reserve: Memory::mb(256),
+        time_factor: 4,
+        default_price: Money::dec_cents(5),
+        unlock_card: Some("a1"),
+        publish_card: "a1p",
+    },
+    ServiceTier {
+        name: "Epic",
+        color: "#efc",
+        mem_required: Memory::mb(1),
+        bandwidth: 4,
+        memory_reserve: Memory::gb(2),
+        time_factor: 16,
+        default_price: Money::cents(5),
+        unlock_card: Some("a2"),
+        publish_card: "a2p",
+    },
+    ServiceTier {
+        name: "Awesome",
+        color: "#ecf",
+        mem_required: Memory::mb(4),
+        bandwidth: 8,
+        memory_reserve: Memory::gb(16),
+        time_factor: 64,
+        default_price: Money::dollars(1),
+        unlock_card: Some("a3"),
+        publish_card: "a3p",
+    },
+    ServiceTier {
+        name: "Legendary",
+        color: "#fdb",
+        mem_required: Memory::mb(16),
+        bandwidth: 16,
+        memory_reserve: Memory::gb(64),
+        time_factor: 256,
+        default_price: Money::dollars(20),
+        unlock_card: Some("a4"),
+        publish_card: "a4p",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::SERVICE_TIERS;
+    use crate::{
+        central::cards::{all::card_by_id, CardEffect},
+        Memory, ServiceKind,
+    };
+
+    #[test]
+    fn test_tier_lookups() {
+        for (code, kind) in ServiceKind::ALL.into_iter().enumerate() {
+            assert_eq!(kind.to_code() as usize, code);
+            assert_eq!(ServiceKind::from_code(code as u8), Some(kind));
+            assert!(std::ptr::eq(kind.tier(), &SERVICE_TIERS[code]));
+            assert_eq!(kind.to_string(), kind.tier().name);
+        }
+        assert_eq!(ServiceKind::from_code(ServiceKind::ALL.len() as u8), None);
+
+        assert_eq!(ServiceKind::Base.mem_required(), Memory::kb(512));
+        assert_eq!(ServiceKind::Awesome.mem_required(), Memory::mb(4));
+        assert_eq!(ServiceKind::Epic.tier().memory_reserve, Memory::gb(2));
+        assert_eq!(ServiceKind::Super.tier().time_factor, 4);
+
+        // each tier is more demanding than the one before
+        for pair in SERVICE_TIERS.windows(2) {
+            assert!(pair[0].mem_required < pair[1].mem_required);
+            assert!(pair[0].memory_reserve < pair[1].memory_reserve);
+            assert!(pair[0].time_factor < pair[1].time_factor);
+            assert!(pair[0].default_price < pair[1].default_price);
+        }
+    }
+
+    #[test]
+    fn test_tier_cards_wired() {
+        for kind in ServiceKind::ALL {
+            let tier = kind.tier();
+            if let Some(id) = tier.unlock_card {
+                let card = card_by_id(id).unwrap();
+                assert_eq!(card.effect, CardEffect::UnlockService(kind), "{id}");
+            }
+            let card = card_by_id(tier.publish_card).unwrap();
+            assert_eq!(card.effect, CardEffect::PublishService(kind));
+        }
+    }
+}
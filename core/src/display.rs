@@ -0,0 +1,216 @@
+use std::fmt;
+
+use crate::{central::engine::DAY_LENGTH, Time, TIME_UNITS_PER_SECOND};
+
+const SEPARATOR_CHAR: &str = "\u{2006}";
+
+/// Convert game time to whole seconds, rounding down.
+pub fn to_seconds(time: Time) -> u64 {
+    time / TIME_UNITS_PER_SECOND
+}
+
+/// A formatting utility that adds a half-width space for each 3 digits
+pub struct Separating(pub i64);
+
+impl fmt::Display for Separating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut n = self.0;
+        if n == 0 {
+            f.write_str("0")?;
+            return Ok(());
+        }
+        if n < 0 {
+            f.write_str("-")?;
+            n = -n;
+        }
+
+        let mut parts = vec![];
+
+        while n > 0 {
+            let part = n % 1000;
+            n /= 1000;
+            parts.push(part);
+        }
+
+        // traverse in reverse order
+        let mut first = true;
+        for (i, part) in parts.iter().rev().enumerate() {
+            if !first {
+                // half-width space
+                f.write_str(SEPARATOR_CHAR)?;
+            }
+            if i == 0 {
+                write!(f, "{part}")?;
+            } else {
+                write!(f, "{part:03}")?;
+            }
+            first = false;
+        }
+
+        Ok(())
+    }
+}
+
+/// A formatting utility for a large count in a few characters,
+/// such as "950", "12.3k", "123k" or "1.2M".
+pub struct ShortCount(pub u64);
+
+impl fmt::Display for ShortCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.0;
+        let (unit, suffix) = match n {
+            0..=999 => return write!(f, "{n}"),
+            1_000..=999_999 => (1_000, "k"),
+            1_000_000..=999_999_999 => (1_000_000, "M"),
+            1_000_000_000..=999_999_999_999 => (1_000_000_000, "G"),
+            _ => (1_000_000_000_000, "T"),
+        };
+        // one decimal below a hundred,
+        // rounded down so as not to overstate it
+        let tenths = n / (unit / 10);
+        if tenths.is_multiple_of(10) || tenths >= 1_000 {
+            write!(f, "{}{suffix}", Separating((tenths / 10) as i64))
+        } else {
+            write!(f, "{}.{}{suffix}", tenths / 10, tenths % 10)
+        }
+    }
+}
+
+/// A formatting utility for a length of game time,
+/// such as "24s", "3m 24s" or "1h 02m".
+///
+/// Seconds are left out once the duration reaches an hour.
+pub struct GameDuration(pub Time);
+
+impl fmt::Display for GameDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = to_seconds(self.0);
+        match (seconds / 3_600, seconds / 60 % 60, seconds % 60) {
+            (0, 0, seconds) => write!(f, "{seconds}s"),
+            (0, minutes, seconds) => write!(f, "{minutes}m {seconds:02}s"),
+            (hours, minutes, _) => write!(f, "{hours}h {minutes:02}m"),
+        }
+    }
+}
+
+/// A formatting utility for a moment in game time,
+/// as the day and time of day of the daily demand cycle,
+/// such as "day 2, 14:32".
+///
+/// The game starts on day 1 at 06:00,
+/// so that the daytime half of the cycle
+/// runs from 06:00 to 18:00.
+pub struct GameTimestamp(pub Time);
+
+impl fmt::Display for GameTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time_of_day = self.0 % DAY_LENGTH + DAY_LENGTH / 4;
+        let day = self.0 / DAY_LENGTH + time_of_day / DAY_LENGTH + 1;
+        let minute_of_day = (time_of_day % DAY_LENGTH) * 24 * 60 / DAY_LENGTH;
+        write!(
+            f,
+            "day {day}, {:02}:{:02}",
+            minute_of_day / 60,
+            minute_of_day % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameDuration, GameTimestamp, Separating, ShortCount};
+    use crate::{central::engine::DAY_LENGTH, Time, TIME_UNITS_PER_SECOND};
+
+    #[test]
+    fn test_separating() {
+        assert_eq!(Separating(0).to_string(), "0");
+
+        assert_eq!(Separating(1).to_string(), "1");
+
+        assert_eq!(Separating(1_000).to_string(), "1\u{2006}000");
+
+        assert_eq!(Separating(435).to_string(), "435");
+
+        assert_eq!(
+            Separating(499_999_999).to_string(),
+            "499\u{2006}999\u{2006}999"
+        );
+
+        assert_eq!(Separating(-45_300).to_string(), "-45\u{2006}300");
+    }
+
+    #[test]
+    fn test_short_count() {
+        assert_eq!(ShortCount(0).to_string(), "0");
+        assert_eq!(ShortCount(999).to_string(), "999");
+        assert_eq!(ShortCount(1_000).to_string(), "1k");
+        assert_eq!(ShortCount(1_250).to_string(), "1.2k");
+        assert_eq!(ShortCount(12_345).to_string(), "12.3k");
+        // no decimals past a hundred
+        assert_eq!(ShortCount(123_456).to_string(), "123k");
+        assert_eq!(ShortCount(999_999).to_string(), "999k");
+        assert_eq!(ShortCount(1_234_567).to_string(), "1.2M");
+        assert_eq!(ShortCount(40_000_000_000).to_string(), "40G");
+        assert_eq!(
+            ShortCount(1_234_000_000_000_000).to_string(),
+            "1\u{2006}234T"
+        );
+    }
+
+    #[test]
+    fn test_game_duration() {
+        assert_eq!(GameDuration(0).to_string(), "0s");
+        // partial seconds are left out
+        assert_eq!(GameDuration(TIME_UNITS_PER_SECOND - 1).to_string(), "0s");
+        assert_eq!(GameDuration(50_000).to_string(), "5s");
+        assert_eq!(GameDuration(59 * TIME_UNITS_PER_SECOND).to_string(), "59s");
+        assert_eq!(
+            GameDuration(60 * TIME_UNITS_PER_SECOND).to_string(),
+            "1m 00s"
+        );
+        assert_eq!(
+            GameDuration(204 * TIME_UNITS_PER_SECOND).to_string(),
+            "3m 24s"
+        );
+        assert_eq!(
+            GameDuration(3_599 * TIME_UNITS_PER_SECOND).to_string(),
+            "59m 59s"
+        );
+        assert_eq!(
+            GameDuration(3_600 * TIME_UNITS_PER_SECOND).to_string(),
+            "1h 00m"
+        );
+        assert_eq!(
+            GameDuration(3_720 * TIME_UNITS_PER_SECOND).to_string(),
+            "1h 02m"
+        );
+        assert_eq!(GameDuration(Time::MAX).to_string(), "512409557603h 02m");
+    }
+
+    #[test]
+    fn test_game_timestamp() {
+        assert_eq!(GameTimestamp(0).to_string(), "day 1, 06:00");
+        assert_eq!(GameTimestamp(DAY_LENGTH / 4).to_string(), "day 1, 12:00");
+        // a game day lasts 10 minutes, so a game minute lasts 0.42 seconds
+        assert_eq!(
+            GameTimestamp(DAY_LENGTH / 2 - 1).to_string(),
+            "day 1, 17:59"
+        );
+        assert_eq!(
+            GameTimestamp(DAY_LENGTH * 3 / 4 - 1).to_string(),
+            "day 1, 23:59"
+        );
+        assert_eq!(
+            GameTimestamp(DAY_LENGTH * 3 / 4).to_string(),
+            "day 2, 00:00"
+        );
+        assert_eq!(
+            GameTimestamp(DAY_LENGTH + DAY_LENGTH * 8 / 24 + 4_000).to_string(),
+            "day 2, 14:00"
+        );
+        assert_eq!(
+            GameTimestamp(Time::MAX).to_string(),
+            "day 3074457345619, 12:12"
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! The English text table, which is also the fallback for other locales.
+//!
+//! Entries must be kept sorted by key.
+
+pub(super) static EN: &[(&str, &str)] = &[
+    ("about.close", "Close"),
+    ("about.credits", "Made by Eduardo Pinho (E_net4) for GitHub Game Off 2023."),
+    ("about.license_code", "Game code: MIT or Apache-2.0, at your choice"),
+    ("about.license_fonts", "PT Sans fonts: SIL Open Font License"),
+    ("about.license_opclick", "Op click sound: Pixabay content license"),
+    ("about.license_zipclick", "Zippo click sound: from OpenGameArt.org"),
+    ("about.licenses", "Licenses"),
+    ("about.report_issue", "Report an issue"),
+    ("about.title", "About 10x Cloud Champion"),
+    ("about.version", "Version {version}"),
+    ("app.buy", "Buy {cost}"),
+    ("app.completed_projects", "Completed projects ({count})"),
+    ("app.filter_projects", "Search projects"),
+    ("app.pause", "Pause the game"),
+    ("app.paused", "Paused"),
+    ("app.pinned", "Pinned"),
+    ("app.resume", "Resume"),
+    ("app.upcoming", "Upcoming"),
+    ("bailout.accept", "Accept loan"),
+    ("bailout.alternative", "Alternatively, you can declare bankruptcy and start over."),
+    ("bailout.bankruptcy", "Declare bankruptcy"),
+    ("bailout.offer", "Your company has been deep in debt for too long. A bank is willing to lend you enough to get back on your feet, but it will charge interest and take a share of all earnings until the loan is repaid."),
+    ("bailout.title", "Bailout"),
+    ("business.auto_pay", "Auto-pay"),
+    ("business.auto_pay_hint", "Pay electricity bills as soon as they come, funds permitting"),
+    ("business.available_ops", "Available {service} ops:"),
+    ("business.change_provider", "Change provider"),
+    ("business.credit", "Electricity credit: "),
+    ("business.customer", "{service}: {revenue} from {ops} ops"),
+    ("business.customer_trial", " (trial)"),
+    ("business.customers", "Top customers"),
+    ("business.daytime", "Daytime: demand is above average"),
+    ("business.drop_rate", "Request drop rate: "),
+    ("business.electricity_bill", "Electricity bill: "),
+    ("business.energy_consumption", "Energy consumption: "),
+    ("business.failure_rate", "Request failure rate: "),
+    ("business.funds", "Funds: "),
+    ("business.growth_stalled", " (growth stalled)"),
+    ("business.growth_stalled_hint", "Too many requests are being dropped, add capacity to grow again"),
+    ("business.in_debt_hint", "The company is in debt: pay it off before buying anything else"),
+    ("business.insurance", "Insured: {premium} per bill, {payouts} paid out"),
+    ("business.insurance_cancel", "Cancel"),
+    ("business.insurance_cancel_hint", "Insurance can only be taken again {time} after cancelling"),
+    ("business.loan", "Loan: "),
+    ("business.next_reward", "{bills} more for {reward}"),
+    ("business.nighttime", "Nighttime: demand is below average"),
+    ("business.pay", "Pay"),
+    ("business.pay_partial", "Pay {amount}"),
+    ("business.powersave_active", "Powersave mode! Pay the bill to restore full power"),
+    ("business.powersave_in", "Powersave in {time} unless bill paid"),
+    ("business.prepay", "Prepay {amount}"),
+    ("business.prepay_hint", "Pay in advance for future bills"),
+    ("business.repay", "Repay"),
+    ("business.sla", "{service} SLA: {rate} dropped this period"),
+    ("business.streak", "Bills paid on time:"),
+    ("business.streak_hint", "Pay each bill before the next one comes in to keep the streak going"),
+    ("business.tariff", "Energy provider:"),
+    ("business.visibility", "Visibility: "),
+    ("card.a0p.description", "Always test before delivering to the public"),
+    ("card.a0p.title", "Test your service"),
+    ("card.a1.description", "Next generation Cloud services"),
+    ("card.a1.title", "Super Ops"),
+    ("card.a1p.description", "Deliver Super Ops to the public"),
+    ("card.a1p.title", "Publish Super Ops"),
+    ("card.a2.description", "State of the art Cloud services"),
+    ("card.a2.title", "Epic Ops"),
+    ("card.a2p.description", "Deliver Epic Ops to the public"),
+    ("card.a2p.title", "Publish Epic Ops"),
+    ("card.a3.description", "The Cloud services to rule them all"),
+    ("card.a3.title", "Awesome Ops"),
+    ("card.a3p.description", "Deliver Awesome Ops to the public"),
+    ("card.a3p.title", "Publish Awesome Ops"),
+    ("card.a4.description", "Cloud services they will tell stories about"),
+    ("card.a4.title", "Legendary Ops"),
+    ("card.a4p.description", "Deliver Legendary Ops to the public"),
+    ("card.a4p.title", "Publish Legendary Ops"),
+    ("card.b0.description", "Father believes in you"),
+    ("card.b0.title", "Incentive from your family"),
+    ("card.b00.description", "Grandpa believes in you"),
+    ("card.b00.title", "Extra bonus from your family"),
+    ("card.b000.description", "Your cool rich cousin believes in you"),
+    ("card.b000.title", "Donation from cousin V"),
+    ("card.b1.description", "All base ops give you an extra $0.00005"),
+    ("card.b1.title", "College fund initiative"),
+    ("card.b1s.description", "Base ops give you an extra $0.00008, but each bill period dropping over 5% of base customer requests costs $40"),
+    ("card.b1s.title", "Sign an SLA for the base service"),
+    ("card.b2.description", "All super ops give you an extra $0.0005"),
+    ("card.b2.title", "Government funded project"),
+    ("card.b2s.description", "Super ops give you an extra $0.0008, but each bill period dropping over 5% of super customer requests costs $400"),
+    ("card.b2s.title", "Sign an SLA for the super service"),
+    ("card.b3.description", "All epic ops give you an extra $0.005"),
+    ("card.b3.title", "United Nations funding"),
+    ("card.b4.description", "All awesome ops give you an extra $0.05"),
+    ("card.b4.title", "Seamless monetary volition"),
+    ("card.bx.description", "Trade your spare ops for ops of a higher tier, at a steep rate"),
+    ("card.bx.title", "Op arbitrage desk"),
+    ("card.c0.description", "Use available memory to make your service faster"),
+    ("card.c0.title", "Implement caching"),
+    ("card.c1.description", "Use more memory to make your service even faster"),
+    ("card.c1.title", "More caching"),
+    ("card.c2.description", "An enhanced cache with greater throughput"),
+    ("card.c2.title", "High-end predictive caching"),
+    ("card.c3.description", "Do caching like it knew almost everything in advance"),
+    ("card.c3.title", "Extrapolating clairvoyant cache system"),
+    ("card.d0.description", "Offer a trial period for your first customer"),
+    ("card.d0.title", "Let someone try"),
+    ("card.d1.description", "Improves your ranking on search engines"),
+    ("card.d1.title", "Optimize SEO"),
+    ("card.d2.description", "Good ol' paper ads around SV"),
+    ("card.d2.title", "Fliers"),
+    ("card.d3.5.description", "Present your services to a savvy audience"),
+    ("card.d3.5.title", "Conference talk"),
+    ("card.d3.description", "A sneak peek into your services"),
+    ("card.d3.title", "3 second video ad"),
+    ("card.d4.5.description", "Regain your clients' trust"),
+    ("card.d4.5.title", "Blame caching"),
+    ("card.d4.description", "Millions will see this board"),
+    ("card.d4.title", "Capital city billboard ad"),
+    ("card.d5.5.description", "“These services are out of this world!”"),
+    ("card.d5.5.title", "SolVision Song Contest ad"),
+    ("card.d5.description", "Great services are advertized in great events"),
+    ("card.d5.title", "Cricket Championship ad"),
+    ("card.d6.description", "Make a deal with EWS, your biggest rival"),
+    ("card.d6.title", "Strategic company purchase"),
+    ("card.d7.description", "Your ultimate brand ambassadors"),
+    ("card.d7.title", "Hypnodrones"),
+    ("card.e0.description", "Get a better deal and choose your energy provider"),
+    ("card.e0.title", "Renegotiate energy contract"),
+    ("card.e1.description", "Increase energy efficiency"),
+    ("card.e1.title", "Repair A/C system"),
+    ("card.e2.description", "Generate some energy to reduce future costs"),
+    ("card.e2.title", "Buy solar panels"),
+    ("card.e3.description", "Commit to clean energy for the long term"),
+    ("card.e3.title", "Clean energy plan"),
+    ("card.e4.description", "All systems powered by your own energy"),
+    ("card.e4.title", "Dedicated Power Plant"),
+    ("card.e5.description", "Develop a groundbreaking source of free energy"),
+    ("card.e5.title", "Free energy research"),
+    ("card.ea.description", "Let the energy company charge your bills as soon as they come"),
+    ("card.ea.title", "Direct debit mandate"),
+    ("card.eta", "~{time}"),
+    ("card.f0.description", "Detect obvious cases of malicious requests"),
+    ("card.f0.title", "Request anomaly monitoring"),
+    ("card.f1.description", "Detect more cases of DoS attacks"),
+    ("card.f1.title", "Adversarial generative spam network detection"),
+    ("card.f2.description", "Eliminate bad requests"),
+    ("card.f2.title", "Universal introspective malice correction"),
+    ("card.g1.description", "A dedicated fiber line for ten times the bandwidth"),
+    ("card.g1.title", "Fiber uplink"),
+    ("card.g2.description", "Exchange traffic directly with the big networks for ten times the bandwidth"),
+    ("card.g2.title", "Peering agreement"),
+    ("card.g3.description", "Run your own backbone for ten times the bandwidth"),
+    ("card.g3.title", "Backbone ownership"),
+    ("card.h0.description", "A free RAM upgrade for every node, bought in bulk"),
+    ("card.h0.title", "Bulk RAM discount"),
+    ("card.h1.description", "Faster CPUs on all current nodes, at 20% more energy per request"),
+    ("card.h1.title", "Overclock firmware"),
+    ("card.h2.description", "Pay a premium with every bill to be compensated when many requests are dropped"),
+    ("card.h2.title", "Infrastructure insurance"),
+    ("card.i0.description", "Estimate the visibility of your services"),
+    ("card.i0.title", "Market introspection"),
+    ("card.i1.description", "Estimate the power consumption of your services"),
+    ("card.i1.title", "Electronic energy meter"),
+    ("card.i2.description", "Estimate request drop & failure rates"),
+    ("card.i2.title", "Service metric ingestion pipeline"),
+    ("card.lose.description", "Don't you have anything else to do?"),
+    ("card.lose.title", "What a waste of time"),
+    ("card.n1.description", "Prepare the space for more nodes"),
+    ("card.n1.title", "Central node routing"),
+    ("card.n2.description", "Distribute routing costs to all nodes"),
+    ("card.n2.title", "Improved routing"),
+    ("card.n3.description", "Make space for more racks"),
+    ("card.n3.title", "Room for more servers"),
+    ("card.n5.description", "Relocate and make reservations for large data centers"),
+    ("card.n5.title", "Geographical expansion"),
+    ("card.n6.description", "Eliminate all routing costs"),
+    ("card.n6.title", "Spectral bandwidth 55G routing"),
+    ("card.needs", "Needs {requirement}"),
+    ("card.needs.awesome_ops", "{ops} total awesome ops"),
+    ("card.needs.base_ops", "{ops} total base ops"),
+    ("card.needs.dropped", "{count} requests dropped"),
+    ("card.needs.earned", "{money} earned"),
+    ("card.needs.epic_ops", "{ops} total epic ops"),
+    ("card.needs.funds", "{money} in funds"),
+    ("card.needs.nodes", "{count} cloud nodes"),
+    ("card.needs.super_ops", "{ops} total super ops"),
+    ("card.o0.description", "Researchers use your service for free and share their findings"),
+    ("card.o0.title", "Open source partnership"),
+    ("card.p0.description", "The neighbors did not enjoy a full rack of servers humming all night"),
+    ("card.p0.title", "Noise complaint fine"),
+    ("card.p1.description", "A burst pipe flooded the new data center, the repairs are on you"),
+    ("card.p1.title", "Datacenter flood repair"),
+    ("card.pin", "Pin to the top while saving for it"),
+    ("card.s1.description", "Improve service performance a small bit"),
+    ("card.s1.title", "Clean up trace logs"),
+    ("card.s2.description", "Improve service performance"),
+    ("card.s2.title", "Profile-guided optimization"),
+    ("card.s3.description", "Improve service performance"),
+    ("card.s3.title", "Peer reviewed algorithmic revision"),
+    ("card.s4.description", "Improve service performance"),
+    ("card.s4.title", "Rewrite in Rust"),
+    ("card.st0.description", "Hire staff to perform ops for you, for a salary"),
+    ("card.st0.title", "First employee"),
+    ("card.test-0.description", "A test card to give you a welcoming bonus"),
+    ("card.test-0.title", "New card"),
+    ("card.test-1.description", "Test improving your services"),
+    ("card.test-1.title", "Powerup"),
+    ("card.test-2.description", "Test adding advertisements"),
+    ("card.test-2.title", "YouTube ads"),
+    ("card.test-3.description", "This one is too expensive"),
+    ("card.test-3.title", "Unreachable"),
+    ("card.test-4.description", "This one should not appear"),
+    ("card.test-4.title", "Wat"),
+    ("card.trial", "{duration} trial"),
+    ("card.unpin", "Unpin"),
+    ("card.wb.description", "The servers kept humming while you were away, collect the spoils"),
+    ("card.wb.title", "Welcome back"),
+    ("card.win0.description", "How's your Cloud going?"),
+    ("card.win0.title", "So..."),
+    ("card.win1.description", "That's a huge deal!"),
+    ("card.win1.title", "You did 1 billion awesome ops"),
+    ("card.win2.description", "And there is not much else to offer here"),
+    ("card.win2.title", "But you must be tired"),
+    ("card.win3.description", "You are far from the 9 quintillion ops needed to break the game"),
+    ("card.win3.title", "There is virtually no op limit"),
+    ("card.win4.description", "You are 0.00000001% into 9 quintillion ops, in case you're curious"),
+    ("card.win4.title", "Really far"),
+    ("card.win5.description", "So you can go and live to your potential!"),
+    ("card.win5.title", "So I offer you a winning condition"),
+    ("card.win6.description", "It's a symbolic cost, really (and it's an awesome op because you're awesome)"),
+    ("card.win6.title", "Win the game"),
+    ("card.win7.description", "You are a true 10\u{00d7} Cloud Champion! 💪"),
+    ("card.win7.title", "Congratulations!"),
+    ("card.win8.description", "Written by E_net4 for GitHub GameOff 2023"),
+    ("card.win8.title", "Thank you for playing 🙏"),
+    ("card.win9.description", "Bye bye now 👋"),
+    ("card.win9.title", "It's over"),
+    ("company.cancel", "Cancel"),
+    ("company.placeholder", "Company name"),
+    ("company.rename", "Rename"),
+    ("confirm_card.cancel", "Cancel"),
+    ("confirm_card.confirm", "Use it"),
+    ("confirm_card.credit", "{amount} refunded for the nodes left over"),
+    ("confirm_card.destructive", "This project changes your hardware for good:"),
+    ("confirm_card.ops_left", "{service} ops: {available} \u{2192} {remaining}"),
+    ("confirm_card.racks", "{nodes} nodes \u{2192} {racks} racks"),
+    ("confirm_card.rerouted", "{requests} requests in flight will be rerouted"),
+    ("confirm_card.title", "Use \u{201c}{card}\u{201d}?"),
+    ("confirm_card.unaffordable", "\u{201c}{card}\u{201d} can no longer be afforded"),
+    ("confirm_card.updated", "The cost changed since you opened this."),
+    ("confirm_card.warning", "This project spends most of your available ops:"),
+    ("corrupt_save.message", "Your saved game could not be read. It was set aside, so you can start over or go back to a backup."),
+    ("corrupt_save.new_game", "Start new game"),
+    ("corrupt_save.restore", "Try restore backup"),
+    ("corrupt_save.title", "Corrupted save"),
+    ("daily.over_message", "Your time for the daily challenge of {date} is up. A new challenge comes tomorrow."),
+    ("daily.over_title", "Daily challenge over"),
+    ("daily.remaining", "Daily {date}: {time} left"),
+    ("error.below_price_floor", "The daily challenge does not allow prices below {floor}"),
+    ("error.cannot_afford", "Cannot afford {cost}"),
+    ("error.in_debt", "Nothing can be bought while the company is in debt"),
+    ("error.insufficient_funds", "Not enough funds: {needed} needed, {have} available"),
+    ("error.insufficient_ops", "Not enough {service} ops: {needed} needed, {have} available"),
+    ("error.max_level", "Already at the highest level"),
+    ("error.no_such_card", "There is no project {id}"),
+    ("error.no_such_node", "There is no node #{node}"),
+    ("error.node_restarting", "Node #{node} is already restarting"),
+    ("error.not_available", "Not available right now"),
+    ("error.not_unlocked", "{service} is not unlocked yet"),
+    ("error.not_upgradable", "Node #{node} is already fully equipped"),
+    ("error.too_many_pinned", "No more than {max} projects can be pinned"),
+    ("error.upgrade_in_use", "Node #{node} is already using its upgrade, which can no longer be undone"),
+    ("event.blogger.bold", "Hand over a free account"),
+    ("event.blogger.safe", "Politely decline"),
+    ("event.blogger.text", "A tech blogger asks for a free account to review your cloud service."),
+    ("event.cat.bold", "Make it the company mascot"),
+    ("event.cat.safe", "Find it a new home"),
+    ("event.cat.text", "A stray cat has moved into the server room and sleeps on the warmest node."),
+    ("event.conference.bold", "Give the talk"),
+    ("event.conference.safe", "You are too busy"),
+    ("event.conference.text", "You are invited to give a keynote at a cloud conference, travel expenses not included."),
+    ("event.hackathon.bold", "Sponsor the hackathon"),
+    ("event.hackathon.safe", "Wish them luck"),
+    ("event.hackathon.text", "Local students are organizing a hackathon and look for a sponsor."),
+    ("event.intern.bold", "Hire them"),
+    ("event.intern.safe", "No vacancies right now"),
+    ("event.intern.text", "A student asks for an internship to research distributed systems on your nodes."),
+    ("event.investor.bold", "Take the money"),
+    ("event.investor.safe", "Stay independent"),
+    ("event.investor.text", "An investor offers you $1,000, on the condition that you guarantee the uptime of your base service."),
+    ("event.outage_rumor.bold", "Offer refunds to everyone"),
+    ("event.outage_rumor.safe", "Let it blow over"),
+    ("event.outage_rumor.text", "Rumors of a major outage are spreading on social media."),
+    ("event.title", "Meanwhile..."),
+    ("exchange.preview", "{obtained} {to} ops for {spent} {from} ops"),
+    ("exchange.title", "Op exchange"),
+    ("exchange.trade", "Trade"),
+    ("exchange.upwards_only", "Only ops of a higher tier can be obtained"),
+    ("hardware.all_datacenters", "All data centers"),
+    ("hardware.all_racks", "All racks"),
+    ("hardware.bandwidth_load", "Bandwidth: {load}%"),
+    ("hardware.buy_node", "Buy node"),
+    ("hardware.buy_rack", "Buy rack"),
+    ("hardware.cache", "Cache: {rate}% hits (up to {max}%)"),
+    ("hardware.cache_fraction", "Cache size: {percent}%"),
+    ("hardware.cache_fraction_hint", "A smaller cache reserves less memory, but fewer requests hit it"),
+    ("hardware.cache_hint", "Requests are processed {speedup}\u{d7} faster on average thanks to the cache"),
+    ("hardware.cpu_load", "CPU: {load}%"),
+    ("hardware.datacenter", "Data center"),
+    ("hardware.energy_used", "{energy} Wev consumed"),
+    ("hardware.memory_cache", "cache reserve"),
+    ("hardware.memory_load", "Memory: {used}/{total}"),
+    ("hardware.memory_requests", "requests"),
+    ("hardware.nodes_one_rack", "{nodes} nodes, 1 rack"),
+    ("hardware.nodes_racks", "{nodes} nodes, {racks} racks"),
+    ("hardware.num_nodes", "{count} nodes"),
+    ("hardware.num_racks", "{count} racks"),
+    ("hardware.overclocked", "\u{26a1} overclocked"),
+    ("hardware.overclocked_hint", "Processes requests faster, but uses more energy per request"),
+    ("hardware.preview_eta", "Affordable in ~{time}"),
+    ("hardware.preview_rate", "~{rate} more {service} requests/s"),
+    ("hardware.preview_specs", "+{cores} cores, +{ram} RAM"),
+    ("hardware.rack", "Rack"),
+    ("hardware.requests_processed", "{count} requests processed"),
+    ("hardware.restart", "Restart"),
+    ("hardware.restart_hint", "Free up memory, at the cost of a short downtime"),
+    ("hardware.restarting", "Restarting ({seconds}s)"),
+    ("hardware.routing_queue", "Routing queue: {load}%"),
+    ("hardware.routing_queue_hint", "Requests waiting for a free node to route them, dropped once the queue is full"),
+    ("hardware.serving_since", "Serving since {duration}, {ops} ops"),
+    ("hardware.specs", "{cores} cores, {ram} RAM"),
+    ("hardware.specs_one", "1 core, {ram} RAM"),
+    ("hardware.undo_cpu", "Undo CPU upgrade of node #{node} ({cost} back)"),
+    ("hardware.undo_ram", "Undo RAM upgrade of node #{node} ({cost} back)"),
+    ("hardware.upgrade_cpu", "Upgrade CPU"),
+    ("hardware.upgrade_ram", "Upgrade RAM"),
+    ("histogram.before_upgrade", ", {share}% before upgrade"),
+    ("histogram.cached", " ({share}% cached)"),
+    ("histogram.title", "Processing times"),
+    ("interaction.deferred", "This will be done once you decide"),
+    ("layout.auto", "Automatic"),
+    ("layout.grid", "Side by side"),
+    ("layout.tabs", "Tabs"),
+    ("log.auto_pay_failed", "Auto-pay failed: not enough funds for the {bill} electricity bill"),
+    ("log.churn", "{count} {service} customers left over the price raise"),
+    ("log.daily_dos_wave", "The daily challenge brings in attackers"),
+    ("log.daily_over", "The daily challenge is over"),
+    ("log.event_auto_resolved", "No answer given in time: {choice}"),
+    ("log.growth_resumed", "Demand is growing again"),
+    ("log.growth_stalled", "Growth stalled due to capacity: customers are turned away"),
+    ("log.hardware_load_bars", "You can now keep track of CPU and memory load"),
+    ("log.hardware_purchases", "You can now purchase more equipment"),
+    ("log.hardware_upgrades", "Your nodes can now be upgraded"),
+    ("log.insurance_payout", "Insurance paid out {payout} for {count} requests dropped"),
+    ("log.queue_cleared", "Your load balancer has caught up, requests are accepted again"),
+    ("log.queue_saturated", "Your load balancer is overwhelmed \u{2014} requests are being rejected"),
+    ("log.rack_credit", "{amount} refunded for the nodes which did not fill a rack"),
+    ("log.sla_penalty", "{service} SLA broken with {rate} of requests dropped: {penalty} penalty added to the bill"),
+    ("log.staff_hired", "Hired a {tier} to perform {service} ops"),
+    ("log.staff_quit", "A {tier} performing {service} ops quit over an unpaid salary"),
+    ("log.streak_reward", "Bills paid on time {streak} times in a row: {reward}"),
+    ("log.tariff_switched", "Switched energy provider to {provider}"),
+    ("log.trial_expired", "A {service} trial customer left after {ops} ops"),
+    ("menu.about", "About"),
+    ("menu.advanced", "Advanced"),
+    ("menu.backup_summary", "{company} ({difficulty}): {hours}h {minutes}m played, {earned} earned, worth {net_worth}"),
+    ("menu.backup_unreadable", "This backup cannot be read"),
+    ("menu.cancel", "Cancel"),
+    ("menu.compact_projects", "Show projects in two compact columns"),
+    ("menu.confirm_spend", "Ask before a project spends over {percent}% of the ops"),
+    ("menu.continue_game", "Continue Game"),
+    ("menu.daily_challenge", "Daily challenge ({date})"),
+    ("menu.github", "On GitHub"),
+    ("menu.language", "Language"),
+    ("menu.layout", "Panel layout"),
+    ("menu.new_game", "New Game"),
+    ("menu.no_backups", "There are no backups yet."),
+    ("menu.no_storage", "WARNING: Your browser is not allowing you to save your game. "),
+    ("menu.no_storage_hint", "Disable shields or enable local storage to save your progress."),
+    ("menu.restore", "Restore"),
+    ("menu.restore_backup", "Restore backup"),
+    ("menu.restore_title", "Restore this backup?"),
+    ("menu.restore_warning", "The current save will be replaced by the backup. This cannot be undone."),
+    ("overview.load", "CPU {cpu}%, memory {memory}%"),
+    ("panel.business", "Business"),
+    ("panel.hardware", "Hardware"),
+    ("panel.projects", "Projects"),
+    ("panel.services", "Services"),
+    ("panel.statistics", "Statistics"),
+    ("recommend.buy_node", "Buy a node"),
+    ("recommend.drops_bandwidth", "{share}% of drops are for lack of bandwidth"),
+    ("recommend.drops_cpu", "{share}% of drops are for lack of CPU"),
+    ("recommend.drops_queue", "{share}% of drops are from a full routing queue"),
+    ("recommend.drops_ram", "{share}% of drops are memory-related"),
+    ("recommend.faster_cores", "{factor}x faster cores"),
+    ("recommend.more_bandwidth", "{factor}x the bandwidth"),
+    ("recommend.more_cores", "+{cores} cores"),
+    ("recommend.more_memory", "+{memory} of memory"),
+    ("recommend.one_more_node", "one more node for about {rate} more {service} requests/s"),
+    ("recommend.reason", "{reason}, {benefit}"),
+    ("recommend.upgrade_cpu", "Upgrade CPU on node {node}"),
+    ("recommend.upgrade_ram", "Upgrade RAM on node {node}"),
+    ("recommend.use_card", "Use {card}"),
+    ("report.dropped", "Dropped: "),
+    ("report.energy_cost", "Energy cost: "),
+    ("report.ops", "{service} ops: {ops}"),
+    ("report.projects", "Projects: "),
+    ("report.revenue", "Revenue: "),
+    ("report.title", "Period report"),
+    ("report.visibility", "Visibility: "),
+    ("score.earned", "Money earned"),
+    ("score.net_worth", "Net worth"),
+    ("score.ops", "{service} ops"),
+    ("score.time", "Time played"),
+    ("score.total", "Score (v{version}): {score}"),
+    ("services.at_capacity", "Your servers are at capacity. Upgrade your hardware to process more ops."),
+    ("services.combo_hint", "Keep clicking to generate more ops per click"),
+    ("services.demand", "Demand: {current} req/s"),
+    ("services.demand_preview", "Demand: {current} \u{2192} {new} req/s"),
+    ("services.end_maintenance", "end maintenance"),
+    ("services.lower", "lower"),
+    ("services.maintenance", "maintenance"),
+    ("services.maintenance_hint", "Pause customer traffic, at a small cost in demand for every minute"),
+    ("services.op", "Op"),
+    ("services.price", "Price: "),
+    ("services.queue_pressure", "Requests wait too long in the queues, so customers are leaving"),
+    ("services.raise", "raise"),
+    ("services.testing", "TESTING"),
+    ("services.undo_price", "undo (back to {price})"),
+    ("staff.fire", "Fire"),
+    ("staff.hire", "Hire {tier} ({cost})"),
+    ("staff.hire_hint", "{ops} ops/s for {salary} per bill"),
+    ("staff.intern", "Intern"),
+    ("staff.junior", "Junior engineer"),
+    ("staff.member", "{tier} on {service}"),
+    ("staff.payroll", "(payroll: {amount})"),
+    ("staff.principal", "Principal engineer"),
+    ("staff.senior", "Senior engineer"),
+    ("staff.severance", "Severance: {amount}"),
+    ("staff.title", "Staff"),
+    ("stats.attacks_mitigated", "Attacks mitigated: {count}"),
+    ("stats.clicks", "Op clicks: {total} ({per_minute} in the last minute)"),
+    ("stats.copied", "Copied!"),
+    ("stats.copy_failed", "Could not copy"),
+    ("stats.dropped", "Dropped"),
+    ("stats.exchanged", "{service} ops obtained at the exchange: {ops}"),
+    ("stats.fulfilled", "Fulfilled"),
+    ("stats.hardware_value", "Hardware value: {value}"),
+    ("stats.net_worth", "Net worth: {value}"),
+    ("stats.requests", "Requests"),
+    ("stats.share", "Share summary"),
+    ("storage.failing", "\u{26a0} Could not save the game"),
+    ("storage.unavailable", "\u{26a0} Storage is unavailable, progress will not be saved"),
+    ("streak.demand_boost", "customers talk you up, +{percent}% demand for a while"),
+    ("streak.loyalty_discount", "a loyalty discount of {percent}% on electricity"),
+    ("streak.rebate", "a {percent}% rebate on the last bill"),
+    ("tariff.flat.description", "The same price for every Wattever."),
+    ("tariff.flat.provider", "Steady Power Co."),
+    ("tariff.peak.description", "50% pricier during the day, half price during the night."),
+    ("tariff.peak.provider", "SunCycle Energy"),
+    ("tariff.tiered.description", "25% pricier up to 50 Wev per bill period, half price beyond that."),
+    ("tariff.tiered.provider", "VolumeVolt"),
+    ("tariffs.close", "Close"),
+    ("tariffs.cooldown", "You switched providers recently. Wait a while before switching again."),
+    ("tariffs.current", "Current provider"),
+    ("tariffs.intro", "Each provider charges for energy in its own way. Projected bills are based on your consumption in the last bill period."),
+    ("tariffs.projected_bill", "Projected bill: {amount}"),
+    ("tariffs.switch", "Switch ({fee})"),
+    ("tariffs.title", "Energy providers"),
+    ("tip.memory_reserve", "Every service sets aside some memory on each node before serving any request, and the cache reserves more on top. What is left holds the requests being processed; when it runs out, requests are dropped."),
+    ("tip.powersave", "Leave too much of the electricity bill unpaid for too long and your nodes fall back to powersave mode, processing requests much more slowly until the bill is paid."),
+    ("tip.pricing", "Customers are sensitive to price: a lower price attracts more requests but earns less from each, a higher one earns more per request but drives demand down. Steep raises can make customers leave altogether."),
+    ("tip.request_rates", "The share of customer requests dropped for lack of memory or routing capacity, and of requests which failed while being processed. Both upset customers and can break your SLAs."),
+    ("tip.visibility", "How well known your cloud is. The higher the visibility, the more customer requests flow in, at every service tier. Advertising raises it."),
+    ("toast.extend_trial", "Extend trial {seconds}s for {fee}"),
+    ("toast.trial_expired", "The trial of a {service} customer ran out after {ops} ops."),
+    ("total_stats.ops", "Total {service} ops:"),
+    ("upgrade.cache_hit_rate", "Cache hit rate {before}% \u{2192} {after}%"),
+    ("upgrade.memory_reserve", "memory reserve {before} \u{2192} {after}"),
+    ("upgrade.op_memory", "{service} op memory {before} \u{2192} {after}"),
+    ("upgrade.op_time", "{service} op time {before} \u{2192} {after}"),
+    ("win.continue", "Keep playing"),
+    ("win.message", "You are the 10x Cloud Champion! Here is how your run scored."),
+    ("win.title", "Victory"),
+];
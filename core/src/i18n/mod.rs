@@ -0,0 +1,296 @@
+//! Module for translating the text shown to the player.
+//!
+//! Text is looked up by key with [`t!`](crate::t),
+//! in a static table per [`Locale`] sorted by key.
+//! Missing translations fall back to English,
+//! and missing keys to the key itself
+//! (which the tests in this module guard against).
+//!
+//! The locale in use is global to the page (see [`set_locale`]).
+//! It is chosen in the main menu and remembered by the front-end.
+//!
+//! Game concepts which need explaining to the player
+//! are listed as [`Topic`]s, each with its own `tip.*` text.
+
+use std::{cell::Cell, fmt};
+
+use serde::{Deserialize, Serialize};
+
+mod en;
+
+/// A language in which the game can be played.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 1] = [Locale::En];
+
+    /// The language code of the locale, as saved in local storage.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+        }
+    }
+
+    /// The locale with the given language code, if supported.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|locale| locale.code() == code)
+    }
+
+    /// The text table of the locale.
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => en::EN,
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // each locale is named in its own language
+        match self {
+            Locale::En => f.write_str("English"),
+        }
+    }
+}
+
+/// A game concept explained to the player in an info tooltip.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// how the service demand drives the inflow of customer requests
+    Visibility,
+    /// the share of requests dropped or failed
+    RequestRates,
+    /// what happens when the electricity bill is left unpaid
+    Powersave,
+    /// the memory set aside by each service and its cache
+    MemoryReserve,
+    /// how the price of a service trades revenue for demand
+    Pricing,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 5] = [
+        Topic::Visibility,
+        Topic::RequestRates,
+        Topic::Powersave,
+        Topic::MemoryReserve,
+        Topic::Pricing,
+    ];
+
+    /// The key of the text explaining the topic.
+    pub fn key(self) -> &'static str {
+        match self {
+            Topic::Visibility => "tip.visibility",
+            Topic::RequestRates => "tip.request_rates",
+            Topic::Powersave => "tip.powersave",
+            Topic::MemoryReserve => "tip.memory_reserve",
+            Topic::Pricing => "tip.pricing",
+        }
+    }
+
+    /// The text explaining the topic in the locale in use.
+    pub fn text(self) -> &'static str {
+        translate(self.key())
+    }
+}
+
+thread_local! {
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::En) };
+}
+
+/// The locale in use.
+pub fn locale() -> Locale {
+    LOCALE.with(Cell::get)
+}
+
+/// Change the locale in use.
+pub fn set_locale(locale: Locale) {
+    LOCALE.with(|cell| cell.set(locale));
+}
+
+/// Look up the text of a key in the given locale only.
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    let table = locale.table();
+    table
+        .binary_search_by(|(k, _)| (*k).cmp(key))
+        .ok()
+        .map(|index| table[index].1)
+}
+
+/// Translate a key to the locale in use,
+/// falling back to English and then to the key itself.
+pub fn translate(key: &'static str) -> &'static str {
+    lookup(locale(), key)
+        .or_else(|| lookup(Locale::En, key))
+        .unwrap_or(key)
+}
+
+/// Fill in the `{name}` placeholders of a translated text.
+pub fn fill(text: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut out = text.to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    out
+}
+
+/// Translate a key to the locale in use.
+///
+/// Named arguments fill in the placeholders of the text,
+/// in which case a `String` is produced instead of a `&'static str`:
+///
+/// ```
+/// # use cloud_champion_core::t;
+/// assert_eq!(t!("business.pay"), "Pay");
+/// assert_eq!(t!("hardware.restarting", seconds = 5), "Restarting (5s)");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::translate($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::fill(
+            $crate::i18n::translate($key),
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{fill, lookup, translate, Locale, Topic};
+    use crate::central::{cards::all::ALL_CARDS, narrative::ALL_EVENTS};
+
+    #[test]
+    fn test_tables_sorted() {
+        for locale in Locale::ALL {
+            let table = locale.table();
+            if let Some(pair) = table.windows(2).find(|pair| pair[0].0 >= pair[1].0) {
+                panic!(
+                    "{locale} table not in strict key order: {} >= {}",
+                    pair[0].0, pair[1].0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_codes() {
+        for locale in Locale::ALL {
+            assert_eq!(Locale::from_code(locale.code()), Some(locale));
+        }
+        assert_eq!(Locale::from_code("xx"), None);
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate("menu.new_game"), "New Game");
+        // missing keys show up as is
+        assert_eq!(translate("no.such.key"), "no.such.key");
+        assert_eq!(
+            fill("Restarting ({seconds}s)", &[("seconds", &12)]),
+            "Restarting (12s)"
+        );
+        assert_eq!(
+            crate::t!("business.powersave_in", time = "1:05"),
+            "Powersave in 1:05 unless bill paid"
+        );
+    }
+
+    #[test]
+    fn test_card_keys_exist() {
+        for card in ALL_CARDS {
+            assert!(
+                lookup(Locale::En, card.title_key).is_some(),
+                "missing {}",
+                card.title_key
+            );
+            assert!(
+                lookup(Locale::En, card.description_key).is_some(),
+                "missing {}",
+                card.description_key
+            );
+        }
+    }
+
+    #[test]
+    fn test_event_keys_exist() {
+        for event in ALL_EVENTS {
+            let choices = event.choices.iter().map(|choice| choice.label_key);
+            for key in std::iter::once(event.text_key).chain(choices) {
+                assert!(lookup(Locale::En, key).is_some(), "missing {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_topic_keys_exist() {
+        for topic in Topic::ALL {
+            assert!(topic.key().starts_with("tip."));
+            assert!(
+                lookup(Locale::En, topic.key()).is_some(),
+                "missing {}",
+                topic.key()
+            );
+            assert_ne!(topic.text(), topic.key());
+        }
+    }
+
+    /// Every key passed to `t!` in the sources,
+    /// including those of the front-end next to this crate,
+    /// must be in the English table.
+    #[test]
+    fn test_source_keys_exist() {
+        fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    rust_files(&path, files);
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    files.push(path);
+                }
+            }
+        }
+        let mut files = vec![];
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        rust_files(&manifest_dir.join("src"), &mut files);
+        let front_end = manifest_dir.join("../src");
+        if front_end.is_dir() {
+            rust_files(&front_end, &mut files);
+        }
+
+        // split up so that this test does not find itself
+        let needle = concat!("t", "!(");
+        let mut count = 0;
+        for file in files {
+            let source = std::fs::read_to_string(&file).unwrap();
+            for (i, _) in source.match_indices(needle) {
+                // skip other macros such as `format!`
+                let before = source[..i].chars().next_back();
+                if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    continue;
+                }
+                // the key may be on the next line once formatted
+                let Some(rest) = source[i + needle.len()..].trim_start().strip_prefix('"') else {
+                    continue;
+                };
+                let key = &rest[..rest.find('"').unwrap()];
+                assert!(
+                    lookup(Locale::En, key).is_some(),
+                    "missing key {key} used in {}",
+                    file.display()
+                );
+                count += 1;
+            }
+        }
+        assert!(count > 50, "only {count} keys found");
+    }
+}
@@ -0,0 +1,215 @@
+//! 10x Cloud Champion game engine
+//!
+//! The game state, the engine simulating it and everything they need,
+//! free of any web dependency so that it can run natively
+//! (e.g. in tests or tools) as well as in the browser.
+//! Messages are logged through the [`log`] facade,
+//! and games are saved through [`Persistence`](central::persistence::Persistence)
+//! on top of whatever storage the front-end provides.
+
+pub mod central;
+pub mod display;
+pub mod i18n;
+
+use rand::SeedableRng;
+use rand_distr::Distribution;
+use rand_pcg::Pcg32;
+
+#[cfg(feature = "debug-tools")]
+pub use crate::central::action::DebugAction;
+pub use crate::central::action::{ActionError, PlayerAction};
+pub use crate::central::audio_event::AudioEvent;
+pub use crate::central::cloud_user::{CloudClientSpec, CloudUserSpec, UserKind};
+pub use crate::central::config::{Difficulty, GameConfig};
+pub use crate::central::queue::Time;
+pub use crate::central::state::WorldState;
+pub use crate::central::stuff::{Cost, Memory, Money, Ops, ServiceKind};
+
+/// the version of the game
+pub const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// the global period of the game watch interval
+pub const MILLISECONDS_PER_CYCLE: u32 = 50;
+
+/// how many time units are in a single millisecond
+pub const TIME_UNITS_PER_MILLISECOND: u32 = 10;
+
+/// how many time units are in a second
+pub const TIME_UNITS_PER_SECOND: Time = TIME_UNITS_PER_MILLISECOND as Time * 1_000;
+
+/// how many time units are in a minute
+pub const TIME_UNITS_PER_MINUTE: Time = TIME_UNITS_PER_SECOND * 60;
+
+/// how many time units are in a single game update cycle
+pub const TIME_UNITS_PER_CYCLE: u32 = TIME_UNITS_PER_MILLISECOND * MILLISECONDS_PER_CYCLE;
+
+/// the longest time between two requests of a cloud user
+/// (see [`SampleGenerator::next_request`])
+pub const MAX_REQUEST_INTERVAL: Time = TIME_UNITS_PER_MILLISECOND as Time * 20_000;
+
+/// The number of requests per second expected from a cloud user
+/// with the given demand (see [`SampleGenerator::next_request`]).
+///
+/// This is a bit more than the demand itself when it is low,
+/// since no user waits longer than [`MAX_REQUEST_INTERVAL`]
+/// between requests.
+pub fn expected_requests_per_second(demand: f64) -> f64 {
+    let max_interval = MAX_REQUEST_INTERVAL as f64 / TIME_UNITS_PER_SECOND as f64;
+    let demand = demand.max(MIN_DEMAND);
+    if demand.is_infinite() {
+        return f64::INFINITY;
+    }
+    // the mean of an exponential distribution capped at the max interval
+    demand / -(-demand * max_interval).exp_m1()
+}
+
+/// The lowest demand considered when sampling request arrivals.
+///
+/// Anything below it, including zero, negative and NaN demand,
+/// is sampled as if it were this demand,
+/// which in practice always waits the full [`MAX_REQUEST_INTERVAL`].
+pub const MIN_DEMAND: f64 = 1e-9;
+
+/// Game construct that produces timed events on demand.
+#[derive(Debug)]
+pub struct SampleGenerator {
+    /// the random number generator
+    rng: Pcg32,
+}
+
+impl SampleGenerator {
+    pub fn new() -> Self {
+        SampleGenerator {
+            rng: Pcg32::from_entropy(),
+        }
+    }
+
+    /// Create a generator which always produces the same samples
+    /// for the same seed.
+    pub fn from_seed(seed: u64) -> Self {
+        SampleGenerator {
+            rng: Pcg32::seed_from_u64(seed),
+        }
+    }
+
+    /// Sample when the next request to cloud service is going to be made
+    /// based on the given demand for that service.
+    ///
+    /// Demand is approximately the number of requests per second.
+    /// This never fails:
+    /// demand below [`MIN_DEMAND`] (or NaN) is raised to it,
+    /// infinite demand arrives immediately,
+    /// and the duration saturates at [`MAX_REQUEST_INTERVAL`].
+    pub fn next_request(&mut self, demand: f64) -> Time {
+        // f64::max also takes the other value over NaN
+        let demand = demand.max(MIN_DEMAND);
+        let Ok(distribution) = rand_distr::Exp::new(demand) else {
+            return MAX_REQUEST_INTERVAL;
+        };
+        let duration =
+            distribution.sample(&mut self.rng) * 1_000. * TIME_UNITS_PER_MILLISECOND as f64;
+        // float to integer casts saturate, so this also holds for huge durations
+        (duration as Time).min(MAX_REQUEST_INTERVAL)
+    }
+
+    /// Pick a number in the `(low..high)` range (excluding `high`).
+    pub fn gen_range(&mut self, low: u32, high: u32) -> u32 {
+        rand_distr::Uniform::new(low, high).sample(&mut self.rng)
+    }
+
+    /// Pick `true` with the given probability.
+    pub fn gen_bool(&mut self, chance: f32) -> bool {
+        rand_distr::Uniform::new_inclusive(0., 1.).sample(&mut self.rng) < chance
+    }
+}
+
+impl Default for SampleGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        expected_requests_per_second, SampleGenerator, Time, MAX_REQUEST_INTERVAL, MIN_DEMAND,
+        TIME_UNITS_PER_SECOND,
+    };
+
+    #[test]
+    fn test_expected_requests_per_second() {
+        // high demand is hardly affected by the max interval
+        assert!((expected_requests_per_second(10.) - 10.).abs() < 1e-9);
+        assert!((expected_requests_per_second(1.) - 1.).abs() < 1e-6);
+        // low demand is
+        let slowest = TIME_UNITS_PER_SECOND as f64 / MAX_REQUEST_INTERVAL as f64;
+        assert!((expected_requests_per_second(0.) / slowest - 1.).abs() < 1e-6);
+        assert_eq!(
+            expected_requests_per_second(-1.),
+            expected_requests_per_second(0.)
+        );
+        assert_eq!(
+            expected_requests_per_second(f64::NAN),
+            expected_requests_per_second(0.)
+        );
+        assert!(expected_requests_per_second(1e-6) > slowest);
+        assert!(expected_requests_per_second(0.05) > 0.05 * 1.1);
+        // and more demand always brings more requests
+        let mut previous = 0.;
+        for demand in [0., 1e-3, 0.01, 0.05, 0.1, 0.5, 1., 5., 100.] {
+            let rate = expected_requests_per_second(demand);
+            assert!(rate > previous, "{demand}: {rate}");
+            previous = rate;
+        }
+    }
+
+    /// The expected requests per second
+    /// match the arrivals sampled for a fixed seed.
+    #[test]
+    fn test_expected_requests_match_samples() {
+        let mut gen = SampleGenerator::from_seed(42);
+        for demand in [0.02, 0.1, 0.5, 2., 40.] {
+            let samples = 20_000;
+            let total: u64 = (0..samples).map(|_| gen.next_request(demand)).sum();
+            let sampled = samples as f64 * TIME_UNITS_PER_SECOND as f64 / total as f64;
+            let expected = expected_requests_per_second(demand);
+            assert!(
+                (sampled / expected - 1.).abs() < 0.02,
+                "{demand}: sampled {sampled}, expected {expected}"
+            );
+        }
+    }
+
+    /// Any demand, however absurd, produces an arrival within the cap,
+    /// and more demand means shorter waits on average.
+    #[test]
+    fn test_next_request_any_demand() {
+        let mut gen = SampleGenerator::from_seed(928);
+        for demand in [f64::NAN, f64::NEG_INFINITY, -1., 0., MIN_DEMAND / 2.] {
+            for _ in 0..100 {
+                assert_eq!(gen.next_request(demand), MAX_REQUEST_INTERVAL, "{demand}");
+            }
+        }
+        assert_eq!(gen.next_request(f64::INFINITY), 0);
+
+        let samples = 2_000;
+        let mut previous_mean = f64::INFINITY;
+        let mut demand = 1e-3;
+        while demand <= 1e9 {
+            let durations: Vec<Time> = (0..samples).map(|_| gen.next_request(demand)).collect();
+            assert!(
+                durations.iter().all(|&d| d <= MAX_REQUEST_INTERVAL),
+                "{demand}: {durations:?}"
+            );
+            let mean = durations.iter().sum::<Time>() as f64 / samples as f64;
+            // allow for sampling noise and for durations rounding down to 0
+            assert!(
+                mean <= previous_mean * 1.05,
+                "{demand}: mean {mean} after {previous_mean}"
+            );
+            previous_mean = mean;
+            demand *= 4.;
+        }
+        assert_eq!(previous_mean, 0.);
+    }
+}
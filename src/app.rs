@@ -1,24 +1,70 @@
-use cloud_champion::central::cards::all::ALL_CARDS;
-use cloud_champion::central::engine::{CloudNode, GameEngine};
+use cloud_champion::audio::AudioBus;
+use cloud_champion::central::away;
+use cloud_champion::central::cards::{all::card_by_id, CardSpec};
+use cloud_champion::central::daily::{DailyChallenge, DailyDate};
+use cloud_champion::central::digest::{card_digest, ui_digest};
+use cloud_champion::central::engine::{
+    cache_hit_rate, trial_extension_fee, CloudNode, ExpiredTrial, GameEngine, HardwareResource,
+    TRIAL_EXTENSION,
+};
+use cloud_champion::central::interaction::{conflicts_with_decision, CardQuote, Revalidation};
+use cloud_champion::central::persistence::{
+    backups, restore_backup, LoadError, SaveSummary, StorageMonitor,
+};
+use cloud_champion::central::preview::{NodeSpec, PurchasePreview};
+use cloud_champion::central::recommend::recommend_purchase;
+use cloud_champion::central::score::ScoreBreakdown;
+use cloud_champion::central::simulation::{SimulationInput, SimulationOutput};
+use cloud_champion::central::state::{sanitize_company_name, DEFAULT_COMPANY_NAME};
+use cloud_champion::central::state::{
+    RoutingLevel, HARDWARE_PANEL_LOAD_BARS, HARDWARE_PANEL_PURCHASES,
+};
+use cloud_champion::central::streak::StreakReward;
 use cloud_champion::components::business::{Business, BusinessProps};
+use cloud_champion::components::company::CompanyName;
 use cloud_champion::components::hardware::{Equipment, NodeProps, Power};
-use cloud_champion::components::menu::Menu;
-use cloud_champion::components::services::CloudService;
+use cloud_champion::components::histogram::ProcessingChart;
+use cloud_champion::components::menu::{BackupEntry, Menu};
+use cloud_champion::components::modal::Modal;
+use cloud_champion::components::notifications::Notifications;
+use cloud_champion::components::report::ReportCard;
+use cloud_champion::components::score::ScoreBoard;
+use cloud_champion::components::services::{CloudService, CloudServiceProps, OpFeedback};
+use cloud_champion::components::stats::{ShareSummary, Stats};
+use cloud_champion::components::tabs::TabbedPanels;
+use cloud_champion::components::toast::Toast;
 use cloud_champion::components::total_stats::{TotalStats, TotalStatsProps};
+use cloud_champion::i18n::{self, Locale};
+use cloud_champion::settings::Settings;
+use cloud_champion::storage::{back_up_local_save, store_save_json, try_local_storage, LocalSave};
+use cloud_champion::web::{self, Today};
 use cloud_champion::{
-    GameMsg, GameWatch, Memory, Money, Ops, PlayerAction, ServiceKind, WorldState,
-    TIME_UNITS_PER_CYCLE,
+    t, Difficulty, GameConfig, GameMsg, GameWatch, Memory, Money, Ops, PauseState, PlayerAction,
+    ServiceKind, WorldState, TIME_UNITS_PER_MILLISECOND, TIME_UNITS_PER_SECOND,
 };
-use js_sys::wasm_bindgen::UnwrapThrowExt;
-use yew::prelude::*;
+use gloo_events::EventListener;
+use js_sys::wasm_bindgen::JsValue;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, virtual_dom::VChild};
 
 use cloud_champion::components::card::*;
+#[cfg(feature = "debug-tools")]
+use cloud_champion::components::debug::{DebugOverlay, DebugSidebar};
 use cloud_champion::components::panel::Panel;
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum Msg {
-    NewGame,
+    /// start a new game with the given difficulty and company name
+    NewGame(Difficulty, String),
     ContinueGame,
+    /// play today's daily challenge,
+    /// starting it with the given company name if not yet started
+    DailyChallenge(String),
+    /// replace the save with the backup in the given slot
+    /// and continue the game from there
+    RestoreBackup(usize),
+    SetLocale(Locale),
+    SetSettings(Settings),
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -28,11 +74,21 @@ pub(crate) enum AppState {
     MainMenu,
     /// A game is ongoing
     Game(GameStateOrigin),
+    /// The saved game could not be read for the given reason,
+    /// so the player is asked how to carry on
+    CorruptSave(String),
 }
 
 #[derive(Debug)]
 pub(crate) struct App {
     state: AppState,
+    audio: AudioBus,
+    /// whether the game runs in sandbox mode
+    sandbox: bool,
+    /// the language of the game
+    locale: Locale,
+    /// the preferences of the player
+    settings: Settings,
 }
 
 impl Component for App {
@@ -40,19 +96,58 @@ impl Component for App {
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
+        let locale = i18n::load_locale();
+        i18n::set_locale(locale);
         Self {
             state: AppState::default(),
+            audio: AudioBus::new(),
+            sandbox: sandbox_requested(),
+            locale,
+            settings: Settings::load(),
         }
     }
 
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::NewGame => {
-                self.state = AppState::Game(GameStateOrigin::New);
+            Msg::NewGame(difficulty, company_name) => {
+                // the new game will overwrite the saved one
+                if WorldState::has_saved_game() == Ok(true) {
+                    back_up_local_save();
+                }
+                self.state = AppState::Game(GameStateOrigin::New(difficulty, company_name));
                 true
             }
             Msg::ContinueGame => {
-                self.state = AppState::Game(GameStateOrigin::Continue);
+                self.continue_game();
+                true
+            }
+            Msg::DailyChallenge(company_name) => {
+                let date = DailyDate::today();
+                self.state = AppState::Game(GameStateOrigin::Daily(date, company_name));
+                true
+            }
+            Msg::RestoreBackup(slot) => {
+                let now = js_sys::Date::now() as u64;
+                match try_local_storage().and_then(|storage| restore_backup(&storage, slot, now)) {
+                    Ok(true) => self.continue_game(),
+                    Ok(false) => gloo_console::warn!("No backup in slot", slot),
+                    Err(e) => gloo_console::error!("Could not restore the backup:", e),
+                }
+                true
+            }
+            Msg::SetLocale(locale) => {
+                self.locale = locale;
+                i18n::set_locale(locale);
+                if let Err(e) = i18n::save_locale(locale) {
+                    gloo_console::warn!("Could not save the locale:", e);
+                }
+                true
+            }
+            Msg::SetSettings(settings) => {
+                self.settings = settings;
+                if let Err(e) = settings.save() {
+                    gloo_console::warn!("Could not save the settings:", e);
+                }
                 true
             }
         }
@@ -67,37 +162,163 @@ impl Component for App {
                     Ok(false) => (true, false),
                     Err(_) => (false, false),
                 };
+                let backups = try_local_storage()
+                    .and_then(|storage| backups(&storage))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|backup| BackupEntry {
+                        slot: backup.slot,
+                        backed_up_at: backup.backed_up_at,
+                        summary: backup.load().as_ref().map(SaveSummary::of),
+                    })
+                    .collect::<Vec<_>>();
 
                 html! {
                     <Menu
-                        newgame_handler={link.callback(|_| Msg::NewGame)}
+                        newgame_handler={link.callback(|(difficulty, name)| Msg::NewGame(difficulty, name))}
                         continuegame_handler={link.callback(|_| Msg::ContinueGame)}
                         {has_save}
                         {can_save}
+                        locale={self.locale}
+                        on_locale={link.callback(Msg::SetLocale)}
+                        {backups}
+                        on_restore={link.callback(Msg::RestoreBackup)}
+                        settings={self.settings}
+                        on_settings={link.callback(Msg::SetSettings)}
+                        on_audio={self.audio.callback()}
+                        daily_date={DailyDate::today()}
+                        on_daily={link.callback(Msg::DailyChallenge)}
                         />
                 }
             }
             AppState::Game(origin) => {
                 html! {
-                    <Game origin={*origin} />
+                    <Game origin={origin.clone()} sandbox={self.sandbox} settings={self.settings} />
+                }
+            }
+            AppState::CorruptSave(reason) => {
+                let link = ctx.link();
+                let on_new_game = link.callback(|_| {
+                    Msg::NewGame(Difficulty::default(), DEFAULT_COMPANY_NAME.to_string())
+                });
+                // the newest backup which can still be read
+                let backup = try_local_storage()
+                    .and_then(|storage| backups(&storage))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|backup| backup.load().is_some());
+
+                html! {
+                    <Modal title={t!("corrupt_save.title")}>
+                        <p>{t!("corrupt_save.message")}</p>
+                        <p class="corrupt-reason">{reason}</p>
+                        <div class="modal-buttons">
+                            <button onclick={on_new_game}>{t!("corrupt_save.new_game")}</button>
+                            if let Some(backup) = backup {
+                                <button onclick={link.callback(move |_| Msg::RestoreBackup(backup.slot))}>
+                                    {t!("corrupt_save.restore")}
+                                </button>
+                            }
+                        </div>
+                    </Modal>
                 }
             }
         }
     }
 }
 
+impl App {
+    /// Load the saved game to continue it,
+    /// or ask the player how to carry on if it cannot be read.
+    fn continue_game(&mut self) {
+        self.state = match WorldState::load_game() {
+            Ok(state) => AppState::Game(GameStateOrigin::Continue(Box::new(
+                state.unwrap_or_default(),
+            ))),
+            Err(LoadError::Corrupt(reason)) => AppState::CorruptSave(reason),
+            Err(LoadError::Storage(e)) => {
+                gloo_console::error!("Could not load the saved game:", e);
+                AppState::MainMenu
+            }
+        };
+    }
+}
+
+/// The key which shows or hides the developer overlay
+#[cfg(feature = "debug-tools")]
+const DEBUG_OVERLAY_KEY: &str = "F9";
+
+/// Whether the sandbox mode was requested
+/// through the `sandbox` URL query flag.
+#[cfg(feature = "debug-tools")]
+fn sandbox_requested() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .is_some_and(|query| query.contains("sandbox"))
+}
+
+#[cfg(not(feature = "debug-tools"))]
+fn sandbox_requested() -> bool {
+    false
+}
+
+/// Save the game if it is time to try,
+/// backing up the previous save every now and then,
+/// and return whether saving started or stopped failing.
+fn autosave(
+    storage: &mut StorageMonitor,
+    time: u64,
+    save: impl FnOnce() -> Result<(), JsValue>,
+) -> bool {
+    if storage.backup_due() {
+        back_up_local_save();
+    }
+    let health = storage.health();
+    if let Some(Err(e)) = storage.autosave(time, save) {
+        gloo_console::error!("Failed to save game state: {:?}", e);
+    }
+    health != storage.health()
+}
+
 /// The top level application state
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) enum GameStateOrigin {
-    /// The player initiated a new game
-    New,
-    /// A game is being continued from a saved state
-    Continue,
+    /// The player initiated a new game at the given difficulty,
+    /// with the given company name
+    New(Difficulty, String),
+    /// A game is being continued from the given saved state
+    Continue(Box<WorldState>),
+    /// The daily challenge of the given date is played,
+    /// continued from its own save if started already,
+    /// or else started with the given company name
+    Daily(DailyDate, String),
 }
 
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub(crate) struct GameProps {
     origin: GameStateOrigin,
+    /// whether to show the sandbox mode controls
+    #[prop_or_default]
+    sandbox: bool,
+    /// the preferences of the player
+    #[prop_or_default]
+    settings: Settings,
+}
+
+/// What the player is in the middle of deciding,
+/// which holds other interactions back.
+#[derive(Debug, Default, Clone, PartialEq)]
+enum PendingInteraction {
+    /// nothing is pending
+    #[default]
+    Idle,
+    /// the player is asked to confirm using a project card
+    /// at the quoted cost,
+    /// which may have been updated since first shown
+    ConfirmCard { quote: CardQuote, updated: bool },
+    /// the player is asked to decide on a narrative event or a bailout,
+    /// and the conflicting messages wait until then
+    Decision { deferred: Vec<GameMsg> },
 }
 
 #[derive(Debug)]
@@ -105,6 +326,49 @@ pub(crate) struct Game {
     state: WorldState,
     engine: GameEngine,
     watch: GameWatch,
+    /// whether the game is paused
+    pause: PauseState,
+    /// digest of the view at the last render
+    /// (see [`ui_digest`])
+    digest: u64,
+    /// digest of the project cards at the last time they were built
+    cards_digest: u64,
+    /// the project cards panel contents, rebuilt only when they change
+    cards: Html,
+    /// the text typed in the filter of the projects panel
+    card_filter: String,
+    on_pay_bills: Callback<()>,
+    on_repay_loan: Callback<()>,
+    /// whether the game can be saved
+    storage: StorageMonitor,
+    /// the rack or data center shown in detail in the equipment panel
+    expanded_equipment: Option<usize>,
+    /// the latest outcome of the player's ops per service kind
+    op_feedback: [OpFeedback; ServiceKind::ALL.len()],
+    /// plays the sounds of the game
+    audio: AudioBus,
+    /// the latest action failure shown to the player,
+    /// and a number which changes with every failure
+    toast: (Option<AttrValue>, u32),
+    /// the label of the button offered with the toast
+    /// and the action it takes, if any
+    toast_action: Option<(AttrValue, PlayerAction)>,
+    /// what the player is in the middle of deciding
+    interaction: PendingInteraction,
+    /// the width of the browser viewport in CSS pixels
+    viewport_width: f64,
+    /// follows the viewport width as the window is resized
+    _resize_listener: Option<EventListener>,
+    /// whether the developer overlay is shown
+    #[cfg(feature = "debug-tools")]
+    debug_overlay: bool,
+    /// toggles the developer overlay on a key press
+    #[cfg(feature = "debug-tools")]
+    _debug_keys: Option<cloud_champion::web::KeyListener>,
+    /// the simulation running in a web worker,
+    /// or `None` if the engine runs on this thread
+    #[cfg(feature = "worker")]
+    simulation: Option<cloud_champion::worker::SimulationBridge>,
 }
 
 impl Component for Game {
@@ -112,15 +376,17 @@ impl Component for Game {
     type Properties = GameProps;
 
     fn create(ctx: &Context<Self>) -> Self {
-        let state = match ctx.props().origin {
-            GameStateOrigin::New => WorldState::default(),
-            GameStateOrigin::Continue => {
-                // load from local storage
-                let mut state = WorldState::load_game()
-                    .expect_throw("Failed to load game state from local storage")
-                    .unwrap_or_default();
-
-                if state.can_buy_datacenters && state.nodes[0].num_cores <= 64 {
+        let state = match &ctx.props().origin {
+            GameStateOrigin::New(difficulty, company_name) => WorldState {
+                difficulty: *difficulty,
+                company_name: sanitize_company_name(company_name),
+                ..Default::default()
+            },
+            GameStateOrigin::Continue(state) => {
+                let mut state = WorldState::clone(state);
+
+                if state.can_buy_datacenters && !state.nodes[0].is_rack() {
+                    back_up_local_save();
                     // transform old save game for rack nodes
                     let num_racks = state.nodes.len() as u32 / 4;
                     state.nodes.clear();
@@ -130,27 +396,102 @@ impl Component for Game {
                     }
                 }
 
+                // welcome back players who were away for a while
+                away::check_return(&mut state, js_sys::Date::now() as u64);
+
                 state
             }
+            GameStateOrigin::Daily(date, company_name) => {
+                let saved = WorldState::load_daily_game().unwrap_or_else(|e| {
+                    gloo_console::warn!("Could not load the daily challenge:", e.to_string());
+                    None
+                });
+                // yesterday's challenge is left behind
+                saved
+                    .filter(|state| state.daily.is_some_and(|daily| daily.date == *date))
+                    .unwrap_or_else(|| {
+                        WorldState::new_daily(
+                            DailyChallenge::for_date(*date),
+                            sanitize_company_name(company_name),
+                        )
+                    })
+            }
         };
 
+        let config = GameConfig::new(state.difficulty);
+        // the daily challenge plays out the same for everyone
+        let engine = match &state.daily {
+            Some(daily) => GameEngine::with_config_seeded(config, daily.seed),
+            None => GameEngine::with_config(config),
+        };
         let mut out = Self {
             state,
-            engine: GameEngine::new(),
+            engine,
             watch: GameWatch::new(),
+            pause: PauseState::default(),
+            digest: 0,
+            cards_digest: 0,
+            cards: Html::default(),
+            card_filter: String::new(),
+            on_pay_bills: ctx.link().callback(|_| PlayerAction::PayElectricityBill),
+            on_repay_loan: ctx.link().callback(|_| PlayerAction::RepayLoan),
+            storage: StorageMonitor::new(),
+            expanded_equipment: None,
+            op_feedback: Default::default(),
+            audio: AudioBus::new(),
+            toast: (None, 0),
+            toast_action: None,
+            interaction: PendingInteraction::Idle,
+            // assume a wide screen if the viewport cannot be measured
+            viewport_width: web::viewport_width().unwrap_or(f64::INFINITY),
+            _resize_listener: {
+                let link = ctx.link().clone();
+                web::listen_resize(move |width| link.send_message(GameMsg::Resize(width)))
+                    .inspect_err(|e| gloo_console::warn!("Could not listen to resizing:", e))
+                    .ok()
+            },
+            #[cfg(feature = "debug-tools")]
+            debug_overlay: false,
+            #[cfg(feature = "debug-tools")]
+            _debug_keys: {
+                let link = ctx.link().clone();
+                cloud_champion::web::KeyListener::new(move |key| {
+                    if key == DEBUG_OVERLAY_KEY {
+                        link.send_message(GameMsg::ToggleDebugOverlay);
+                    }
+                })
+                .inspect_err(|e| gloo_console::warn!("Could not listen to keys:", e))
+                .ok()
+            },
+            #[cfg(feature = "worker")]
+            simulation: web::supports_workers().then(|| {
+                let link = ctx.link().clone();
+                cloud_champion::worker::spawn(move |output| {
+                    link.send_message(GameMsg::Simulated(Box::new(output)))
+                })
+            }),
         };
 
         let link = ctx.link().clone();
         out.watch
-            .start_with(move || link.send_message(GameMsg::Tick));
+            .start_with(move |elapsed| link.send_message(GameMsg::Tick(elapsed)));
 
-        out.engine.bootstrap_events(&mut out.state);
+        let start = SimulationInput::Start {
+            state: Box::new(out.state.clone()),
+            seed: out.state.daily.map(|daily| daily.seed),
+        };
+        if !out.send_to_worker(start) {
+            out.engine.bootstrap_events(&out.state);
+        }
+        out.refresh(ctx);
 
         out
     }
 
     fn destroy(&mut self, _ctx: &Context<Self>) {
         // try to save before closing
+        // (whether paused or not),
+        // with the latest state seen from the worker if there is one
         if let Err(e) = self.state.save_game() {
             gloo_console::error!("Failed to save game state: {:?}", e);
         }
@@ -159,35 +500,165 @@ impl Component for Game {
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             GameMsg::Action(action) => {
-                self.engine.apply_action(&mut self.state, action);
+                if let PendingInteraction::Decision { deferred } = &mut self.interaction {
+                    if conflicts_with_decision(&action, &self.state) {
+                        deferred.push(GameMsg::Action(action));
+                        self.show_toast(t!("interaction.deferred"));
+                        return true;
+                    }
+                }
+                if let PlayerAction::UseCard { id } = &action {
+                    if let PendingInteraction::ConfirmCard { quote, .. } = &self.interaction {
+                        // the player confirmed what they saw,
+                        // which may be out of date by now
+                        if quote.id == id {
+                            match quote.revalidate(&self.engine, &self.state) {
+                                Revalidation::Confirmed => {}
+                                Revalidation::Changed(quote) => {
+                                    self.interaction = PendingInteraction::ConfirmCard {
+                                        quote,
+                                        updated: true,
+                                    };
+                                    return true;
+                                }
+                                Revalidation::Unaffordable => {
+                                    let card = card_by_id(quote.id).map_or("", |card| card.title());
+                                    self.interaction = PendingInteraction::Idle;
+                                    self.show_toast(t!("confirm_card.unaffordable", card = card));
+                                    return true;
+                                }
+                            }
+                        }
+                    }
+                    if matches!(self.interaction, PendingInteraction::ConfirmCard { .. }) {
+                        self.interaction = PendingInteraction::Idle;
+                    }
+                }
+                if action == PlayerAction::DeclareBankruptcy {
+                    // starting over wipes the saved game on the next save
+                    back_up_local_save();
+                }
+                if self.send_to_worker(SimulationInput::Action(action.clone())) {
+                    // the outcome comes back with the next messages from the worker
+                    return true;
+                }
+                let result = self
+                    .engine
+                    .apply_action(&mut self.state, action.clone())
+                    .map_err(|e| e.to_string());
+                self.action_done(&action, result);
+                self.audio.emit_all(self.engine.take_audio_events());
+                self.sync_interaction(ctx);
+                self.refresh(ctx);
                 true
             }
-            GameMsg::Tick => {
-                let time = self.state.time + TIME_UNITS_PER_CYCLE as u64;
+            GameMsg::Tick(elapsed) => {
+                if !self.pause.should_tick() {
+                    // a stray tick must not advance a paused game
+                    return false;
+                }
+                if self.send_to_worker(SimulationInput::Tick { elapsed }) {
+                    // the view follows once the worker sends a frame
+                    return false;
+                }
+                let time = self.state.time + (elapsed * TIME_UNITS_PER_MILLISECOND) as u64;
                 self.engine.update(&mut self.state, time);
-                true
+                self.after_update(ctx)
             }
+            GameMsg::Simulated(output) => match *output {
+                SimulationOutput::Frame(frame) => {
+                    frame.apply(&mut self.engine, &mut self.state);
+                    self.after_update(ctx)
+                }
+                SimulationOutput::ActionDone { action, result } => {
+                    self.action_done(&action, result);
+                    true
+                }
+                SimulationOutput::Save { json, daily, time } => {
+                    autosave(&mut self.storage, time, || store_save_json(&json, daily))
+                }
+            },
             GameMsg::Pause => {
+                if !self.pause.pause() {
+                    return false;
+                }
                 self.watch.stop();
                 true
             }
             GameMsg::Resume => {
+                if !self.pause.resume() {
+                    return false;
+                }
                 let link = ctx.link().clone();
                 self.watch
-                    .start_with(move || link.send_message(GameMsg::Tick));
+                    .start_with(move |elapsed| link.send_message(GameMsg::Tick(elapsed)));
+                true
+            }
+            GameMsg::ExpandEquipment(expanded) => {
+                self.expanded_equipment = expanded;
+                true
+            }
+            GameMsg::FilterCards(filter) => {
+                self.card_filter = filter;
+                self.cards = self.project_cards(ctx);
+                true
+            }
+            GameMsg::ConfirmCard(Some(id)) => {
+                if let PendingInteraction::Decision { deferred } = &mut self.interaction {
+                    let action = PlayerAction::UseCard { id: id.into() };
+                    if conflicts_with_decision(&action, &self.state) {
+                        deferred.push(GameMsg::ConfirmCard(Some(id)));
+                        self.show_toast(t!("interaction.deferred"));
+                        return true;
+                    }
+                }
+                let settings = &ctx.props().settings;
+                let quote = card_by_id(id)
+                    .filter(|card| settings.needs_card_confirmation(card, &self.state))
+                    .map(|card| CardQuote::new(&self.engine, &self.state, card));
+                match quote {
+                    Some(quote) if self.interaction == PendingInteraction::Idle => {
+                        self.interaction = PendingInteraction::ConfirmCard {
+                            quote,
+                            updated: false,
+                        };
+                        true
+                    }
+                    // one confirmation at a time
+                    Some(_) => false,
+                    None => {
+                        ctx.link()
+                            .send_message(PlayerAction::UseCard { id: id.into() });
+                        false
+                    }
+                }
+            }
+            GameMsg::ConfirmCard(None) => {
+                if !matches!(self.interaction, PendingInteraction::ConfirmCard { .. }) {
+                    return false;
+                }
+                self.interaction = PendingInteraction::Idle;
+                true
+            }
+            GameMsg::Resize(width) => {
+                let layout = ctx.props().settings.layout;
+                let was_compact = layout.is_compact(self.viewport_width);
+                self.viewport_width = width;
+                was_compact != layout.is_compact(width)
+            }
+            #[cfg(feature = "debug-tools")]
+            GameMsg::ToggleDebugOverlay => {
+                self.debug_overlay = !self.debug_overlay;
                 true
             }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        // gather service state
-        let super_service = &self.state.super_service;
-        let epic_service = &self.state.epic_service;
-        let awesome_service = &self.state.awesome_service;
+        let compact = ctx.props().settings.layout.is_compact(self.viewport_width);
 
         // business panel: stats & electricity bills
-        let electricity_bill = self.state.electricity.total_due.into_cent_precision();
+        let electricity_bill = self.state.electricity.bill_amount();
         let business_props = BusinessProps {
             funds: self.state.funds,
             energy_consumption_rate: if self.state.can_see_energy_consumption {
@@ -200,215 +671,138 @@ impl Component for Game {
             } else {
                 None
             },
-            base_ops_available: self.state.base_service.available,
-            super_ops_available: if super_service.unlocked {
-                Some(super_service.available)
-            } else {
-                None
-            },
-            epic_ops_available: if epic_service.unlocked {
-                Some(epic_service.available)
-            } else {
-                None
-            },
-            awesome_ops_available: if awesome_service.unlocked {
-                Some(awesome_service.available)
-            } else {
-                None
-            },
+            ops_available: ServiceKind::ALL.map(|kind| {
+                let service = self.state.service_by_kind(kind);
+                Some(service.available).filter(|_| service.state.is_unlocked())
+            }),
 
             electricity_bill,
-            can_pay_bill: electricity_bill <= self.state.funds,
-            on_pay_bills: {
-                let link = ctx.link().clone();
-                Callback::from(move |_| link.send_message(PlayerAction::PayElectricityBill))
-            },
+            bill_payment: self.state.bill_payment(),
+            can_exchange_ops: self.state.can_exchange_ops,
+            on_player_action: ctx.link().callback(GameMsg::Action),
+            slas: self.state.slas.clone(),
+            insurance: self.state.insurance.clone(),
+            top_customers: self
+                .state
+                .can_see_request_rates
+                .then(|| self.state.top_customers()),
+            time_until_powersave: self.state.time_until_powersave(),
+            on_pay_bills: self.on_pay_bills.clone(),
+            auto_pay: Some(self.state.auto_pay).filter(|_| self.state.can_auto_pay),
+            on_time_streak: (self.state.electricity.last_bill > Money::zero())
+                .then_some(self.state.electricity.on_time_streak),
+            next_streak_reward: StreakReward::next(self.state.electricity.streak_rewards),
+            electricity_credit: self.state.electricity.credit,
+            prepay_amount: self.state.electricity.last_bill.round_to_cents_half_up(),
+            tariff: Some(self.state.electricity.tariff).filter(|_| self.state.can_choose_tariff),
+            tariff_offers: self.engine.tariff_offers(&self.state),
+            can_switch_tariff: self.state.time >= self.state.electricity.tariff_switch_ready_at(),
+            staff: Some(self.state.staff.clone()).filter(|_| self.state.can_hire_staff),
             demand: Some(self.state.demand).filter(|_| self.state.can_see_demand),
+            day_phase: Some(self.engine.day_phase(self.state.time))
+                .filter(|_| self.state.can_see_demand),
+            growth_stalled: self.engine.growth_stalled(),
+            loan: self.state.loan.as_ref().map(|loan| loan.balance),
+            can_repay_loan: self
+                .state
+                .loan
+                .as_ref()
+                .is_some_and(|loan| loan.balance <= self.state.funds),
+            on_repay_loan: self.on_repay_loan.clone(),
+            on_audio: self.audio.callback(),
         };
 
         // service panel: cloud services
-        let base_c = {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Base,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = self.state.ops_per_click;
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Base,
-                        amount,
-                    })
+        let on_player_action = ctx.link().callback(GameMsg::Action);
+        let customers = self.state.customers_per_service();
+        let services: Html = self
+            .state
+            .services()
+            .filter(|(_, service)| service.state.is_unlocked())
+            .map(|(kind, _)| {
+                let props = CloudServiceProps {
+                    feedback: self.op_feedback[kind.to_code() as usize],
+                    on_audio: self.audio.callback(),
+                    compact,
+                    ..CloudServiceProps::for_service(
+                        kind,
+                        &self.state,
+                        &self.engine,
+                        customers[kind as usize],
+                        &on_player_action,
+                    )
+                };
+                html! {
+                    <CloudService ..props />
                 }
-            };
+            })
+            .collect();
 
-            html! {
-                <CloudService
-                    kind={ServiceKind::Base}
-                    price={self.state.base_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={self.state.base_service.total == Ops(0)}
-                    private={self.state.base_service.private}
-                    />
-            }
+        let total_stats_props = TotalStatsProps {
+            ops_total: ServiceKind::ALL.map(|kind| {
+                let service = self.state.service_by_kind(kind);
+                Some(service.total).filter(|_| service.state.is_unlocked())
+            }),
         };
 
-        let super_c = if super_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Super,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = self.state.ops_per_click;
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Super,
-                        amount,
-                    })
-                }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Super}
-                    price={super_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={super_service.total == Ops(0)}
-                    private={super_service.private}
+        let report = match self.state.reports.as_slice() {
+            [.., previous, report] if !self.state.report_dismissed => html! {
+                <ReportCard
+                    report={report.clone()}
+                    previous={Some(previous.clone())}
+                    on_dismiss={ctx.link().callback(|_| PlayerAction::DismissReport)}
+                    on_audio={self.audio.callback()}
                     />
-            }
-        } else {
-            html! {}
-        };
-
-        let epic_c = if epic_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Epic,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = self.state.ops_per_click;
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Epic,
-                        amount,
-                    })
-                }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Epic}
-                    price={epic_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={epic_service.total == Ops(0)}
-                    private={epic_service.private}
+            },
+            [report] if !self.state.report_dismissed => html! {
+                <ReportCard
+                    report={report.clone()}
+                    on_dismiss={ctx.link().callback(|_| PlayerAction::DismissReport)}
+                    on_audio={self.audio.callback()}
                     />
-            }
-        } else {
-            html! {}
+            },
+            _ => html! {},
         };
 
-        let awesome_c = if awesome_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Awesome,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = self.state.ops_per_click;
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Awesome,
-                        amount,
-                    })
-                }
-            };
+        let on_rename = ctx
+            .link()
+            .callback(|name| PlayerAction::RenameCompany { name });
 
-            html! {
-                <CloudService
-                    kind={ServiceKind::Awesome}
-                    price={awesome_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={awesome_service.total == Ops(0)}
-                    private={awesome_service.private}
-                    />
-            }
-        } else {
-            html! {}
-        };
+        let notifications: Vec<_> = self.engine.notifications().recent(5).cloned().collect();
 
-        let total_stats_props = TotalStatsProps {
-            base_ops_total: self.state.base_service.total,
-            super_ops_total: if super_service.unlocked {
-                Some(super_service.total)
-            } else {
-                None
-            },
-            epic_ops_total: if epic_service.unlocked {
-                Some(epic_service.total)
-            } else {
-                None
-            },
-            awesome_ops_total: if awesome_service.unlocked {
-                Some(awesome_service.total)
-            } else {
-                None
-            },
+        let (toast_label, on_action) = match &self.toast_action {
+            Some((label, action)) => {
+                let action = action.clone();
+                (
+                    Some(label.clone()),
+                    ctx.link().callback(move |_| action.clone()),
+                )
+            }
+            None => (None, Callback::noop()),
         };
 
-        let all_cards = ALL_CARDS;
-
-        let cards: Html = all_cards
-            .iter()
-            .filter(|card| card.should_appear(&self.state))
-            .map(|card| {
-                let link = ctx.link().clone();
-                let cost = card.cost.clone();
-                let disabled = !self.state.can_afford(&cost);
-                let id = card.id;
-                html! {
-                    <Card
-                        {id}
-                        title={card.title}
-                        description={card.description}
-                        {cost}
-                        {disabled}
-                        on_click={move |_| link.send_message(PlayerAction::UseCard { id: id.into() })}
-                        />
-                }
-            })
-            .collect();
-
         let (cpu_load, mem_load) = self.state.total_processing();
         let mem_total: Memory = self.state.nodes.iter().map(|n| n.ram_capacity).sum();
+        // shown once the first caching card is owned
+        let cache = (self.state.cache_level > 0).then(|| {
+            (
+                self.engine.cache_hit_rate,
+                cache_hit_rate(self.state.cache_level, self.state.cache_fraction),
+            )
+        });
+        let cache_speedup = self.engine.cache_speedup;
+        let cache_fraction = self.state.cache_fraction;
+        // egress only becomes a concern at rack scale
+        let bandwidth_load = (self.state.can_buy_racks || self.state.bandwidth_level > 0)
+            .then(|| self.engine.bandwidth_load(&self.state));
+        // the load balancer only queues requests with more than one node
+        let waiting_queue = (self.state.nodes.len() > 1
+            && self.state.routing_level != RoutingLevel::NoRoutingCost)
+            .then(|| self.engine.waiting_queue_occupancy(&self.state));
+        let (mem_reserved, _) = self.state.memory_breakdown();
+        let on_cache_fraction = ctx
+            .link()
+            .callback(|fraction| PlayerAction::SetCacheFraction { fraction });
 
         let powersave = self.state.is_powersaving();
         let nodes: Vec<NodeProps> = self
@@ -419,61 +813,681 @@ impl Component for Game {
                 id: node.id,
                 num_cores: node.num_cores,
                 ram_capacity: node.ram_capacity,
-                cpu_upgrade_cost: node.next_cpu_upgrade_cost(),
-                ram_upgrade_cost: node.next_ram_upgrade_cost(),
+                cpu_upgrade_cost: node.next_cpu_upgrade_cost(self.engine.config()),
+                ram_upgrade_cost: node.next_ram_upgrade_cost(self.engine.config()),
                 powersave,
+                restart_cooldown: node.restarting_until.saturating_sub(self.state.time),
+                overclocked: !node.overclock.is_none(),
+                memory: node.memory_breakdown(),
+                lifetime: node.lifetime,
+                serving_for: self.state.time.saturating_sub(node.lifetime.created_at),
             })
             .collect();
 
         let equipment = {
+            let config = self.engine.config();
+            // preview purchases once the player can make them
+            let (node_preview, rack_preview) = if self.state.can_buy_nodes
+                && self.state.hardware_panel_level >= HARDWARE_PANEL_PURCHASES
+            {
+                let rates = self.engine.resource_rates(&self.state);
+                let (node, node_cost) = if self.state.can_buy_racks {
+                    (NodeSpec::fully_upgraded(config), config.upgraded_node_cost)
+                } else {
+                    (NodeSpec::bare(config), config.bare_node_cost)
+                };
+                let node_preview = PurchasePreview::new(&self.state, &node, node_cost, &rates);
+                let rack_preview = self.state.can_buy_datacenters.then(|| {
+                    PurchasePreview::new(
+                        &self.state,
+                        &NodeSpec::upgraded_rack(config),
+                        config.upgraded_rack_cost,
+                        &rates,
+                    )
+                });
+                (Some(node_preview), rack_preview)
+            } else {
+                (None, None)
+            };
             let link = ctx.link().clone();
             let on_player_action = move |action| link.send_message(action);
+            let on_expand = ctx.link().callback(GameMsg::ExpandEquipment);
             html! {
                 <Equipment
                     can_buy_nodes={self.state.can_buy_nodes}
                     can_buy_racks={self.state.can_buy_racks}
                     can_buy_datacenters={self.state.can_buy_datacenters}
                     funds={self.state.funds}
+                    bare_node_cost={config.bare_node_cost}
+                    upgraded_node_cost={config.upgraded_node_cost}
+                    upgraded_rack_cost={config.upgraded_rack_cost}
+                    panel_level={self.state.hardware_panel_level}
                     nodes={nodes}
                     {powersave}
-                    {on_player_action} />
+                    {node_preview}
+                    {rack_preview}
+                    rack_utilizations={self.state.rack_utilizations()}
+                    expanded={self.expanded_equipment}
+                    {on_expand}
+                    {on_player_action}
+                    on_audio={self.audio.callback()}
+                    {compact} />
+            }
+        };
+
+        // shortly after an upgrade, in case it was a misclick
+        let undo_purchase = match self.engine.undoable_purchase(self.state.time) {
+            Some(purchase) => {
+                let label = match purchase.resource {
+                    HardwareResource::Cpu => t!(
+                        "hardware.undo_cpu",
+                        node = purchase.node,
+                        cost = purchase.cost
+                    ),
+                    HardwareResource::Ram => t!(
+                        "hardware.undo_ram",
+                        node = purchase.node,
+                        cost = purchase.cost
+                    ),
+                };
+                let onclick = ctx.link().callback(|_| PlayerAction::UndoLastPurchase);
+                html! {
+                    <div class="undo-purchase">
+                        <button {onclick}>{"\u{21a9} "}{label}</button>
+                    </div>
+                }
+            }
+            None => html! {},
+        };
+
+        let metrics = self.engine.metrics(&self.state);
+        let recommendation = match recommend_purchase(&self.state, &metrics, self.engine.config()) {
+            Some(recommendation) => {
+                let onclick = {
+                    let link = ctx.link().clone();
+                    let action = recommendation.action;
+                    move |_| link.send_message(action.clone())
+                };
+                html! {
+                    <div class="hint">
+                        {"\u{1f4a1} "}{recommendation.label}{" ("}{recommendation.benefit}{") "}
+                        <button {onclick}>{t!("app.buy", cost = recommendation.cost)}</button>
+                    </div>
+                }
+            }
+            None => html! {},
+        };
+
+        // the numbers are those quoted when the confirmation opened,
+        // so that they hold still while the player reads them
+        let card_confirmation = match &self.interaction {
+            PendingInteraction::ConfirmCard { quote, updated } => {
+                let card = card_by_id(quote.id).map_or("", |card| card.title());
+                let remaining: Html = quote
+                    .ops_left()
+                    .map(|(kind, available, remaining)| {
+                        html! {
+                            <li>
+                                {t!(
+                                    "confirm_card.ops_left",
+                                    service = kind,
+                                    available = available,
+                                    remaining = remaining,
+                                )}
+                            </li>
+                        }
+                    })
+                    .collect();
+                let conversion = match quote.conversion {
+                    Some(conversion) => html! {
+                        <>
+                            <p class="destructive">{t!("confirm_card.destructive")}</p>
+                            <ul>
+                                <li>
+                                    {t!(
+                                        "confirm_card.racks",
+                                        nodes = conversion.nodes,
+                                        racks = conversion.racks,
+                                    )}
+                                </li>
+                                if conversion.credit > Money::zero() {
+                                    <li>{t!("confirm_card.credit", amount = conversion.credit)}</li>
+                                }
+                                <li>{t!("confirm_card.rerouted", requests = quote.requests_rerouted)}</li>
+                            </ul>
+                        </>
+                    },
+                    None => html! {},
+                };
+                let id = quote.id;
+                let on_confirm = ctx
+                    .link()
+                    .callback(move |_| PlayerAction::UseCard { id: id.into() });
+                let on_cancel = ctx.link().callback(|_| GameMsg::ConfirmCard(None));
+                html! {
+                    <Modal title={t!("confirm_card.title", card = card)}>
+                        if *updated {
+                            <p class="updated">{t!("confirm_card.updated")}</p>
+                        }
+                        {conversion}
+                        if quote.ops_left().next().is_some()
+                            && ctx.props().settings.needs_confirmation(&quote.cost, &self.state)
+                        {
+                            <p>{t!("confirm_card.warning")}</p>
+                            <ul>{remaining}</ul>
+                        }
+                        <div class="modal-buttons">
+                            <button onclick={on_confirm}>{t!("confirm_card.confirm")}</button>
+                            <button onclick={on_cancel}>{t!("confirm_card.cancel")}</button>
+                        </div>
+                    </Modal>
+                }
+            }
+            _ => html! {},
+        };
+
+        let bailout = if self.state.bailout_pending {
+            let on_accept = {
+                let link = ctx.link().clone();
+                move |_| link.send_message(PlayerAction::AcceptBailout)
+            };
+            let on_bankruptcy = {
+                let link = ctx.link().clone();
+                move |_| link.send_message(PlayerAction::DeclareBankruptcy)
+            };
+            html! {
+                <Modal title={t!("bailout.title")}>
+                    <p>
+                        {t!("bailout.offer")}
+                    </p>
+                    <p>{t!("bailout.alternative")}</p>
+                    <div class="modal-buttons">
+                        <button onclick={on_accept}>{t!("bailout.accept")}</button>
+                        <button onclick={on_bankruptcy}>{t!("bailout.bankruptcy")}</button>
+                    </div>
+                </Modal>
+            }
+        } else {
+            html! {}
+        };
+
+        let narrative_event = match self
+            .state
+            .narrative
+            .pending()
+            .and_then(|pending| pending.event())
+        {
+            Some(event) => {
+                let choices: Html = event
+                    .choices
+                    .iter()
+                    .enumerate()
+                    .map(|(choice, spec)| {
+                        let onclick = ctx.link().callback(move |_| PlayerAction::ResolveEvent {
+                            id: event.id.into(),
+                            choice,
+                        });
+                        html! {
+                            <button {onclick}>{spec.label()}</button>
+                        }
+                    })
+                    .collect();
+                html! {
+                    <Modal title={t!("event.title")}>
+                        <p>{event.text()}</p>
+                        <div class="modal-buttons">
+                            {choices}
+                        </div>
+                    </Modal>
+                }
             }
+            None => html! {},
+        };
+
+        // the daily challenge ends for good
+        let daily_over = match &self.state.daily {
+            Some(daily) if self.state.is_daily_over() => html! {
+                <Modal title={t!("daily.over_title")}>
+                    <p>{t!("daily.over_message", date = daily.date)}</p>
+                    <ScoreBoard score={ScoreBreakdown::of(&self.state)} />
+                    <ShareSummary summary={self.state.summary_text()} />
+                </Modal>
+            },
+            _ => html! {},
+        };
+        let daily_countdown = match (&self.state.daily, self.state.daily_time_left()) {
+            (Some(daily), Some(left)) => {
+                let seconds = left.div_ceil(TIME_UNITS_PER_SECOND);
+                let time = format!("{}:{:02}", seconds / 60, seconds % 60);
+                html! {
+                    <span class="daily-countdown">
+                        {t!("daily.remaining", date = daily.date, time = time)}
+                    </span>
+                }
+            }
+            _ => html! {},
+        };
+
+        // announce the win until the player moves on with the story
+        let win = if self.state.is_win_announced() {
+            let on_continue = ctx.link().callback(|_| PlayerAction::DismissWin);
+            html! {
+                <Modal title={t!("win.title")}>
+                    <p>{t!("win.message")}</p>
+                    <ScoreBoard score={ScoreBreakdown::of(&self.state)} />
+                    <div class="modal-buttons">
+                        <button onclick={on_continue}>{t!("win.continue")}</button>
+                    </div>
+                </Modal>
+            }
+        } else {
+            html! {}
+        };
+
+        let paused = if self.pause.is_paused() {
+            let on_resume = ctx.link().callback(|_| GameMsg::Resume);
+            html! {
+                <Modal title={t!("app.paused")}>
+                    <div class="modal-buttons">
+                        <button onclick={on_resume}>{t!("app.resume")}</button>
+                    </div>
+                </Modal>
+            }
+        } else {
+            html! {}
+        };
+
+        #[cfg(feature = "debug-tools")]
+        let debug_sidebar = if ctx.props().sandbox {
+            let link = ctx.link().clone();
+            let on_player_action = move |action| link.send_message(action);
+            html! {
+                <DebugSidebar demand={self.state.demand} {on_player_action} />
+            }
+        } else {
+            html! {}
+        };
+        #[cfg(not(feature = "debug-tools"))]
+        let debug_sidebar = html! {};
+
+        // only built while shown
+        #[cfg(feature = "debug-tools")]
+        let debug_overlay = if self.debug_overlay {
+            let snapshot = std::rc::Rc::new(self.engine.debug_snapshot(&self.state));
+            let metrics_text = AttrValue::from(self.engine.metrics_text(&self.state));
+            html! { <DebugOverlay {snapshot} {metrics_text} /> }
+        } else {
+            html! {}
         };
+        #[cfg(not(feature = "debug-tools"))]
+        let debug_overlay = html! {};
+
+        let mut panels: Vec<VChild<Panel>> = vec![
+            html_nested! {
+                <Panel title={t!("panel.services")}>
+                    <div>
+                        {services}
+                    </div>
+                </Panel>
+            },
+            html_nested! {
+                <Panel title={t!("panel.business")}>
+                    {report}
+                    <Business ..business_props />
+                    <Notifications entries={notifications} />
+                </Panel>
+            },
+            html_nested! {
+                <Panel title={t!("panel.hardware")}>
+                    if self.state.hardware_panel_level >= HARDWARE_PANEL_LOAD_BARS {
+                        <Power {cpu_load} {mem_load} {mem_reserved} {mem_total} {cache} {cache_speedup} {cache_fraction} {on_cache_fraction} {bandwidth_load} {waiting_queue} />
+                    }
+                    {equipment}
+                    {undo_purchase}
+                    if self.state.can_see_request_rates {
+                        <ProcessingChart
+                            current={self.engine.processing_times().current_overall()}
+                            previous={self.engine.processing_times().previous_overall()} />
+                    }
+                    {recommendation}
+                </Panel>
+            },
+            html_nested! {
+                <Panel title={t!("panel.projects")} classes={classes!["projects", ctx.props().settings.compact_projects.then_some("compact")]}>
+                    {self.cards.clone()}
+                </Panel>
+            },
+        ];
+        if self.state.can_see_request_rates {
+            panels.push(html_nested! {
+                <Panel title={t!("panel.statistics")}>
+                    <Stats metrics={self.engine.metrics(&self.state)} summary={self.state.summary_text()} />
+                </Panel>
+            });
+        }
 
         html! {
             <>
+                {paused}
+                {bailout}
+                {narrative_event}
+                {win}
+                {daily_over}
+                {card_confirmation}
+                {debug_sidebar}
+                {debug_overlay}
                 <header>
                     <TotalStats ..total_stats_props />
                     <div>
                         <h1>{ "10\u{00d7} Cloud Champion" }</h1>
-                        <span class="subtitle"></span>
+                        <span class="subtitle">
+                            <CompanyName name={self.state.company_name.clone()} {on_rename} on_audio={self.audio.callback()} />
+                        </span>
+                        {daily_countdown}
+                    </div>
+                    <div class="save-status">
+                        if let Some(banner) = self.storage.banner() {
+                            {banner}
+                        }
+                        <button class="pause" title={t!("app.pause")} onclick={ctx.link().callback(|_| GameMsg::Pause)}>
+                            {"\u{23f8}"}
+                        </button>
                     </div>
-                    // empty div to make it even
-                    <div />
                 </header>
                 <main>
-                    <div class="panel-container">
-                        <Panel title="Services">
-                            <div>
-                                {base_c}
-                                {super_c}
-                                {epic_c}
-                                {awesome_c}
-                            </div>
-                        </Panel>
-                        <Panel title="Business">
-                            <Business ..business_props />
-                        </Panel>
-                        <Panel title="Hardware">
-                            <Power {cpu_load} {mem_load} {mem_total} />
-                            {equipment}
-                        </Panel>
-                        <Panel title="Projects" classes={classes!["projects"]}>
-                            {cards}
-                        </Panel>
-                    </div>
+                    if compact {
+                        <TabbedPanels>
+                            {for panels}
+                        </TabbedPanels>
+                    } else {
+                        <div class="panel-container">
+                            {for panels}
+                        </div>
+                    }
                 </main>
+                if let (Some(message), serial) = self.toast.clone() {
+                    <Toast {message} {serial} action={toast_label} {on_action} />
+                }
+            </>
+        }
+    }
+}
+
+impl Game {
+    /// Send a message to the simulation if it runs in a web worker,
+    /// returning whether it does.
+    fn send_to_worker(&self, input: SimulationInput) -> bool {
+        #[cfg(feature = "worker")]
+        if let Some(simulation) = &self.simulation {
+            simulation.send(input);
+            return true;
+        }
+        let _ = input;
+        false
+    }
+
+    /// Follow the outcome of a player action.
+    fn action_done(&mut self, action: &PlayerAction, result: Result<(), String>) {
+        let offered = self
+            .toast_action
+            .as_ref()
+            .is_some_and(|(_, offered)| offered == action);
+        match result {
+            Ok(()) if offered => {
+                // the offer was taken up
+                self.toast = (None, self.toast.1);
+                self.toast_action = None;
+            }
+            Ok(()) => {}
+            Err(e) => {
+                gloo_console::warn!("Could not apply action:", &e);
+                self.show_toast(e);
+            }
+        }
+    }
+
+    /// Follow the engine after it advanced the game,
+    /// returning whether the view needs to be re-rendered.
+    fn after_update(&mut self, ctx: &Context<Self>) -> bool {
+        self.sync_interaction(ctx);
+        self.audio.emit_all(self.engine.take_audio_events());
+        let expired_trials = self.engine.take_expired_trials();
+        for trial in &expired_trials {
+            self.show_expired_trial(trial);
+        }
+        // report whether the player's ops went through
+        let mut feedback_changed = false;
+        for (feedback, ops) in self
+            .op_feedback
+            .iter_mut()
+            .zip(self.engine.take_player_ops())
+        {
+            if !ops.is_empty() {
+                feedback.serial = feedback.serial.wrapping_add(1);
+                feedback.ops = ops;
+                feedback_changed = true;
+            }
+        }
+        let mut save_status_changed = false;
+        if self.engine.take_save_request() {
+            let state = &mut self.state;
+            save_status_changed = autosave(&mut self.storage, state.time, || state.save_game());
+        }
+        // only re-render if something visible changed
+        let changed = self.refresh(ctx)
+            || save_status_changed
+            || feedback_changed
+            || !expired_trials.is_empty();
+        // the developer overlay follows the engine on every tick
+        #[cfg(feature = "debug-tools")]
+        let changed = changed || self.debug_overlay;
+        changed
+    }
+
+    /// Show a message to the player for a short while.
+    fn show_toast(&mut self, message: impl Into<AttrValue>) {
+        let (toast, serial) = &mut self.toast;
+        *toast = Some(message.into());
+        *serial = serial.wrapping_add(1);
+        self.toast_action = None;
+    }
+
+    /// Let the player know that a trial ran out,
+    /// offering to extend it.
+    fn show_expired_trial(&mut self, trial: &ExpiredTrial) {
+        self.show_toast(t!(
+            "toast.trial_expired",
+            service = trial.spec.service,
+            ops = Ops(trial.ops as i64),
+        ));
+        let label = t!(
+            "toast.extend_trial",
+            seconds = TRIAL_EXTENSION / TIME_UNITS_PER_SECOND,
+            fee = trial_extension_fee(trial.spec.service, TRIAL_EXTENSION),
+        );
+        let action = PlayerAction::ExtendTrial {
+            spec_id: trial.spec.id,
+            duration: TRIAL_EXTENSION,
+        };
+        self.toast_action = Some((label.into(), action));
+    }
+
+    /// Whether the game awaits a decision from the player
+    /// on a narrative event or a bailout.
+    fn decision_pending(&self) -> bool {
+        self.state.bailout_pending || self.state.narrative.pending().is_some()
+    }
+
+    /// Follow the decisions which came up or were made since,
+    /// resuming the messages which waited for them.
+    fn sync_interaction(&mut self, ctx: &Context<Self>) {
+        let pending = self.decision_pending();
+        match &mut self.interaction {
+            PendingInteraction::Decision { deferred } if !pending => {
+                for msg in deferred.drain(..) {
+                    ctx.link().send_message(msg);
+                }
+                self.interaction = PendingInteraction::Idle;
+            }
+            PendingInteraction::Decision { .. } => {}
+            // a confirmation opened before the decision came up
+            // would describe numbers which the decision may change
+            _ if pending => {
+                self.interaction = PendingInteraction::Decision { deferred: vec![] };
+            }
+            _ => {}
+        }
+    }
+
+    /// Update the view digest and the project cards if they changed,
+    /// returning whether the view needs to be re-rendered.
+    fn refresh(&mut self, ctx: &Context<Self>) -> bool {
+        let cards_digest = card_digest(&self.state, &self.engine);
+        if cards_digest != self.cards_digest {
+            self.cards_digest = cards_digest;
+            self.cards = self.project_cards(ctx);
+        }
+        let digest = ui_digest(&self.state, &self.engine);
+        let changed = digest != self.digest;
+        self.digest = digest;
+        changed
+    }
+    /// Build the project cards panel,
+    /// with the cards pinned by the player on top,
+    /// the other cards available to use
+    /// and a preview of upcoming cards.
+    fn project_cards(&self, ctx: &Context<Self>) -> Html {
+        // only the pinned cards still on offer are shown
+        let pinned: Vec<Html> = self
+            .state
+            .cards_pinned
+            .iter()
+            .filter_map(|id| self.engine.visible_cards().find(|card| card.id == id))
+            .map(|card| self.project_card(ctx, card))
+            .collect();
+        let cards: Html = self
+            .engine
+            .visible_cards()
+            .filter(|card| !self.state.is_card_pinned(card.id))
+            .filter(|card| matches_filter(&self.card_filter, card.title(), card.description()))
+            .map(|card| self.project_card(ctx, card))
+            .collect();
+
+        // the filter also searches through the completed projects
+        let filter = if self.state.cards_used.is_empty() {
+            html! {}
+        } else {
+            let oninput = ctx.link().callback(|e: InputEvent| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                GameMsg::FilterCards(input.value())
+            });
+            html! {
+                <input
+                    type="search"
+                    class="card-filter"
+                    placeholder={t!("app.filter_projects")}
+                    value={self.card_filter.clone()}
+                    {oninput} />
+            }
+        };
+        let completed = CompletedProject::list(&self.state, &self.card_filter);
+
+        // preview the nearest milestones
+        // once the player can introspect the market
+        let upcoming: Html = if self.state.can_see_demand {
+            let mut upcoming: Vec<_> = self
+                .engine
+                .upcoming_cards()
+                .filter_map(|card| {
+                    card.upcoming_progress(&self.state)
+                        .map(|(progress, requirement)| (card, progress, requirement))
+                })
+                .collect();
+            upcoming.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+            upcoming.truncate(3);
+
+            if upcoming.is_empty() {
+                html! {}
+            } else {
+                html! {
+                    <>
+                        <h4 class="upcoming-title">{t!("app.upcoming")}</h4>
+                        {upcoming.into_iter().map(|(card, progress, requirement)| html! {
+                            <UpcomingCard
+                                key={card.id}
+                                title={card.title()}
+                                {requirement}
+                                {progress}
+                                />
+                        }).collect::<Html>()}
+                    </>
+                }
+            }
+        } else {
+            html! {}
+        };
+
+        html! {
+            <>
+                if !pinned.is_empty() {
+                    <div class="pinned-cards">
+                        <h4 class="pinned-title">{t!("app.pinned")}</h4>
+                        {for pinned}
+                    </div>
+                }
+                {filter}
+                <div class="card-list">
+                    {cards}
+                </div>
+                {upcoming}
+                if !self.state.cards_used.is_empty() {
+                    <CompletedProjects projects={completed} />
+                }
             </>
         }
     }
+
+    /// Build the card of a project available to use.
+    fn project_card(&self, ctx: &Context<Self>, card: &'static CardSpec) -> Html {
+        let link = ctx.link().clone();
+        let cost = self.state.card_cost(card);
+        let disabled = !self.state.can_afford(&cost);
+        let hint = (self.state.is_in_debt() && cost.money > Money::zero())
+            .then(|| AttrValue::from(t!("business.in_debt_hint")));
+        // estimate the wait once the player can introspect the market
+        let eta = if disabled && self.state.can_see_demand {
+            self.engine.card_eta(&self.state, card)
+        } else {
+            None
+        };
+        let id = card.id;
+        let pinned = self.state.is_card_pinned(id);
+        let affordable_shares = pinned.then(|| cost.affordable_shares(&self.state));
+        let needs_confirm = ctx.props().settings.may_confirm_card(card);
+        let on_confirm = ctx.link().callback(move |_| GameMsg::ConfirmCard(Some(id)));
+        let on_pin = ctx.link().callback(move |_| {
+            if pinned {
+                PlayerAction::UnpinCard { id: id.into() }
+            } else {
+                PlayerAction::PinCard { id: id.into() }
+            }
+        });
+        html! {
+            <Card
+                {id}
+                title={card.title()}
+                description={card.full_description()}
+                {cost}
+                {disabled}
+                {hint}
+                on_click={move |_| link.send_message(PlayerAction::UseCard { id: id.into() })}
+                {needs_confirm}
+                {on_confirm}
+                {eta}
+                {affordable_shares}
+                {pinned}
+                {on_pin}
+                />
+        }
+    }
 }
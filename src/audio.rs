@@ -1,12 +1,16 @@
 //! Audio module
+//!
+//! The rest of the game emits [`AudioEvent`]s,
+//! and this module decides which sound each of them makes.
 
 use js_sys::{
     wasm_bindgen::{JsCast as _, JsValue, UnwrapThrowExt},
     Reflect,
 };
 use web_sys::HtmlAudioElement;
+use yew::Callback;
 
-use crate::central::state::try_local_storage;
+use crate::{central::persistence::SaveStorage, storage::try_local_storage, AudioEvent};
 
 pub static BUTTON_OP_CLICK: &str = "assets/audio/opclick.ogg";
 pub static BUTTON_ZIP_CLICK: &str = "assets/audio/zipclick.ogg";
@@ -24,7 +28,7 @@ fn load_audio_once(file_path: &str, property_name: &str) -> HtmlAudioElement {
     if let Some(window) = web_sys::window() {
         let audio_elem = window.get(property_name);
         if let Some(audio_elem) = audio_elem {
-            return audio_elem.dyn_into::<HtmlAudioElement>().unwrap_throw();
+            audio_elem.dyn_into::<HtmlAudioElement>().unwrap_throw()
         } else {
             let audio_elem = create_audio_element(file_path);
             let _ = Reflect::set(&window, &JsValue::from_str(property_name), &audio_elem);
@@ -43,12 +47,55 @@ fn load_zip_click() -> HtmlAudioElement {
     load_audio_once(BUTTON_ZIP_CLICK, "__zip_click_audio")
 }
 
-pub fn play_op_click() {
-    play(&load_op_click(), 0.1);
+/// Play the sound for the given audio event.
+///
+/// Events without a sound of their own
+/// reuse the click sounds at a different volume.
+pub fn play_event(event: AudioEvent) {
+    match event {
+        AudioEvent::OpClick => play(&load_op_click(), 0.1),
+        AudioEvent::UiClick => play(&load_zip_click(), 0.25),
+        AudioEvent::Warning => play(&load_zip_click(), 0.4),
+        AudioEvent::Bill => play(&load_op_click(), 0.2),
+        AudioEvent::CardAvailable => play(&load_zip_click(), 0.15),
+        AudioEvent::Win => play(&load_zip_click(), 0.5),
+    }
+}
+
+/// The channel through which the game plays its sounds.
+///
+/// The game component owns one
+/// and hands out its callback to the components which make sounds,
+/// as well as playing the events returned by the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBus {
+    callback: Callback<AudioEvent>,
 }
 
-pub fn play_zip_click() {
-    play(&load_zip_click(), 0.25);
+impl AudioBus {
+    pub fn new() -> Self {
+        AudioBus {
+            callback: Callback::from(play_event),
+        }
+    }
+
+    /// The callback for components to emit audio events through.
+    pub fn callback(&self) -> Callback<AudioEvent> {
+        self.callback.clone()
+    }
+
+    /// Play the sounds for all of the given audio events.
+    pub fn emit_all(&self, events: impl IntoIterator<Item = AudioEvent>) {
+        for event in events {
+            self.callback.emit(event);
+        }
+    }
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub fn play(elem: &HtmlAudioElement, volume: f64) {
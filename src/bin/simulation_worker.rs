@@ -0,0 +1,7 @@
+//! The web worker running the game simulation
+//! (see [`cloud_champion::worker`]).
+
+fn main() {
+    cloud_champion::web::init_logging();
+    cloud_champion::worker::register();
+}
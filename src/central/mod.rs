@@ -1,7 +0,0 @@
-pub mod action;
-pub mod cards;
-pub mod cloud_user;
-pub mod engine;
-pub mod queue;
-pub mod state;
-pub mod stuff;
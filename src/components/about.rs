@@ -0,0 +1,105 @@
+//! Module for the About box,
+//! which shows the build of the game, its credits and licenses.
+
+use yew::prelude::*;
+
+use crate::{components::modal::Modal, t, GAME_VERSION, GIT_HASH};
+
+/// the page for reporting issues with the game
+pub const ISSUE_TRACKER_URL: &str = "https://github.com/Enet4/10xCloudChampion/issues/new";
+
+/// The link to report an issue with the game,
+/// with the given version filled in.
+pub fn issue_url(version: &str) -> String {
+    let body = format!("\n\n---\nGame version: {version}");
+    format!("{ISSUE_TRACKER_URL}?body={}", percent_encode(&body))
+}
+
+/// Encode text for use in a URL query,
+/// keeping only unreserved characters as they are.
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct AboutProps {
+    /// the version of the game
+    #[prop_or(GAME_VERSION.to_string())]
+    pub version: String,
+    /// the git commit which the game was built from
+    #[prop_or(GIT_HASH.to_string())]
+    pub git_hash: String,
+    /// called when the player closes the box
+    #[prop_or_default]
+    pub on_close: Callback<()>,
+}
+
+impl AboutProps {
+    /// The version with the commit it was built from,
+    /// as shown to the player and included in bug reports.
+    pub fn version_text(&self) -> String {
+        format!("{} ({})", self.version, self.git_hash)
+    }
+}
+
+/// A modal box about the game.
+#[function_component]
+pub fn About(props: &AboutProps) -> Html {
+    let version = props.version_text();
+    let on_close = props.on_close.clone();
+    html! {
+        <Modal title={t!("about.title")}>
+            <div class="about">
+                <p class="version">{t!("about.version", version = version)}</p>
+                <p>{t!("about.credits")}</p>
+                <h3>{t!("about.licenses")}</h3>
+                <ul>
+                    <li>{t!("about.license_code")}</li>
+                    <li>{t!("about.license_opclick")}</li>
+                    <li>{t!("about.license_zipclick")}</li>
+                    <li>{t!("about.license_fonts")}</li>
+                </ul>
+            </div>
+            <div class="modal-buttons">
+                <a class="button-link" href={issue_url(&version)} target="_blank">
+                    {t!("about.report_issue")}
+                </a>
+                <button onclick={move |_| on_close.emit(())}>{t!("about.close")}</button>
+            </div>
+        </Modal>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{issue_url, AboutProps};
+    use crate::GIT_HASH;
+
+    #[test]
+    fn test_about_props_carry_build() {
+        let props = yew::props!(AboutProps {});
+        assert_eq!(props.version, env!("CARGO_PKG_VERSION"));
+        assert!(!props.git_hash.is_empty());
+        assert_eq!(
+            props.version_text(),
+            format!("{} ({})", env!("CARGO_PKG_VERSION"), GIT_HASH)
+        );
+    }
+
+    #[test]
+    fn test_issue_url_includes_version() {
+        let url = issue_url("1.2.3 (abc1234)");
+        assert!(url.starts_with("https://github.com/Enet4/10xCloudChampion/issues/new?body="));
+        assert!(url.ends_with("Game%20version%3A%201.2.3%20%28abc1234%29"));
+        assert!(!url.contains(' '));
+    }
+}
@@ -2,7 +2,20 @@
 //! which shows some metrics about how the cloud management business is going.
 use yew::prelude::*;
 
-use crate::{audio::play_zip_click, Money, Ops};
+use crate::{
+    central::{
+        insurance::{InsurancePolicy, INSURANCE_COOLDOWN},
+        revenue::TopCustomer,
+        staff::StaffMember,
+        state::ServiceLevelAgreement,
+        streak::StreakReward,
+        tariff::{Tariff, TARIFF_SWITCH_FEE},
+    },
+    components::{exchange::OpExchange, info_tip::InfoTip, modal::Modal, staff::StaffRoster},
+    display::{to_seconds, GameDuration},
+    i18n::Topic,
+    t, AudioEvent, Money, Ops, PlayerAction, ServiceKind, Time,
+};
 
 #[derive(Debug, Default, PartialEq, Properties)]
 pub struct BusinessProps {
@@ -18,93 +31,473 @@ pub struct BusinessProps {
     /// (or `None` if this has not been unlocked yet)
     pub request_rates: Option<(f32, f32)>,
 
-    /// ops available for the base service
-    pub base_ops_available: Ops,
+    /// ops available for each service,
+    /// indexed by [`ServiceKind::to_code`]
+    /// (or `None` if the service is not available yet)
+    pub ops_available: [Option<Ops>; ServiceKind::ALL.len()],
 
-    /// ops available for the super service
-    /// (or `None` if this service is not available yet)
+    /// whether the player can trade ops at the op exchange
     #[prop_or_default]
-    pub super_ops_available: Option<Ops>,
+    pub can_exchange_ops: bool,
 
-    /// ops available for the epic service
-    /// (or `None` if this service is not available yet)
+    /// callback for the trades made at the op exchange
     #[prop_or_default]
-    pub epic_ops_available: Option<Ops>,
-
-    /// ops available for the awesome service
-    /// (or `None` if this service is not available yet)
-    #[prop_or_default]
-    pub awesome_ops_available: Option<Ops>,
+    pub on_player_action: Callback<PlayerAction>,
 
     /// the amount of money to be paid for electricity
     /// (bill should not appear if the money is below 1 cent)
     pub electricity_bill: Money,
 
-    /// whether the player can afford to pay the electricity bill
-    pub can_pay_bill: bool,
+    /// the time left until powersave mode kicks in
+    /// (or `None` if there is no bill big enough for it)
+    #[prop_or_default]
+    pub time_until_powersave: Option<Time>,
+
+    /// the amount paid for electricity with the "Pay" button,
+    /// which is less than the bill if funds are short
+    pub bill_payment: Money,
 
     /// callback for when the player clicks the "Pay" button
     pub on_pay_bills: Callback<()>,
 
+    /// the number of bills in a row paid on time
+    /// (or `None` if no bill was issued yet)
+    #[prop_or_default]
+    pub on_time_streak: Option<u32>,
+
+    /// the next reward for paying on time and the streak needed for it
+    /// (or `None` if all rewards were granted)
+    #[prop_or_default]
+    pub next_streak_reward: Option<(u32, StreakReward)>,
+
+    /// whether electricity bills are paid automatically
+    /// (or `None` if this has not been unlocked yet)
+    #[prop_or_default]
+    pub auto_pay: Option<bool>,
+
+    /// the money paid in advance for electricity
+    #[prop_or_default]
+    pub electricity_credit: Money,
+
+    /// the amount paid in advance with the "Prepay" button,
+    /// the same as the last bill
+    /// (no button is shown if zero)
+    #[prop_or_default]
+    pub prepay_amount: Money,
+
+    /// the tariff of the current energy provider
+    /// (or `None` if choosing providers has not been unlocked yet)
+    #[prop_or_default]
+    pub tariff: Option<Tariff>,
+
+    /// the tariffs on offer,
+    /// with the projected bill of a full period under each
+    #[prop_or_default]
+    pub tariff_offers: Vec<(Tariff, Money)>,
+
+    /// whether the cooldown after the last provider switch is over
+    #[prop_or_default]
+    pub can_switch_tariff: bool,
+
+    /// the balance of the loan to be repaid
+    /// (or `None` if there is no loan)
+    #[prop_or_default]
+    pub loan: Option<Money>,
+
+    /// whether the player can afford to repay the loan at once
+    #[prop_or_default]
+    pub can_repay_loan: bool,
+
+    /// callback for when the player clicks the "Repay" button
+    #[prop_or_default]
+    pub on_repay_loan: Callback<()>,
+
+    /// the staff hired
+    /// (or `None` if hiring has not been unlocked yet)
+    #[prop_or_default]
+    pub staff: Option<Vec<StaffMember>>,
+
+    /// the service-level agreements signed
+    #[prop_or_default]
+    pub slas: Vec<ServiceLevelAgreement>,
+
+    /// the infrastructure insurance policy taken, if any
+    #[prop_or_default]
+    pub insurance: Option<InsurancePolicy>,
+
+    /// the customers which brought the most revenue
+    /// (or `None` if request analytics have not been unlocked yet)
+    #[prop_or_default]
+    pub top_customers: Option<Vec<TopCustomer>>,
+
     /// estimate for the service demand
     /// (or `None` if this has not been unlocked yet)
-    pub demand: Option<f32>,
+    pub demand: Option<f64>,
+
+    /// the phase of the daily demand cycle
+    /// (or `None` if demand estimation has not been unlocked yet)
+    #[prop_or_default]
+    pub day_phase: Option<f32>,
+
+    /// whether demand growth is paused for lack of capacity
+    #[prop_or_default]
+    pub growth_stalled: bool,
+
+    /// callback for the sounds made by the business panel
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
 }
 
 /// The business component.
 
 #[function_component]
 pub fn Business(props: &BusinessProps) -> Html {
-    let available_ops_to_show: Html = [
-        ("super", props.super_ops_available),
-        ("epic", props.epic_ops_available),
-        ("awesome", props.awesome_ops_available),
-    ]
-    .iter()
-    .filter_map(|(name, maybe)| maybe.map(|ops| (name, ops)))
-    .map(|(name, counts)| {
-        html! {
-            <><span>{"Available "} {name} {" ops:"}</span> {" "} {counts}<br/></>
-        }
-    })
-    .collect();
+    let choosing_tariff = use_state(|| false);
+
+    let available_ops_to_show: Html = ServiceKind::ALL
+        .iter()
+        .zip(props.ops_available)
+        .filter_map(|(kind, maybe)| maybe.map(|ops| (kind, ops)))
+        .map(|(kind, counts)| {
+            let name = kind.tier().name.to_lowercase();
+            html! {
+                <><span>{t!("business.available_ops", service = name)}</span> {" "} {counts}<br/></>
+            }
+        })
+        .collect();
 
     let electricity = if props.electricity_bill >= Money::cents(1) {
         let onclick = props.on_pay_bills.clone();
+        let on_audio = props.on_audio.clone();
         let onclick = move |_| {
-            play_zip_click();
+            on_audio.emit(AudioEvent::UiClick);
             onclick.emit(())
         };
+        let warning = match props.time_until_powersave {
+            Some(0) => html! {
+                <span class="powersave-warning">{t!("business.powersave_active")} <InfoTip topic={Topic::Powersave}/><br/></span>
+            },
+            Some(time) => {
+                let seconds = to_seconds(time);
+                html! {
+                    <span class="powersave-warning">
+                        {t!("business.powersave_in", time = format!("{}:{:02}", seconds / 60, seconds % 60))}
+                        {" "}<InfoTip topic={Topic::Powersave}/>
+                        <br/>
+                    </span>
+                }
+            }
+            None => html! {},
+        };
         html! {
             <p>
-                <span>{"Electricity bill: "}</span> {props.electricity_bill.into_cent_precision().to_string()}
-                <button disabled={!props.can_pay_bill} {onclick}>{"Pay"}</button>
+                {warning}
+                <span>{t!("business.electricity_bill")}</span> {props.electricity_bill.to_string()}
+                <button disabled={props.bill_payment == Money::zero()} {onclick}>
+                    if props.bill_payment < props.electricity_bill {
+                        {t!("business.pay_partial", amount = props.bill_payment)}
+                    } else {
+                        {t!("business.pay")}
+                    }
+                </button>
+            </p>
+        }
+    } else {
+        html! {}
+    };
+
+    let streak = if let Some(streak) = props.on_time_streak {
+        html! {
+            <p class="bill-streak" title={t!("business.streak_hint")}>
+                <span>{t!("business.streak")}</span> {streak}
+                if let Some((milestone, reward)) = props.next_streak_reward {
+                    <br/>
+                    <span class="next-reward">
+                        {t!("business.next_reward", bills = milestone - streak.min(milestone), reward = reward)}
+                    </span>
+                }
             </p>
         }
     } else {
         html! {}
     };
 
+    let auto_pay = if let Some(on) = props.auto_pay {
+        let on_player_action = props.on_player_action.clone();
+        let on_audio = props.on_audio.clone();
+        let onchange = move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            on_player_action.emit(PlayerAction::SetAutoPay { on: !on })
+        };
+        html! {
+            <label class="auto-pay" title={t!("business.auto_pay_hint")}>
+                <input type="checkbox" checked={on} {onchange} />
+                {t!("business.auto_pay")}
+            </label>
+        }
+    } else {
+        html! {}
+    };
+
+    let prepay = if props.prepay_amount > Money::zero() {
+        let amount = props.prepay_amount;
+        let on_player_action = props.on_player_action.clone();
+        let on_audio = props.on_audio.clone();
+        let onclick = move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            on_player_action.emit(PlayerAction::PrepayElectricity { amount })
+        };
+        html! {
+            <button disabled={props.funds < amount} title={t!("business.prepay_hint")} {onclick}>
+                {t!("business.prepay", amount = amount)}
+            </button>
+        }
+    } else {
+        html! {}
+    };
+
+    let tariff = if let Some(tariff) = props.tariff {
+        let onclick = {
+            let choosing_tariff = choosing_tariff.clone();
+            let on_audio = props.on_audio.clone();
+            move |_| {
+                on_audio.emit(AudioEvent::UiClick);
+                choosing_tariff.set(true);
+            }
+        };
+        html! {
+            <p class="tariff">
+                <span>{t!("business.tariff")}</span> {tariff.provider()}
+                <button {onclick}>{t!("business.change_provider")}</button>
+            </p>
+        }
+    } else {
+        html! {}
+    };
+
+    let tariff_chooser = match props.tariff {
+        Some(current) if *choosing_tariff => {
+            let offers: Html = props
+                .tariff_offers
+                .iter()
+                .map(|&(tariff, projected_bill)| {
+                    let button = if tariff == current {
+                        html! { <span class="tariff-current">{t!("tariffs.current")}</span> }
+                    } else {
+                        let on_player_action = props.on_player_action.clone();
+                        let on_audio = props.on_audio.clone();
+                        let choosing_tariff = choosing_tariff.clone();
+                        let onclick = move |_| {
+                            on_audio.emit(AudioEvent::UiClick);
+                            on_player_action.emit(PlayerAction::SwitchTariff { tariff });
+                            choosing_tariff.set(false);
+                        };
+                        html! {
+                            <button
+                                disabled={!props.can_switch_tariff || props.funds < TARIFF_SWITCH_FEE}
+                                {onclick}>
+                                {t!("tariffs.switch", fee = TARIFF_SWITCH_FEE)}
+                            </button>
+                        }
+                    };
+                    html! {
+                        <li>
+                            <b>{tariff.provider()}</b><br/>
+                            <span>{tariff.description()}</span><br/>
+                            <span>{t!("tariffs.projected_bill", amount = projected_bill.for_display())}</span>
+                            {" "}
+                            {button}
+                        </li>
+                    }
+                })
+                .collect();
+            let on_close = {
+                let choosing_tariff = choosing_tariff.clone();
+                move |_| choosing_tariff.set(false)
+            };
+            html! {
+                <Modal title={t!("tariffs.title")}>
+                    <p>{t!("tariffs.intro")}</p>
+                    <ul class="tariffs">{offers}</ul>
+                    if !props.can_switch_tariff {
+                        <p class="tariff-cooldown">{t!("tariffs.cooldown")}</p>
+                    }
+                    <div class="modal-buttons">
+                        <button onclick={on_close}>{t!("tariffs.close")}</button>
+                    </div>
+                </Modal>
+            }
+        }
+        _ => html! {},
+    };
+
+    let credit = if props.electricity_credit > Money::zero() {
+        html! {
+            <><span>{t!("business.credit")}</span> {props.electricity_credit.for_display().to_string()} {" "}</>
+        }
+    } else {
+        html! {}
+    };
+
+    let loan = if let Some(balance) = props.loan {
+        let onclick = props.on_repay_loan.clone();
+        let on_audio = props.on_audio.clone();
+        let onclick = move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            onclick.emit(())
+        };
+        html! {
+            <p>
+                <span>{t!("business.loan")}</span> {balance.for_display().to_string()}
+                <button disabled={!props.can_repay_loan} {onclick}>{t!("business.repay")}</button>
+            </p>
+        }
+    } else {
+        html! {}
+    };
+
+    let slas: Html = props
+        .slas
+        .iter()
+        .map(|sla| {
+            let class = if sla.is_violated() {
+                "sla sla-broken"
+            } else {
+                "sla sla-kept"
+            };
+            html! {
+                <span class={class}>
+                    {t!(
+                        "business.sla",
+                        service = sla.service,
+                        rate = format!("{:.1}%", sla.drop_rate() * 100.)
+                    )}
+                    <br/>
+                </span>
+            }
+        })
+        .collect();
+
+    let insurance = if let Some(policy) = &props.insurance {
+        let on_player_action = props.on_player_action.clone();
+        let on_audio = props.on_audio.clone();
+        let onclick = move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            on_player_action.emit(PlayerAction::CancelInsurance)
+        };
+        html! {
+            <p class="insurance">
+                {t!(
+                    "business.insurance",
+                    premium = policy.premium,
+                    payouts = policy.payouts.for_display()
+                )}
+                {" "}
+                <button {onclick} title={t!("business.insurance_cancel_hint", time = GameDuration(INSURANCE_COOLDOWN))}>
+                    {t!("business.insurance_cancel")}
+                </button>
+            </p>
+        }
+    } else {
+        html! {}
+    };
+
+    let top_customers = match &props.top_customers {
+        Some(customers) if !customers.is_empty() => {
+            let customers: Html = customers
+                .iter()
+                .map(|customer| {
+                    html! {
+                        <li>
+                            {t!(
+                                "business.customer",
+                                service = customer.service,
+                                revenue = customer.revenue.for_display(),
+                                ops = customer.ops
+                            )}
+                            if customer.in_trial {
+                                <span class="customer-trial">{t!("business.customer_trial")}</span>
+                            }
+                        </li>
+                    }
+                })
+                .collect();
+            html! {
+                <div class="customers">
+                    <span>{t!("business.customers")}</span>
+                    <ul>{customers}</ul>
+                </div>
+            }
+        }
+        _ => html! {},
+    };
+
     html! {
         <div class="business">
             <p>
-                <span>{"Funds: "}</span> {props.funds.into_cent_precision().to_string()} <br/>
-                <span>{"Available base ops: "}</span> {props.base_ops_available} <br/>
+                <span>{t!("business.funds")}</span>
+                if props.funds < Money::zero() {
+                    <span class="funds negative" title={t!("business.in_debt_hint")}>
+                        {props.funds.for_display().to_string()}
+                    </span>
+                } else {
+                    <span class="funds">{props.funds.for_display().to_string()}</span>
+                }
+                <br/>
                 {available_ops_to_show}
             </p>
             <p>
                 if let Some(demand) = props.demand {
-                    <><span>{"Visibility: "}</span> {format!("{:.2}%", demand / 100.)} <br/></>
+                    <><span>{t!("business.visibility")}</span> {format!("{:.2}%", demand / 100.)} <InfoTip topic={Topic::Visibility}/>
+                    {props.day_phase.map(|phase| if phase < 0.5 {
+                        html! { <span class="day-phase" title={t!("business.daytime")}>{" \u{2600}"}</span> }
+                    } else {
+                        html! { <span class="day-phase" title={t!("business.nighttime")}>{" \u{263E}"}</span> }
+                    })}
+                    if props.growth_stalled {
+                        <span class="growth-stalled" title={t!("business.growth_stalled_hint")}>{t!("business.growth_stalled")}</span>
+                    }
+                    <br/></>
                 }
                 if let Some(energy_consumption_rate) = props.energy_consumption_rate {
-                    <><span>{"Energy consumption: "}</span> {format!("{:.2} Wev", energy_consumption_rate)} <br/></>
+                    <><span>{t!("business.energy_consumption")}</span> {format!("{:.2} Wev", energy_consumption_rate)} <br/></>
                 }
                 if let Some((drop_rate, failure_rate)) = props.request_rates {
-                    <><span>{"Request drop rate: "}</span> {format!("{:.1}%", drop_rate * 100.)}<br/></>
-                    <><span>{"Request failure rate: "}</span> {format!("{:.1}%", failure_rate * 100.)}<br/></>
+                    <><span>{t!("business.drop_rate")}</span> {format!("{:.1}%", drop_rate * 100.)} <InfoTip topic={Topic::RequestRates}/><br/></>
+                    <><span>{t!("business.failure_rate")}</span> {format!("{:.1}%", failure_rate * 100.)}<br/></>
                 }
             </p>
             {electricity}
+            {streak}
+            {tariff}
+            {tariff_chooser}
+            if props.auto_pay.is_some() || props.prepay_amount > Money::zero() {
+                <p class="electricity-payment">
+                    {credit}
+                    {prepay}
+                    {auto_pay}
+                </p>
+            }
+            {loan}
+            {insurance}
+            if !props.slas.is_empty() {
+                <p>{slas}</p>
+            }
+            {top_customers}
+            if props.can_exchange_ops {
+                <OpExchange
+                    ops_available={props.ops_available}
+                    on_player_action={props.on_player_action.clone()}
+                    on_audio={props.on_audio.clone()} />
+            }
+            if let Some(staff) = &props.staff {
+                <StaffRoster
+                    staff={staff.clone()}
+                    funds={props.funds}
+                    ops_available={props.ops_available}
+                    on_player_action={props.on_player_action.clone()}
+                    on_audio={props.on_audio.clone()} />
+            }
         </div>
     }
 }
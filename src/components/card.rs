@@ -2,7 +2,7 @@
 
 use yew::prelude::*;
 
-use crate::Cost;
+use crate::{central::cards::all::card_by_id, display::GameDuration, t, Cost, Time, WorldState};
 
 #[derive(PartialEq, Properties)]
 pub struct CardProps {
@@ -12,8 +12,34 @@ pub struct CardProps {
     pub cost: Cost,
     #[prop_or_default]
     pub disabled: bool,
+    /// why the card cannot be used right now, if not for its cost
+    #[prop_or_default]
+    pub hint: Option<AttrValue>,
     #[prop_or_default]
     pub on_click: Option<Callback<()>>,
+    /// whether using the card should be confirmed first,
+    /// in which case clicking it calls `on_confirm` instead of `on_click`
+    #[prop_or_default]
+    pub needs_confirm: bool,
+    #[prop_or_default]
+    pub on_confirm: Option<Callback<()>>,
+    /// the estimated game time until the card can be afforded,
+    /// shown under the cost while the card is disabled
+    /// (or `None` to show no estimate)
+    #[prop_or_default]
+    pub eta: Option<Time>,
+    /// the share of each part of the cost which the player has,
+    /// shown as a bar while the card is disabled
+    /// (see [`Cost::affordable_shares`])
+    #[prop_or_default]
+    pub affordable_shares: Option<Vec<f32>>,
+    /// whether the card is pinned to the top of the projects panel
+    #[prop_or_default]
+    pub pinned: bool,
+    /// callback for pinning or unpinning the card,
+    /// or `None` if it cannot be pinned
+    #[prop_or_default]
+    pub on_pin: Option<Callback<()>>,
 }
 
 /// The bonus/purchase project card component.
@@ -27,7 +53,11 @@ pub fn Card(props: &CardProps) -> Html {
     let disabled = props.disabled;
 
     let on_card_click = {
-        let on_click = props.on_click.clone();
+        let on_click = if props.needs_confirm && props.on_confirm.is_some() {
+            props.on_confirm.clone()
+        } else {
+            props.on_click.clone()
+        };
         Callback::from(move |_e: MouseEvent| {
             if let Some(on_click) = &on_click {
                 on_click.emit(());
@@ -43,17 +73,211 @@ pub fn Card(props: &CardProps) -> Html {
                 {"("}
                 {props.cost.to_string()}
                 {")"}
+                if let Some(eta) = props.eta.filter(|_| disabled) {
+                    <br/>
+                    <span class="eta">{t!("card.eta", time = GameDuration(eta))}</span>
+                }
             </span>
         }
     };
 
-    html! {
-        <button key={props.id} class={class} disabled={disabled} onclick={on_card_click}>
+    let affordability = match props.affordable_shares.as_ref().filter(|_| disabled) {
+        Some(shares) => html! {
+            <div class="affordability">
+                {shares.iter().map(|share| {
+                    let percent = (share * 100.).floor() as i32;
+                    html! {
+                        <div class="progress-bar">
+                            <div class="progress-bar-inner" style={format!("width:{percent}%")} />
+                        </div>
+                    }
+                }).collect::<Html>()}
+            </div>
+        },
+        None => html! {},
+    };
+
+    let card = html! {
+        <button key={props.id} class={class} disabled={disabled} title={props.hint.clone()} onclick={on_card_click}>
             <div>
                 <b>{ &props.title }</b>
                 {cost}
             </div>
             <p>{ &props.description }</p>
+            {affordability}
         </button>
+    };
+
+    let Some(on_pin) = props.on_pin.clone() else {
+        return card;
+    };
+    // disabled buttons take no clicks,
+    // so the pin sits next to the card rather than in it
+    let on_pin_click = {
+        let on_pin = on_pin.clone();
+        move |_: MouseEvent| on_pin.emit(())
+    };
+    let on_context_menu = move |e: MouseEvent| {
+        e.prevent_default();
+        on_pin.emit(());
+    };
+    let (pin_class, pin_title) = if props.pinned {
+        (classes!["card-pin", "pinned"], t!("card.unpin"))
+    } else {
+        (classes!["card-pin"], t!("card.pin"))
+    };
+    html! {
+        <div key={props.id} class="card-slot" oncontextmenu={on_context_menu}>
+            {card}
+            <button class={pin_class} title={pin_title} onclick={on_pin_click}>{"\u{1f4cc}"}</button>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct UpcomingCardProps {
+    pub title: AttrValue,
+    /// a human-readable description of what the card needs
+    pub requirement: AttrValue,
+    /// the completion fraction between 0 and 1
+    pub progress: f32,
+}
+
+/// A preview of a card which is not available yet,
+/// with the progress towards its milestone.
+#[function_component]
+pub fn UpcomingCard(props: &UpcomingCardProps) -> Html {
+    let percent = (props.progress * 100.).floor() as i32;
+    html! {
+        <div class="card upcoming">
+            <div>
+                <b>{ &props.title }</b>
+                <span class="cost">{percent}{"%"}</span>
+            </div>
+            <p>{t!("card.needs", requirement = props.requirement)}</p>
+            <div class="progress-bar">
+                <div class="progress-bar-inner" style={format!("width:{percent}%")} />
+            </div>
+        </div>
+    }
+}
+
+/// Whether a card matches the text typed in the projects filter,
+/// by its title or description, ignoring case.
+pub fn matches_filter(filter: &str, title: &str, description: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty()
+        || title.to_lowercase().contains(&filter)
+        || description.to_lowercase().contains(&filter)
+}
+
+/// A project card which the player used earlier in the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedProject {
+    /// the title of the card,
+    /// or its ID if the card no longer exists
+    pub title: AttrValue,
+    /// the description of the card,
+    /// empty if the card no longer exists
+    pub description: AttrValue,
+    /// the game time at which the card was used
+    pub time: Time,
+}
+
+impl CompletedProject {
+    /// List the cards used in the given game in the order they were used,
+    /// keeping only those which match the projects filter.
+    pub fn list(state: &WorldState, filter: &str) -> Vec<Self> {
+        state
+            .cards_used_chronological()
+            .into_iter()
+            .map(|used| match card_by_id(&used.id) {
+                Some(card) => CompletedProject {
+                    title: card.title().into(),
+                    description: card.description().into(),
+                    time: used.time,
+                },
+                // removed in an update
+                None => CompletedProject {
+                    title: used.id.to_string().into(),
+                    description: AttrValue::default(),
+                    time: used.time,
+                },
+            })
+            .filter(|project| matches_filter(filter, &project.title, &project.description))
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CompletedProjectsProps {
+    /// the cards used so far, oldest first
+    pub projects: Vec<CompletedProject>,
+}
+
+/// A collapsible history of the project cards used so far.
+#[function_component]
+pub fn CompletedProjects(props: &CompletedProjectsProps) -> Html {
+    html! {
+        <details class="completed-projects">
+            <summary>{t!("app.completed_projects", count = props.projects.len())}</summary>
+            <ul>
+                {props.projects.iter().map(|project| html! {
+                    <li>
+                        <span class="completed-time">{GameDuration(project.time).to_string()}</span>
+                        <b>{&project.title}</b>
+                        if !project.description.is_empty() {
+                            <p>{&project.description}</p>
+                        }
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches_filter, CompletedProject};
+    use crate::central::{state::UsedCard, testing::WorldStateBuilder};
+
+    #[test]
+    fn test_matches_filter() {
+        assert!(matches_filter("", "Fiber uplink", "More bandwidth"));
+        assert!(matches_filter(" FIBER ", "Fiber uplink", "More bandwidth"));
+        assert!(matches_filter(
+            "bandwidth",
+            "Fiber uplink",
+            "More bandwidth"
+        ));
+        assert!(!matches_filter("cache", "Fiber uplink", "More bandwidth"));
+    }
+
+    #[test]
+    fn test_completed_projects() {
+        let mut state = WorldStateBuilder::new()
+            .time(200)
+            .cards_used(&["b0"])
+            .time(100)
+            .cards_used(&["a0p"])
+            .build();
+        // a card removed in an update
+        state.cards_used.push(UsedCard {
+            id: "gone".into(),
+            time: 150,
+        });
+
+        let projects = CompletedProject::list(&state, "");
+        let titles: Vec<_> = projects.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Test your service", "gone", "Incentive from your family"]
+        );
+        assert!(projects[1].description.is_empty());
+        assert_eq!(projects[1].time, 150);
+
+        let projects = CompletedProject::list(&state, "gone");
+        assert_eq!(projects.len(), 1);
+        assert!(CompletedProject::list(&state, "nothing like it").is_empty());
     }
 }
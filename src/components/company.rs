@@ -0,0 +1,97 @@
+//! Module for showing and editing the name of the player's company.
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{central::state::MAX_COMPANY_NAME_LEN, t, AudioEvent};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct CompanyNameInputProps {
+    /// the name typed in so far
+    pub value: AttrValue,
+    /// called with the new contents of the input
+    pub on_input: Callback<String>,
+}
+
+/// A text input for the name of the company.
+#[function_component]
+pub fn CompanyNameInput(props: &CompanyNameInputProps) -> Html {
+    let on_input = props.on_input.clone();
+    let oninput = move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        on_input.emit(input.value());
+    };
+    html! {
+        <input
+            type="text"
+            class="company-name-input"
+            placeholder={t!("company.placeholder")}
+            maxlength={MAX_COMPANY_NAME_LEN.to_string()}
+            value={props.value.clone()}
+            {oninput}
+        />
+    }
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct CompanyNameProps {
+    /// the current name of the company
+    pub name: AttrValue,
+    /// called with the new name when the player renames the company
+    pub on_rename: Callback<String>,
+    /// callback for the sounds made by the buttons
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+}
+
+/// The name of the company,
+/// which turns into a form when the player wants to rename it.
+#[function_component]
+pub fn CompanyName(props: &CompanyNameProps) -> Html {
+    // the name being typed in, if renaming
+    let editing = use_state(|| None::<String>);
+
+    let Some(value) = (*editing).clone() else {
+        let onclick = {
+            let editing = editing.clone();
+            let name = props.name.to_string();
+            let on_audio = props.on_audio.clone();
+            move |_| {
+                on_audio.emit(AudioEvent::UiClick);
+                editing.set(Some(name.clone()))
+            }
+        };
+        return html! {
+            <span class="company-name">
+                {props.name.clone()}
+                {" "}
+                <button class="link" title="Rename company" {onclick}>{"\u{270e}"}</button>
+            </span>
+        };
+    };
+
+    let on_input = {
+        let editing = editing.clone();
+        Callback::from(move |value| editing.set(Some(value)))
+    };
+    let onsubmit = {
+        let editing = editing.clone();
+        let on_rename = props.on_rename.clone();
+        let on_audio = props.on_audio.clone();
+        let value = value.clone();
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            on_audio.emit(AudioEvent::UiClick);
+            on_rename.emit(value.clone());
+            editing.set(None);
+        }
+    };
+    let on_cancel = move |_| editing.set(None);
+    html! {
+        <form class="company-name" {onsubmit}>
+            <CompanyNameInput value={value} {on_input} />
+            <button type="submit">{t!("company.rename")}</button>
+            <button type="button" onclick={on_cancel}>{t!("company.cancel")}</button>
+        </form>
+    }
+}
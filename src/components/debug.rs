@@ -0,0 +1,177 @@
+//! Module for the sandbox mode sidebar,
+//! which manipulates the game state directly,
+//! and the developer overlay,
+//! which shows the internals of the game engine.
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+use crate::{
+    central::{inspect::DebugSnapshot, queue::RequestEventStage},
+    web::copy_to_clipboard,
+    DebugAction, Money, Ops, PlayerAction, ServiceKind,
+};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct DebugSidebarProps {
+    /// the current base service demand
+    pub demand: f64,
+    /// callback for each debug action issued
+    pub on_player_action: Callback<PlayerAction>,
+}
+
+/// The sandbox mode sidebar.
+#[function_component]
+pub fn DebugSidebar(props: &DebugSidebarProps) -> Html {
+    let button = |label: &'static str, action: DebugAction| {
+        let on_player_action = props.on_player_action.clone();
+        let onclick = move |_| on_player_action.emit(PlayerAction::Debug(action.clone()));
+        html! {
+            <button {onclick}>{label}</button>
+        }
+    };
+
+    let ops_buttons: Html = [
+        ("+1000 base ops", ServiceKind::Base),
+        ("+1000 super ops", ServiceKind::Super),
+        ("+1000 epic ops", ServiceKind::Epic),
+        ("+1000 awesome ops", ServiceKind::Awesome),
+        ("+1000 legendary ops", ServiceKind::Legendary),
+    ]
+    .into_iter()
+    .map(|(label, kind)| {
+        button(
+            label,
+            DebugAction::AddOps {
+                kind,
+                amount: Ops(1_000),
+            },
+        )
+    })
+    .collect();
+
+    html! {
+        <div class="debug-sidebar">
+            <h3>{"Sandbox"}</h3>
+            {button("+$1000", DebugAction::AddFunds(Money::dollars(1_000)))}
+            {ops_buttons}
+            {button("Double demand", DebugAction::SetDemand((props.demand * 2.).max(1.)))}
+            {button("Reset demand", DebugAction::SetDemand(0.))}
+            {button("Advance 1 minute", DebugAction::AdvanceTime { seconds: 60 })}
+            {button("Toggle powersave", DebugAction::TogglePowersave)}
+            {button("Spawn DoS", DebugAction::SpawnDos(ServiceKind::Base))}
+        </div>
+    }
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct DebugOverlayProps {
+    /// the latest snapshot of the engine internals
+    pub snapshot: Rc<DebugSnapshot>,
+    /// the game metrics in the Prometheus text exposition format,
+    /// copied for external dashboards and bug reports
+    pub metrics_text: AttrValue,
+}
+
+/// The developer overlay.
+#[function_component]
+pub fn DebugOverlay(props: &DebugOverlayProps) -> Html {
+    let snapshot = &props.snapshot;
+
+    // the outcome of the last attempt to copy the metrics
+    let copy_status = use_state(|| None::<bool>);
+    let on_copy_metrics = {
+        let metrics_text = props.metrics_text.clone();
+        let copy_status = copy_status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let copy_status = copy_status.clone();
+            copy_to_clipboard(&metrics_text, move |copied| copy_status.set(Some(copied)));
+        })
+    };
+    let copy_message = match *copy_status {
+        Some(true) => "Copied",
+        Some(false) => "Could not copy",
+        None => "",
+    };
+
+    let events: Html = snapshot
+        .next_events
+        .iter()
+        .map(|event| {
+            let stage = match event.kind {
+                RequestEventStage::RequestArrived { .. } => "arrived".to_string(),
+                RequestEventStage::RequestRouted { node_num } => format!("routed to {node_num}"),
+                RequestEventStage::RequestProcessed { node_num, .. } => {
+                    format!("processed by {node_num}")
+                }
+            };
+            html! {
+                <tr>
+                    <td>{event.timestamp}</td>
+                    <td>{stage}</td>
+                    <td>{event.service.to_string()}</td>
+                    <td>{event.amount}{if event.bad { " (bad)" } else { "" }}</td>
+                </tr>
+            }
+        })
+        .collect();
+
+    let nodes: Html = snapshot
+        .nodes
+        .iter()
+        .map(|node| {
+            html! {
+                <tr>
+                    <td>{node.id}</td>
+                    <td>{format!("{}/{}", node.processing, node.num_cores)}</td>
+                    <td>{format!("{}/{}", node.ram_reserved, node.ram_capacity)}</td>
+                    <td>{node.queued}</td>
+                </tr>
+            }
+        })
+        .collect();
+
+    let demand: Html = ServiceKind::ALL
+        .iter()
+        .zip(snapshot.demand)
+        .filter_map(|(kind, demand)| {
+            demand.map(|demand| html! { <li>{format!("{kind}: {demand:.3}")}</li> })
+        })
+        .collect();
+
+    let warnings: Html = snapshot
+        .warnings
+        .iter()
+        .map(|(time, message)| html! { <li>{format!("[{time}] {message}")}</li> })
+        .collect();
+
+    html! {
+        <div class="debug-overlay">
+            <h3>{format!("Engine at {}", snapshot.time)}</h3>
+            <p>
+                {format!(
+                    "Event queue: {}, waiting to route: {} ({}% of {})",
+                    snapshot.queue_len,
+                    snapshot.waiting_len,
+                    snapshot.waiting_len * 100 / snapshot.waiting_capacity.max(1),
+                    snapshot.waiting_capacity,
+                )}
+            </p>
+            <table>
+                <tr><th>{"Time"}</th><th>{"Stage"}</th><th>{"Service"}</th><th>{"Amount"}</th></tr>
+                {events}
+            </table>
+            <table>
+                <tr><th>{"Node"}</th><th>{"Cores"}</th><th>{"RAM"}</th><th>{"Queued"}</th></tr>
+                {nodes}
+            </table>
+            <h4>{"Demand per customer"}</h4>
+            <ul>{demand}</ul>
+            <p>{format!("Memory accounting anomalies: {}", snapshot.accounting_anomalies)}</p>
+            <h4>{"Warnings"}</h4>
+            <ul>{warnings}</ul>
+            <button onclick={on_copy_metrics}>{"Copy metrics"}</button>
+            <span>{copy_message}</span>
+        </div>
+    }
+}
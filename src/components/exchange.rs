@@ -0,0 +1,98 @@
+//! Module for the op exchange widget,
+//! where the player trades ops for ops of a higher tier.
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{central::exchange::Exchange, t, AudioEvent, Ops, PlayerAction, ServiceKind};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct OpExchangeProps {
+    /// ops available for each service,
+    /// indexed by [`ServiceKind::to_code`]
+    /// (or `None` if the service is not available yet)
+    pub ops_available: [Option<Ops>; ServiceKind::ALL.len()],
+    /// callback for when the player makes an exchange
+    pub on_player_action: Callback<PlayerAction>,
+    /// callback for the sounds made by the widget
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+}
+
+/// A widget for trading ops at the op exchange,
+/// previewing the outcome before the trade.
+#[function_component]
+pub fn OpExchange(props: &OpExchangeProps) -> Html {
+    let from = use_state(|| ServiceKind::Base);
+    let to = use_state(|| ServiceKind::Super);
+    let amount = use_state(|| Ops(1_000));
+
+    let unlocked: Vec<ServiceKind> = ServiceKind::ALL
+        .into_iter()
+        .filter(|kind| props.ops_available[kind.to_code() as usize].is_some())
+        .collect();
+    let options = |selected: ServiceKind| -> Html {
+        unlocked
+            .iter()
+            .map(|&kind| {
+                html! {
+                    <option value={kind.to_code().to_string()} selected={kind == selected}>
+                        {kind.to_string()}
+                    </option>
+                }
+            })
+            .collect()
+    };
+    let on_select = |state: UseStateHandle<ServiceKind>| {
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(kind) = select.value().parse().ok().and_then(ServiceKind::from_code) {
+                state.set(kind);
+            }
+        }
+    };
+    let oninput = {
+        let amount = amount.clone();
+        move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            amount.set(Ops(input.value().parse().unwrap_or(0)));
+        }
+    };
+
+    let available = props.ops_available[from.to_code() as usize].unwrap_or_default();
+    let exchange = Exchange::preview(*from, *to, *amount);
+    let preview = match exchange {
+        Some(exchange) => t!(
+            "exchange.preview",
+            obtained = exchange.obtained,
+            to = *to,
+            spent = exchange.spent,
+            from = *from
+        ),
+        None => t!("exchange.upwards_only").to_string(),
+    };
+    let disabled =
+        !exchange.is_some_and(|exchange| exchange.obtained > Ops(0) && exchange.spent <= available);
+    let onclick = {
+        let on_player_action = props.on_player_action.clone();
+        let on_audio = props.on_audio.clone();
+        let (from, to, amount) = (*from, *to, *amount);
+        move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            on_player_action.emit(PlayerAction::ConvertOps { from, to, amount });
+        }
+    };
+
+    html! {
+        <div class="op-exchange">
+            <span>{t!("exchange.title")}</span><br/>
+            <input type="number" min="0" value={amount.0.to_string()} {oninput} />
+            <select onchange={on_select(from.clone())}>{options(*from)}</select>
+            {" \u{2192} "}
+            <select onchange={on_select(to.clone())}>{options(*to)}</select>
+            <br/>
+            <span class="exchange-preview">{preview}</span>
+            <button {disabled} {onclick}>{t!("exchange.trade")}</button>
+        </div>
+    }
+}
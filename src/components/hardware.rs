@@ -1,17 +1,27 @@
 //! Module for hardware and overall computational power and load indicators
 
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 use crate::{
-    audio::play_zip_click,
-    central::engine::{BARE_NODE_COST, UPGRADED_NODE_COST, UPGRADED_RACK_COST},
-    components::load_bar::LoadBar,
-    Memory, Money, PlayerAction,
+    central::{
+        engine::{NodeLifetime, DATACENTER_CAPACITY, RACK_CAPACITY},
+        preview::PurchasePreview,
+        state::{HARDWARE_PANEL_PURCHASES, HARDWARE_PANEL_UPGRADES, MICRO_WATTEVER_PER_WATTEVER},
+    },
+    components::{
+        info_tip::InfoTip,
+        load_bar::LoadBar,
+        overview::{Breadcrumb, OverviewCell, OverviewGrid},
+    },
+    display::{GameDuration, ShortCount},
+    i18n::Topic,
+    t, AudioEvent, Memory, Money, PlayerAction, Time, TIME_UNITS_PER_MILLISECOND,
 };
 
-/// The number of nodes that fit in a rack
-pub(crate) const RACK_CAPACITY: u32 = 4;
-pub(crate) const DATACENTER_CAPACITY: u32 = 32;
+/// The number of racks or data centers
+/// above which they are shown in an overview grid
+pub(crate) const OVERVIEW_THRESHOLD: usize = 8;
 
 #[derive(Debug, PartialEq, Properties)]
 pub struct PowerProps {
@@ -19,23 +29,97 @@ pub struct PowerProps {
     pub cpu_load: f32,
     /// the current memory load between 0 and 1
     pub mem_load: f32,
+    /// the part of the memory load reserved (mostly for caching),
+    /// between 0 and 1
+    #[prop_or_default]
+    pub mem_reserved: f32,
     /// the total memory available
     pub mem_total: Memory,
+    /// the measured share of requests served from the cache
+    /// and the highest share at the current caching level,
+    /// once caching is available
+    #[prop_or_default]
+    pub cache: Option<(f32, f32)>,
+    /// how many times faster requests are processed thanks to the cache
+    #[prop_or(1.)]
+    pub cache_speedup: f32,
+    /// the share of the cache in use
+    #[prop_or(1.)]
+    pub cache_fraction: f32,
+    /// called with the share of the cache to use
+    /// when the player changes it
+    #[prop_or_default]
+    pub on_cache_fraction: Callback<f32>,
+    /// the share of the network bandwidth taken between 0 and 1,
+    /// once it is worth showing
+    #[prop_or_default]
+    pub bandwidth_load: Option<f32>,
+    /// the share of the load balancer's waiting queue in use
+    /// between 0 and 1,
+    /// if requests are routed through it
+    #[prop_or_default]
+    pub waiting_queue: Option<f32>,
 }
 
-/// An indicator of the total CPU and Memory usage
+/// An indicator of the total CPU, Memory and bandwidth usage
 #[function_component]
 pub fn Power(props: &PowerProps) -> Html {
     let memory_used = props.mem_total * props.mem_load;
+    let on_cache_fraction = {
+        let cb = props.on_cache_fraction.clone();
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(percent) = input.value().parse::<f32>() {
+                cb.emit(percent / 100.);
+            }
+        }
+    };
 
     html! {
         <div class="power">
             <div class="power-cpu">
-                {"CPU: "} {(props.cpu_load * 100.).round()} {"%"} <LoadBar load={props.cpu_load}/>
+                {t!("hardware.cpu_load", load = (props.cpu_load * 100.).round())} <LoadBar load={props.cpu_load}/>
             </div>
             <div class="power-mem">
-                {"Memory: "} {memory_used} {"/"} {props.mem_total} <LoadBar load={props.mem_load}/>
+                {t!("hardware.memory_load", used = memory_used, total = props.mem_total)}
+                {" "}<InfoTip topic={Topic::MemoryReserve}/>
+                <LoadBar load={props.mem_load} reserved={props.mem_reserved}/>
+                if props.mem_reserved > 0. {
+                    <div class="load-legend">
+                        <span class="swatch reserved" />{t!("hardware.memory_cache")}
+                        <span class="swatch used" />{t!("hardware.memory_requests")}
+                    </div>
+                }
             </div>
+            if let Some(load) = props.waiting_queue {
+                <div class="power-routing" title={t!("hardware.routing_queue_hint")}>
+                    {t!("hardware.routing_queue", load = (load * 100.).round())} <LoadBar {load}/>
+                </div>
+            }
+            if let Some(load) = props.bandwidth_load {
+                <div class="power-bandwidth">
+                    {t!("hardware.bandwidth_load", load = (load * 100.).round())} <LoadBar {load}/>
+                </div>
+            }
+            if let Some((hit_rate, max_rate)) = props.cache {
+                <div class="power-cache" title={t!("hardware.cache_hint", speedup = format!("{:.1}", props.cache_speedup))}>
+                    {t!(
+                        "hardware.cache",
+                        rate = (hit_rate * 100.).round(),
+                        max = (max_rate * 100.).round(),
+                    )}
+                </div>
+                <label class="power-cache-fraction" title={t!("hardware.cache_fraction_hint")}>
+                    {t!("hardware.cache_fraction", percent = (props.cache_fraction * 100.).round())}
+                    <input
+                        type="range"
+                        min="0"
+                        max="100"
+                        step="5"
+                        value={((props.cache_fraction * 100.).round() as u32).to_string()}
+                        onchange={on_cache_fraction} />
+                </label>
+            }
         </div>
     }
 }
@@ -57,6 +141,23 @@ pub struct NodeProps {
     /// the cost for the next RAM upgrade
     /// (or None if no upgrade is available)
     pub ram_upgrade_cost: Option<Money>,
+    /// the time left until the node finishes restarting
+    /// (0 if it is not restarting)
+    #[prop_or_default]
+    pub restart_cooldown: Time,
+    /// whether the node is overclocked
+    #[prop_or_default]
+    pub overclocked: bool,
+    /// the share of the node's memory reserved
+    /// and the share taken by requests
+    #[prop_or_default]
+    pub memory: (f32, f32),
+    /// what the node has done since it was set up
+    #[prop_or_default]
+    pub lifetime: NodeLifetime,
+    /// how long the node has been serving
+    #[prop_or_default]
+    pub serving_for: Time,
 }
 
 /// Props for a Cloud Node component
@@ -77,69 +178,128 @@ pub struct UpgradableNodeProps {
     /// the cost for the next RAM upgrade
     /// (or None if no upgrade is available)
     pub ram_upgrade_cost: Option<Money>,
-    /// whether the CPU upgrade can be afforded
+    /// whether the CPU upgrade button is disabled,
+    /// as it cannot be afforded
     pub cpu_upgrade_disabled: bool,
-    /// whether the RAM upgrade can be afforded
+    /// whether the RAM upgrade button is disabled,
+    /// as it cannot be afforded
     pub ram_upgrade_disabled: bool,
     /// callback for when the CPU upgrade button is clicked
     pub on_cpu_upgrade: Callback<()>,
     /// callback for when the RAM upgrade button is clicked
     pub on_ram_upgrade: Callback<()>,
+    /// whether to show the upgrade buttons at all
+    #[prop_or(true)]
+    pub show_upgrades: bool,
+    /// why the upgrades cannot be bought right now, if not for their cost
+    #[prop_or_default]
+    pub upgrade_hint: Option<AttrValue>,
+    /// the time left until the node finishes restarting
+    /// (0 if it is not restarting)
+    #[prop_or_default]
+    pub restart_cooldown: Time,
+    /// callback for when the restart button is clicked
+    #[prop_or_default]
+    pub on_restart: Callback<()>,
+    /// whether the node is overclocked
+    #[prop_or_default]
+    pub overclocked: bool,
+    /// the share of the node's memory reserved
+    /// and the share taken by requests
+    #[prop_or_default]
+    pub memory: (f32, f32),
+    /// what the node has done since it was set up
+    #[prop_or_default]
+    pub lifetime: NodeLifetime,
+    /// how long the node has been serving
+    #[prop_or_default]
+    pub serving_for: Time,
+    /// callback for the sounds made by the upgrade buttons
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
 }
 
 /// A node in the Cloud network
 #[function_component]
 pub fn UpgradableNode(props: &UpgradableNodeProps) -> Html {
-    let cores = if props.num_cores == 1 {
-        "core"
+    let (reserved, used) = props.memory;
+    let lifetime = props.lifetime;
+    let specs = if props.num_cores == 1 {
+        t!("hardware.specs_one", ram = props.ram_capacity)
     } else {
-        "cores"
+        t!(
+            "hardware.specs",
+            cores = props.num_cores,
+            ram = props.ram_capacity
+        )
     };
 
     let on_cpu_upgrade = {
         let cb = props.on_cpu_upgrade.clone();
+        let on_audio = props.on_audio.clone();
         move |_ev| {
-            play_zip_click();
+            on_audio.emit(AudioEvent::UiClick);
             cb.emit(())
         }
     };
     let on_ram_upgrade = {
         let cb = props.on_ram_upgrade.clone();
+        let on_audio = props.on_audio.clone();
         move |_ev| {
-            play_zip_click();
+            on_audio.emit(AudioEvent::UiClick);
             cb.emit(())
         }
     };
 
-    let cpu_enabled = if !props.cpu_upgrade_disabled {
-        "true"
-    } else {
-        "false"
-    };
-    let ram_enabled = if !props.ram_upgrade_disabled {
-        "true"
-    } else {
-        "false"
+    let on_restart = {
+        let cb = props.on_restart.clone();
+        let on_audio = props.on_audio.clone();
+        move |_ev| {
+            on_audio.emit(AudioEvent::UiClick);
+            cb.emit(())
+        }
     };
 
     html! {
         <div class="node-container">
             <CloudNodeIcon powersave={props.powersave} />
-            <span class="specs">{props.num_cores} {" "} {cores} {", "} {props.ram_capacity} {" RAM"}</span>
+            <span class="specs">{specs}</span>
+            <span class="node-memory">
+                <LoadBar load={reserved + used} {reserved} />
+            </span>
+            if props.overclocked {
+                <span class="overclocked" title={t!("hardware.overclocked_hint")}>{t!("hardware.overclocked")}</span>
+            }
+            <details class="node-lifetime">
+                <summary>{t!("hardware.serving_since", duration = GameDuration(props.serving_for), ops = ShortCount(lifetime.ops_served))}</summary>
+                <span>{t!("hardware.requests_processed", count = ShortCount(lifetime.requests_processed))}</span>
+                <span>{t!("hardware.energy_used", energy = format!("{:.1}", lifetime.energy as f64 / MICRO_WATTEVER_PER_WATTEVER as f64))}</span>
+            </details>
+            if props.show_upgrades {
             <div class="upgrade-container">
             if let Some(cost) = props.cpu_upgrade_cost {
                 <div class="upgrade">
                     <span>{cost.to_string()}</span>
-                    <button enabled={cpu_enabled} onclick={on_cpu_upgrade}>{"Upgrade CPU"}</button>
+                    <button disabled={props.cpu_upgrade_disabled} title={props.upgrade_hint.clone()} onclick={on_cpu_upgrade}>{t!("hardware.upgrade_cpu")}</button>
                 </div>
             }
             if let Some(cost) = props.ram_upgrade_cost {
                 <div class="upgrade">
                     <span>{cost.to_string()}</span>
-                    <button enabled={ram_enabled} onclick={on_ram_upgrade}>{"Upgrade RAM"}</button>
+                    <button disabled={props.ram_upgrade_disabled} title={props.upgrade_hint.clone()} onclick={on_ram_upgrade}>{t!("hardware.upgrade_ram")}</button>
                 </div>
             }
+            <div class="upgrade">
+            if props.restart_cooldown > 0 {
+                <span class="restarting">
+                    {t!("hardware.restarting", seconds = props.restart_cooldown.div_ceil(TIME_UNITS_PER_MILLISECOND as Time * 1_000))}
+                </span>
+            } else {
+                <button onclick={on_restart} title={t!("hardware.restart_hint")}>{t!("hardware.restart")}</button>
+            }
+            </div>
             </div>
+            }
         </div>
     }
 }
@@ -175,66 +335,144 @@ pub struct RackProps {
     /// (means all node purchases are for fully upgraded nodes)
     pub can_buy_racks: bool,
     pub funds: Money,
+    /// the cost of a bare node
+    pub bare_node_cost: Money,
+    /// the cost of a fully upgraded node
+    pub upgraded_node_cost: Money,
+    /// how much of the hardware panel is revealed
+    /// (see [`WorldState::hardware_panel_level`](crate::WorldState::hardware_panel_level))
+    pub panel_level: u8,
     pub nodes: Vec<NodeProps>,
     pub powersave: bool,
+    /// what buying one more node would bring
+    #[prop_or_default]
+    pub node_preview: Option<PurchasePreview>,
     pub on_player_action: Callback<PlayerAction>,
+    /// callback for the sounds made by the rack's buttons
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
 }
 
 /// A rack of nodes
 #[function_component]
 pub fn OpenRack(props: &RackProps) -> Html {
-    let can_buy_more_nodes = props.can_buy_nodes && (props.nodes.len() as u32) < RACK_CAPACITY;
+    let can_buy_more_nodes = props.can_buy_nodes
+        && props.panel_level >= HARDWARE_PANEL_PURCHASES
+        && (props.nodes.len() as u32) < RACK_CAPACITY;
+    let show_upgrades = props.panel_level >= HARDWARE_PANEL_UPGRADES;
     let purchase_button = if can_buy_more_nodes {
         let on_player_action = props.on_player_action.clone();
         let (action, disabled) = if !props.can_buy_racks {
-            (PlayerAction::AddNode, props.funds < BARE_NODE_COST)
+            (PlayerAction::AddNode, props.funds < props.bare_node_cost)
         } else {
             (
                 PlayerAction::AddUpgradedNode,
-                props.funds < UPGRADED_NODE_COST,
+                props.funds < props.upgraded_node_cost,
             )
         };
         let onclick = move |_| on_player_action.emit(action.clone());
         html! {
-            <>
-                <button {onclick} disabled={disabled}>
-                    {"Buy node"}
+            <span class="buy-node">
+                <button {onclick} disabled={disabled} title={debt_hint(props.funds)}>
+                    {t!("hardware.buy_node")}
                 </button>
                 {" "}
                 if !props.can_buy_racks {
-                    <span class="small">{BARE_NODE_COST.to_string()}</span>
+                    <span class="small">{props.bare_node_cost.to_string()}</span>
                 } else {
-                    <span class="small">{UPGRADED_NODE_COST.to_string()}</span>
+                    <span class="small">{props.upgraded_node_cost.to_string()}</span>
                 }
-            </>
+                {purchase_preview(props.node_preview.as_ref())}
+            </span>
         }
     } else {
         html! {}
     };
-    let powersave = props.powersave;
+    let nodes = upgradable_nodes(
+        &props.nodes,
+        props.funds,
+        show_upgrades,
+        props.powersave,
+        &props.on_player_action,
+        &props.on_audio,
+    );
+
+    html! {
+        <div class="rack">
+            {nodes}
+            {purchase_button}
+        </div>
+    }
+}
+
+/// Why nothing can be bought with the given funds, if they are negative
+/// (see [`WorldState::is_in_debt`](crate::WorldState::is_in_debt)).
+fn debt_hint(funds: Money) -> Option<AttrValue> {
+    (funds < Money::zero()).then(|| AttrValue::from(t!("business.in_debt_hint")))
+}
+
+/// Render what a purchase would bring,
+/// revealed when hovering or focusing its button.
+fn purchase_preview(preview: Option<&PurchasePreview>) -> Html {
+    let Some(preview) = preview else {
+        return html! {};
+    };
+    html! {
+        <div class="purchase-preview">
+            <div>{t!("hardware.preview_specs", cores = preview.cores, ram = preview.ram)}</div>
+            <div>
+                {t!(
+                    "hardware.preview_rate",
+                    rate = format!("{:.1}", preview.requests_per_second),
+                    service = preview.service
+                )}
+            </div>
+            if let Some(eta) = preview.eta {
+                <div class="eta">{t!("hardware.preview_eta", time = GameDuration(eta))}</div>
+            }
+        </div>
+    }
+}
+
+/// Whether the button of an upgrade with the given cost is disabled,
+/// which is when the funds are short of it
+/// (and always while in debt).
+fn upgrade_disabled(cost: Option<Money>, funds: Money) -> bool {
+    cost.is_some_and(|cost| funds < cost)
+}
 
-    let nodes: Html = props
-        .nodes
+/// Render the given nodes so that they can be upgraded and restarted.
+fn upgradable_nodes(
+    nodes: &[NodeProps],
+    funds: Money,
+    show_upgrades: bool,
+    powersave: bool,
+    on_player_action: &Callback<PlayerAction>,
+    on_audio: &Callback<AudioEvent>,
+) -> Html {
+    let upgrade_hint = debt_hint(funds);
+    nodes
         .iter()
         .map(|node| {
             let cpu_upgrade_cost = node.cpu_upgrade_cost;
             let ram_upgrade_cost = node.ram_upgrade_cost;
-            let cpu_upgrade_disabled = cpu_upgrade_cost
-                .map(|cost| props.funds < cost)
-                .unwrap_or_default();
-            let ram_upgrade_disabled = ram_upgrade_cost
-                .map(|cost| props.funds < cost)
-                .unwrap_or_default();
+            let cpu_upgrade_disabled = upgrade_disabled(cpu_upgrade_cost, funds);
+            let ram_upgrade_disabled = upgrade_disabled(ram_upgrade_cost, funds);
             let on_cpu_upgrade = {
-                let on_player_action = props.on_player_action.clone();
+                let on_player_action = on_player_action.clone();
                 let node = node.id;
                 move |_| on_player_action.emit(PlayerAction::UpgradeCpu { node })
             };
             let on_ram_upgrade = {
-                let on_player_action = props.on_player_action.clone();
+                let on_player_action = on_player_action.clone();
                 let node = node.id;
                 move |_| on_player_action.emit(PlayerAction::UpgradeRam { node })
             };
+            let on_restart = {
+                let on_player_action = on_player_action.clone();
+                let node = node.id;
+                move |_| on_player_action.emit(PlayerAction::RestartNode { node })
+            };
             html! {
                 <UpgradableNode
                     id={node.id}
@@ -246,17 +484,19 @@ pub fn OpenRack(props: &RackProps) -> Html {
                     {ram_upgrade_disabled}
                     {on_cpu_upgrade}
                     {on_ram_upgrade}
+                    {show_upgrades}
+                    upgrade_hint={upgrade_hint.clone()}
+                    restart_cooldown={node.restart_cooldown}
+                    {on_restart}
+                    overclocked={node.overclocked}
+                    memory={node.memory}
+                    lifetime={node.lifetime}
+                    serving_for={node.serving_for}
+                    on_audio={on_audio.clone()}
                  />
             }
         })
-        .collect();
-
-    html! {
-        <div class="rack">
-            {nodes}
-            {purchase_button}
-        </div>
-    }
+        .collect()
 }
 
 /// Properties for the Equipment component
@@ -267,14 +507,88 @@ pub struct EquipmentProps {
     pub can_buy_racks: bool,
     pub can_buy_datacenters: bool,
     pub funds: Money,
+    /// the cost of a bare node
+    pub bare_node_cost: Money,
+    /// the cost of a fully upgraded node
+    pub upgraded_node_cost: Money,
+    /// the cost of a fully upgraded rack
+    pub upgraded_rack_cost: Money,
+    /// how much of the hardware panel is revealed
+    /// (see [`WorldState::hardware_panel_level`](crate::WorldState::hardware_panel_level))
+    pub panel_level: u8,
     pub powersave: bool,
+    /// what buying one more node would bring
+    #[prop_or_default]
+    pub node_preview: Option<PurchasePreview>,
+    /// what buying one more rack would bring
+    #[prop_or_default]
+    pub rack_preview: Option<PurchasePreview>,
+    /// the CPU and memory usage of each rack
+    /// (see [`WorldState::rack_utilizations`](crate::WorldState::rack_utilizations))
+    #[prop_or_default]
+    pub rack_utilizations: Vec<(f32, f32)>,
+    /// the rack or data center shown in detail, if any
+    #[prop_or_default]
+    pub expanded: Option<usize>,
+    /// callback for when a rack or data center is opened,
+    /// or `None` to return to the overview
+    #[prop_or_default]
+    pub on_expand: Callback<Option<usize>>,
     pub on_player_action: Callback<PlayerAction>,
+    /// callback for the sounds made by the equipment panel
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+    /// whether to lay out the equipment for a small touch screen,
+    /// with larger buttons
+    #[prop_or_default]
+    pub compact: bool,
 }
 
 /// UI component for the whole equipment panel
 #[derive(Debug)]
 pub struct Equipment;
 
+impl Equipment {
+    /// Render the overview grid of racks or data centers,
+    /// or the nodes of the expanded one.
+    ///
+    /// Returns `None` if there are too few of them for an overview.
+    fn overview(
+        &self,
+        ctx: &Context<Self>,
+        root: &'static str,
+        name: &'static str,
+        cells: Vec<OverviewCell>,
+        cell_nodes: &[&[NodeProps]],
+    ) -> Option<Html> {
+        let props = ctx.props();
+        if let Some(index) = props.expanded.filter(|&index| index < cell_nodes.len()) {
+            let on_back = props.on_expand.reform(|_| None);
+            let nodes = upgradable_nodes(
+                cell_nodes[index],
+                props.funds,
+                true,
+                props.powersave,
+                &props.on_player_action,
+                &props.on_audio,
+            );
+            return Some(html! {
+                <div class="overview-detail">
+                    <Breadcrumb {root} current={format!("{name} {}", index + 1)} {on_back} />
+                    {nodes}
+                </div>
+            });
+        }
+        if cells.len() <= OVERVIEW_THRESHOLD {
+            return None;
+        }
+        let on_select = props.on_expand.reform(Some);
+        Some(html! {
+            <OverviewGrid {cells} {on_select} />
+        })
+    }
+}
+
 impl Component for Equipment {
     type Message = ();
     type Properties = EquipmentProps;
@@ -287,72 +601,105 @@ impl Component for Equipment {
         let powersave = ctx.props().powersave;
         let can_buy_racks = ctx.props().can_buy_racks;
         let can_buy_datacenters = ctx.props().can_buy_datacenters;
+        let can_purchase = ctx.props().panel_level >= HARDWARE_PANEL_PURCHASES;
+        let classes = if ctx.props().compact {
+            classes!["equipment", "compact"]
+        } else {
+            classes!["equipment"]
+        };
+        let in_debt_hint = debt_hint(ctx.props().funds);
+        let in_debt = in_debt_hint.is_some();
+        let purchase = |action: PlayerAction| {
+            let on_audio = ctx.props().on_audio.clone();
+            ctx.props().on_player_action.reform(move |_| {
+                on_audio.emit(AudioEvent::UiClick);
+                action.clone()
+            })
+        };
 
         match (can_buy_racks, can_buy_datacenters) {
             (false, false) => {
                 let nodes = ctx.props().nodes.clone();
                 html! {
-                    <div class="equipment">
+                    <div class={classes}>
                         <OpenRack
                             nodes={nodes}
                             can_buy_nodes={ctx.props().can_buy_nodes}
                             can_buy_racks={false}
                             funds={ctx.props().funds}
+                            bare_node_cost={ctx.props().bare_node_cost}
+                            upgraded_node_cost={ctx.props().upgraded_node_cost}
+                            panel_level={ctx.props().panel_level}
                             powersave={powersave}
+                            node_preview={ctx.props().node_preview}
                             on_player_action={ctx.props().on_player_action.clone()}
+                            on_audio={ctx.props().on_audio.clone()}
                         />
                     </div>
                 }
             }
             (true, false) => {
-                // show closed racks instead
-                let racks: Html = ctx
-                    .props()
-                    .nodes
-                    .chunks(RACK_CAPACITY as usize)
-                    .map(|nodes| {
-                        html! {
-                            <div class="closed-rack">
-                                <div class="closed-rack-inner">
-                                    {nodes.iter().map(|node| {
-                                        html! {
-                                            <CloudNodeIcon powersave={node.powersave} />
-                                        }
-                                    }).collect::<Html>()}
-                                </div>
-                            </div>
-                        }
+                let rack_nodes: Vec<_> = ctx.props().nodes.chunks(RACK_CAPACITY as usize).collect();
+                let cells = rack_nodes
+                    .iter()
+                    .zip(&ctx.props().rack_utilizations)
+                    .map(|(nodes, &(cpu_load, mem_load))| OverviewCell {
+                        cpu_load,
+                        mem_load,
+                        label: t!("hardware.num_nodes", count = nodes.len()),
                     })
                     .collect();
+                // show closed racks instead
+                let racks: Html = match self.overview(
+                    ctx,
+                    t!("hardware.all_racks"),
+                    t!("hardware.rack"),
+                    cells,
+                    &rack_nodes,
+                ) {
+                    Some(overview) => overview,
+                    None => rack_nodes
+                        .iter()
+                        .map(|nodes| {
+                            html! {
+                                <div class="closed-rack">
+                                    <div class="closed-rack-inner">
+                                        {nodes.iter().map(|node| {
+                                            html! {
+                                                <CloudNodeIcon powersave={node.powersave} />
+                                            }
+                                        }).collect::<Html>()}
+                                    </div>
+                                </div>
+                            }
+                        })
+                        .collect(),
+                };
 
                 html! {
-                    <div class="equipment">
+                    <div class={classes}>
                         {racks}
                         // show buy button if available
                         // (first office only has room for 10 racks)
-                        if ctx.props().nodes.len() < (10 * RACK_CAPACITY) as usize {
+                        if can_purchase && ctx.props().nodes.len() < (10 * RACK_CAPACITY) as usize {
                             <div class="buy">
-                                <button onclick={ctx.props().on_player_action.reform(|_| {
-                                    play_zip_click();
-                                    PlayerAction::AddUpgradedNode
-                                })}>
-                                    {"Buy node"}
+                                <button onclick={purchase(PlayerAction::AddUpgradedNode)} disabled={in_debt} title={in_debt_hint.clone()}>
+                                    {t!("hardware.buy_node")}
                                 </button>
                                 <span>
-                                    {UPGRADED_NODE_COST.to_string()}
+                                    {ctx.props().upgraded_node_cost.to_string()}
                                 </span>
+                                {purchase_preview(ctx.props().node_preview.as_ref())}
                             </div>
-                        } else if ctx.props().can_buy_datacenters {
+                        } else if can_purchase && ctx.props().can_buy_datacenters {
                             <div class="buy">
-                                <button onclick={ctx.props().on_player_action.reform(|_| {
-                                    play_zip_click();
-                                    PlayerAction::AddRack
-                                })}>
-                                    {"Buy rack"}
+                                <button onclick={purchase(PlayerAction::AddRack)} disabled={in_debt} title={in_debt_hint.clone()}>
+                                    {t!("hardware.buy_rack")}
                                 </button>
                                 <span>
-                                    {UPGRADED_RACK_COST.to_string()}
+                                    {ctx.props().upgraded_rack_cost.to_string()}
                                 </span>
+                                {purchase_preview(ctx.props().rack_preview.as_ref())}
                             </div>
                         }
                     </div>
@@ -361,17 +708,45 @@ impl Component for Equipment {
             (_, true) => {
                 // show closed datacenters instead,
                 // and each node is actually a rack
-                let datacenters: Html = ctx
+                let datacenter_nodes: Vec<_> = ctx
                     .props()
                     .nodes
                     .chunks(DATACENTER_CAPACITY as usize)
+                    .collect();
+                let cells = datacenter_nodes
+                    .iter()
+                    .zip(
+                        ctx.props()
+                            .rack_utilizations
+                            .chunks(DATACENTER_CAPACITY as usize),
+                    )
+                    .map(|(nodes, racks)| {
+                        // all racks have the same capacity
+                        let count = racks.len().max(1) as f32;
+                        OverviewCell {
+                            cpu_load: racks.iter().map(|(cpu, _)| cpu).sum::<f32>() / count,
+                            mem_load: racks.iter().map(|(_, mem)| mem).sum::<f32>() / count,
+                            label: t!("hardware.num_racks", count = nodes.len()),
+                        }
+                    })
+                    .collect();
+                let datacenters: Html = match self.overview(
+                    ctx,
+                    t!("hardware.all_datacenters"),
+                    t!("hardware.datacenter"),
+                    cells,
+                    &datacenter_nodes,
+                ) {
+                    Some(overview) => overview,
+                    None => datacenter_nodes
+                    .iter()
                     .map(|nodes| {
                         let num_racks = nodes.len() as u32;
                         let num_nodes = num_racks * RACK_CAPACITY;
                         let rack_count: Html = if num_racks == 1 {
-                            html! { <span>{num_nodes} {" nodes, 1 rack"}</span> }
+                            html! { <span>{t!("hardware.nodes_one_rack", nodes = num_nodes)}</span> }
                         } else {
-                            html! { <span>{num_nodes} {" nodes, "} {num_racks} {" racks"}</span> }
+                            html! { <span>{t!("hardware.nodes_racks", nodes = num_nodes, racks = num_racks)}</span> }
                         };
                         let leds = if ctx.props().powersave {
                             classes!["datacenter-led", "led-powersave"]
@@ -392,25 +767,44 @@ impl Component for Equipment {
                             </div>
                         }
                     })
-                    .collect();
+                    .collect(),
+                };
 
                 html! {
-                    <div class="equipment">
+                    <div class={classes}>
                         {datacenters}
+                        if can_purchase {
                         <div class="buy">
-                            <button onclick={ctx.props().on_player_action.reform(|_| {
-                                play_zip_click();
-                                PlayerAction::AddRack
-                            })}>
-                                {"Buy rack"}
+                            <button onclick={purchase(PlayerAction::AddRack)} disabled={in_debt} title={in_debt_hint.clone()}>
+                                {t!("hardware.buy_rack")}
                             </button>
                             <span>
-                                {UPGRADED_RACK_COST.to_string()}
+                                {ctx.props().upgraded_rack_cost.to_string()}
                             </span>
+                            {purchase_preview(ctx.props().rack_preview.as_ref())}
                         </div>
+                        }
                     </div>
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::upgrade_disabled;
+    use crate::Money;
+
+    #[test]
+    fn test_upgrade_disabled_when_unaffordable() {
+        let cost = Some(Money::dollars(50));
+        assert!(upgrade_disabled(cost, Money::dollars(10)));
+        assert!(!upgrade_disabled(cost, Money::dollars(50)));
+        assert!(!upgrade_disabled(cost, Money::dollars(100)));
+        // always while in debt
+        assert!(upgrade_disabled(Some(Money::zero()), Money::dollars(-1)));
+        // there is no button without an upgrade
+        assert!(!upgrade_disabled(None, Money::dollars(-1)));
+    }
+}
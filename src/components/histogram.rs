@@ -0,0 +1,66 @@
+//! Module for the bar chart of request processing times.
+
+use yew::prelude::*;
+
+use crate::{
+    central::histogram::{bucket_bound, Histogram, HISTOGRAM_BUCKETS},
+    t,
+};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ProcessingChartProps {
+    /// the processing times since the last upgrade
+    pub current: Histogram,
+    /// the processing times before the last upgrade, if any
+    #[prop_or_default]
+    pub previous: Option<Histogram>,
+}
+
+/// A tiny bar chart of how long requests take to process,
+/// from fastest (left) to slowest (right).
+///
+/// Requests which hit the cache are shown in a different color,
+/// and the shares before the last upgrade are marked for comparison.
+#[function_component]
+pub fn ProcessingChart(props: &ProcessingChartProps) -> Html {
+    let shares = props.current.shares();
+    let cache_hit_shares = props.current.cache_hit_shares();
+    let previous_shares = props.previous.as_ref().map(Histogram::shares);
+
+    let bars: Html = (0..HISTOGRAM_BUCKETS)
+        .map(|bucket| {
+            let share = shares[bucket];
+            let cached = cache_hit_shares[bucket];
+            let range = match bucket_bound(bucket) {
+                Some(bound) => format!("< {} ms", bound as f32 / 10.),
+                None => format!("\u{2265} {} ms", bucket_bound(bucket - 1).unwrap_or(0) / 10),
+            };
+            let mut title = format!("{range}: {:.0}%", share * 100.);
+            if cached > 0. {
+                title += &t!("histogram.cached", share = format!("{:.0}", cached * 100.));
+            }
+            if let Some(previous) = previous_shares {
+                title += &t!("histogram.before_upgrade", share = format!("{:.0}", previous[bucket] * 100.));
+            }
+            html! {
+                <div class="histogram-bar" {title}>
+                    <div class="histogram-fill" style={format!("height: {}%", share * 100.)}>
+                        <div class="histogram-cached" style={format!("height: {}%", cached / share.max(f32::EPSILON) * 100.)} />
+                    </div>
+                    if let Some(previous) = previous_shares {
+                        <div class="histogram-previous" style={format!("bottom: {}%", previous[bucket] * 100.)} />
+                    }
+                </div>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="histogram">
+            <span>{t!("histogram.title")}</span>
+            <div class="histogram-bars">
+                {bars}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,122 @@
+//! Module for the info tooltip,
+//! a small ⓘ icon next to a readout
+//! which explains a game concept to the player.
+//!
+//! The text comes from the [`Topic`] given,
+//! so it is shared with the rest of the translated text.
+//! The tooltip opens on hover, focus or click,
+//! and can be dismissed with Escape.
+//! Since a click also focuses the icon,
+//! clicking keeps the tooltip open rather than toggling it.
+
+use yew::prelude::*;
+
+use crate::i18n::Topic;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct InfoTipProps {
+    /// the concept to explain
+    pub topic: Topic,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InfoTipMsg {
+    /// the pointer entered the icon or the icon gained focus
+    Open,
+    /// the pointer left the icon or the icon lost focus
+    Close,
+    /// the icon was clicked or activated with the keyboard
+    Activate,
+    /// Escape was pressed while the icon had focus
+    Dismiss,
+}
+
+/// An ⓘ icon showing the explanation of a topic in a popover.
+#[derive(Debug, Default)]
+pub struct InfoTip {
+    /// whether the popover is shown
+    open: bool,
+}
+
+impl Component for InfoTip {
+    type Message = InfoTipMsg;
+    type Properties = InfoTipProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let open = next_open(msg);
+        let changed = self.open != open;
+        self.open = open;
+        changed
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let text = ctx.props().topic.text();
+        let link = ctx.link();
+        let onmouseenter = link.callback(|_: MouseEvent| InfoTipMsg::Open);
+        let onmouseleave = link.callback(|_: MouseEvent| InfoTipMsg::Close);
+        let onfocus = link.callback(|_: FocusEvent| InfoTipMsg::Open);
+        let onblur = link.callback(|_: FocusEvent| InfoTipMsg::Close);
+        let onclick = link.callback(|_: MouseEvent| InfoTipMsg::Activate);
+        let onkeydown = link.batch_callback(|e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => Some(InfoTipMsg::Dismiss),
+            "Enter" | " " => {
+                e.prevent_default();
+                Some(InfoTipMsg::Activate)
+            }
+            _ => None,
+        });
+
+        html! {
+            <span class="info-tip" tabindex="0" role="button"
+                aria-label={text} aria-expanded={self.open.to_string()}
+                {onmouseenter} {onmouseleave} {onfocus} {onblur} {onclick} {onkeydown}>
+                {"\u{24D8}"}
+                if self.open {
+                    <span class="info-tip-text" role="tooltip">{text}</span>
+                }
+            </span>
+        }
+    }
+}
+
+/// Whether the popover is shown after a message,
+/// regardless of whether it was shown before.
+fn next_open(msg: InfoTipMsg) -> bool {
+    match msg {
+        InfoTipMsg::Open | InfoTipMsg::Activate => true,
+        InfoTipMsg::Close | InfoTipMsg::Dismiss => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_open, InfoTipMsg};
+
+    #[test]
+    fn test_open_close() {
+        // hover or focus, then leave
+        assert!(next_open(InfoTipMsg::Open));
+        assert!(!next_open(InfoTipMsg::Close));
+        // clicking or pressing Enter opens
+        assert!(next_open(InfoTipMsg::Activate));
+        // Escape dismisses
+        assert!(!next_open(InfoTipMsg::Dismiss));
+    }
+
+    #[test]
+    fn test_click_after_focus_stays_open() {
+        let mut tip = super::InfoTip::default();
+        // a click first focuses the icon, then clicks it
+        for msg in [InfoTipMsg::Open, InfoTipMsg::Activate] {
+            tip.open = next_open(msg);
+        }
+        assert!(tip.open);
+        // until the pointer leaves
+        tip.open = next_open(InfoTipMsg::Close);
+        assert!(!tip.open);
+    }
+}
@@ -7,6 +7,11 @@ use yew::prelude::*;
 pub struct LoadBarProps {
     /// the current load level between 0 and 1
     pub load: f32,
+    /// the part of the load which is only reserved
+    /// (such as memory kept for the cache),
+    /// shown apart at the start of the bar
+    #[prop_or_default]
+    pub reserved: f32,
 }
 
 #[function_component]
@@ -14,6 +19,9 @@ pub fn LoadBar(props: &LoadBarProps) -> Html {
     html! {
         <div class="load-bar">
             <div class="load-bar-inner" />
+            if props.reserved > 0. {
+                <div class="load-bar-reserved" style={format!("width:{}%", (props.reserved * 100.) as i32)}/>
+            }
             <div class="load-bar-cover" style={format!("left:{}%", (props.load * 100.) as i32)}/>
         </div>
     }
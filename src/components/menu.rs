@@ -1,19 +1,241 @@
 use yew::prelude::*;
 
-use crate::audio::play_zip_click;
+use crate::{
+    central::{daily::DailyDate, persistence::SaveSummary},
+    components::{about::About, company::CompanyNameInput, modal::Modal},
+    i18n::Locale,
+    settings::{PanelLayout, Settings, DEFAULT_CONFIRM_SPEND_FRACTION},
+    t, AudioEvent, Difficulty,
+};
+
+/// A backup of the save which the player can restore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupEntry {
+    /// the backup slot, 0 being the newest
+    pub slot: usize,
+    /// when the backup was made, in milliseconds since the Unix epoch
+    pub backed_up_at: u64,
+    /// what is in the backup, if it can be read
+    pub summary: Option<SaveSummary>,
+}
+
+impl BackupEntry {
+    fn to_html(&self) -> Html {
+        let date = js_sys::Date::new(&(self.backed_up_at as f64).into());
+        let date: String = date
+            .to_locale_string("default", &js_sys::wasm_bindgen::JsValue::UNDEFINED)
+            .into();
+        let summary = match &self.summary {
+            Some(summary) => t!(
+                "menu.backup_summary",
+                company = summary.company_name,
+                difficulty = summary.difficulty,
+                hours = summary.playtime / 3_600,
+                minutes = format!("{:02}", summary.playtime / 60 % 60),
+                earned = summary.earned,
+                net_worth = summary.net_worth,
+            ),
+            None => t!("menu.backup_unreadable").to_string(),
+        };
+        html! {
+            <>
+                <b>{date}</b>
+                <br/>
+                {summary}
+            </>
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub struct MenuProps {
-    pub newgame_handler: Callback<()>,
+    /// called with the chosen difficulty and company name
+    /// when starting a new game
+    pub newgame_handler: Callback<(Difficulty, String)>,
     pub continuegame_handler: Callback<()>,
     pub has_save: bool,
     pub can_save: bool,
+    /// the locale in use
+    #[prop_or_default]
+    pub locale: Locale,
+    /// called when the player picks a different locale
+    #[prop_or_default]
+    pub on_locale: Callback<Locale>,
+    /// the backups of the save, newest first
+    #[prop_or_default]
+    pub backups: Vec<BackupEntry>,
+    /// called with the slot of the backup to restore
+    /// once the player confirms it
+    #[prop_or_default]
+    pub on_restore: Callback<usize>,
+    /// the preferences of the player
+    #[prop_or_default]
+    pub settings: Settings,
+    /// called when the player changes a preference
+    #[prop_or_default]
+    pub on_settings: Callback<Settings>,
+    /// callback for the sounds made by the menu
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+    /// the date of today's daily challenge,
+    /// if it can be played
+    #[prop_or_default]
+    pub daily_date: Option<DailyDate>,
+    /// called with the chosen company name
+    /// when starting or continuing the daily challenge
+    #[prop_or_default]
+    pub on_daily: Callback<String>,
 }
 
 #[function_component]
 pub fn Menu(props: &MenuProps) -> Html {
     let newgame_handler = props.newgame_handler.clone();
     let continuegame_handler = props.continuegame_handler.clone();
+    let on_audio = props.on_audio.clone();
+    let on_audio_continue = props.on_audio.clone();
+    let on_audio_daily = props.on_audio.clone();
+    let on_daily = props.on_daily.clone();
+    let difficulty = use_state(Difficulty::default);
+    let company_name = use_state(String::new);
+
+    let difficulty_options: Html = Difficulty::ALL
+        .into_iter()
+        .map(|option| {
+            let difficulty = difficulty.clone();
+            let classes = if *difficulty == option {
+                classes!["selected"]
+            } else {
+                classes![]
+            };
+            html! {
+                <button class={classes} onclick={move |_| difficulty.set(option)}>
+                    {option.to_string()}
+                </button>
+            }
+        })
+        .collect();
+    let locale_options: Html = Locale::ALL
+        .into_iter()
+        .map(|option| {
+            let on_locale = props.on_locale.clone();
+            let classes = if props.locale == option {
+                classes!["selected"]
+            } else {
+                classes![]
+            };
+            html! {
+                <button class={classes} onclick={move |_| on_locale.emit(option)}>
+                    {option.to_string()}
+                </button>
+            }
+        })
+        .collect();
+    // the backup to restore, once the player confirms it
+    let restoring = use_state(|| None::<usize>);
+    let backup_options: Html = props
+        .backups
+        .iter()
+        .map(|backup| {
+            let restoring = restoring.clone();
+            let slot = backup.slot;
+            html! {
+                <li>
+                    <button onclick={move |_| restoring.set(Some(slot))}>
+                        {backup.to_html()}
+                    </button>
+                </li>
+            }
+        })
+        .collect();
+    let restore_confirmation = match *restoring {
+        Some(slot) => {
+            let on_restore = props.on_restore.clone();
+            let on_confirm = {
+                let restoring = restoring.clone();
+                move |_| {
+                    restoring.set(None);
+                    on_restore.emit(slot);
+                }
+            };
+            let on_cancel = {
+                let restoring = restoring.clone();
+                move |_| restoring.set(None)
+            };
+            html! {
+                <Modal title={t!("menu.restore_title")}>
+                    <p>{t!("menu.restore_warning")}</p>
+                    <div class="modal-buttons">
+                        <button onclick={on_confirm}>{t!("menu.restore")}</button>
+                        <button onclick={on_cancel}>{t!("menu.cancel")}</button>
+                    </div>
+                </Modal>
+            }
+        }
+        None => html! {},
+    };
+    let on_confirm_spend_toggle = {
+        let settings = props.settings;
+        let on_settings = props.on_settings.clone();
+        move |_| {
+            let mut settings = settings;
+            settings.confirm_spend_fraction = match settings.confirm_spend_fraction {
+                Some(_) => None,
+                None => Some(DEFAULT_CONFIRM_SPEND_FRACTION),
+            };
+            on_settings.emit(settings);
+        }
+    };
+    let on_compact_projects_toggle = {
+        let settings = props.settings;
+        let on_settings = props.on_settings.clone();
+        move |_| {
+            on_settings.emit(Settings {
+                compact_projects: !settings.compact_projects,
+                ..settings
+            })
+        }
+    };
+    let layout_options: Html = PanelLayout::ALL
+        .into_iter()
+        .map(|option| {
+            let settings = props.settings;
+            let on_settings = props.on_settings.clone();
+            let classes = if settings.layout == option {
+                classes!["selected"]
+            } else {
+                classes![]
+            };
+            let onclick = move |_| {
+                on_settings.emit(Settings {
+                    layout: option,
+                    ..settings
+                })
+            };
+            html! {
+                <button class={classes} {onclick}>
+                    {option.name()}
+                </button>
+            }
+        })
+        .collect();
+    let about_open = use_state(|| false);
+    let about = if *about_open {
+        let on_close = {
+            let about_open = about_open.clone();
+            move |_| about_open.set(false)
+        };
+        html! { <About {on_close} /> }
+    } else {
+        html! {}
+    };
+    let on_about = move |e: MouseEvent| {
+        e.prevent_default();
+        about_open.set(true);
+    };
+    let chosen_difficulty = *difficulty;
+    let chosen_name = (*company_name).clone();
+    let daily_name = chosen_name.clone();
+    let on_name_input = Callback::from(move |name| company_name.set(name));
     html! {
         <>
         <div class="main-menu-back" />
@@ -22,23 +244,74 @@ pub fn Menu(props: &MenuProps) -> Html {
             <div class="main-menu-prompt">
                 if props.has_save {
                     <button onclick={move |_| {
-                        play_zip_click();
+                        on_audio_continue.emit(AudioEvent::UiClick);
                         continuegame_handler.emit(())
-                    }}>{"Continue Game"}</button>
+                    }}>{t!("menu.continue_game")}</button>
                 } else if !props.can_save {
                     <div class="menu-warn">
-                        {"WARNING: Your browser is not allowing you to save your game. "}
+                        {t!("menu.no_storage")}
                         <br/>
-                        {"Disable shields or enable local storage to save your progress."}
+                        {t!("menu.no_storage_hint")}
                     </div>
                 }
+                <CompanyNameInput value={chosen_name.clone()} on_input={on_name_input} />
                 <button onclick={move |_| {
-                    play_zip_click();
-                    newgame_handler.emit(())
-                }}>{"New Game"}</button>
+                    on_audio.emit(AudioEvent::UiClick);
+                    newgame_handler.emit((chosen_difficulty, chosen_name.clone()))
+                }}>{t!("menu.new_game")}</button>
+                <div class="difficulty">
+                    {difficulty_options}
+                </div>
+                if let Some(date) = props.daily_date {
+                    <button class="daily" onclick={move |_| {
+                        on_audio_daily.emit(AudioEvent::UiClick);
+                        on_daily.emit(daily_name.clone())
+                    }}>{t!("menu.daily_challenge", date = date)}</button>
+                }
             </div>
-            <footer><a href="https://github.com/Enet4/10xCloudChampion">{"On GitHub"}</a></footer>
+            <details class="advanced">
+                <summary>{t!("menu.advanced")}</summary>
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={props.settings.confirm_spend_fraction.is_some()}
+                        onchange={on_confirm_spend_toggle} />
+                    {t!(
+                        "menu.confirm_spend",
+                        percent = (props.settings.confirm_spend_fraction.unwrap_or(DEFAULT_CONFIRM_SPEND_FRACTION) * 100.).round(),
+                    )}
+                </label>
+                <div class="layout">
+                    {t!("menu.layout")}{" "}
+                    {layout_options}
+                </div>
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={props.settings.compact_projects}
+                        onchange={on_compact_projects_toggle} />
+                    {t!("menu.compact_projects")}
+                </label>
+                <h3>{t!("menu.restore_backup")}</h3>
+                if props.backups.is_empty() {
+                    <p>{t!("menu.no_backups")}</p>
+                } else {
+                    <ul class="backups">{backup_options}</ul>
+                }
+            </details>
+            if Locale::ALL.len() > 1 {
+                <div class="locale" title={t!("menu.language")}>
+                    {locale_options}
+                </div>
+            }
+            <footer>
+                <a href="#" onclick={on_about}>{t!("menu.about")}</a>
+                {" · "}
+                <a href="https://github.com/Enet4/10xCloudChampion">{t!("menu.github")}</a>
+            </footer>
         </div>
+        {restore_confirmation}
+        {about}
         </>
     }
 }
@@ -1,10 +1,25 @@
+pub mod about;
 pub mod business;
 pub mod card;
+pub mod company;
+#[cfg(feature = "debug-tools")]
+pub mod debug;
+pub mod exchange;
 pub mod hardware;
+pub mod histogram;
+pub mod info_tip;
 pub mod load_bar;
 pub mod menu;
 pub mod modal;
+pub mod notifications;
+pub mod overview;
 pub mod panel;
 pub mod pop;
+pub mod report;
+pub mod score;
 pub mod services;
+pub mod staff;
+pub mod stats;
+pub mod tabs;
+pub mod toast;
 pub mod total_stats;
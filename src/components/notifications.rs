@@ -0,0 +1,26 @@
+//! Module for the notification log component.
+use yew::prelude::*;
+
+use crate::central::notification::Notification;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct NotificationsProps {
+    /// the notifications to show, newest first
+    pub entries: Vec<Notification>,
+}
+
+/// A short list of the latest notifications.
+#[function_component]
+pub fn Notifications(props: &NotificationsProps) -> Html {
+    if props.entries.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <ul class="notifications">
+            {props.entries.iter().map(|entry| html! {
+                <li>{entry.message.clone()}</li>
+            }).collect::<Html>()}
+        </ul>
+    }
+}
@@ -0,0 +1,79 @@
+//! Module for the overview grid of racks and data centers,
+//! used in place of the equipment icons once there are too many of them.
+
+use yew::prelude::*;
+
+use crate::t;
+
+/// A single cell in the overview grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverviewCell {
+    /// the CPU load between 0 and 1
+    pub cpu_load: f32,
+    /// the memory load between 0 and 1
+    pub mem_load: f32,
+    /// what the cell holds, e.g. "4 nodes"
+    pub label: String,
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct OverviewGridProps {
+    pub cells: Vec<OverviewCell>,
+    /// callback for when a cell is clicked, with its index
+    pub on_select: Callback<usize>,
+}
+
+/// A grid of cells colored by utilization,
+/// from green (idle) to red (saturated).
+#[function_component]
+pub fn OverviewGrid(props: &OverviewGridProps) -> Html {
+    let cells: Html = props
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| {
+            let load = cell.cpu_load.max(cell.mem_load).clamp(0., 1.);
+            let style = format!("background-color: hsl({}, 60%, 70%)", 120. * (1. - load));
+            let title = t!(
+                "overview.load",
+                cpu = format!("{:.0}", cell.cpu_load * 100.),
+                memory = format!("{:.0}", cell.mem_load * 100.),
+            );
+            let onclick = props.on_select.reform(move |_| index);
+            html! {
+                <button class="overview-cell" {style} {title} {onclick}>
+                    {&cell.label}
+                </button>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="overview-grid">
+            {cells}
+        </div>
+    }
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct BreadcrumbProps {
+    /// the name of the overview, e.g. "All racks"
+    pub root: AttrValue,
+    /// the name of the cell being shown
+    pub current: AttrValue,
+    /// callback for returning to the overview
+    pub on_back: Callback<()>,
+}
+
+/// A trail back to the overview grid from a detail view.
+#[function_component]
+pub fn Breadcrumb(props: &BreadcrumbProps) -> Html {
+    let onclick = props.on_back.reform(|_| ());
+    html! {
+        <div class="breadcrumb">
+            <button class="link" {onclick}>{props.root.clone()}</button>
+            {" \u{203a} "}
+            <span>{props.current.clone()}</span>
+        </div>
+    }
+}
@@ -1,6 +1,5 @@
 use yew::prelude::*;
 
-
 #[derive(Debug, PartialEq, Properties)]
 pub struct PanelProps {
     pub children: Html,
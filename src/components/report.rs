@@ -0,0 +1,100 @@
+//! Module for the period report component,
+//! a digest of how the business went in the last period.
+use yew::prelude::*;
+
+use crate::{central::report::PeriodReport, display::Separating, t, AudioEvent, ServiceKind};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ReportCardProps {
+    /// the latest report
+    pub report: PeriodReport,
+    /// the report of the period before, to compare against
+    #[prop_or_default]
+    pub previous: Option<PeriodReport>,
+    /// callback for when the player dismisses the report
+    pub on_dismiss: Callback<()>,
+    /// callback for the sounds made by the report card
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+}
+
+/// Arrow indicating whether a metric went up or down,
+/// colored by whether that is good for the player.
+fn trend(current: f64, previous: Option<f64>, higher_is_better: bool) -> Html {
+    let Some(previous) = previous else {
+        return html! {};
+    };
+    if current == previous {
+        return html! {};
+    }
+    let up = current > previous;
+    let class = if up == higher_is_better {
+        "trend better"
+    } else {
+        "trend worse"
+    };
+    let arrow = if up { "\u{25B2}" } else { "\u{25BC}" };
+    html! { <span {class}>{arrow}</span> }
+}
+
+/// The period report card.
+#[function_component]
+pub fn ReportCard(props: &ReportCardProps) -> Html {
+    let report = &props.report;
+    let previous = props.previous.as_ref();
+
+    let ops: Html = ServiceKind::ALL
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| report.ops[*i].0 > 0 || previous.is_some_and(|p| p.ops[*i].0 > 0))
+        .map(|(i, kind)| {
+            let name = kind.tier().name.to_lowercase();
+            html! {
+                <li>
+                    {t!("report.ops", service = name, ops = report.ops[i])}
+                    {trend(report.ops[i].0 as f64, previous.map(|p| p.ops[i].0 as f64), true)}
+                </li>
+            }
+        })
+        .collect();
+
+    let on_dismiss = {
+        let cb = props.on_dismiss.clone();
+        let on_audio = props.on_audio.clone();
+        move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            cb.emit(())
+        }
+    };
+
+    html! {
+        <div class="report-card">
+            <div>
+                <b>{t!("report.title")}</b>
+                <button class="dismiss" onclick={on_dismiss}>{"\u{00d7}"}</button>
+            </div>
+            <ul>
+                <li>
+                    {t!("report.revenue")} {report.revenue.for_display().to_string()}
+                    {trend(report.revenue.to_millicents() as f64, previous.map(|p| p.revenue.to_millicents() as f64), true)}
+                </li>
+                {ops}
+                <li>
+                    {t!("report.dropped")} {Separating(report.requests_dropped as i64).to_string()}
+                    {trend(report.requests_dropped as f64, previous.map(|p| p.requests_dropped as f64), false)}
+                </li>
+                <li>
+                    {t!("report.energy_cost")} {report.energy_cost.for_display().to_string()}
+                    {trend(report.energy_cost.to_millicents() as f64, previous.map(|p| p.energy_cost.to_millicents() as f64), false)}
+                </li>
+                <li>
+                    {t!("report.visibility")} {format!("{:+.2}%", report.demand_change / 100.)}
+                    {trend(report.demand_change, previous.map(|p| p.demand_change), true)}
+                </li>
+                if report.cards_bought > 0 {
+                    <li>{t!("report.projects")} {report.cards_bought}</li>
+                }
+            </ul>
+        </div>
+    }
+}
@@ -0,0 +1,61 @@
+//! Module for the score component,
+//! which shows the score of the game and what it is made of.
+use yew::prelude::*;
+
+use crate::{
+    central::score::{ScoreBreakdown, SCORE_VERSION},
+    display::Separating,
+    t, ServiceKind,
+};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ScoreBoardProps {
+    /// the components of the score
+    pub score: ScoreBreakdown,
+}
+
+/// The score component.
+#[function_component]
+pub fn ScoreBoard(props: &ScoreBoardProps) -> Html {
+    let score = &props.score;
+    let ops: Html = ServiceKind::ALL
+        .iter()
+        .zip(score.ops)
+        .filter(|(_, points)| *points > 0)
+        .map(|(kind, points)| {
+            html! {
+                <tr>
+                    <td>{t!("score.ops", service = kind)}</td>
+                    <td>{format!("+{}", Separating(points as i64))}</td>
+                </tr>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="score">
+            <p>
+                {t!(
+                    "score.total",
+                    version = SCORE_VERSION,
+                    score = Separating(score.total() as i64)
+                )}
+            </p>
+            <table class="stats-table">
+                {ops}
+                <tr>
+                    <td>{t!("score.earned")}</td>
+                    <td>{format!("+{}", Separating(score.earned as i64))}</td>
+                </tr>
+                <tr>
+                    <td>{t!("score.net_worth")}</td>
+                    <td>{format!("+{}", Separating(score.net_worth as i64))}</td>
+                </tr>
+                <tr>
+                    <td>{t!("score.time")}</td>
+                    <td>{format!("-{}", Separating(score.time_penalty as i64))}</td>
+                </tr>
+            </table>
+        </div>
+    }
+}
@@ -4,13 +4,14 @@
 use core::fmt;
 use std::collections::VecDeque;
 
-use gloo_timers::callback::Timeout;
+use gloo_timers::callback::{Interval, Timeout};
 use yew::prelude::*;
 
 use crate::{
-    audio::{play_op_click, play_zip_click},
-    components::pop::Pop,
-    Money, ServiceKind,
+    central::{engine::GameEngine, metrics::PlayerOps, state::ServiceState},
+    components::{info_tip::InfoTip, pop::Pop},
+    i18n::Topic,
+    t, AudioEvent, Money, Ops, PlayerAction, ServiceKind, WorldState,
 };
 
 #[derive(Debug, PartialEq, Properties)]
@@ -21,26 +22,173 @@ pub struct CloudServiceProps {
     pub price: Money,
     #[prop_or_default]
     pub new: bool,
+    /// whether the service is open to customers
+    pub state: ServiceState,
+    /// whether customer traffic is paused for maintenance
     #[prop_or_default]
-    pub private: bool,
+    pub maintenance: bool,
+    /// callback for turning maintenance on or off
+    #[prop_or_default]
+    pub on_maintenance: Callback<bool>,
+    /// how full the op button combo is, between 0 and 1
+    #[prop_or_default]
+    pub combo: f32,
+    /// the multiplier on the ops per click given by the combo
+    #[prop_or(1.)]
+    pub combo_multiplier: f32,
+    /// the latest outcome of the ops requested by the player
+    #[prop_or_default]
+    pub feedback: OpFeedback,
+    /// callback for the sounds made by this service
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+    /// whether to lay out the service for a small touch screen,
+    /// with larger buttons
+    #[prop_or_default]
+    pub compact: bool,
+    /// whether requests wait so long in the queues
+    /// that customers are leaving the service
+    #[prop_or_default]
+    pub pressured: bool,
+    /// the projected demand at the current price and the next ones,
+    /// if the player can see demand estimates
+    #[prop_or_default]
+    pub demand_preview: Option<DemandPreview>,
+}
+
+impl CloudServiceProps {
+    /// The properties of a cloud service as it stands in the game,
+    /// turning what the player does with it into player actions.
+    ///
+    /// `customers` is the number of customers of the service
+    /// (see [`WorldState::customers_per_service`]),
+    /// counted once by the caller for all services.
+    /// The op feedback and sounds are left for the caller to fill in.
+    pub fn for_service(
+        kind: ServiceKind,
+        state: &WorldState,
+        engine: &GameEngine,
+        customers: usize,
+        on_player_action: &Callback<PlayerAction>,
+    ) -> Self {
+        let service = state.service_by_kind(kind);
+        let amount = state.ops_per_click;
+        let combo = engine.combo(kind);
+        let demand_preview = (state.can_see_demand && service.state.is_published()).then(|| {
+            let projected = |price| {
+                engine.projected_requests_per_customer(state, kind, price) * customers as f64
+            };
+            DemandPreview {
+                current: projected(service.price),
+                lower: projected(lower_price(service.price)),
+                raise: projected(raise_price(service.price)),
+            }
+        });
+        Self {
+            kind,
+            on_click: on_player_action.reform(move |_| PlayerAction::OpClick { kind, amount }),
+            on_price_change: on_player_action
+                .reform(move |new_price| PlayerAction::ChangePrice { kind, new_price }),
+            price: service.price,
+            new: service.total == Ops(0),
+            state: service.state,
+            maintenance: service.in_maintenance(),
+            on_maintenance: on_player_action
+                .reform(move |on| PlayerAction::SetMaintenance { kind, on }),
+            combo: combo.level(),
+            combo_multiplier: combo.multiplier(),
+            feedback: OpFeedback::default(),
+            on_audio: Callback::default(),
+            compact: false,
+            pressured: engine.is_queue_pressured(kind),
+            demand_preview,
+        }
+    }
+}
+
+/// The requests per second projected for a service
+/// at its current price and at the prices which the buttons lead to
+/// (see [`GameEngine::projected_requests_per_customer`]).
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct DemandPreview {
+    pub current: f64,
+    pub lower: f64,
+    pub raise: f64,
+}
+
+impl DemandPreview {
+    /// The projected requests per second after the given price change.
+    fn after(&self, change: PriceChange) -> f64 {
+        match change {
+            PriceChange::Lower => self.lower,
+            PriceChange::Raise => self.raise,
+        }
+    }
+}
+
+/// A press of the price buttons.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PriceChange {
+    Lower,
+    Raise,
+}
+
+impl PriceChange {
+    /// The price after the change.
+    fn apply(self, price: Money) -> Money {
+        match self {
+            PriceChange::Lower => lower_price(price),
+            PriceChange::Raise => raise_price(price),
+        }
+    }
+}
+
+/// Format a number of requests per second for the player.
+fn requests_per_second(rate: f64) -> String {
+    if rate < 10. {
+        format!("{rate:.1}")
+    } else {
+        format!("{rate:.0}")
+    }
+}
+
+/// The outcome of the player's ops in a game update,
+/// as reported by the engine.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct OpFeedback {
+    /// a number which changes with every new outcome
+    pub serial: u32,
+    pub ops: PlayerOps,
 }
 
 /// the information to be shown in a cloud service op pop-up
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct CountPop {
     count: i32,
+    /// whether the ops were dropped instead of fulfilled
+    dropped: bool,
 }
 
 impl fmt::Display for CountPop {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:+}", self.count)
+        if self.dropped {
+            write!(f, "{} dropped!", self.count)
+        } else {
+            write!(f, "{:+}", self.count)
+        }
     }
 }
 
 impl ToHtml for CountPop {
     fn to_html(&self) -> Html {
-        html! {
-            {self}
+        if self.dropped {
+            html! {
+                <span class="dropped">{self.to_string()}</span>
+            }
+        } else {
+            html! {
+                {self}
+            }
         }
     }
 }
@@ -51,13 +199,58 @@ pub enum CloudServiceMessage {
     New(CountPop),
     /// make the oldest one disappear
     Disappear,
+    /// stop shaking the op button
+    Calm,
+    /// the op button was pressed down
+    HoldStart,
+    /// the op button was held long enough to start repeating
+    HoldRepeat,
+    /// the op button was released
+    HoldEnd,
+    /// repeat the op click while the button is held
+    RepeatClick,
+    /// the pointer went over a price button,
+    /// or left it (`None`)
+    PreviewPrice(Option<PriceChange>),
+    /// a price button was pressed
+    ChangePrice(PriceChange),
+    /// the player took back the latest price changes
+    UndoPrice,
+    /// the price changes can no longer be taken back
+    UndoExpired,
 }
 
+/// the time in milliseconds for which the op button is held
+/// before it starts repeating
+pub const HOLD_DELAY_MS: u32 = 400;
+
+/// the time in milliseconds between repeated op clicks
+/// while the op button is held,
+/// which stays well below the clicking rate deemed automated
+pub const HOLD_REPEAT_MS: u32 = 125;
+
+/// the time in milliseconds for which a price change can be taken back
+pub const UNDO_PRICE_MS: u32 = 5_000;
+
 /// The cloud service component.
 #[derive(Debug)]
 pub struct CloudService {
     k: u32,
     popups: VecDeque<(u32, CountPop)>,
+    /// whether the player's ops have just been dropped
+    /// (and the op button is shaking)
+    dropping: bool,
+    /// whether the player's latest ops were dropped
+    saturated: bool,
+    /// waits for the op button to be held long enough to repeat
+    hold_delay: Option<Timeout>,
+    /// repeats the op click while the op button is held
+    hold_repeat: Option<Interval>,
+    /// the price button under the pointer
+    previewed: Option<PriceChange>,
+    /// the price before the latest changes,
+    /// and the timer until they can no longer be taken back
+    undo: Option<(Money, Timeout)>,
 }
 
 impl Component for CloudService {
@@ -68,16 +261,46 @@ impl Component for CloudService {
         Self {
             k: 0,
             popups: VecDeque::new(),
+            dropping: false,
+            saturated: false,
+            hold_delay: None,
+            hold_repeat: None,
+            previewed: None,
+            undo: None,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let feedback = ctx.props().feedback;
+        if feedback.serial != old_props.feedback.serial {
+            let link = ctx.link();
+            if feedback.ops.fulfilled > 0 {
+                link.send_message(CloudServiceMessage::New(CountPop {
+                    count: feedback.ops.fulfilled as i32,
+                    dropped: false,
+                }));
+            }
+            self.saturated = feedback.ops.dropped > 0;
+            if feedback.ops.dropped > 0 {
+                link.send_message(CloudServiceMessage::New(CountPop {
+                    count: feedback.ops.dropped as i32,
+                    dropped: true,
+                }));
+                self.dropping = true;
+                let link = link.clone();
+                Timeout::new(500, move || link.send_message(CloudServiceMessage::Calm)).forget();
+            }
+        }
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             CloudServiceMessage::New(c) => {
                 self.popups.push_back((self.k, c));
                 self.k = self.k.wrapping_add(1);
                 // create a timeout to emit a message to make the pop-up disappear
-                let link = _ctx.link().clone();
+                let link = ctx.link().clone();
                 let timeout = Timeout::new(800, move || {
                     link.send_message(CloudServiceMessage::Disappear);
                 });
@@ -86,6 +309,59 @@ impl Component for CloudService {
             CloudServiceMessage::Disappear => {
                 self.popups.pop_front();
             }
+            CloudServiceMessage::Calm => {
+                self.dropping = false;
+            }
+            CloudServiceMessage::HoldStart => {
+                let link = ctx.link().clone();
+                self.hold_delay = Some(Timeout::new(HOLD_DELAY_MS, move || {
+                    link.send_message(CloudServiceMessage::HoldRepeat)
+                }));
+                return false;
+            }
+            CloudServiceMessage::HoldRepeat => {
+                self.hold_delay = None;
+                let link = ctx.link().clone();
+                self.hold_repeat = Some(Interval::new(HOLD_REPEAT_MS, move || {
+                    link.send_message(CloudServiceMessage::RepeatClick)
+                }));
+                return false;
+            }
+            CloudServiceMessage::HoldEnd => {
+                // dropping the timers cancels them
+                self.hold_delay = None;
+                self.hold_repeat = None;
+                return false;
+            }
+            CloudServiceMessage::RepeatClick => {
+                ctx.props().on_audio.emit(AudioEvent::OpClick);
+                ctx.props().on_click.emit(());
+                return false;
+            }
+            CloudServiceMessage::PreviewPrice(change) => {
+                self.previewed = change;
+            }
+            CloudServiceMessage::ChangePrice(change) => {
+                let price = ctx.props().price;
+                ctx.props().on_audio.emit(AudioEvent::UiClick);
+                ctx.props().on_price_change.emit(change.apply(price));
+                // a streak of changes is taken back all at once
+                let previous = self.undo.take().map_or(price, |(previous, _)| previous);
+                let link = ctx.link().clone();
+                let timeout = Timeout::new(UNDO_PRICE_MS, move || {
+                    link.send_message(CloudServiceMessage::UndoExpired)
+                });
+                self.undo = Some((previous, timeout));
+            }
+            CloudServiceMessage::UndoPrice => {
+                if let Some((previous, _)) = self.undo.take() {
+                    ctx.props().on_audio.emit(AudioEvent::UiClick);
+                    ctx.props().on_price_change.emit(previous);
+                }
+            }
+            CloudServiceMessage::UndoExpired => {
+                self.undo = None;
+            }
         }
         true
     }
@@ -93,71 +369,137 @@ impl Component for CloudService {
     fn view(&self, ctx: &Context<Self>) -> Html {
         let name = ctx.props().kind.to_string();
 
-        let color = match ctx.props().kind {
-            ServiceKind::Base => "#ccc",
-            ServiceKind::Super => "#bbf",
-            ServiceKind::Epic => "#efc",
-            ServiceKind::Awesome => "#ecf",
-        };
+        let color = ctx.props().kind.tier().color;
 
         let on_click = ctx.props().on_click.clone();
 
         let onclick = {
-            let onclick = Callback::from(move |_e: MouseEvent| {
-                play_op_click();
+            let on_audio = ctx.props().on_audio.clone();
+            Callback::from(move |_e: MouseEvent| {
+                on_audio.emit(AudioEvent::OpClick);
                 on_click.emit(());
-            });
-            onclick
+            })
         };
 
-        let on_lower_price = {
-            let on_price_change = ctx.props().on_price_change.clone();
-            let price = ctx.props().price;
-            let on_lower_price = Callback::from(move |_e: MouseEvent| {
-                play_zip_click();
-                let new_price = lower_price(price);
-                on_price_change.emit(new_price);
-            });
-            on_lower_price
+        // holding the op button down repeats the op,
+        // which is the only way to click steadily on a touch screen
+        let onpointerdown = ctx.link().batch_callback(|e: PointerEvent| {
+            (e.is_primary() && e.button() == 0).then_some(CloudServiceMessage::HoldStart)
+        });
+        let on_release = ctx
+            .link()
+            .callback(|_: PointerEvent| CloudServiceMessage::HoldEnd);
+        // a long press on a touch screen would open the context menu
+        // and interrupt the hold, but only on the op button
+        let oncontextmenu = Callback::from(|e: MouseEvent| e.prevent_default());
+
+        let link = ctx.link();
+        let price_button = |change: PriceChange, label: &'static str| {
+            html! {
+                <button onclick={link.callback(move |_| CloudServiceMessage::ChangePrice(change))}
+                    onpointerenter={link.callback(move |_| CloudServiceMessage::PreviewPrice(Some(change)))}
+                    onpointerleave={link.callback(|_| CloudServiceMessage::PreviewPrice(None))}>
+                    {label}
+                </button>
+            }
         };
 
-        let on_raise_price = {
-            let on_price_change = ctx.props().on_price_change.clone();
-            let price = ctx.props().price;
-            let on_raise_price = Callback::from(move |_e: MouseEvent| {
-                play_zip_click();
-                let new_price = raise_price(price);
-                on_price_change.emit(new_price);
-            });
-            on_raise_price
+        let demand_preview = ctx.props().demand_preview.map(|preview| {
+            let current = requests_per_second(preview.current);
+            match self.previewed {
+                Some(change) => t!(
+                    "services.demand_preview",
+                    current = current,
+                    new = requests_per_second(preview.after(change))
+                ),
+                None => t!("services.demand", current = current),
+            }
+        });
+        let undo_price = self.undo.as_ref().map(|(previous, _)| {
+            html! {
+                <button class="undo-price"
+                    onclick={link.callback(|_| CloudServiceMessage::UndoPrice)}>
+                    {t!("services.undo_price", price = previous)}
+                </button>
+            }
+        });
+
+        let on_toggle_maintenance = {
+            let on_maintenance = ctx.props().on_maintenance.clone();
+            let on_audio = ctx.props().on_audio.clone();
+            let maintenance = ctx.props().maintenance;
+            Callback::from(move |_e: MouseEvent| {
+                on_audio.emit(AudioEvent::UiClick);
+                on_maintenance.emit(!maintenance);
+            })
         };
 
         let style = format!("background-color: {color}");
 
-        let button_classes: Classes = if ctx.props().new {
+        let mut button_classes: Classes = if ctx.props().new {
             classes!("op", "new")
         } else {
             classes!("op")
         };
+        if self.dropping {
+            button_classes.push("shake");
+        }
+        let hint = self.saturated.then_some(t!("services.at_capacity"));
+
+        let service_classes = if ctx.props().compact {
+            classes!("service", "compact")
+        } else {
+            classes!("service")
+        };
+        let (header_class, header_hint) = if ctx.props().pressured {
+            (Some("pressured"), Some(t!("services.queue_pressure")))
+        } else {
+            (None, None)
+        };
 
         html! {
-            <div class="service" style={style}>
-                <h4>{ name }</h4>
-                <button class={button_classes} onclick={onclick}>{"Op"}</button>
+            <div class={service_classes} style={style}>
+                <h4 class={header_class} title={header_hint}>{ name }</h4>
+                if ctx.props().maintenance {
+                    <span class="maintenance">{t!("services.maintenance")}</span>
+                }
+                <button class={button_classes} onclick={onclick} title={hint}
+                    {onpointerdown}
+                    onpointerup={on_release.clone()}
+                    onpointerleave={on_release.clone()}
+                    onpointercancel={on_release}
+                    {oncontextmenu}>
+                    {t!("services.op")}
+                </button>
+                if ctx.props().combo > 0. {
+                    <div class="combo" title={t!("services.combo_hint")}>
+                        <div class="combo-fill" style={format!("width: {}%", ctx.props().combo * 100.)} />
+                        <span>{format!("\u{d7}{:.1}", ctx.props().combo_multiplier)}</span>
+                    </div>
+                }
                 // price and buttons to lower/raise
-                if ctx.props().private {
+                if !ctx.props().state.is_published() {
                     <div class="private">
-                        <span>{"TESTING"}</span>
+                        <span>{t!("services.testing")}</span>
                     </div>
                 } else {
                     <div class="price-container">
                         <div class="price">
-                            <span>{"Price: "}</span><span class="money">{ctx.props().price.to_string()}</span>
+                            <span>{t!("services.price")}</span><span class="money">{ctx.props().price.to_string()}</span>
+                            {" "}<InfoTip topic={Topic::Pricing}/>
                         </div>
+                        if let Some(demand_preview) = demand_preview {
+                            <div class="demand-preview">{demand_preview}</div>
+                        }
                         <div class="change">
-                            <button onclick={on_lower_price}>{"lower"}</button>
-                            <button onclick={on_raise_price}>{"raise"}</button>
+                            {price_button(PriceChange::Lower, t!("services.lower"))}
+                            {price_button(PriceChange::Raise, t!("services.raise"))}
                         </div>
+                        {undo_price}
+                        <button onclick={on_toggle_maintenance}
+                            title={t!("services.maintenance_hint")}>
+                            { if ctx.props().maintenance { t!("services.end_maintenance") } else { t!("services.maintenance") } }
+                        </button>
                     </div>
                 }
                 // pop-ups
@@ -173,64 +515,146 @@ impl Component for CloudService {
     }
 }
 
-/// based on current price, decide how to lower it
+/// the lowest price which can be set with the price buttons
+const MIN_PRICE: Money = Money::millicents(1);
+
+/// the highest price which can be set with the price buttons
+const MAX_PRICE: Money = Money::dollars(25);
+
+/// The ladder of prices which the price buttons go through,
+/// as (threshold, step) pairs in increasing order:
+/// prices above the previous threshold and up to this one
+/// go up and down by the given step.
+///
+/// Every threshold is a multiple of the steps on both sides of it,
+/// so that prices on the ladder's grid stay on it.
+static PRICE_LADDER: [(Money, Money); 10] = [
+    (Money::millicents(20), Money::millicents(1)),
+    (Money::millicents(100), Money::millicents(5)),
+    (Money::millicents(200), Money::millicents(10)),
+    (Money::cents(1), Money::millicents(50)),
+    (Money::cents(2), Money::dec_cents(1)),
+    (Money::cents(10), Money::dec_cents(5)),
+    (Money::cents(20), Money::cents(1)),
+    (Money::dollars(1), Money::cents(5)),
+    (Money::dollars(2), Money::cents(10)),
+    (MAX_PRICE, Money::cents(50)),
+];
+
+/// based on current price, decide how to lower it:
+/// to the next price on the ladder below it
 fn lower_price(price: Money) -> Money {
-    if price <= Money::millicents(1) {
-        Money::millicents(1)
-    } else if price <= Money::millicents(20) {
-        price - Money::millicents(1)
-    } else if price <= Money::millicents(100) {
-        price - Money::millicents(5)
-    } else if price <= Money::millicents(200) {
-        price - Money::millicents(10)
-    } else if price <= Money::cents(1) {
-        price - Money::millicents(50)
-    } else if price <= Money::cents(2) {
-        price - Money::millicents(100)
-    } else if price <= Money::cents(10) {
-        price - Money::millicents(500)
-    } else if price <= Money::cents(20) {
-        price - Money::cents(1)
-    } else if price <= Money::dollars(1) {
-        price - Money::cents(5)
-    } else if price <= Money::dollars(2) {
-        price - Money::cents(10)
-    } else {
-        price - Money::cents(50)
+    if price <= MIN_PRICE {
+        return MIN_PRICE;
     }
+    let step = PRICE_LADDER
+        .iter()
+        .find(|&&(threshold, _)| price <= threshold)
+        .map_or(PRICE_LADDER[PRICE_LADDER.len() - 1].1, |&(_, step)| step)
+        .to_millicents();
+    Money::millicents((price.to_millicents() - 1) / step * step)
 }
 
-/// based on current price, decide how to raise it
+/// based on current price, decide how to raise it:
+/// to the next price on the ladder above it
 fn raise_price(price: Money) -> Money {
-    if price >= Money::dollars(25) {
-        Money::dollars(25)
-    } else if price >= Money::dollars(2) {
-        price + Money::cents(50)
-    } else if price >= Money::dollars(1) {
-        price + Money::cents(10)
-    } else if price >= Money::cents(20) {
-        price + Money::cents(5)
-    } else if price >= Money::cents(10) {
-        price + Money::cents(1)
-    } else if price >= Money::cents(2) {
-        price + Money::dec_cents(5)
-    } else if price >= Money::cents(1) {
-        price + Money::dec_cents(1)
-    } else if price >= Money::millicents(200) {
-        price + Money::millicents(50)
-    } else if price >= Money::millicents(100) {
-        price + Money::millicents(10)
-    } else if price >= Money::millicents(20) {
-        price + Money::millicents(5)
-    } else {
-        price + Money::millicents(1)
+    if price >= MAX_PRICE {
+        return MAX_PRICE;
+    }
+    if price < MIN_PRICE {
+        return MIN_PRICE;
     }
+    let (_, step) = PRICE_LADDER
+        .iter()
+        .find(|&&(threshold, _)| price < threshold)
+        .expect("the last threshold is the highest price");
+    let step = step.to_millicents();
+    Money::millicents((price.to_millicents() / step + 1) * step)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{lower_price, raise_price};
-    use crate::Money;
+    use std::{cell::RefCell, rc::Rc};
+
+    use yew::Callback;
+
+    use super::{lower_price, raise_price, CloudServiceProps, MAX_PRICE, MIN_PRICE, PRICE_LADDER};
+    use crate::{
+        central::{engine::GameEngine, testing::WorldStateBuilder},
+        Money, PlayerAction, ServiceKind, WorldState,
+    };
+
+    #[test]
+    fn test_props_for_each_service() {
+        let mut builder = WorldStateBuilder::new();
+        for (i, kind) in ServiceKind::ALL.into_iter().enumerate() {
+            builder = builder
+                .service_unlocked(kind)
+                .price(kind, Money::cents(i as i64 + 1));
+        }
+        let state = builder.build();
+        let engine = GameEngine::new();
+        let actions = Rc::new(RefCell::new(vec![]));
+        let on_player_action = {
+            let actions = actions.clone();
+            Callback::from(move |action| actions.borrow_mut().push(action))
+        };
+
+        for (i, kind) in ServiceKind::ALL.into_iter().enumerate() {
+            let props = CloudServiceProps::for_service(kind, &state, &engine, 0, &on_player_action);
+            assert_eq!(props.kind, kind);
+            assert_eq!(props.price, Money::cents(i as i64 + 1));
+
+            props.on_click.emit(());
+            props.on_price_change.emit(Money::cents(50));
+            props.on_maintenance.emit(true);
+            assert_eq!(
+                actions.borrow_mut().split_off(0),
+                [
+                    PlayerAction::OpClick { kind, amount: 1 },
+                    PlayerAction::ChangePrice {
+                        kind,
+                        new_price: Money::cents(50)
+                    },
+                    PlayerAction::SetMaintenance { kind, on: true },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_demand_preview() {
+        let engine = GameEngine::new();
+        let on_player_action = Callback::noop();
+        let mut state = WorldStateBuilder::new()
+            .demand(50.)
+            .service_published(ServiceKind::Base, Money::millicents(50))
+            .service_unlocked(ServiceKind::Super)
+            .with_customer(ServiceKind::Base)
+            .build();
+        let preview = |state: &WorldState, kind: ServiceKind| {
+            let customers = state.customers_per_service()[kind as usize];
+            CloudServiceProps::for_service(kind, state, &engine, customers, &on_player_action)
+                .demand_preview
+        };
+
+        // not until demand estimates are unlocked
+        assert_eq!(preview(&state, ServiceKind::Base), None);
+
+        state.can_see_demand = true;
+        let base = preview(&state, ServiceKind::Base).unwrap();
+        assert_eq!(
+            base.current,
+            engine.projected_requests_per_customer(
+                &state,
+                ServiceKind::Base,
+                Money::millicents(50)
+            )
+        );
+        assert!(base.lower > base.current && base.raise < base.current);
+        // nor for services closed to customers
+        assert_eq!(preview(&state, ServiceKind::Super), None);
+    }
 
     #[test]
     fn test_price_changes() {
@@ -275,4 +699,50 @@ mod tests {
         assert_eq!(lower_price(Money::cents(200)), Money::cents(190));
         assert_eq!(lower_price(Money::cents(250)), Money::cents(200));
     }
+
+    #[test]
+    fn test_price_ladder_is_consistent() {
+        let mut previous = MIN_PRICE;
+        for pair in PRICE_LADDER.windows(2) {
+            let [(threshold, step), (next_threshold, next_step)] = [pair[0], pair[1]];
+            assert!(threshold > previous && next_threshold > threshold);
+            assert_eq!(threshold.to_millicents() % step.to_millicents(), 0);
+            assert_eq!(threshold.to_millicents() % next_step.to_millicents(), 0);
+            previous = threshold;
+        }
+        assert_eq!(PRICE_LADDER[PRICE_LADDER.len() - 1].0, MAX_PRICE);
+    }
+
+    /// Check the price buttons against every price up to the highest one.
+    #[test]
+    fn test_price_ladder_invariants() {
+        // the grid of prices on the ladder
+        let mut grid = vec![MIN_PRICE];
+        while grid[grid.len() - 1] < MAX_PRICE {
+            let next = raise_price(grid[grid.len() - 1]);
+            assert!(next > grid[grid.len() - 1]);
+            grid.push(next);
+        }
+        assert_eq!(grid[grid.len() - 1], MAX_PRICE);
+        assert_eq!(raise_price(MAX_PRICE), MAX_PRICE);
+        assert_eq!(lower_price(MIN_PRICE), MIN_PRICE);
+
+        // on the grid, lowering undoes raising and vice versa
+        for pair in grid.windows(2) {
+            assert_eq!(lower_price(pair[1]), pair[0]);
+            assert_eq!(raise_price(pair[0]), pair[1]);
+        }
+
+        // off the grid, prices snap to the nearest grid prices
+        for millicents in 1..=MAX_PRICE.to_millicents() {
+            let price = Money::millicents(millicents);
+            match grid.binary_search(&price) {
+                Ok(_) => {}
+                Err(i) => {
+                    assert_eq!(lower_price(price), grid[i - 1], "lower {price:?}");
+                    assert_eq!(raise_price(price), grid[i], "raise {price:?}");
+                }
+            }
+        }
+    }
 }
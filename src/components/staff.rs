@@ -0,0 +1,115 @@
+//! Module for the staff widget,
+//! where the player hires and fires the staff who perform ops for them.
+
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::{
+    central::staff::{payroll, StaffMember, StaffTier, MAX_STAFF},
+    t, AudioEvent, Money, Ops, PlayerAction, ServiceKind,
+};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct StaffRosterProps {
+    /// the staff hired, oldest first
+    pub staff: Vec<StaffMember>,
+    /// the available funds
+    pub funds: Money,
+    /// ops available for each service,
+    /// indexed by [`ServiceKind::to_code`]
+    /// (or `None` if the service is not available yet)
+    pub ops_available: [Option<Ops>; ServiceKind::ALL.len()],
+    /// callback for when the player hires or fires someone
+    pub on_player_action: Callback<PlayerAction>,
+    /// callback for the sounds made by the widget
+    #[prop_or_default]
+    pub on_audio: Callback<AudioEvent>,
+}
+
+/// A widget listing the staff and the payroll,
+/// with buttons for hiring and firing.
+#[function_component]
+pub fn StaffRoster(props: &StaffRosterProps) -> Html {
+    let service = use_state(|| ServiceKind::Base);
+
+    let action = |action: PlayerAction| {
+        let on_player_action = props.on_player_action.clone();
+        let on_audio = props.on_audio.clone();
+        move |_| {
+            on_audio.emit(AudioEvent::UiClick);
+            on_player_action.emit(action.clone());
+        }
+    };
+
+    let members: Html = props
+        .staff
+        .iter()
+        .enumerate()
+        .map(|(index, member)| {
+            let severance = member.tier.severance();
+            html! {
+                <li>
+                    {t!("staff.member", tier = member.tier, service = member.service)}
+                    <button
+                        disabled={props.funds < severance}
+                        title={t!("staff.severance", amount = severance)}
+                        onclick={action(PlayerAction::Fire { index })}>
+                        {t!("staff.fire")}
+                    </button>
+                </li>
+            }
+        })
+        .collect();
+
+    let options: Html = ServiceKind::ALL
+        .into_iter()
+        .filter(|kind| props.ops_available[kind.to_code() as usize].is_some())
+        .map(|kind| {
+            html! {
+                <option value={kind.to_code().to_string()} selected={kind == *service}>
+                    {kind.to_string()}
+                </option>
+            }
+        })
+        .collect();
+    let onchange = {
+        let service = service.clone();
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(kind) = select.value().parse().ok().and_then(ServiceKind::from_code) {
+                service.set(kind);
+            }
+        }
+    };
+
+    let full = props.staff.len() >= MAX_STAFF;
+    let hire_buttons: Html = StaffTier::ALL
+        .into_iter()
+        .map(|tier| {
+            let cost = tier.hiring_cost();
+            let title = t!(
+                "staff.hire_hint",
+                ops = tier.ops_per_second(),
+                salary = tier.salary()
+            );
+            html! {
+                <button
+                    disabled={full || props.funds < cost}
+                    {title}
+                    onclick={action(PlayerAction::Hire { tier, service: *service })}>
+                    {t!("staff.hire", tier = tier, cost = cost)}
+                </button>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="staff">
+            <span>{t!("staff.title")}</span>{" "}
+            <span>{t!("staff.payroll", amount = payroll(&props.staff))}</span>
+            <ul>{members}</ul>
+            <select {onchange}>{options}</select>
+            <div class="staff-hire">{hire_buttons}</div>
+        </div>
+    }
+}
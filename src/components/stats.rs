@@ -0,0 +1,118 @@
+//! Module for the statistics component,
+//! which shows the score of the game
+//! and breaks down requests by where they came from.
+use yew::prelude::*;
+
+use crate::{
+    central::{metrics::MetricsSnapshot, state::TrafficClass},
+    components::score::ScoreBoard,
+    display::Separating,
+    t,
+    web::copy_to_clipboard,
+    ServiceKind,
+};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct StatsProps {
+    /// the latest game metrics
+    pub metrics: MetricsSnapshot,
+    /// the plain text summary of the game,
+    /// copied when the player shares it
+    pub summary: AttrValue,
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ShareSummaryProps {
+    /// the plain text summary of the game
+    pub summary: AttrValue,
+}
+
+/// A button copying the summary of the game for the player to share.
+#[function_component]
+pub fn ShareSummary(props: &ShareSummaryProps) -> Html {
+    // the outcome of the last attempt to share the summary
+    let share_status = use_state(|| None::<bool>);
+    let on_share = {
+        let summary = props.summary.clone();
+        let share_status = share_status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let share_status = share_status.clone();
+            copy_to_clipboard(&summary, move |copied| share_status.set(Some(copied)));
+        })
+    };
+    let share_message = match *share_status {
+        Some(true) => t!("stats.copied"),
+        Some(false) => t!("stats.copy_failed"),
+        None => "",
+    };
+    html! {
+        <div class="share-summary">
+            <button onclick={on_share}>{t!("stats.share")}</button>
+            <span>{share_message}</span>
+        </div>
+    }
+}
+
+/// The statistics component.
+#[function_component]
+pub fn Stats(props: &StatsProps) -> Html {
+    let rows: Html = TrafficClass::ALL
+        .iter()
+        .map(|&class| {
+            let counters = props.metrics.traffic(class);
+            html! {
+                <tr>
+                    <td>{class.to_string()}</td>
+                    <td>{Separating(counters.fulfilled as i64).to_string()}</td>
+                    <td>{Separating(counters.dropped as i64).to_string()}</td>
+                </tr>
+            }
+        })
+        .collect();
+
+    let exchanged: Html = ServiceKind::ALL
+        .iter()
+        .zip(props.metrics.ops_exchanged)
+        .filter(|(_, ops)| ops.0 > 0)
+        .map(|(kind, ops)| {
+            html! {
+                <p>{t!("stats.exchanged", service = kind, ops = ops)}</p>
+            }
+        })
+        .collect();
+
+    html! {
+        <>
+            <ScoreBoard score={props.metrics.score} />
+            <p>{t!("stats.hardware_value", value = props.metrics.hardware_value)}</p>
+            <p>{t!("stats.net_worth", value = props.metrics.net_worth)}</p>
+            <table class="stats-table">
+                <tr>
+                    <th>{t!("stats.requests")}</th>
+                    <th>{t!("stats.fulfilled")}</th>
+                    <th>{t!("stats.dropped")}</th>
+                </tr>
+                {rows}
+            </table>
+            if props.metrics.attacks_mitigated > 0 {
+                <p>
+                    {t!(
+                        "stats.attacks_mitigated",
+                        count = Separating(props.metrics.attacks_mitigated as i64)
+                    )}
+                </p>
+            }
+            if props.metrics.total_player_clicks > 0 {
+                <p>
+                    {t!(
+                        "stats.clicks",
+                        total = Separating(props.metrics.total_player_clicks as i64),
+                        per_minute = props.metrics.clicks_per_minute
+                    )}
+                </p>
+            }
+            {exchanged}
+            <ShareSummary summary={props.summary.clone()} />
+        </>
+    }
+}
@@ -0,0 +1,164 @@
+//! Module for the tabbed panels container,
+//! which shows one game panel at a time on narrow screens.
+//!
+//! The player switches panels with the tab bar
+//! or by swiping sideways over the panel.
+//! Touch events are only observed, never prevented,
+//! so that scrolling the panel keeps working.
+
+use yew::prelude::*;
+
+use crate::components::panel::Panel;
+
+/// the horizontal distance in CSS pixels
+/// for a touch gesture to count as a swipe
+pub const SWIPE_MIN_DISTANCE: i32 = 60;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct TabbedPanelsProps {
+    /// the panels, one per tab,
+    /// named after their titles
+    pub children: ChildrenWithProps<Panel>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TabbedPanelsMsg {
+    /// the player chose the tab at this index
+    Select(usize),
+    /// a touch started at these client coordinates
+    TouchStart(i32, i32),
+    /// a touch ended at these client coordinates
+    TouchEnd(i32, i32),
+}
+
+/// A container showing one of its panels at a time.
+///
+/// The active tab is kept in the component,
+/// so it stays put as the panels are updated.
+#[derive(Debug, Default)]
+pub struct TabbedPanels {
+    /// the index of the panel shown
+    active: usize,
+    /// where the ongoing touch started, if any
+    touch_start: Option<(i32, i32)>,
+}
+
+impl Component for TabbedPanels {
+    type Message = TabbedPanelsMsg;
+    type Properties = TabbedPanelsProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let count = ctx.props().children.len();
+        match msg {
+            TabbedPanelsMsg::Select(index) => {
+                let changed = self.active != index;
+                self.active = index;
+                changed
+            }
+            TabbedPanelsMsg::TouchStart(x, y) => {
+                self.touch_start = Some((x, y));
+                false
+            }
+            TabbedPanelsMsg::TouchEnd(x, y) => {
+                let Some((start_x, start_y)) = self.touch_start.take() else {
+                    return false;
+                };
+                let active = swipe(
+                    self.active.min(count.saturating_sub(1)),
+                    count,
+                    x - start_x,
+                    y - start_y,
+                );
+                let changed = self.active != active;
+                self.active = active;
+                changed
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let children = &ctx.props().children;
+        // panels may come and go as the game unfolds
+        let active = self.active.min(children.len().saturating_sub(1));
+
+        let tabs: Html = children
+            .iter()
+            .enumerate()
+            .map(|(index, panel)| {
+                let classes = if index == active {
+                    classes!["tab", "active"]
+                } else {
+                    classes!["tab"]
+                };
+                let onclick = ctx.link().callback(move |_| TabbedPanelsMsg::Select(index));
+                html! {
+                    <button class={classes} role="tab" {onclick}>
+                        {panel.props.title.clone()}
+                    </button>
+                }
+            })
+            .collect();
+
+        let ontouchstart = ctx.link().batch_callback(|e: TouchEvent| {
+            e.touches()
+                .get(0)
+                .map(|touch| TabbedPanelsMsg::TouchStart(touch.client_x(), touch.client_y()))
+        });
+        let ontouchend = ctx.link().batch_callback(|e: TouchEvent| {
+            e.changed_touches()
+                .get(0)
+                .map(|touch| TabbedPanelsMsg::TouchEnd(touch.client_x(), touch.client_y()))
+        });
+
+        html! {
+            <div class="tabbed-panels">
+                <div class="tabs" role="tablist">
+                    {tabs}
+                </div>
+                <div class="tab-content" {ontouchstart} {ontouchend}>
+                    {children.iter().nth(active)}
+                </div>
+            </div>
+        }
+    }
+}
+
+/// The tab to show after a touch gesture
+/// which moved by `dx` and `dy` pixels
+/// from the tab at `active` out of `count`.
+///
+/// Only a mostly horizontal gesture longer than [`SWIPE_MIN_DISTANCE`]
+/// is a swipe: swiping left shows the next tab,
+/// swiping right shows the previous one.
+fn swipe(active: usize, count: usize, dx: i32, dy: i32) -> usize {
+    if dx.abs() < SWIPE_MIN_DISTANCE || dx.abs() <= dy.abs() {
+        active
+    } else if dx < 0 {
+        (active + 1).min(count.saturating_sub(1))
+    } else {
+        active.saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{swipe, SWIPE_MIN_DISTANCE};
+
+    #[test]
+    fn test_swipe() {
+        assert_eq!(swipe(1, 4, -SWIPE_MIN_DISTANCE, 0), 2);
+        assert_eq!(swipe(1, 4, SWIPE_MIN_DISTANCE, 0), 0);
+        // taps and short drags do not switch tabs
+        assert_eq!(swipe(1, 4, 0, 0), 1);
+        assert_eq!(swipe(1, 4, -SWIPE_MIN_DISTANCE + 1, 0), 1);
+        // neither does scrolling
+        assert_eq!(swipe(1, 4, -SWIPE_MIN_DISTANCE, 200), 1);
+        // no wrapping around
+        assert_eq!(swipe(3, 4, -200, 0), 3);
+        assert_eq!(swipe(0, 4, 200, 0), 0);
+    }
+}
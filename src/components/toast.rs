@@ -0,0 +1,38 @@
+//! Module for a brief message which shows up and fades out on its own,
+//! e.g. when an action of the player could not be applied,
+//! possibly offering the player something to do about it
+
+use yew::prelude::*;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ToastProps {
+    /// the message to show
+    pub message: AttrValue,
+    /// a number which changes with every new message,
+    /// so that the same message can be shown again
+    pub serial: u32,
+    /// the label of a button offered with the message, if any
+    #[prop_or_default]
+    pub action: Option<AttrValue>,
+    /// called when the player clicks the button
+    #[prop_or_default]
+    pub on_action: Callback<()>,
+}
+
+/// The toast component.
+#[function_component]
+pub fn Toast(props: &ToastProps) -> Html {
+    let on_action = props.on_action.reform(|_| ());
+    html! {
+        <div
+            class={classes!["toast", props.action.is_some().then_some("with-action")]}
+            key={props.serial.to_string()}
+            role="status">
+            {props.message.clone()}
+            if let Some(action) = &props.action {
+                {" "}
+                <button onclick={on_action}>{action.clone()}</button>
+            }
+        </div>
+    }
+}
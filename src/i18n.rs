@@ -0,0 +1,25 @@
+//! Module for translating the text shown to the player
+//! (see [`cloud_champion_core::i18n`]),
+//! remembering the locale chosen by the player in local storage.
+
+use js_sys::wasm_bindgen::JsValue;
+
+pub use cloud_champion_core::i18n::*;
+
+use crate::{central::persistence::SaveStorage, storage::try_local_storage};
+
+/// Load the locale chosen by the player from local storage,
+/// or the default locale if none was saved.
+pub fn load_locale() -> Locale {
+    try_local_storage()
+        .and_then(|storage| storage.get("locale"))
+        .ok()
+        .flatten()
+        .and_then(|code| Locale::from_code(&code))
+        .unwrap_or_default()
+}
+
+/// Save the locale chosen by the player to local storage.
+pub fn save_locale(locale: Locale) -> Result<(), JsValue> {
+    try_local_storage()?.set("locale", locale.code())
+}
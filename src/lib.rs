@@ -1,33 +1,86 @@
 //! 10x Cloud Champion component library
+//!
+//! The game engine lives in the `cloud-champion-core` crate,
+//! re-exported here so that it can be reached from the front-end as before.
 
 pub mod audio;
-pub mod central;
 pub mod components;
-pub mod display;
+pub mod i18n;
+pub mod settings;
+pub mod storage;
+pub mod web;
+#[cfg(feature = "worker")]
+pub mod worker;
 
 use std::fmt;
 
 use gloo_timers::callback::Interval;
-use rand::SeedableRng;
-use rand_distr::Distribution;
-use rand_pcg::Pcg32;
 
-pub use crate::central::action::PlayerAction;
-pub use crate::central::cloud_user::{CloudClientSpec, CloudUserSpec};
-pub use crate::central::queue::Time;
-pub use crate::central::state::WorldState;
-pub use crate::central::stuff::{Cost, Memory, Money, Ops, ServiceKind};
+use crate::central::simulation::SimulationOutput;
 
-/// the global period of the game watch interval
-pub const MILLISECONDS_PER_CYCLE: u32 = 50;
+#[cfg(feature = "debug-tools")]
+pub use cloud_champion_core::DebugAction;
+pub use cloud_champion_core::{central, display, t};
+pub use cloud_champion_core::{
+    ActionError, AudioEvent, CloudClientSpec, CloudUserSpec, Cost, Difficulty, GameConfig, Memory,
+    Money, Ops, PlayerAction, SampleGenerator, ServiceKind, Time, UserKind, WorldState,
+};
+pub use cloud_champion_core::{
+    GAME_VERSION, MILLISECONDS_PER_CYCLE, TIME_UNITS_PER_CYCLE, TIME_UNITS_PER_MILLISECOND,
+    TIME_UNITS_PER_SECOND,
+};
 
-/// how many time units are in a single millisecond
-pub const TIME_UNITS_PER_MILLISECOND: u32 = 10;
+/// the git commit which the game was built from,
+/// or "unknown" if it was built without git
+pub const GIT_HASH: &str = env!("GIT_HASH");
 
-/// how many time units are in a single game update cycle
-pub const TIME_UNITS_PER_CYCLE: u32 = TIME_UNITS_PER_MILLISECOND * MILLISECONDS_PER_CYCLE;
+/// the most wall time which a single tick may advance the game by,
+/// in milliseconds
+///
+/// Browsers throttle timers in background tabs to about once per second,
+/// which is still simulated in full,
+/// but longer gaps (such as a device waking up from sleep)
+/// are not caught up on.
+pub const MAX_MILLISECONDS_PER_TICK: u32 = 2_000;
 
-/// The time watch service, emits ticks at a fixed interval when started.
+/// Measures the wall time between the ticks of the game watch,
+/// so that the game advances by the time which actually passed
+/// rather than by the nominal interval period.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TickClock {
+    /// the wall time (in milliseconds) up to which the game was advanced
+    last: f64,
+}
+
+impl TickClock {
+    /// Start measuring from the given wall time in milliseconds.
+    pub fn new(now: f64) -> Self {
+        TickClock { last: now }
+    }
+
+    /// The whole milliseconds elapsed since the last tick,
+    /// up to [`MAX_MILLISECONDS_PER_TICK`].
+    ///
+    /// Fractions of a millisecond are carried over to the next tick,
+    /// while the wall time beyond the maximum is skipped.
+    /// A clock going backwards yields no time at all.
+    pub fn tick(&mut self, now: f64) -> u32 {
+        let elapsed = (now - self.last).floor();
+        if elapsed <= 0. {
+            self.last = self.last.min(now);
+            return 0;
+        }
+        if elapsed > MAX_MILLISECONDS_PER_TICK as f64 {
+            self.last = now;
+            return MAX_MILLISECONDS_PER_TICK;
+        }
+        self.last += elapsed;
+        elapsed as u32
+    }
+}
+
+/// The time watch service, emits ticks at a fixed interval when started,
+/// each with the milliseconds of wall time elapsed since the previous one.
 pub struct GameWatch {
     interval: Option<Interval>,
 }
@@ -45,15 +98,18 @@ impl GameWatch {
         GameWatch { interval: None }
     }
 
-    pub fn start_with<F>(&mut self, tick_fn: F)
+    pub fn start_with<F>(&mut self, mut tick_fn: F)
     where
-        F: 'static + FnMut() + Clone,
+        F: 'static + FnMut(u32),
     {
         if self.interval.is_some() {
             return;
         }
 
-        let interval = Interval::new(MILLISECONDS_PER_CYCLE, tick_fn);
+        let mut clock = TickClock::new(js_sys::Date::now());
+        let interval = Interval::new(MILLISECONDS_PER_CYCLE, move || {
+            tick_fn(clock.tick(js_sys::Date::now()))
+        });
         self.interval = Some(interval);
     }
 
@@ -62,6 +118,51 @@ impl GameWatch {
             interval.cancel();
         }
     }
+
+    /// Whether the watch is currently emitting ticks.
+    pub fn is_running(&self) -> bool {
+        self.interval.is_some()
+    }
+}
+
+impl Default for GameWatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the game loop is paused,
+/// deciding which messages may advance the game.
+///
+/// Pausing and resuming are idempotent,
+/// so that repeated messages do not restart the watch
+/// or let a stray tick through.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PauseState {
+    paused: bool,
+}
+
+impl PauseState {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause the game,
+    /// returning whether it was running until now.
+    pub fn pause(&mut self) -> bool {
+        !std::mem::replace(&mut self.paused, true)
+    }
+
+    /// Resume the game,
+    /// returning whether it was paused until now.
+    pub fn resume(&mut self) -> bool {
+        std::mem::replace(&mut self.paused, false)
+    }
+
+    /// Whether a tick of the game watch should advance the game.
+    pub fn should_tick(&self) -> bool {
+        !self.paused
+    }
 }
 
 /// Top level game message for the game loop and reacting to player actions.
@@ -71,11 +172,29 @@ pub enum GameMsg {
     Action(PlayerAction),
     /// the game watch ticked,
     /// so the game loop should advance
-    Tick,
+    /// by the given milliseconds of wall time
+    Tick(u32),
     /// the game loop should stop
+    /// (does nothing if already paused)
     Pause,
     /// the game loop should resume
+    /// (does nothing if not paused)
     Resume,
+    /// the player opened a rack or data center in the equipment overview,
+    /// or returned to the overview (`None`)
+    ExpandEquipment(Option<usize>),
+    /// the player is asked to confirm using the project card with this ID,
+    /// or dismissed the confirmation (`None`)
+    ConfirmCard(Option<&'static str>),
+    /// the player typed in the filter of the projects panel
+    FilterCards(String),
+    /// the browser viewport was resized to this width in CSS pixels
+    Resize(f64),
+    /// the developer overlay should be shown or hidden
+    #[cfg(feature = "debug-tools")]
+    ToggleDebugOverlay,
+    /// a message came from the simulation running in a web worker
+    Simulated(Box<SimulationOutput>),
 }
 
 impl From<PlayerAction> for GameMsg {
@@ -84,44 +203,64 @@ impl From<PlayerAction> for GameMsg {
     }
 }
 
-/// Game construct that produces timed events on demand.
-#[derive(Debug)]
-pub struct SampleGenerator {
-    /// the random number generator
-    rng: Pcg32,
-}
+#[cfg(test)]
+mod tests {
+    use super::{GameWatch, PauseState, TickClock, MAX_MILLISECONDS_PER_TICK};
 
-impl SampleGenerator {
-    pub fn new() -> Self {
-        SampleGenerator {
-            rng: Pcg32::from_entropy(),
-        }
-    }
+    #[test]
+    fn test_pause_idempotent() {
+        let mut pause = PauseState::default();
+        assert!(pause.should_tick());
+        // resuming a running game changes nothing
+        assert!(!pause.resume());
+        assert!(pause.should_tick());
 
-    /// Sample when the next request to cloud service is going to be made
-    /// based on the given demand for that service.
-    ///
-    /// Demand is approximately the number of requests per second.
-    pub fn next_request(&mut self, demand: f32) -> Time {
-        let distribution = rand_distr::Exp::new(demand).unwrap();
-        ((distribution.sample(&mut self.rng) * 1_000. * TIME_UNITS_PER_MILLISECOND as f32) as Time)
-            // ~ 15 second max
-            .min(TIME_UNITS_PER_MILLISECOND as Time * 20_000)
-    }
+        assert!(pause.pause());
+        assert!(pause.is_paused());
+        assert!(!pause.should_tick());
+        // pausing again changes nothing
+        assert!(!pause.pause());
+        assert!(!pause.should_tick());
 
-    /// Pick a number in the `(low..high)` range (excluding `high`).
-    pub fn gen_range(&mut self, low: u32, high: u32) -> u32 {
-        rand_distr::Uniform::new(low, high).sample(&mut self.rng)
+        assert!(pause.resume());
+        assert!(!pause.resume());
+        assert!(pause.should_tick());
     }
 
-    /// Pick `true` with the given probability.
-    pub fn gen_bool(&mut self, chance: f32) -> bool {
-        rand_distr::Uniform::new_inclusive(0., 1.).sample(&mut self.rng) < chance
+    #[test]
+    fn test_watch_stopped() {
+        let mut watch = GameWatch::new();
+        assert!(!watch.is_running());
+        // stopping a stopped watch is harmless
+        watch.stop();
+        assert!(!watch.is_running());
     }
-}
 
-impl Default for SampleGenerator {
-    fn default() -> Self {
-        Self::new()
+    /// The game advances by the wall time which passed,
+    /// however irregular the ticks.
+    #[test]
+    fn test_tick_clock_follows_wall_time() {
+        let mut clock = TickClock::new(1_000.);
+        let mut now = 1_000.;
+        let mut simulated = 0;
+        for elapsed in [
+            50., 50., 12.5, 87.5, 0., 1_000., 999.9, 0.1, 3.3, 46.7, 1_000.,
+        ] {
+            now += elapsed;
+            simulated += clock.tick(now);
+        }
+        assert_eq!(simulated as f64, now - 1_000.);
+
+        // long gaps are clamped
+        now += 60_000.;
+        assert_eq!(clock.tick(now), MAX_MILLISECONDS_PER_TICK);
+        now += 50.;
+        assert_eq!(clock.tick(now), 50);
+
+        // the clock going backwards does not rewind the game
+        now -= 500.;
+        assert_eq!(clock.tick(now), 0);
+        now += 30.;
+        assert_eq!(clock.tick(now), 30);
     }
 }
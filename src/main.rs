@@ -6,10 +6,12 @@ mod playground;
 
 #[cfg(not(feature = "playground"))]
 fn main() {
+    cloud_champion::web::init_logging();
     yew::Renderer::<app::App>::new().render();
 }
 
 #[cfg(feature = "playground")]
 fn main() {
+    cloud_champion::web::init_logging();
     yew::Renderer::<playground::Playground>::new().render();
 }
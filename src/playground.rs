@@ -1,367 +1,349 @@
-use cloud_champion::central::cards::all::ALL_CARDS;
-use cloud_champion::central::engine::{CloudNode, GameEngine};
-use cloud_champion::central::state::ServiceInfo;
+//! The component playground,
+//! a place where the game's components can be seen
+//! with a made-up game state,
+//! plus controls to manipulate it directly
+//! for trying out new engine features by hand.
+
+use cloud_champion::central::engine::GameEngine;
+use cloud_champion::central::state::HARDWARE_PANEL_PURCHASES;
+use cloud_champion::central::streak::StreakReward;
+use cloud_champion::central::testing::WorldStateBuilder;
 use cloud_champion::components::business::{Business, BusinessProps};
-use cloud_champion::components::hardware::{Node, Power, Rack};
-use cloud_champion::components::services::CloudService;
+use cloud_champion::components::hardware::{Equipment, NodeProps, Power};
+use cloud_champion::components::histogram::ProcessingChart;
+use cloud_champion::components::notifications::Notifications;
+use cloud_champion::components::services::{CloudService, CloudServiceProps};
+use cloud_champion::components::stats::Stats;
+use cloud_champion::components::toast::Toast;
 use cloud_champion::components::total_stats::{TotalStats, TotalStatsProps};
 use cloud_champion::{
-    CloudUserSpec, GameMsg, GameWatch, Memory, Money, Ops, PlayerAction, ServiceKind, WorldState,
-    TIME_UNITS_PER_TICK,
+    t, GameMsg, GameWatch, Memory, Money, Ops, PauseState, PlayerAction, ServiceKind, UserKind,
+    WorldState, TIME_UNITS_PER_MILLISECOND,
 };
 use yew::prelude::*;
 
 use cloud_champion::components::card::*;
 use cloud_champion::components::panel::Panel;
 
+/// A direct manipulation of the game state,
+/// not available in the main game.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Control {
+    AddFunds(Money),
+    AddOps(ServiceKind, Ops),
+    AddDemand(f64),
+    /// add a user of the given kind to a service
+    SpawnUser(ServiceKind, UserKind),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PlaygroundMsg {
+    Game(GameMsg),
+    Control(Control),
+}
+
+impl From<GameMsg> for PlaygroundMsg {
+    fn from(msg: GameMsg) -> Self {
+        PlaygroundMsg::Game(msg)
+    }
+}
+
+impl From<PlayerAction> for PlaygroundMsg {
+    fn from(action: PlayerAction) -> Self {
+        PlaygroundMsg::Game(GameMsg::Action(action))
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Playground {
     state: WorldState,
     engine: GameEngine,
     watch: GameWatch,
+    /// whether the simulation is paused
+    pause: PauseState,
+    /// the rack or data center shown in detail in the equipment panel
+    expanded_equipment: Option<usize>,
+    /// the latest action failure,
+    /// and a number which changes with every failure
+    toast: (Option<AttrValue>, u32),
+}
+
+/// The made-up game state to start with,
+/// with a few services and customers.
+pub(crate) fn initial_state() -> WorldState {
+    let state = WorldStateBuilder::new()
+        .time(100)
+        .funds(Money::dollars(50))
+        .demand(20.)
+        .service_published(ServiceKind::Base, Money::dec_cents(1))
+        .available_ops(ServiceKind::Base, Ops(100))
+        .total_ops(ServiceKind::Base, Ops(500))
+        .service_published(ServiceKind::Super, Money::dec_cents(5))
+        .total_ops(ServiceKind::Super, Ops(20))
+        .service_unlocked(ServiceKind::Epic)
+        .price(ServiceKind::Epic, Money::cents(2))
+        .price(ServiceKind::Awesome, Money::cents(50))
+        .with_customer(ServiceKind::Base)
+        .with_customer(ServiceKind::Super)
+        .build();
+    WorldState {
+        can_buy_nodes: true,
+        can_see_demand: true,
+        can_see_energy_consumption: true,
+        can_see_request_rates: true,
+        hardware_panel_level: HARDWARE_PANEL_PURCHASES,
+        ..state
+    }
+}
+
+/// Apply a playground control to the game state.
+pub(crate) fn apply_control(state: &mut WorldState, engine: &mut GameEngine, control: Control) {
+    match control {
+        Control::AddFunds(money) => {
+            state.funds += money;
+        }
+        Control::AddOps(kind, amount) => {
+            let service = state.service_by_kind_mut(kind);
+            service.available += amount;
+            service.total += amount;
+        }
+        Control::AddDemand(demand) => {
+            state.demand = (state.demand + demand).max(0.);
+        }
+        Control::SpawnUser(service, kind) => {
+            let user_spec = state.add_user_spec(service, kind, 0);
+            engine.bootstrap_events_for(state, &user_spec);
+        }
+    }
+    // the controls may reveal new cards
+    engine.update_visible_cards(state);
 }
 
 impl Component for Playground {
-    type Message = GameMsg;
+    type Message = PlaygroundMsg;
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
-        // whichever we want it to be
-        let state = WorldState {
-            time: 100,
-            funds: Money::dollars(50),
-            spent: Money::zero(),
-            demand: 1.,
-            software_level: 0,
-            base_service: ServiceInfo {
-                price: Money::dec_cents(1),
-                available: Ops(100),
-                total: Ops(500),
-                unlocked: true,
-                private: false,
-            },
-            super_service: ServiceInfo {
-                price: Money::dec_cents(5),
-                available: Ops(0),
-                total: Ops(20),
-                unlocked: true,
-                private: false,
-            },
-            epic_service: ServiceInfo::new_private(Money::cents(2)),
-            awesome_service: ServiceInfo::new_locked(Money::cents(50)),
-            nodes: vec![CloudNode::new(0)],
-            user_specs: vec![
-                CloudUserSpec {
-                    amount: 1,
-                    service: ServiceKind::Base,
-                    trial_time: 0,
-                    bad: false,
-                },
-                CloudUserSpec {
-                    amount: 1,
-                    service: ServiceKind::Super,
-                    trial_time: 0,
-                    bad: false,
-                },
-            ],
-            ..Default::default()
-        };
-
-        let link = ctx.link().clone();
         let mut out = Self {
-            state,
-            engine: GameEngine::new(link),
+            state: initial_state(),
+            engine: GameEngine::new(),
             watch: GameWatch::new(),
+            pause: PauseState::default(),
+            expanded_equipment: None,
+            toast: (None, 0),
         };
 
         let link = ctx.link().clone();
         out.watch
-            .start_with(move || link.send_message(GameMsg::Tick));
+            .start_with(move |elapsed| link.send_message(GameMsg::Tick(elapsed)));
 
-        out.engine.bootstrap_events(&mut out.state);
+        out.engine.bootstrap_events(&out.state);
 
         out
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            GameMsg::Action(action) => {
-                self.engine.apply_action(&mut self.state, action);
-                true
+            PlaygroundMsg::Game(GameMsg::Action(action)) => {
+                if let Err(e) = self.engine.apply_action(&mut self.state, action) {
+                    gloo_console::warn!("Could not apply action:", format!("{e:?}"));
+                    let (message, serial) = &mut self.toast;
+                    *message = Some(e.to_string().into());
+                    *serial = serial.wrapping_add(1);
+                }
             }
-            GameMsg::Tick => {
-                let time = self.state.time + TIME_UNITS_PER_TICK as u64;
+            PlaygroundMsg::Game(GameMsg::Tick(elapsed)) => {
+                if !self.pause.should_tick() {
+                    return false;
+                }
+                let time = self.state.time + (elapsed * TIME_UNITS_PER_MILLISECOND) as u64;
                 self.engine.update(&mut self.state, time);
-                true
+                // sounds and saving are left out of the playground
+                self.engine.take_audio_events();
+                self.engine.take_save_request();
+                self.engine.take_player_ops();
+            }
+            PlaygroundMsg::Game(GameMsg::Pause) => {
+                if self.pause.pause() {
+                    self.watch.stop();
+                }
+            }
+            PlaygroundMsg::Game(GameMsg::Resume) => {
+                if self.pause.resume() {
+                    let link = ctx.link().clone();
+                    self.watch
+                        .start_with(move |elapsed| link.send_message(GameMsg::Tick(elapsed)));
+                }
+            }
+            PlaygroundMsg::Game(GameMsg::ExpandEquipment(expanded)) => {
+                self.expanded_equipment = expanded;
+            }
+            PlaygroundMsg::Game(GameMsg::ConfirmCard(_)) => {
+                // cards are used right away in the playground
+            }
+            PlaygroundMsg::Game(GameMsg::FilterCards(_)) => {
+                // the playground lists all cards as they are
+            }
+            PlaygroundMsg::Game(GameMsg::Resize(_)) => {
+                // the playground always lays out the components side by side
+            }
+            PlaygroundMsg::Game(GameMsg::Simulated(_)) => {
+                // the playground always runs the engine on the page
             }
-            GameMsg::Pause => {
-                self.watch.stop();
-                true
+            #[cfg(feature = "debug-tools")]
+            PlaygroundMsg::Game(GameMsg::ToggleDebugOverlay) => {
+                // the playground has its own controls
             }
-            GameMsg::Resume => {
-                let link = ctx.link().clone();
-                self.watch
-                    .start_with(move || link.send_message(GameMsg::Tick));
-                true
+            PlaygroundMsg::Control(control) => {
+                apply_control(&mut self.state, &mut self.engine, control);
             }
         }
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let super_service = &self.state.super_service;
-        let epic_service = &self.state.epic_service;
-        let awesome_service = &self.state.awesome_service;
-        let electricity_bill = self.state.electricity.total_due.into_cent_precision();
+        let electricity_bill = self.state.electricity.bill_amount();
         let business_props = BusinessProps {
             funds: self.state.funds,
-            base_ops_available: self.state.base_service.available,
-            super_ops_available: if super_service.unlocked {
-                Some(super_service.available)
-            } else {
-                None
-            },
-            epic_ops_available: if epic_service.unlocked {
-                Some(epic_service.available)
-            } else {
-                None
-            },
-            awesome_ops_available: if awesome_service.unlocked {
-                Some(awesome_service.available)
-            } else {
-                None
-            },
-
+            energy_consumption_rate: Some(self.state.electricity.energy_consumption_rate),
+            request_rates: Some((self.engine.drop_rate, self.engine.failure_rate)),
+            ops_available: ServiceKind::ALL.map(|kind| {
+                let service = self.state.service_by_kind(kind);
+                Some(service.available).filter(|_| service.state.is_unlocked())
+            }),
             electricity_bill,
-            can_pay_bill: electricity_bill <= self.state.funds,
-            on_pay_bills: {
-                let link = ctx.link().clone();
-                Callback::from(move |_| link.send_message(PlayerAction::PayElectricityBill))
-            },
-            client_count: None,
-        };
-
-        let base_c = {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Base,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = self.state.ops_per_click;
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Base,
-                        amount,
-                    })
-                }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Base}
-                    price={self.state.base_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    />
-            }
-        };
-
-        let super_c = if super_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Super,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = (self.state.ops_per_click / 10).min(1);
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Super,
-                        amount,
-                    })
-                }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Super}
-                    price={super_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={super_service.total == Ops(0)}
-                    />
-            }
-        } else {
-            html! {}
-        };
-
-        let epic_c = if epic_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Epic,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = (self.state.ops_per_click / 50).max(1);
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Epic,
-                        amount,
-                    })
-                }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Epic}
-                    price={epic_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={epic_service.total == Ops(0)}
-                    private={true}
-                    />
-            }
-        } else {
-            html! {}
+            bill_payment: self.state.bill_payment(),
+            can_exchange_ops: self.state.can_exchange_ops,
+            on_player_action: ctx.link().callback(|action| action),
+            slas: self.state.slas.clone(),
+            insurance: self.state.insurance.clone(),
+            top_customers: Some(self.state.top_customers()),
+            time_until_powersave: self.state.time_until_powersave(),
+            on_pay_bills: ctx.link().callback(|_| PlayerAction::PayElectricityBill),
+            auto_pay: Some(self.state.auto_pay).filter(|_| self.state.can_auto_pay),
+            on_time_streak: (self.state.electricity.last_bill > Money::zero())
+                .then_some(self.state.electricity.on_time_streak),
+            next_streak_reward: StreakReward::next(self.state.electricity.streak_rewards),
+            electricity_credit: self.state.electricity.credit,
+            prepay_amount: self.state.electricity.last_bill.round_to_cents_half_up(),
+            tariff: Some(self.state.electricity.tariff).filter(|_| self.state.can_choose_tariff),
+            tariff_offers: self.engine.tariff_offers(&self.state),
+            can_switch_tariff: self.state.time >= self.state.electricity.tariff_switch_ready_at(),
+            staff: Some(self.state.staff.clone()).filter(|_| self.state.can_hire_staff),
+            demand: Some(self.state.demand),
+            day_phase: Some(self.engine.day_phase(self.state.time)),
+            growth_stalled: self.engine.growth_stalled(),
+            loan: self.state.loan.as_ref().map(|loan| loan.balance),
+            can_repay_loan: self
+                .state
+                .loan
+                .as_ref()
+                .is_some_and(|loan| loan.balance <= self.state.funds),
+            on_repay_loan: ctx.link().callback(|_| PlayerAction::RepayLoan),
+            on_audio: Callback::default(),
         };
 
-        let awesome_c = if awesome_service.unlocked {
-            let on_price_change = {
-                let link = ctx.link().clone();
-                move |new_price: Money| {
-                    link.send_message(PlayerAction::ChangePrice {
-                        kind: ServiceKind::Awesome,
-                        new_price,
-                    });
-                }
-            };
-            let on_op_click = {
-                let link = ctx.link().clone();
-                let amount = (self.state.ops_per_click / 1_000).max(1);
-                move |_| {
-                    link.send_message(PlayerAction::OpClick {
-                        kind: ServiceKind::Awesome,
-                        amount,
-                    })
+        // Services panel: all unlocked services
+        let on_player_action = ctx.link().callback(|action: PlayerAction| action);
+        let customers = self.state.customers_per_service();
+        let services: Html = self
+            .state
+            .services()
+            .filter(|(_, service)| service.state.is_unlocked())
+            .map(|(kind, _)| {
+                let props = CloudServiceProps::for_service(
+                    kind,
+                    &self.state,
+                    &self.engine,
+                    customers[kind as usize],
+                    &on_player_action,
+                );
+                html! {
+                    <CloudService ..props />
                 }
-            };
-
-            html! {
-                <CloudService
-                    kind={ServiceKind::Epic}
-                    price={epic_service.price}
-                    on_click={on_op_click}
-                    {on_price_change}
-                    new={epic_service.total == Ops(0)}
-                    private={true}
-                    />
-            }
-        } else {
-            html! {}
-        };
+            })
+            .collect();
 
         // Total stats header: all op counts
         let total_stats_props = TotalStatsProps {
-            base_ops_total: self.state.base_service.total,
-            super_ops_total: if super_service.unlocked {
-                Some(super_service.total)
-            } else {
-                None
-            },
-            epic_ops_total: if epic_service.unlocked {
-                Some(epic_service.total)
-            } else {
-                None
-            },
-            awesome_ops_total: if awesome_service.unlocked {
-                Some(awesome_service.total)
-            } else {
-                None
-            },
+            ops_total: ServiceKind::ALL.map(|kind| {
+                let service = self.state.service_by_kind(kind);
+                Some(service.total).filter(|_| service.state.is_unlocked())
+            }),
         };
 
         // Projects panel: cards
-        let all_cards = ALL_CARDS;
-
-        let cards: Html = all_cards
-            .iter()
-            .filter(|card| {
-                // should not be a used card
-                !self.state.is_card_used(card.id)
-                // condition of appearance is fulfilled
-                    && card.condition.should_appear(&self.state)
-            })
+        let cards: Html = self
+            .engine
+            .visible_cards()
             .map(|card| {
-                let link = ctx.link().clone();
-                let cost = card.cost.clone();
+                let cost = self.state.card_cost(card);
                 let disabled = !self.state.can_afford(&cost);
+                let eta = self.engine.card_eta(&self.state, card);
                 let id = card.id;
+                let on_click = ctx
+                    .link()
+                    .callback(move |_| PlayerAction::UseCard { id: id.into() });
                 html! {
                     <Card
                         {id}
-                        title={card.title}
-                        description={card.description}
+                        title={card.title()}
+                        description={card.full_description()}
                         {cost}
                         {disabled}
-                        on_click={move |_| link.send_message(PlayerAction::UseCard { id: id.into() })}
+                        {on_click}
+                        {eta}
                         />
                 }
             })
             .collect();
 
         // Hardware panel: power stats and cloud nodes
-
         let (cpu_load, mem_load) = self.state.total_processing();
+        let (mem_reserved, _) = self.state.memory_breakdown();
         let mem_total: Memory = self.state.nodes.iter().map(|n| n.ram_capacity).sum();
-
-        let nodes: Html = self
+        let powersave = self.state.is_powersaving();
+        let config = self.engine.config();
+        let nodes: Vec<NodeProps> = self
             .state
             .nodes
             .iter()
-            .map(|node| {
-                let cpu_upgrade_cost = node.next_cpu_upgrade_cost();
-                let ram_upgrade_cost = node.next_ram_upgrade_cost();
-                let cpu_upgrade_disabled = cpu_upgrade_cost
-                    .map(|cost| self.state.funds < cost)
-                    .unwrap_or_default();
-                let ram_upgrade_disabled = ram_upgrade_cost
-                    .map(|cost| self.state.funds < cost)
-                    .unwrap_or_default();
-                let on_cpu_upgrade = {
-                    let link = ctx.link().clone();
-                    let node = node.id;
-                    move |_| link.send_message(PlayerAction::UpgradeCpu { node })
-                };
-                let on_ram_upgrade = {
-                    let link = ctx.link().clone();
-                    let node = node.id;
-                    move |_| link.send_message(PlayerAction::UpgradeRam { node })
-                };
+            .map(|node| NodeProps {
+                id: node.id,
+                num_cores: node.num_cores,
+                ram_capacity: node.ram_capacity,
+                cpu_upgrade_cost: node.next_cpu_upgrade_cost(config),
+                ram_upgrade_cost: node.next_ram_upgrade_cost(config),
+                powersave,
+                restart_cooldown: node.restarting_until.saturating_sub(self.state.time),
+                overclocked: !node.overclock.is_none(),
+                memory: node.memory_breakdown(),
+                lifetime: node.lifetime,
+                serving_for: self.state.time.saturating_sub(node.lifetime.created_at),
+            })
+            .collect();
+
+        // Playground panel: direct manipulation of the state
+        let control = |label: &'static str, control: Control| {
+            let onclick = ctx
+                .link()
+                .callback(move |_| PlaygroundMsg::Control(control.clone()));
+            html! {
+                <button {onclick}>{label}</button>
+            }
+        };
+        let ops_controls: Html = ServiceKind::ALL
+            .into_iter()
+            .filter(|&kind| self.state.service_by_kind(kind).state.is_unlocked())
+            .map(|kind| {
                 html! {
-                    <Node
-                        cpus={node.num_cores} ram={Memory::mb(256)}
-                        {cpu_upgrade_cost}
-                        {ram_upgrade_cost}
-                        {cpu_upgrade_disabled}
-                        {ram_upgrade_disabled}
-                        {on_cpu_upgrade}
-                        {on_ram_upgrade}
-                        />
+                    <>
+                        {control("+1000 ops", Control::AddOps(kind, Ops(1_000)))}
+                        {control("+customer", Control::SpawnUser(kind, UserKind::Customer))}
+                        {control("+researcher", Control::SpawnUser(kind, UserKind::Researcher))}
+                        {control("+attacker", Control::SpawnUser(kind, UserKind::Attacker))}
+                        {" "}{kind.to_string()}<br/>
+                    </>
                 }
             })
             .collect();
@@ -382,29 +364,116 @@ impl Component for Playground {
                 </header>
                 <main>
                     <div class="panel-container">
-                        <Panel title="Services">
+                        <Panel title="Playground">
+                            <div class="playground-controls">
+                                {control("+$1000", Control::AddFunds(Money::dollars(1_000)))}
+                                {control("-$1000", Control::AddFunds(Money::dollars(-1_000)))}
+                                {control("+100 demand", Control::AddDemand(100.))}
+                                {control("-100 demand", Control::AddDemand(-100.))}
+                                <br/>
+                                {ops_controls}
+                                <span>{format!("{} users", self.state.user_specs.len())}</span>
+                            </div>
+                        </Panel>
+                        <Panel title={t!("panel.services")}>
                             <div>
-                                {base_c}
-                                {super_c}
-                                {epic_c}
-                                {awesome_c}
+                                {services}
                             </div>
                         </Panel>
-                        <Panel title="Business">
+                        <Panel title={t!("panel.business")}>
                             <Business ..business_props />
+                            <Notifications entries={self.engine.notifications().recent(5).cloned().collect::<Vec<_>>()} />
                         </Panel>
-                        <Panel title="Hardware">
-                            <Power {cpu_load} {mem_load} {mem_total} />
-                            <Rack>
+                        <Panel title={t!("panel.hardware")}>
+                            <Power {cpu_load} {mem_load} {mem_reserved} {mem_total} />
+                            <Equipment
+                                can_buy_nodes={self.state.can_buy_nodes}
+                                can_buy_racks={self.state.can_buy_racks}
+                                can_buy_datacenters={self.state.can_buy_datacenters}
+                                funds={self.state.funds}
+                                bare_node_cost={config.bare_node_cost}
+                                upgraded_node_cost={config.upgraded_node_cost}
+                                upgraded_rack_cost={config.upgraded_rack_cost}
+                                panel_level={self.state.hardware_panel_level}
                                 {nodes}
-                            </Rack>
+                                {powersave}
+                                rack_utilizations={self.state.rack_utilizations()}
+                                expanded={self.expanded_equipment}
+                                on_expand={ctx.link().callback(GameMsg::ExpandEquipment)}
+                                on_player_action={ctx.link().callback(|action: PlayerAction| action)} />
+                            <ProcessingChart
+                                current={self.engine.processing_times().current_overall()}
+                                previous={self.engine.processing_times().previous_overall()} />
                         </Panel>
-                        <Panel title="Projects" classes={classes!["projects"]}>
+                        <Panel title={t!("panel.projects")} classes={classes!["projects"]}>
                             {cards}
                         </Panel>
+                        <Panel title={t!("panel.statistics")}>
+                            <Stats metrics={self.engine.metrics(&self.state)} summary={self.state.summary_text()} />
+                        </Panel>
                     </div>
                 </main>
+                if let (Some(message), serial) = self.toast.clone() {
+                    <Toast {message} {serial} />
+                }
             </>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_control, initial_state, Control};
+    use cloud_champion::{
+        central::engine::GameEngine, Money, Ops, ServiceKind, UserKind, TIME_UNITS_PER_CYCLE,
+    };
+
+    #[test]
+    fn test_initial_state_runs() {
+        let mut state = initial_state();
+        let mut engine = GameEngine::new();
+        engine.bootstrap_events(&state);
+        let total = state.base_service.total;
+        let mut time = state.time;
+        for _ in 0..200 {
+            time += TIME_UNITS_PER_CYCLE as u64;
+            engine.update(&mut state, time);
+        }
+        // the customers are making requests
+        assert!(state.base_service.total > total);
+    }
+
+    #[test]
+    fn test_controls() {
+        let mut state = initial_state();
+        let mut engine = GameEngine::new();
+        let funds = state.funds;
+
+        apply_control(
+            &mut state,
+            &mut engine,
+            Control::AddFunds(Money::dollars(1_000)),
+        );
+        assert_eq!(state.funds, funds + Money::dollars(1_000));
+
+        apply_control(
+            &mut state,
+            &mut engine,
+            Control::AddOps(ServiceKind::Super, Ops(1_000)),
+        );
+        assert_eq!(state.super_service.available, Ops(1_000));
+
+        apply_control(&mut state, &mut engine, Control::AddDemand(-1_000.));
+        assert_eq!(state.demand, 0.);
+
+        apply_control(
+            &mut state,
+            &mut engine,
+            Control::SpawnUser(ServiceKind::Epic, UserKind::Researcher),
+        );
+        let spec = state.user_specs.last().unwrap();
+        assert_eq!(spec.id, 3);
+        assert_eq!(spec.service, ServiceKind::Epic);
+        assert_eq!(spec.kind, UserKind::Researcher);
+    }
+}
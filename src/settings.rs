@@ -0,0 +1,217 @@
+//! Module for the preferences of the player,
+//! which apply to every game and are remembered in local storage.
+
+use js_sys::wasm_bindgen::JsValue;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    central::{cards::CardSpec, persistence::SaveStorage},
+    storage::try_local_storage,
+    t, Cost, WorldState,
+};
+
+/// the local storage key of the settings
+const SETTINGS_KEY: &str = "settings";
+
+/// the default share of the available ops of a service
+/// above which using a project card asks for confirmation
+pub const DEFAULT_CONFIRM_SPEND_FRACTION: f32 = 0.75;
+
+/// the widest viewport in CSS pixels
+/// for which the automatic layout shows the panels as tabs
+pub const COMPACT_LAYOUT_MAX_WIDTH: f64 = 720.;
+
+/// How the game panels are laid out.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelLayout {
+    /// side by side on wide screens, as tabs on narrow ones
+    #[default]
+    Auto,
+    /// always side by side
+    Grid,
+    /// always as tabs, one panel at a time
+    Tabs,
+}
+
+impl PanelLayout {
+    pub const ALL: [PanelLayout; 3] = [PanelLayout::Auto, PanelLayout::Grid, PanelLayout::Tabs];
+
+    /// Whether the panels are shown as tabs
+    /// on a viewport of the given width in CSS pixels.
+    pub fn is_compact(self, viewport_width: f64) -> bool {
+        match self {
+            PanelLayout::Auto => viewport_width <= COMPACT_LAYOUT_MAX_WIDTH,
+            PanelLayout::Grid => false,
+            PanelLayout::Tabs => true,
+        }
+    }
+
+    /// The name of the layout in the locale in use.
+    pub fn name(self) -> &'static str {
+        match self {
+            PanelLayout::Auto => t!("layout.auto"),
+            PanelLayout::Grid => t!("layout.grid"),
+            PanelLayout::Tabs => t!("layout.tabs"),
+        }
+    }
+}
+
+/// The preferences of the player.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// the share of the available ops of a service
+    /// above which using a project card asks for confirmation,
+    /// or `None` to never ask
+    pub confirm_spend_fraction: Option<f32>,
+    /// how the game panels are laid out
+    pub layout: PanelLayout,
+    /// whether the project cards are shown in two compact columns
+    pub compact_projects: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            confirm_spend_fraction: Some(DEFAULT_CONFIRM_SPEND_FRACTION),
+            layout: PanelLayout::Auto,
+            compact_projects: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Load the settings from local storage,
+    /// or the default settings if none were saved.
+    pub fn load() -> Self {
+        try_local_storage()
+            .and_then(|storage| storage.get(SETTINGS_KEY))
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the settings to local storage.
+    pub fn save(&self) -> Result<(), JsValue> {
+        let json = serde_json::to_string(self).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        try_local_storage()?.set(SETTINGS_KEY, &json)
+    }
+
+    /// Whether using a project card with the given cost
+    /// should be confirmed by the player first.
+    pub fn needs_confirmation(&self, cost: &Cost, state: &WorldState) -> bool {
+        self.confirm_spend_fraction
+            .is_some_and(|threshold| cost.fraction_of_available(state) > threshold)
+    }
+
+    /// Whether using the given project card
+    /// should be confirmed by the player first,
+    /// which is always the case for destructive cards.
+    pub fn needs_card_confirmation(&self, card: &CardSpec, state: &WorldState) -> bool {
+        card.is_destructive() || self.needs_confirmation(&state.card_cost(card), state)
+    }
+
+    /// Whether clicking on the given project card
+    /// may ask the player to confirm it.
+    pub fn may_confirm_card(&self, card: &CardSpec) -> bool {
+        card.is_destructive() || self.confirm_spend_fraction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PanelLayout, Settings, COMPACT_LAYOUT_MAX_WIDTH};
+    use crate::{
+        central::{cards::all::card_by_id, testing::WorldStateBuilder},
+        Cost, Ops, ServiceKind,
+    };
+
+    #[test]
+    fn test_spending_most_ops_needs_confirmation() {
+        let state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(1_000))
+            .available_ops(ServiceKind::Super, Ops(100))
+            .build();
+        let settings = Settings::default();
+
+        assert!(!settings.needs_confirmation(&Cost::base_ops(750), &state));
+        assert!(settings.needs_confirmation(&Cost::base_ops(751), &state));
+        // a cheap base cost does not hide an expensive super cost
+        let cost = Cost::base_ops(10).and(Cost::super_ops(90));
+        assert!(settings.needs_confirmation(&cost, &state));
+        assert!(!settings.needs_confirmation(&Cost::dollars(1_000), &state));
+
+        let settings = Settings {
+            confirm_spend_fraction: Some(0.5),
+            ..Default::default()
+        };
+        assert!(settings.needs_confirmation(&Cost::base_ops(600), &state));
+    }
+
+    #[test]
+    fn test_confirmation_disabled() {
+        let state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(1_000))
+            .build();
+        let settings = Settings {
+            confirm_spend_fraction: None,
+            ..Default::default()
+        };
+        assert!(!settings.needs_confirmation(&Cost::base_ops(1_000), &state));
+
+        // saved settings without the field keep the default
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+        let json = serde_json::to_string(&Settings {
+            confirm_spend_fraction: None,
+            ..Default::default()
+        })
+        .unwrap();
+        let settings: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(settings.confirm_spend_fraction, None);
+    }
+
+    #[test]
+    fn test_destructive_cards_always_confirmed() {
+        let state = WorldStateBuilder::new()
+            .available_ops(ServiceKind::Base, Ops(10_000))
+            .build();
+        let settings = Settings {
+            confirm_spend_fraction: None,
+            ..Default::default()
+        };
+        // turning nodes into racks is asked for even with confirmations off
+        let datacenters = card_by_id("n5").unwrap();
+        assert!(settings.may_confirm_card(datacenters));
+        assert!(settings.needs_card_confirmation(datacenters, &state));
+
+        // other cards skip the confirmation
+        let caching = card_by_id("c0").unwrap();
+        assert!(!caching.is_destructive());
+        assert!(!settings.may_confirm_card(caching));
+        assert!(!settings.needs_card_confirmation(caching, &state));
+        let settings = Settings::default();
+        assert!(settings.may_confirm_card(caching));
+        assert!(!settings.needs_card_confirmation(caching, &state));
+    }
+
+    #[test]
+    fn test_panel_layout() {
+        assert!(PanelLayout::Auto.is_compact(390.));
+        assert!(PanelLayout::Auto.is_compact(COMPACT_LAYOUT_MAX_WIDTH));
+        assert!(!PanelLayout::Auto.is_compact(1_280.));
+        // an explicit choice ignores the viewport
+        assert!(!PanelLayout::Grid.is_compact(390.));
+        assert!(PanelLayout::Tabs.is_compact(1_280.));
+
+        let json = serde_json::to_string(&Settings {
+            layout: PanelLayout::Tabs,
+            ..Default::default()
+        })
+        .unwrap();
+        let settings: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(settings.layout, PanelLayout::Tabs);
+    }
+}
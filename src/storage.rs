@@ -0,0 +1,119 @@
+//! Module for the browser's local storage,
+//! where the game and the player's preferences are saved
+//! (see [`Persistence`] for how games are saved).
+
+use js_sys::wasm_bindgen::JsValue;
+
+use crate::{
+    central::persistence::{back_up, LoadError, Persistence, SaveSlot, SaveStorage},
+    WorldState,
+};
+
+/// The Web local storage API.
+#[derive(Debug, Clone)]
+pub struct LocalStorage(web_sys::Storage);
+
+impl SaveStorage for LocalStorage {
+    type Error = JsValue;
+
+    fn get(&self, key: &str) -> Result<Option<String>, JsValue> {
+        self.0.get_item(key)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), JsValue> {
+        self.0.set_item(key, value)
+    }
+
+    fn remove(&self, key: &str) -> Result<(), JsValue> {
+        self.0.remove_item(key)
+    }
+}
+
+/// Gracefully try to obtain the Web local storage API.
+pub fn try_local_storage() -> Result<LocalStorage, JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from_str("Could not obtain window"))
+        .and_then(|window| {
+            window
+                .local_storage()
+                .and_then(|x| x.ok_or_else(|| JsValue::from_str("Could not obtain local storage")))
+        })
+        .map(LocalStorage)
+}
+
+/// Saving and loading the game in local storage.
+pub trait LocalSave: Sized {
+    /// Load the game from local storage.
+    ///
+    /// Returns `Ok(None)` if there is no game save.
+    /// A corrupted save is moved aside (see [`Persistence::load_game`]).
+    fn load_game() -> Result<Option<Self>, LoadError<JsValue>>;
+
+    /// Load the daily challenge from local storage.
+    ///
+    /// Returns `Ok(None)` if there is no daily challenge save.
+    fn load_daily_game() -> Result<Option<Self>, LoadError<JsValue>>;
+
+    /// Checks whether there is a saved game.
+    fn has_saved_game() -> Result<bool, JsValue>;
+
+    /// Returns `Ok(())` if the game environment can be saved.
+    fn can_save_game() -> Result<(), JsValue>;
+
+    /// save the world state to local storage,
+    /// recording the wall-clock time of the save
+    ///
+    /// A daily challenge goes to its own slot.
+    fn save_game(&mut self) -> Result<(), JsValue>;
+}
+
+impl LocalSave for WorldState {
+    fn load_game() -> Result<Option<Self>, LoadError<JsValue>> {
+        try_local_storage()
+            .map_err(LoadError::Storage)?
+            .load_game(SaveSlot::Main)
+    }
+
+    fn load_daily_game() -> Result<Option<Self>, LoadError<JsValue>> {
+        try_local_storage()
+            .map_err(LoadError::Storage)?
+            .load_game(SaveSlot::Daily)
+    }
+
+    fn has_saved_game() -> Result<bool, JsValue> {
+        try_local_storage()?.has_saved_game(SaveSlot::Main)
+    }
+
+    fn can_save_game() -> Result<(), JsValue> {
+        try_local_storage().map(|_| ())
+    }
+
+    fn save_game(&mut self) -> Result<(), JsValue> {
+        try_local_storage()?.save_game(self, js_sys::Date::now() as u64)
+    }
+}
+
+/// Write a serialized world state (see [`WorldState::to_save_json`])
+/// to local storage,
+/// in the slot of the daily challenge if `daily` is true.
+///
+/// This is for saves serialized away from the main thread,
+/// which is the only one with access to local storage.
+pub fn store_save_json(json: &str, daily: bool) -> Result<(), JsValue> {
+    let slot = if daily {
+        SaveSlot::Daily
+    } else {
+        SaveSlot::Main
+    };
+    try_local_storage()?.store_save_json(json, slot)
+}
+
+/// Back up the save in local storage
+/// before an operation which replaces or wipes it.
+pub fn back_up_local_save() {
+    let result =
+        try_local_storage().and_then(|storage| back_up(&storage, js_sys::Date::now() as u64));
+    if let Err(e) = result {
+        gloo_console::warn!("Could not back up the saved game:", e);
+    }
+}
@@ -0,0 +1,173 @@
+//! Web utility module,
+//! for browser APIs which may not be available or allowed.
+
+use gloo_events::EventListener;
+use js_sys::{
+    wasm_bindgen::{closure::Closure, JsCast as _, JsValue},
+    Function, Promise, Reflect,
+};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::central::daily::DailyDate;
+
+/// Copy the given text to the clipboard,
+/// calling `on_done` with whether it succeeded.
+///
+/// Fails gracefully if the clipboard API is missing
+/// or the permission to write to it is denied.
+pub fn copy_to_clipboard(text: &str, on_done: impl FnOnce(bool) + 'static) {
+    match write_clipboard_text(text) {
+        Ok(promise) => {
+            // only one of the two will ever be called
+            let on_done = std::rc::Rc::new(std::cell::Cell::new(Some(on_done)));
+            let on_ok = {
+                let on_done = on_done.clone();
+                Closure::once_into_js(move |_: JsValue| {
+                    if let Some(on_done) = on_done.take() {
+                        on_done(true);
+                    }
+                })
+            };
+            let on_err = Closure::once_into_js(move |e: JsValue| {
+                gloo_console::warn!("Could not copy to clipboard:", e);
+                if let Some(on_done) = on_done.take() {
+                    on_done(false);
+                }
+            });
+            // the closures are passed as plain JS functions,
+            // so that they are dropped once called
+            let then = Reflect::get(&promise, &JsValue::from_str("then"))
+                .ok()
+                .and_then(|f| f.dyn_into::<Function>().ok());
+            if let Some(then) = then {
+                let _ = then.call2(&promise, &on_ok, &on_err);
+            }
+        }
+        Err(e) => {
+            gloo_console::warn!("Clipboard is not available:", e);
+            on_done(false);
+        }
+    }
+}
+
+/// Call `navigator.clipboard.writeText(text)`.
+fn write_clipboard_text(text: &str) -> Result<Promise, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("Could not obtain window"))?;
+    let navigator = Reflect::get(&window, &JsValue::from_str("navigator"))?;
+    let clipboard = Reflect::get(&navigator, &JsValue::from_str("clipboard"))?;
+    if clipboard.is_undefined() {
+        return Err(JsValue::from_str("Clipboard API not supported"));
+    }
+    let write_text: Function = Reflect::get(&clipboard, &JsValue::from_str("writeText"))?
+        .dyn_into()
+        .map_err(|_| JsValue::from_str("Clipboard API not supported"))?;
+    write_text
+        .call1(&clipboard, &JsValue::from_str(text))?
+        .dyn_into()
+        .map_err(|_| JsValue::from_str("writeText did not return a promise"))
+}
+
+/// A listener of key presses anywhere on the page,
+/// which stops listening once dropped.
+pub struct KeyListener {
+    closure: Closure<dyn FnMut(web_sys::KeyboardEvent)>,
+}
+
+impl KeyListener {
+    /// Start listening to key presses,
+    /// calling `on_key` with the key of each one.
+    pub fn new(on_key: impl Fn(&str) + 'static) -> Result<Self, JsValue> {
+        let window =
+            web_sys::window().ok_or_else(|| JsValue::from_str("Could not obtain window"))?;
+        let closure = Closure::<dyn FnMut(web_sys::KeyboardEvent)>::new(
+            move |event: web_sys::KeyboardEvent| on_key(&event.key()),
+        );
+        window.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+        Ok(Self { closure })
+    }
+}
+
+impl std::fmt::Debug for KeyListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("KeyListener")
+    }
+}
+
+impl Drop for KeyListener {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            let _ = window.remove_event_listener_with_callback(
+                "keydown",
+                self.closure.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
+
+/// Getting the current date from the browser.
+pub trait Today {
+    /// The current UTC date, according to the browser.
+    fn today() -> Self;
+}
+
+impl Today for DailyDate {
+    fn today() -> Self {
+        Self::from_unix_millis(js_sys::Date::now() as u64)
+    }
+}
+
+/// Forwards the log messages of the game engine to the browser console.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let message = record.args().to_string();
+        match record.level() {
+            Level::Error => gloo_console::error!(message),
+            Level::Warn => gloo_console::warn!(message),
+            Level::Info => gloo_console::log!(message),
+            Level::Debug | Level::Trace => gloo_console::debug!(message),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CONSOLE_LOGGER: ConsoleLogger = ConsoleLogger;
+
+/// Send log messages to the browser console.
+///
+/// Does nothing if a logger was already installed.
+pub fn init_logging() {
+    if log::set_logger(&CONSOLE_LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// Whether the browser can run scripts in web workers.
+pub fn supports_workers() -> bool {
+    Reflect::has(&js_sys::global(), &JsValue::from_str("Worker")).unwrap_or(false)
+}
+
+/// The width of the browser viewport in CSS pixels,
+/// or `None` if it cannot be obtained.
+pub fn viewport_width() -> Option<f64> {
+    web_sys::window()?.inner_width().ok()?.as_f64()
+}
+
+/// Start listening to the browser window being resized,
+/// calling `on_resize` with the new viewport width.
+///
+/// The listener stops listening once dropped.
+pub fn listen_resize(on_resize: impl Fn(f64) + 'static) -> Result<EventListener, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("Could not obtain window"))?;
+    Ok(EventListener::new(&window, "resize", move |_| {
+        if let Some(width) = viewport_width() {
+            on_resize(width);
+        }
+    }))
+}
@@ -0,0 +1,82 @@
+//! Module for the web worker running the game simulation
+//! (see [`simulation`](crate::central::simulation)),
+//! so that event processing stays off the page's thread.
+
+use gloo_worker::{Codec, HandlerId, Registrable, Spawnable, Worker, WorkerBridge, WorkerScope};
+use js_sys::wasm_bindgen::{JsValue, UnwrapThrowExt};
+use serde::{Deserialize, Serialize};
+
+use crate::central::simulation::{Simulation, SimulationInput, SimulationOutput};
+
+/// the script of the simulation worker, as built by Trunk
+const WORKER_SCRIPT: &str = "./simulation_worker.js";
+
+/// Messages to and from the worker as JSON,
+/// since the world state skips default fields when serialized,
+/// which only self-describing formats can take.
+#[derive(Debug)]
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<I>(input: I) -> JsValue
+    where
+        I: Serialize,
+    {
+        let json = serde_json::to_string(&input).expect_throw("Failed to encode a worker message");
+        JsValue::from_str(&json)
+    }
+
+    fn decode<O>(input: JsValue) -> O
+    where
+        O: for<'de> Deserialize<'de>,
+    {
+        let json = input
+            .as_string()
+            .expect_throw("Worker message is not a string");
+        serde_json::from_str(&json).expect_throw("Failed to decode a worker message")
+    }
+}
+
+/// The view's connection to the simulation worker.
+pub type SimulationBridge = WorkerBridge<SimulationWorker>;
+
+/// The worker simulating the game.
+#[derive(Debug)]
+pub struct SimulationWorker {
+    simulation: Simulation,
+}
+
+impl Worker for SimulationWorker {
+    type Message = ();
+    type Input = SimulationInput;
+    type Output = SimulationOutput;
+
+    fn create(_scope: &WorkerScope<Self>) -> Self {
+        SimulationWorker {
+            simulation: Simulation::new(),
+        }
+    }
+
+    fn update(&mut self, _scope: &WorkerScope<Self>, _msg: Self::Message) {}
+
+    fn received(&mut self, scope: &WorkerScope<Self>, msg: Self::Input, id: HandlerId) {
+        let now = js_sys::Date::now() as u64;
+        for output in self.simulation.handle(msg, now) {
+            scope.respond(id, output);
+        }
+    }
+}
+
+/// Start the simulation worker,
+/// calling `on_output` with every message from it.
+pub fn spawn(on_output: impl Fn(SimulationOutput) + 'static) -> SimulationBridge {
+    SimulationWorker::spawner()
+        .callback(on_output)
+        .encoding::<Json>()
+        .spawn(WORKER_SCRIPT)
+}
+
+/// Run the simulation worker in the current web worker.
+pub fn register() {
+    SimulationWorker::registrar().encoding::<Json>().register();
+}